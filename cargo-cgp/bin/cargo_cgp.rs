@@ -1,25 +1,57 @@
 use std::env;
 
-use anyhow::{Result, bail};
-use cargo_cgp::run_check::run_check;
+use anyhow::Result;
+use cargo_cgp::check_options::CheckOptions;
+use cargo_cgp::cli::{CargoArgs, CgpCommand, Cli};
+use cargo_cgp::config::CgpConfig;
+use cargo_cgp::doctor::run_doctor;
+use cargo_cgp::document_wiring::run_document_wiring;
+use cargo_cgp::expand_check::run_expand_check;
+use cargo_cgp::explain::run_explain;
+use cargo_cgp::fields::run_fields;
+use cargo_cgp::fix::run_fix;
+use cargo_cgp::graph::run_graph;
+use cargo_cgp::lsp::run_lsp;
+use cargo_cgp::run_check::{run_analyze, run_build, run_cargo_command, run_check, run_test};
+use cargo_cgp::serve::run_serve;
+use cargo_cgp::verify::run_verify;
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
-
     // Cargo invokes us as: cargo-cgp cgp <subcommand> [args...]
-    // We want to support: cargo cgp check
-    if args.len() < 2 {
-        bail!("Usage: cargo cgp check");
+    // Running the binary directly (cargo-cgp <subcommand>) works too
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().is_some_and(|arg| arg == "cgp") {
+        args.next();
     }
 
-    // Skip program name and "cgp" argument
-    let subcommand = args.get(2);
-
-    match subcommand.map(|s| s.as_str()) {
-        Some("check") => run_check()?,
-        Some(other) => bail!("Unknown subcommand: {}", other),
-        None => bail!("Usage: cargo cgp check"),
+    match Cli::parse_args(args).command {
+        CgpCommand::Check(args) => run_check(&check_options(args)?)?,
+        CgpCommand::Build(args) => run_build(&check_options(args)?)?,
+        CgpCommand::Test(args) => run_test(&check_options(args)?)?,
+        // Documentation builds report the same trait errors as `cargo check`
+        CgpCommand::Doc(args) => run_cargo_command("doc", &check_options(args)?)?,
+        CgpCommand::Rustdoc(args) => run_cargo_command("rustdoc", &check_options(args)?)?,
+        // Reads cargo JSON messages captured elsewhere instead of running cargo
+        CgpCommand::Analyze(args) => run_analyze(&check_options(args)?)?,
+        // Reports the verified wiring when the check succeeds
+        CgpCommand::Verify(args) => run_verify(&check_options(args)?)?,
+        CgpCommand::Fix(options) => run_fix(&options)?,
+        CgpCommand::Graph(options) => run_graph(&options)?,
+        CgpCommand::Doctor(options) => run_doctor(&options)?,
+        CgpCommand::Explain { code } => run_explain(code.into_iter())?,
+        CgpCommand::DocumentWiring(options) => run_document_wiring(&options)?,
+        CgpCommand::Fields(options) => run_fields(&options)?,
+        CgpCommand::ExpandCheck(options) => run_expand_check(&options)?,
+        CgpCommand::Lsp => run_lsp()?,
+        CgpCommand::Serve => run_serve()?,
     }
 
     Ok(())
 }
+
+/// Builds the options of the cargo commands, with defaults from `cgp.toml`
+fn check_options(args: CargoArgs) -> Result<CheckOptions> {
+    Ok(args
+        .options
+        .into_options(args.cargo_args, CgpConfig::load()?.check_defaults()?))
+}
@@ -0,0 +1,267 @@
+/// Module for the error budget of a workspace (`--budget <file>`)
+/// Large codebases adopt cargo-cgp with existing wiring errors. The budget file records
+/// the fingerprints of the accepted errors and a limit on the errors of each kind, so that a
+/// CI run fails only when a new error appears or a kind goes over its limit. Writing the
+/// file again with `--update-budget` after fixing errors lowers the limits, which ratchets
+/// the budget down over time.
+///
+/// ```toml
+/// known = ["3b198120", "ee9a125a"]
+///
+/// [limits]
+/// missing-field = 2
+/// ```
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::run_log::RunLogEntry;
+
+/// The contents of a budget file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Budget {
+    /// Fingerprints of the CGP errors accepted when the budget was written
+    pub known: Vec<String>,
+    /// Maximum number of CGP errors of each kind (e.g., "missing-field"), unlimited if absent
+    pub limits: BTreeMap<String, usize>,
+}
+
+/// A CGP error that is not in the budget
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NewError {
+    pub fingerprint: String,
+    pub message: String,
+}
+
+/// A kind of CGP error with more errors than its limit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OverLimit {
+    pub kind: String,
+    pub count: usize,
+    pub limit: usize,
+}
+
+/// How the errors of a run compare with the budget
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BudgetReport {
+    /// The errors whose fingerprints are not in the budget
+    pub new_errors: Vec<NewError>,
+    /// The kinds of errors over their limit
+    pub over_limit: Vec<OverLimit>,
+    /// The number of known errors that no longer occur
+    pub fixed: usize,
+    /// Whether the budget file was rewritten from this run (`--update-budget`)
+    pub updated: bool,
+}
+
+impl Budget {
+    /// Reads a budget file
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read the budget at {} (create it with `--update-budget`)",
+                path.display()
+            )
+        })?;
+        toml::from_str(&content).with_context(|| format!("Invalid budget at {}", path.display()))
+    }
+
+    /// Writes a budget file
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string(self)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write the budget at {}", path.display()))
+    }
+
+    /// The budget that accepts exactly the errors of a run
+    /// Kinds that had a limit keep one, so that a kind without errors stays at zero
+    pub fn from_run(&self, errors: &[RunLogEntry], by_kind: &BTreeMap<String, usize>) -> Self {
+        let mut known: Vec<String> = errors
+            .iter()
+            .map(|error| error.fingerprint.clone())
+            .collect();
+        known.sort();
+        known.dedup();
+
+        let mut limits: BTreeMap<String, usize> =
+            self.limits.keys().map(|kind| (kind.clone(), 0)).collect();
+        limits.extend(by_kind.iter().map(|(kind, count)| (kind.clone(), *count)));
+
+        Budget { known, limits }
+    }
+
+    /// Compares the errors of a run with the budget
+    pub fn compare(
+        &self,
+        errors: &[RunLogEntry],
+        by_kind: &BTreeMap<String, usize>,
+    ) -> BudgetReport {
+        let new_errors = errors
+            .iter()
+            .filter(|error| !self.known.contains(&error.fingerprint))
+            .map(|error| NewError {
+                fingerprint: error.fingerprint.clone(),
+                message: error.message.clone(),
+            })
+            .collect();
+
+        let over_limit = self
+            .limits
+            .iter()
+            .filter_map(|(kind, limit)| {
+                let count = by_kind.get(kind).copied().unwrap_or(0);
+                (count > *limit).then(|| OverLimit {
+                    kind: kind.clone(),
+                    count,
+                    limit: *limit,
+                })
+            })
+            .collect();
+
+        let fixed = self
+            .known
+            .iter()
+            .filter(|fingerprint| {
+                !errors
+                    .iter()
+                    .any(|error| error.fingerprint == **fingerprint)
+            })
+            .count();
+
+        BudgetReport {
+            new_errors,
+            over_limit,
+            fixed,
+            updated: false,
+        }
+    }
+}
+
+impl BudgetReport {
+    /// Returns true if no new error appeared and no kind is over its limit
+    pub fn passed(&self) -> bool {
+        self.new_errors.is_empty() && self.over_limit.is_empty()
+    }
+
+    /// Renders the delta between the run and the budget, one line per finding
+    pub fn render(&self, path: &Path) -> String {
+        let mut lines = Vec::new();
+
+        if !self.new_errors.is_empty() {
+            lines.push(format!(
+                "budget: {} not in {}:",
+                match self.new_errors.len() {
+                    1 => "1 new CGP error".to_string(),
+                    n => format!("{} new CGP errors", n),
+                },
+                path.display()
+            ));
+            for error in &self.new_errors {
+                lines.push(format!("    {} {}", error.fingerprint, error.message));
+            }
+        }
+        for over in &self.over_limit {
+            lines.push(format!(
+                "budget: {} `{}` errors, over the limit of {}",
+                over.count, over.kind, over.limit
+            ));
+        }
+        if self.fixed > 0 && !self.updated {
+            lines.push(format!(
+                "budget: {} known {} fixed, run with `--update-budget` to lower the limits",
+                self.fixed,
+                if self.fixed == 1 { "error" } else { "errors" }
+            ));
+        }
+        if self.updated {
+            lines.push(format!("budget: updated {}", path.display()));
+        }
+        lines.push(
+            if self.passed() {
+                "budget: passed"
+            } else {
+                "budget: exceeded"
+            }
+            .to_string(),
+        );
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+}
+
+/// Compares the errors of a run with the budget file
+/// With `--update-budget`, the file is rewritten (or created) from the run first
+pub fn check_budget(
+    path: &Path,
+    update: bool,
+    errors: &[RunLogEntry],
+    by_kind: &BTreeMap<String, usize>,
+) -> Result<BudgetReport> {
+    let budget = match Budget::read(path) {
+        Ok(budget) => budget,
+        Err(_) if update && !path.exists() => Budget::default(),
+        Err(error) => return Err(error),
+    };
+
+    if !update {
+        return Ok(budget.compare(errors, by_kind));
+    }
+
+    // The rewritten budget accepts every error of the run
+    let updated = budget.from_run(errors, by_kind);
+    updated.write(path)?;
+    Ok(BudgetReport {
+        updated: true,
+        ..updated.compare(errors, by_kind)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_with_budget() {
+        let error = |fingerprint: &str| RunLogEntry {
+            fingerprint: fingerprint.to_string(),
+            message: format!("error {}", fingerprint),
+            raw_notes: Vec::new(),
+        };
+        let budget: Budget = toml::from_str(
+            "known = [\"aaaa\", \"bbbb\"]\n\n[limits]\nmissing-field = 2\nmissing-delegation = 1\n",
+        )
+        .unwrap();
+
+        // One known error is fixed, and no kind is over its limit
+        let by_kind = BTreeMap::from([("missing-field".to_string(), 1)]);
+        let report = budget.compare(&[error("aaaa")], &by_kind);
+        assert!(report.passed());
+        assert_eq!(report.fixed, 1);
+
+        // A new error fails the budget, even within the limits
+        let by_kind = BTreeMap::from([("missing-field".to_string(), 3)]);
+        let report = budget.compare(&[error("aaaa"), error("bbbb"), error("cccc")], &by_kind);
+        assert!(!report.passed());
+        assert_eq!(
+            report.render(Path::new("cgp-budget.toml")),
+            "budget: 1 new CGP error not in cgp-budget.toml:\n    \
+             cccc error cccc\n\
+             budget: 3 `missing-field` errors, over the limit of 2\n\
+             budget: exceeded\n"
+        );
+
+        // Updating the budget accepts the errors of the run, keeping the kinds with limits
+        let updated = budget.from_run(&[error("cccc"), error("aaaa")], &by_kind);
+        assert_eq!(updated.known, vec!["aaaa", "cccc"]);
+        assert_eq!(
+            updated.limits,
+            BTreeMap::from([
+                ("missing-delegation".to_string(), 0),
+                ("missing-field".to_string(), 3)
+            ])
+        );
+    }
+}
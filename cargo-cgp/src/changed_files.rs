@@ -0,0 +1,169 @@
+/// Module for `--since <rev>`, which focuses the output on the errors of the current change
+/// Diagnostics whose spans, or whose context struct, are in a file changed since the
+/// revision are rendered in full. All other diagnostics are summarized in one line each,
+/// so that a wave of errors during a refactoring does not bury the new ones.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use crate::cgp_diagnostic::CgpDiagnostic;
+use crate::workspace_scan::WorkspaceScan;
+
+/// The files changed since a git revision, as absolute paths
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangedFiles {
+    /// The root that diagnostics' file names are relative to
+    workspace_root: PathBuf,
+    files: Vec<PathBuf>,
+}
+
+impl ChangedFiles {
+    pub fn new(workspace_root: PathBuf, files: Vec<PathBuf>) -> Self {
+        ChangedFiles {
+            workspace_root,
+            files,
+        }
+    }
+
+    /// Asks git for the files changed since the revision, including uncommitted and
+    /// untracked files
+    pub fn since(rev: &str, workspace_root: &Path) -> Result<Self> {
+        let toplevel = run_git(workspace_root, &["rev-parse", "--show-toplevel"])
+            .context("`--since` requires the workspace to be in a git repository")?;
+        let toplevel = PathBuf::from(toplevel.trim());
+
+        let changed = run_git(workspace_root, &["diff", "--name-only", rev, "--"])
+            .with_context(|| format!("Failed to list the files changed since `{}`", rev))?;
+        let untracked = run_git(
+            workspace_root,
+            &["ls-files", "--others", "--exclude-standard", "--full-name"],
+        )?;
+
+        let files = changed
+            .lines()
+            .chain(untracked.lines())
+            .filter(|line| !line.is_empty())
+            .map(|line| toplevel.join(line))
+            .collect();
+
+        Ok(Self::new(workspace_root.to_path_buf(), files))
+    }
+
+    /// Returns true if a file, as named in a diagnostic, is one of the changed files
+    pub fn contains(&self, file: &str) -> bool {
+        let path = self.workspace_root.join(file);
+        self.files.contains(&path)
+    }
+
+    /// Returns true if a diagnostic touches a changed file: one of its spans is in a
+    /// changed file, or the struct of its context is defined in one
+    pub fn touches(&self, diagnostic: &CgpDiagnostic, scan: &WorkspaceScan) -> bool {
+        diagnostic
+            .spans
+            .iter()
+            .any(|span| self.contains(&span.file))
+            || diagnostic
+                .context_type
+                .as_deref()
+                .and_then(|context_type| scan.context_struct(context_type))
+                .is_some_and(|context| {
+                    self.contains(&context.location.display_path.to_string_lossy())
+                })
+    }
+}
+
+/// Runs a git command in a directory and returns its stdout
+fn run_git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Summarizes the diagnostics outside the changed files, one line each
+pub fn render_unchanged_summary(diagnostics: &[CgpDiagnostic], rev: &str) -> String {
+    let mut lines = vec![match diagnostics.len() {
+        1 => format!(
+            "note: 1 CGP error is outside the files changed since `{}`:",
+            rev
+        ),
+        n => format!(
+            "note: {} CGP errors are outside the files changed since `{}`:",
+            n, rev
+        ),
+    }];
+
+    for diagnostic in diagnostics {
+        match diagnostic.spans.first() {
+            Some(span) => lines.push(format!(
+                "  {}:{}:{}: {}",
+                span.file, span.line_start, span.column_start, diagnostic.message
+            )),
+            None => lines.push(format!("  {}", diagnostic.message)),
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cgp_diagnostic::SpanLocation;
+
+    fn diagnostic(file: &str, context_type: &str) -> CgpDiagnostic {
+        let span = SpanLocation {
+            file: file.to_string(),
+            line_start: 3,
+            column_start: 5,
+            line_end: 3,
+            column_end: 9,
+            label: String::new(),
+            text: Vec::new(),
+            source_path: None,
+        };
+        let mut diagnostic = CgpDiagnostic::new(format!("error in `{}`", context_type), vec![span]);
+        diagnostic.context_type = Some(context_type.to_string());
+        diagnostic
+    }
+
+    #[test]
+    fn test_touches() {
+        let mut scan = WorkspaceScan::new();
+        let path = Path::new("src/context.rs");
+        scan.scan_file(
+            path,
+            path,
+            "shapes",
+            "crate",
+            "#[derive(HasField)]\npub struct Rectangle {\n    pub width: f64,\n}\n",
+        );
+
+        let changed = ChangedFiles::new(
+            PathBuf::from("/repo"),
+            vec![PathBuf::from("/repo/src/context.rs")],
+        );
+
+        // The context struct was changed, although the error is reported elsewhere
+        assert!(changed.touches(&diagnostic("src/checks.rs", "Rectangle"), &scan));
+        assert!(changed.touches(&diagnostic("src/context.rs", "Circle"), &scan));
+        assert!(!changed.touches(&diagnostic("src/checks.rs", "Circle"), &scan));
+
+        assert_eq!(
+            render_unchanged_summary(&[diagnostic("src/checks.rs", "Circle")], "main"),
+            "note: 1 CGP error is outside the files changed since `main`:\n  src/checks.rs:3:5: error in `Circle`"
+        );
+    }
+}
@@ -0,0 +1,683 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
+use clap::{ArgAction, Args};
+
+use crate::cgp_patterns::PathStyle;
+use crate::cli::parse_with_passthrough;
+use crate::error_formatting::is_terminal;
+use crate::macro_warnings::MacroWarningPolicy;
+use crate::name_resolution::{NamingConventions, ResolutionMode};
+use crate::pager::PagerMode;
+use crate::raw_filter::RawFilter;
+use crate::source_snippet::PathRemap;
+use crate::workspace_scan::base_type_name;
+
+/// Output format for the diagnostics printed by `cargo cgp`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Human-readable diagnostics rendered with miette
+    #[default]
+    Human,
+    /// Newline-delimited JSON: cargo's own messages are forwarded unchanged,
+    /// and improved CGP errors are emitted as `cgp-diagnostic` messages
+    CgpJson,
+    /// Newline-delimited JSON in cargo's own format: CGP errors are forwarded as
+    /// `compiler-message`s whose `rendered` text is the improved error, so that tools
+    /// showing that text (e.g., rust-analyzer's flycheck) show the improved errors
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "human" => Ok(OutputFormat::Human),
+            "cgp-json" => Ok(OutputFormat::CgpJson),
+            "json" => Ok(OutputFormat::Json),
+            other => bail!(
+                "Unknown output format `{}` (expected `human`, `json` or `cgp-json`)",
+                other
+            ),
+        }
+    }
+}
+
+/// How the dependency chain of each error is exported in the human-readable output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TreeFormat {
+    /// Only the tree drawn in the help text
+    #[default]
+    Text,
+    /// A Graphviz DOT graph after each error
+    Dot,
+    /// A Mermaid flowchart after each error
+    Mermaid,
+}
+
+impl FromStr for TreeFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(TreeFormat::Text),
+            "dot" => Ok(TreeFormat::Dot),
+            "mermaid" => Ok(TreeFormat::Mermaid),
+            other => bail!(
+                "Unknown tree format `{}` (expected `text`, `dot` or `mermaid`)",
+                other
+            ),
+        }
+    }
+}
+
+/// When human-readable diagnostics are rendered with colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// Use colors if stdout is a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Returns true if the output should be colored
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => is_terminal(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            other => bail!(
+                "Unknown color mode `{}` (expected `auto`, `always` or `never`)",
+                other
+            ),
+        }
+    }
+}
+
+/// Where to write the run statistics requested with `--stats-json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatsTarget {
+    /// After the diagnostics, as the last line of stdout
+    Stdout,
+    /// To a file, keeping stdout for the diagnostics only
+    File(PathBuf),
+}
+
+/// Options for `cargo cgp check`, `cargo cgp build`, `cargo cgp test`, `cargo cgp doc`
+/// and `cargo cgp rustdoc`
+/// Flags understood by cargo-cgp are extracted, everything else is passed through to cargo
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckOptions {
+    /// The output format
+    pub format: OutputFormat,
+    /// Path remapping rules applied before reading source files for snippets
+    pub path_remaps: Vec<PathRemap>,
+    /// When to show the human-readable output through a pager
+    pub pager: PagerMode,
+    /// Stop cargo as soon as one CGP root cause is found and show only that error
+    pub first_error: bool,
+    /// Whether names missing from the component index may be derived with naming heuristics
+    pub resolution: ResolutionMode,
+    /// Write a JSON summary of the run (`--stats-json` or `--stats-json=<path>`)
+    pub stats_json: Option<StatsTarget>,
+    /// Write Prometheus metrics of the run to a file (`--metrics-file <path>`)
+    pub metrics_file: Option<PathBuf>,
+    /// Compare the CGP errors with a budget file, and fail only if a new error appears or
+    /// a kind of error is over its limit (`--budget <file>`)
+    pub budget: Option<PathBuf>,
+    /// Rewrite the budget file from the errors of the run (`--update-budget`)
+    pub update_budget: bool,
+    /// CGP diagnostics to show as reported by rustc (`--raw-component` and `--raw-code`)
+    pub raw_filters: Vec<RawFilter>,
+    /// Rerun the command whenever a source file changes
+    pub watch: bool,
+    /// Replay saved cargo JSON messages instead of running cargo (`--from-json <file>`)
+    pub from_json: Option<PathBuf>,
+    /// Write a trace of how diagnostics were grouped (`--dump-grouping <file>`)
+    pub dump_grouping: Option<PathBuf>,
+    /// When to color the human-readable output (cargo's `--color`, which is also passed through)
+    pub color: ColorMode,
+    /// How many levels of the dependency chain to show (`--tree-depth <n>`), all if unset
+    pub tree_depth: Option<usize>,
+    /// How the dependency chain of each error is exported (`--tree-format <format>`)
+    pub tree_format: TreeFormat,
+    /// Keep the hops of the dependency chains that are removed as redundant (`--no-dedup`)
+    pub no_dedup: bool,
+    /// Extra strings that mark a diagnostic as CGP-related (from `cgp.toml` only)
+    pub cgp_patterns: Vec<String>,
+    /// Naming conventions used to derive names in heuristic mode (from `cgp.toml` only)
+    pub naming: NamingConventions,
+    /// Only render the errors touching files changed since this git revision in full
+    /// (`--since <rev>`), the others are summarized in one line each
+    pub since: Option<String>,
+    /// Only render the errors about these contexts (`--context <Type>`), all if empty
+    pub contexts: Vec<String>,
+    /// Only render the errors about components matching these globs (`--component <glob>`)
+    pub components: Vec<String>,
+    /// How warnings in code generated by the cgp macros are shown (`--macro-warnings <policy>`)
+    pub macro_warnings: MacroWarningPolicy,
+    /// Do not explain above the improved errors how they relate to the compiler errors
+    /// (`--no-banner`, or `banner = false` in `cgp.toml`)
+    pub no_banner: bool,
+    /// List the compiler diagnostics merged into each error and the suppressed errors
+    /// (`-v` / `--verbose`, which is also passed through)
+    pub verbose: bool,
+    /// Print rustc's notes for one error of the last run instead of running cargo
+    /// (`--raw-notes <fingerprint>`)
+    pub raw_notes: Option<String>,
+    /// Describe the errors in sentences for screen readers, without drawings
+    /// (`--narratable`, or the `CARGO_CGP_NARRATABLE` environment variable)
+    pub narratable: bool,
+    /// Make the output stable for snapshot tests (`--normalize-output`): paths are
+    /// relative to the workspace root, and there are no colors, banner, pager or timings
+    pub normalize_output: bool,
+    /// How the paths of types are written in the errors
+    /// (`--qualified-paths`, or `qualified-paths = true` in `cgp.toml`)
+    pub path_style: PathStyle,
+    /// Remaining arguments passed through to the cargo command
+    pub cargo_args: Vec<String>,
+}
+
+/// Environment variable that enables `--narratable` when set to anything but `0`
+pub const NARRATABLE_ENV: &str = "CARGO_CGP_NARRATABLE";
+
+/// Flags of cargo that cargo-cgp reads too, so they are also passed through
+pub const SHARED_CARGO_FLAGS: [&str; 2] = ["color", "verbose"];
+
+/// The flags understood by cargo-cgp in `cargo cgp check` and the other cargo commands
+#[derive(Debug, Clone, Default, Args)]
+pub struct CheckArgs {
+    /// Output format: `human`, `json` or `cgp-json`
+    #[arg(long, value_name = "FORMAT")]
+    pub format: Option<OutputFormat>,
+    /// Read source snippets from another location (e.g., `/build=/home/me/project`)
+    #[arg(long = "remap-path-prefix", value_name = "FROM=TO")]
+    pub path_remaps: Vec<PathRemap>,
+    /// Show the output through a pager: `auto`, `always` (the default without a value) or `never`
+    #[arg(
+        long,
+        value_name = "WHEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "always"
+    )]
+    pub pager: Option<PagerMode>,
+    /// Never show the output through a pager
+    #[arg(long, conflicts_with = "pager")]
+    pub no_pager: bool,
+    /// Stop as soon as one CGP root cause is found and show only that error
+    #[arg(long)]
+    pub first_error: bool,
+    /// Only use names from the workspace component index, never naming heuristics
+    #[arg(long)]
+    pub strict_resolution: bool,
+    /// Write a JSON summary of the run to stdout, or to a file
+    #[arg(
+        long,
+        value_name = "PATH",
+        num_args = 0..=1,
+        require_equals = true
+    )]
+    pub stats_json: Option<Option<PathBuf>>,
+    /// Write Prometheus metrics of the run to a file, for node_exporter's textfile collector
+    #[arg(long, value_name = "PATH")]
+    pub metrics_file: Option<PathBuf>,
+    /// Fail only if a CGP error is not in the budget file or a kind of error is over its limit
+    #[arg(long, value_name = "FILE")]
+    pub budget: Option<PathBuf>,
+    /// Rewrite the budget file to accept the CGP errors of this run
+    #[arg(long, requires = "budget")]
+    pub update_budget: bool,
+    /// Show the CGP errors of matching components as reported by rustc (e.g., `Area*`)
+    #[arg(long, value_name = "GLOB")]
+    pub raw_component: Vec<String>,
+    /// Show the CGP errors with this error code as reported by rustc (e.g., `E0277`)
+    #[arg(long, value_name = "CODE")]
+    pub raw_code: Vec<String>,
+    /// Rerun the command whenever a source file changes
+    #[arg(long)]
+    pub watch: bool,
+    /// Replay saved cargo JSON messages instead of running cargo
+    #[arg(long, value_name = "FILE")]
+    pub from_json: Option<PathBuf>,
+    /// Print rustc's notes for an error of the last run, by the fingerprint shown in its header
+    #[arg(long, value_name = "FINGERPRINT")]
+    pub raw_notes: Option<String>,
+    /// Write a trace of how diagnostics were grouped, as JSON lines
+    #[arg(long, value_name = "FILE")]
+    pub dump_grouping: Option<PathBuf>,
+    /// Only render the errors touching files changed since a git revision in full
+    #[arg(long, value_name = "REV")]
+    pub since: Option<String>,
+    /// Only render the CGP errors about this context (can be repeated)
+    #[arg(long = "context", value_name = "TYPE")]
+    pub contexts: Vec<String>,
+    /// Only render the CGP errors about matching components (e.g., `*Calculator*`, can be repeated)
+    #[arg(long = "component", value_name = "GLOB")]
+    pub components: Vec<String>,
+    /// Warnings in code generated by cgp macros: `tag`, `group` or `hide`
+    #[arg(long, value_name = "POLICY")]
+    pub macro_warnings: Option<MacroWarningPolicy>,
+    /// Do not print the line explaining how the errors relate to the compiler errors
+    #[arg(long)]
+    pub no_banner: bool,
+    /// List the compiler diagnostics merged into each error (passed through to cargo)
+    #[arg(short, long, action = ArgAction::Count)]
+    pub verbose: u8,
+    /// How many levels of the dependency chain to show
+    #[arg(long, value_name = "N")]
+    pub tree_depth: Option<usize>,
+    /// Also print the dependency chain of each error as a graph: `text`, `dot` or `mermaid`
+    #[arg(long, value_name = "FORMAT")]
+    pub tree_format: Option<TreeFormat>,
+    /// Show the full dependency chains, without removing redundant hops
+    #[arg(long)]
+    pub no_dedup: bool,
+    /// Color the output: `auto`, `always` or `never` (passed through to cargo)
+    #[arg(long, value_name = "WHEN")]
+    pub color: Option<ColorMode>,
+    /// Make the output stable for snapshot tests: relative paths, no colors, banner or timings
+    #[arg(long)]
+    pub normalize_output: bool,
+    /// Describe the errors and their dependency chains in sentences, for screen readers
+    #[arg(long)]
+    pub narratable: bool,
+    /// Show the module paths of types, to tell apart identically-named types
+    #[arg(long)]
+    pub qualified_paths: bool,
+}
+
+impl CheckArgs {
+    /// Applies the flags to the defaults
+    pub fn into_options(self, cargo_args: Vec<String>, defaults: CheckOptions) -> CheckOptions {
+        let mut options = defaults;
+
+        if let Some(format) = self.format {
+            options.format = format;
+        }
+        options.path_remaps.extend(self.path_remaps);
+        if let Some(pager) = self.pager {
+            options.pager = pager;
+        }
+        if self.no_pager {
+            options.pager = PagerMode::Never;
+        }
+        options.first_error |= self.first_error;
+        if self.strict_resolution {
+            options.resolution = ResolutionMode::Strict;
+        }
+        if let Some(target) = self.stats_json {
+            options.stats_json = Some(target.map_or(StatsTarget::Stdout, StatsTarget::File));
+        }
+        options.metrics_file = self.metrics_file.or(options.metrics_file);
+        options.budget = self.budget.or(options.budget);
+        options.update_budget |= self.update_budget;
+        options.raw_filters.extend(
+            self.raw_component
+                .into_iter()
+                .map(RawFilter::Component)
+                .chain(self.raw_code.into_iter().map(RawFilter::ErrorCode)),
+        );
+        options.watch |= self.watch;
+        options.from_json = self.from_json.or(options.from_json);
+        options.raw_notes = self.raw_notes.or(options.raw_notes);
+        options.dump_grouping = self.dump_grouping.or(options.dump_grouping);
+        options.since = self.since.or(options.since);
+        options.contexts.extend(self.contexts);
+        options.components.extend(self.components);
+        if let Some(policy) = self.macro_warnings {
+            options.macro_warnings = policy;
+        }
+        options.no_banner |= self.no_banner;
+        options.verbose |= self.verbose > 0;
+        options.tree_depth = self.tree_depth.or(options.tree_depth);
+        if let Some(tree_format) = self.tree_format {
+            options.tree_format = tree_format;
+        }
+        options.no_dedup |= self.no_dedup;
+        if let Some(color) = self.color {
+            options.color = color;
+        }
+        options.narratable |= self.narratable;
+        if self.qualified_paths {
+            options.path_style = PathStyle::Qualified;
+        }
+        if self.normalize_output {
+            options.normalize_output = true;
+            options.no_banner = true;
+            options.color = ColorMode::Never;
+            options.pager = PagerMode::Never;
+        }
+        options.cargo_args = cargo_args;
+
+        options
+    }
+}
+
+impl CheckOptions {
+    /// Parses the arguments following `cargo cgp check`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        Self::parse_with_defaults(args, CheckOptions::default())
+    }
+
+    /// Parses the arguments following `cargo cgp check`, starting from defaults
+    /// (e.g., those of `cgp.toml`) that the flags override
+    pub fn parse_with_defaults(
+        args: impl IntoIterator<Item = String>,
+        defaults: CheckOptions,
+    ) -> Result<Self> {
+        let (check_args, cargo_args) = parse_with_passthrough::<CheckArgs>("check", args)?;
+        Ok(check_args.into_options(cargo_args, defaults))
+    }
+
+    /// Returns true if the errors are narrated for screen readers, with `--narratable` or
+    /// the `CARGO_CGP_NARRATABLE` environment variable
+    pub fn narrates(&self) -> bool {
+        self.narratable || std::env::var_os(NARRATABLE_ENV).is_some_and(|value| value != "0")
+    }
+
+    /// Returns true if the errors about a context are rendered with the `--context` filter
+    /// Contexts are compared by name, without their crate path or generic arguments
+    pub fn shows_context(&self, context_type: Option<&str>) -> bool {
+        self.contexts.is_empty()
+            || context_type.is_some_and(|context_type| {
+                self.contexts
+                    .iter()
+                    .any(|context| base_type_name(context) == base_type_name(context_type))
+            })
+    }
+
+    /// Returns the cargo profile selected by the arguments passed through to cargo
+    /// (`--release`, `-r` or `--profile NAME`), or the default profile of the command
+    /// Per-run outputs such as the run statistics are labelled with it, since cfgs
+    /// that depend on the profile can change the wiring
+    pub fn profile(&self, command: &str) -> String {
+        let mut profile = None;
+        let mut args = self.cargo_args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--" => break,
+                "--release" | "-r" => profile = Some("release".to_string()),
+                "--profile" => profile = args.next().cloned(),
+                _ => {
+                    if let Some(name) = arg.strip_prefix("--profile=") {
+                        profile = Some(name.to_string());
+                    }
+                }
+            }
+        }
+
+        profile.unwrap_or_else(|| match command {
+            "test" => "test".to_string(),
+            _ => "dev".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CheckOptions> {
+        CheckOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_format() {
+        let options = parse(&["--format", "cgp-json", "--workspace"]).unwrap();
+        assert_eq!(options.format, OutputFormat::CgpJson);
+        assert_eq!(options.cargo_args, vec!["--workspace".to_string()]);
+
+        let options = parse(&["--format=human", "--", "--format=cgp-json"]).unwrap();
+        assert_eq!(options.format, OutputFormat::Human);
+        assert_eq!(options.cargo_args, vec!["--", "--format=cgp-json"]);
+
+        assert_eq!(
+            parse(&["--format=json"]).unwrap().format,
+            OutputFormat::Json
+        );
+
+        assert!(parse(&["--format=xml"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_remap_path_prefix() {
+        let options = parse(&[
+            "--remap-path-prefix",
+            "/build=/src",
+            "--remap-path-prefix=/a=/b",
+        ])
+        .unwrap();
+        assert_eq!(options.path_remaps.len(), 2);
+        assert_eq!(options.path_remaps[1].from, "/a");
+        assert!(options.cargo_args.is_empty());
+    }
+
+    #[test]
+    fn test_parse_pager() {
+        assert_eq!(parse(&[]).unwrap().pager, PagerMode::Auto);
+        assert_eq!(parse(&["--pager"]).unwrap().pager, PagerMode::Always);
+        assert_eq!(parse(&["--no-pager"]).unwrap().pager, PagerMode::Never);
+        assert_eq!(parse(&["--pager=never"]).unwrap().pager, PagerMode::Never);
+        assert!(parse(&["--pager=sometimes"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_first_error() {
+        let options = parse(&["--first-error", "-p", "shapes"]).unwrap();
+        assert!(options.first_error);
+        assert_eq!(options.cargo_args, vec!["-p", "shapes"]);
+    }
+
+    #[test]
+    fn test_parse_macro_warnings() {
+        assert_eq!(parse(&[]).unwrap().macro_warnings, MacroWarningPolicy::Tag);
+        assert_eq!(
+            parse(&["--macro-warnings=group"]).unwrap().macro_warnings,
+            MacroWarningPolicy::Group
+        );
+        assert!(parse(&["--macro-warnings", "mute"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_no_banner() {
+        assert!(!parse(&[]).unwrap().no_banner);
+        assert!(parse(&["--no-banner"]).unwrap().no_banner);
+    }
+
+    #[test]
+    fn test_parse_normalize_output() {
+        assert!(!parse(&[]).unwrap().normalize_output);
+
+        let options = parse(&["--color=always", "--normalize-output"]).unwrap();
+        assert!(options.normalize_output);
+        assert!(options.no_banner);
+        assert_eq!(options.color, ColorMode::Never);
+        assert_eq!(options.pager, PagerMode::Never);
+        // cargo still receives its own `--color` flag
+        assert_eq!(options.cargo_args, vec!["--color=always"]);
+    }
+
+    #[test]
+    fn test_parse_narratable() {
+        assert!(!parse(&[]).unwrap().narratable);
+        assert!(parse(&["--narratable"]).unwrap().narrates());
+    }
+
+    #[test]
+    fn test_parse_qualified_paths() {
+        assert_eq!(parse(&[]).unwrap().path_style, PathStyle::Short);
+        let options = parse(&["--qualified-paths"]).unwrap();
+        assert_eq!(options.path_style, PathStyle::Qualified);
+    }
+
+    #[test]
+    fn test_parse_strict_resolution() {
+        assert_eq!(parse(&[]).unwrap().resolution, ResolutionMode::Heuristic);
+        let options = parse(&["--strict-resolution"]).unwrap();
+        assert_eq!(options.resolution, ResolutionMode::Strict);
+        assert!(options.cargo_args.is_empty());
+    }
+
+    #[test]
+    fn test_profile() {
+        assert_eq!(parse(&[]).unwrap().profile("check"), "dev");
+        assert_eq!(parse(&[]).unwrap().profile("test"), "test");
+        assert_eq!(parse(&["--release"]).unwrap().profile("check"), "release");
+        assert_eq!(
+            parse(&["--profile", "ci", "-p", "shapes"])
+                .unwrap()
+                .profile("build"),
+            "ci"
+        );
+        assert_eq!(parse(&["--profile=ci"]).unwrap().profile("check"), "ci");
+
+        // Arguments after `--` belong to the test harness
+        assert_eq!(parse(&["--", "--release"]).unwrap().profile("test"), "test");
+    }
+
+    #[test]
+    fn test_parse_stats_json() {
+        assert_eq!(parse(&[]).unwrap().stats_json, None);
+        assert_eq!(
+            parse(&["--stats-json"]).unwrap().stats_json,
+            Some(StatsTarget::Stdout)
+        );
+        assert_eq!(
+            parse(&["--stats-json=stats.json"]).unwrap().stats_json,
+            Some(StatsTarget::File(PathBuf::from("stats.json")))
+        );
+        assert_eq!(
+            parse(&["--metrics-file", "cgp.prom"]).unwrap().metrics_file,
+            Some(PathBuf::from("cgp.prom"))
+        );
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        assert!(!parse(&[]).unwrap().watch);
+        let options = parse(&["--watch", "--workspace"]).unwrap();
+        assert!(options.watch);
+        assert_eq!(options.cargo_args, vec!["--workspace"]);
+    }
+
+    #[test]
+    fn test_parse_raw_filters() {
+        let options = parse(&["--raw-component", "Area*", "--raw-code=E0277", "-p", "a"]).unwrap();
+        assert_eq!(
+            options.raw_filters,
+            vec![
+                RawFilter::Component("Area*".to_string()),
+                RawFilter::ErrorCode("E0277".to_string())
+            ]
+        );
+        assert_eq!(options.cargo_args, vec!["-p", "a"]);
+    }
+
+    #[test]
+    fn test_parse_from_json() {
+        assert_eq!(parse(&[]).unwrap().from_json, None);
+        assert_eq!(
+            parse(&["--from-json", "dump.json"]).unwrap().from_json,
+            Some(PathBuf::from("dump.json"))
+        );
+        assert_eq!(
+            parse(&["--from-json=dump.json"]).unwrap().from_json,
+            Some(PathBuf::from("dump.json"))
+        );
+        assert!(parse(&["--from-json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_dump_grouping() {
+        assert_eq!(parse(&[]).unwrap().dump_grouping, None);
+        let options = parse(&["--dump-grouping", "grouping.jsonl", "-p", "a"]).unwrap();
+        assert_eq!(options.dump_grouping, Some(PathBuf::from("grouping.jsonl")));
+        assert_eq!(options.cargo_args, vec!["-p", "a"]);
+    }
+
+    #[test]
+    fn test_parse_tree_depth_and_color() {
+        let options = parse(&["--tree-depth", "2", "--color=never"]).unwrap();
+        assert_eq!(options.tree_depth, Some(2));
+        assert_eq!(options.color, ColorMode::Never);
+        assert_eq!(options.cargo_args, vec!["--color=never"]);
+        assert!(parse(&["--tree-depth=deep"]).is_err());
+        assert!(parse(&["--color", "sometimes"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_tree_format() {
+        assert_eq!(parse(&[]).unwrap().tree_format, TreeFormat::Text);
+        assert_eq!(
+            parse(&["--tree-format", "mermaid"]).unwrap().tree_format,
+            TreeFormat::Mermaid
+        );
+        assert_eq!(
+            parse(&["--tree-format=dot"]).unwrap().tree_format,
+            TreeFormat::Dot
+        );
+        assert!(parse(&["--tree-format=svg"]).is_err());
+        assert!(parse(&["--no-dedup"]).unwrap().no_dedup);
+    }
+
+    #[test]
+    fn test_parse_with_defaults() {
+        let defaults = CheckOptions {
+            format: OutputFormat::CgpJson,
+            tree_depth: Some(3),
+            ..CheckOptions::default()
+        };
+        let options =
+            CheckOptions::parse_with_defaults(["--tree-depth=5".to_string()], defaults).unwrap();
+        assert_eq!(options.format, OutputFormat::CgpJson);
+        assert_eq!(options.tree_depth, Some(5));
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse(&[]).unwrap().since, None);
+        let options = parse(&["--since", "main", "-p", "a"]).unwrap();
+        assert_eq!(options.since.as_deref(), Some("main"));
+        assert_eq!(options.cargo_args, vec!["-p", "a"]);
+        assert_eq!(
+            parse(&["--since=HEAD~2"]).unwrap().since.as_deref(),
+            Some("HEAD~2")
+        );
+    }
+
+    #[test]
+    fn test_parse_context() {
+        let options = parse(&["--context", "Rectangle", "--context=shapes::Circle"]).unwrap();
+        assert_eq!(options.contexts, vec!["Rectangle", "shapes::Circle"]);
+        assert!(options.shows_context(Some("Rectangle<f64>")));
+        assert!(options.shows_context(Some("Circle")));
+        assert!(!options.shows_context(Some("Square")));
+        assert!(!options.shows_context(None));
+        assert!(parse(&[]).unwrap().shows_context(None));
+    }
+
+    #[test]
+    fn test_parse_component() {
+        let options = parse(&["--component", "*Calculator*", "-p", "shapes"]).unwrap();
+        assert_eq!(options.components, vec!["*Calculator*"]);
+        assert_eq!(options.cargo_args, vec!["-p", "shapes"]);
+    }
+}
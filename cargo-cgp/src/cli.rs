@@ -0,0 +1,369 @@
+/// Module for the command line interface of `cargo cgp`, built on clap
+/// The cargo commands (`check`, `build`, ...) accept every flag of the underlying cargo
+/// command, so their arguments are split first: the flags that clap knows for the
+/// subcommand are parsed and validated, and everything else is passed through to cargo
+/// in its original order.
+use anyhow::Result;
+use clap::{Args, Command, CommandFactory, FromArgMatches, Parser, Subcommand};
+
+use crate::check_options::{CheckArgs, SHARED_CARGO_FLAGS};
+use crate::doctor::DoctorOptions;
+use crate::document_wiring::DocumentWiringOptions;
+use crate::expand_check::ExpandCheckOptions;
+use crate::fields::FieldsOptions;
+use crate::fix::FixOptions;
+use crate::graph::GraphOptions;
+
+/// Improves the errors that the compiler reports for CGP code
+#[derive(Debug, Parser)]
+#[command(name = "cargo cgp", bin_name = "cargo cgp", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: CgpCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CgpCommand {
+    /// Run `cargo check` and improve the CGP errors in its output
+    Check(CargoArgs),
+    /// Run `cargo build` and improve the CGP errors in its output
+    Build(CargoArgs),
+    /// Run `cargo test` and improve the CGP errors in its output
+    Test(CargoArgs),
+    /// Run `cargo doc` and improve the CGP errors in its output
+    Doc(CargoArgs),
+    /// Run `cargo rustdoc` and improve the CGP errors in its output
+    Rustdoc(CargoArgs),
+    /// Improve the CGP errors in captured cargo JSON messages (`-` reads from stdin)
+    Analyze(CargoArgs),
+    /// Run `cargo check` and report the wiring it verified
+    Verify(CargoArgs),
+    /// Apply the fixes suggested for CGP errors to the context structs
+    Fix(FixOptions),
+    /// Explain a CGP error code, or list all codes
+    Explain {
+        /// The error code (e.g., CGP0001)
+        code: Option<String>,
+    },
+    /// Show how the contexts of the workspace are wired
+    Graph(GraphOptions),
+    /// Check the workspace sources for CGP misconfigurations
+    Doctor(DoctorOptions),
+    /// Generate a markdown document describing how each context is wired
+    DocumentWiring(DocumentWiringOptions),
+    /// List the fields a context exposes and the fields its wired providers read
+    Fields(FieldsOptions),
+    /// Show the macro expansion of a `check_components!` or `delegate_components!` block
+    ExpandCheck(ExpandCheckOptions),
+    /// Run a language server that publishes the improved CGP errors on save
+    Lsp,
+    /// Serve analysis requests as JSON-RPC over stdin and stdout, keeping the index warm
+    Serve,
+}
+
+/// Arguments of the subcommands that run cargo
+#[derive(Debug, Clone, Args)]
+pub struct CargoArgs {
+    #[command(flatten)]
+    pub options: CheckArgs,
+    /// Arguments passed through to cargo
+    #[arg(value_name = "CARGO_ARGS", allow_hyphen_values = true)]
+    pub cargo_args: Vec<String>,
+}
+
+impl Cli {
+    /// Parses the arguments following `cargo cgp`, exiting with clap's message on errors
+    /// and for `--help` and `--version`
+    /// Arguments of the cargo commands that clap does not know are passed through to cargo
+    pub fn parse_args(args: impl IntoIterator<Item = String>) -> Self {
+        let args: Vec<String> = args.into_iter().collect();
+        let (known, passthrough) = match args.first() {
+            Some(subcommand) if is_cargo_command(subcommand) => {
+                let command = Self::command();
+                let subcommand_args = command
+                    .find_subcommand(subcommand)
+                    .expect("cargo commands are subcommands");
+                // `analyze` reads captured messages without starting cargo, so the flags
+                // shared with cargo would be taken for extra inputs
+                let shared_flags: &[&str] = match subcommand.as_str() {
+                    "analyze" => &[],
+                    _ => &SHARED_CARGO_FLAGS,
+                };
+                let (known, passthrough) = split_passthrough_args(
+                    subcommand_args,
+                    shared_flags,
+                    args[1..].iter().cloned(),
+                );
+                (
+                    std::iter::once(args[0].clone()).chain(known).collect(),
+                    passthrough,
+                )
+            }
+            _ => (args, Vec::new()),
+        };
+
+        let mut cli = Self::parse_from(std::iter::once("cargo cgp".to_string()).chain(known));
+        match &mut cli.command {
+            CgpCommand::Check(args)
+            | CgpCommand::Build(args)
+            | CgpCommand::Test(args)
+            | CgpCommand::Doc(args)
+            | CgpCommand::Rustdoc(args)
+            | CgpCommand::Analyze(args)
+            | CgpCommand::Verify(args) => args.cargo_args = passthrough,
+            CgpCommand::Fix(args) => args.cargo_args = passthrough,
+            _ => {}
+        }
+        cli
+    }
+}
+
+/// Returns true for the subcommands that pass unknown arguments through to cargo
+fn is_cargo_command(subcommand: &str) -> bool {
+    matches!(
+        subcommand,
+        "check" | "build" | "test" | "doc" | "rustdoc" | "analyze" | "verify" | "fix"
+    )
+}
+
+/// Parses the arguments of a subcommand that passes unknown arguments through to cargo
+/// Returns the parsed flags and the arguments for cargo
+pub fn parse_with_passthrough<T: Args + FromArgMatches>(
+    name: &'static str,
+    args: impl IntoIterator<Item = String>,
+) -> Result<(T, Vec<String>)> {
+    let command = T::augment_args(Command::new(name).no_binary_name(true));
+    let (known, passthrough) = split_passthrough_args(&command, &SHARED_CARGO_FLAGS, args);
+    let matches = command.try_get_matches_from(known)?;
+    Ok((T::from_arg_matches(&matches)?, passthrough))
+}
+
+/// Parses the arguments of a subcommand that only accepts its own flags
+pub fn parse_args<T: Args + FromArgMatches>(
+    name: &'static str,
+    args: impl IntoIterator<Item = String>,
+) -> Result<T> {
+    let command = T::augment_args(Command::new(name).no_binary_name(true));
+    let matches = command.try_get_matches_from(args)?;
+    Ok(T::from_arg_matches(&matches)?)
+}
+
+/// Splits arguments into the flags that the command knows (with their values) and the
+/// arguments for cargo, keeping the order of both
+/// Positional arguments and everything after `--` belong to cargo, and the `shared_flags`
+/// that cargo understands too (e.g., `--color`) are kept in both
+pub fn split_passthrough_args(
+    command: &Command,
+    shared_flags: &[&str],
+    args: impl IntoIterator<Item = String>,
+) -> (Vec<String>, Vec<String>) {
+    let mut command = command.clone();
+    command.build();
+
+    let mut known = Vec::new();
+    let mut passthrough = Vec::new();
+    let mut args = expand_short_flag_clusters(&command, args).into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            passthrough.push(arg);
+            passthrough.extend(args.by_ref());
+            break;
+        }
+
+        let (name, has_value) = match arg.split_once('=') {
+            Some((name, _)) => (name, true),
+            None => (arg.as_str(), false),
+        };
+        let flag = command.get_arguments().find(|flag| {
+            if let Some(long) = name.strip_prefix("--") {
+                flag.get_long() == Some(long)
+            } else if let Some(short) = name.strip_prefix('-') {
+                let mut chars = short.chars();
+                chars.next().is_some_and(|c| flag.get_short() == Some(c)) && chars.next().is_none()
+            } else {
+                false
+            }
+        });
+
+        let Some(flag) = flag.filter(|flag| !flag.is_positional()) else {
+            passthrough.push(arg);
+            continue;
+        };
+
+        // Flags with an optional value only take it after `=`
+        let takes_next =
+            !has_value && flag.get_action().takes_values() && !flag.is_require_equals_set();
+        let value = if takes_next { args.next() } else { None };
+
+        let shared = flag
+            .get_long()
+            .is_some_and(|long| shared_flags.contains(&long));
+        if shared {
+            passthrough.push(arg.clone());
+            passthrough.extend(value.clone());
+        }
+
+        known.push(arg);
+        known.extend(value);
+    }
+
+    (known, passthrough)
+}
+
+/// Short flags of cargo that take a value, which is the rest of a cluster (e.g., `-pshapes`)
+const CARGO_SHORT_VALUE_FLAGS: [char; 4] = ['p', 'j', 'F', 'Z'];
+
+/// Splits clusters of short flags (e.g., `-rv` or `-vv`) into separate flags, so that the
+/// flags known to the command are found in them
+/// A cargo flag that takes a value keeps the rest of the cluster as its value
+fn expand_short_flag_clusters(
+    command: &Command,
+    args: impl IntoIterator<Item = String>,
+) -> Vec<String> {
+    let is_known = |c: char| {
+        command
+            .get_arguments()
+            .any(|flag| !flag.is_positional() && flag.get_short() == Some(c))
+    };
+
+    let mut expanded = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            expanded.push(arg);
+            expanded.extend(args.by_ref());
+            break;
+        }
+
+        let cluster = arg
+            .strip_prefix('-')
+            .filter(|flags| flags.len() > 1 && flags.chars().all(|c| c.is_ascii_alphanumeric()))
+            .filter(|flags| flags.chars().any(is_known));
+        let Some(cluster) = cluster else {
+            expanded.push(arg);
+            continue;
+        };
+
+        for (i, c) in cluster.char_indices() {
+            if !is_known(c) && CARGO_SHORT_VALUE_FLAGS.contains(&c) {
+                expanded.push(format!("-{}", &cluster[i..]));
+                break;
+            }
+            expanded.push(format!("-{}", c));
+        }
+    }
+
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_split_passthrough_args() {
+        let command = CheckArgs::augment_args(Command::new("check").no_binary_name(true));
+        let (known, passthrough) = split_passthrough_args(
+            &command,
+            &SHARED_CARGO_FLAGS,
+            args(&[
+                "-p",
+                "shapes",
+                "--format",
+                "cgp-json",
+                "--features=serde",
+                "--pager",
+                "-v",
+                "--color",
+                "never",
+                "--",
+                "--format",
+            ]),
+        );
+
+        assert_eq!(
+            known,
+            args(&["--format", "cgp-json", "--pager", "-v", "--color", "never"])
+        );
+        assert_eq!(
+            passthrough,
+            args(&[
+                "-p",
+                "shapes",
+                "--features=serde",
+                "-v",
+                "--color",
+                "never",
+                "--",
+                "--format"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_cli() {
+        use crate::check_options::{CheckOptions, ColorMode};
+
+        Cli::command().debug_assert();
+
+        let cli = Cli::parse_args(args(&["check", "--workspace", "--first-error"]));
+        match cli.command {
+            CgpCommand::Check(args) => {
+                assert!(args.options.first_error);
+                assert_eq!(args.cargo_args, vec!["--workspace".to_string()]);
+            }
+            command => panic!("unexpected command {:?}", command),
+        }
+
+        let cli = Cli::parse_args(args(&[
+            "expand-check",
+            "src/lib.rs:42",
+            "--",
+            "--all-features",
+        ]));
+        match cli.command {
+            CgpCommand::ExpandCheck(options) => {
+                assert_eq!(options.location, "src/lib.rs:42");
+                assert_eq!(options.cargo_args, vec!["--all-features".to_string()]);
+            }
+            command => panic!("unexpected command {:?}", command),
+        }
+
+        // `analyze` does not start cargo, so the shared flags are not passed through
+        let analyze = |flags: &[&str]| match Cli::parse_args(args(flags)).command {
+            CgpCommand::Analyze(args) => {
+                assert_eq!(args.cargo_args, vec!["-".to_string()]);
+                args.options
+                    .into_options(args.cargo_args, CheckOptions::default())
+            }
+            command => panic!("unexpected command {:?}", command),
+        };
+        assert_eq!(
+            analyze(&["analyze", "-", "--color=never"]).color,
+            ColorMode::Never
+        );
+        assert!(analyze(&["analyze", "-v", "-"]).verbose);
+
+        // Clustered short flags are split, so `-v` is found next to cargo's `-r`
+        match Cli::parse_args(args(&["check", "-rv", "-vpshapes"])).command {
+            CgpCommand::Check(args) => {
+                assert_eq!(args.options.verbose, 2);
+                assert_eq!(args.cargo_args, vec!["-r", "-v", "-v", "-pshapes"]);
+                let options = args
+                    .options
+                    .into_options(args.cargo_args, CheckOptions::default());
+                assert!(options.verbose);
+                assert_eq!(options.profile("check"), "release");
+            }
+            command => panic!("unexpected command {:?}", command),
+        }
+
+        let cli = Cli::parse_args(args(&["explain", "CGP0001"]));
+        assert!(matches!(cli.command, CgpCommand::Explain { code: Some(_) }));
+    }
+}
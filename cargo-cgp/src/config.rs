@@ -0,0 +1,161 @@
+/// Module for the `cgp.toml` configuration file
+/// The file holds defaults for the options of `cargo cgp check` (and the other cargo
+/// commands), so that they do not have to be passed as flags on every run.
+/// It is looked up in the current directory and its ancestors, usually at the workspace root.
+/// Flags passed on the command line take precedence over the file.
+///
+/// ```toml
+/// format = "human"
+/// color = "auto"
+/// tree-depth = 4
+/// cgp-patterns = ["HasErrorType"]
+/// macro-warnings = "group"
+/// banner = false
+/// max-provider-depth = 4
+/// qualified-paths = false
+///
+/// [naming]
+/// consumer-prefix = "Can"
+/// component-suffix = "Component"
+/// ```
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::cgp_patterns::PathStyle;
+use crate::check_options::CheckOptions;
+use crate::name_resolution::NamingConventions;
+
+/// Name of the configuration file
+pub const CONFIG_FILE: &str = "cgp.toml";
+
+/// The contents of `cgp.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CgpConfig {
+    /// Default output format (`human`, `json` or `cgp-json`)
+    pub format: Option<String>,
+    /// Default color mode (`auto`, `always` or `never`)
+    pub color: Option<String>,
+    /// Default number of dependency chain levels to show
+    pub tree_depth: Option<usize>,
+    /// Extra strings that mark a diagnostic as CGP-related, e.g. the names of
+    /// marker traits from crates built on top of cgp
+    pub cgp_patterns: Vec<String>,
+    /// Default policy for warnings in code generated by the cgp macros
+    /// (`tag`, `group` or `hide`)
+    pub macro_warnings: Option<String>,
+    /// Whether to explain above the improved errors how they relate to the compiler errors
+    pub banner: Option<bool>,
+    /// Budget of nested providers in a wiring, above which `cargo cgp doctor` warns
+    pub max_provider_depth: Option<usize>,
+    /// Whether to show the module paths of types, for workspaces with identically-named types
+    pub qualified_paths: Option<bool>,
+    /// Naming conventions used to derive names in heuristic mode
+    pub naming: NamingConventions,
+}
+
+impl CgpConfig {
+    /// Loads `cgp.toml` from the current directory or its closest ancestor that has one
+    /// Returns the default configuration if there is no such file
+    pub fn load() -> Result<Self> {
+        let current_dir =
+            std::env::current_dir().context("Failed to read the current directory")?;
+
+        match find_config_file(&current_dir) {
+            Some(path) => Self::read(&path),
+            None => Ok(CgpConfig::default()),
+        }
+    }
+
+    /// Reads and parses a configuration file
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        content
+            .parse()
+            .with_context(|| format!("Invalid configuration in {}", path.display()))
+    }
+
+    /// Returns the options that flags start from
+    pub fn check_defaults(&self) -> Result<CheckOptions> {
+        let mut options = CheckOptions::default();
+
+        if let Some(format) = &self.format {
+            options.format = format.parse()?;
+        }
+        if let Some(color) = &self.color {
+            options.color = color.parse()?;
+        }
+        options.tree_depth = self.tree_depth;
+        if let Some(policy) = &self.macro_warnings {
+            options.macro_warnings = policy.parse()?;
+        }
+        options.no_banner = self.banner == Some(false);
+        if self.qualified_paths == Some(true) {
+            options.path_style = PathStyle::Qualified;
+        }
+        options.cgp_patterns = self.cgp_patterns.clone();
+        options.naming = self.naming.clone();
+
+        Ok(options)
+    }
+}
+
+impl std::str::FromStr for CgpConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(content: &str) -> Result<Self> {
+        let config: CgpConfig = toml::from_str(content)?;
+        // Report invalid values when the file is read, not when they are used
+        config.check_defaults()?;
+        Ok(config)
+    }
+}
+
+/// Finds `cgp.toml` in a directory or its closest ancestor that has one
+fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check_options::{ColorMode, OutputFormat};
+
+    #[test]
+    fn test_parse_config() {
+        let config: CgpConfig = r#"
+format = "cgp-json"
+color = "never"
+tree-depth = 3
+cgp-patterns = ["HasErrorType"]
+banner = false
+max-provider-depth = 4
+qualified-paths = true
+
+[naming]
+consumer-prefix = "Has"
+"#
+        .parse()
+        .unwrap();
+
+        let options = config.check_defaults().unwrap();
+        assert_eq!(options.format, OutputFormat::CgpJson);
+        assert_eq!(options.color, ColorMode::Never);
+        assert_eq!(options.tree_depth, Some(3));
+        assert_eq!(options.cgp_patterns, vec!["HasErrorType".to_string()]);
+        assert!(options.no_banner);
+        assert_eq!(config.max_provider_depth, Some(4));
+        assert_eq!(options.path_style, PathStyle::Qualified);
+        assert_eq!(options.naming.consumer_prefix, "Has");
+        assert_eq!(options.naming.component_suffix, "Component");
+
+        assert_eq!("".parse::<CgpConfig>().unwrap(), CgpConfig::default());
+        assert!("format = \"xml\"".parse::<CgpConfig>().is_err());
+        assert!("tree_depth = 3".parse::<CgpConfig>().is_err());
+    }
+}
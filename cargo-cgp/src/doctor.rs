@@ -0,0 +1,544 @@
+/// Module for `cargo cgp doctor`
+/// Statically analyzes the workspace sources for CGP misconfigurations that compile
+/// without errors, but hide wiring problems until a context is used elsewhere:
+/// components that are never checked, providers that are never wired, and contexts
+/// that cannot provide fields to getters because they do not derive `HasField`.
+/// Opt-in, it also warns about provider stacks nested deeper than a depth budget
+/// (`--max-provider-depth` or `max-provider-depth` in `cgp.toml`).
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::cgp_diagnostic::{CgpDiagnostic, Severity, SpanLocation};
+use crate::cli::parse_args;
+use crate::config::CgpConfig;
+use crate::error_formatting::{render_diagnostic_graphical, render_diagnostic_plain};
+use crate::explain::{CgpErrorCode, format_explain_trailer};
+use crate::graph::{Requirement, classify_requirement};
+use crate::workspace_scan::{
+    Delegation, SourceLocation, WorkspaceScan, base_type_name, generic_arguments, is_type_argument,
+    workspace_source_roots,
+};
+
+/// Options for `cargo cgp doctor`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct DoctorOptions {
+    /// Warn about provider stacks with more than DEPTH nested providers
+    #[arg(long, value_name = "DEPTH")]
+    pub max_provider_depth: Option<usize>,
+}
+
+impl DoctorOptions {
+    /// Parses the arguments following `cargo cgp doctor`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        parse_args("doctor", args)
+    }
+}
+
+pub fn run_doctor(options: &DoctorOptions) -> Result<()> {
+    let (workspace_root, source_roots) =
+        workspace_source_roots().context("Failed to read the workspace metadata")?;
+    let scan = WorkspaceScan::scan_roots(&source_roots, &workspace_root);
+
+    for file in &scan.unreadable_files {
+        eprintln!(
+            "warning: could not read `{}`, the report may be incomplete",
+            file.display()
+        );
+    }
+
+    let config = CgpConfig::load()?;
+    let color = config.check_defaults()?.color;
+    let options = DoctorOptions {
+        max_provider_depth: options.max_provider_depth.or(config.max_provider_depth),
+    };
+    let diagnostics = diagnose(&scan, &options);
+    for diagnostic in &diagnostics {
+        if color.enabled() {
+            println!("{}", render_diagnostic_graphical(diagnostic));
+        } else {
+            println!("{}", render_diagnostic_plain(diagnostic));
+        }
+    }
+
+    match diagnostics.len() {
+        0 => println!("cargo cgp doctor: no problems found"),
+        1 => println!("cargo cgp doctor: found 1 problem"),
+        n => println!("cargo cgp doctor: found {} problems", n),
+    }
+
+    if let Some(trailer) =
+        format_explain_trailer(diagnostics.iter().filter_map(|d| d.code.as_deref()))
+    {
+        println!("{}", trailer);
+    }
+
+    Ok(())
+}
+
+/// Runs all checks on the scanned sources, in a stable order
+pub fn diagnose(scan: &WorkspaceScan, options: &DoctorOptions) -> Vec<CgpDiagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for context_type in scan.wired_contexts() {
+        diagnostics.extend(check_has_field_derive(scan, context_type));
+        diagnostics.extend(check_unchecked_components(scan, context_type));
+        if let Some(max_depth) = options.max_provider_depth {
+            diagnostics.extend(check_provider_depth(scan, context_type, max_depth));
+        }
+    }
+
+    diagnostics.extend(check_unused_providers(scan));
+    diagnostics
+}
+
+/// Reports the components that a context delegates but no `check_components!` block checks
+fn check_unchecked_components(scan: &WorkspaceScan, context_type: &str) -> Option<CgpDiagnostic> {
+    let checks: Vec<_> = scan
+        .check_blocks
+        .iter()
+        .filter(|block| base_type_name(&block.context_type) == base_type_name(context_type))
+        .collect();
+
+    let unchecked: Vec<&Delegation> = scan
+        .delegations_of(context_type)
+        .into_iter()
+        .filter(|delegation| {
+            !checks.iter().any(|block| {
+                block.components.iter().any(|component| {
+                    base_type_name(&component.name) == base_type_name(&delegation.component)
+                })
+            })
+        })
+        .collect();
+
+    if unchecked.is_empty() {
+        return None;
+    }
+
+    let message = match unchecked.len() {
+        1 => format!(
+            "`{}` delegates `{}`, which is never checked with `check_components!`",
+            context_type, unchecked[0].component
+        ),
+        n => format!(
+            "`{}` delegates {} components that are never checked with `check_components!`",
+            context_type, n
+        ),
+    };
+
+    let mut help_sections = vec![
+        "Wiring errors in unchecked components are only reported where the components are used."
+            .to_string(),
+        String::new(),
+    ];
+
+    match checks.first() {
+        Some(block) => help_sections.push(format!(
+            "To fix this warning, add the components to `{}` at {}:{}:",
+            block.trait_name,
+            block.display_path.display(),
+            block.line
+        )),
+        None => help_sections.push("To fix this warning, check the components:".to_string()),
+    }
+
+    let trait_name = checks.first().map_or_else(
+        || format!("CanUse{}", base_type_name(context_type)),
+        |block| block.trait_name.clone(),
+    );
+    help_sections.push("    check_components! {".to_string());
+    help_sections.push(format!("        {} for {} {{", trait_name, context_type));
+    for delegation in &unchecked {
+        help_sections.push(format!("            {},", delegation.component));
+    }
+    help_sections.push("        }".to_string());
+    help_sections.push("    }".to_string());
+
+    let spans = unchecked
+        .iter()
+        .map(|delegation| {
+            location_span(&delegation.location, &delegation.component, "never checked")
+        })
+        .collect();
+
+    let mut diagnostic = CgpDiagnostic::new(message, spans);
+    diagnostic.code = Some(CgpErrorCode::UncheckedComponent.code().to_string());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.severity = Some(Severity::Warning);
+
+    Some(diagnostic)
+}
+
+/// Reports a context whose providers read fields through getters, although the
+/// context struct does not derive `HasField`
+fn check_has_field_derive(scan: &WorkspaceScan, context_type: &str) -> Option<CgpDiagnostic> {
+    let context = scan.context_struct(context_type)?;
+    if context.derives_has_field {
+        return None;
+    }
+
+    // The getters required by the providers wired for the context, with the provider
+    let mut getters: Vec<(String, String)> = Vec::new();
+    for delegation in scan.delegations_of(context_type) {
+        let provider_trait = scan
+            .component_decl(&delegation.component)
+            .map(|decl| decl.provider_trait.as_str());
+
+        for provider_impl in scan.provider_impls_in(&delegation.provider, provider_trait) {
+            for bound in &provider_impl.requirements {
+                let getter = (bound.clone(), provider_impl.provider_name().to_string());
+                if matches!(
+                    classify_requirement(scan, context_type, bound),
+                    Requirement::Getter(_)
+                ) && !getters.contains(&getter)
+                {
+                    getters.push(getter);
+                }
+            }
+        }
+    }
+
+    if getters.is_empty() {
+        return None;
+    }
+
+    let message = format!(
+        "`{}` is used as a CGP context, but does not derive `HasField`",
+        context_type
+    );
+
+    let mut help_sections = vec![format!(
+        "The providers wired for `{}` read its fields through getters:",
+        context_type
+    )];
+    for (getter, provider) in &getters {
+        help_sections.push(format!("    • `{}`, required by `{}`", getter, provider));
+    }
+    help_sections.push(String::new());
+    help_sections.push("To fix this warning:".to_string());
+    help_sections.push(format!(
+        "    • Add `#[derive(HasField)]` to the struct definition at {}:{}",
+        context.location.display_path.display(),
+        context.location.line
+    ));
+
+    let span = location_span(
+        &context.location,
+        &context.type_name,
+        "does not derive `HasField`",
+    );
+
+    let mut diagnostic = CgpDiagnostic::new(message, vec![span]);
+    diagnostic.code = Some(CgpErrorCode::MissingHasFieldDerive.code().to_string());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.severity = Some(Severity::Warning);
+
+    Some(diagnostic)
+}
+
+/// Reports providers that are implemented but not used in any `delegate_components!` block
+/// Providers used as the inner provider of a higher-order provider count as used
+fn check_unused_providers(scan: &WorkspaceScan) -> Vec<CgpDiagnostic> {
+    let used_names: Vec<&str> = scan
+        .delegations
+        .iter()
+        .flat_map(|delegation| {
+            delegation
+                .provider
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        })
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    let mut reported: Vec<&str> = Vec::new();
+
+    for provider_impl in &scan.provider_impls {
+        let provider_name = provider_impl.provider_name();
+        if used_names.contains(&provider_name) || reported.contains(&provider_name) {
+            continue;
+        }
+        reported.push(provider_name);
+
+        let message = format!(
+            "provider `{}` is never used in `delegate_components!`",
+            provider_name
+        );
+        let help = [
+            format!(
+                "`{}` implements `{}`, but no context in the workspace is wired to it, so it is never checked against a context.",
+                provider_impl.provider_type, provider_impl.provider_trait
+            ),
+            String::new(),
+            "If the provider is meant to be wired by downstream crates, this can be ignored."
+                .to_string(),
+        ];
+
+        let span = location_span(
+            &provider_impl.location,
+            &provider_impl.provider_type,
+            "provider defined here",
+        );
+
+        let mut diagnostic = CgpDiagnostic::new(message, vec![span]);
+        diagnostic.code = Some(CgpErrorCode::UnusedProvider.code().to_string());
+        diagnostic.help = Some(help.join("\n"));
+        diagnostic.severity = Some(Severity::Advice);
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics
+}
+
+/// Reports the delegations of a context whose provider stack has more than `max_depth`
+/// nested providers (e.g., `ScaledArea<DoubledArea<RectangleArea>>` has 3)
+fn check_provider_depth(
+    scan: &WorkspaceScan,
+    context_type: &str,
+    max_depth: usize,
+) -> Vec<CgpDiagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for delegation in scan.delegations_of(context_type) {
+        let stack = provider_stack(&delegation.provider);
+        if stack.len() <= max_depth {
+            continue;
+        }
+
+        let message = format!(
+            "`{}` wires `{}` to a stack of {} nested providers (the budget is {})",
+            context_type,
+            delegation.component,
+            stack.len(),
+            max_depth
+        );
+
+        let mut help_sections = vec!["The provider stack, outermost first:".to_string()];
+        for (level, provider) in stack.iter().enumerate() {
+            help_sections.push(format!("    {}{}", "  ".repeat(level), provider));
+        }
+        help_sections.push(String::new());
+        help_sections.push(
+            "Errors in deep stacks are hard to read, since rustc reports them through every wrapping provider."
+                .to_string(),
+        );
+        help_sections.push(
+            "Consider defining a named provider for part of the stack, or flattening the wrappers."
+                .to_string(),
+        );
+
+        let span = location_span(
+            &delegation.location,
+            &delegation.component,
+            &format!("{} nested providers", stack.len()),
+        );
+
+        let mut diagnostic = CgpDiagnostic::new(message, vec![span]);
+        diagnostic.code = Some(CgpErrorCode::DeepProviderStack.code().to_string());
+        diagnostic.help = Some(help_sections.join("\n"));
+        diagnostic.severity = Some(Severity::Warning);
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics
+}
+
+/// Returns the providers of the deepest nesting path in a provider type, outermost first
+/// (e.g., "ScaledArea<DoubledArea<RectangleArea>, f64>" -> ScaledArea, DoubledArea, RectangleArea)
+fn provider_stack(provider: &str) -> Vec<String> {
+    let mut stack = vec![base_type_name(provider).to_string()];
+
+    // Lifetimes, const arguments, primitive types and macro calls such as
+    // `Symbol!("width")` are not providers.
+    // Reversed so that the first of several equally deep arguments is kept
+    let deepest = generic_arguments(provider)
+        .into_iter()
+        .rev()
+        .filter(|argument| {
+            let name = base_type_name(argument);
+            is_type_argument(argument)
+                && name.starts_with(|c: char| c.is_ascii_uppercase())
+                && !name.contains('!')
+        })
+        .map(provider_stack)
+        .max_by_key(Vec::len);
+    stack.extend(deepest.unwrap_or_default());
+    stack
+}
+
+/// Creates a span highlighting `text` at a scanned source location
+fn location_span(location: &SourceLocation, text: &str, label: &str) -> SpanLocation {
+    SpanLocation {
+        file: location.display_path.display().to_string(),
+        line_start: location.line,
+        column_start: location.column,
+        line_end: location.line,
+        column_end: location.column + text.chars().count(),
+        label: label.to_string(),
+        text: Vec::new(),
+        source_path: Some(location.file.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const SOURCE: &str = r#"
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_component(DensityCalculator)]
+pub trait CanCalculateDensity {
+    fn density(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn area(&self) -> f64 {
+        self.width()
+    }
+}
+
+#[cgp_impl(new ScaledArea<InnerCalculator>)]
+impl<InnerCalculator> AreaCalculator {
+    fn area(&self) -> f64 {
+        0.0
+    }
+}
+
+#[cgp_impl(new SquareArea)]
+impl AreaCalculator {
+    fn area(&self) -> f64 {
+        0.0
+    }
+}
+
+pub struct Rectangle {
+    pub width: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: ScaledArea<RectangleArea>,
+        DensityCalculatorComponent: DensityFromArea,
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
+"#;
+
+    #[test]
+    fn test_diagnose() {
+        let mut scan = WorkspaceScan::new();
+        let path = Path::new("src/lib.rs");
+        scan.scan_file(path, path, "shapes", "crate", SOURCE);
+
+        let diagnostics = diagnose(&scan, &DoctorOptions::default());
+        let summaries: Vec<(&str, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.code.as_deref().unwrap(), d.message.as_str()))
+            .collect();
+
+        assert_eq!(
+            summaries,
+            vec![
+                (
+                    "CGP0003",
+                    "`Rectangle` is used as a CGP context, but does not derive `HasField`"
+                ),
+                (
+                    "CGP0007",
+                    "`Rectangle` delegates `DensityCalculatorComponent`, which is never checked with `check_components!`"
+                ),
+                (
+                    "CGP0008",
+                    "provider `SquareArea` is never used in `delegate_components!`"
+                ),
+            ]
+        );
+
+        assert!(
+            diagnostics[0]
+                .help
+                .as_ref()
+                .unwrap()
+                .contains("`HasRectangleFields`, required by `RectangleArea`")
+        );
+        assert!(
+            diagnostics[1]
+                .help
+                .as_ref()
+                .unwrap()
+                .contains("add the components to `CanUseRectangle` at src/lib.rs:53:")
+        );
+        assert_eq!(diagnostics[1].spans[0].line_start, 48);
+        assert_eq!(diagnostics[1].spans[0].column_start, 9);
+    }
+
+    #[test]
+    fn test_provider_depth() {
+        assert_eq!(
+            provider_stack("ScaledArea<DoubledArea<RectangleArea>, f64>"),
+            vec!["ScaledArea", "DoubledArea", "RectangleArea"]
+        );
+        assert_eq!(
+            provider_stack("UseField<Symbol!(\"width\")>"),
+            vec!["UseField"]
+        );
+        assert_eq!(
+            provider_stack("Retry<'static, 3, ScaledArea<RectangleArea>>"),
+            vec!["Retry", "ScaledArea", "RectangleArea"]
+        );
+
+        let mut scan = WorkspaceScan::new();
+        let path = Path::new("src/lib.rs");
+        scan.scan_file(path, path, "shapes", "crate", SOURCE);
+
+        let options = DoctorOptions {
+            max_provider_depth: Some(1),
+        };
+        let diagnostics: Vec<_> = diagnose(&scan, &options)
+            .into_iter()
+            .filter(|d| d.code.as_deref() == Some("CGP0010"))
+            .collect();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`Rectangle` wires `AreaCalculatorComponent` to a stack of 2 nested providers (the budget is 1)"
+        );
+        assert!(
+            diagnostics[0]
+                .help
+                .as_ref()
+                .unwrap()
+                .contains("    ScaledArea\n      RectangleArea\n")
+        );
+        assert_eq!(diagnostics[0].spans[0].line_start, 47);
+
+        let parse = |args: &[&str]| DoctorOptions::parse(args.iter().map(|arg| arg.to_string()));
+        assert_eq!(
+            parse(&["--max-provider-depth", "4"])
+                .unwrap()
+                .max_provider_depth,
+            Some(4)
+        );
+        assert_eq!(parse(&[]).unwrap(), DoctorOptions::default());
+    }
+}
@@ -0,0 +1,213 @@
+/// Module for `cargo cgp document-wiring`
+/// Generates a markdown document describing how each context is wired, based on the
+/// workspace source index: its components, their providers, and the getters and fields
+/// the providers require from the context
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::cli::parse_args;
+use crate::graph::{Requirement, build_wiring_graph, classify_requirement, field_status};
+use crate::workspace_scan::{WorkspaceScan, workspace_source_roots};
+
+/// Options for `cargo cgp document-wiring`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct DocumentWiringOptions {
+    /// Embed a Mermaid wiring graph for each context
+    #[arg(long)]
+    pub mermaid: bool,
+    /// Write the document to this file instead of stdout
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+impl DocumentWiringOptions {
+    /// Parses the arguments following `cargo cgp document-wiring`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        parse_args("document-wiring", args)
+    }
+}
+
+pub fn run_document_wiring(options: &DocumentWiringOptions) -> Result<()> {
+    let (workspace_root, source_roots) =
+        workspace_source_roots().context("Failed to read the workspace metadata")?;
+    let scan = WorkspaceScan::scan_roots(&source_roots, &workspace_root);
+
+    for file in &scan.unreadable_files {
+        eprintln!(
+            "warning: could not read `{}`, the document may be incomplete",
+            file.display()
+        );
+    }
+
+    let document = render_wiring_document(&scan, options.mermaid);
+
+    match &options.output {
+        Some(path) => std::fs::write(path, document)
+            .with_context(|| format!("Failed to write `{}`", path.display()))?,
+        None => print!("{}", document),
+    }
+
+    Ok(())
+}
+
+/// Renders the markdown document for all contexts wired in the workspace
+pub fn render_wiring_document(scan: &WorkspaceScan, include_mermaid: bool) -> String {
+    let mut lines = vec![
+        "# CGP wiring".to_string(),
+        String::new(),
+        "Generated by `cargo cgp document-wiring` from the workspace sources.".to_string(),
+    ];
+
+    let contexts = scan.wired_contexts();
+    if contexts.is_empty() {
+        lines.push(String::new());
+        lines.push("No `delegate_components!` blocks were found.".to_string());
+    }
+
+    for context_type in contexts {
+        lines.push(String::new());
+        lines.extend(render_context_section(scan, context_type, include_mermaid));
+    }
+
+    let mut document = lines.join("\n");
+    document.push('\n');
+    document
+}
+
+/// Renders the section describing a single context
+fn render_context_section(
+    scan: &WorkspaceScan,
+    context_type: &str,
+    include_mermaid: bool,
+) -> Vec<String> {
+    let context = scan.context_struct(context_type);
+    let mut lines = vec![format!("## `{}`", context_type), String::new()];
+
+    if let Some(context) = context
+        && !context.derives_has_field
+    {
+        lines.push(format!(
+            "`{}` does not derive `HasField`, so getters cannot read its fields.",
+            context_type
+        ));
+        lines.push(String::new());
+    }
+
+    lines.push("| Component | Provider | Provider trait |".to_string());
+    lines.push("| --- | --- | --- |".to_string());
+
+    let delegations = scan.delegations_of(context_type);
+    for delegation in &delegations {
+        let provider_trait = scan
+            .component_decl(&delegation.component)
+            .map_or("unknown".to_string(), |decl| {
+                format!("`{}`", decl.provider_trait)
+            });
+        lines.push(format!(
+            "| `{}` | `{}` | {} |",
+            delegation.component, delegation.provider, provider_trait
+        ));
+    }
+
+    // List the requirements of each provider implementation once
+    let mut requirement_lines = Vec::new();
+    let mut listed_providers: Vec<&str> = Vec::new();
+
+    for delegation in &delegations {
+        let provider_trait = scan
+            .component_decl(&delegation.component)
+            .map(|decl| decl.provider_trait.as_str());
+
+        for provider_impl in scan.provider_impls_in(&delegation.provider, provider_trait) {
+            if listed_providers.contains(&provider_impl.provider_type.as_str())
+                || provider_impl.requirements.is_empty()
+            {
+                continue;
+            }
+            listed_providers.push(&provider_impl.provider_type);
+
+            requirement_lines.push(format!("- `{}` requires:", provider_impl.provider_type));
+
+            for bound in &provider_impl.requirements {
+                match classify_requirement(scan, context_type, bound) {
+                    Requirement::Getter(getter) => {
+                        requirement_lines.push(format!("  - `{}`, reading fields:", bound));
+                        for field in &getter.fields {
+                            let status = match field_status(context, &field.name) {
+                                Some(false) => " (missing)",
+                                _ => "",
+                            };
+                            requirement_lines.push(format!(
+                                "    - `{}: {}`{}",
+                                field.name, field.field_type, status
+                            ));
+                        }
+                    }
+                    Requirement::Consumer {
+                        component,
+                        is_wired: true,
+                    } => {
+                        requirement_lines
+                            .push(format!("  - `{}`, provided by `{}`", bound, component));
+                    }
+                    Requirement::Consumer {
+                        component,
+                        is_wired: false,
+                    } => {
+                        requirement_lines.push(format!(
+                            "  - `{}`, provided by `{}`, which is not wired for `{}`",
+                            bound, component, context_type
+                        ));
+                    }
+                    Requirement::Other => {
+                        requirement_lines.push(format!("  - `{}`", bound));
+                    }
+                }
+            }
+        }
+    }
+
+    if !requirement_lines.is_empty() {
+        lines.push(String::new());
+        lines.push("### Requirements".to_string());
+        lines.push(String::new());
+        lines.extend(requirement_lines);
+    }
+
+    if include_mermaid {
+        lines.push(String::new());
+        lines.push("### Wiring graph".to_string());
+        lines.push(String::new());
+        lines.push("```mermaid".to_string());
+        lines.push(render_mermaid_graph(scan, context_type));
+        lines.push("```".to_string());
+    }
+
+    lines
+}
+
+/// Renders the wiring of a context as a Mermaid flowchart
+pub fn render_mermaid_graph(scan: &WorkspaceScan, context_type: &str) -> String {
+    build_wiring_graph(scan, context_type).render_mermaid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<DocumentWiringOptions> {
+        DocumentWiringOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_document_wiring_options() {
+        let options = parse(&["--mermaid", "--output", "WIRING.md"]).unwrap();
+        assert!(options.mermaid);
+        assert_eq!(options.output, Some(PathBuf::from("WIRING.md")));
+
+        assert_eq!(parse(&[]).unwrap(), DocumentWiringOptions::default());
+        assert!(parse(&["--workspace"]).is_err());
+    }
+}
@@ -0,0 +1,530 @@
+/// Module for `cargo cgp expand-check <file:line>`
+/// Shows what a `check_components!` or `delegate_components!` block expands to, so that
+/// errors naming generated items (e.g., a check trait or an `IsProviderFor` impl) can be
+/// traced back to the block. The module containing the block is expanded with
+/// `cargo expand`, or with rustc's `-Zunpretty=expanded` if cargo-expand is not installed,
+/// and only the items that the block generated are printed, each with a comment.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use syn::spanned::Spanned;
+use syn::{GenericArgument, Item, PathArguments, Type};
+
+use crate::cli::parse_args;
+use crate::workspace_scan::{
+    Delegation, ModuleFile, WiringMacro, WorkspaceScan, base_type_name, find_wiring_macros,
+    workspace_source_roots,
+};
+
+/// Options for `cargo cgp expand-check`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct ExpandCheckOptions {
+    /// Any line of the block (e.g., src/lib.rs:42), as `FILE:LINE` or `FILE:LINE:COLUMN`
+    #[arg(value_name = "FILE:LINE")]
+    pub location: String,
+    /// Arguments passed through to cargo after `--` (e.g., `-- --features serde`)
+    #[arg(value_name = "CARGO_ARGS", last = true)]
+    pub cargo_args: Vec<String>,
+}
+
+impl ExpandCheckOptions {
+    /// Parses the arguments following `cargo cgp expand-check`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        parse_args("expand-check", args)
+    }
+}
+
+/// A wiring block and the items it generates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WiringBlock {
+    pub wiring_macro: WiringMacro,
+    /// The check traits of a `check_components!` block, with the context each one checks
+    pub check_traits: Vec<(String, String)>,
+    /// The delegations of a `delegate_components!` block
+    pub delegations: Vec<Delegation>,
+}
+
+/// An item of the expansion that the block generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedItem {
+    /// What the item is for (e.g., "delegation: `App` delegates ...")
+    pub note: String,
+    /// The expanded code of the item
+    pub code: String,
+}
+
+pub fn run_expand_check(options: &ExpandCheckOptions) -> Result<()> {
+    let (file, line) = parse_location(&options.location)?;
+    let content = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read `{}`", file.display()))?;
+
+    let (workspace_root, source_roots) =
+        workspace_source_roots().context("Failed to read the workspace metadata")?;
+    let scan = WorkspaceScan::scan_roots(&source_roots, &workspace_root);
+
+    let block = locate_block(&scan, &file, &content, line)?;
+    let module = scan
+        .module_files
+        .iter()
+        .find(|module| same_file(&module.file, &file))
+        .with_context(|| {
+            format!(
+                "`{}` is not a module of a workspace crate (is it declared with `mod`?)",
+                file.display()
+            )
+        })?;
+
+    let expanded = expand_module(module, &options.cargo_args)?;
+    let items = generated_items(&expanded, &module.module_path, &block)?;
+    print!(
+        "{}",
+        render_expansion(&file, &module.module_path, &block, &items)
+    );
+
+    Ok(())
+}
+
+/// Parses a location given as `FILE:LINE` or `FILE:LINE:COLUMN`
+pub fn parse_location(location: &str) -> Result<(PathBuf, usize)> {
+    let invalid = || {
+        format!(
+            "Expected a location as FILE:LINE (e.g., src/lib.rs:42), got `{}`",
+            location
+        )
+    };
+    let (rest, last) = location.rsplit_once(':').with_context(invalid)?;
+    let last: usize = last.parse().with_context(invalid)?;
+
+    // With a column, the line comes before it
+    match rest.rsplit_once(':') {
+        Some((file, line)) if line.parse::<usize>().is_ok() => {
+            Ok((PathBuf::from(file), line.parse()?))
+        }
+        _ => Ok((PathBuf::from(rest), last)),
+    }
+}
+
+/// Finds the wiring block that contains a line of a file, with what the scan knows of it
+pub fn locate_block(
+    scan: &WorkspaceScan,
+    file: &Path,
+    content: &str,
+    line: usize,
+) -> Result<WiringBlock> {
+    let Some(wiring_macro) = find_wiring_macros(content)
+        .into_iter()
+        .find(|found| (found.start_line..=found.end_line).contains(&line))
+    else {
+        bail!(
+            "No `check_components!` or `delegate_components!` block contains {}:{}",
+            file.display(),
+            line
+        );
+    };
+    let lines = wiring_macro.start_line..=wiring_macro.end_line;
+
+    let check_traits = scan
+        .check_blocks
+        .iter()
+        .filter(|block| same_file(&block.file, file) && lines.contains(&block.line))
+        .map(|block| (block.trait_name.clone(), block.context_type.clone()))
+        .collect();
+    let delegations = scan
+        .delegations
+        .iter()
+        .filter(|delegation| {
+            same_file(&delegation.location.file, file) && lines.contains(&delegation.location.line)
+        })
+        .cloned()
+        .collect();
+
+    Ok(WiringBlock {
+        wiring_macro,
+        check_traits,
+        delegations,
+    })
+}
+
+/// Returns true if both paths name the same file on disk
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Expands a module with `cargo expand`, or with `-Zunpretty=expanded` as a fallback
+fn expand_module(module: &ModuleFile, cargo_args: &[String]) -> Result<String> {
+    let target = target_args(module, cargo_args);
+    let item_path = module
+        .module_path
+        .strip_prefix("crate")
+        .map_or(Some(module.module_path.as_str()), |_| None);
+
+    let output = Command::new("cargo")
+        .args(["expand", "--color", "never", "-p", &module.crate_name])
+        .args(&target)
+        .args(cargo_args)
+        .args(item_path)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !stderr.contains("no such command") {
+                bail!("cargo expand failed:\n{}", stderr.trim_end());
+            }
+        }
+        Err(_) => {}
+    }
+
+    // rustc prints the expansion of the whole crate, like `cargo expand` does on nightly
+    let output = Command::new("cargo")
+        .args(["rustc", "-p", &module.crate_name])
+        .args(&target)
+        .args(["--profile", "check"])
+        .args(cargo_args)
+        .args(["--", "-Zunpretty=expanded"])
+        .env("RUSTC_BOOTSTRAP", "1")
+        .output()
+        .context("Failed to spawn cargo rustc")?;
+    if !output.status.success() {
+        bail!(
+            "Expanding the macros with `-Zunpretty=expanded` failed (install cargo-expand \
+             for another way):\n{}",
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The cargo flags that select the target declaring a module, unless the arguments
+/// select one already (e.g., `--lib`, or `--bin app` for "src/bin/app.rs")
+pub fn target_args(module: &ModuleFile, cargo_args: &[String]) -> Vec<String> {
+    const TARGET_FLAGS: [&str; 5] = ["--lib", "--bin", "--test", "--example", "--bench"];
+    if cargo_args.iter().any(|arg| {
+        TARGET_FLAGS
+            .iter()
+            .any(|flag| arg == flag || arg.starts_with(&format!("{}=", flag)))
+    }) {
+        return Vec::new();
+    }
+
+    let root_file = &module.root_file;
+    let file_name = |path: Option<&Path>| {
+        path.and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let stem = root_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let parent = root_file.parent();
+    let grandparent = parent.and_then(Path::parent);
+
+    // Targets of several files have a `main.rs` in a directory named after them
+    let (kind_dir, name) = match stem.as_str() {
+        "lib" => return vec!["--lib".to_string()],
+        "main" if file_name(parent) == "src" => {
+            return vec!["--bin".to_string(), module.crate_name.clone()];
+        }
+        "main" => (file_name(grandparent), file_name(parent)),
+        _ => (file_name(parent), stem),
+    };
+    let flag = match kind_dir.as_str() {
+        "bin" => "--bin",
+        "tests" => "--test",
+        "examples" => "--example",
+        "benches" => "--bench",
+        _ => "--lib",
+    };
+    match flag {
+        "--lib" => vec![flag.to_string()],
+        _ => vec![flag.to_string(), name],
+    }
+}
+
+/// Finds the items of an expanded module that a wiring block generated
+pub fn generated_items(
+    expanded: &str,
+    module_path: &str,
+    block: &WiringBlock,
+) -> Result<Vec<GeneratedItem>> {
+    let file = syn::parse_file(expanded).context("Failed to parse the expanded code")?;
+
+    // `cargo expand` prints the module itself, and rustc the whole crate
+    let mut items = &file.items;
+    for segment in module_path
+        .split("::")
+        .filter(|segment| *segment != "crate")
+    {
+        let child = items.iter().find_map(|item| match item {
+            Item::Mod(module) if module.ident == segment => {
+                module.content.as_ref().map(|(_, items)| items)
+            }
+            _ => None,
+        });
+        if let Some(child) = child {
+            items = child;
+        }
+    }
+
+    let lines: Vec<&str> = expanded.lines().collect();
+    let mut generated = Vec::new();
+    collect_generated_items(items, block, &lines, &mut generated);
+    if generated.is_empty() {
+        bail!(
+            "The expansion of module `{}` contains no items generated by the `{}!` block \
+             (are the same features enabled?)",
+            module_path,
+            block.wiring_macro.macro_name
+        );
+    }
+    Ok(generated)
+}
+
+fn collect_generated_items(
+    items: &[Item],
+    block: &WiringBlock,
+    lines: &[&str],
+    generated: &mut Vec<GeneratedItem>,
+) {
+    for item in items {
+        let note = match item {
+            Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    collect_generated_items(items, block, lines, generated);
+                }
+                continue;
+            }
+            Item::Trait(item) => block
+                .check_traits
+                .iter()
+                .find(|(trait_name, _)| item.ident == trait_name)
+                .map(|(trait_name, context_type)| {
+                    format!(
+                        "check trait: `{}` implements `{}` for every checked component, \
+                         which requires `CanUseComponent` for it",
+                        context_type, trait_name
+                    )
+                }),
+            Item::Impl(item) => impl_note(item, block),
+            _ => None,
+        };
+        let Some(note) = note else {
+            continue;
+        };
+
+        let span = item.span();
+        let code = lines
+            .get(span.start().line.saturating_sub(1)..span.end().line.min(lines.len()))
+            .map(dedent)
+            .unwrap_or_default();
+        generated.push(GeneratedItem { note, code });
+    }
+}
+
+/// Describes an impl that a wiring block generated, or returns `None` for other impls
+fn impl_note(item: &syn::ItemImpl, block: &WiringBlock) -> Option<String> {
+    let (_, trait_path, _) = item.trait_.as_ref()?;
+    let segment = trait_path.segments.last()?;
+    let trait_name = segment.ident.to_string();
+    let self_type = type_name(&item.self_ty)?;
+    let component = first_type_argument(&segment.arguments)?;
+
+    if let Some((_, context_type)) =
+        block
+            .check_traits
+            .iter()
+            .find(|(check_trait, context_type)| {
+                *check_trait == trait_name && base_type_name(context_type) == self_type
+            })
+    {
+        return Some(format!(
+            "check: requires `{}: CanUseComponent<{}>`",
+            context_type, component
+        ));
+    }
+
+    let delegation = block.delegations.iter().find(|delegation| {
+        base_type_name(&delegation.context_type) == self_type
+            && base_type_name(&delegation.component) == component
+    })?;
+    let (context_type, provider) = (&delegation.context_type, &delegation.provider);
+    Some(match trait_name.as_str() {
+        "DelegateComponent" => format!(
+            "delegation: `{}` delegates `{}` to `{}`",
+            context_type, component, provider
+        ),
+        "IsProviderFor" => format!(
+            "marker impl: `{}` is a provider of `{}` wherever `{}` is, which errors about \
+             `IsProviderFor` follow",
+            context_type, component, provider
+        ),
+        _ => return None,
+    })
+}
+
+/// The first type argument of a path segment (e.g., the component of `DelegateComponent<C>`)
+fn first_type_argument(arguments: &PathArguments) -> Option<String> {
+    let PathArguments::AngleBracketed(arguments) = arguments else {
+        return None;
+    };
+    arguments.args.iter().find_map(|argument| match argument {
+        GenericArgument::Type(argument) => type_name(argument),
+        _ => None,
+    })
+}
+
+/// The name of a type without module path and generic arguments (e.g., "App")
+fn type_name(type_expr: &Type) -> Option<String> {
+    match type_expr {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Removes the indentation that all lines share
+fn dedent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the generated items as Rust code, each item after a comment describing it
+pub fn render_expansion(
+    file: &Path,
+    module_path: &str,
+    block: &WiringBlock,
+    items: &[GeneratedItem],
+) -> String {
+    let mut output = format!(
+        "// expansion of `{}!` at {}:{}, in module `{}`\n",
+        block.wiring_macro.macro_name,
+        file.display(),
+        block.wiring_macro.start_line,
+        module_path
+    );
+    for item in items {
+        output.push_str(&format!("\n// {}\n{}\n", item.note, item.code));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(
+            parse_location("src/lib.rs:42").unwrap(),
+            (PathBuf::from("src/lib.rs"), 42)
+        );
+        assert_eq!(
+            parse_location("src/lib.rs:42:9").unwrap(),
+            (PathBuf::from("src/lib.rs"), 42)
+        );
+        assert!(parse_location("src/lib.rs").is_err());
+    }
+
+    #[test]
+    fn test_target_args() {
+        let module = |root_file: &str| ModuleFile {
+            file: PathBuf::from("src/wiring.rs"),
+            crate_name: "app".to_string(),
+            root_file: PathBuf::from(root_file),
+            module_path: "wiring".to_string(),
+        };
+
+        assert_eq!(target_args(&module("/ws/app/src/lib.rs"), &[]), ["--lib"]);
+        assert_eq!(
+            target_args(&module("/ws/app/src/main.rs"), &[]),
+            ["--bin", "app"]
+        );
+        assert_eq!(
+            target_args(&module("/ws/app/src/bin/server/main.rs"), &[]),
+            ["--bin", "server"]
+        );
+        assert_eq!(
+            target_args(&module("/ws/app/tests/wiring.rs"), &[]),
+            ["--test", "wiring"]
+        );
+        assert!(target_args(&module("/ws/app/src/lib.rs"), &["--bin=app".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_generated_items() {
+        use crate::test_utils::{examples_dir, scan_example};
+
+        let file = examples_dir().join("send_context.rs");
+        let content = std::fs::read_to_string(&file).unwrap();
+        let expanded =
+            std::fs::read_to_string(examples_dir().join("send_context_expanded.rs")).unwrap();
+        let scan = scan_example("send_context", &content);
+
+        // Any line of the block locates it
+        let block = locate_block(&scan, &file, &content, 46).unwrap();
+        assert_eq!(block.wiring_macro.start_line, 44);
+        let items = generated_items(&expanded, "send_context", &block).unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(
+            items[0]
+                .note
+                .starts_with("check trait: `App` implements `CanUseApp`")
+        );
+        assert_eq!(
+            items[1].note,
+            "check: requires `App: CanUseComponent<UserFetcherComponent>`"
+        );
+        assert_eq!(
+            items[1].code,
+            "impl CanUseApp<UserFetcherComponent, ()> for App {}"
+        );
+
+        let block = locate_block(&scan, &file, &content, 38).unwrap();
+        let items = generated_items(&expanded, "crate::send_context", &block).unwrap();
+        let rendered = render_expansion(
+            Path::new("examples/src/send_context.rs"),
+            "send_context",
+            &block,
+            &items,
+        );
+        assert_eq!(
+            rendered,
+            "// expansion of `delegate_components!` at examples/src/send_context.rs:37, in module `send_context`
+
+// delegation: `App` delegates `UserFetcherComponent` to `FetchInBackground`
+impl DelegateComponent<UserFetcherComponent> for App {
+    type Delegate = FetchInBackground;
+}
+
+// marker impl: `App` is a provider of `UserFetcherComponent` wherever `FetchInBackground` is, which errors about `IsProviderFor` follow
+impl<__Context__, __Params__>
+    IsProviderFor<UserFetcherComponent, __Context__, __Params__> for App
+    where
+    FetchInBackground: IsProviderFor<UserFetcherComponent, __Context__,
+    __Params__> {}
+"
+        );
+
+        assert!(locate_block(&scan, &file, &content, 20).is_err());
+    }
+}
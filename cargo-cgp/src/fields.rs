@@ -0,0 +1,369 @@
+/// Module for `cargo cgp fields`
+/// Lists the fields that a context exposes through `HasField` next to the fields that the
+/// getters of its wired providers read, so that surplus and missing fields show up even
+/// while the code compiles (e.g., before a provider is checked with `check_components!`)
+use anyhow::{Context, Result, bail};
+use clap::Args;
+
+use crate::cli::parse_args;
+use crate::graph::{Requirement, classify_requirement};
+use crate::workspace_scan::{ContextStruct, FieldDecl, WorkspaceScan, workspace_source_roots};
+
+/// Options for `cargo cgp fields`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct FieldsOptions {
+    /// The context whose fields are listed (e.g., Rectangle)
+    #[arg(value_name = "CONTEXT")]
+    pub context: String,
+}
+
+impl FieldsOptions {
+    /// Parses the arguments following `cargo cgp fields`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        parse_args("fields", args)
+    }
+}
+
+pub fn run_fields(options: &FieldsOptions) -> Result<()> {
+    let (workspace_root, source_roots) =
+        workspace_source_roots().context("Failed to read the workspace metadata")?;
+    let scan = WorkspaceScan::scan_roots(&source_roots, &workspace_root);
+
+    for file in &scan.unreadable_files {
+        eprintln!(
+            "warning: could not read `{}`, the inventory may be incomplete",
+            file.display()
+        );
+    }
+
+    let inventory = FieldInventory::build(&scan, &options.context)?;
+    println!("{}", inventory.render().join("\n"));
+
+    Ok(())
+}
+
+/// A field read by the getter of a wired provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredField {
+    pub field: FieldDecl,
+    /// The getter trait reading the field (e.g., "HasRectangleFields")
+    pub getter_trait: String,
+    /// The provider requiring the getter (e.g., "RectangleArea")
+    pub provider: String,
+}
+
+impl RequiredField {
+    fn describe(&self) -> String {
+        format!(
+            "read by `{}` for the provider `{}`",
+            self.getter_trait, self.provider
+        )
+    }
+}
+
+/// The fields a context exposes and the fields its wired providers require
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldInventory<'a> {
+    pub context_type: String,
+    /// The struct whose fields are exposed: the context, or the struct it derefs to
+    pub field_struct: Option<&'a ContextStruct>,
+    /// Fields exposed through `HasField`, empty if the struct does not derive it
+    pub exposed: Vec<&'a FieldDecl>,
+    /// Fields read by the getters of the wired providers, once per getter and provider
+    pub required: Vec<RequiredField>,
+}
+
+impl<'a> FieldInventory<'a> {
+    /// Collects the fields of a context from the workspace sources
+    pub fn build(scan: &'a WorkspaceScan, context_type: &str) -> Result<Self> {
+        let delegations = scan.delegations_of(context_type);
+        let context = scan.context_struct(context_type);
+        if context.is_none() && delegations.is_empty() {
+            bail!(
+                "No struct or `delegate_components!` block for `{}` was found in the workspace sources",
+                context_type
+            );
+        }
+
+        // A context without `HasField` may expose the fields of the struct it derefs to
+        let field_struct = match context {
+            Some(context) if context.derives_has_field => Some(context),
+            _ => scan.deref_field_struct(context_type).or(context),
+        };
+        let exposed = field_struct
+            .filter(|field_struct| field_struct.derives_has_field)
+            .map(|field_struct| field_struct.fields.iter().collect())
+            .unwrap_or_default();
+
+        let mut required: Vec<RequiredField> = Vec::new();
+        for delegation in &delegations {
+            let provider_trait = scan
+                .component_decl(&delegation.component)
+                .map(|decl| decl.provider_trait.as_str());
+
+            for provider_impl in scan.provider_impls_in(&delegation.provider, provider_trait) {
+                for bound in &provider_impl.requirements {
+                    let Requirement::Getter(getter) =
+                        classify_requirement(scan, context_type, bound)
+                    else {
+                        continue;
+                    };
+                    for field in &getter.fields {
+                        let required_field = RequiredField {
+                            field: field.clone(),
+                            getter_trait: getter.trait_name.clone(),
+                            provider: provider_impl.provider_name().to_string(),
+                        };
+                        if !required.contains(&required_field) {
+                            required.push(required_field);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(FieldInventory {
+            context_type: context_type.to_string(),
+            field_struct,
+            exposed,
+            required,
+        })
+    }
+
+    /// Exposed fields that no wired provider reads
+    pub fn surplus(&self) -> Vec<&'a FieldDecl> {
+        self.exposed
+            .iter()
+            .filter(|field| {
+                !self
+                    .required
+                    .iter()
+                    .any(|required| required.field.name == field.name)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Required fields that the context does not expose
+    pub fn missing(&self) -> Vec<&RequiredField> {
+        self.required
+            .iter()
+            .filter(|required| self.exposed_field(&required.field.name).is_none())
+            .collect()
+    }
+
+    /// Required fields that the context exposes with another type, with the exposed field
+    pub fn mismatched(&self) -> Vec<(&RequiredField, &'a FieldDecl)> {
+        self.required
+            .iter()
+            .filter_map(|required| {
+                let exposed = self.exposed_field(&required.field.name)?;
+                (normalize_type(&exposed.field_type) != normalize_type(&required.field.field_type))
+                    .then_some((required, exposed))
+            })
+            .collect()
+    }
+
+    fn exposed_field(&self, name: &str) -> Option<&'a FieldDecl> {
+        self.exposed
+            .iter()
+            .find(|field| field.name == name)
+            .copied()
+    }
+
+    /// Renders the inventory, with the exposed fields first and then the differences
+    pub fn render(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        match self.field_struct {
+            Some(field_struct) => {
+                lines.push(format!(
+                    "`{}` ({}:{})",
+                    self.context_type,
+                    field_struct.location.display_path.display(),
+                    field_struct.location.line
+                ));
+                if field_struct.type_name != self.context_type {
+                    lines.push(format!(
+                        "  fields come from `{}` through `Deref`",
+                        field_struct.type_name
+                    ));
+                }
+                if !field_struct.derives_has_field {
+                    lines.push(format!(
+                        "  `{}` does not derive `HasField`, so getters cannot read its fields",
+                        field_struct.type_name
+                    ));
+                }
+            }
+            None => lines.push(format!(
+                "`{}` (the struct was not found in the workspace sources)",
+                self.context_type
+            )),
+        }
+
+        lines.push(String::new());
+        lines.push("Exposed fields:".to_string());
+        if self.exposed.is_empty() {
+            lines.push("  (none)".to_string());
+        }
+        let surplus = self.surplus();
+        for field in &self.exposed {
+            let readers: Vec<String> = self
+                .required
+                .iter()
+                .filter(|required| required.field.name == field.name)
+                .map(RequiredField::describe)
+                .collect();
+            let note = if surplus.contains(field) {
+                "not read by any wired provider".to_string()
+            } else {
+                readers.join(", ")
+            };
+            lines.push(format!("  {}: {} — {}", field.name, field.field_type, note));
+        }
+
+        let missing = self.missing();
+        if !missing.is_empty() {
+            lines.push(String::new());
+            lines.push("Missing fields:".to_string());
+            for required in &missing {
+                lines.push(format!(
+                    "  {}: {} — {}",
+                    required.field.name,
+                    required.field.field_type,
+                    required.describe()
+                ));
+            }
+        }
+
+        let mismatched = self.mismatched();
+        if !mismatched.is_empty() {
+            lines.push(String::new());
+            lines.push("Fields with another type:".to_string());
+            for (required, exposed) in &mismatched {
+                lines.push(format!(
+                    "  {}: {} — {}, which expects `{}`",
+                    exposed.name,
+                    exposed.field_type,
+                    required.describe(),
+                    required.field.field_type
+                ));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "summary: {} exposed, {} surplus, {} missing, {} with another type",
+            self.exposed.len(),
+            surplus.len(),
+            missing.len(),
+            mismatched.len()
+        ));
+
+        lines
+    }
+}
+
+/// Removes the whitespace from a type, so that types compare the same however they are
+/// formatted (e.g., "Vec<u8>" and "Vec< u8 >")
+fn normalize_type(field_type: &str) -> String {
+    field_type.split_whitespace().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const SOURCE: &str = r#"
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasScaleFactor {
+    fn scale_factor(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+#[cgp_impl(new ScaledArea<InnerCalculator>)]
+impl<InnerCalculator> AreaCalculator
+where
+    Self: HasScaleFactor,
+    InnerCalculator: AreaCalculator<Self>,
+{
+    fn area(&self) -> f64 {
+        self.scale_factor() * InnerCalculator::area(self)
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    pub scale_factor: f32,
+    pub label: String,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: ScaledArea<RectangleArea>,
+    }
+}
+"#;
+
+    #[test]
+    fn test_field_inventory() {
+        let mut scan = WorkspaceScan::new();
+        let path = Path::new("src/lib.rs");
+        scan.scan_file(path, path, "shapes", "crate", SOURCE);
+
+        let inventory = FieldInventory::build(&scan, "Rectangle").unwrap();
+        assert_eq!(
+            inventory.render(),
+            vec![
+                "`Rectangle` (src/lib.rs:41)",
+                "",
+                "Exposed fields:",
+                "  width: f64 — read by `HasRectangleFields` for the provider `RectangleArea`",
+                "  scale_factor: f32 — read by `HasScaleFactor` for the provider `ScaledArea`",
+                "  label: String — not read by any wired provider",
+                "",
+                "Missing fields:",
+                "  height: f64 — read by `HasRectangleFields` for the provider `RectangleArea`",
+                "",
+                "Fields with another type:",
+                "  scale_factor: f32 — read by `HasScaleFactor` for the provider `ScaledArea`, which expects `f64`",
+                "",
+                "summary: 3 exposed, 1 surplus, 1 missing, 1 with another type",
+            ]
+        );
+
+        assert!(FieldInventory::build(&scan, "Circle").is_err());
+    }
+
+    #[test]
+    fn test_parse_fields_options() {
+        let parse = |args: &[&str]| FieldsOptions::parse(args.iter().map(|arg| arg.to_string()));
+
+        assert_eq!(parse(&["Rectangle"]).unwrap().context, "Rectangle");
+        assert!(parse(&[]).is_err());
+    }
+}
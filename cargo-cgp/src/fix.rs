@@ -0,0 +1,495 @@
+/// Module for `cargo cgp fix`
+/// Applies the fixes suggested for missing field errors: the missing field is added to the
+/// context struct, and `#[derive(HasField)]` is added if the struct does not derive it.
+/// The context struct is located with the workspace source index and edited with `syn`.
+use std::collections::BTreeMap;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+use cargo_metadata::Message;
+use clap::Args;
+use proc_macro2::LineColumn;
+use similar::TextDiff;
+use syn::spanned::Spanned;
+use syn::{Fields, Item, ItemStruct, Meta};
+
+use crate::cgp_patterns::is_cgp_diagnostic;
+use crate::cli::parse_with_passthrough;
+use crate::diagnostic_db::{DiagnosticDatabase, DiagnosticEntry};
+use crate::field_name::{FieldName, HIDDEN_CHAR};
+use crate::package_selection::checked_source_roots;
+use crate::workspace_scan::WorkspaceScan;
+
+/// Placeholder type for a new field whose type cannot be inferred from a getter trait
+const PLACEHOLDER_FIELD_TYPE: &str = "() /* TODO: set the field type */";
+
+/// Options for `cargo cgp fix`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct FixOptions {
+    /// Show the changes as a diff instead of writing the files
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Remaining arguments passed through to `cargo check`
+    #[arg(value_name = "CARGO_ARGS", allow_hyphen_values = true)]
+    pub cargo_args: Vec<String>,
+}
+
+impl FixOptions {
+    /// Parses the arguments following `cargo cgp fix`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let (options, cargo_args) = parse_with_passthrough::<FixOptions>("fix", args)?;
+        Ok(FixOptions {
+            cargo_args,
+            ..options
+        })
+    }
+}
+
+/// A change to a context struct
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    /// Add a named field to the struct
+    AddField {
+        context_type: String,
+        field_name: String,
+        field_type: Option<String>,
+    },
+    /// Add `#[derive(HasField)]` to the struct
+    DeriveHasField { context_type: String },
+}
+
+impl Fix {
+    /// The context struct that the fix changes
+    pub fn context_type(&self) -> &str {
+        match self {
+            Fix::AddField { context_type, .. } | Fix::DeriveHasField { context_type } => {
+                context_type
+            }
+        }
+    }
+
+    /// Describes the fix for the summary printed after applying it
+    pub fn describe(&self) -> String {
+        match self {
+            Fix::AddField {
+                context_type,
+                field_name,
+                field_type,
+            } => format!(
+                "add field `{}: {}` to `{}`",
+                field_name,
+                field_type.as_deref().unwrap_or("()"),
+                context_type
+            ),
+            Fix::DeriveHasField { context_type } => {
+                format!("add `#[derive(HasField)]` to `{}`", context_type)
+            }
+        }
+    }
+}
+
+pub fn run_fix(options: &FixOptions) -> Result<()> {
+    let (workspace_root, source_roots) = checked_source_roots(&options.cargo_args)
+        .context("Failed to read the workspace metadata")?;
+    let scan = WorkspaceScan::scan_roots(&source_roots, &workspace_root);
+    let db = collect_diagnostics(&options.cargo_args)?;
+
+    // Group the fixes by the file containing the context struct
+    let mut fixes_by_file: BTreeMap<PathBuf, Vec<Fix>> = BTreeMap::new();
+    for fix in plan_fixes(&db, &scan) {
+        match scan.context_struct(fix.context_type()) {
+            Some(context) => fixes_by_file
+                .entry(context.location.file.clone())
+                .or_default()
+                .push(fix),
+            None => eprintln!(
+                "warning: cannot {}: the struct was not found in the workspace",
+                fix.describe()
+            ),
+        }
+    }
+
+    if fixes_by_file.is_empty() {
+        println!("No CGP fixes to apply");
+        return Ok(());
+    }
+
+    for (file, fixes) in fixes_by_file {
+        let content = std::fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read `{}`", file.display()))?;
+        let fixed = apply_fixes(&content, &fixes)
+            .with_context(|| format!("Failed to fix `{}`", file.display()))?;
+        let path = file.display().to_string();
+
+        if options.dry_run {
+            print!(
+                "{}",
+                TextDiff::from_lines(&content, &fixed)
+                    .unified_diff()
+                    .header(&path, &path)
+            );
+        } else {
+            std::fs::write(&file, fixed).with_context(|| format!("Failed to write `{}`", path))?;
+            for fix in &fixes {
+                println!("Fixed `{}`: {}", path, fix.describe());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs `cargo check` and collects its CGP diagnostics
+/// Fails with cargo's own errors if cargo failed before the compiler reported anything
+/// (e.g., for an unknown package or a broken manifest)
+pub fn collect_diagnostics(cargo_args: &[String]) -> Result<DiagnosticDatabase> {
+    let mut child = Command::new("cargo")
+        .arg("check")
+        .arg("--message-format=json")
+        .args(cargo_args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn cargo check")?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .context("Failed to capture stdout from cargo check")?;
+    let mut stderr = child
+        .stderr
+        .take()
+        .context("Failed to capture stderr from cargo check")?;
+    let stderr_reader = std::thread::spawn(move || {
+        let mut content = String::new();
+        let _ = stderr.read_to_string(&mut content);
+        content
+    });
+
+    let mut db = DiagnosticDatabase::new();
+    let mut compiler_messages = 0;
+    for message in Message::parse_stream(BufReader::new(stdout)) {
+        let message = message.context("Failed to parse JSON message from cargo")?;
+        if let Message::CompilerMessage(msg) = &message {
+            compiler_messages += 1;
+            if is_cgp_diagnostic(&msg.message) {
+                db.add_diagnostic(msg);
+            }
+        }
+    }
+
+    let status = child.wait().context("Failed to wait for cargo check")?;
+    let cargo_stderr = stderr_reader.join().unwrap_or_default();
+    if !status.success() && compiler_messages == 0 {
+        bail!("cargo check failed:\n{}", cargo_stderr.trim_end());
+    }
+
+    Ok(db)
+}
+
+/// Plans the fixes for the missing field errors in the database
+/// Field names that were shortened by the compiler are completed from the getter traits,
+/// and fields whose name stays uncertain are skipped
+pub fn plan_fixes(db: &DiagnosticDatabase, scan: &WorkspaceScan) -> Vec<Fix> {
+    plan_entry_fixes(db.get_active_entries(), scan)
+}
+
+/// Plans the fixes for the missing field errors of some entries of the database
+pub fn plan_entry_fixes<'a>(
+    entries: impl IntoIterator<Item = &'a DiagnosticEntry>,
+    scan: &WorkspaceScan,
+) -> Vec<Fix> {
+    let mut fixes = Vec::new();
+
+    for entry in entries {
+        let Some(field_info) = &entry.field_info else {
+            continue;
+        };
+        let context_type = &field_info.target_type;
+        let context = scan.context_struct(context_type);
+
+        if context.is_none_or(|context| !context.derives_has_field) {
+            let fix = Fix::DeriveHasField {
+                context_type: context_type.clone(),
+            };
+            if !fixes.contains(&fix) {
+                fixes.push(fix);
+            }
+        }
+
+        // Getter fields with a matching name give the complete name and the type
+        let getter_field = scan
+            .getter_decls
+            .iter()
+            .flat_map(|getter| &getter.fields)
+            .find(|field| {
+                if field_info.is_complete && !field_info.has_unknown_chars {
+                    field.name == field_info.field_name
+                } else {
+                    let prefix = field_info.field_name.split(HIDDEN_CHAR).next();
+                    prefix
+                        .is_some_and(|prefix| !prefix.is_empty() && field.name.starts_with(prefix))
+                }
+            });
+
+        let field_name = match getter_field {
+            Some(field) => field.name.clone(),
+            None if field_info.is_complete && !field_info.has_unknown_chars => {
+                field_info.field_name.clone()
+            }
+            None => {
+                eprintln!(
+                    "warning: cannot add the field `{}` to `{}`: its full name is unknown",
+                    FieldName::of(field_info),
+                    context_type
+                );
+                continue;
+            }
+        };
+
+        let has_field =
+            context.is_some_and(|context| context.fields.iter().any(|f| f.name == field_name));
+        let fix = Fix::AddField {
+            context_type: context_type.clone(),
+            field_name,
+            field_type: getter_field.map(|field| field.field_type.clone()),
+        };
+        if !has_field && !fixes.contains(&fix) {
+            fixes.push(fix);
+        }
+    }
+
+    fixes
+}
+
+/// Applies fixes to the content of a source file, returning the new content
+pub fn apply_fixes(content: &str, fixes: &[Fix]) -> Result<String> {
+    let file = syn::parse_file(content).context("Failed to parse the source file")?;
+
+    // Edits are (byte offset, inserted text), applied from the end of the file
+    let mut edits: Vec<(usize, String)> = Vec::new();
+
+    for fix in fixes {
+        let Some(item) = find_struct(&file.items, fix.context_type()) else {
+            bail!("struct `{}` not found", fix.context_type());
+        };
+
+        match fix {
+            Fix::DeriveHasField { .. } => edits.push(derive_has_field_edit(content, item)),
+            Fix::AddField {
+                field_name,
+                field_type,
+                ..
+            } => {
+                let field_type = field_type.as_deref().unwrap_or(PLACEHOLDER_FIELD_TYPE);
+                edits.extend(add_field_edits(content, item, field_name, field_type)?);
+            }
+        }
+    }
+
+    // At the same offset, later edits are inserted first, so that they end up after earlier ones
+    let mut edits: Vec<(usize, (usize, String))> = edits.into_iter().enumerate().collect();
+    edits.sort_by_key(|(index, (offset, _))| std::cmp::Reverse((*offset, *index)));
+
+    let mut fixed = content.to_string();
+    for (_, (offset, text)) in edits {
+        fixed.insert_str(offset, &text);
+    }
+
+    Ok(fixed)
+}
+
+/// Finds a struct by name, including inside inline modules
+fn find_struct<'a>(items: &'a [Item], name: &str) -> Option<&'a ItemStruct> {
+    items.iter().find_map(|item| match item {
+        Item::Struct(item) if item.ident == name => Some(item),
+        Item::Mod(module) => module
+            .content
+            .as_ref()
+            .and_then(|(_, items)| find_struct(items, name)),
+        _ => None,
+    })
+}
+
+/// Adds `HasField` to an existing `#[derive(...)]`, or adds a new derive attribute
+fn derive_has_field_edit(content: &str, item: &ItemStruct) -> (usize, String) {
+    for attr in &item.attrs {
+        if let Meta::List(list) = &attr.meta
+            && list.path.is_ident("derive")
+        {
+            let close = to_offset(content, list.delimiter.span().close().start());
+            let separator = if list.tokens.is_empty() { "" } else { ", " };
+            return (close, format!("{}HasField", separator));
+        }
+    }
+
+    // Insert the attribute on its own line, right before the visibility or `struct` keyword
+    let start = to_offset(content, item.vis.span().start())
+        .min(to_offset(content, item.struct_token.span.start()));
+    let line_start = content[..start].rfind('\n').map_or(0, |pos| pos + 1);
+    let indent = &content[line_start..start];
+
+    (start, format!("#[derive(HasField)]\n{}", indent))
+}
+
+/// Adds a named field at the end of the struct body, and a comma after the last field
+/// if it has none
+fn add_field_edits(
+    content: &str,
+    item: &ItemStruct,
+    field_name: &str,
+    field_type: &str,
+) -> Result<Vec<(usize, String)>> {
+    let Fields::Named(fields) = &item.fields else {
+        bail!("struct `{}` does not have named fields", item.ident);
+    };
+
+    let mut edits = Vec::new();
+    if let Some(last) = fields.named.last()
+        && !fields.named.trailing_punct()
+    {
+        edits.push((to_offset(content, last.span().end()), ",".to_string()));
+    }
+
+    let close = to_offset(content, fields.brace_token.span.close().start());
+    let close_line_start = content[..close].rfind('\n').map_or(0, |pos| pos + 1);
+
+    if content[close_line_start..close].trim().is_empty() {
+        // Add a line before the closing brace, with the indentation of the last field
+        // or one level more than the brace
+        let indent = match fields.named.last() {
+            Some(field) => {
+                let start = to_offset(content, field.span().start());
+                let line_start = content[..start].rfind('\n').map_or(0, |pos| pos + 1);
+                content[line_start..start].to_string()
+            }
+            None => format!("{}    ", &content[close_line_start..close]),
+        };
+        edits.push((
+            close_line_start,
+            format!("{}{}: {},\n", indent, field_name, field_type),
+        ));
+    } else {
+        let end = content[..close].trim_end().len();
+        edits.push((end, format!(" {}: {}", field_name, field_type)));
+    }
+
+    Ok(edits)
+}
+
+/// Converts a line and character column from a `syn` span into a byte offset
+fn to_offset(content: &str, position: LineColumn) -> usize {
+    let line_start: usize = content
+        .split_inclusive('\n')
+        .take(position.line - 1)
+        .map(str::len)
+        .sum();
+
+    let line = &content[line_start..];
+    line_start
+        + line
+            .char_indices()
+            .nth(position.column)
+            .map_or(line.len(), |(offset, _)| offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_field(context_type: &str, field_name: &str, field_type: Option<&str>) -> Fix {
+        Fix::AddField {
+            context_type: context_type.to_string(),
+            field_name: field_name.to_string(),
+            field_type: field_type.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let content = "\
+use cgp::prelude::*;
+
+#[derive(Debug, HasField)]
+pub struct Rectangle {
+    pub width: f64
+}
+
+pub struct Circle { pub radius: f64 }
+";
+        let fixed = apply_fixes(
+            content,
+            &[
+                add_field("Rectangle", "height", Some("f64")),
+                Fix::DeriveHasField {
+                    context_type: "Circle".to_string(),
+                },
+                add_field("Circle", "density", None),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            fixed,
+            "\
+use cgp::prelude::*;
+
+#[derive(Debug, HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    height: f64,
+}
+
+#[derive(HasField)]
+pub struct Circle { pub radius: f64, density: () /* TODO: set the field type */ }
+"
+        );
+        assert!(syn::parse_file(&fixed).is_ok());
+    }
+
+    #[test]
+    fn test_derive_has_field_in_existing_derive() {
+        let content = "mod shapes {\n    #[derive(Clone)]\n    struct Square { side: f64 }\n}\n";
+        let fixed = apply_fixes(
+            content,
+            &[Fix::DeriveHasField {
+                context_type: "Square".to_string(),
+            }],
+        )
+        .unwrap();
+
+        assert!(fixed.contains("#[derive(Clone, HasField)]"));
+
+        // The comma goes after the last field, not after a trailing comment
+        let content = "struct Point {\n    x: f64 // horizontal\n}\n";
+        let fixed = apply_fixes(content, &[add_field("Point", "y", Some("f64"))]).unwrap();
+        assert_eq!(
+            fixed,
+            "struct Point {\n    x: f64, // horizontal\n    y: f64,\n}\n"
+        );
+        assert!(apply_fixes(content, &[add_field("Missing", "x", None)]).is_err());
+    }
+
+    #[test]
+    fn test_plan_fixes() {
+        use crate::test_utils::{examples_dir, load_example_with_index};
+
+        let (db, scan) = load_example_with_index("base_area");
+        let content = std::fs::read_to_string(examples_dir().join("base_area.rs")).unwrap();
+
+        let fixes = plan_fixes(&db, &scan);
+        assert_eq!(fixes, vec![add_field("Rectangle", "height", Some("f64"))]);
+
+        let fixed = apply_fixes(&content, &fixes).unwrap();
+        assert!(fixed.contains("    height: f64,\n}"));
+    }
+
+    #[test]
+    fn test_parse_fix_options() {
+        let options = FixOptions::parse(["--dry-run", "-p", "shapes"].map(str::to_string)).unwrap();
+        assert!(options.dry_run);
+        assert_eq!(options.cargo_args, vec!["-p", "shapes"]);
+    }
+}
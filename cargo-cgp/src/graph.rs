@@ -0,0 +1,421 @@
+/// Module for `cargo cgp graph`
+/// Visualizes how contexts are wired, based on the workspace source index: each context
+/// delegates components to providers, and providers require traits from the context.
+/// The wiring is rendered as a tree in the terminal, or as a Graphviz DOT graph.
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+
+use crate::cli::parse_args;
+use crate::error_formatting::escape_mermaid_label;
+use crate::workspace_scan::{
+    ContextStruct, GetterDecl, WorkspaceScan, base_type_name, workspace_source_roots,
+};
+
+/// Output format of `cargo cgp graph`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// An indented tree, for reading in the terminal
+    #[default]
+    Tree,
+    /// A Graphviz DOT graph, e.g. for `dot -Tsvg`
+    Dot,
+}
+
+/// Options for `cargo cgp graph`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct GraphOptions {
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    pub format: GraphFormat,
+    /// Only show these contexts (all wired contexts if empty)
+    #[arg(long = "context", value_name = "TYPE")]
+    pub contexts: Vec<String>,
+}
+
+impl GraphOptions {
+    /// Parses the arguments following `cargo cgp graph`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        parse_args("graph", args)
+    }
+}
+
+pub fn run_graph(options: &GraphOptions) -> Result<()> {
+    let (workspace_root, source_roots) =
+        workspace_source_roots().context("Failed to read the workspace metadata")?;
+    let scan = WorkspaceScan::scan_roots(&source_roots, &workspace_root);
+
+    for file in &scan.unreadable_files {
+        eprintln!(
+            "warning: could not read `{}`, the graph may be incomplete",
+            file.display()
+        );
+    }
+
+    let contexts: Vec<&str> = scan
+        .wired_contexts()
+        .into_iter()
+        .filter(|context| {
+            options.contexts.is_empty() || options.contexts.iter().any(|name| name == context)
+        })
+        .collect();
+
+    if contexts.is_empty() {
+        eprintln!("No wired contexts were found in `delegate_components!` blocks.");
+        return Ok(());
+    }
+
+    match options.format {
+        GraphFormat::Tree => {
+            for context_type in contexts {
+                println!("{}", render_wiring_tree(&scan, context_type).join("\n"));
+            }
+        }
+        GraphFormat::Dot => {
+            let mut graph = WiringGraph::default();
+            for context_type in contexts {
+                add_context_wiring(&mut graph, &scan, context_type);
+            }
+            println!("{}", graph.render_dot());
+        }
+    }
+
+    Ok(())
+}
+
+/// A requirement of a provider on the context, classified using the source index
+pub enum Requirement<'a> {
+    /// A getter trait, which reads fields from the context
+    Getter(&'a GetterDecl),
+    /// A consumer trait of a component, which must also be wired for the context
+    Consumer { component: &'a str, is_wired: bool },
+    /// Any other trait bound
+    Other,
+}
+
+pub fn classify_requirement<'a>(
+    scan: &'a WorkspaceScan,
+    context_type: &str,
+    bound: &str,
+) -> Requirement<'a> {
+    if let Some(getter) = scan.getter_decl(bound) {
+        return Requirement::Getter(getter);
+    }
+
+    if let Some(component) = scan.component_of_consumer_trait(bound) {
+        let is_wired = scan
+            .delegations_of(context_type)
+            .iter()
+            .any(|d| scan.component_decl(&d.component) == Some(component));
+        return Requirement::Consumer {
+            component: &component.component_name,
+            is_wired,
+        };
+    }
+
+    Requirement::Other
+}
+
+/// Returns whether a context provides a field, or None if the context struct is not indexed
+pub fn field_status(context: Option<&ContextStruct>, field_name: &str) -> Option<bool> {
+    context.map(|context| {
+        context.derives_has_field && context.fields.iter().any(|f| f.name == field_name)
+    })
+}
+
+/// Renders the wiring of a context as a tree: context, then components, then the
+/// provider of each component and the requirements of its implementations
+pub fn render_wiring_tree(scan: &WorkspaceScan, context_type: &str) -> Vec<String> {
+    let context = scan.context_struct(context_type);
+    let mut lines = vec![context_type.to_string()];
+
+    let delegations = scan.delegations_of(context_type);
+    for (index, delegation) in delegations.iter().enumerate() {
+        let is_last = index + 1 == delegations.len();
+        let (branch, indent) = tree_prefixes("", is_last);
+
+        let provider_trait = scan
+            .component_decl(&delegation.component)
+            .map(|decl| decl.provider_trait.as_str());
+        lines.push(match provider_trait {
+            Some(provider_trait) => {
+                format!("{}{} ({})", branch, delegation.component, provider_trait)
+            }
+            None => format!("{}{}", branch, delegation.component),
+        });
+        lines.push(format!("{}└─ {}", indent, delegation.provider));
+
+        let mut requirements = Vec::new();
+        for provider_impl in scan.provider_impls_in(&delegation.provider, provider_trait) {
+            for bound in &provider_impl.requirements {
+                let description = match classify_requirement(scan, context_type, bound) {
+                    Requirement::Getter(getter) => {
+                        let missing: Vec<&str> = getter
+                            .fields
+                            .iter()
+                            .filter(|field| field_status(context, &field.name) == Some(false))
+                            .map(|field| field.name.as_str())
+                            .collect();
+                        if missing.is_empty() {
+                            format!("{} (getter)", bound)
+                        } else {
+                            format!("{} (getter, missing: {})", bound, missing.join(", "))
+                        }
+                    }
+                    Requirement::Consumer {
+                        component,
+                        is_wired: true,
+                    } => format!("{} (via {})", bound, component),
+                    Requirement::Consumer {
+                        component,
+                        is_wired: false,
+                    } => format!("{} (via {}, not wired)", bound, component),
+                    Requirement::Other => bound.clone(),
+                };
+                requirements.push(format!(
+                    "{} requires {}",
+                    provider_impl.provider_name(),
+                    description
+                ));
+            }
+        }
+
+        let provider_indent = format!("{}   ", indent);
+        for (index, requirement) in requirements.iter().enumerate() {
+            let (branch, _) = tree_prefixes(&provider_indent, index + 1 == requirements.len());
+            lines.push(format!("{}{}", branch, requirement));
+        }
+    }
+
+    lines
+}
+
+/// Returns the branch prefix of a tree node and the indentation of its children
+fn tree_prefixes(indent: &str, is_last: bool) -> (String, String) {
+    if is_last {
+        (format!("{}└─ ", indent), format!("{}   ", indent))
+    } else {
+        (format!("{}├─ ", indent), format!("{}│  ", indent))
+    }
+}
+
+/// Builds the wiring graph of a single context
+pub fn build_wiring_graph(scan: &WorkspaceScan, context_type: &str) -> WiringGraph {
+    let mut graph = WiringGraph::default();
+    add_context_wiring(&mut graph, scan, context_type);
+    graph
+}
+
+/// Adds the wiring of a context to a graph
+/// Edges go from the context to its providers (labelled with the component),
+/// from providers to the traits they require, and from getter traits to fields
+fn add_context_wiring(graph: &mut WiringGraph, scan: &WorkspaceScan, context_type: &str) {
+    let context = scan.context_struct(context_type);
+    let context_node = graph.node(context_type);
+
+    for delegation in scan.delegations_of(context_type) {
+        let provider_node = graph.node(&delegation.provider);
+        graph.edge(context_node, provider_node, Some(&delegation.component));
+
+        let provider_trait = scan
+            .component_decl(&delegation.component)
+            .map(|decl| decl.provider_trait.as_str());
+
+        for provider_impl in scan.provider_impls_in(&delegation.provider, provider_trait) {
+            for bound in &provider_impl.requirements {
+                let bound_node = graph.node(bound);
+                graph.edge(provider_node, bound_node, None);
+
+                match classify_requirement(scan, context_type, bound) {
+                    Requirement::Getter(getter) => {
+                        for field in &getter.fields {
+                            let label = match field_status(context, &field.name) {
+                                Some(false) => {
+                                    format!("{}: {} (missing)", field.name, field.field_type)
+                                }
+                                _ => format!("{}: {}", field.name, field.field_type),
+                            };
+                            let field_node = graph.node(&label);
+                            graph.edge(bound_node, field_node, None);
+                        }
+                    }
+                    // A consumer trait is satisfied by the provider wired for its component
+                    Requirement::Consumer {
+                        component,
+                        is_wired: true,
+                    } => {
+                        for wired in scan.delegations_of(context_type) {
+                            if base_type_name(&wired.component) == component {
+                                let wired_node = graph.node(&wired.provider);
+                                graph.edge(bound_node, wired_node, Some(component));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// A wiring graph with deduplicated nodes and edges
+#[derive(Default)]
+pub struct WiringGraph {
+    nodes: Vec<String>,
+    node_ids: HashMap<String, usize>,
+    edges: Vec<(usize, usize, Option<String>)>,
+}
+
+impl WiringGraph {
+    fn node(&mut self, label: &str) -> usize {
+        if let Some(id) = self.node_ids.get(label) {
+            return *id;
+        }
+        let id = self.nodes.len();
+        self.nodes.push(label.to_string());
+        self.node_ids.insert(label.to_string(), id);
+        id
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: Option<&str>) {
+        let edge = (from, to, label.map(str::to_string));
+        if !self.edges.contains(&edge) {
+            self.edges.push(edge);
+        }
+    }
+
+    /// Renders the graph as a Mermaid flowchart
+    pub fn render_mermaid(&self) -> String {
+        let mut lines = vec!["graph LR".to_string()];
+
+        for (id, label) in self.nodes.iter().enumerate() {
+            lines.push(format!("    n{}[\"{}\"]", id, escape_mermaid_label(label)));
+        }
+
+        for (from, to, label) in &self.edges {
+            match label {
+                Some(label) => lines.push(format!(
+                    "    n{} -->|\"{}\"| n{}",
+                    from,
+                    escape_mermaid_label(label),
+                    to
+                )),
+                None => lines.push(format!("    n{} --> n{}", from, to)),
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    /// Renders the graph as a Graphviz DOT digraph
+    pub fn render_dot(&self) -> String {
+        let mut lines = vec![
+            "digraph cgp_wiring {".to_string(),
+            "    rankdir=LR;".to_string(),
+        ];
+
+        for (id, label) in self.nodes.iter().enumerate() {
+            lines.push(format!("    n{} [label={:?}];", id, label));
+        }
+
+        for (from, to, label) in &self.edges {
+            match label {
+                Some(label) => lines.push(format!("    n{} -> n{} [label={:?}];", from, to, label)),
+                None => lines.push(format!("    n{} -> n{};", from, to)),
+            }
+        }
+
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const SOURCE: &str = r#"
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasScaleFactor {
+    fn scale_factor(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator {
+    fn area(&self) -> f64 {
+        1.0
+    }
+}
+
+#[cgp_impl(new ScaledArea<InnerCalculator>)]
+impl<InnerCalculator> AreaCalculator
+where
+    Self: HasScaleFactor,
+    InnerCalculator: AreaCalculator<Self>,
+{
+    fn area(&self) -> f64 {
+        self.scale_factor() * InnerCalculator::area(self)
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: ScaledArea<RectangleArea>,
+    }
+}
+"#;
+
+    fn scan() -> WorkspaceScan {
+        let mut scan = WorkspaceScan::new();
+        let path = Path::new("src/lib.rs");
+        scan.scan_file(path, path, "shapes", "crate", SOURCE);
+        scan
+    }
+
+    #[test]
+    fn test_render_wiring_tree() {
+        assert_eq!(
+            render_wiring_tree(&scan(), "Rectangle"),
+            vec![
+                "Rectangle",
+                "└─ AreaCalculatorComponent (AreaCalculator)",
+                "   └─ ScaledArea<RectangleArea>",
+                "      └─ ScaledArea requires HasScaleFactor (getter, missing: scale_factor)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let dot = build_wiring_graph(&scan(), "Rectangle").render_dot();
+
+        assert!(dot.starts_with("digraph cgp_wiring {"));
+        assert!(dot.contains("n0 [label=\"Rectangle\"];"));
+        assert!(dot.contains("n0 -> n1 [label=\"AreaCalculatorComponent\"];"));
+        assert!(dot.contains("[label=\"scale_factor: f64 (missing)\"];"));
+    }
+
+    #[test]
+    fn test_parse_graph_options() {
+        let parse = |args: &[&str]| GraphOptions::parse(args.iter().map(|arg| arg.to_string()));
+
+        let options = parse(&["--format=dot", "--context", "Rectangle"]).unwrap();
+        assert_eq!(options.format, GraphFormat::Dot);
+        assert_eq!(options.contexts, vec!["Rectangle".to_string()]);
+
+        assert_eq!(parse(&[]).unwrap(), GraphOptions::default());
+        assert!(parse(&["--format", "svg"]).is_err());
+    }
+}
@@ -1,8 +1,32 @@
-pub mod cgp_diagnostic;
-pub mod cgp_patterns;
-pub mod diagnostic_db;
-pub mod error_formatting;
+pub mod budget;
+pub mod changed_files;
+pub mod check_options;
+pub mod cli;
+pub mod config;
+pub mod doctor;
+pub mod document_wiring;
+pub mod expand_check;
+pub mod fields;
+pub mod fix;
+pub mod graph;
+pub mod lsp;
+pub mod metrics_file;
+pub mod multi_workspace;
+pub mod pager;
 pub mod render;
-pub mod root_cause;
 pub mod run_check;
-pub mod test_utils;
+pub mod run_log;
+pub mod run_stats;
+pub mod serve;
+pub mod target_repeats;
+pub mod verify;
+pub mod watch;
+
+// The analysis and rendering modules live in `cgp-diagnostics`, re-exported under their old paths
+pub use cgp_diagnostics::expect_chain;
+pub use cgp_diagnostics::{
+    cgp_diagnostic, cgp_patterns, diagnostic_db, doc_tests, error_formatting, explain, field_name,
+    grouping_trace, json_output, long_types, macro_warnings, name_resolution, package_selection,
+    panic_guard, plain_render, raw_filter, root_cause, source_snippet, symbol_cache,
+    symbol_display, test_utils, type_expr, workspace_scan,
+};
@@ -0,0 +1,391 @@
+/// Module for `cargo cgp lsp`, a language server that publishes the improved CGP errors
+/// The server speaks the Language Server Protocol over stdin and stdout. It runs
+/// `cargo check` when the editor connects and whenever a file is saved, and publishes the
+/// improved CGP errors as `textDocument/publishDiagnostics` notifications, with one
+/// related-information entry for each node of the delegation chain. It complements
+/// rust-analyzer, which keeps reporting the other errors.
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+
+use crate::cgp_diagnostic::{CgpDiagnostic, Severity, SpanLocation};
+use crate::error_formatting::{DependencyNode, merge_duplicate_check_errors};
+use crate::fix::collect_diagnostics;
+use crate::name_resolution::{NameResolver, ResolutionMode};
+use crate::run_check::scan_checked_crates;
+
+/// JSON-RPC error code for requests that the server does not implement
+const METHOD_NOT_FOUND: i64 = -32601;
+
+pub fn run_lsp() -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+    let mut writer = std::io::stdout();
+
+    let mut server = LspServer::default();
+    while let Some(message) = read_message(&mut reader)? {
+        if !server.handle(&message, &mut writer)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// The state of the language server between messages
+#[derive(Debug, Default)]
+struct LspServer {
+    /// The workspace root, from the `initialize` request
+    root: Option<PathBuf>,
+    /// The files that diagnostics were last published for, to clear them once fixed
+    published: BTreeSet<String>,
+}
+
+impl LspServer {
+    /// Handles one message from the client
+    /// Returns false once the client asked the server to exit
+    fn handle(&mut self, message: &Value, writer: &mut impl Write) -> Result<bool> {
+        let id = message.get("id").cloned();
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+
+        match (method, id) {
+            ("initialize", Some(id)) => {
+                self.root = message
+                    .pointer("/params/rootUri")
+                    .and_then(Value::as_str)
+                    .and_then(uri_to_path);
+                let result = json!({
+                    "capabilities": {
+                        "textDocumentSync": { "openClose": true, "save": true },
+                    },
+                    "serverInfo": { "name": "cargo-cgp", "version": env!("CARGO_PKG_VERSION") },
+                });
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                )?;
+            }
+            ("initialized" | "textDocument/didSave", None) => {
+                // A failed check (e.g., a broken manifest) must not stop the server
+                if let Err(error) = self.check(writer) {
+                    eprintln!("cargo cgp lsp: {:#}", error);
+                }
+            }
+            ("shutdown", Some(id)) => {
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "result": null }),
+                )?;
+            }
+            ("exit", _) => return Ok(false),
+            (_, Some(id)) => {
+                let error = json!({
+                    "code": METHOD_NOT_FOUND,
+                    "message": format!("Unsupported method `{}`", method),
+                });
+                write_message(
+                    writer,
+                    &json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+                )?;
+            }
+            // Other notifications (e.g., `textDocument/didOpen`) need no answer
+            (_, None) => {}
+        }
+
+        Ok(true)
+    }
+
+    /// Runs `cargo check` in the workspace and publishes the improved diagnostics
+    fn check(&mut self, writer: &mut impl Write) -> Result<()> {
+        if let Some(root) = &self.root {
+            std::env::set_current_dir(root)
+                .with_context(|| format!("Failed to enter {}", root.display()))?;
+        }
+        let root = std::env::current_dir().context("Failed to read the current directory")?;
+
+        let scan = scan_checked_crates(&[]);
+        let mut db = collect_diagnostics(&[])?;
+        db.set_name_resolver(NameResolver::from_scan(&scan, ResolutionMode::Heuristic));
+        let diagnostics =
+            merge_duplicate_check_errors(db.render_cgp_diagnostics(), &scan.duplicate_checks());
+
+        let mut by_file = to_lsp_diagnostics(&diagnostics, &root);
+
+        // A file without errors anymore gets an empty list, which clears the old ones
+        for uri in &self.published {
+            by_file.entry(uri.clone()).or_default();
+        }
+        self.published = by_file
+            .iter()
+            .filter(|(_, diagnostics)| !diagnostics.is_empty())
+            .map(|(uri, _)| uri.clone())
+            .collect();
+
+        for (uri, diagnostics) in by_file {
+            let params = json!({ "uri": uri, "diagnostics": diagnostics });
+            write_message(
+                writer,
+                &json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": params,
+                }),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads one message with a `Content-Length` header, or None at the end of the input
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = None;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            content_length = Some(value.trim().parse::<usize>()?);
+        }
+    }
+
+    let Some(content_length) = content_length else {
+        bail!("LSP message without a Content-Length header");
+    };
+
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+/// Writes one message with a `Content-Length` header
+fn write_message(writer: &mut impl Write, message: &Value) -> Result<()> {
+    let content = serde_json::to_string(message)?;
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Converts improved diagnostics to LSP diagnostics, by the URI of the file of their
+/// first span
+fn to_lsp_diagnostics(diagnostics: &[CgpDiagnostic], root: &Path) -> BTreeMap<String, Vec<Value>> {
+    let mut by_file: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+
+    for diagnostic in diagnostics {
+        let Some(primary) = diagnostic.spans.first() else {
+            continue;
+        };
+        let location = |span: &SpanLocation| {
+            let uri = file_uri(&root.join(&span.file));
+            json!({ "uri": uri, "range": range(span) })
+        };
+
+        // The other spans (e.g., one per component), then the delegation chain
+        let mut related: Vec<Value> = diagnostic.spans[1..]
+            .iter()
+            .map(|span| json!({ "location": location(span), "message": span.label }))
+            .collect();
+        if let Some(tree) = &diagnostic.dependency_tree {
+            let mut nodes = Vec::new();
+            flatten_tree(tree, 0, &mut nodes);
+            related.extend(
+                nodes
+                    .into_iter()
+                    .map(|message| json!({ "location": location(primary), "message": message })),
+            );
+        }
+
+        let message = match &diagnostic.help {
+            Some(help) => format!("{}\n\n{}", diagnostic.message, help),
+            None => diagnostic.message.clone(),
+        };
+        let severity = match diagnostic.severity {
+            None | Some(Severity::Error) => 1,
+            Some(Severity::Warning) => 2,
+            Some(Severity::Advice) => 3,
+        };
+
+        by_file
+            .entry(file_uri(&root.join(&primary.file)))
+            .or_default()
+            .push(json!({
+                "range": range(primary),
+                "severity": severity,
+                "code": diagnostic.code,
+                "source": "cargo-cgp",
+                "message": message,
+                "relatedInformation": related,
+            }));
+    }
+
+    by_file
+}
+
+/// Lists the nodes of a dependency tree depth-first, indented by depth
+fn flatten_tree(node: &DependencyNode, depth: usize, lines: &mut Vec<String>) {
+    let status = match node.is_satisfied {
+        Some(true) => " ✓",
+        Some(false) => " ✗",
+        None => "",
+    };
+    lines.push(format!(
+        "{}{}{}",
+        "  ".repeat(depth),
+        node.description,
+        status
+    ));
+
+    for child in &node.children {
+        flatten_tree(child, depth + 1, lines);
+    }
+}
+
+/// Converts a span to an LSP range, whose lines and characters start at 0
+fn range(span: &SpanLocation) -> Value {
+    let position = |line: usize, column: usize| {
+        let (line, character) = (line.saturating_sub(1), column.saturating_sub(1));
+        json!({ "line": line, "character": character })
+    };
+    json!({
+        "start": position(span.line_start, span.column_start),
+        "end": position(span.line_end, span.column_end),
+    })
+}
+
+/// Converts an absolute path to a `file://` URI, percent-encoding reserved characters
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Converts a `file://` URI back to a path, or None for other schemes
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = match tail {
+            [high, low, ..] if byte == b'%' => std::str::from_utf8(&[*high, *low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    Some(PathBuf::from(String::from_utf8(bytes).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        let mut output = Vec::new();
+        write_message(&mut output, &json!({ "jsonrpc": "2.0", "method": "exit" })).unwrap();
+        assert_eq!(
+            String::from_utf8(output.clone()).unwrap(),
+            "Content-Length: 33\r\n\r\n{\"jsonrpc\":\"2.0\",\"method\":\"exit\"}"
+        );
+
+        let mut reader = std::io::BufReader::new(output.as_slice());
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some(json!({ "jsonrpc": "2.0", "method": "exit" }))
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+
+        let mut server = LspServer::default();
+        let mut output = Vec::new();
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "textDocument/hover" });
+        assert!(server.handle(&request, &mut output).unwrap());
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .contains("\"code\":-32601")
+        );
+        assert!(
+            !server
+                .handle(&json!({ "method": "exit" }), &mut Vec::new())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_file_uri() {
+        let path = Path::new("/home/me/my project/src/lib.rs");
+        assert_eq!(file_uri(path), "file:///home/me/my%20project/src/lib.rs");
+        assert_eq!(uri_to_path(&file_uri(path)).as_deref(), Some(path));
+        assert_eq!(uri_to_path("untitled:1"), None);
+    }
+
+    #[test]
+    fn test_to_lsp_diagnostics() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let mut db = load_diagnostic_database(format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        ));
+        let by_file = to_lsp_diagnostics(&db.render_cgp_diagnostics(), Path::new("/work"));
+
+        let diagnostics = &by_file["file:///work/examples/src/base_area.rs"];
+        assert_eq!(diagnostics.len(), 1);
+
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic["code"], "CGP0001");
+        assert_eq!(diagnostic["severity"], 1);
+        assert_eq!(diagnostic["range"]["start"]["line"], 40);
+        assert!(
+            diagnostic["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("missing field `heig")
+        );
+
+        let related: Vec<&str> = diagnostic["relatedInformation"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|info| info["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(related[0], "`CanUseRectangle` for `Rectangle`");
+        assert!(related.iter().any(|message| message.ends_with("✗")));
+    }
+}
@@ -0,0 +1,144 @@
+/// Module for the `--metrics-file` output, in the Prometheus textfile format
+/// Build machines running node_exporter's textfile collector pick up the file after each
+/// run, so that the health of the CGP wiring can be tracked without any network access.
+/// Each run overwrites the file, so the values are gauges of the last run.
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+
+use crate::run_stats::{ALL_KINDS, RunStats};
+
+/// Renders the statistics of a run as Prometheus metrics
+/// Every error kind is listed, so that the series of a kind do not disappear when it is fixed
+pub fn render_metrics(stats: &RunStats, timestamp_seconds: u64) -> String {
+    let profile = format!("profile=\"{}\"", escape_label_value(&stats.profile));
+    let mut output = String::new();
+
+    let mut gauge = |name: &str, help: &str, samples: &[(String, String)]| {
+        let _ = writeln!(output, "# HELP cargo_cgp_{} {}", name, help);
+        let _ = writeln!(output, "# TYPE cargo_cgp_{} gauge", name);
+        for (labels, value) in samples {
+            let _ = writeln!(output, "cargo_cgp_{}{{{}}} {}", name, labels, value);
+        }
+    };
+
+    let count = |value: usize| vec![(profile.clone(), value.to_string())];
+    let by_kind: Vec<(String, String)> = ALL_KINDS
+        .iter()
+        .map(|kind| {
+            let value = stats.by_kind.get(kind.as_str()).copied().unwrap_or(0);
+            (
+                format!("{},kind=\"{}\"", profile, kind.as_str()),
+                value.to_string(),
+            )
+        })
+        .collect();
+
+    gauge(
+        "errors",
+        "CGP errors of the last run, after merging related diagnostics.",
+        &by_kind,
+    );
+    gauge(
+        "other_errors",
+        "Compiler errors of the last run that are not related to CGP.",
+        &count(stats.other_errors),
+    );
+    gauge(
+        "warnings",
+        "Warnings of the last run from the workspace scan.",
+        &count(stats.warnings),
+    );
+    gauge(
+        "merged_diagnostics",
+        "Compiler diagnostics of the last run merged into CGP errors.",
+        &count(stats.merged),
+    );
+    gauge(
+        "suppressed_errors",
+        "CGP errors of the last run suppressed as redundant.",
+        &count(stats.suppressed),
+    );
+    gauge(
+        "run_duration_seconds",
+        "Wall-clock time of the last run.",
+        &[(
+            profile.clone(),
+            format!("{:.3}", stats.elapsed_ms as f64 / 1000.0),
+        )],
+    );
+    gauge(
+        "last_run_timestamp_seconds",
+        "Unix time at which the last run finished.",
+        &[(profile.clone(), timestamp_seconds.to_string())],
+    );
+
+    output
+}
+
+/// Writes the metrics of a run to a file
+/// The file is replaced at once, so that the collector never reads a partial file
+pub fn write_metrics_file(stats: &RunStats, path: &Path) -> Result<()> {
+    let timestamp_seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    std::fs::write(&temp_path, render_metrics(stats, timestamp_seconds))
+        .with_context(|| format!("Failed to write `{}`", temp_path.display()))?;
+    std::fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to write `{}`", path.display()))
+}
+
+/// Escapes a label value of the Prometheus text format
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let mut stats = RunStats {
+            profile: "dev".to_string(),
+            cgp_errors: 2,
+            other_errors: 1,
+            merged: 3,
+            elapsed_ms: 1250,
+            ..RunStats::default()
+        };
+        stats.by_kind.insert("missing-field".to_string(), 2);
+
+        let metrics = render_metrics(&stats, 1_700_000_000);
+        assert!(metrics.contains("# TYPE cargo_cgp_errors gauge\n"));
+        assert!(metrics.contains("cargo_cgp_errors{profile=\"dev\",kind=\"missing-field\"} 2\n"));
+        assert!(metrics.contains("cargo_cgp_errors{profile=\"dev\",kind=\"unsized-type\"} 0\n"));
+        assert!(metrics.contains("cargo_cgp_other_errors{profile=\"dev\"} 1\n"));
+        assert!(metrics.contains("cargo_cgp_merged_diagnostics{profile=\"dev\"} 3\n"));
+        assert!(metrics.contains("cargo_cgp_suppressed_errors{profile=\"dev\"} 0\n"));
+        assert!(metrics.contains("cargo_cgp_run_duration_seconds{profile=\"dev\"} 1.250\n"));
+        assert!(
+            metrics.contains("cargo_cgp_last_run_timestamp_seconds{profile=\"dev\"} 1700000000\n")
+        );
+
+        assert_eq!(escape_label_value("a\"b\\c"), "a\\\"b\\\\c");
+
+        let path = std::env::temp_dir().join(format!("cargo-cgp-{}.prom", std::process::id()));
+        write_metrics_file(&stats, &path).unwrap();
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .starts_with("# HELP cargo_cgp_errors")
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,271 @@
+/// Module for checking several independent workspaces in one invocation
+/// Monorepos may aggregate workspaces that cargo cannot check together, so `--manifest-path`
+/// may be given several times, or as a glob (e.g., `services/*/Cargo.toml`). Each workspace
+/// is checked in turn with its own source index, under a section header, and a merged
+/// summary follows the last one.
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+use anyhow::{Context, Result, bail};
+
+use crate::check_options::{CheckOptions, OutputFormat};
+use crate::pager::PagerMode;
+use crate::run_check::{run_cargo_pipeline_with_stats, scan_checked_crates, write_stats};
+use crate::run_stats::RunStats;
+
+/// Returns the manifests selected by the `--manifest-path` flags of the cargo arguments,
+/// with globs expanded
+pub fn manifest_paths(cargo_args: &[String]) -> Result<Vec<PathBuf>> {
+    let mut manifests = Vec::new();
+
+    for pattern in manifest_path_values(cargo_args) {
+        for manifest in expand_manifest_glob(&pattern)? {
+            if !manifests.contains(&manifest) {
+                manifests.push(manifest);
+            }
+        }
+    }
+
+    Ok(manifests)
+}
+
+/// Returns the values of the `--manifest-path` flags, in order
+fn manifest_path_values(cargo_args: &[String]) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut args = cargo_args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        } else if arg == "--manifest-path" {
+            values.extend(args.next().cloned());
+        } else if let Some(value) = arg.strip_prefix("--manifest-path=") {
+            values.push(value.to_string());
+        }
+    }
+
+    values
+}
+
+/// Returns the cargo arguments with the given manifest replacing every `--manifest-path` flag
+pub fn with_manifest_path(cargo_args: &[String], manifest: &Path) -> Vec<String> {
+    let mut result = vec![
+        "--manifest-path".to_string(),
+        manifest.display().to_string(),
+    ];
+    let mut args = cargo_args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            result.push(arg.clone());
+            result.extend(args.by_ref().cloned());
+        } else if arg == "--manifest-path" {
+            args.next();
+        } else if !arg.starts_with("--manifest-path=") {
+            result.push(arg.clone());
+        }
+    }
+
+    result
+}
+
+/// Expands `*` and `?` in the components of a manifest path
+/// A path without wildcards is returned as-is, so that cargo reports a missing manifest
+fn expand_manifest_glob(pattern: &str) -> Result<Vec<PathBuf>> {
+    if !pattern.contains(['*', '?']) {
+        return Ok(vec![PathBuf::from(pattern)]);
+    }
+
+    let mut paths = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let name = component.as_os_str().to_string_lossy();
+        if !name.contains(['*', '?']) {
+            for path in &mut paths {
+                path.push(component);
+            }
+            continue;
+        }
+
+        let mut matches = Vec::new();
+        for dir in &paths {
+            let read_from = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir.as_path()
+            };
+            let Ok(entries) = std::fs::read_dir(read_from) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let file_name = entry.file_name();
+                if matches_wildcard(&name, &file_name.to_string_lossy()) {
+                    matches.push(dir.join(file_name));
+                }
+            }
+        }
+        paths = matches;
+    }
+
+    paths.retain(|path| path.is_file());
+    paths.sort();
+    if paths.is_empty() {
+        bail!("No manifest matches `--manifest-path {}`", pattern);
+    }
+    Ok(paths)
+}
+
+/// Matches a name against a pattern in which `*` stands for any characters and `?` for one
+fn matches_wildcard(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skip| matches(rest, &name[skip..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+
+    matches(&pattern, &name)
+}
+
+/// Runs the cargo command in each workspace, then prints a summary of all of them
+/// Exits with the status of the first workspace that failed
+pub fn run_workspaces(command: &str, options: &CheckOptions, manifests: &[PathBuf]) -> Result<()> {
+    let human = options.format == OutputFormat::Human;
+    let mut results: Vec<(&Path, RunStats)> = Vec::new();
+    let mut failure: Option<ExitStatus> = None;
+
+    for (index, manifest) in manifests.iter().enumerate() {
+        if human {
+            println!(
+                "==> workspace `{}` ({}/{})",
+                manifest.display(),
+                index + 1,
+                manifests.len()
+            );
+        }
+
+        // The sections are printed one after the other, and the merged statistics are
+        // written once at the end
+        let workspace_options = CheckOptions {
+            cargo_args: with_manifest_path(&options.cargo_args, manifest),
+            pager: PagerMode::Never,
+            stats_json: None,
+            metrics_file: None,
+            ..options.clone()
+        };
+        let scan = scan_checked_crates(&workspace_options.cargo_args);
+        let (status, stats) = run_cargo_pipeline_with_stats(command, &workspace_options, &scan)
+            .with_context(|| format!("Failed to check `{}`", manifest.display()))?;
+
+        if human {
+            println!();
+        }
+        if !status.success() {
+            failure.get_or_insert(status);
+        }
+        results.push((manifest, stats));
+    }
+
+    let mut total = RunStats {
+        reason: "cgp-run-stats",
+        profile: options.profile(command),
+        ..RunStats::default()
+    };
+    for (_, stats) in &results {
+        total.merge(stats);
+    }
+
+    if human {
+        println!("{}", render_workspace_summary(&results, &total));
+    }
+    write_stats(&total, options)?;
+
+    if let Some(status) = failure {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+/// Renders the summary of several workspaces, one line per workspace and a total
+fn render_workspace_summary(results: &[(&Path, RunStats)], total: &RunStats) -> String {
+    let mut lines = vec![format!("summary of {} workspaces:", results.len())];
+    for (manifest, stats) in results {
+        lines.push(format!(
+            "    {}: {}",
+            manifest.display(),
+            stats.render_counts()
+        ));
+    }
+    lines.push(format!("    total: {}", total.render_counts()));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_manifest_paths() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let glob = format!("--manifest-path={}/*/Cargo.toml", root.display());
+
+        assert_eq!(
+            manifest_paths(&args(&["--manifest-path", "a/Cargo.toml", &glob])).unwrap(),
+            vec![
+                PathBuf::from("a/Cargo.toml"),
+                root.join("cargo-cgp/Cargo.toml"),
+                root.join("cgp-diagnostics/Cargo.toml"),
+                root.join("examples/Cargo.toml"),
+            ]
+        );
+        assert!(manifest_paths(&args(&["--manifest-path=missing/*/Cargo.toml"])).is_err());
+        assert!(
+            manifest_paths(&args(&["--", "--manifest-path=a/Cargo.toml"]))
+                .unwrap()
+                .is_empty()
+        );
+
+        assert_eq!(
+            with_manifest_path(
+                &args(&["-p", "shapes", "--manifest-path=a/Cargo.toml", "--", "x"]),
+                Path::new("b/Cargo.toml")
+            ),
+            args(&["--manifest-path", "b/Cargo.toml", "-p", "shapes", "--", "x"])
+        );
+
+        assert!(matches_wildcard("service-*", "service-billing"));
+        assert!(matches_wildcard("v?", "v2"));
+        assert!(!matches_wildcard("v?", "v10"));
+        assert!(!matches_wildcard("service-*", "services"));
+    }
+
+    #[test]
+    fn test_render_workspace_summary() {
+        let stats = |cgp_errors: usize| RunStats {
+            cgp_errors,
+            ..RunStats::default()
+        };
+        let mut total = stats(0);
+        total.merge(&stats(2));
+
+        assert_eq!(
+            render_workspace_summary(
+                &[
+                    (Path::new("a/Cargo.toml"), stats(2)),
+                    (Path::new("b/Cargo.toml"), stats(0))
+                ],
+                &total
+            ),
+            "summary of 2 workspaces:\n    a/Cargo.toml: 2 CGP errors\n    b/Cargo.toml: 0 CGP errors\n    total: 2 CGP errors"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+/// Module for showing long human-readable output through a pager
+/// Follows the `git` conventions: the pager is only used when stdout is a terminal,
+/// it is taken from `CARGO_CGP_PAGER` or `PAGER`, and defaults to `less` with `LESS=FRX`
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use anyhow::bail;
+use terminal_size::{Height, terminal_size};
+
+use crate::error_formatting::is_terminal;
+
+/// When to pipe the output through a pager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PagerMode {
+    /// Use the pager if stdout is a terminal and the output does not fit on one screen
+    #[default]
+    Auto,
+    /// Always use the pager if stdout is a terminal
+    Always,
+    /// Never use the pager
+    Never,
+}
+
+impl FromStr for PagerMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "auto" => Ok(PagerMode::Auto),
+            "always" => Ok(PagerMode::Always),
+            "never" => Ok(PagerMode::Never),
+            other => bail!(
+                "Unknown pager mode `{}` (expected `auto`, `always` or `never`)",
+                other
+            ),
+        }
+    }
+}
+
+/// Environment variable overriding the pager command, checked before `PAGER`
+pub const PAGER_ENV: &str = "CARGO_CGP_PAGER";
+
+/// Prints the output, through a pager if the mode and terminal call for it
+/// Output is printed directly if stdout is not a terminal or the pager fails to start
+pub fn print_paged(output: &str, mode: PagerMode) {
+    if output.is_empty() {
+        return;
+    }
+
+    let use_pager = match mode {
+        PagerMode::Never => false,
+        PagerMode::Always => is_terminal(),
+        PagerMode::Auto => is_terminal() && exceeds_screen(output),
+    };
+
+    if !(use_pager && run_pager(output)) {
+        print!("{}", output);
+    }
+}
+
+/// Checks whether the output has more lines than the terminal can show at once
+fn exceeds_screen(output: &str) -> bool {
+    match terminal_size() {
+        Some((_, Height(height))) => output.lines().count() >= height as usize,
+        None => false,
+    }
+}
+
+/// Returns the pager command configured in the environment, if any
+fn pager_command() -> Option<String> {
+    let command = std::env::var(PAGER_ENV)
+        .or_else(|_| std::env::var("PAGER"))
+        .unwrap_or_else(|_| "less".to_string());
+
+    // An empty pager or `cat` disables paging, as in git
+    let command = command.trim().to_string();
+    if command.is_empty() || command == "cat" {
+        None
+    } else {
+        Some(command)
+    }
+}
+
+/// Pipes the output through the pager and waits for it to exit
+/// Returns false if the pager could not be started
+fn run_pager(output: &str) -> bool {
+    let Some(command) = pager_command() else {
+        return false;
+    };
+
+    let mut pager = shell_command(&command);
+
+    // Quit if the output fits on one screen, keep colors, and don't clear the screen
+    if std::env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+
+    let Ok(mut child) = pager.stdin(Stdio::piped()).spawn() else {
+        return false;
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager early, which closes the pipe; that is not an error
+        let _ = stdin.write_all(output.as_bytes());
+    }
+
+    let _ = child.wait();
+    true
+}
+
+/// Builds a command that runs the pager through the platform shell
+/// This allows pager commands with arguments, such as `less -S`
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
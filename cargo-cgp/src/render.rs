@@ -1,20 +1,37 @@
-use cargo_metadata::Message;
+use cargo_metadata::diagnostic::DiagnosticLevel;
+use cargo_metadata::{CompilerMessage, Message};
 
-use crate::cgp_patterns::is_cgp_diagnostic;
+use crate::cgp_patterns::is_cgp_diagnostic_with;
+use crate::check_options::{CheckOptions, OutputFormat};
 use crate::diagnostic_db::DiagnosticDatabase;
+use crate::macro_warnings::{MacroWarningPolicy, macro_origin, tag_rendered};
+use crate::raw_filter::is_raw;
+
+/// CGP diagnostics selected by the raw filters are rendered like non-CGP diagnostics
+pub fn render_message(message: &Message, db: &mut DiagnosticDatabase, options: &CheckOptions) {
+    // Warnings in code generated by the cgp macros follow their own policy
+    if let Message::CompilerMessage(msg) = message
+        && let Some(macro_name) = macro_origin(&msg.message)
+    {
+        render_macro_warning(message, msg, macro_name, db, options);
+        return;
+    }
 
-pub fn render_message(message: &Message, db: &mut DiagnosticDatabase) {
     match message {
+        Message::CompilerMessage(msg) if is_improved(msg, options) => {
+            // Add to database for later processing, don't render yet
+            db.add_diagnostic(msg);
+        }
+        _ if options.format != OutputFormat::Human => {
+            // Machine output: forward cargo's own message unchanged
+            if let Ok(json) = serde_json::to_string(message) {
+                println!("{}", json);
+            }
+        }
         Message::CompilerMessage(msg) => {
-            // Check if this is a CGP-related error
-            if is_cgp_diagnostic(&msg.message) {
-                // Add to database for later processing, don't render yet
-                db.add_diagnostic(msg);
-            } else {
-                // Non-CGP error: render immediately using the original rendered field
-                if let Some(rendered) = &msg.message.rendered {
-                    println!("{}", rendered);
-                }
+            // Non-CGP error: render immediately using the original rendered field
+            if let Some(rendered) = &msg.message.rendered {
+                println!("{}", rendered);
             }
         }
         Message::CompilerArtifact(artifact) => {
@@ -27,10 +44,8 @@ pub fn render_message(message: &Message, db: &mut DiagnosticDatabase) {
         Message::BuildScriptExecuted(_) => {
             // Silently skip build script notifications for now
         }
-        Message::BuildFinished(finished) => {
-            if !finished.success {
-                eprintln!("Build failed");
-            }
+        Message::BuildFinished(finished) if !finished.success => {
+            eprintln!("Build failed");
         }
         Message::TextLine(_) => {
             // Suppress text lines as they may contain fragments from Cargo's output
@@ -41,3 +56,52 @@ pub fn render_message(message: &Message, db: &mut DiagnosticDatabase) {
         }
     }
 }
+
+/// Renders a warning in code generated by a cgp macro according to `--macro-warnings`
+fn render_macro_warning(
+    message: &Message,
+    msg: &CompilerMessage,
+    macro_name: String,
+    db: &mut DiagnosticDatabase,
+    options: &CheckOptions,
+) {
+    match (options.macro_warnings, options.format) {
+        (MacroWarningPolicy::Hide, _) => {}
+        (_, OutputFormat::CgpJson | OutputFormat::Json) => {
+            // Machine output: forward cargo's own message unchanged
+            if let Ok(json) = serde_json::to_string(message) {
+                println!("{}", json);
+            }
+        }
+        (MacroWarningPolicy::Group, OutputFormat::Human) => {
+            db.add_macro_warning(&msg.message, macro_name);
+        }
+        (MacroWarningPolicy::Tag, OutputFormat::Human) => {
+            if let Some(rendered) = &msg.message.rendered {
+                println!("{}", tag_rendered(rendered, &macro_name));
+            }
+        }
+    }
+}
+
+/// Returns true if the message is a compiler error that is shown unimproved, because it is
+/// not related to CGP or it is selected by the raw filters
+/// The final "aborting due to N previous errors" summary is not counted as an error
+pub fn is_other_compiler_error(message: &Message, options: &CheckOptions) -> bool {
+    match message {
+        Message::CompilerMessage(msg) => {
+            matches!(
+                msg.message.level,
+                DiagnosticLevel::Error | DiagnosticLevel::Ice
+            ) && !msg.message.message.starts_with("aborting due to")
+                && !is_improved(msg, options)
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if the message is a CGP diagnostic that is not selected by the raw filters
+fn is_improved(msg: &CompilerMessage, options: &CheckOptions) -> bool {
+    is_cgp_diagnostic_with(&msg.message, &options.cgp_patterns)
+        && !is_raw(&options.raw_filters, &msg.message)
+}
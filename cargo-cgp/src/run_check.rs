@@ -1,66 +1,942 @@
-use std::env;
-use std::io::BufReader;
-use std::process::{Command, Stdio};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
 
+use crate::budget::check_budget;
+use crate::cgp_diagnostic::CgpDiagnostic;
+use crate::changed_files::{ChangedFiles, render_unchanged_summary};
+use crate::check_options::{CheckOptions, OutputFormat, TreeFormat};
 use crate::diagnostic_db::DiagnosticDatabase;
-use crate::error_formatting::{is_terminal, render_diagnostic_graphical, render_diagnostic_plain};
-use crate::render::render_message;
-use anyhow::{Context, Result};
+use crate::error_formatting::{
+    format_duplicate_check_warning, format_shadowed_check_trait_warning,
+    merge_duplicate_check_errors, render_diagnostic_graphical, render_diagnostic_narrated,
+    render_diagnostic_plain,
+};
+use crate::explain::format_explain_trailer;
+use crate::json_output::{
+    render_compiler_message_json, render_diagnostic_json, render_exit_summary_json,
+};
+use crate::metrics_file::write_metrics_file;
+use crate::multi_workspace::{manifest_paths, run_workspaces, with_manifest_path};
+use crate::name_resolution::NameResolver;
+use crate::package_selection::checked_source_roots;
+use crate::pager::print_paged;
+use crate::render::{is_other_compiler_error, render_message};
+use crate::run_log::{RunLog, fingerprint, print_raw_notes, raw_notes_hint, run_log_path};
+use crate::run_stats::RunStats;
+use crate::source_snippet::path_remaps_from_env;
+use crate::target_repeats::TargetRepeats;
+use crate::watch::run_watch;
+use crate::workspace_scan::{WorkspaceScan, workspace_source_roots};
+use anyhow::{Context, Result, bail};
 use cargo_metadata::Message;
 
-pub fn run_check() -> Result<()> {
-    // Get any additional arguments to pass through to cargo
-    let args: Vec<String> = env::args().skip(3).collect();
+/// Environment variable that enables debug output about resolution decisions on stderr
+pub const DEBUG_ENV: &str = "CARGO_CGP_DEBUG";
 
-    // Spawn cargo check with JSON output
+pub fn run_check(options: &CheckOptions) -> Result<()> {
+    run_cargo_command("check", options)
+}
+
+/// Runs `cargo test`, passing the output of the test harness through unmodified
+pub fn run_test(options: &CheckOptions) -> Result<()> {
+    run_cargo_command("test", options)
+}
+
+/// Runs a full `cargo build`, which reports the same CGP errors as `cargo check`
+pub fn run_build(options: &CheckOptions) -> Result<()> {
+    run_cargo_command("build", options)
+}
+
+/// Runs a cargo command that builds or documents crates (e.g., `check` or `doc`)
+/// and improves the CGP errors in its output
+/// The command must accept `--message-format=json`
+pub fn run_cargo_command(command: &str, options: &CheckOptions) -> Result<()> {
+    if let Some(fingerprint) = &options.raw_notes {
+        return print_raw_notes(fingerprint, &options.profile(command));
+    }
+
+    if let Some(path) = &options.from_json {
+        return replay_file(path, options);
+    }
+
+    let manifest_paths = manifest_paths(&options.cargo_args)?;
+    let options = &match manifest_paths.as_slice() {
+        [] => options.clone(),
+        // Cargo accepts a single `--manifest-path`, so a glob or repeated flags are replaced
+        [manifest] => CheckOptions {
+            cargo_args: with_manifest_path(&options.cargo_args, manifest),
+            ..options.clone()
+        },
+        _ if options.watch => {
+            bail!("`--watch` cannot be used with several `--manifest-path`s");
+        }
+        _ if options.budget.is_some() => {
+            bail!("`--budget` cannot be used with several `--manifest-path`s");
+        }
+        _ => return run_workspaces(command, options, &manifest_paths),
+    };
+
+    if options.watch {
+        if options.budget.is_some() {
+            bail!("`--budget` cannot be used with `--watch`");
+        }
+        return run_watch(command, options);
+    }
+
+    let (status, stats) =
+        run_cargo_pipeline_with_stats(command, options, &scan_checked_crates(&options.cargo_args))?;
+    if !run_succeeded(status.success(), &stats) {
+        std::process::exit(status.code().filter(|code| *code != 0).unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+/// Returns true if a run succeeded, or with `--budget`, if it failed only because of CGP
+/// errors within the budget
+fn run_succeeded(cargo_succeeded: bool, stats: &RunStats) -> bool {
+    match &stats.budget {
+        Some(report) => {
+            report.passed()
+                && (cargo_succeeded || (stats.cgp_errors > 0 && stats.other_errors == 0))
+        }
+        None => cargo_succeeded,
+    }
+}
+
+/// Runs the cargo command once, prints the improved diagnostics and returns cargo's exit status
+pub fn run_cargo_pipeline(command: &str, options: &CheckOptions) -> Result<ExitStatus> {
+    run_cargo_pipeline_with_scan(command, options, &scan_checked_crates(&options.cargo_args))
+}
+
+/// Runs the cargo command once with an existing scan of the workspace sources
+pub fn run_cargo_pipeline_with_scan(
+    command: &str,
+    options: &CheckOptions,
+    scan: &WorkspaceScan,
+) -> Result<ExitStatus> {
+    run_cargo_pipeline_with_stats(command, options, scan).map(|(status, _)| status)
+}
+
+/// Runs the cargo command once, returning cargo's exit status with the statistics of the run
+pub fn run_cargo_pipeline_with_stats(
+    command: &str,
+    options: &CheckOptions,
+    scan: &WorkspaceScan,
+) -> Result<(ExitStatus, RunStats)> {
+    let started = Instant::now();
+
+    // Spawn the cargo command with JSON output
     let mut child = Command::new("cargo")
-        .arg("check")
+        .arg(command)
         .arg("--message-format=json")
-        .args(&args)
+        .args(&options.cargo_args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped()) // Capture stderr to prevent progress bar interference
         .spawn()
-        .context("Failed to spawn cargo check")?;
+        .with_context(|| format!("Failed to spawn cargo {}", command))?;
 
     // Get stdout handle
     let stdout = child
         .stdout
         .take()
-        .context("Failed to capture stdout from cargo check")?;
+        .with_context(|| format!("Failed to capture stdout from cargo {}", command))?;
+
+    // Collect stderr in the background, so that cargo never blocks on a full pipe
+    // It is only shown if cargo fails without reporting any compiler errors
+    let mut stderr = child
+        .stderr
+        .take()
+        .with_context(|| format!("Failed to capture stderr from cargo {}", command))?;
+    let stderr_reader = std::thread::spawn(move || {
+        let mut content = String::new();
+        let _ = stderr.read_to_string(&mut content);
+        content
+    });
+
+    // Lines that are not JSON are printed by the test harness
+    let mut analysis = analyze_messages(
+        BufReader::new(stdout),
+        scan,
+        options,
+        &options.profile(command),
+        command == "test",
+        || {
+            let _ = child.kill();
+        },
+    )?;
+
+    // Wait for the cargo command to complete
+    let status = child
+        .wait()
+        .with_context(|| format!("Failed to wait for cargo {}", command))?;
+    let cargo_stderr = stderr_reader.join().unwrap_or_default();
+
+    // If cargo failed for reasons unrelated to CGP, say so explicitly,
+    // so that it is clear that cargo-cgp ran and found nothing to improve
+    if !status.success() && analysis.build_succeeded {
+        // The compiler is done, so cargo's own errors (e.g., "test failed") explain the failure
+        eprint!("{}", cargo_stderr);
+    } else if !status.success() && analysis.cgp_errors == 0 {
+        if analysis.other_errors == 0 {
+            eprint!("{}", cargo_stderr);
+        }
+        analysis.push_no_cgp_errors_trailer(options.format);
+    }
+
+    let stats = analysis.finish(options, started)?;
+
+    Ok((status, stats))
+}
+
+/// Improves the CGP errors in cargo JSON messages captured by another process
+/// (`cargo check --message-format=json | cargo cgp analyze -`), read from stdin
+/// or from a file
+/// Exits with a failure status if the messages contain compiler errors
+pub fn run_analyze(options: &CheckOptions) -> Result<()> {
+    let input = match options.cargo_args.as_slice() {
+        [input] => input,
+        [] => bail!("Usage: cargo cgp analyze <-|FILE> [OPTIONS], where `-` reads from stdin"),
+        [_, extra, ..] => bail!("Unexpected argument for `cargo cgp analyze`: {}", extra),
+    };
+
+    if input == "-" {
+        replay_messages(BufReader::new(std::io::stdin()), options)
+    } else {
+        replay_file(Path::new(input), options)
+    }
+}
+
+/// Replays a saved file of cargo JSON messages (e.g., attached to a bug report)
+fn replay_file(path: &Path, options: &CheckOptions) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    replay_messages(BufReader::new(file), options)
+}
+
+/// Renders the improved diagnostics for cargo JSON messages that were saved or piped in,
+/// without running cargo
+fn replay_messages(reader: impl BufRead, options: &CheckOptions) -> Result<()> {
+    if options.watch {
+        bail!("`--watch` cannot be used with saved cargo messages, since cargo is not run");
+    }
+
+    let started = Instant::now();
+    let scan = scan_workspace();
+
+    // The producer is not ours to stop, so first-error mode only limits the output
+    // Lines that are not JSON (e.g., from a test harness) are passed through
+    let profile = options.profile("check");
+    let mut analysis = analyze_messages(reader, &scan, options, &profile, true, || {})?;
 
-    // Parse JSON messages from stdout
-    let reader = BufReader::new(stdout);
+    let failed = analysis.cgp_errors > 0 || analysis.other_errors > 0;
+    if failed && analysis.cgp_errors == 0 {
+        analysis.push_no_cgp_errors_trailer(options.format);
+    }
+
+    let stats = analysis.finish(options, started)?;
+
+    if !run_succeeded(!failed, &stats) {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Scans the workspace sources for the component index and for problems that the compiler
+/// does not report directly
+/// Scanning is best-effort: if cargo metadata is unavailable, the index is empty
+pub fn scan_workspace() -> WorkspaceScan {
+    match workspace_source_roots() {
+        Ok((workspace_root, source_roots)) => {
+            WorkspaceScan::scan_roots(&source_roots, &workspace_root)
+        }
+        Err(_) => WorkspaceScan::new(),
+    }
+}
+
+/// Scans the sources of the workspace members that a cargo command with these arguments
+/// checks, falling back to all workspace members
+pub fn scan_checked_crates(cargo_args: &[String]) -> WorkspaceScan {
+    match checked_source_roots(cargo_args) {
+        Ok((workspace_root, source_roots)) => {
+            WorkspaceScan::scan_roots(&source_roots, &workspace_root)
+        }
+        Err(_) => scan_workspace(),
+    }
+}
+
+/// Returns the workspace root, or the current directory if cargo metadata is unavailable
+fn workspace_root() -> PathBuf {
+    workspace_source_roots()
+        .map(|(workspace_root, _)| workspace_root)
+        .or_else(|_| std::env::current_dir())
+        .unwrap_or_else(|_| PathBuf::from("."))
+}
+
+/// The improved output for a stream of cargo messages, before it is printed
+struct Analysis {
+    db: DiagnosticDatabase,
+    output: String,
+    cgp_errors: usize,
+    other_errors: usize,
+    warnings: usize,
+    /// The rendered errors with the compiler diagnostics behind them, for `--raw-notes`
+    run_log: RunLog,
+    /// The cargo profile of the run, which the run log and statistics are kept for
+    profile: String,
+    /// Whether compilation succeeded, in which case a failure comes from the built
+    /// programs (e.g., failing tests) rather than from the compiler
+    build_succeeded: bool,
+}
+
+/// Renders the improved diagnostics for a stream of cargo JSON messages
+/// `stop` is called once a root cause is known in first-error mode
+fn analyze_messages(
+    reader: impl BufRead,
+    scan: &WorkspaceScan,
+    options: &CheckOptions,
+    profile: &str,
+    forward_text_lines: bool,
+    mut stop: impl FnMut(),
+) -> Result<Analysis> {
+    // Parse JSON messages from the reader
     let messages = Message::parse_stream(reader);
 
+    // With `--since`, only the errors of the current change are rendered in full
+    // The changed files are listed first, so that a bad revision fails before cargo's output
+    let changed_files = match &options.since {
+        Some(rev) => Some(ChangedFiles::since(rev, &workspace_root())?),
+        None => None,
+    };
+
     // Create database to collect CGP diagnostics
     let mut db = DiagnosticDatabase::new();
+    db.set_name_resolver(
+        NameResolver::from_scan(scan, options.resolution)
+            .with_naming(options.naming.clone())
+            .with_path_style(options.path_style),
+    );
+    db.set_tree_depth(options.tree_depth);
+    db.set_narrate_chains(options.narrates());
+    db.set_keep_redundant_hops(options.no_dedup);
+    db.set_component_filters(options.components.clone());
+    if options.dump_grouping.is_some() {
+        db.enable_grouping_trace();
+    }
+
+    // Count compiler errors that are not related to CGP, which are shown as-is
+    let mut other_errors = 0;
+    let mut build_succeeded = false;
+
+    // The lib and derive crates of a package can report the same diagnostic
+    let mut repeats = TargetRepeats::new();
 
     // Process and render each message
     for message in messages {
         let message = message.context("Failed to parse JSON message from cargo")?;
-        render_message(&message, &mut db);
+
+        match &message {
+            Message::TextLine(line) if forward_text_lines => println!("{}", line),
+            Message::BuildFinished(finished) => build_succeeded = finished.success,
+            _ => {}
+        }
+
+        if let Message::CompilerMessage(msg) = &message
+            && repeats.is_repeat(msg)
+        {
+            continue;
+        }
+
+        render_message(&message, &mut db, options);
+
+        if is_other_compiler_error(&message, options) {
+            other_errors += 1;
+        }
+
+        // In first-error mode, stop cargo once a root cause is known
+        // to save the rest of a long workspace check
+        if options.first_error && db.has_root_cause() {
+            stop();
+            break;
+        }
+    }
+
+    if std::env::var_os(DEBUG_ENV).is_some() {
+        eprint!("{}", db.symbol_cache().render_debug());
+        eprint!("{}", db.long_types().render_debug());
     }
 
+    if let (Some(path), Some(trace)) = (&options.dump_grouping, db.grouping_trace()) {
+        trace.write(path)?;
+    }
+
+    // Remapping rules from the environment come first, so that CLI rules take precedence
+    let mut path_remaps = path_remaps_from_env()?;
+    path_remaps.extend(options.path_remaps.iter().cloned());
+
     // After all messages are processed, render all CGP errors
-    // Use colorful output if in terminal, plain text otherwise
-    let use_color = is_terminal();
-    let cgp_diagnostics = db.render_cgp_diagnostics();
+    // In first-error mode, only the first root cause is shown if one was found
+    let first_root_cause = if options.first_error {
+        db.render_first_root_cause()
+    } else {
+        None
+    };
+    let show_only_first = first_root_cause.is_some();
+
+    let cgp_diagnostics = match first_root_cause {
+        Some(diagnostic) => vec![diagnostic],
+        None => db.render_cgp_diagnostics(),
+    };
+
+    // The same check in several files produces the same error several times
+    let duplicate_checks = scan.duplicate_checks();
+    let cgp_diagnostics = merge_duplicate_check_errors(cgp_diagnostics, &duplicate_checks);
 
+    // The errors hidden by `--context` and `--component` still fail the run
+    let other_components = db.hidden_by_component_filters();
+    let cgp_errors = cgp_diagnostics.len() + other_components;
+    let (cgp_diagnostics, other_contexts): (Vec<_>, Vec<_>) = cgp_diagnostics
+        .into_iter()
+        .partition(|diagnostic| options.shows_context(diagnostic.context_type.as_deref()));
+
+    // Machine output always contains every diagnostic of the selected contexts
+    let (cgp_diagnostics, unchanged): (Vec<_>, Vec<_>) = match (&changed_files, options.format) {
+        (Some(changed), OutputFormat::Human) => cgp_diagnostics
+            .into_iter()
+            .partition(|diagnostic| changed.touches(diagnostic, scan)),
+        _ => (cgp_diagnostics, Vec::new()),
+    };
+
+    // The improved diagnostics are collected first, so that human-readable output
+    // can be shown through a pager when it does not fit on one screen
+    let mut output = String::new();
+    let explain_trailer = format_explain_trailer(
+        cgp_diagnostics
+            .iter()
+            .filter_map(|diagnostic| diagnostic.code.as_deref()),
+    );
+
+    // First-time users are told why the output differs from `cargo check`
+    if options.format == OutputFormat::Human && !options.no_banner && !cgp_diagnostics.is_empty() {
+        output.push_str(&banner(db.diagnostics_seen(), cgp_errors));
+        output.push('\n');
+    }
+
+    // Paths are normalized before the fingerprints are taken, so that they are stable too
+    let normalized_prefixes = if options.normalize_output {
+        normalized_path_prefixes()
+    } else {
+        Vec::new()
+    };
+    let mut cgp_diagnostics = cgp_diagnostics;
+    for diagnostic in &mut cgp_diagnostics {
+        diagnostic.remap_source_paths(&path_remaps);
+        diagnostic.normalize_paths(&normalized_prefixes);
+    }
+
+    let run_log = RunLog::from_diagnostics(&cgp_diagnostics);
     for diagnostic in cgp_diagnostics {
-        let rendered = if use_color {
-            render_diagnostic_graphical(&diagnostic)
+        if options.format == OutputFormat::Human {
+            // The header ends with the fingerprint taken for `--raw-notes`
+            let mut headed = diagnostic.clone();
+            headed.message = format!("{} [{}]", diagnostic.message, fingerprint(&diagnostic));
+            output.push_str(&render_diagnostic(&headed, options));
+            output.push('\n');
+            output.push_str(&render_tree_export(&diagnostic, options.tree_format));
         } else {
-            render_diagnostic_plain(&diagnostic)
-        };
-        println!("{}", rendered);
+            output.push_str(&render_diagnostic(&diagnostic, options));
+            output.push('\n');
+        }
+        if options.verbose && options.format == OutputFormat::Human {
+            output.push_str(&render_hidden_diagnostics(
+                "merged into the error above",
+                &diagnostic.merged,
+                options.color.enabled(),
+            ));
+        }
+    }
+
+    if options.verbose && options.format == OutputFormat::Human {
+        output.push_str(&render_hidden_diagnostics(
+            "suppressed as redundant",
+            &db.suppressed_summaries(),
+            options.color.enabled(),
+        ));
+    }
+
+    if let (Some(rev), false) = (&options.since, unchanged.is_empty()) {
+        output.push_str(&render_unchanged_summary(&unchanged, rev));
+        output.push('\n');
+    }
+
+    if options.format == OutputFormat::Human {
+        for (hidden, filter) in [
+            (other_contexts.len(), "context"),
+            (other_components, "component"),
+        ] {
+            if hidden > 0 {
+                output.push_str(&hidden_errors_note(hidden, filter));
+                output.push('\n');
+            }
+        }
+
+        if let Some(note) = repeats.render_note() {
+            output.push_str(&note);
+            output.push('\n');
+        }
+    }
+
+    let mut warnings = 0;
+    if !show_only_first {
+        for blocks in scan.shadowed_check_traits() {
+            if let Some(diagnostic) = format_shadowed_check_trait_warning(&blocks) {
+                output.push_str(&render_diagnostic(&diagnostic, options));
+                output.push('\n');
+                warnings += 1;
+            }
+        }
+
+        for duplicate in &duplicate_checks {
+            if let Some(diagnostic) = format_duplicate_check_warning(duplicate) {
+                output.push_str(&render_diagnostic(&diagnostic, options));
+                output.push('\n');
+                warnings += 1;
+            }
+        }
     }
 
-    // Wait for cargo check to complete
-    let status = child.wait().context("Failed to wait for cargo check")?;
+    if let (OutputFormat::Human, Some(summary)) = (options.format, db.macro_warnings().render()) {
+        output.push_str(&summary);
+        output.push('\n');
+    }
 
-    if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+    if let (OutputFormat::Human, Some(trailer)) = (options.format, explain_trailer) {
+        output.push_str(&trailer);
+        output.push('\n');
     }
 
+    Ok(Analysis {
+        db,
+        output,
+        cgp_errors,
+        other_errors,
+        warnings,
+        run_log,
+        profile: profile.to_string(),
+        build_succeeded,
+    })
+}
+
+/// The machine-specific path prefixes replaced with `--normalize-output`: paths in the
+/// workspace become relative, and paths of dependencies start with `$CARGO_HOME`
+fn normalized_path_prefixes() -> Vec<(String, String)> {
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo")));
+
+    let mut prefixes = vec![(format!("{}/", workspace_root().display()), String::new())];
+    if let Some(cargo_home) = cargo_home {
+        prefixes.push((
+            format!("{}/", cargo_home.display()),
+            "$CARGO_HOME/".to_string(),
+        ));
+    }
+    prefixes
+}
+
+/// Explains how the improved errors relate to the compiler errors they replace
+fn banner(compiler_errors: usize, cgp_errors: usize) -> String {
+    format!(
+        "note: cargo-cgp reinterpreted {} as {} (disable this note with `--no-banner`)",
+        match compiler_errors {
+            1 => "1 compiler error".to_string(),
+            n => format!("{} compiler errors", n),
+        },
+        match cgp_errors {
+            1 => "1 CGP wiring problem".to_string(),
+            n => format!("{} CGP wiring problems", n),
+        }
+    )
+}
+
+/// Tells how to see rustc's notes for an error (`--raw-notes`), dimmed if the output
+/// is colored
+fn render_raw_notes_hint(profile: &str, color: bool) -> String {
+    let hint = raw_notes_hint(profile);
+    if color {
+        format!("\x1b[2m{}\x1b[0m\n\n", hint)
+    } else {
+        format!("{}\n\n", hint)
+    }
+}
+
+/// Lists compiler diagnostics that are not shown on their own (`--verbose`), dimmed if
+/// the output is colored
+fn render_hidden_diagnostics(note: &str, summaries: &[String], color: bool) -> String {
+    if summaries.is_empty() {
+        return String::new();
+    }
+
+    let mut lines = vec![match summaries.len() {
+        1 => format!("note: 1 compiler diagnostic {}:", note),
+        n => format!("note: {} compiler diagnostics {}:", n, note),
+    }];
+    lines.extend(summaries.iter().map(|summary| format!("    {}", summary)));
+
+    let text = lines.join("\n");
+    if color {
+        format!("\x1b[2m{}\x1b[0m\n\n", text)
+    } else {
+        format!("{}\n\n", text)
+    }
+}
+
+/// Counts the errors hidden by a filter (`--context` or `--component`)
+fn hidden_errors_note(hidden: usize, filter: &str) -> String {
+    match hidden {
+        1 => format!(
+            "note: 1 CGP error about another {} is hidden by `--{}`",
+            filter, filter
+        ),
+        n => format!(
+            "note: {} CGP errors about other {}s are hidden by `--{}`",
+            n, filter, filter
+        ),
+    }
+}
+
+impl Analysis {
+    /// Explains a failure in which no CGP errors were found
+    fn push_no_cgp_errors_trailer(&mut self, format: OutputFormat) {
+        match format {
+            OutputFormat::Human => {
+                self.output
+                    .push_str(&no_cgp_errors_trailer(self.other_errors));
+            }
+            OutputFormat::CgpJson | OutputFormat::Json => {
+                self.output
+                    .push_str(&render_exit_summary_json(self.other_errors));
+            }
+        }
+        self.output.push('\n');
+    }
+
+    /// Prints the output and writes the run statistics if requested
+    fn finish(mut self, options: &CheckOptions, started: Instant) -> Result<RunStats> {
+        // Normalized output has no timings, so that it can be compared between runs
+        let elapsed = if options.normalize_output {
+            Duration::ZERO
+        } else {
+            started.elapsed()
+        };
+        let mut stats = RunStats::collect(
+            &self.db,
+            &self.profile,
+            self.other_errors,
+            self.warnings,
+            elapsed,
+        );
+
+        if let Some(path) = &options.budget {
+            let report = check_budget(
+                path,
+                options.update_budget,
+                &self.run_log.entries,
+                &stats.by_kind,
+            )?;
+            // Machine output keeps stdout for the diagnostics
+            match options.format {
+                OutputFormat::Human => self.output.push_str(&report.render(path)),
+                OutputFormat::CgpJson | OutputFormat::Json => eprint!("{}", report.render(path)),
+            }
+            stats.budget = Some(report);
+        }
+
+        // The hint for `--raw-notes` is given once, rather than under each error
+        if options.format == OutputFormat::Human && !self.run_log.entries.is_empty() {
+            self.output.push_str(&render_raw_notes_hint(
+                &self.profile,
+                options.color.enabled(),
+            ));
+        }
+
+        // The footer summarizes the errors of a failed build, after everything else
+        if options.format == OutputFormat::Human && (stats.cgp_errors > 0 || stats.other_errors > 0)
+        {
+            self.output.push_str(&stats.render_footer());
+            self.output.push('\n');
+        }
+
+        match options.format {
+            OutputFormat::Human => print_paged(&self.output, options.pager),
+            // Machine output is never paged
+            OutputFormat::CgpJson | OutputFormat::Json => print!("{}", self.output),
+        }
+
+        write_stats(&stats, options)?;
+
+        // Failing to save the log must not hide the errors of the run
+        if let Err(error) = self.run_log.write(&run_log_path(&self.profile)) {
+            eprintln!("warning: {:#}", error);
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Writes the statistics of a run to the `--stats-json` and `--metrics-file` targets
+pub fn write_stats(stats: &RunStats, options: &CheckOptions) -> Result<()> {
+    if let Some(target) = &options.stats_json {
+        stats.write(target)?;
+    }
+    if let Some(path) = &options.metrics_file {
+        write_metrics_file(stats, path)?;
+    }
     Ok(())
 }
+
+/// Renders the dependency chain of a diagnostic as a graph (`--tree-format dot|mermaid`)
+fn render_tree_export(diagnostic: &CgpDiagnostic, tree_format: TreeFormat) -> String {
+    let Some(tree) = &diagnostic.dependency_tree else {
+        return String::new();
+    };
+
+    let (name, graph) = match tree_format {
+        TreeFormat::Text => return String::new(),
+        TreeFormat::Dot => ("a Graphviz DOT graph", tree.render_dot()),
+        TreeFormat::Mermaid => ("a Mermaid flowchart", tree.render_mermaid()),
+    };
+    format!(
+        "note: the dependency chain of the error above, as {}:\n{}\n\n",
+        name, graph
+    )
+}
+
+/// Renders a diagnostic in the requested output format
+fn render_diagnostic(diagnostic: &CgpDiagnostic, options: &CheckOptions) -> String {
+    match options.format {
+        OutputFormat::Human => render_diagnostic_text(diagnostic, options),
+        OutputFormat::CgpJson => render_diagnostic_json(diagnostic),
+        // The improved text replaces rustc's rendering of the compiler message
+        OutputFormat::Json => {
+            render_compiler_message_json(diagnostic, &render_diagnostic_text(diagnostic, options))
+        }
+    }
+}
+
+/// Renders a diagnostic as text, narrated if requested, or else colorful if enabled by
+/// the color mode
+fn render_diagnostic_text(diagnostic: &CgpDiagnostic, options: &CheckOptions) -> String {
+    if options.narrates() {
+        render_diagnostic_narrated(diagnostic)
+    } else if options.color.enabled() {
+        render_diagnostic_graphical(diagnostic)
+    } else {
+        render_diagnostic_plain(diagnostic)
+    }
+}
+
+/// Explains a failed build in which no CGP errors were found
+fn no_cgp_errors_trailer(other_errors: usize) -> String {
+    match other_errors {
+        0 => "note: no CGP-related errors detected; cargo failed without reporting compiler errors"
+            .to_string(),
+        1 => "note: no CGP-related errors detected; 1 other compiler error shown above".to_string(),
+        n => format!(
+            "note: no CGP-related errors detected; {} other compiler errors shown above",
+            n
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_cgp_errors_trailer() {
+        assert_eq!(
+            no_cgp_errors_trailer(3),
+            "note: no CGP-related errors detected; 3 other compiler errors shown above"
+        );
+        assert!(no_cgp_errors_trailer(1).contains("1 other compiler error shown"));
+        assert!(no_cgp_errors_trailer(0).contains("without reporting compiler errors"));
+    }
+
+    #[test]
+    fn test_analyze_messages() {
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let reader = BufReader::new(File::open(json_path).unwrap());
+
+        let analysis = analyze_messages(
+            reader,
+            &WorkspaceScan::new(),
+            &CheckOptions::default(),
+            "dev",
+            false,
+            || {},
+        )
+        .unwrap();
+
+        assert_eq!(analysis.cgp_errors, 1);
+        assert_eq!(analysis.other_errors, 0);
+        assert!(analysis.output.contains("missing field `heig�t`"));
+        assert!(analysis.output.contains("cargo cgp explain CGP0001"));
+    }
+
+    #[test]
+    fn test_run_succeeded_with_budget() {
+        use crate::budget::{BudgetReport, NewError};
+
+        let stats =
+            |cgp_errors: usize, other_errors: usize, budget: Option<BudgetReport>| RunStats {
+                cgp_errors,
+                other_errors,
+                budget,
+                ..RunStats::default()
+            };
+        let exceeded = BudgetReport {
+            new_errors: vec![NewError {
+                fingerprint: "3b198120".to_string(),
+                message: "field `height` has type `u32` but `f64` is required".to_string(),
+            }],
+            ..BudgetReport::default()
+        };
+
+        assert!(!run_succeeded(false, &stats(1, 0, None)));
+        // CGP errors within the budget do not fail the run, other compiler errors still do
+        assert!(run_succeeded(
+            false,
+            &stats(1, 0, Some(BudgetReport::default()))
+        ));
+        assert!(!run_succeeded(
+            false,
+            &stats(1, 1, Some(BudgetReport::default()))
+        ));
+        assert!(!run_succeeded(
+            false,
+            &stats(0, 0, Some(BudgetReport::default()))
+        ));
+        assert!(!run_succeeded(false, &stats(1, 0, Some(exceeded))));
+    }
+
+    #[test]
+    fn test_banner() {
+        assert_eq!(
+            banner(3, 1),
+            "note: cargo-cgp reinterpreted 3 compiler errors as 1 CGP wiring problem (disable this note with `--no-banner`)"
+        );
+    }
+
+    #[test]
+    fn test_hidden_errors_note() {
+        assert_eq!(
+            hidden_errors_note(2, "component"),
+            "note: 2 CGP errors about other components are hidden by `--component`"
+        );
+    }
+
+    #[test]
+    fn test_analyze_messages_repeated_by_another_target() {
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+
+        // The derive crate of the package reports every diagnostic again
+        let mut stream = String::new();
+        for line in std::fs::read_to_string(json_path).unwrap().lines() {
+            stream.push_str(line);
+            stream.push('\n');
+            let mut message: serde_json::Value = serde_json::from_str(line).unwrap();
+            if message["reason"] == "compiler-message" {
+                message["target"]["name"] = "base_area_derive".into();
+                stream.push_str(&message.to_string());
+                stream.push('\n');
+            }
+        }
+
+        let analysis = analyze_messages(
+            stream.as_bytes(),
+            &WorkspaceScan::new(),
+            &CheckOptions::default(),
+            "dev",
+            false,
+            || {},
+        )
+        .unwrap();
+
+        assert_eq!(analysis.cgp_errors, 1);
+        assert_eq!(analysis.output.matches("missing field `heig�t`").count(), 1);
+        assert!(analysis.output.contains(
+            "reported again by another target of `cgp-error-messages-example` are shown once"
+        ));
+    }
+
+    #[test]
+    fn test_analyze_messages_for_context() {
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let options = CheckOptions {
+            contexts: vec!["Circle".to_string()],
+            ..CheckOptions::default()
+        };
+
+        let reader = BufReader::new(File::open(json_path).unwrap());
+        let analysis =
+            analyze_messages(reader, &WorkspaceScan::new(), &options, "dev", false, || {}).unwrap();
+
+        assert_eq!(analysis.cgp_errors, 1);
+        assert!(!analysis.output.contains("missing field"));
+        assert!(
+            analysis
+                .output
+                .contains("note: 1 CGP error about another context is hidden by `--context`")
+        );
+    }
+
+    #[test]
+    fn test_analyze_messages_verbose() {
+        let json_path = format!(
+            "{}/../examples/src/scaled_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let options = CheckOptions {
+            verbose: true,
+            ..CheckOptions::default()
+        };
+
+        let reader = BufReader::new(File::open(json_path).unwrap());
+        let analysis =
+            analyze_messages(reader, &WorkspaceScan::new(), &options, "dev", false, || {}).unwrap();
+
+        assert!(analysis.output.contains(
+            "note: 1 compiler diagnostic merged into the error above:\n    \
+             examples/src/scaled_area.rs:58:9: the trait bound `Rectangle: \
+             cgp::prelude::CanUseComponent<AreaCalculatorComponent>` is not satisfied\n"
+        ));
+
+        // The merged diagnostic is in the run log too, for `--raw-notes`
+        let entry = &analysis.run_log.entries[0];
+        assert_eq!(entry.raw_notes.len(), 2);
+        assert!(
+            analysis
+                .output
+                .contains(&format!(" [{}]", entry.fingerprint))
+        );
+
+        assert_eq!(render_hidden_diagnostics("suppressed", &[], true), "");
+        assert_eq!(
+            render_hidden_diagnostics("suppressed", &["src/lib.rs:3:1: a".to_string()], true),
+            "\x1b[2mnote: 1 compiler diagnostic suppressed:\n    src/lib.rs:3:1: a\x1b[0m\n\n"
+        );
+    }
+}
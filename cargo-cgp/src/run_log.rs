@@ -0,0 +1,193 @@
+/// Module for the log of the last run, from which `--raw-notes` reprints rustc's output
+/// The header of each improved error in the human-readable output ends with a
+/// fingerprint. The compiler diagnostics behind the errors are saved in the target directory, so that the
+/// original notes of one error can be shown later without running cargo again.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use cargo_metadata::MetadataCommand;
+use serde::{Deserialize, Serialize};
+
+use crate::cgp_diagnostic::CgpDiagnostic;
+
+/// Number of hex digits of a fingerprint shown in the output
+const FINGERPRINT_LEN: usize = 8;
+
+/// The improved errors of the last run, with the compiler diagnostics behind each of them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunLog {
+    pub entries: Vec<RunLogEntry>,
+}
+
+/// One improved error in the run log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunLogEntry {
+    pub fingerprint: String,
+    /// The improved error message
+    pub message: String,
+    /// rustc's rendering of each compiler diagnostic behind the error
+    pub raw_notes: Vec<String>,
+}
+
+impl RunLog {
+    pub fn from_diagnostics(diagnostics: &[CgpDiagnostic]) -> Self {
+        RunLog {
+            entries: diagnostics
+                .iter()
+                .map(|diagnostic| RunLogEntry {
+                    fingerprint: fingerprint(diagnostic),
+                    message: diagnostic.message.clone(),
+                    raw_notes: diagnostic.raw_notes.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read the log of the last run at {}",
+                path.display()
+            )
+        })?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid run log at {}", path.display()))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Finds the entry whose fingerprint starts with `prefix`
+    pub fn find(&self, prefix: &str) -> Result<&RunLogEntry> {
+        let matches: Vec<&RunLogEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.fingerprint.starts_with(prefix))
+            .collect();
+
+        match matches.as_slice() {
+            [entry] => Ok(entry),
+            [] => bail!("No error with the fingerprint `{}` in the last run", prefix),
+            _ => bail!(
+                "The fingerprint `{}` matches {} errors of the last run",
+                prefix,
+                matches.len()
+            ),
+        }
+    }
+}
+
+/// A stable fingerprint of an improved error, from its first location and its message
+/// FNV-1a is used since it does not change between Rust versions, unlike `DefaultHasher`
+pub fn fingerprint(diagnostic: &CgpDiagnostic) -> String {
+    let location = diagnostic
+        .spans
+        .first()
+        .map(|span| format!("{}:{}:{}", span.file, span.line_start, span.column_start))
+        .unwrap_or_default();
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in format!("{}\n{}", location, diagnostic.message).bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    format!("{:016x}", hash)[..FINGERPRINT_LEN].to_string()
+}
+
+/// The line after the improved errors that tells how to see rustc's notes
+/// The command selects the profile of the run, since each profile has its own run log
+pub fn raw_notes_hint(profile: &str) -> String {
+    let profile_args = match profile {
+        "dev" => String::new(),
+        "release" => "--release ".to_string(),
+        _ => format!("--profile {} ", profile),
+    };
+    format!(
+        "note: run `cargo cgp check {}--raw-notes <fingerprint>` to see the notes reported by \
+         rustc for the error with that fingerprint in its header",
+        profile_args
+    )
+}
+
+/// Returns the path of the run log of a cargo profile in the target directory
+/// Runs with different profiles may report different errors, so they do not share a log
+pub fn run_log_path(profile: &str) -> PathBuf {
+    let target_dir = match std::env::var_os("CARGO_TARGET_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => MetadataCommand::new()
+            .no_deps()
+            .exec()
+            .map(|metadata| metadata.target_directory.into_std_path_buf())
+            .unwrap_or_else(|_| PathBuf::from("target")),
+    };
+    target_dir.join("cgp").join(profile).join("last-run.json")
+}
+
+/// Prints rustc's notes for one error of the last run with a profile
+/// (`--raw-notes <fingerprint>`)
+pub fn print_raw_notes(prefix: &str, profile: &str) -> Result<()> {
+    let log = RunLog::read(&run_log_path(profile))?;
+    let entry = log.find(prefix)?;
+
+    for notes in &entry.raw_notes {
+        println!("{}", notes.trim_end());
+        println!();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cgp_diagnostic::SpanLocation;
+
+    fn diagnostic(message: &str, line: usize) -> CgpDiagnostic {
+        let span = SpanLocation {
+            file: "src/lib.rs".to_string(),
+            line_start: line,
+            column_start: 9,
+            line_end: line,
+            column_end: 20,
+            label: String::new(),
+            text: Vec::new(),
+            source_path: None,
+        };
+        let mut diagnostic = CgpDiagnostic::new(message.to_string(), vec![span]);
+        diagnostic.raw_notes = vec![format!("error[E0277]: {}\n  = note: ...\n", message)];
+        diagnostic
+    }
+
+    #[test]
+    fn test_run_log() {
+        let first = diagnostic("missing field `height`", 41);
+        let second = diagnostic("missing field `height`", 42);
+        assert_eq!(fingerprint(&first), fingerprint(&first.clone()));
+        assert_ne!(fingerprint(&first), fingerprint(&second));
+        assert_eq!(fingerprint(&first).len(), FINGERPRINT_LEN);
+        assert!(raw_notes_hint("dev").contains("`cargo cgp check --raw-notes <fingerprint>`"));
+        assert!(raw_notes_hint("release").contains("`cargo cgp check --release --raw-notes"));
+        assert!(raw_notes_hint("ci").contains("`cargo cgp check --profile ci --raw-notes"));
+
+        let log = RunLog::from_diagnostics(&[first.clone(), second]);
+        let path = std::env::temp_dir()
+            .join(format!("cargo-cgp-run-log-{}", std::process::id()))
+            .join("last-run.json");
+        log.write(&path).unwrap();
+        let log = RunLog::read(&path).unwrap();
+
+        let entry = log.find(&fingerprint(&first)).unwrap();
+        assert_eq!(entry.raw_notes, first.raw_notes);
+        assert!(log.find("").is_err());
+        assert!(log.find("zzzz").is_err());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,232 @@
+/// Module for the `--stats-json` summary of a run
+/// The summary is a single JSON document, separate from the diagnostics stream, meant for
+/// dashboards that track the health of the CGP wiring over time
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::budget::BudgetReport;
+use crate::cgp_patterns::extract_unsatisfied_bound;
+use crate::check_options::StatsTarget;
+use crate::diagnostic_db::{DiagnosticDatabase, DiagnosticEntry, ErrorKind};
+
+/// Counts of the CGP errors found in a run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RunStats {
+    /// Always "cgp-run-stats"
+    pub reason: &'static str,
+    /// The cargo profile of the run (e.g., "dev" or "release")
+    /// Runs with different profiles may see different wiring, so they are not comparable
+    pub profile: String,
+    /// The number of CGP errors, after merging related diagnostics
+    pub cgp_errors: usize,
+    /// The number of other compiler errors reported by cargo
+    pub other_errors: usize,
+    /// The number of warnings from the workspace scan (e.g., shadowed check traits)
+    pub warnings: usize,
+    /// The number of compiler diagnostics merged into the CGP errors
+    pub merged: usize,
+    /// The number of CGP errors suppressed as redundant
+    pub suppressed: usize,
+    /// CGP errors by kind (e.g., "missing-field")
+    pub by_kind: BTreeMap<String, usize>,
+    /// CGP errors by the crate they were reported in
+    pub by_crate: BTreeMap<String, usize>,
+    /// CGP errors by the context type that failed to use a component
+    pub by_context: BTreeMap<String, usize>,
+    /// Wall-clock time of the run in milliseconds
+    pub elapsed_ms: u128,
+    /// How the errors compare with the budget file, with `--budget`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetReport>,
+}
+
+impl RunStats {
+    /// Collects the counts from the active entries of the database
+    pub fn collect(
+        db: &DiagnosticDatabase,
+        profile: &str,
+        other_errors: usize,
+        warnings: usize,
+        elapsed: Duration,
+    ) -> Self {
+        let mut stats = RunStats {
+            reason: "cgp-run-stats",
+            profile: profile.to_string(),
+            other_errors,
+            warnings,
+            merged: db.merged_count(),
+            suppressed: db.suppressed_count(),
+            elapsed_ms: elapsed.as_millis(),
+            ..RunStats::default()
+        };
+
+        for entry in db.get_active_entries() {
+            stats.cgp_errors += 1;
+            *stats
+                .by_kind
+                .entry(entry.kind().as_str().to_string())
+                .or_default() += 1;
+            *stats.by_crate.entry(entry.target.name.clone()).or_default() += 1;
+
+            if let Some(context) = entry_context(entry) {
+                *stats.by_context.entry(context).or_default() += 1;
+            }
+        }
+
+        stats
+    }
+
+    /// Adds the counts of another run (e.g., of another workspace) to these
+    pub fn merge(&mut self, other: &RunStats) {
+        self.cgp_errors += other.cgp_errors;
+        self.other_errors += other.other_errors;
+        self.warnings += other.warnings;
+        self.merged += other.merged;
+        self.suppressed += other.suppressed;
+        for (counts, other_counts) in [
+            (&mut self.by_kind, &other.by_kind),
+            (&mut self.by_crate, &other.by_crate),
+            (&mut self.by_context, &other.by_context),
+        ] {
+            for (name, count) in other_counts {
+                *counts.entry(name.clone()).or_default() += count;
+            }
+        }
+        self.elapsed_ms += other.elapsed_ms;
+    }
+
+    /// Renders the summary footer of the human-readable output, e.g.
+    /// "summary: 2 CGP errors (1 missing field, 1 unsatisfied provider), 3 diagnostics merged"
+    pub fn render_footer(&self) -> String {
+        format!("summary: {}", self.render_counts())
+    }
+
+    /// Renders the counts of the summary footer, without the "summary: " prefix
+    pub fn render_counts(&self) -> String {
+        let mut kinds: Vec<(ErrorKind, usize)> = Vec::new();
+        for (name, count) in &self.by_kind {
+            if let Some(kind) = ALL_KINDS.iter().find(|kind| kind.as_str() == name) {
+                kinds.push((*kind, *count));
+            }
+        }
+        kinds.sort();
+
+        let mut parts = vec![plural(self.cgp_errors, "CGP error", "CGP errors")];
+        if !kinds.is_empty() {
+            let kinds: Vec<String> = kinds
+                .iter()
+                .map(|(kind, count)| format!("{} {}", count, kind.label()))
+                .collect();
+            parts[0].push_str(&format!(" ({})", kinds.join(", ")));
+        }
+        if self.merged > 0 {
+            parts.push(plural(
+                self.merged,
+                "diagnostic merged",
+                "diagnostics merged",
+            ));
+        }
+        if self.suppressed > 0 {
+            parts.push(format!("{} suppressed", self.suppressed));
+        }
+        if self.other_errors > 0 {
+            parts.push(plural(
+                self.other_errors,
+                "other compiler error",
+                "other compiler errors",
+            ));
+        }
+
+        parts.join(", ")
+    }
+
+    /// Renders the statistics as a single line of JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Writes the statistics to stdout or to a file
+    pub fn write(&self, target: &StatsTarget) -> Result<()> {
+        match target {
+            StatsTarget::Stdout => println!("{}", self.to_json()),
+            StatsTarget::File(path) => std::fs::write(path, self.to_json() + "\n")
+                .with_context(|| format!("Failed to write `{}`", path.display()))?,
+        }
+        Ok(())
+    }
+}
+
+/// Every error kind, in the order of the summary footer
+pub const ALL_KINDS: [ErrorKind; 10] = [
+    ErrorKind::MissingField,
+    ErrorKind::FieldTypeMismatch,
+    ErrorKind::MissingHasField,
+    ErrorKind::UnsizedType,
+    ErrorKind::MissingDelegation,
+    ErrorKind::NotAProvider,
+    ErrorKind::RequirementOverflow,
+    ErrorKind::AutoTrait,
+    ErrorKind::UnsatisfiedProvider,
+    ErrorKind::UnsatisfiedBound,
+];
+
+/// Formats a count with the singular or plural form of a noun
+fn plural(count: usize, singular: &str, plural: &str) -> String {
+    match count {
+        1 => format!("1 {}", singular),
+        n => format!("{} {}", n, plural),
+    }
+}
+
+/// Returns the context type of an entry, from the missing field or the unsatisfied bound
+fn entry_context(entry: &DiagnosticEntry) -> Option<String> {
+    if let Some(field_info) = &entry.field_info {
+        return Some(field_info.target_type.clone());
+    }
+
+    let bound = extract_unsatisfied_bound(&entry.message)?;
+    let (context, _) = bound.split_once(": ")?;
+    Some(context.rsplit("::").next().unwrap_or(context).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::load_diagnostic_database;
+
+    #[test]
+    fn test_collect_run_stats() {
+        let fixture =
+            |name: &str| format!("{}/../examples/src/{}", env!("CARGO_MANIFEST_DIR"), name);
+
+        let mut db = load_diagnostic_database(fixture("density_3.json"));
+        db.render_cgp_diagnostics();
+        let stats = RunStats::collect(&db, "release", 2, 1, Duration::from_millis(1500));
+
+        assert_eq!(stats.cgp_errors, 1);
+        assert_eq!(stats.by_kind.get("missing-field"), Some(&1));
+        assert_eq!(stats.by_context.get("Rectangle"), Some(&1));
+        assert_eq!(stats.by_crate.values().sum::<usize>(), 1);
+
+        let value: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+        assert_eq!(value["reason"], "cgp-run-stats");
+        assert_eq!(value["profile"], "release");
+        assert_eq!(value["other_errors"], 2);
+        assert_eq!(value["elapsed_ms"], 1500);
+
+        assert_eq!(stats.merged, 1);
+        assert_eq!(
+            stats.render_footer(),
+            "summary: 1 CGP error (1 missing field), 1 diagnostic merged, 2 other compiler errors"
+        );
+
+        let mut total = stats.clone();
+        total.merge(&stats);
+        assert_eq!(total.cgp_errors, 2);
+        assert_eq!(total.by_kind.get("missing-field"), Some(&2));
+        assert_eq!(total.elapsed_ms, 3000);
+    }
+}
@@ -0,0 +1,415 @@
+/// Module for `cargo cgp serve`, a long-running JSON-RPC server for build servers and IDEs
+/// The server reads one JSON-RPC 2.0 request per line from stdin and writes one response
+/// per line to stdout. It keeps the component index of the workspace between requests,
+/// so a build server that already runs cargo can ask for the improved errors of its
+/// messages without paying for a new process and a new scan on every check:
+///
+/// - `index { workspace }` scans the sources of a workspace (a directory or a manifest)
+/// - `analyze { json_lines }` improves the CGP errors in cargo's JSON messages
+/// - `fixes { fingerprint }` lists the fixes for one error of the last analysis
+/// - `shutdown` stops the server
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use cargo_metadata::Message;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::cgp_diagnostic::{CgpDiagnostic, SpanLocation};
+use crate::cgp_patterns::is_cgp_diagnostic;
+use crate::diagnostic_db::{DiagnosticDatabase, DiagnosticEntry};
+use crate::error_formatting::{merge_duplicate_check_errors, render_diagnostic_plain};
+use crate::fix::{Fix, plan_entry_fixes};
+use crate::json_output::CgpJsonMessage;
+use crate::name_resolution::{NameResolver, ResolutionMode};
+use crate::package_selection::checked_source_roots;
+use crate::run_check::scan_workspace;
+use crate::run_log::fingerprint;
+use crate::workspace_scan::WorkspaceScan;
+
+/// JSON-RPC error code for requests that are not valid JSON
+const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code for requests that the server does not implement
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for requests with missing or malformed parameters
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code for requests that failed (e.g., an unreadable workspace)
+const INTERNAL_ERROR: i64 = -32603;
+
+pub fn run_serve() -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut writer = std::io::stdout();
+
+    let mut server = ServeServer::default();
+    for line in stdin.lock().lines() {
+        let line = line.context("Failed to read a request from stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => server.handle(&request),
+            Err(error) => Some(error_response(
+                Value::Null,
+                PARSE_ERROR,
+                &format!("Invalid JSON: {}", error),
+            )),
+        };
+
+        if let Some(response) = response {
+            writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+            writer.flush()?;
+        }
+        if server.stopped {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// The state of the server between requests
+#[derive(Debug, Default)]
+struct ServeServer {
+    /// The component index, scanned on the first request that needs it
+    scan: Option<WorkspaceScan>,
+    /// The last analysis, which `fixes` refers to by fingerprint
+    last_analysis: Option<(DiagnosticDatabase, Vec<CgpDiagnostic>)>,
+    /// Set by `shutdown`
+    stopped: bool,
+}
+
+/// An improved error in the result of `analyze`
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    /// The fingerprint to request the fixes of this error with
+    fingerprint: String,
+    #[serde(flatten)]
+    message: CgpJsonMessage<'a>,
+    /// The error as shown by `cargo cgp check --color=never`
+    rendered: String,
+}
+
+impl ServeServer {
+    /// Handles one request, returning the response, or None for notifications
+    fn handle(&mut self, request: &Value) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "index" => self.index(&params),
+            "analyze" => self.analyze(&params),
+            "fixes" => self.fixes(&params),
+            "shutdown" => {
+                self.stopped = true;
+                Ok(Value::Null)
+            }
+            _ => {
+                let message = format!("Unsupported method `{}`", method);
+                return id.map(|id| error_response(id, METHOD_NOT_FOUND, &message));
+            }
+        };
+
+        // Notifications are handled for their effect, but never answered
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(RequestError::InvalidParams(message)) => {
+                error_response(id, INVALID_PARAMS, &message)
+            }
+            Err(RequestError::Failed(error)) => {
+                error_response(id, INTERNAL_ERROR, &format!("{:#}", error))
+            }
+        })
+    }
+
+    /// Scans the workspace at `params.workspace`, or the current directory without it
+    fn index(&mut self, params: &Value) -> Result<Value, RequestError> {
+        let scan = match params.get("workspace") {
+            Some(Value::String(workspace)) => scan_workspace_at(Path::new(workspace))?,
+            None | Some(Value::Null) => scan_workspace(),
+            Some(_) => return Err(RequestError::invalid("`workspace` must be a path")),
+        };
+
+        let result = json!({
+            "contexts": scan.context_structs.len(),
+            "components": scan.component_decls.len(),
+            "providers": scan.provider_impls.len(),
+            "check_blocks": scan.check_blocks.len(),
+            "unreadable_files": scan.unreadable_files,
+        });
+        self.scan = Some(scan);
+        Ok(result)
+    }
+
+    /// Improves the CGP errors in `params.json_lines`, cargo's JSON messages given as one
+    /// string or as an array of lines
+    fn analyze(&mut self, params: &Value) -> Result<Value, RequestError> {
+        let json_lines = match params.get("json_lines") {
+            Some(Value::String(text)) => text.clone(),
+            Some(Value::Array(lines)) => lines
+                .iter()
+                .map(|line| line.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| RequestError::invalid("`json_lines` must contain strings"))?
+                .join("\n"),
+            _ => {
+                return Err(RequestError::invalid(
+                    "`json_lines` must be a string or an array of strings",
+                ));
+            }
+        };
+
+        let scan = self.scan.get_or_insert_with(scan_workspace);
+        let mut db = DiagnosticDatabase::new();
+        db.set_name_resolver(NameResolver::from_scan(scan, ResolutionMode::Heuristic));
+        for message in Message::parse_stream(json_lines.as_bytes()) {
+            let message = message
+                .map_err(|error| RequestError::invalid(&format!("Invalid message: {}", error)))?;
+            if let Message::CompilerMessage(msg) = &message
+                && is_cgp_diagnostic(&msg.message)
+            {
+                db.add_diagnostic(msg);
+            }
+        }
+
+        let diagnostics =
+            merge_duplicate_check_errors(db.render_cgp_diagnostics(), &scan.duplicate_checks());
+        let reports: Vec<Report> = diagnostics
+            .iter()
+            .map(|diagnostic| Report {
+                fingerprint: fingerprint(diagnostic),
+                message: CgpJsonMessage::from_diagnostic(diagnostic),
+                rendered: render_diagnostic_plain(diagnostic),
+            })
+            .collect();
+        let result = json!({ "reports": reports });
+
+        self.last_analysis = Some((db, diagnostics));
+        Ok(result)
+    }
+
+    /// Lists the fixes for the error of the last analysis whose fingerprint starts with
+    /// `params.fingerprint`
+    fn fixes(&mut self, params: &Value) -> Result<Value, RequestError> {
+        let Some(prefix) = params.get("fingerprint").and_then(Value::as_str) else {
+            return Err(RequestError::invalid("`fingerprint` must be a string"));
+        };
+        let Some((db, diagnostics)) = &self.last_analysis else {
+            return Err(RequestError::Failed(anyhow::anyhow!(
+                "No analysis to take the fixes from: call `analyze` first"
+            )));
+        };
+
+        let matches: Vec<&CgpDiagnostic> = diagnostics
+            .iter()
+            .filter(|diagnostic| fingerprint(diagnostic).starts_with(prefix))
+            .collect();
+        let diagnostic = match matches.as_slice() {
+            [diagnostic] => diagnostic,
+            [] => {
+                return Err(RequestError::invalid(&format!(
+                    "No error with the fingerprint `{}` in the last analysis",
+                    prefix
+                )));
+            }
+            _ => {
+                return Err(RequestError::invalid(&format!(
+                    "The fingerprint `{}` matches {} errors of the last analysis",
+                    prefix,
+                    matches.len()
+                )));
+            }
+        };
+
+        let scan = self.scan.get_or_insert_with(scan_workspace);
+        let fixes: Vec<Value> = plan_entry_fixes(entries_behind(db, diagnostic), scan)
+            .iter()
+            .map(|fix| fix_to_json(fix, scan))
+            .collect();
+        Ok(json!({ "fixes": fixes }))
+    }
+}
+
+/// Why a request failed
+#[derive(Debug)]
+enum RequestError {
+    InvalidParams(String),
+    Failed(anyhow::Error),
+}
+
+impl RequestError {
+    fn invalid(message: &str) -> Self {
+        RequestError::InvalidParams(message.to_string())
+    }
+}
+
+impl From<anyhow::Error> for RequestError {
+    fn from(error: anyhow::Error) -> Self {
+        RequestError::Failed(error)
+    }
+}
+
+/// Scans the members of the workspace at a directory or a manifest path
+fn scan_workspace_at(workspace: &Path) -> Result<WorkspaceScan> {
+    let manifest: PathBuf = if workspace.is_dir() {
+        workspace.join("Cargo.toml")
+    } else {
+        workspace.to_path_buf()
+    };
+    if !manifest.is_file() {
+        bail!("No manifest at {}", manifest.display());
+    }
+
+    let cargo_args = [
+        "--manifest-path".to_string(),
+        manifest.display().to_string(),
+        "--workspace".to_string(),
+    ];
+    let (workspace_root, source_roots) = checked_source_roots(&cargo_args)
+        .with_context(|| format!("Failed to read the metadata of {}", manifest.display()))?;
+    Ok(WorkspaceScan::scan_roots(&source_roots, &workspace_root))
+}
+
+/// Describes a fix, with the file of the context struct it changes if it is known
+fn fix_to_json(fix: &Fix, scan: &WorkspaceScan) -> Value {
+    let file = scan
+        .context_struct(fix.context_type())
+        .map(|context| context.location.file.display().to_string());
+    let mut value = match fix {
+        Fix::AddField {
+            context_type,
+            field_name,
+            field_type,
+        } => json!({
+            "kind": "add-field",
+            "context_type": context_type,
+            "field_name": field_name,
+            "field_type": field_type,
+        }),
+        Fix::DeriveHasField { context_type } => json!({
+            "kind": "derive-has-field",
+            "context_type": context_type,
+        }),
+    };
+    value["description"] = json!(fix.describe());
+    value["file"] = json!(file);
+    value
+}
+
+/// The entries of the database that an improved error was rendered from, found by
+/// their primary spans, which the error highlights
+fn entries_behind<'a>(
+    db: &'a DiagnosticDatabase,
+    diagnostic: &CgpDiagnostic,
+) -> Vec<&'a DiagnosticEntry> {
+    db.get_active_entries()
+        .into_iter()
+        .filter(|entry| {
+            entry.primary_spans().iter().any(|span| {
+                let location = SpanLocation::from_span(span, "");
+                diagnostic.spans.iter().any(|shown| {
+                    shown.file == location.file
+                        && shown.line_start == location.line_start
+                        && shown.column_start == location.column_start
+                })
+            })
+        })
+        .collect()
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: i64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    #[test]
+    fn test_serve_requests() {
+        use crate::test_utils::{examples_dir, load_example_with_index};
+
+        let (_, scan) = load_example_with_index("base_area");
+
+        let mut server = ServeServer {
+            scan: Some(scan),
+            ..ServeServer::default()
+        };
+
+        let response = server.handle(&request(1, "fixes", json!({ "fingerprint": "" })));
+        assert_eq!(response.unwrap()["error"]["code"], INTERNAL_ERROR);
+
+        let json_lines = std::fs::read_to_string(examples_dir().join("base_area.json")).unwrap();
+        let response = server
+            .handle(&request(2, "analyze", json!({ "json_lines": json_lines })))
+            .unwrap();
+        let reports = response["result"]["reports"].as_array().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["code"], "CGP0001");
+        assert!(
+            reports[0]["rendered"]
+                .as_str()
+                .unwrap()
+                .contains("missing field `heig")
+        );
+
+        let fingerprint = reports[0]["fingerprint"].as_str().unwrap()[..4].to_string();
+        let response = server
+            .handle(&request(3, "fixes", json!({ "fingerprint": fingerprint })))
+            .unwrap();
+        assert_eq!(
+            response["result"]["fixes"],
+            json!([{
+                "kind": "add-field",
+                "context_type": "Rectangle",
+                "field_name": "height",
+                "field_type": "f64",
+                "description": "add field `height: f64` to `Rectangle`",
+                "file": examples_dir().join("base_area.rs"),
+            }])
+        );
+
+        // Each error of an analysis only gets the fixes of its own entries
+        let json_lines = format!(
+            "{}\n{}",
+            json_lines,
+            std::fs::read_to_string(examples_dir().join("base_area_2.json")).unwrap()
+        );
+        server
+            .handle(&request(4, "analyze", json!({ "json_lines": json_lines })))
+            .unwrap();
+        let (db, diagnostics) = server.last_analysis.as_ref().unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        for diagnostic in diagnostics {
+            let entries = entries_behind(db, diagnostic);
+            assert_eq!(entries.len(), 1);
+            assert_eq!(
+                entries[0].primary_span().unwrap().file_name,
+                diagnostic.spans[0].file
+            );
+        }
+
+        let response = server.handle(&request(4, "analyze", json!({ "json_lines": 1 })));
+        assert_eq!(response.unwrap()["error"]["code"], INVALID_PARAMS);
+        let response = server.handle(&request(5, "hover", Value::Null));
+        assert_eq!(response.unwrap()["error"]["code"], METHOD_NOT_FOUND);
+
+        // Notifications are not answered
+        assert_eq!(
+            server.handle(&json!({ "jsonrpc": "2.0", "method": "shutdown" })),
+            None
+        );
+        assert!(server.stopped);
+    }
+}
@@ -0,0 +1,128 @@
+/// Module for diagnostics that cargo reports once per target of a package
+/// A package with a companion derive crate, or a lib checked together with its tests, can
+/// have the same wiring error reported by several of its targets, with identical text.
+/// The repeats are recognized by the package and a fingerprint of the diagnostic's content,
+/// so that each error is shown once, followed by a note naming the affected packages.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+use cargo_metadata::CompilerMessage;
+use cargo_metadata::diagnostic::Diagnostic;
+
+use crate::diagnostic_db::package_name;
+
+/// The diagnostics seen so far, by package and content
+#[derive(Debug, Default)]
+pub struct TargetRepeats {
+    /// Map from (package ID, content fingerprint) to the target that first reported it
+    seen: HashMap<(String, u64), String>,
+    /// Number of repeats skipped, by package name
+    skipped: BTreeMap<String, usize>,
+}
+
+impl TargetRepeats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if another target of the same package already reported the same
+    /// diagnostic, in which case the message is counted and should be skipped
+    pub fn is_repeat(&mut self, message: &CompilerMessage) -> bool {
+        let key = (
+            message.package_id.repr.clone(),
+            fingerprint(&message.message),
+        );
+        match self.seen.get(&key) {
+            Some(target) if *target != message.target.name => {
+                *self
+                    .skipped
+                    .entry(package_name(&message.package_id).to_string())
+                    .or_default() += 1;
+                true
+            }
+            Some(_) => false,
+            None => {
+                self.seen.insert(key, message.target.name.clone());
+                false
+            }
+        }
+    }
+
+    /// Returns the number of repeats skipped so far
+    pub fn skipped_count(&self) -> usize {
+        self.skipped.values().sum()
+    }
+
+    /// Renders a note naming the packages whose repeats were skipped, if there were any
+    pub fn render_note(&self) -> Option<String> {
+        let packages: Vec<String> = self
+            .skipped
+            .keys()
+            .map(|name| format!("`{}`", name))
+            .collect();
+        let (noun, verb) = match self.skipped_count() {
+            0 => return None,
+            1 => ("1 diagnostic".to_string(), "is"),
+            n => (format!("{} diagnostics", n), "are"),
+        };
+        Some(format!(
+            "note: {} reported again by another target of {} {} shown once",
+            noun,
+            packages.join(", "),
+            verb
+        ))
+    }
+}
+
+/// Hashes the content of a diagnostic, which is the same for every target reporting it
+fn fingerprint(diagnostic: &Diagnostic) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match &diagnostic.rendered {
+        Some(rendered) => rendered.hash(&mut hasher),
+        None => serde_json::to_string(diagnostic)
+            .unwrap_or_default()
+            .hash(&mut hasher),
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeats_from_another_target() {
+        use cargo_metadata::Message;
+
+        let json = include_str!("../../examples/src/base_area.json");
+        let message = Message::parse_stream(json.as_bytes())
+            .find_map(|message| match message.unwrap() {
+                Message::CompilerMessage(message) => Some(message),
+                _ => None,
+            })
+            .unwrap();
+        let mut derive_target = message.clone();
+        derive_target.target.name = "base_area_derive".to_string();
+
+        let mut repeats = TargetRepeats::new();
+        assert!(!repeats.is_repeat(&message));
+        assert!(repeats.is_repeat(&derive_target));
+        assert!(repeats.is_repeat(&derive_target));
+        // Only the repeats from another target are skipped
+        assert!(!repeats.is_repeat(&message));
+
+        let mut other_package = derive_target.clone();
+        other_package.package_id.repr = "path+file:///work/other#0.1.0".to_string();
+        assert!(!repeats.is_repeat(&other_package));
+
+        assert_eq!(repeats.skipped_count(), 2);
+        assert_eq!(
+            repeats.render_note().unwrap(),
+            format!(
+                "note: 2 diagnostics reported again by another target of `{}` are shown once",
+                package_name(&message.package_id)
+            )
+        );
+    }
+}
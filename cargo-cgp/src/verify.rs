@@ -0,0 +1,280 @@
+/// Module for `cargo cgp verify`
+/// Runs `cargo check`, and when it succeeds, reports what the compiler verified: the
+/// contexts of the workspace, their components and the providers wired for them.
+/// Only components listed in a `check_components!` block are verified for a context,
+/// so the report separates them from components that are delegated but never checked.
+/// The report is meant to be kept as a CI artifact proving wiring coverage.
+use anyhow::{Result, bail};
+use serde::Serialize;
+
+use crate::check_options::{CheckOptions, OutputFormat};
+use crate::run_check::{run_cargo_pipeline_with_scan, scan_checked_crates};
+use crate::workspace_scan::{WorkspaceScan, base_type_name};
+
+/// The wiring verified by a successful check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerificationReport {
+    /// Always "cgp-verify", to distinguish from cargo's own messages
+    pub reason: &'static str,
+    pub contexts: Vec<VerifiedContext>,
+}
+
+/// The components of one context
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerifiedContext {
+    pub context_type: String,
+    /// The check traits generated for the context (e.g., "CanUseRectangle")
+    pub check_traits: Vec<String>,
+    /// Delegated and checked components, in source order
+    pub components: Vec<VerifiedComponent>,
+}
+
+/// A component of a context, with the provider it is wired to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerifiedComponent {
+    pub component: String,
+    /// The provider as written in `delegate_components!`, if the delegation was found
+    pub provider: Option<String>,
+    /// The provider implementations behind the provider, outermost first
+    /// (e.g., "ScaledArea<InnerCalculator>", then "RectangleArea")
+    pub provider_chain: Vec<String>,
+    /// Whether a `check_components!` block checks the component for the context
+    pub checked: bool,
+}
+
+impl VerificationReport {
+    /// Collects the wiring of every context that is delegated or checked in the sources
+    pub fn collect(scan: &WorkspaceScan) -> Self {
+        let mut context_types: Vec<&str> = scan.wired_contexts();
+        for block in &scan.check_blocks {
+            if !context_types
+                .iter()
+                .any(|context| base_type_name(context) == base_type_name(&block.context_type))
+            {
+                context_types.push(&block.context_type);
+            }
+        }
+
+        let contexts = context_types
+            .into_iter()
+            .map(|context_type| verify_context(scan, context_type))
+            .collect();
+
+        VerificationReport {
+            reason: "cgp-verify",
+            contexts,
+        }
+    }
+
+    /// Returns the number of checked components and the number of all components
+    pub fn coverage(&self) -> (usize, usize) {
+        let components = self.contexts.iter().flat_map(|context| &context.components);
+        let checked = components.clone().filter(|c| c.checked).count();
+        (checked, components.count())
+    }
+
+    /// Renders the report for the terminal
+    pub fn render(&self) -> String {
+        let mut lines = Vec::new();
+
+        for context in &self.contexts {
+            match context.check_traits.as_slice() {
+                [] => lines.push(context.context_type.clone()),
+                traits => lines.push(format!(
+                    "{} (checked by {})",
+                    context.context_type,
+                    traits.join(", ")
+                )),
+            }
+
+            for component in &context.components {
+                let marker = if component.checked { "✓" } else { "·" };
+                let mut line = format!("  {} {}", marker, component.component);
+                if let Some(provider) = &component.provider {
+                    line.push_str(&format!(" → {}", provider));
+                }
+                if component.provider_chain.len() > 1 {
+                    line.push_str(&format!(" ({})", component.provider_chain.join(" → ")));
+                }
+                if !component.checked {
+                    line.push_str(" (not checked)");
+                }
+                lines.push(line);
+            }
+        }
+
+        let (checked, total) = self.coverage();
+        let contexts = match self.contexts.len() {
+            1 => "1 context".to_string(),
+            n => format!("{} contexts", n),
+        };
+        lines.push(format!(
+            "cargo cgp verify: wiring OK, {} of {} components checked in {}",
+            checked, total, contexts
+        ));
+
+        lines.join("\n")
+    }
+}
+
+/// Collects the components of a context, with the components of its delegations first
+fn verify_context(scan: &WorkspaceScan, context_type: &str) -> VerifiedContext {
+    let checks: Vec<_> = scan
+        .check_blocks
+        .iter()
+        .filter(|block| base_type_name(&block.context_type) == base_type_name(context_type))
+        .collect();
+    let is_checked = |component: &str| {
+        checks.iter().any(|block| {
+            block
+                .components
+                .iter()
+                .any(|checked| base_type_name(&checked.name) == base_type_name(component))
+        })
+    };
+
+    let mut components: Vec<VerifiedComponent> = scan
+        .delegations_of(context_type)
+        .into_iter()
+        .map(|delegation| VerifiedComponent {
+            component: delegation.component.clone(),
+            provider: Some(delegation.provider.clone()),
+            provider_chain: provider_chain(scan, &delegation.component, &delegation.provider),
+            checked: is_checked(&delegation.component),
+        })
+        .collect();
+
+    // Components can be checked without a delegation in the scanned sources,
+    // e.g. when the context delegates through a table from another crate
+    for block in &checks {
+        for checked in &block.components {
+            if !components
+                .iter()
+                .any(|c| base_type_name(&c.component) == base_type_name(&checked.name))
+            {
+                components.push(VerifiedComponent {
+                    component: checked.name.clone(),
+                    provider: None,
+                    provider_chain: Vec::new(),
+                    checked: true,
+                });
+            }
+        }
+    }
+
+    let mut check_traits: Vec<String> = Vec::new();
+    for block in &checks {
+        if !check_traits.contains(&block.trait_name) {
+            check_traits.push(block.trait_name.clone());
+        }
+    }
+
+    VerifiedContext {
+        context_type: context_type.to_string(),
+        check_traits,
+        components,
+    }
+}
+
+/// Returns the provider implementations used by a provider expression, in the order
+/// their names appear in it
+fn provider_chain(scan: &WorkspaceScan, component: &str, provider: &str) -> Vec<String> {
+    let provider_trait = scan
+        .component_decl(component)
+        .map(|decl| decl.provider_trait.as_str());
+
+    let mut impls = scan.provider_impls_in(provider, provider_trait);
+    impls.sort_by_key(|provider_impl| provider.find(provider_impl.provider_name()));
+
+    let mut chain: Vec<String> = Vec::new();
+    for provider_impl in impls {
+        if !chain.contains(&provider_impl.provider_type) {
+            chain.push(provider_impl.provider_type.clone());
+        }
+    }
+    chain
+}
+
+/// Runs `cargo check` and prints the verification report if it succeeds
+/// Exits with cargo's status if it fails, after showing the improved errors
+pub fn run_verify(options: &CheckOptions) -> Result<()> {
+    if options.watch || options.from_json.is_some() {
+        bail!(
+            "`cargo cgp verify` runs cargo once, so `--watch` and `--from-json` are not supported"
+        );
+    }
+
+    let scan = scan_checked_crates(&options.cargo_args);
+    let status = run_cargo_pipeline_with_scan("check", options, &scan)?;
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
+    let report = VerificationReport::collect(&scan);
+    match options.format {
+        OutputFormat::Human => println!("{}", report.render()),
+        OutputFormat::CgpJson | OutputFormat::Json => {
+            println!("{}", serde_json::to_string(&report)?)
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const SOURCE: &str = r#"
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator {
+    fn area(&self) -> f64 {
+        0.0
+    }
+}
+
+#[cgp_impl(new ScaledArea<InnerCalculator>)]
+impl<InnerCalculator> AreaCalculator {
+    fn area(&self) -> f64 {
+        0.0
+    }
+}
+
+pub struct Rectangle {
+    pub width: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: ScaledArea<RectangleArea>,
+        DensityCalculatorComponent: DensityFromArea,
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
+"#;
+
+    #[test]
+    fn test_verification_report() {
+        let mut scan = WorkspaceScan::new();
+        let path = Path::new("src/lib.rs");
+        scan.scan_file(path, path, "shapes", "crate", SOURCE);
+
+        let report = VerificationReport::collect(&scan);
+        assert_eq!(report.coverage(), (1, 2));
+        assert_eq!(
+            report.render(),
+            "Rectangle (checked by CanUseRectangle)\n  ✓ AreaCalculatorComponent → ScaledArea<RectangleArea> (ScaledArea<InnerCalculator> → RectangleArea)\n  · DensityCalculatorComponent → DensityFromArea (not checked)\ncargo cgp verify: wiring OK, 1 of 2 components checked in 1 context"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+/// Module for `--watch`, which reruns a cargo command whenever a source file changes
+/// Changes are detected by polling modification times, so the improved diagnostics keep
+/// the terminal detection and colors of a direct run
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+
+use crate::check_options::CheckOptions;
+use crate::error_formatting::is_terminal;
+use crate::pager::PagerMode;
+use crate::run_check::run_cargo_pipeline;
+use crate::workspace_scan::workspace_source_roots;
+
+/// How often the sources are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Modification times of the watched files, used to detect changes
+type SourceSnapshot = BTreeMap<PathBuf, SystemTime>;
+
+pub fn run_watch(command: &str, options: &CheckOptions) -> Result<()> {
+    let root = workspace_source_roots()
+        .map(|(workspace_root, _)| workspace_root)
+        .unwrap_or_else(|_| PathBuf::from("."));
+
+    // A pager would wait for input and block the loop
+    let options = CheckOptions {
+        pager: PagerMode::Never,
+        watch: false,
+        ..options.clone()
+    };
+
+    loop {
+        let snapshot = snapshot_sources(&root);
+
+        if is_terminal() {
+            // Clear the screen and move the cursor to the top left corner
+            print!("\x1b[2J\x1b[H");
+        }
+
+        // An error of one run (e.g., cargo cannot be started) is fixed by a later change,
+        // so it is reported without stopping the loop
+        let outcome = match run_cargo_pipeline(command, &options) {
+            Ok(status) if status.success() => "succeeded",
+            Ok(_) => "failed",
+            Err(error) => {
+                eprintln!("error: {:#}", error);
+                "could not run"
+            }
+        };
+        eprintln!(
+            "cargo {} {}; waiting for changes (press Ctrl-C to stop)",
+            command, outcome
+        );
+
+        while snapshot_sources(&root) == snapshot {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Records the modification times of the Rust sources and manifests under a directory
+fn snapshot_sources(root: &Path) -> SourceSnapshot {
+    let mut snapshot = SourceSnapshot::new();
+    collect_sources(root, &mut snapshot);
+    snapshot
+}
+
+fn collect_sources(dir: &Path, snapshot: &mut SourceSnapshot) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        // Build outputs and hidden directories (e.g., `.git`) never affect the result
+        if name.starts_with('.') || name == "target" {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_sources(&path, snapshot);
+        } else if (name.ends_with(".rs") || name == "Cargo.toml")
+            && let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified())
+        {
+            snapshot.insert(path, modified);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_sources() {
+        let root = std::env::temp_dir().join(format!("cargo-cgp-watch-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.join("target/generated.rs"), "").unwrap();
+        std::fs::write(root.join("README.md"), "").unwrap();
+
+        let snapshot = snapshot_sources(&root);
+        let files: Vec<_> = snapshot
+            .keys()
+            .map(|path| path.strip_prefix(&root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("Cargo.toml"), PathBuf::from("src/lib.rs")]
+        );
+
+        // Adding a source file is a change
+        std::fs::write(root.join("src/new.rs"), "").unwrap();
+        assert_ne!(snapshot_sources(&root), snapshot);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
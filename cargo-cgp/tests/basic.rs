@@ -1,4 +1,7 @@
-use cargo_cgp::test_utils::test_cgp_error_from_json;
+// The snapshots are rendered with miette
+#![cfg(feature = "graphical")]
+
+use cargo_cgp::test_utils::{load_diagnostic_database, test_cgp_error_from_json};
 use insta::assert_snapshot;
 
 #[test]
@@ -9,30 +12,32 @@ fn test_base_area_error() {
     assert_eq!(outputs.len(), 1, "Expected 1 error message");
 
     assert_snapshot!(outputs[0], @"
-     x missing field `heig�t` in the context `Rectangle`.
-       ,-[examples/src/base_area.rs:41:9]
-    40 |     CanUseRectangle for Rectangle {
-    41 |         AreaCalculatorComponent,
-       :         ^^^^^^^^^^^|^^^^^^^^^^^
-       :                    `-- unsatisfied trait bound
-    42 |     }
-       `----
-     help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
-               note: Missing field: `heig�t`
-           
-           note: some characters in the field name are hidden by the compiler and shown as '�'
-           
-           The struct `Rectangle` is defined at `examples/src/base_area.rs:41` but does not have the required field `heig�t`.
-           
-           Dependency chain:
-               `CanUseRectangle` for `Rectangle` (check trait)
-               └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
-                  └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)
-                     └─ `HasRectangleFields` for `Rectangle` (getter trait)
-                        └─ field `heig�t` on `Rectangle` ✗
-           
-           To fix this error:
-               • Add a field `heig�t` to the `Rectangle` struct at examples/src/base_area.rs:41
+    CGP0001
+
+      x missing field `heig�t` in the context `Rectangle`.
+        ,-[examples/src/base_area.rs:41:9]
+     40 |     CanUseRectangle for Rectangle {
+     41 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- unsatisfied trait bound
+     42 |     }
+        `----
+      help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
+                note: Missing field: `heig�t`
+            
+            note: some characters in the field name are hidden by the compiler and shown as '�'
+            
+            The struct `Rectangle` is defined at `examples/src/base_area.rs:41` but does not have the required field `heig�t`.
+            
+            Dependency chain:
+                `CanUseRectangle` for `Rectangle` (check trait)
+                └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
+                   └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)
+                      └─ `HasRectangleFields` for `Rectangle` (getter trait)
+                         └─ field `heig�t` on `Rectangle` ✗
+            
+            To fix this error:
+                • Add a field `heig�t` to the `Rectangle` struct at examples/src/base_area.rs:41
     ");
 }
 
@@ -52,29 +57,31 @@ fn test_base_area_2_error() {
     );
 
     assert_snapshot!(outputs[0], @"
-     x missing field `width` or `#[derive(HasField)]` in the context `Rectangle`.
-       ,-[examples/src/base_area_2.rs:41:9]
-    40 |     CanUseRectangle for Rectangle {
-    41 |         AreaCalculatorComponent,
-       :         ^^^^^^^^^^^|^^^^^^^^^^^
-       :                    `-- unsatisfied trait bound
-    42 |     }
-       `----
-     help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
-               note: Missing field: `width` or struct needs `#[derive(HasField)]`
-           
-           The struct `Rectangle` is defined at `examples/src/base_area_2.rs:41` but does not have the required field `width`.
-           
-           Dependency chain:
-               `CanUseRectangle` for `Rectangle` (check trait)
-               └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
-                  └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)
-                     └─ `HasRectangleFields` for `Rectangle` (getter trait)
-                        └─ field `width` on `Rectangle` ✗
-           
-           To fix this error:
-               • If the struct has the field `width`, add `#[derive(HasField)]` to the struct definition at `examples/src/base_area_2.rs:41`
-               • If the field is missing, add a `width` field to the struct
+    CGP0003
+
+      x missing field `width` or `#[derive(HasField)]` in the context `Rectangle`.
+        ,-[examples/src/base_area_2.rs:41:9]
+     40 |     CanUseRectangle for Rectangle {
+     41 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- unsatisfied trait bound
+     42 |     }
+        `----
+      help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
+                note: Missing field: `width` or struct needs `#[derive(HasField)]`
+            
+            The struct `Rectangle` is defined at `examples/src/base_area_2.rs:41` but does not have the required field `width`.
+            
+            Dependency chain:
+                `CanUseRectangle` for `Rectangle` (check trait)
+                └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
+                   └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)
+                      └─ `HasRectangleFields` for `Rectangle` (getter trait)
+                         └─ field `width` on `Rectangle` ✗
+            
+            To fix this error:
+                • If the struct has the field `width`, add `#[derive(HasField)]` to the struct definition at `examples/src/base_area_2.rs:41`
+                • If the field is missing, add a `width` field to the struct
     ");
 }
 
@@ -82,8 +89,17 @@ fn test_base_area_2_error() {
 fn test_scaled_area_error() {
     let outputs = test_cgp_error_from_json("scaled_area.json", "scaled_area");
 
-    // Now correctly merged into one error message
+    // The provider-trait error and the field error are folded into one error message,
+    // rather than one of them being suppressed
     assert_eq!(outputs.len(), 1, "Expected 1 error message");
+    assert!(outputs.iter().all(|output| !output.trim().is_empty()));
+
+    let db = load_diagnostic_database(format!(
+        "{}/../examples/src/scaled_area.json",
+        env!("CARGO_MANIFEST_DIR")
+    ));
+    assert_eq!(db.merged_count(), 1);
+    assert_eq!(db.suppressed_count(), 0);
 
     // The error should be the comprehensive CGP-formatted missing field error
     assert!(
@@ -92,31 +108,33 @@ fn test_scaled_area_error() {
     );
 
     assert_snapshot!(outputs[0], @"
-     x missing field `height` in the context `Rectangle`.
-       ,-[examples/src/scaled_area.rs:58:9]
-    57 |     CanUseRectangle for Rectangle {
-    58 |         AreaCalculatorComponent,
-       :         ^^^^^^^^^^^|^^^^^^^^^^^
-       :                    `-- unsatisfied trait bound
-    59 |     }
-       `----
-     help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
-               note: Missing field: `height`
-           
-           The struct `Rectangle` is defined at `examples/src/scaled_area.rs:58` but does not have the required field `height`.
-           
-           Dependency chain:
-               `CanUseRectangle` for `Rectangle` (check trait)
-               └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
-                  └─ `AreaCalculator<Rectangle>` for provider `ScaledArea<RectangleArea>` (provider trait)
-                     ├─ `HasRectangleFields` for `Rectangle` (getter trait)
-                     │  └─ field `height` on `Rectangle` ✗
-                     └─ `AreaCalculator<Rectangle>` for inner provider `RectangleArea` (provider trait) ✓
-           
-           The error in the higher-order provider `ScaledArea<RectangleArea>` might be caused by its inner provider `RectangleArea`.
-           
-           To fix this error:
-               • Add a field `height` to the `Rectangle` struct at examples/src/scaled_area.rs:58
+    CGP0001
+
+      x missing field `height` in the context `Rectangle`.
+        ,-[examples/src/scaled_area.rs:58:9]
+     57 |     CanUseRectangle for Rectangle {
+     58 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- unsatisfied trait bound
+     59 |     }
+        `----
+      help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
+                note: Missing field: `height`
+            
+            The struct `Rectangle` is defined at `examples/src/scaled_area.rs:58` but does not have the required field `height`.
+            
+            Dependency chain:
+                `CanUseRectangle` for `Rectangle` (check trait)
+                └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
+                   └─ `AreaCalculator<Rectangle>` for provider `ScaledArea<RectangleArea>` (provider trait)
+                      ├─ `HasRectangleFields` for `Rectangle` (getter trait)
+                      │  └─ field `height` on `Rectangle` ✗
+                      └─ `AreaCalculator<Rectangle>` for inner provider `RectangleArea` (provider trait) ✓
+            
+            The error in the higher-order provider `ScaledArea<RectangleArea>` might be caused by its inner provider `RectangleArea`.
+            
+            To fix this error:
+                • Add a field `height` to the `Rectangle` struct at examples/src/scaled_area.rs:58
     ");
 }
 
@@ -127,27 +145,532 @@ fn test_scaled_area_2_error() {
     assert_eq!(outputs.len(), 1, "Expected 1 error message");
 
     assert_snapshot!(outputs[0], @"
-     x missing field `scale_factor` in the context `Rectangle`.
-       ,-[examples/src/scaled_area_2.rs:58:9]
-    57 |     CanUseRectangle for Rectangle {
-    58 |         AreaCalculatorComponent,
-       :         ^^^^^^^^^^^|^^^^^^^^^^^
-       :                    `-- unsatisfied trait bound
-    59 |     }
-       `----
-     help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
-               note: Missing field: `scale_factor`
-           
-           The struct `Rectangle` is defined at `examples/src/scaled_area_2.rs:58` but does not have the required field `scale_factor`.
-           
-           Dependency chain:
-               `CanUseRectangle` for `Rectangle` (check trait)
-               └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
-                  └─ `AreaCalculator<Rectangle>` for provider `ScaledArea<RectangleArea>` (provider trait)
-                     └─ `HasScaleFactor` for `Rectangle` (getter trait)
-                        └─ field `scale_factor` on `Rectangle` ✗
-           
-           To fix this error:
-               • Add a field `scale_factor` to the `Rectangle` struct at examples/src/scaled_area_2.rs:58
+    CGP0001
+
+      x missing field `scale_factor` in the context `Rectangle`.
+        ,-[examples/src/scaled_area_2.rs:58:9]
+     57 |     CanUseRectangle for Rectangle {
+     58 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- unsatisfied trait bound
+     59 |     }
+        `----
+      help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
+                note: Missing field: `scale_factor`
+            
+            The struct `Rectangle` is defined at `examples/src/scaled_area_2.rs:58` but does not have the required field `scale_factor`.
+            
+            Dependency chain:
+                `CanUseRectangle` for `Rectangle` (check trait)
+                └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
+                   └─ `AreaCalculator<Rectangle>` for provider `ScaledArea<RectangleArea>` (provider trait)
+                      └─ `HasScaleFactor` for `Rectangle` (getter trait)
+                         └─ field `scale_factor` on `Rectangle` ✗
+            
+            To fix this error:
+                • Wire `AreaCalculatorComponent` to the inner provider `RectangleArea` instead of `ScaledArea<RectangleArea>` for `Rectangle`
+                  (only `ScaledArea` requires the field `scale_factor`, so this also drops the behavior of `ScaledArea`)
+                • Add a field `scale_factor` to the `Rectangle` struct at examples/src/scaled_area_2.rs:58
+    ");
+}
+
+#[test]
+fn test_retry_area_error() {
+    // The provider has a lifetime and a const argument: `Retry<'static, 3, RectangleArea>`
+    let outputs = test_cgp_error_from_json("retry_area.json", "retry_area");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0001
+
+      x missing field `heig�t` in the context `Rectangle`.
+        ,-[examples/src/retry_area.rs:55:9]
+     54 |     CanUseRectangle for Rectangle {
+     55 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- unsatisfied trait bound
+     56 |     }
+        `----
+      help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
+                note: Missing field: `heig�t`
+            
+            note: some characters in the field name are hidden by the compiler and shown as '�'
+            
+            The struct `Rectangle` is defined at `examples/src/retry_area.rs:55` but does not have the required field `heig�t`.
+            
+            Dependency chain:
+                `CanUseRectangle` for `Rectangle` (check trait)
+                └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
+                   └─ `AreaCalculator<Rectangle>` for provider `Retry<'static, 3, RectangleArea>` (provider trait)
+                      ├─ `HasRectangleFields` for `Rectangle` (getter trait)
+                      │  └─ field `heig�t` on `Rectangle` ✗
+                      └─ `AreaCalculator<Rectangle>` for inner provider `RectangleArea` (provider trait) ✓
+            
+            The error in the higher-order provider `Retry<'static, 3, RectangleArea>` might be caused by its inner provider `RectangleArea`.
+            
+            To fix this error:
+                • Add a field `heig�t` to the `Rectangle` struct at examples/src/retry_area.rs:55
+    ");
+}
+
+#[test]
+fn test_usage_area_error() {
+    // A generic function bounded by `CanCalculateArea` is called without a check block
+    let outputs = test_cgp_error_from_json("usage_area.json", "usage_area");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0011
+
+      x `Rectangle` does not implement `CanCalculateArea`, which `total_area` requires
+        ,-[examples/src/usage_area.rs:39:28]
+     38 | 
+     39 | pub fn total_area<Context: CanCalculateArea>(shapes: &[Context]) -> f64 {
+        :                            ^^^^^^^^|^^^^^^^
+        :                                    `-- [1] required by this bound in `total_area`
+     40 |     shapes.iter().map(|shape| shape.area()).sum()
+     41 | }
+     42 | 
+     43 | pub fn rectangles_area(rectangles: &[Rectangle]) -> f64 {
+     44 |     total_area(rectangles)
+        :                ^^^^^|^^^^
+        :                     `-- [2] unsatisfied trait bound
+     45 | }
+        `----
+      help: The function `total_area` requires `Rectangle: CanCalculateArea` at `examples/src/usage_area.rs:39`.
+            
+            Dependency chain:
+                `CanCalculateArea` for `Rectangle` (consumer trait)
+                └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait) ✗
+            
+            To fix this error:
+                • If `Rectangle` does not wire `AreaCalculatorComponent`, wire it to a provider in `delegate_components!`
+                • Check `AreaCalculatorComponent` next to the definition of `Rectangle`, so that the compiler reports the dependencies that its provider is missing:
+                  check_components! {
+                      CanUseRectangle for Rectangle {
+                          AreaCalculatorComponent,
+                      }
+                  }
+    ");
+}
+
+#[test]
+fn test_undelegated_area_error() {
+    // A checked component has no entry in `delegate_components!`
+    let outputs = test_cgp_error_from_json("undelegated_area.json", "undelegated_area");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0002
+
+      x context `Rectangle` does not delegate `PerimeterCalculatorComponent`; add it to `delegate_components!`
+        ,-[examples/src/undelegated_area.rs:46:1]
+     45 |     
+     46 | ,-> delegate_components! {
+     47 | |       Rectangle {
+     48 | |           AreaCalculatorComponent:
+     49 | |               RectangleArea,
+     50 | |           // missing PerimeterCalculatorComponent to trigger error
+     51 | |       }
+     52 | |-> }
+        : `---- [1] `PerimeterCalculatorComponent` is not wired here
+     53 |     
+     54 |     check_components! {
+     55 |         CanUseRectangle for Rectangle {
+     56 |             AreaCalculatorComponent,
+     57 |             PerimeterCalculatorComponent,
+        :             ^^^^^^^^^^^^^^|^^^^^^^^^^^^^
+        :                           `-- [2] unsatisfied trait bound
+     58 |         }
+        `----
+      help: Components checked by `CanUseRectangle`:
+                `AreaCalculatorComponent` ✓
+                `PerimeterCalculatorComponent` ✗
+            
+            The component `PerimeterCalculatorComponent` is used with `Rectangle`, but no provider is wired for it.
+            
+            To fix this error:
+                • Wire `PerimeterCalculatorComponent` to a provider implementing `PerimeterCalculator` in the `delegate_components!` block of `Rectangle` at examples/src/undelegated_area.rs:46
+                • Or remove `PerimeterCalculatorComponent` from `CanUseRectangle`, if `Rectangle` should not use it
+    ");
+}
+
+#[test]
+fn test_misnamed_component_error() {
+    // The check names `AreaCalculationComponent`, while `AreaCalculatorComponent` is wired
+    let outputs = test_cgp_error_from_json("misnamed_component.json", "misnamed_component");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0002
+
+      x context `Rectangle` does not delegate `AreaCalculationComponent`; did you mean `AreaCalculatorComponent`?
+        ,-[examples/src/misnamed_component.rs:36:1]
+     35 |     
+     36 | ,-> delegate_components! {
+     37 | |->     Rectangle {
+        : `---- [1] `AreaCalculationComponent` is not wired here
+     38 |             AreaCalculatorComponent:
+     39 |                 RectangleArea,
+     40 |         }
+     41 |     }
+     42 |     
+     43 |     check_components! {
+     44 |         CanUseRectangle for Rectangle {
+     45 |             // a typo of the wired AreaCalculatorComponent
+     46 |             AreaCalculationComponent,
+        :             ^^^^^^^^^^^^|^^^^^^^^^^^
+        :                         `-- [2] unsatisfied trait bound
+     47 |         }
+        `----
+      help: The component `AreaCalculationComponent` is used with `Rectangle`, but no provider is wired for it.
+                note: `Rectangle` wires `AreaCalculatorComponent`, whose name is close to `AreaCalculationComponent`
+            
+            To fix this error:
+                • If `AreaCalculationComponent` is misspelled, use `AreaCalculatorComponent` in `CanUseRectangle` instead
+                • Or, if `AreaCalculatorComponent` is misspelled, rename it to `AreaCalculationComponent` in `delegate_components!`
+                • Or wire `AreaCalculationComponent` to a provider in the `delegate_components!` block of `Rectangle` at examples/src/misnamed_component.rs:36
+                • Or remove `AreaCalculationComponent` from `CanUseRectangle`, if `Rectangle` should not use it
+    ");
+}
+
+#[test]
+fn test_not_a_provider_area_error() {
+    // A component is delegated to the provider of another component
+    let outputs = test_cgp_error_from_json("not_a_provider_area.json", "not_a_provider_area");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0012
+
+      x `RectangleArea` is not a provider of `PerimeterCalculator`
+        ,-[examples/src/not_a_provider_area.rs:20:1]
+     19 | 
+     20 | #[cgp_impl(new RectangleArea)]
+        : ^^^^^^^^^^^^^^^|^^^^^^^^^^^^^^
+        :                `-- [1] `RectangleArea` is defined here
+     21 | impl AreaCalculator
+        `----
+        ,-[examples/src/not_a_provider_area.rs:59:9]
+     58 |         AreaCalculatorComponent,
+     59 |         PerimeterCalculatorComponent,
+        :         ^^^^^^^^^^^^^^|^^^^^^^^^^^^^
+        :                       `-- [2] unsatisfied trait bound
+     60 |     }
+        `----
+      help: Components checked by `CanUseRectangle`:
+                `AreaCalculatorComponent` ✓
+                `PerimeterCalculatorComponent` ✗
+            
+            `Rectangle` delegates `PerimeterCalculatorComponent` to `RectangleArea`, but `RectangleArea` does not implement `PerimeterCalculator`.
+                note: `RectangleArea` implements `AreaCalculator`
+            
+            To fix this error:
+                • Check that the `#[cgp_impl]` attribute of `RectangleArea` names the provider trait `PerimeterCalculator`
+                • Or delegate `PerimeterCalculatorComponent` to a provider of `PerimeterCalculator` in the `delegate_components!` block of `Rectangle`
+    ");
+}
+
+#[test]
+fn test_wrong_type_area_error() {
+    // The context has the field, but with another type than the getter reads
+    let outputs = test_cgp_error_from_json("wrong_type_area.json", "wrong_type_area");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0013
+
+      x field `height` has type `u32` but `f64` is required
+        ,-[examples/src/wrong_type_area.rs:41:9]
+     40 |     CanUseRectangle for Rectangle {
+     41 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- expected `f64`, found `u32`
+     42 |     }
+        `----
+      help: Context `Rectangle` has the field `height`, but not with the type required to use `AreaCalculatorComponent`.
+                note: `HasRectangleFields` reads `height` as `f64`
+            
+            Dependency chain:
+                `CanUseRectangle` for `Rectangle` (check trait)
+                └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
+                   └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)
+                      └─ `HasRectangleFields` for `Rectangle` (getter trait)
+                         └─ field `height: f64` on `Rectangle` ✗
+            
+            To fix this error:
+                • Change the type of the field `height` of `Rectangle` to `f64`
+                • Or, if the field has the intended type, change `height` in `HasRectangleFields` to return `u32`
+    ");
+}
+
+#[test]
+fn test_cyclic_area_error() {
+    // The provider requires the consumer trait of its own component, which overflows
+    let outputs = test_cgp_error_from_json("cyclic_area.json", "cyclic_area");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0014
+
+      x `Rectangle: CanCalculateArea` depends on itself through its wiring
+        ,-[examples/src/cyclic_area.rs:17:28]
+     16 | where
+     17 |     Self: HasScaleFactor + CanCalculateArea,
+        :                            ^^^^^^^^|^^^^^^^
+        :                                    `-- [1] this bound requires `Rectangle: CanCalculateArea` again
+     18 | {
+        `----
+        ,-[examples/src/cyclic_area.rs:40:9]
+     39 |     CanUseRectangle for Rectangle {
+     40 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- [2] unsatisfied trait bound
+     41 |     }
+        `----
+      help: Resolving `Rectangle: CanCalculateArea` leads back to itself, so the compiler gives up with an overflow that raising the recursion limit does not fix.
+            
+            Cycle:
+                `Rectangle: CanCalculateArea` (consumer trait)
+                → `Rectangle: AreaCalculator<Rectangle>` (delegated by `Rectangle`)
+                → `ScaledArea: AreaCalculator<Rectangle>` (provider trait)
+                → `Rectangle: CanCalculateArea` (consumer trait)
+            
+            To fix this error:
+                • Remove the bound `CanCalculateArea` from `ScaledArea` at examples/src/cyclic_area.rs:17; to call another provider, take it as a type parameter bounded by `AreaCalculator<Self>`
+                • Or delegate `AreaCalculatorComponent` to a provider that does not require `Rectangle: CanCalculateArea` in the `delegate_components!` block of `Rectangle`
+    ");
+}
+
+#[test]
+fn test_send_context_error() {
+    // The provider requires the context to be `Sync`, but a field of it is an `Rc`
+    let outputs = test_cgp_error_from_json("send_context.json", "send_context");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @r#"
+    CGP0015
+
+      x provider `FetchInBackground` requires the context `App` to be `Sync`
+        ,-[examples/src/send_context.rs:19:28]
+     18 | where
+     19 |     Self: HasDatabaseUrl + Sync,
+        :                            ^^|^
+        :                              `-- [1] `Sync` is required here
+     20 | {
+     21 |     fn fetch_user(&self, id: u64) -> String {
+     22 |         std::thread::scope(|scope| {
+     23 |             scope
+     24 |                 .spawn(|| format!("{}/users/{}", self.database_url(), id))
+     25 |                 .join()
+     26 |                 .unwrap()
+     27 |         })
+     28 |     }
+     29 | }
+     30 | 
+     31 | #[derive(HasField)]
+     32 | pub struct App {
+        :            ^|^
+        :             `-- [2] `App` contains `Rc<String>`
+     33 |     pub database_url: String,
+     34 |     pub cache: Rc<String>,
+     35 | }
+     36 | 
+     37 | delegate_components! {
+     38 |     App {
+     39 |         UserFetcherComponent:
+     40 |             FetchInBackground,
+     41 |     }
+     42 | }
+     43 | 
+     44 | check_components! {
+     45 |     CanUseApp for App {
+     46 |         UserFetcherComponent,
+        :         ^^^^^^^^^^|^^^^^^^^^
+        :                   `-- [3] `Rc<String>` cannot be shared between threads safely
+     47 |     }
+        `----
+      help: `App` contains `Rc<String>`, which is not `Sync`, so `App` is not `Sync` either.
+            
+            To fix this error:
+                • Replace `Rc<String>` in `App` with a type that is `Sync` (e.g., `Arc<String>`)
+                • Or, if `App` is not shared between threads, delegate `UserFetcherComponent` to a provider that does not require `Sync`
+    "#);
+}
+
+#[test]
+fn test_generic_context_error() {
+    // The context has a lifetime and a type parameter, which the chain spells out in full
+    let outputs = test_cgp_error_from_json("generic_context.json", "generic_context");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0001
+
+      x missing field `height` in the context `Rectangle<'a, u32>`.
+        ,-[examples/src/generic_context.rs:60:9]
+     59 |     <'a> CanUseRectangle for Rectangle<'a, u32> {
+     60 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- unsatisfied trait bound
+     61 |     }
+        `----
+      help: Context `Rectangle<'a, u32>` is missing a required field to use `AreaCalculatorComponent`.
+                note: Missing field: `height`
+            
+            The struct `Rectangle<'a, u32>` is defined at `examples/src/generic_context.rs:60` but does not have the required field `height`.
+            
+            Dependency chain:
+                `CanUseRectangle` for `Rectangle<'a, u32>` (check trait)
+                └─ consumer trait of `AreaCalculatorComponent` for `Rectangle<'a, u32>` (consumer trait)
+                   └─ `AreaCalculator<Rectangle<'a, u32>>` for provider `ScaledArea<RectangleArea>` (provider trait)
+                      ├─ `HasRectangleFields` for `Rectangle<'a, u32>` (getter trait)
+                      │  └─ field `height` on `Rectangle<'a, u32>` ✗
+                      └─ `AreaCalculator<Rectangle<'a, u32>>` for inner provider `RectangleArea` (provider trait) ✓
+            
+            The error in the higher-order provider `ScaledArea<RectangleArea>` might be caused by its inner provider `RectangleArea`.
+            
+            To fix this error:
+                • Add a field `height` to the `Rectangle<'a, u32>` struct at examples/src/generic_context.rs:60
+    ");
+}
+
+#[test]
+fn test_nested_higher_order_error() {
+    // `LoggedArea` wraps `ScaledArea`, which wraps the failing `RectangleArea`
+    let outputs = test_cgp_error_from_json("nested_area.json", "nested_area");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0001
+
+      x missing field `height` in the context `Rectangle`.
+        ,-[examples/src/nested_area.rs:70:9]
+     69 |     CanUseRectangle for Rectangle {
+     70 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- unsatisfied trait bound
+     71 |     }
+        `----
+      help: Context `Rectangle` is missing a required field to use `AreaCalculatorComponent`.
+                note: Missing field: `height`
+            
+            The struct `Rectangle` is defined at `examples/src/nested_area.rs:70` but does not have the required field `height`.
+            
+            Dependency chain:
+                `CanUseRectangle` for `Rectangle` (check trait)
+                └─ consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait)
+                   └─ `AreaCalculator<Rectangle>` for provider `LoggedArea<ScaledArea<RectangleArea>>` (provider trait)
+                      ├─ `HasRectangleFields` for `Rectangle` (getter trait)
+                      │  └─ field `height` on `Rectangle` ✗
+                      └─ `AreaCalculator<Rectangle>` for inner provider `RectangleArea` of `ScaledArea<RectangleArea>` (provider trait) ✓
+            
+            The error in the higher-order provider `LoggedArea<ScaledArea<RectangleArea>>` might be caused by its inner provider `RectangleArea`.
+            
+            To fix this error:
+                • Add a field `height` to the `Rectangle` struct at examples/src/nested_area.rs:70
+    ");
+}
+
+#[test]
+fn test_linked_chain_error() {
+    // The provider that fails the density check is traced down to the missing field by the
+    // separate check of the area, which is shown first as the root cause
+    let outputs = test_cgp_error_from_json("linked_density.json", "linked_density");
+
+    assert_eq!(outputs.len(), 2, "Expected 2 error messages");
+    assert!(outputs[0].contains("missing field `height`"));
+
+    assert_snapshot!(outputs[1], @"
+    CGP0004
+
+      x the trait bound `RectangleArea: AreaCalculator<Rectangle>` is not satisfied
+        ,-[examples/src/linked_density.rs:67:9]
+     66 |     CanUseRectangle for Rectangle {
+     67 |         DensityCalculatorComponent,
+        :         ^^^^^^^^^^^^^|^^^^^^^^^^^^
+        :                      `-- unsatisfied trait bound
+     68 |     }
+        `----
+      help: Dependency chain:
+              `CanUseRectangle` for `Rectangle` (check trait)
+              └─ consumer trait of `DensityCalculatorComponent` for `Rectangle` (consumer trait)
+                 └─ `DensityCalculator<Rectangle>` for provider `DensityFromMassField` (provider trait)
+                    └─ `CanCalculateArea` for `Rectangle` (consumer trait)
+                       └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)
+                          └─ `HasRectangleFields` for `Rectangle` (getter trait)
+                             └─ field `height` on `Rectangle` ✗
+            
+            The requirements below `AreaCalculator<Rectangle>` for provider `RectangleArea` are traced by the error at examples/src/linked_density.rs:73:9.
+    ");
+}
+
+#[test]
+fn test_promoted_lint_passthrough() {
+    // With `-D warnings`, an unused import that mentions `HasField` is an error, but not a
+    // CGP one, so it is never absorbed into the database
+    let db = load_diagnostic_database(format!(
+        "{}/../examples/src/promoted_lint.json",
+        env!("CARGO_MANIFEST_DIR")
+    ));
+    assert!(db.get_all_entries().is_empty());
+
+    let outputs = test_cgp_error_from_json("promoted_lint.json", "promoted_lint");
+    assert!(outputs.is_empty());
+}
+
+#[test]
+fn test_partial_check_error() {
+    // Only `MassGetterComponent` of the check block is wired correctly
+    let outputs = test_cgp_error_from_json("partial_check.json", "partial_check");
+
+    assert_eq!(outputs.len(), 1, "Expected 1 error message");
+
+    assert_snapshot!(outputs[0], @"
+    CGP0001
+
+      x missing field `height` in the context `Rectangle`.
+        ,-[examples/src/partial_check.rs:69:9]
+     68 |     CanUseRectangle for Rectangle {
+     69 |         AreaCalculatorComponent,
+        :         ^^^^^^^^^^^|^^^^^^^^^^^
+        :                    `-- [1] unsatisfied trait bound
+     70 |         DensityCalculatorComponent,
+        :         ^^^^^^^^^^^^^|^^^^^^^^^^^^
+        :                      `-- [2] unsatisfied trait bound
+     71 |         MassGetterComponent,
+        `----
+      help: Components checked by `CanUseRectangle`:
+                `AreaCalculatorComponent` ✗
+                `DensityCalculatorComponent` ✗
+                `MassGetterComponent` ✓
+            
+            Context `Rectangle` is missing a required field to use multiple components: `AreaCalculatorComponent` (see label [1]), `DensityCalculatorComponent` (see label [2]).
+                note: Missing field: `height`
+            
+            The struct `Rectangle` is defined at `examples/src/partial_check.rs:69` but does not have the required field `height`.
+            
+            Dependency chain:
+                `CanUseRectangle` for `Rectangle` (check trait)
+                ├─ `CanCalculateArea` for `Rectangle` (consumer trait)
+                │  └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)
+                │     └─ `HasRectangleFields` for `Rectangle` (getter trait)
+                │        └─ field `height` on `Rectangle` ✗
+                └─ consumer trait of `DensityCalculatorComponent` for `Rectangle` (consumer trait)
+                   └─ `DensityCalculator<Rectangle>` for provider `DensityFromMassField` (provider trait)
+                      └─ `CanCalculateArea` for `Rectangle` (consumer trait) (*)
+            
+            To fix this error:
+                • Add a field `height` to the `Rectangle` struct at examples/src/partial_check.rs:69
     ");
 }
@@ -0,0 +1,43 @@
+use cargo_cgp::expect_chain;
+
+#[test]
+fn test_base_area_chain() {
+    expect_chain!("../examples/src/base_area.json" =>
+        check "CanUseRectangle",
+        consumer "AreaCalculatorComponent",
+        provider "RectangleArea",
+        getter "HasRectangleFields",
+        field "heig",
+    );
+}
+
+#[test]
+fn test_density_chain() {
+    expect_chain!("../examples/src/density.json" =>
+        check "CanUseRectangle",
+        provider "DensityFromMassField",
+        consumer "CanCalculateArea",
+        provider "RectangleArea",
+    );
+}
+
+#[test]
+fn test_linked_density_chain() {
+    // The chain of the density check continues with the separate check of the area
+    expect_chain!("../examples/src/linked_density.json" =>
+        check "CanUseRectangle",
+        provider "DensityFromMassField",
+        provider "RectangleArea",
+        getter "HasRectangleFields",
+        field "height",
+    );
+}
+
+#[test]
+#[should_panic(expected = "No dependency chain")]
+fn test_missing_chain() {
+    expect_chain!("../examples/src/scaled_area_2.json" =>
+        check "CanUseRectangle",
+        field "height",
+    );
+}
@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use cargo_cgp::document_wiring::render_wiring_document;
+use cargo_cgp::workspace_scan::WorkspaceScan;
+use insta::assert_snapshot;
+
+/// Scans a single example file as if it were the whole workspace
+fn scan_example(file_name: &str) -> WorkspaceScan {
+    let file = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../examples/src")
+        .join(file_name);
+    let content = std::fs::read_to_string(&file).unwrap();
+
+    let mut scan = WorkspaceScan::new();
+    scan.scan_file(&file, Path::new(file_name), "examples", file_name, &content);
+    scan
+}
+
+#[test]
+fn test_document_density_wiring() {
+    let document = render_wiring_document(&scan_example("density.rs"), true);
+    assert_snapshot!(document, @r#"
+    # CGP wiring
+
+    Generated by `cargo cgp document-wiring` from the workspace sources.
+
+    ## `Rectangle`
+
+    | Component | Provider | Provider trait |
+    | --- | --- | --- |
+    | `AreaCalculatorComponent` | `RectangleArea` | `AreaCalculator` |
+    | `DensityCalculatorComponent` | `DensityFromMassField` | `DensityCalculator` |
+
+    ### Requirements
+
+    - `RectangleArea` requires:
+      - `HasRectangleFields`, reading fields:
+        - `width: f64`
+        - `height: f64` (missing)
+    - `DensityFromMassField` requires:
+      - `CanCalculateArea`, provided by `AreaCalculatorComponent`
+      - `HasMass`, reading fields:
+        - `mass: f64`
+
+    ### Wiring graph
+
+    ```mermaid
+    graph LR
+        n0["Rectangle"]
+        n1["RectangleArea"]
+        n2["HasRectangleFields"]
+        n3["width: f64"]
+        n4["height: f64 (missing)"]
+        n5["DensityFromMassField"]
+        n6["CanCalculateArea"]
+        n7["HasMass"]
+        n8["mass: f64"]
+        n0 -->|"AreaCalculatorComponent"| n1
+        n1 --> n2
+        n2 --> n3
+        n2 --> n4
+        n0 -->|"DensityCalculatorComponent"| n5
+        n5 --> n6
+        n6 -->|"AreaCalculatorComponent"| n1
+        n5 --> n7
+        n7 --> n8
+    ```
+    "#);
+}
+
+#[test]
+fn test_document_scaled_area_wiring() {
+    let document = render_wiring_document(&scan_example("scaled_area.rs"), false);
+    assert_snapshot!(document, @"
+    # CGP wiring
+
+    Generated by `cargo cgp document-wiring` from the workspace sources.
+
+    ## `Rectangle`
+
+    | Component | Provider | Provider trait |
+    | --- | --- | --- |
+    | `AreaCalculatorComponent` | `ScaledArea<RectangleArea>` | `AreaCalculator` |
+
+    ### Requirements
+
+    - `RectangleArea` requires:
+      - `HasRectangleFields`, reading fields:
+        - `width: f64`
+        - `height: f64` (missing)
+    - `ScaledArea<InnerCalculator>` requires:
+      - `HasScaleFactor`, reading fields:
+        - `scale_factor: f64`
+    ");
+}
@@ -0,0 +1,228 @@
+// The snapshots are rendered with miette
+#![cfg(feature = "graphical")]
+
+use cargo_cgp::test_utils::diff_cgp_error_from_json;
+use insta::assert_snapshot;
+
+// Lines prefixed with `-` are information in rustc's message that the improved message
+// does not show. Review any new `-` line before accepting a snapshot change.
+
+#[test]
+fn test_base_area_diff() {
+    let diff = diff_cgp_error_from_json("base_area.json");
+
+    assert_snapshot!(diff.render(), @"
+    - help: the trait `HasField<Symbol<6, cgp::prelude::Chars<'h', cgp::prelude::Chars<'e', cgp::prelude::Chars<'i', cgp::prelude::Chars<'g', cgp::prelude::Chars<_, cgp::prelude::Chars<'t', Nil>>>>>>>>` is not implemented for `Rectangle`
+    - but trait `HasField<Symbol<5, cgp::prelude::Chars<'w', cgp::prelude::Chars<'i', cgp::prelude::Chars<'d', cgp::prelude::Chars<'t', cgp::prelude::Chars<_, Nil>>>>>>>` is implemented for it
+    - examples/src/base_area.rs:25:10
+    - #[derive(HasField)]
+    - examples/src/base_area.rs:8:1
+    - #[cgp_auto_getter]
+    - examples/src/base_area.rs:15:1
+    - #[cgp_impl(new RectangleArea)]
+    - unsatisfied trait bound introduced here
+    - examples/src/base_area.rs:39:1
+    - check_components! {
+    - note: this error originates in the derive macro `HasField` which comes from the expansion of the macro `check_components` (in Nightly builds, run with -Z macro-backtrace for more info)
+    + CGP0001
+    + x missing field `heig�t` in the context `Rectangle`.
+    + note: Missing field: `heig�t`
+    + note: some characters in the field name are hidden by the compiler and shown as '�'
+    + The struct `Rectangle` is defined at `examples/src/base_area.rs:41` but does not have the required field `heig�t`.
+    + Dependency chain:
+    + └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)
+    + └─ field `heig�t` on `Rectangle` ✗
+    + To fix this error:
+    + • Add a field `heig�t` to the `Rectangle` struct at examples/src/base_area.rs:41
+    ");
+}
+
+#[test]
+fn test_base_area_2_diff() {
+    let diff = diff_cgp_error_from_json("base_area_2.json");
+
+    assert_snapshot!(diff.render(), @"
+    - examples/src/base_area_2.rs:27:1
+    - examples/src/base_area_2.rs:8:1
+    - #[cgp_auto_getter]
+    - examples/src/base_area_2.rs:15:1
+    - #[cgp_impl(new RectangleArea)]
+    - unsatisfied trait bound introduced here
+    - examples/src/base_area_2.rs:39:1
+    - check_components! {
+    - note: this error originates in the attribute macro `cgp_auto_getter` which comes from the expansion of the macro `check_components` (in Nightly builds, run with -Z macro-backtrace for more info)
+    + CGP0003
+    + x missing field `width` or `#[derive(HasField)]` in the context `Rectangle`.
+    + note: Missing field: `width` or struct needs `#[derive(HasField)]`
+    + The struct `Rectangle` is defined at `examples/src/base_area_2.rs:41` but does not have the required field `width`.
+    + Dependency chain:
+    + └─ `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)
+    + └─ field `width` on `Rectangle` ✗
+    + To fix this error:
+    + • If the struct has the field `width`, add `#[derive(HasField)]` to the struct definition at `examples/src/base_area_2.rs:41`
+    + • If the field is missing, add a `width` field to the struct
+    ");
+}
+
+#[test]
+fn test_density_diff() {
+    let diff = diff_cgp_error_from_json("density.json");
+
+    assert_snapshot!(diff.render(), @"
+    - examples/src/density.rs:25:1
+    - #[cgp_impl(new RectangleArea)]
+    - examples/src/density.rs:3:1
+    - #[cgp_component(AreaCalculator)]
+    - examples/src/density.rs:35:1
+    - #[cgp_impl(new DensityFromMassField)]
+    - Self: CanCalculateArea + HasMass,
+    - unsatisfied trait bound introduced here
+    - examples/src/density.rs:62:1
+    - note: this error originates in the attribute macro `cgp_impl` which comes from the expansion of the macro `check_components` (in Nightly builds, run with -Z macro-backtrace for more info)
+    + CGP0004
+    + help: Dependency chain:
+    + └─ `DensityCalculator<Rectangle>` for provider `DensityFromMassField` (provider trait)
+    + Add a check that `Rectangle` can use `CalculateAreaComponent` using `check_components!` to get further details on the missing dependencies.
+    ");
+}
+
+#[test]
+fn test_density_2_diff() {
+    let diff = diff_cgp_error_from_json("density_2.json");
+
+    assert_snapshot!(diff.render(), @"
+    - examples/src/density_2.rs:35:1
+    - #[cgp_impl(new ScaledArea<InnerCalculator>)]
+    - help: the trait `AreaCalculator<__Context__>` is implemented for `ScaledArea<InnerCalculator>`
+    - examples/src/density_2.rs:3:1
+    - #[cgp_component(AreaCalculator)]
+    - examples/src/density_2.rs:51:1
+    - #[cgp_impl(new DensityFromMassField)]
+    - Self: CanCalculateArea + HasMass,
+    - unsatisfied trait bound introduced here
+    - examples/src/density_2.rs:78:1
+    - note: this error originates in the attribute macro `cgp_impl` which comes from the expansion of the macro `check_components` (in Nightly builds, run with -Z macro-backtrace for more info)
+    + CGP0004
+    + help: Dependency chain:
+    + └─ `DensityCalculator<Rectangle>` for provider `DensityFromMassField` (provider trait)
+    + Add a check that `Rectangle` can use `CalculateAreaComponent` using `check_components!` to get further details on the missing dependencies.
+    ");
+}
+
+#[test]
+fn test_density_3_diff() {
+    let diff = diff_cgp_error_from_json("density_3.json");
+
+    assert_snapshot!(diff.render(), @"
+    - unsatisfied trait bound
+    - help: the trait `cgp::prelude::HasField<Symbol<6, cgp::prelude::Chars<'h', cgp::prelude::Chars<'e', cgp::prelude::Chars<'i', cgp::prelude::Chars<'g', cgp::prelude::Chars<'h', cgp::prelude::Chars<'t', Nil>>>>>>>>` is not implemented for `Rectangle`
+    - examples/src/density_3.rs:46:1
+    - help: the following other types implement trait `cgp::prelude::HasField<Tag>`
+    - examples/src/density_3.rs:45:10
+    - #[derive(HasField)]
+    - `Rectangle` implements `HasField<Symbol<4, Chars<'m', ...>>>`
+    - `Rectangle` implements `HasField<Symbol<5, Chars<'w', ...>>>`
+    - examples/src/density_3.rs:13:1
+    - #[cgp_auto_getter]
+    - examples/src/density_3.rs:25:1
+    - #[cgp_impl(new RectangleArea)]
+    - unsatisfied trait bound introduced here
+    - examples/src/density_3.rs:64:1
+    - check_components! {
+    - note: the full name for the type has been written to '/home/soares/development/cgp-error-improvement/target/debug/deps/cgp_error_messages_example-8e13d63a65e6d8bf.long-type-3432498361301212235.txt'
+    - note: consider using `--verbose` to print the full type name to the console
+    - note: this error originates in the derive macro `HasField` which comes from the expansion of the macro `check_components` (in Nightly builds, run with -Z macro-backtrace for more info)
+    - examples/src/density_3.rs:67:9
+    - examples/src/density_3.rs:3:1
+    - #[cgp_component(AreaCalculator)]
+    - examples/src/density_3.rs:35:1
+    - #[cgp_impl(new DensityFromMassField)]
+    - Self: CanCalculateArea + HasMass,
+    - note: the full name for the type has been written to '/home/soares/development/cgp-error-improvement/target/debug/deps/cgp_error_messages_example-8e13d63a65e6d8bf.long-type-14628059510759430129.txt'
+    - note: this error originates in the attribute macro `cgp_impl` which comes from the expansion of the macro `check_components` (in Nightly builds, run with -Z macro-backtrace for more info)
+    + CGP0001
+    + x missing field `height` in the context `Rectangle`.
+    + [1] unsatisfied trait bound
+    + [2] unsatisfied trait bound
+    + note: Missing field: `height`
+    + The struct `Rectangle` is defined at `examples/src/density_3.rs:66` but does not have the required field `height`.
+    + Dependency chain:
+    + │ └─ field `height` on `Rectangle` ✗
+    + └─ `DensityCalculator<Rectangle>` for provider `DensityFromMassField` (provider trait)
+    + To fix this error:
+    + • Add a field `height` to the `Rectangle` struct at examples/src/density_3.rs:66
+    ");
+}
+
+#[test]
+fn test_scaled_area_diff() {
+    let diff = diff_cgp_error_from_json("scaled_area.json");
+
+    assert_snapshot!(diff.render(), @"
+    - examples/src/scaled_area.rs:15:1
+    - #[cgp_impl(new RectangleArea)]
+    - examples/src/scaled_area.rs:30:1
+    - #[cgp_impl(new ScaledArea<InnerCalculator>)]
+    - InnerCalculator: AreaCalculator<Self>,
+    - unsatisfied trait bound introduced here
+    - examples/src/scaled_area.rs:56:1
+    - check_components! {
+    - note: this error originates in the attribute macro `cgp_impl` which comes from the expansion of the macro `check_components` (in Nightly builds, run with -Z macro-backtrace for more info)
+    - help: the trait `cgp::prelude::HasField<Symbol<6, cgp::prelude::Chars<'h', cgp::prelude::Chars<'e', cgp::prelude::Chars<'i', cgp::prelude::Chars<'g', cgp::prelude::Chars<'h', cgp::prelude::Chars<'t', Nil>>>>>>>>` is not implemented for `Rectangle`
+    - examples/src/scaled_area.rs:42:1
+    - help: the following other types implement trait `cgp::prelude::HasField<Tag>`
+    - examples/src/scaled_area.rs:41:10
+    - #[derive(HasField)]
+    - `Rectangle` implements `HasField<Symbol<12, Chars<'s', Chars<'c', Chars<'a', Chars<'l', ...>>>>>>`
+    - `Rectangle` implements `HasField<Symbol<5, Chars<'w', Chars<'i', Chars<'d', Chars<'t', ...>>>>>>`
+    - examples/src/scaled_area.rs:8:1
+    - #[cgp_auto_getter]
+    - note: 1 redundant requirement hidden
+    - note: the full name for the type has been written to '/home/soares/development/cgp-error-improvement/target/debug/deps/cgp_error_messages_example-8e13d63a65e6d8bf.long-type-15971751320143258672.txt'
+    - note: consider using `--verbose` to print the full type name to the console
+    - note: this error originates in the derive macro `HasField` which comes from the expansion of the macro `check_components` (in Nightly builds, run with -Z macro-backtrace for more info)
+    + CGP0001
+    + x missing field `height` in the context `Rectangle`.
+    + note: Missing field: `height`
+    + The struct `Rectangle` is defined at `examples/src/scaled_area.rs:58` but does not have the required field `height`.
+    + Dependency chain:
+    + │ └─ field `height` on `Rectangle` ✗
+    + To fix this error:
+    + • Add a field `height` to the `Rectangle` struct at examples/src/scaled_area.rs:58
+    ");
+}
+
+#[test]
+fn test_scaled_area_2_diff() {
+    let diff = diff_cgp_error_from_json("scaled_area_2.json");
+
+    assert_snapshot!(diff.render(), @"
+    - help: the trait `cgp::prelude::HasField<Symbol<12, cgp::prelude::Chars<'s', cgp::prelude::Chars<'c', cgp::prelude::Chars<'a', cgp::prelude::Chars<'l', cgp::prelude::Chars<'e', cgp::prelude::Chars<'_', cgp::prelude::Chars<'f', cgp::prelude::Chars<'a', cgp::prelude::Chars<'c', cgp::prelude::Chars<'t', cgp::prelude::Chars<'o', cgp::prelude::Chars<'r', Nil>>>>>>>>>>>>>>` is not implemented for `Rectangle`
+    - examples/src/scaled_area_2.rs:42:1
+    - help: the following other types implement trait `cgp::prelude::HasField<Tag>`
+    - examples/src/scaled_area_2.rs:41:10
+    - #[derive(HasField)]
+    - `Rectangle` implements `HasField<Symbol<5, Chars<'w', Chars<'i', ...>>>>`
+    - `Rectangle` implements `HasField<Symbol<6, Chars<'h', Chars<'e', ...>>>>`
+    - examples/src/scaled_area_2.rs:25:1
+    - #[cgp_auto_getter]
+    - examples/src/scaled_area_2.rs:30:1
+    - #[cgp_impl(new ScaledArea<InnerCalculator>)]
+    - unsatisfied trait bound introduced here
+    - examples/src/scaled_area_2.rs:56:1
+    - check_components! {
+    - note: the full name for the type has been written to '/home/soares/development/cgp-error-improvement/target/debug/deps/cgp_error_messages_example-8e13d63a65e6d8bf.long-type-8046747906968115155.txt'
+    - note: consider using `--verbose` to print the full type name to the console
+    - note: this error originates in the derive macro `HasField` which comes from the expansion of the macro `check_components` (in Nightly builds, run with -Z macro-backtrace for more info)
+    + CGP0001
+    + x missing field `scale_factor` in the context `Rectangle`.
+    + note: Missing field: `scale_factor`
+    + The struct `Rectangle` is defined at `examples/src/scaled_area_2.rs:58` but does not have the required field `scale_factor`.
+    + Dependency chain:
+    + └─ `AreaCalculator<Rectangle>` for provider `ScaledArea<RectangleArea>` (provider trait)
+    + └─ field `scale_factor` on `Rectangle` ✗
+    + To fix this error:
+    + (only `ScaledArea` requires the field `scale_factor`, so this also drops the behavior of `ScaledArea`)
+    + • Add a field `scale_factor` to the `Rectangle` struct at examples/src/scaled_area_2.rs:58
+    ");
+}
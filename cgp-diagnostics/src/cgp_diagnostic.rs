@@ -0,0 +1,447 @@
+#[cfg(feature = "graphical")]
+use miette::{Diagnostic, LabeledSpan, NamedSource};
+use serde::Serialize;
+#[cfg(feature = "graphical")]
+use std::cell::OnceCell;
+use std::fmt;
+use std::path::PathBuf;
+
+use cargo_metadata::diagnostic::{Diagnostic as RustcDiagnostic, DiagnosticSpan};
+use cargo_metadata::{PackageId, Target};
+
+use crate::error_formatting::DependencyNode;
+use crate::source_snippet::{PathRemap, remap_path};
+#[cfg(feature = "graphical")]
+use crate::source_snippet::{SnippetSource, build_source_and_labels};
+use crate::symbol_display::display_symbols;
+
+/// How severe a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Advice,
+    Warning,
+    Error,
+}
+
+#[cfg(feature = "graphical")]
+impl From<Severity> for miette::Severity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Advice => miette::Severity::Advice,
+            Severity::Warning => miette::Severity::Warning,
+            Severity::Error => miette::Severity::Error,
+        }
+    }
+}
+
+/// A source location highlighted by a diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpanLocation {
+    /// File path as reported by rustc (usually relative to the workspace root)
+    pub file: String,
+    /// Start line (1-indexed)
+    pub line_start: usize,
+    /// Start column (1-indexed)
+    pub column_start: usize,
+    /// End line (1-indexed)
+    pub line_end: usize,
+    /// End column (1-indexed, exclusive)
+    pub column_end: usize,
+    /// Label shown next to the highlighted code
+    pub label: String,
+    /// Source lines covered by the span, as embedded in rustc's JSON output
+    /// Used to render a snippet when the source file cannot be read
+    #[serde(skip)]
+    pub text: Vec<String>,
+    /// Local path to read the source from, when it differs from `file`
+    /// (e.g., after applying path remapping rules)
+    #[serde(skip)]
+    pub source_path: Option<PathBuf>,
+}
+
+/// Macros whose generated code is reported at the user's invocation instead
+const WIRING_MACROS: [&str; 2] = ["check_components!", "delegate_components!"];
+
+impl SpanLocation {
+    /// Converts a rustc span, using `default_label` if the span has no label
+    /// Spans in code generated by the wiring macros are moved to the macro invocation
+    pub fn from_span(span: &DiagnosticSpan, default_label: &str) -> Self {
+        let label = span
+            .label
+            .clone()
+            .unwrap_or_else(|| default_label.to_string());
+        let span = user_visible_span(span);
+
+        SpanLocation {
+            file: span.file_name.clone(),
+            line_start: span.line_start,
+            column_start: span.column_start,
+            line_end: span.line_end,
+            column_end: span.column_end,
+            label,
+            text: span.text.iter().map(|line| line.text.clone()).collect(),
+            source_path: None,
+        }
+    }
+}
+
+/// Follows the expansion of a span out of the wiring macros until it points at code the
+/// user wrote: tokens passed to the macro are kept, while generated code (e.g., from
+/// another crate's source, or outside the invocation) is replaced by the invocation
+fn user_visible_span(span: &DiagnosticSpan) -> &DiagnosticSpan {
+    let mut current = span;
+    while let Some(expansion) = current.expansion.as_deref()
+        && WIRING_MACROS.contains(&expansion.macro_decl_name.as_str())
+        && !contains(&expansion.span, current)
+    {
+        current = &expansion.span;
+    }
+    current
+}
+
+/// Returns true if the `inner` span lies within the `outer` span
+fn contains(outer: &DiagnosticSpan, inner: &DiagnosticSpan) -> bool {
+    outer.file_name == inner.file_name
+        && (outer.line_start, outer.column_start) <= (inner.line_start, inner.column_start)
+        && (inner.line_end, inner.column_end) <= (outer.line_end, outer.column_end)
+}
+
+/// The kind of wiring construct that a diagnostic involves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WiringKind {
+    /// The `check_components!` block generating the check trait
+    CheckBlock,
+    /// The entry of a component in `delegate_components!`
+    DelegationEntry,
+    /// The `#[cgp_impl]` implementation of a provider
+    ProviderImpl,
+    /// The declaration of a getter trait
+    GetterTrait,
+    /// The definition of the context struct
+    ContextStruct,
+}
+
+/// A wiring construct involved in a diagnostic, located in the workspace sources, so that
+/// editors can annotate the construct (e.g., "2 CGP errors involve this delegation")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WiringLocation {
+    pub kind: WiringKind,
+    /// The name of the construct (e.g., "CanUseRectangle" or "AreaCalculatorComponent")
+    pub name: String,
+    /// File path relative to the workspace root, as rustc displays it
+    pub file: String,
+    /// Line (1-indexed)
+    pub line: usize,
+    /// Column (1-indexed)
+    pub column: usize,
+}
+
+/// The compiler message that an improved diagnostic was built from, which the `json`
+/// output format forwards with the improved text as its rendering
+#[derive(Debug, Clone)]
+pub struct CompilerOrigin {
+    pub package_id: PackageId,
+    pub target: Target,
+    pub diagnostic: RustcDiagnostic,
+}
+
+/// A CGP-aware diagnostic, which implements miette's Diagnostic trait with the `graphical`
+/// feature
+#[derive(Debug, Clone)]
+pub struct CgpDiagnostic {
+    /// The main error message
+    pub message: String,
+    /// Error code, from the CGP error catalog (e.g., "CGP0001") or the compiler (e.g., "E0277")
+    pub code: Option<String>,
+    /// Help text with suggestions
+    pub help: Option<String>,
+    /// Source locations to highlight
+    pub spans: Vec<SpanLocation>,
+    /// Severity of the diagnostic (None is rendered as an error)
+    pub severity: Option<Severity>,
+    /// Dependency tree behind the error, for machine-readable outputs
+    pub dependency_tree: Option<DependencyNode>,
+    /// The context type that the error is about, if known
+    pub context_type: Option<String>,
+    /// The wiring constructs involved in the error that were found in the workspace sources
+    pub wiring: Vec<WiringLocation>,
+    /// The compiler diagnostics merged into this one, as `file:line:column: message`
+    /// (shown with `--verbose`)
+    pub merged: Vec<String>,
+    /// rustc's rendering of the compiler diagnostics behind this one (`--raw-notes`)
+    pub raw_notes: Vec<String>,
+    /// The compiler message this diagnostic improves, if it was built from one
+    pub origin: Option<CompilerOrigin>,
+    /// Source code with file name and labeled spans, loaded from disk on first use
+    /// Machine-readable outputs only need the span locations and never load the source
+    #[cfg(feature = "graphical")]
+    snippet: OnceCell<(Option<NamedSource<SnippetSource>>, Vec<LabeledSpan>)>,
+}
+
+impl CgpDiagnostic {
+    /// The spans are sorted into canonical order, see `sort_spans`
+    pub fn new(message: String, mut spans: Vec<SpanLocation>) -> Self {
+        sort_spans(&mut spans);
+
+        CgpDiagnostic {
+            message,
+            code: None,
+            help: None,
+            spans,
+            severity: None,
+            dependency_tree: None,
+            context_type: None,
+            wiring: Vec::new(),
+            merged: Vec::new(),
+            raw_notes: Vec::new(),
+            origin: None,
+            #[cfg(feature = "graphical")]
+            snippet: OnceCell::new(),
+        }
+    }
+
+    /// Applies path remapping rules to the spans, so that snippets are read from the
+    /// local checkout while the reported file names stay unchanged
+    pub fn remap_source_paths(&mut self, remaps: &[PathRemap]) {
+        for span in &mut self.spans {
+            if let Some(local_path) = remap_path(remaps, &span.file) {
+                span.source_path = Some(local_path);
+            }
+        }
+
+        // Discard any snippet loaded from the old paths
+        #[cfg(feature = "graphical")]
+        {
+            self.snippet = OnceCell::new();
+        }
+    }
+
+    /// Replaces path prefixes in the spans and the text of the diagnostic, e.g. the
+    /// workspace root with nothing to make paths relative (`--normalize-output`)
+    /// The spans keep reading their source from the original paths
+    pub fn normalize_paths(&mut self, prefixes: &[(String, String)]) {
+        let replace = |text: &str| {
+            prefixes
+                .iter()
+                .fold(text.to_string(), |text, (prefix, replacement)| {
+                    text.replace(prefix.as_str(), replacement)
+                })
+        };
+
+        for span in &mut self.spans {
+            let file = replace(&span.file);
+            if file != span.file {
+                span.source_path
+                    .get_or_insert_with(|| PathBuf::from(&span.file));
+                span.file = file;
+            }
+            span.label = replace(&span.label);
+        }
+        for location in &mut self.wiring {
+            location.file = replace(&location.file);
+        }
+        self.message = replace(&self.message);
+        self.help = self.help.as_deref().map(replace);
+        self.merged = self.merged.iter().map(|line| replace(line)).collect();
+    }
+
+    /// Rewrites the `Symbol` types left in the text of the diagnostic as `Symbol!("name")`,
+    /// including the text copied from compiler messages
+    /// The raw notes stay as rustc reported them.
+    pub fn display_symbols(&mut self) {
+        self.message = display_symbols(&self.message);
+        self.help = self.help.as_deref().map(display_symbols);
+        for span in &mut self.spans {
+            span.label = display_symbols(&span.label);
+        }
+        if let Some(tree) = &mut self.dependency_tree {
+            tree.display_symbols();
+        }
+        self.merged = self
+            .merged
+            .iter()
+            .map(|line| display_symbols(line))
+            .collect();
+    }
+
+    /// Returns the source code and labels, reading the source file on first use
+    #[cfg(feature = "graphical")]
+    fn snippet(&self) -> &(Option<NamedSource<SnippetSource>>, Vec<LabeledSpan>) {
+        self.snippet
+            .get_or_init(|| build_source_and_labels(&self.spans))
+    }
+}
+
+/// Sorts spans by file, line and column, so that labels do not depend on the order in
+/// which rustc reported them
+/// The label ID of a span is its 1-based position in this order
+pub fn sort_spans(spans: &mut [SpanLocation]) {
+    spans.sort_by(|a, b| {
+        (&a.file, a.line_start, a.column_start).cmp(&(&b.file, b.line_start, b.column_start))
+    });
+}
+
+/// Returns the ID of the label whose highlighted code is exactly `text`, if there is one
+pub fn label_id_of(spans: &[SpanLocation], text: &str) -> Option<usize> {
+    spans
+        .iter()
+        .position(|span| {
+            span.line_start == span.line_end
+                && span.text.first().and_then(|line| {
+                    line.get(span.column_start.saturating_sub(1)..span.column_end.saturating_sub(1))
+                }) == Some(text)
+        })
+        .map(|index| index + 1)
+}
+
+impl fmt::Display for CgpDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CgpDiagnostic {}
+
+#[cfg(feature = "graphical")]
+impl Diagnostic for CgpDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        // Only CGP error codes are shown, since they can be looked up with `cargo cgp explain`
+        // Compiler codes like "E0277" are not useful for framework-specific trait bound failures
+        self.code
+            .as_ref()
+            .filter(|code| code.starts_with("CGP"))
+            .map(|code| Box::new(code.clone()) as Box<dyn fmt::Display>)
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        self.severity.map(miette::Severity::from)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.help
+            .as_ref()
+            .map(|h| Box::new(h.clone()) as Box<dyn fmt::Display>)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.snippet()
+            .0
+            .as_ref()
+            .map(|s| s as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let labels = &self.snippet().1;
+        if labels.is_empty() {
+            None
+        } else {
+            Some(Box::new(labels.clone().into_iter()))
+        }
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        None // We'll add related diagnostics through help/notes text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(
+        file_name: &str,
+        (line_start, column_start): (usize, usize),
+        (line_end, column_end): (usize, usize),
+        expansion: serde_json::Value,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "file_name": file_name,
+            "byte_start": 0,
+            "byte_end": 1,
+            "line_start": line_start,
+            "line_end": line_end,
+            "column_start": column_start,
+            "column_end": column_end,
+            "is_primary": true,
+            "text": [],
+            "label": "unsatisfied trait bound",
+            "suggested_replacement": null,
+            "suggestion_applicability": null,
+            "expansion": expansion
+        })
+    }
+
+    fn expanded_span(
+        file_name: &str,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> DiagnosticSpan {
+        let invocation = span("src/lib.rs", (39, 1), (43, 2), serde_json::Value::Null);
+        let expansion = serde_json::json!({
+            "span": invocation,
+            "macro_decl_name": "check_components!",
+            "def_site_span": null
+        });
+        serde_json::from_value(span(file_name, start, end, expansion)).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_paths() {
+        let span = SpanLocation {
+            file: "/work/shapes/src/lib.rs".to_string(),
+            line_start: 3,
+            column_start: 1,
+            line_end: 3,
+            column_end: 5,
+            label: "unsatisfied trait bound".to_string(),
+            text: Vec::new(),
+            source_path: None,
+        };
+        let mut diagnostic = CgpDiagnostic::new(
+            "missing field `height` in the context `Rectangle`.".to_string(),
+            vec![span],
+        );
+        diagnostic.help = Some("required by /home/me/.cargo/registry/cgp/src/lib.rs:7".to_string());
+
+        diagnostic.normalize_paths(&[
+            ("/work/".to_string(), String::new()),
+            ("/home/me/.cargo/".to_string(), "$CARGO_HOME/".to_string()),
+        ]);
+        assert_eq!(diagnostic.spans[0].file, "shapes/src/lib.rs");
+        assert_eq!(
+            diagnostic.spans[0].source_path,
+            Some(PathBuf::from("/work/shapes/src/lib.rs"))
+        );
+        assert_eq!(
+            diagnostic.help.as_deref(),
+            Some("required by $CARGO_HOME/registry/cgp/src/lib.rs:7")
+        );
+    }
+
+    #[test]
+    fn test_from_span_in_wiring_macro() {
+        // Tokens passed to the macro keep their own location
+        let location =
+            SpanLocation::from_span(&expanded_span("src/lib.rs", (41, 9), (41, 32)), "unused");
+        assert_eq!(
+            (
+                location.line_start,
+                location.column_start,
+                location.column_end
+            ),
+            (41, 9, 32)
+        );
+
+        // Generated code is reported at the invocation, with the label of the original span
+        for location in [
+            SpanLocation::from_span(&expanded_span("src/lib.rs", (12, 1), (12, 20)), "unused"),
+            SpanLocation::from_span(&expanded_span("<cgp macros>", (41, 9), (41, 32)), "unused"),
+        ] {
+            assert_eq!(location.file, "src/lib.rs");
+            assert_eq!((location.line_start, location.column_start), (39, 1));
+            assert_eq!((location.line_end, location.column_end), (43, 2));
+            assert_eq!(location.label, "unsatisfied trait bound");
+        }
+    }
+}
@@ -0,0 +1,1867 @@
+/// Module for detecting and extracting CGP-specific patterns from compiler diagnostics
+/// This module only patterns match on CGP library constructs, never on user code
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel, DiagnosticSpan};
+
+use crate::field_name::HIDDEN_CHAR;
+use crate::symbol_cache::SymbolCache;
+use crate::type_expr::{generic_arguments_at, normalize_type};
+
+/// Checks if a diagnostic is related to CGP constructs
+pub fn is_cgp_diagnostic(diagnostic: &Diagnostic) -> bool {
+    is_cgp_diagnostic_with(diagnostic, &[])
+}
+
+/// Checks if a diagnostic is related to CGP constructs or mentions one of the extra
+/// patterns configured in `cgp.toml`
+pub fn is_cgp_diagnostic_with(diagnostic: &Diagnostic, extra_patterns: &[String]) -> bool {
+    // Lints are never CGP errors, even when `-D warnings` promotes them to the error level
+    // (e.g., an unused import of `HasField`)
+    if lint_name(diagnostic).is_some() {
+        return false;
+    }
+
+    let cgp_patterns = [
+        "CanUseComponent",
+        "IsProviderFor",
+        "HasField",
+        "cgp_impl",
+        "cgp_component",
+        "cgp_auto_getter",
+        "delegate_components",
+        "check_components",
+    ];
+    let matches = |message: &str| {
+        cgp_patterns.iter().any(|p| message.contains(p))
+            || extra_patterns.iter().any(|p| message.contains(p.as_str()))
+    };
+
+    // Check main message
+    if matches(&diagnostic.message) {
+        return true;
+    }
+
+    // Check children messages
+    if diagnostic
+        .children
+        .iter()
+        .any(|child| matches(&child.message))
+    {
+        return true;
+    }
+
+    // Errors at a usage site (e.g., a call to a function bounded by a consumer trait) may
+    // only show that the unsatisfied impls come from CGP macros
+    has_cgp_macro_expansion(diagnostic)
+}
+
+/// Returns the lint that reported a diagnostic (e.g., "unused_imports"), which rustc gives
+/// as its code in place of an error code such as "E0277"
+/// Lints promoted to errors keep their name, which tells them apart from hard errors
+pub fn lint_name(diagnostic: &Diagnostic) -> Option<&str> {
+    let code = diagnostic.code.as_ref()?.code.as_str();
+    let is_error_code = code.len() == 5
+        && code.starts_with('E')
+        && code[1..].bytes().all(|byte| byte.is_ascii_digit());
+    (!is_error_code).then_some(code)
+}
+
+/// Returns true if a span of the diagnostic or of its children is expanded from a CGP macro
+/// (e.g., a note pointing at `#[cgp_component]`)
+fn has_cgp_macro_expansion(diagnostic: &Diagnostic) -> bool {
+    let cgp_macros = [
+        "cgp_impl",
+        "cgp_component",
+        "cgp_provider",
+        "cgp_auto_getter",
+    ];
+
+    diagnostic
+        .spans
+        .iter()
+        .any(|span| cgp_macros.iter().any(|name| is_expanded_from(span, name)))
+        || diagnostic.children.iter().any(has_cgp_macro_expansion)
+}
+
+/// Extracts the bound from a "the trait bound `Type: Trait` is not satisfied" message
+pub fn extract_unsatisfied_bound(message: &str) -> Option<&str> {
+    let start = message.find("the trait bound `")? + "the trait bound `".len();
+    let end = message[start..].find("` is not satisfied")?;
+    Some(&message[start..start + end])
+}
+
+/// Returns true if a diagnostic mentions the trait of an unsatisfied bound,
+/// e.g., in a "required for `Type` to implement `Trait`" note
+pub fn mentions_bound_trait(diagnostic: &Diagnostic, bound: &str) -> bool {
+    let Some((_, trait_name)) = bound.split_once(": ") else {
+        return false;
+    };
+    let quoted = format!("`{}`", trait_name);
+
+    diagnostic.message.contains(&quoted)
+        || diagnostic
+            .children
+            .iter()
+            .any(|child| child.message.contains(&quoted))
+}
+
+/// The outcome of parsing a bracketed type in a compiler message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseOutcome<T> {
+    /// The brackets are balanced and no part of the type is elided
+    Complete(T),
+    /// The message ends before the brackets are closed, or the compiler elided part of
+    /// the type with `...`; the value is a best-effort result
+    Truncated(T),
+    /// Nothing could be parsed
+    Failed,
+}
+
+impl<T> ParseOutcome<T> {
+    /// The parsed value, complete or not
+    pub fn value(self) -> Option<T> {
+        match self {
+            ParseOutcome::Complete(value) | ParseOutcome::Truncated(value) => Some(value),
+            ParseOutcome::Failed => None,
+        }
+    }
+
+    /// Maps the parsed value, keeping whether it is complete
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ParseOutcome<U> {
+        match self {
+            ParseOutcome::Complete(value) => ParseOutcome::Complete(f(value)),
+            ParseOutcome::Truncated(value) => ParseOutcome::Truncated(f(value)),
+            ParseOutcome::Failed => ParseOutcome::Failed,
+        }
+    }
+
+    /// The parsed value, only if it is complete
+    pub fn complete(self) -> Option<T> {
+        match self {
+            ParseOutcome::Complete(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// How far the parsed value can be trusted
+    pub fn confidence(&self) -> Confidence {
+        match self {
+            ParseOutcome::Complete(_) => Confidence::High,
+            ParseOutcome::Truncated(_) | ParseOutcome::Failed => Confidence::Low,
+        }
+    }
+}
+
+/// How far a name extracted from a compiler message can be trusted
+/// Names from truncated or elided types may be missing a part, so they are only used
+/// when no complete name is available, and never to suggest code changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Confidence {
+    Low,
+    #[default]
+    High,
+}
+
+impl Confidence {
+    /// The confidence of a name that the compiler may have elided with `...`
+    fn of_name(name: &str) -> Self {
+        if name.contains("...") {
+            Confidence::Low
+        } else {
+            Confidence::High
+        }
+    }
+}
+
+/// Information about a component extracted from CGP patterns
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ComponentInfo {
+    /// Full component type name (e.g., "AreaCalculatorComponent", "ScaledArea<RectangleArea>")
+    pub component_type: String,
+    /// Provider trait name derived from component (e.g., "AreaCalculator" from "AreaCalculatorComponent")
+    pub provider_trait: Option<String>,
+    /// Whether the component name was parsed completely
+    pub confidence: Confidence,
+}
+
+/// Information about a field extracted from HasField patterns
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldInfo {
+    /// The field name extracted from Symbol pattern
+    pub field_name: String,
+    /// Whether the field name was fully extracted (false if truncated)
+    pub is_complete: bool,
+    /// Whether the field name contains unknown characters (shown as �)
+    pub has_unknown_chars: bool,
+    /// The struct/type that is missing the field
+    pub target_type: String,
+}
+
+/// Information about an unsized type that a provider requires to be `Sized`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SizedInfo {
+    /// The type whose size is unknown (e.g., "str" or "dyn Display")
+    pub unsized_type: String,
+    /// The type parameter of the provider implementation that is bound to the unsized type
+    pub type_parameter: Option<String>,
+    /// Where the type parameter is declared (e.g., "src/lib.rs:31")
+    pub parameter_location: Option<String>,
+    /// The item that has the implicit `Sized` bound, if the compiler names it
+    pub required_by: Option<String>,
+}
+
+/// A component that the context does not wire in `delegate_components!`, reported as an
+/// unsatisfied `DelegateComponent<Component>` bound
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingDelegation {
+    /// The context that does not delegate the component (e.g., "Rectangle")
+    pub context_type: String,
+    /// The component (e.g., "PerimeterCalculatorComponent")
+    pub component: String,
+    /// The `delegate_components!` block of the context, if the compiler points at it
+    pub block_span: Option<DiagnosticSpan>,
+    /// The components that the compiler notes are delegated instead
+    /// (e.g., "but trait `DelegateComponent<AreaCalculatorComponent>` is implemented for it")
+    pub wired_components: Vec<String>,
+}
+
+/// A type wired as the provider of a component without implementing its provider trait,
+/// reported as an unsatisfied `IsProviderFor<Component, Context>` bound
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotAProvider {
+    /// The type that is not a provider of the component (e.g., "RectangleArea")
+    pub provider_type: String,
+    /// The component it is wired for (e.g., "PerimeterCalculatorComponent")
+    pub component: String,
+    /// The context that wires it (e.g., "Rectangle")
+    pub context_type: String,
+    /// Components the type does provide, if the compiler names them
+    pub provided_components: Vec<String>,
+    /// The definition of the type, if the compiler points at it
+    pub provider_span: Option<DiagnosticSpan>,
+}
+
+/// A field that the context has, but with another type than its getter reads, reported as
+/// a type mismatch on the `Value` of `HasField` (E0271)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldTypeMismatch {
+    /// The type that the getter requires (e.g., "f64")
+    pub expected_type: String,
+    /// The type of the field, if the compiler names it (e.g., "u32")
+    pub actual_type: Option<String>,
+}
+
+/// A requirement in an overflowing chain: `Type: Trait`, from a
+/// "required for `Type` to implement `Trait`" note
+#[derive(Debug, Clone, PartialEq)]
+pub struct Requirement {
+    /// The type that must implement the trait (e.g., "ScaledArea")
+    pub type_name: String,
+    /// The trait (e.g., "AreaCalculator<Rectangle>")
+    pub trait_name: String,
+    /// The bound that the implementation for the type introduces the previous requirement
+    /// with, if the compiler points at it
+    pub bound_span: Option<DiagnosticSpan>,
+}
+
+impl Requirement {
+    /// Returns true if both requirements are on the same type and trait
+    pub fn is_same(&self, other: &Requirement) -> bool {
+        self.type_name == other.type_name && self.trait_name == other.trait_name
+    }
+}
+
+impl std::fmt::Display for Requirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.type_name, self.trait_name)
+    }
+}
+
+/// A requirement whose evaluation overflowed (E0275), which wiring that depends on itself
+/// or provider types that grow without bound (e.g., `ScaledArea<ScaledArea<...>>`) cause
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequirementOverflow {
+    /// The requirement that overflowed (e.g., `Rectangle: CanCalculateArea`)
+    pub requirement: Requirement,
+    /// The requirements that led to it, from the overflowing one outwards
+    pub required_for: Vec<Requirement>,
+    /// The number of requirements the compiler hid as redundant
+    pub hidden: usize,
+}
+
+impl RequirementOverflow {
+    /// The requirements of the first cycle in the chain, from the requirement that
+    /// depends on itself inwards, if the chain repeats a requirement
+    /// (e.g., `Rectangle: CanCalculateArea`, `Rectangle: AreaCalculator<Rectangle>`,
+    /// `ScaledArea: AreaCalculator<Rectangle>`)
+    pub fn cycle(&self) -> Option<Vec<&Requirement>> {
+        let chain: Vec<&Requirement> = std::iter::once(&self.requirement)
+            .chain(&self.required_for)
+            .collect();
+        (1..chain.len()).find_map(|end| {
+            let start = chain[..end]
+                .iter()
+                .position(|requirement| requirement.is_same(chain[end]))?;
+            Some(chain[start + 1..=end].iter().rev().copied().collect())
+        })
+    }
+}
+
+/// An auto trait (e.g., `Send`) that a provider requires of the context, which a type in
+/// the context does not implement
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoTraitFailure {
+    /// The auto traits that are not implemented (e.g., ["Send", "Sync"])
+    pub auto_traits: Vec<String>,
+    /// The type that does not implement them (e.g., "Rc<String>")
+    pub offending_type: String,
+    /// The outermost type that contains it (e.g., "App"), if it is not the type itself
+    pub within_type: Option<String>,
+    /// The definition of the outermost type, if the compiler points at it
+    pub within_span: Option<DiagnosticSpan>,
+    /// The provider whose implementation requires the auto trait, if the chain names it
+    pub provider: Option<ProviderRelationship>,
+    /// The bound that requires the auto trait, if the compiler points at it
+    pub bound_span: Option<DiagnosticSpan>,
+}
+
+/// Hints written by the cgp library in `#[diagnostic::on_unimplemented]` attributes
+/// They replace rustc's headline and add notes without spans to the error
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct UnimplementedHint {
+    /// The custom headline, if the attribute replaced rustc's message
+    pub message: Option<String>,
+    /// The custom notes, in the order rustc reports them
+    pub notes: Vec<String>,
+}
+
+/// Information about provider trait relationships from IsProviderFor patterns
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProviderRelationship {
+    /// The provider implementation type
+    pub provider_type: String,
+    /// The component being provided
+    pub component: String,
+    /// The context type
+    pub context: String,
+    /// Whether the provider and context types were parsed completely
+    pub confidence: Confidence,
+}
+
+/// Extracts component information from CanUseComponent patterns
+/// Pattern: `CanUseComponent<ComponentType>`
+pub fn extract_component_from_can_use(message: &str) -> Option<ComponentInfo> {
+    let start = message.find("CanUseComponent<")?;
+    let after_start = start + "CanUseComponent<".len();
+
+    let outcome = extract_balanced_generic(message, after_start);
+    let confidence = outcome.confidence();
+    let component_type = outcome.value()?;
+    let provider_trait = derive_provider_trait_name(&component_type);
+
+    Some(ComponentInfo {
+        component_type,
+        provider_trait,
+        confidence,
+    })
+}
+
+/// Extracts component information from various patterns in a message
+pub fn extract_component_info(message: &str) -> Option<ComponentInfo> {
+    // Try CanUseComponent pattern first
+    if let Some(info) = extract_component_from_can_use(message) {
+        return Some(info);
+    }
+    // Try IsProviderFor pattern - extract component from inside the generic
+    // Pattern: `IsProviderFor<ComponentName, Context>`
+    if let Some(start) = message.find("IsProviderFor<") {
+        let after_start = start + "IsProviderFor<".len();
+
+        // The component is the first argument, which may have lifetimes and const
+        // arguments of its own (e.g., `FooComponent<'a, 3>`)
+        if let Some(list) = generic_arguments_at(message, after_start).value()
+            && let [component, _, ..] = list.arguments.as_slice()
+        {
+            let component_type = &message[component.clone()];
+
+            // Verify it looks like a component (ends with "Component" or contains it)
+            if component_type.contains("Component") {
+                let provider_trait = derive_provider_trait_name(component_type);
+                return Some(ComponentInfo {
+                    component_type: component_type.to_string(),
+                    provider_trait,
+                    confidence: Confidence::of_name(component_type),
+                });
+            }
+        }
+    }
+
+    // Try to find component names by the "*Component" suffix pattern
+    // This is a general CGP naming convention
+    let (component_type, confidence) = find_component_type(message)?;
+    let provider_trait = derive_provider_trait_name(&component_type);
+    Some(ComponentInfo {
+        component_type,
+        provider_trait,
+        confidence,
+    })
+}
+
+/// Traits of the cgp library whose names end in "Component", which take the component
+/// as a generic argument
+const COMPONENT_TRAITS: [&str; 2] = ["CanUseComponent", "DelegateComponent"];
+
+/// Finds the first type named `*Component` in a message, with its generic arguments
+/// (e.g., "ScaledAreaComponent<'static, 3>")
+fn find_component_type(message: &str) -> Option<(String, Confidence)> {
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    for (pos, _) in message.match_indices("Component") {
+        let end = pos + "Component".len();
+        // Skip longer words such as "Components"
+        if message[end..].starts_with(is_identifier_char) {
+            continue;
+        }
+
+        let start = message[..pos]
+            .rfind(|c: char| !is_identifier_char(c))
+            .map_or(0, |i| i + 1);
+        let name = &message[start..end];
+        if name == "Component" || COMPONENT_TRAITS.contains(&name) {
+            continue;
+        }
+
+        if !message[end..].starts_with('<') {
+            return Some((name.to_string(), Confidence::of_name(name)));
+        }
+
+        let outcome = extract_balanced_generic(message, end + 1);
+        let confidence = outcome.confidence();
+        return Some(match outcome.value() {
+            Some(arguments) => (format!("{}<{}>", name, arguments), confidence),
+            None => (name.to_string(), Confidence::Low),
+        });
+    }
+
+    None
+}
+
+/// Derives provider trait name from component name by removing "Component" suffix
+/// Example: "AreaCalculatorComponent" -> Some("AreaCalculator")
+pub fn derive_provider_trait_name(component_name: &str) -> Option<String> {
+    // Handle simple case: "XyzComponent" -> "Xyz"
+    if let Some(stripped) = component_name.strip_suffix("Component")
+        && !stripped.is_empty()
+    {
+        return Some(stripped.to_string());
+    }
+
+    // Handle complex generic cases like "Wrapper<Inner>Component"
+    // This shouldn't normally happen in CGP, but handle it gracefully
+    if component_name.contains("Component")
+        && let Some(pos) = component_name.rfind("Component")
+    {
+        let before = &component_name[..pos];
+        if !before.is_empty() {
+            return Some(before.to_string());
+        }
+    }
+
+    None
+}
+
+/// Extracts field information from HasField diagnostic patterns
+/// Pattern: `HasField<Symbol<N, Chars<'c1', Chars<'c2', ...>>>>` is not implemented for `Type`
+/// Field names are decoded through the cache, since many diagnostics mention the same field
+pub fn extract_field_info(
+    diagnostic: &Diagnostic,
+    cache: &mut SymbolCache,
+    paths: PathStyle,
+) -> Option<FieldInfo> {
+    for child in &diagnostic.children {
+        if matches!(child.level, DiagnosticLevel::Help) {
+            let message = &child.message;
+
+            if message.contains("HasField") && message.contains("is not implemented for") {
+                // Extract the field name from Symbol pattern
+                let symbol = cache.resolve(message)?;
+                let (field_name, is_complete, has_unknown_chars) = (
+                    symbol.field_name.clone(),
+                    symbol.is_complete,
+                    symbol.has_unknown_chars,
+                );
+
+                // Extract the target type
+                let target_type = extract_type_from_not_implemented(message, paths)?;
+
+                return Some(FieldInfo {
+                    field_name,
+                    is_complete,
+                    has_unknown_chars,
+                    target_type,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Extracts the unsized type of a "the size for values of type `T` cannot be known" error
+/// The type parameter is taken from the span where the compiler says the bound is introduced,
+/// or from the position of its suggestion to relax the bound with `?Sized`
+pub fn extract_sized_info(diagnostic: &Diagnostic) -> Option<SizedInfo> {
+    let start = diagnostic.message.find("the size for values of type `")?
+        + "the size for values of type `".len();
+    let end = diagnostic.message[start..].find("` cannot be known")?;
+    let unsized_type = diagnostic.message[start..start + end].to_string();
+
+    let mut type_parameter = None;
+    let mut parameter_location = None;
+    let mut required_by = None;
+
+    for child in &diagnostic.children {
+        if let Some(item) = child
+            .message
+            .strip_prefix("required by an implicit `Sized` bound in `")
+            .and_then(|rest| rest.strip_suffix('`'))
+        {
+            required_by = Some(item.split("::").last().unwrap_or(item).to_string());
+        }
+
+        for span in &child.spans {
+            let Some(line) = span.text.first() else {
+                continue;
+            };
+            let chars: Vec<char> = line.text.chars().collect();
+            let highlight_start = line.highlight_start.saturating_sub(1).min(chars.len());
+
+            let parameter: String =
+                if span.label.as_deref() == Some("unsatisfied trait bound introduced here") {
+                    let highlight_end = line.highlight_end.saturating_sub(1).min(chars.len());
+                    chars[highlight_start..highlight_end.max(highlight_start)]
+                        .iter()
+                        .collect()
+                } else if child
+                    .message
+                    .starts_with("consider relaxing the implicit `Sized`")
+                {
+                    // The suggestion is inserted right after the type parameter
+                    let before = &chars[..highlight_start];
+                    let ident_start = before
+                        .iter()
+                        .rposition(|c| !(c.is_alphanumeric() || *c == '_'))
+                        .map_or(0, |pos| pos + 1);
+                    before[ident_start..].iter().collect()
+                } else {
+                    continue;
+                };
+
+            if type_parameter.is_none() && is_identifier(&parameter) {
+                type_parameter = Some(parameter);
+                parameter_location = Some(format!("{}:{}", span.file_name, span.line_start));
+            }
+        }
+    }
+
+    Some(SizedInfo {
+        unsized_type,
+        type_parameter,
+        parameter_location,
+        required_by,
+    })
+}
+
+/// Extracts the component that a context does not delegate, from an unsatisfied
+/// `DelegateComponent<Component>` bound in the message or in a help note
+/// (e.g., "the trait `DelegateComponent<FooComponent>` is not implemented for `Context`")
+pub fn extract_missing_delegation(
+    diagnostic: &Diagnostic,
+    paths: PathStyle,
+) -> Option<MissingDelegation> {
+    let candidates = std::iter::once((&diagnostic.message, None)).chain(
+        diagnostic
+            .children
+            .iter()
+            .map(|child| (&child.message, child.spans.iter().find(|s| s.is_primary))),
+    );
+    for (message, span) in candidates {
+        let Some((context, trait_name)) = unimplemented_bound(message) else {
+            continue;
+        };
+        let trait_name = strip_module_prefixes(&trait_name);
+        if !trait_name.starts_with("DelegateComponent<") {
+            continue;
+        }
+        let Some(component) = generic_arguments_at(&trait_name, "DelegateComponent<".len())
+            .value()
+            .and_then(|list| list.arguments.first().cloned())
+            .map(|range| &trait_name[range])
+        else {
+            continue;
+        };
+
+        // rustc names the other implementation when there is only one
+        let wired_components = message
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.trim().strip_prefix("but trait `"))
+            .filter_map(|rest| rest.split('`').next())
+            .map(strip_module_prefixes)
+            .filter(|trait_name| trait_name.starts_with("DelegateComponent<"))
+            .filter_map(|trait_name| {
+                generic_arguments_at(&trait_name, "DelegateComponent<".len())
+                    .value()
+                    .and_then(|list| list.arguments.first().cloned())
+                    .map(|range| trait_name[range].to_string())
+            })
+            .collect();
+
+        return Some(MissingDelegation {
+            context_type: strip_module_prefixes_in(&context, paths),
+            component: component.to_string(),
+            block_span: span
+                .filter(|span| span_text_starts_with(span, "delegate_components!"))
+                .cloned(),
+            wired_components,
+        });
+    }
+
+    None
+}
+
+/// Extracts a type wired as a provider without implementing the provider trait, from an
+/// unsatisfied `IsProviderFor<Component, Context>` bound in the message or in a help note
+/// (e.g., "the trait `IsProviderFor<FooComponent, Context>` is not implemented for `Bar`")
+pub fn extract_not_a_provider(diagnostic: &Diagnostic, paths: PathStyle) -> Option<NotAProvider> {
+    let candidates = std::iter::once((&diagnostic.message, None)).chain(
+        diagnostic
+            .children
+            .iter()
+            .map(|child| (&child.message, child.spans.iter().find(|s| s.is_primary))),
+    );
+    for (message, span) in candidates {
+        let Some((provider, trait_name)) = unimplemented_bound(message) else {
+            continue;
+        };
+        let Some([component, context]) = is_provider_for_arguments(&trait_name) else {
+            continue;
+        };
+
+        // rustc names the other implementation when there is only one
+        // (e.g., "but trait `IsProviderFor<BarComponent, Context>` is implemented for it")
+        let provided_components = message
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.trim().strip_prefix("but trait `"))
+            .filter_map(|rest| rest.split('`').next())
+            .filter_map(is_provider_for_arguments)
+            .map(|[component, _]| component)
+            .collect();
+
+        return Some(NotAProvider {
+            provider_type: strip_module_prefixes_in(&provider, paths),
+            component,
+            context_type: strip_module_prefixes_in(&context, paths),
+            provided_components,
+            provider_span: span.cloned(),
+        });
+    }
+
+    None
+}
+
+/// Extracts a field with the wrong type from a type mismatch on the `Value` of `HasField`
+/// Pattern: type mismatch resolving `<Type as HasField<Symbol<...>>>::Value == Expected`,
+/// with "expected `Expected`, found `Actual`" as the label of the primary span
+pub fn extract_field_type_mismatch(
+    diagnostic: &Diagnostic,
+    cache: &mut SymbolCache,
+    paths: PathStyle,
+) -> Option<(FieldInfo, FieldTypeMismatch)> {
+    let projection = diagnostic
+        .message
+        .strip_prefix("type mismatch resolving `<")?
+        .strip_suffix('`')?;
+    let (target_type, rest) = projection.split_once(" as ")?;
+    let (has_field, expected_type) = rest.rsplit_once(">::Value == ")?;
+    if !has_field.contains("HasField<") {
+        return None;
+    }
+
+    let symbol = cache.resolve(has_field)?;
+    let field_info = FieldInfo {
+        field_name: symbol.field_name.clone(),
+        is_complete: symbol.is_complete,
+        has_unknown_chars: symbol.has_unknown_chars,
+        target_type: paths.apply(target_type),
+    };
+
+    let actual_type = diagnostic
+        .spans
+        .iter()
+        .filter_map(|span| span.label.as_deref())
+        .find_map(|label| {
+            let (_, found) = label.split_once(", found `")?;
+            found.strip_suffix('`')
+        })
+        .map(|found| paths.apply(found));
+
+    Some((
+        field_info,
+        FieldTypeMismatch {
+            expected_type: paths.apply(expected_type),
+            actual_type,
+        },
+    ))
+}
+
+/// Extracts the chain of requirements of an overflow (E0275)
+/// Pattern: overflow evaluating the requirement `Type: Trait`, followed by
+/// "required for `Type` to implement `Trait`" notes and "N redundant requirements hidden"
+pub fn extract_requirement_overflow(
+    diagnostic: &Diagnostic,
+    paths: PathStyle,
+) -> Option<RequirementOverflow> {
+    let bound = diagnostic
+        .message
+        .strip_prefix("overflow evaluating the requirement `")?
+        .strip_suffix('`')?;
+    let (type_name, trait_name) = bound.split_once(": ")?;
+    let requirement = |type_name: &str, trait_name: &str, bound_span| Requirement {
+        type_name: strip_module_prefixes_in(type_name, paths),
+        trait_name: strip_path_prefixes(trait_name, false),
+        bound_span,
+    };
+
+    let mut required_for = Vec::new();
+    let mut hidden = 0;
+    for child in &diagnostic.children {
+        if let Some((type_name, trait_name)) = extract_required_for(&child.message) {
+            let bound_span = child
+                .spans
+                .iter()
+                .find(|span| {
+                    span.label
+                        .as_deref()
+                        .is_some_and(|label| label.contains("introduced here"))
+                })
+                .cloned();
+            required_for.push(requirement(type_name, trait_name, bound_span));
+        } else if let Some(count) = child
+            .message
+            .split_once(" redundant requirement")
+            .and_then(|(count, _)| count.parse::<usize>().ok())
+        {
+            hidden += count;
+        }
+    }
+
+    Some(RequirementOverflow {
+        requirement: requirement(type_name, trait_name, None),
+        required_for,
+        hidden,
+    })
+}
+
+/// Extracts an auto trait that a type in the context does not implement, from the help of
+/// an auto trait error (e.g., "within `App`, the trait `Sync` is not implemented for
+/// `Rc<String>`") and the "required because it appears within the type `App`" and
+/// `IsProviderFor` notes that lead to it
+pub fn extract_auto_trait_failure(
+    diagnostic: &Diagnostic,
+    paths: PathStyle,
+) -> Option<AutoTraitFailure> {
+    let (offending_type, auto_trait, within_type) = std::iter::once(&diagnostic.message)
+        .chain(diagnostic.children.iter().map(|child| &child.message))
+        .find_map(|message| unimplemented_auto_trait(message))?;
+
+    // The notes go from the innermost type outwards
+    let within_note = diagnostic.children.iter().rev().find_map(|child| {
+        let type_name = child
+            .message
+            .strip_prefix("required because it appears within the type `")?
+            .strip_suffix('`')?;
+        Some((type_name, child.spans.iter().find(|span| span.is_primary)))
+    });
+    let within_type = within_type.or(within_note.map(|(type_name, _)| type_name));
+
+    let (provider, bound_span) = diagnostic
+        .children
+        .iter()
+        .find_map(|child| {
+            let relationship = extract_provider_relationship(&child.message, paths)?;
+            let bound_span = child.spans.iter().find(|span| {
+                span.label
+                    .as_deref()
+                    .is_some_and(|label| label.contains("introduced here"))
+            });
+            Some((Some(relationship), bound_span.cloned()))
+        })
+        .unwrap_or_default();
+
+    Some(AutoTraitFailure {
+        auto_traits: vec![auto_trait],
+        offending_type: strip_module_prefixes_in(offending_type, paths),
+        within_type: within_type.map(|type_name| strip_module_prefixes_in(type_name, paths)),
+        within_span: within_note.and_then(|(_, span)| span.cloned()),
+        provider,
+        bound_span,
+    })
+}
+
+/// The auto traits that a provider may require of the context
+const AUTO_TRAITS: [&str; 5] = ["Send", "Sync", "Unpin", "UnwindSafe", "RefUnwindSafe"];
+
+/// The type, auto trait and outermost type of an unimplemented auto trait
+/// Pattern: [within `Outer`, ]the trait `Send` is not implemented for `Type`, or rustc's
+/// headlines "`Type` cannot be sent between threads safely" and "... shared ..."
+fn unimplemented_auto_trait(message: &str) -> Option<(&str, String, Option<&str>)> {
+    let first_line = message.lines().next()?;
+    for (suffix, auto_trait) in [
+        ("` cannot be sent between threads safely", "Send"),
+        ("` cannot be shared between threads safely", "Sync"),
+    ] {
+        if let Some(type_name) = first_line
+            .strip_prefix('`')
+            .and_then(|rest| rest.strip_suffix(suffix))
+        {
+            return Some((type_name, auto_trait.to_string(), None));
+        }
+    }
+
+    let (within_type, bound) = match first_line
+        .strip_prefix("within `")
+        .and_then(|rest| rest.split_once("`, "))
+    {
+        Some((within_type, bound)) => (Some(within_type), bound),
+        None => (None, first_line),
+    };
+    let start = bound.strip_prefix("the trait `")?;
+    let (trait_name, rest) = start.split_once("` is not implemented for `")?;
+    let type_name = rest.split('`').next()?;
+    let trait_name = strip_path_prefixes(trait_name, false);
+    AUTO_TRAITS
+        .contains(&trait_name.as_str())
+        .then_some((type_name, trait_name, within_type))
+}
+
+/// The component and context arguments of an `IsProviderFor<Component, Context>` trait
+fn is_provider_for_arguments(trait_name: &str) -> Option<[String; 2]> {
+    // Only the paths are removed, since `strip_module_prefixes` hides `IsProviderFor`
+    let trait_name = strip_path_prefixes(trait_name, false);
+    if !trait_name.starts_with("IsProviderFor<") {
+        return None;
+    }
+    let list = generic_arguments_at(&trait_name, "IsProviderFor<".len()).value()?;
+    let mut arguments = list
+        .arguments
+        .iter()
+        .map(|range| trait_name[range.clone()].trim().to_string());
+    Some([arguments.next()?, arguments.next()?])
+}
+
+/// The type and trait of an unsatisfied bound on the first line of a message, whether
+/// rustc writes it as "the trait bound `T: Trait` is not satisfied" or
+/// "the trait `Trait` is not implemented for `T`"
+fn unimplemented_bound(message: &str) -> Option<(String, String)> {
+    let first_line = message.lines().next()?;
+    if let Some((type_name, trait_name)) =
+        extract_unsatisfied_bound(first_line).and_then(|bound| bound.split_once(": "))
+    {
+        return Some((type_name.to_string(), trait_name.to_string()));
+    }
+    let start = first_line.strip_prefix("the trait `")?;
+    let (trait_name, rest) = start.split_once("` is not implemented for `")?;
+    let type_name = rest.split('`').next()?;
+    Some((type_name.to_string(), trait_name.to_string()))
+}
+
+/// Returns true if the first highlighted line of a span starts with a text
+fn span_text_starts_with(span: &DiagnosticSpan, prefix: &str) -> bool {
+    span.text
+        .first()
+        .is_some_and(|line| line.text.trim_start().starts_with(prefix))
+}
+
+/// Headlines of unsatisfied bound errors that rustc writes itself
+const RUSTC_HEADLINES: [&str; 3] = [
+    "the trait bound `",
+    "the size for values of type `",
+    "the trait `",
+];
+
+/// Notes that rustc adds to unsatisfied bound errors itself
+const RUSTC_NOTES: [&str; 9] = [
+    "required for `",
+    "required by ",
+    "the trait `",
+    "but trait `",
+    "the following other types implement",
+    "the full name for the type",
+    "consider ",
+    "this error originates",
+    "for more information",
+];
+
+/// Extracts the hints of a `#[diagnostic::on_unimplemented]` attribute from an unsatisfied
+/// bound error (E0277): a headline that rustc does not write itself, and notes without spans
+/// that are not part of rustc's explanation of the bound
+pub fn extract_unimplemented_hint(diagnostic: &Diagnostic) -> Option<UnimplementedHint> {
+    if diagnostic.code.as_ref().map(|code| code.code.as_str()) != Some("E0277") {
+        return None;
+    }
+
+    // The standard library writes the headlines of `Send` and `Sync` the same way
+    let message = Some(diagnostic.message.clone()).filter(|message| {
+        !RUSTC_HEADLINES
+            .iter()
+            .any(|headline| message.starts_with(headline))
+            && unimplemented_auto_trait(message).is_none()
+    });
+
+    let notes: Vec<String> = diagnostic
+        .children
+        .iter()
+        .filter(|child| matches!(child.level, DiagnosticLevel::Note) && child.spans.is_empty())
+        .map(|child| child.message.clone())
+        .filter(|note| {
+            !RUSTC_NOTES.iter().any(|prefix| note.starts_with(prefix))
+                && !note.contains("redundant requirement")
+        })
+        .collect();
+
+    if message.is_none() && notes.is_empty() {
+        return None;
+    }
+
+    Some(UnimplementedHint { message, notes })
+}
+
+/// Returns true if the text is a single Rust identifier
+fn is_identifier(text: &str) -> bool {
+    text.chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Returns the first `Symbol<N, Chars<...>>` type in a message, before any "but trait" part
+/// (to focus on the unsatisfied trait)
+pub fn extract_symbol_type(message: &str) -> Option<&str> {
+    let relevant_part = if let Some(pos) = message.find("but trait") {
+        &message[..pos]
+    } else {
+        message
+    };
+
+    let start = relevant_part.find("Symbol<")?;
+    let mut depth = 0;
+
+    for (i, ch) in relevant_part[start..].char_indices() {
+        match ch {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&relevant_part[start..start + i + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The type may be cut off by the compiler; use everything that is available
+    Some(&relevant_part[start..])
+}
+
+/// Extracts field name from Symbol<N, Chars<'x', Chars<'y', ...>>> pattern
+/// Returns (field_name, is_complete, has_unknown_chars)
+pub fn extract_field_name_from_symbol(message: &str) -> Option<(String, bool, bool)> {
+    // Get the part before "but trait" if it exists (to focus on the unsatisfied trait)
+    let relevant_part = if let Some(pos) = message.find("but trait") {
+        &message[..pos]
+    } else {
+        message
+    };
+
+    // Extract expected length from Symbol<N, ...>
+    let expected_length = extract_symbol_length(relevant_part)?;
+
+    // Extract visible characters from Chars<'x', Chars<'y', ...>> chain
+    let chars = extract_chars_from_pattern(relevant_part);
+
+    if chars.is_empty() {
+        return None;
+    }
+
+    // Hidden characters are shown as �
+    let field_name: String = chars.iter().map(|ch| ch.unwrap_or(HIDDEN_CHAR)).collect();
+    let has_unknown = chars.contains(&None);
+    // The length counts bytes, and a hidden character is counted as one byte
+    let byte_length: usize = chars.iter().map(|ch| ch.map_or(1, char::len_utf8)).sum();
+    let is_complete = byte_length == expected_length;
+
+    Some((field_name, is_complete, has_unknown))
+}
+
+/// Extracts the expected length from Symbol<N, ...> pattern
+fn extract_symbol_length(text: &str) -> Option<usize> {
+    let start = text.find("Symbol<")?;
+    let after_symbol = &text[start + 7..];
+    let comma_pos = after_symbol.find(',')?;
+    after_symbol[..comma_pos].trim().parse::<usize>().ok()
+}
+
+/// Extracts all characters from Chars<'x', Chars<'y', ...>> pattern
+/// Each character is None if the compiler hid it, which it writes as `_` without quotes,
+/// unlike a literal underscore `'_'`
+fn extract_chars_from_pattern(text: &str) -> Vec<Option<char>> {
+    let mut chars = Vec::new();
+
+    for (idx, _) in text.match_indices("Chars<") {
+        let rest = &text[idx + "Chars<".len()..];
+        if let Some(literal) = rest.strip_prefix('\'') {
+            // The character literal after the quote, which may be escaped (e.g., '\'')
+            if let Some(ch) = parse_char_literal(literal) {
+                chars.push(Some(ch));
+            }
+        } else if rest.starts_with('_') {
+            chars.push(None);
+        }
+    }
+
+    chars
+}
+
+/// Parses the body of a char literal up to its closing quote, e.g. `a'` or `\u{e9}'`
+fn parse_char_literal(text: &str) -> Option<char> {
+    let mut rest = text.chars();
+    let ch = match rest.next()? {
+        '\'' => return None,
+        '\\' => match rest.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'u' => {
+                let hex = rest.as_str().strip_prefix('{')?.split_once('}')?.0;
+                rest = rest.as_str()[hex.len() + 2..].chars();
+                char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+            }
+            other => other,
+        },
+        ch => ch,
+    };
+
+    (rest.next() == Some('\'')).then_some(ch)
+}
+
+/// Extracts type name from "is not implemented for `Type`" pattern
+fn extract_type_from_not_implemented(message: &str, paths: PathStyle) -> Option<String> {
+    let start = message.find("is not implemented for `")?;
+    let after_start = start + "is not implemented for `".len();
+    let end = message[after_start..].find('`')?;
+    let full_name = &message[after_start..after_start + end];
+
+    // Remove module prefixes (e.g., "module::Wrapper<module::Type>" -> "Wrapper<Type>")
+    Some(paths.apply(full_name))
+}
+
+/// Extracts provider relationship from IsProviderFor patterns
+/// Pattern: `for `Provider` to implement `IsProviderFor<Component, Context>`
+pub fn extract_provider_relationship(
+    message: &str,
+    paths: PathStyle,
+) -> Option<ProviderRelationship> {
+    if !message.contains("IsProviderFor") {
+        return None;
+    }
+
+    // Extract provider type: "for `Provider` to implement"
+    let provider_type = extract_type_from_for_to_implement(message, paths)?;
+
+    // Extract component and context from IsProviderFor<Component, Context>
+    let start = message.find("IsProviderFor<")?;
+    let after_start = start + "IsProviderFor<".len();
+
+    let outcome = generic_arguments_at(message, after_start);
+    let confidence = outcome.confidence();
+    let list = outcome.value()?;
+    let [component, context, ..] = list.arguments.as_slice() else {
+        return None;
+    };
+    // Generic contexts (e.g., `MyContext<Foo, 'a>`) are spelled the same wherever they appear
+    let component = normalize_type(&message[component.clone()]);
+    let context = normalize_type(&message[context.clone()]);
+    let confidence = confidence
+        .min(Confidence::of_name(&provider_type))
+        .min(Confidence::of_name(&component));
+
+    Some(ProviderRelationship {
+        provider_type,
+        component,
+        context,
+        confidence,
+    })
+}
+
+/// Extracts type from "for `Type` to implement" pattern
+fn extract_type_from_for_to_implement(message: &str, paths: PathStyle) -> Option<String> {
+    let start = message.find("for `")?;
+    let after_start = start + 5;
+    let end = message[after_start..].find("` to")?;
+    let full_name = &message[after_start..after_start + end];
+
+    // Remove module prefixes (e.g., "module::Wrapper<module::Type>" -> "Wrapper<Type>")
+    Some(paths.apply(full_name))
+}
+
+/// Extracts the contents of a generic argument list, given the position right after its `<`
+/// Example: extract "Foo<Bar, Baz>" from "Wrapper<Foo<Bar, Baz>>"
+/// If the list is not closed, the arguments read before it stops are returned as truncated
+pub fn extract_balanced_generic(text: &str, start_pos: usize) -> ParseOutcome<String> {
+    generic_arguments_at(text, start_pos).map(|list| {
+        let start = list
+            .arguments
+            .first()
+            .map_or(start_pos, |range| range.start);
+        let end = list.arguments.last().map_or(start_pos, |range| range.end);
+        text[start..end].to_string()
+    })
+}
+
+/// Extracts check trait name from "required by a bound in `TraitName`" pattern
+/// Note: This extracts the check trait (e.g., CanUseRectangle), NOT the consumer trait
+pub fn extract_check_trait(message: &str) -> Option<String> {
+    let start = message.find("required by a bound in `")?;
+    let after_start = start + "required by a bound in `".len();
+    let end = message[after_start..].find('`')?;
+    Some(message[after_start..after_start + end].to_string())
+}
+
+/// A generic function whose bound on a consumer trait is not satisfied by its caller's
+/// context, e.g. `fn total_area<Context: CanCalculateArea>` called with a `Rectangle`
+/// Such errors come from plain code rather than `check_components!`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageSite {
+    /// The function with the bound (e.g., "total_area")
+    pub function: String,
+    /// The consumer trait of the bound (e.g., "CanCalculateArea")
+    pub consumer_trait: String,
+    /// The context that does not implement the consumer trait (e.g., "Rectangle")
+    pub context_type: String,
+    /// The span of the bound in the function signature
+    pub bound_span: Option<DiagnosticSpan>,
+}
+
+/// Extracts the usage site of an error: the last "required by a bound in `function`" note,
+/// with the consumer trait from the "required for `Context` to implement `Trait`" note
+/// before it (or from the unsatisfied bound itself)
+/// Bounds of the check traits generated by `check_components!` are not usage sites.
+pub fn extract_usage_site(diagnostic: &Diagnostic, paths: PathStyle) -> Option<UsageSite> {
+    let mut required: Option<(String, String)> = extract_unsatisfied_bound(&diagnostic.message)
+        .and_then(|bound| bound.split_once(": "))
+        .map(|(context, trait_name)| (context.to_string(), trait_name.to_string()));
+    let mut usage_site = None;
+
+    for child in &diagnostic.children {
+        if !matches!(child.level, DiagnosticLevel::Note) {
+            continue;
+        }
+        if let Some((context, trait_name)) = extract_required_for(&child.message) {
+            required = Some((context.to_string(), trait_name.to_string()));
+            continue;
+        }
+
+        let Some(function) = extract_check_trait(&child.message) else {
+            continue;
+        };
+        let bound_span = child.spans.iter().find(|span| span.is_primary).cloned();
+        if !is_function_name(&function)
+            || bound_span
+                .as_ref()
+                .is_some_and(|span| is_expanded_from(span, "check_components"))
+        {
+            continue;
+        }
+        if let Some((context, trait_name)) = &required {
+            usage_site = Some(UsageSite {
+                function: strip_module_prefixes(&function),
+                consumer_trait: strip_module_prefixes(trait_name),
+                context_type: strip_module_prefixes_in(context, paths),
+                bound_span,
+            });
+        }
+    }
+
+    usage_site
+}
+
+/// Extracts the type and trait of a "required for `Type` to implement `Trait`" note
+fn extract_required_for(message: &str) -> Option<(&str, &str)> {
+    let rest = &message[message.find("required for `")? + "required for `".len()..];
+    let (type_name, rest) = rest.split_once("` to implement `")?;
+    let (trait_name, _) = rest.split_once('`')?;
+    Some((type_name, trait_name))
+}
+
+/// Returns true for the path of a function or method (e.g., `total_area` or
+/// `Shape::total_area`), as opposed to a type or trait
+pub fn is_function_name(path: &str) -> bool {
+    let name = path.rsplit("::").next().unwrap_or(path);
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_lowercase() || c == '_')
+}
+
+/// Returns true if a span comes from the expansion of the given macro
+fn is_expanded_from(span: &DiagnosticSpan, macro_name: &str) -> bool {
+    let mut expansion = span.expansion.as_deref();
+    while let Some(current) = expansion {
+        if current.macro_decl_name.contains(macro_name) {
+            return true;
+        }
+        expansion = current.span.expansion.as_deref();
+    }
+    false
+}
+
+/// Checks if a diagnostic has help messages indicating other HasField implementations exist
+pub fn has_other_hasfield_implementations(diagnostic: &Diagnostic) -> bool {
+    for child in &diagnostic.children {
+        if matches!(child.level, DiagnosticLevel::Help)
+            && (child.message.contains("but trait `HasField")
+                || child
+                    .message
+                    .contains("the following other types implement trait"))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Information about a consumer trait dependency extracted from delegation notes
+/// This represents a consumer trait that a provider depends on
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConsumerTraitDependency {
+    /// The consumer trait name (e.g., "CanCalculateArea")
+    pub trait_name: String,
+    /// The context type (e.g., "Rectangle")
+    pub context_type: String,
+    /// The component that provides this consumer trait (if we can derive it)
+    pub component_name: Option<String>,
+}
+
+/// Extracts consumer trait dependencies from delegation notes
+/// These are consumer traits that providers depend on, shown in notes like:
+/// "required for `Rectangle` to implement `CanCalculateArea`"
+/// Returns None if the trait is not a consumer trait (e.g., internal CGP traits)
+pub fn extract_consumer_trait_dependency(
+    note: &str,
+    paths: PathStyle,
+) -> Option<ConsumerTraitDependency> {
+    // Look for pattern: "required for `Context` to implement `TraitName`"
+    // This indicates that the provider depends on this consumer trait
+    if let Some(for_pos) = note.find("required for `") {
+        let after_for = for_pos + "required for `".len();
+
+        // Extract context type (between first ` and next `)
+        if let Some(context_end) = note[after_for..].find('`') {
+            let context_type = &note[after_for..after_for + context_end];
+
+            // Look for the trait name after "to implement `"
+            if let Some(implement_pos) = note[after_for + context_end..].find("to implement `") {
+                let trait_start = after_for + context_end + implement_pos + "to implement `".len();
+
+                if let Some(trait_end) = note[trait_start..].find('`') {
+                    let trait_name = &note[trait_start..trait_start + trait_end];
+
+                    // Filter out internal CGP traits - we only want consumer traits
+                    // Consumer traits typically start with "Can" but exclude framework traits
+                    let cleaned_trait = strip_module_prefixes(trait_name);
+
+                    // Skip if it's an IsProviderFor or CanUseComponent trait (these are internal)
+                    if cleaned_trait.starts_with("Can")
+                        && !cleaned_trait.contains("CanUseComponent")
+                        && !cleaned_trait.starts_with("IsProviderFor")
+                    {
+                        // Try to derive the component name from the consumer trait
+                        let component_name = derive_component_from_consumer_trait(&cleaned_trait);
+
+                        return Some(ConsumerTraitDependency {
+                            trait_name: cleaned_trait,
+                            context_type: normalize_type(&strip_module_prefixes_in(
+                                context_type,
+                                paths,
+                            )),
+                            component_name,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Derives a component name from a consumer trait name
+/// E.g., "CanCalculateArea" -> "AreaCalculatorComponent"
+/// This is a heuristic that works for common CGP naming patterns:
+/// - Consumer trait: Can{Action} (e.g., CanCalculateArea)
+/// - Component: {Action}Component (e.g., AreaCalculatorComponent)
+pub fn derive_component_from_consumer_trait(consumer_trait: &str) -> Option<String> {
+    // Check if it starts with "Can"
+    consumer_trait
+        .strip_prefix("Can")
+        .map(|action_part| format!("{}Component", action_part))
+}
+
+/// How the paths of the types in the messages are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathStyle {
+    /// Only the last segment of each path (e.g., "Rectangle")
+    #[default]
+    Short,
+    /// The paths as reported by the compiler (e.g., "shapes::Rectangle"), for workspaces
+    /// with identically-named types in different modules (`--qualified-paths`)
+    Qualified,
+}
+
+impl PathStyle {
+    /// Writes the paths of a text in this style
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            PathStyle::Short => strip_path_prefixes(text, false),
+            PathStyle::Qualified => text.to_string(),
+        }
+    }
+}
+
+/// Removes all module prefixes from a message (e.g., "foo::bar::Baz" -> "Baz")
+pub fn strip_module_prefixes(message: &str) -> String {
+    strip_module_prefixes_in(message, PathStyle::Short)
+}
+
+/// Removes the module prefixes from a message if the paths are shortened
+pub fn strip_module_prefixes_in(message: &str, paths: PathStyle) -> String {
+    let mut result = paths.apply(message);
+
+    // Remove IsProviderFor wrapper that sometimes appears
+    // This handles cases like "IsProviderFor<AreaCalculator<..."
+    if result.starts_with("IsProviderFor<") {
+        // Extract what's inside and clean it up
+        if let Some(start) = result.find('<') {
+            let after_start = start + 1;
+            result = result[after_start..].to_string();
+        }
+    }
+
+    result
+}
+
+/// Shortens every path in a text to its last segments, by removing the module segments
+/// (e.g., "Wrapper<my::deep::module::Type>" -> "Wrapper<Type>")
+/// Modules are told apart from types by Rust's naming conventions: snake_case segments are
+/// modules, so associated items (e.g., `Self::Output` or `T::Item`) are kept. With
+/// `keep_crate_name`, the first segment of a path is kept to disambiguate types of the
+/// same name from different crates (e.g., "my_crate::Type"), unless it is `crate`,
+/// `self` or `super`.
+pub fn strip_path_prefixes(text: &str, keep_crate_name: bool) -> String {
+    let is_identifier_start = |c: char| c.is_alphabetic() || c == '_';
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+    let follows_path = |rest: &str| {
+        rest.strip_prefix("::")
+            .and_then(|after| after.chars().next())
+            .is_some_and(is_identifier_start)
+    };
+
+    let mut result = String::with_capacity(text.len());
+    let mut previous: Option<char> = None;
+    let mut idx = 0;
+
+    while let Some(c) = text[idx..].chars().next() {
+        let starts_word = previous.is_none_or(|p| !is_identifier_char(p));
+
+        // A global path (e.g., "::std::fmt::Debug") loses its leading `::`
+        if starts_word
+            && previous.is_none_or(|p| p != '>' && p != ':')
+            && follows_path(&text[idx..])
+        {
+            idx += 2;
+            previous = Some(':');
+            continue;
+        }
+
+        if starts_word && is_identifier_start(c) {
+            // Raw identifiers (e.g., `r#mod`) are one segment
+            let raw_prefix = if text[idx..].starts_with("r#") { 2 } else { 0 };
+            let length = text[idx + raw_prefix..]
+                .find(|c: char| !is_identifier_char(c))
+                .unwrap_or(text.len() - idx - raw_prefix);
+            let segment = &text[idx..idx + raw_prefix + length];
+            let end = idx + raw_prefix + length;
+
+            let is_module =
+                segment[raw_prefix..].starts_with(|c: char| c.is_lowercase() || c == '_');
+            if is_module && follows_path(&text[end..]) {
+                let is_first = !text[..idx].ends_with("::");
+                let is_relative = matches!(segment, "crate" | "self" | "super");
+                if keep_crate_name && is_first && !is_relative {
+                    result.push_str(&text[idx..end + 2]);
+                }
+                idx = end + 2;
+                previous = Some(':');
+                continue;
+            }
+
+            result.push_str(segment);
+            idx = end;
+            previous = segment.chars().last();
+            continue;
+        }
+
+        result.push(c);
+        idx += c.len_utf8();
+        previous = Some(c);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_provider_trait_name() {
+        assert_eq!(
+            derive_provider_trait_name("AreaCalculatorComponent"),
+            Some("AreaCalculator".to_string())
+        );
+        assert_eq!(
+            derive_provider_trait_name("FooComponent"),
+            Some("Foo".to_string())
+        );
+        assert_eq!(derive_provider_trait_name("Component"), None);
+        assert_eq!(derive_provider_trait_name("NoSuffix"), None);
+    }
+
+    #[test]
+    fn test_extract_symbol_length() {
+        let text = "Symbol<6, Chars<'h', Chars<'e', ...>>>";
+        assert_eq!(extract_symbol_length(text), Some(6));
+
+        let text2 = "Symbol<5, Chars<'w', ...>>";
+        assert_eq!(extract_symbol_length(text2), Some(5));
+    }
+
+    #[test]
+    fn test_extract_chars_from_pattern() {
+        let text = "Chars<'h', Chars<'e', Chars<'i', Chars<'g', Chars<'h', Chars<'t', Nil>>>>>>";
+        let chars = extract_chars_from_pattern(text);
+        assert_eq!(chars, "height".chars().map(Some).collect::<Vec<_>>());
+
+        // When a character is hidden by the compiler, it appears as Chars<_, (without quotes)
+        let text2 = "Chars<'w', Chars<'i', Chars<'d', Chars<_, Chars<'h', Nil>>>>>";
+        let chars2 = extract_chars_from_pattern(text2);
+        assert_eq!(
+            chars2,
+            vec![Some('w'), Some('i'), Some('d'), None, Some('h')]
+        );
+
+        // Escaped characters are decoded, as in raw identifiers or `symbol!` names
+        let text3 =
+            r"Chars<'r', Chars<'#', Chars<'\'', Chars<'\\', Chars<'\u{e9}', Chars<'\n', Nil>>>>>>";
+        let chars3 = extract_chars_from_pattern(text3);
+        assert_eq!(chars3, "r#'\\é\n".chars().map(Some).collect::<Vec<_>>());
+
+        // Literal underscores and digits are kept, and differ from hidden characters
+        let text4 = "Chars<'x', Chars<'_', Chars<_, Chars<'1', Chars<'é', Nil>>>>>";
+        let chars4 = extract_chars_from_pattern(text4);
+        assert_eq!(
+            chars4,
+            vec![Some('x'), Some('_'), None, Some('1'), Some('é')]
+        );
+    }
+
+    #[test]
+    fn test_extract_field_name_from_symbol() {
+        let hidden =
+            "Symbol<6, Chars<'h', Chars<'e', Chars<'i', Chars<'g', Chars<_, Chars<'t', Nil>>>>>>>";
+        assert_eq!(
+            extract_field_name_from_symbol(hidden),
+            Some(("heig\u{FFFD}t".to_string(), true, true))
+        );
+
+        let raw = "Symbol<6, Chars<'r', Chars<'#', Chars<'t', Chars<'y', Chars<'p', Chars<'e', Nil>>>>>>>";
+        assert_eq!(
+            extract_field_name_from_symbol(raw),
+            Some(("r#type".to_string(), true, false))
+        );
+
+        let digits = "Symbol<3, Chars<'x', Chars<'_', Chars<'1', Nil>>>>";
+        assert_eq!(
+            extract_field_name_from_symbol(digits),
+            Some(("x_1".to_string(), true, false))
+        );
+
+        let hidden_underscore = "Symbol<13, Chars<'s', Chars<'c', Chars<'a', Chars<'l', \
+             Chars<'e', Chars<_, Chars<'f', Chars<'a', Chars<'c', Chars<'t', Chars<'o', \
+             Chars<'r', Chars<'2', Nil>>>>>>>>>>>>>>";
+        assert_eq!(
+            extract_field_name_from_symbol(hidden_underscore),
+            Some(("scale\u{FFFD}factor2".to_string(), true, true))
+        );
+
+        let truncated = "Symbol<6, Chars<'h', Chars<'e', ...>>>";
+        assert_eq!(
+            extract_field_name_from_symbol(truncated),
+            Some(("he".to_string(), false, false))
+        );
+    }
+
+    #[test]
+    fn test_strip_path_prefixes() {
+        let strip = |text: &str| strip_path_prefixes(text, false);
+        assert_eq!(strip("my::deep::module::Type"), "Type");
+        assert_eq!(
+            strip(
+                "cgp::prelude::IsProviderFor<base_area::AreaCalculatorComponent, crate::Rectangle>"
+            ),
+            "IsProviderFor<AreaCalculatorComponent, Rectangle>"
+        );
+        assert_eq!(
+            strip("&mut ::std::vec::Vec<(a::B, [c_2::D; 3])>"),
+            "&mut Vec<(B, [D; 3])>"
+        );
+        assert_eq!(
+            strip("`fn(a::B) -> c::D` for `r#mod::E`"),
+            "`fn(B) -> D` for `E`"
+        );
+
+        // Associated items, turbofish and qualified paths are kept
+        assert_eq!(strip("Self::Output"), "Self::Output");
+        assert_eq!(strip("r#type::r#Struct"), "r#Struct");
+        assert_eq!(
+            strip("<m::T as m::Trait>::Assoc, Vec::<m::U>"),
+            "<T as Trait>::Assoc, Vec::<U>"
+        );
+
+        let keep_crate = |text: &str| strip_path_prefixes(text, true);
+        assert_eq!(keep_crate("my_crate::deep::Type"), "my_crate::Type");
+        assert_eq!(
+            keep_crate("Wrapper<crate::a::Type, super::Other>"),
+            "Wrapper<Type, Other>"
+        );
+    }
+
+    #[test]
+    fn test_derive_component_from_consumer_trait() {
+        assert_eq!(
+            derive_component_from_consumer_trait("CanCalculateArea"),
+            Some("CalculateAreaComponent".to_string())
+        );
+        assert_eq!(
+            derive_component_from_consumer_trait("CanFoo"),
+            Some("FooComponent".to_string())
+        );
+        assert_eq!(
+            derive_component_from_consumer_trait("NotAConsumerTrait"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_consumer_trait_dependency() {
+        let note = "required for `Rectangle` to implement `CanCalculateArea`";
+        let dep = extract_consumer_trait_dependency(note, PathStyle::Short).unwrap();
+        assert_eq!(dep.trait_name, "CanCalculateArea");
+        assert_eq!(dep.context_type, "Rectangle");
+        assert_eq!(
+            dep.component_name,
+            Some("CalculateAreaComponent".to_string())
+        );
+
+        // Should filter out internal traits
+        let note2 = "required for `Rectangle` to implement `CanUseComponent<Something>`";
+        assert!(extract_consumer_trait_dependency(note2, PathStyle::Short).is_none());
+    }
+
+    #[test]
+    fn test_extract_component_info_with_lifetimes() {
+        let component = |message: &str| extract_component_info(message).unwrap();
+
+        let info = component(
+            "required for `Retry<'static, 3, RectangleArea>` to implement \
+             `IsProviderFor<CacheComponent<'static, 3>, Rectangle>`",
+        );
+        assert_eq!(info.component_type, "CacheComponent<'static, 3>");
+        assert_eq!(info.provider_trait.as_deref(), Some("Cache"));
+        assert_eq!(info.confidence, Confidence::High);
+
+        let info =
+            component("the trait `DelegateComponent<CacheComponent<'a, 3>>` is not implemented");
+        assert_eq!(info.component_type, "CacheComponent<'a, 3>");
+
+        let info = component(
+            "the components of `Retry<'static, 3, Inner>` include AreaCalculatorComponent",
+        );
+        assert_eq!(info.component_type, "AreaCalculatorComponent");
+        assert_eq!(info.provider_trait.as_deref(), Some("AreaCalculator"));
+    }
+
+    #[test]
+    fn test_is_function_name() {
+        assert!(is_function_name("total_area"));
+        assert!(is_function_name("shapes::Shape::total_area"));
+        assert!(is_function_name("_private"));
+        assert!(!is_function_name("CanUseRectangle"));
+        assert!(!is_function_name("shapes::CanUseRectangle"));
+    }
+
+    #[test]
+    fn test_extract_balanced_generic() {
+        let complete = |text: &str| ParseOutcome::Complete(text.to_string());
+        let truncated = |text: &str| ParseOutcome::Truncated(text.to_string());
+        let start = "IsProviderFor<".len();
+
+        // Nested generics end at the matching bracket
+        assert_eq!(
+            extract_balanced_generic(
+                "IsProviderFor<ScaledArea<RectangleArea>, Rectangle>`",
+                start
+            ),
+            complete("ScaledArea<RectangleArea>, Rectangle")
+        );
+        assert_eq!(
+            extract_balanced_generic("A<B<C<D>>, E<F>> and more", 2),
+            complete("B<C<D>>, E<F>")
+        );
+
+        // Messages cut off before the brackets are closed keep the best-effort prefix
+        assert_eq!(
+            extract_balanced_generic("IsProviderFor<ScaledArea<RectangleArea", start),
+            truncated("ScaledArea<RectangleArea")
+        );
+        assert_eq!(
+            extract_balanced_generic("IsProviderFor<Foo<Bar>", start),
+            truncated("Foo<Bar>")
+        );
+
+        // Types elided by the compiler are balanced, but not complete
+        assert_eq!(
+            extract_balanced_generic("HasField<Symbol<5, Chars<'w', ...>>>`", "HasField<".len()),
+            truncated("Symbol<5, Chars<'w', ...>>")
+        );
+
+        // Nothing to extract
+        assert_eq!(extract_balanced_generic("Foo<>", 4), ParseOutcome::Failed);
+        assert_eq!(extract_balanced_generic("Foo<", 4), ParseOutcome::Failed);
+        assert_eq!(extract_balanced_generic("Foo", 10), ParseOutcome::Failed);
+    }
+
+    #[test]
+    fn test_promoted_lint_is_not_cgp() {
+        use serde_json::json;
+
+        let diagnostic = |code: &str, level: &str, message: &str| -> Diagnostic {
+            serde_json::from_value(json!({
+                "message": message,
+                "code": { "code": code, "explanation": null },
+                "level": level,
+                "spans": [], "children": [], "rendered": null,
+            }))
+            .unwrap()
+        };
+
+        // With `-D warnings`, the lint arrives at the error level but keeps its name
+        let promoted = diagnostic(
+            "unused_imports",
+            "error",
+            "unused import: `cgp::prelude::HasFieldsRef`",
+        );
+        assert_eq!(lint_name(&promoted), Some("unused_imports"));
+        assert!(!is_cgp_diagnostic(&promoted));
+
+        let hard_error = diagnostic(
+            "E0277",
+            "error",
+            "the trait bound `Rectangle: HasField<Symbol<6, Chars<'h', Nil>>>` is not satisfied",
+        );
+        assert_eq!(lint_name(&hard_error), None);
+        assert!(is_cgp_diagnostic(&hard_error));
+    }
+
+    #[test]
+    fn test_extract_requirement_overflow() {
+        use serde_json::json;
+
+        let note = |message: &str| {
+            json!({
+                "message": message, "code": null, "level": "note",
+                "spans": [], "children": [], "rendered": null,
+            })
+        };
+        let overflow = |requirement: &str, notes: &[&str]| {
+            let diagnostic: Diagnostic = serde_json::from_value(json!({
+                "message": format!("overflow evaluating the requirement `{}`", requirement),
+                "code": { "code": "E0275", "explanation": null },
+                "level": "error",
+                "spans": [],
+                "children": notes.iter().map(|message| note(message)).collect::<Vec<_>>(),
+                "rendered": null,
+            }))
+            .unwrap();
+            extract_requirement_overflow(&diagnostic, PathStyle::default()).unwrap()
+        };
+
+        // A provider that requires the consumer trait of its own component
+        let cyclic = overflow(
+            "app::Rectangle: app::CanCalculateArea",
+            &[
+                "required for `app::ScaledArea` to implement `app::AreaCalculator<app::Rectangle>`",
+                "required for `app::Rectangle` to implement `app::AreaCalculator<app::Rectangle>`",
+                "required for `app::Rectangle` to implement `app::CanCalculateArea`",
+                "125 redundant requirements hidden",
+            ],
+        );
+        assert_eq!(cyclic.hidden, 125);
+        let cycle: Vec<String> = cyclic
+            .cycle()
+            .unwrap()
+            .iter()
+            .map(|requirement| requirement.to_string())
+            .collect();
+        assert_eq!(
+            cycle,
+            vec![
+                "Rectangle: CanCalculateArea",
+                "Rectangle: AreaCalculator<Rectangle>",
+                "ScaledArea: AreaCalculator<Rectangle>",
+            ]
+        );
+
+        // A provider type that wraps itself grows without repeating a requirement
+        let unbounded = overflow(
+            "ScaledArea<ScaledArea<ScaledArea<RectangleArea>>>: AreaCalculator<Rectangle>",
+            &[
+                "required for `ScaledArea<ScaledArea<RectangleArea>>` to implement `AreaCalculator<Rectangle>`",
+                "1 redundant requirement hidden",
+            ],
+        );
+        assert_eq!(unbounded.required_for.len(), 1);
+        assert_eq!(unbounded.hidden, 1);
+        assert_eq!(unbounded.cycle(), None);
+    }
+
+    #[test]
+    fn test_extract_auto_trait_failure() {
+        use serde_json::json;
+
+        let auto_trait_error = |message: &str, notes: &[&str]| {
+            let diagnostic: Diagnostic = serde_json::from_value(json!({
+                "message": message,
+                "code": { "code": "E0277", "explanation": null },
+                "level": "error",
+                "spans": [],
+                "children": notes.iter().map(|message| json!({
+                    "message": message, "code": null, "level": "note",
+                    "spans": [], "children": [], "rendered": null,
+                })).collect::<Vec<_>>(),
+                "rendered": null,
+            }))
+            .unwrap();
+            extract_auto_trait_failure(&diagnostic, PathStyle::default())
+        };
+
+        // A field of the context that is not `Sync`, required by a provider
+        let failure = auto_trait_error(
+            "`Rc<String>` cannot be shared between threads safely",
+            &[
+                "within `app::App`, the trait `Sync` is not implemented for `Rc<String>`",
+                "required because it appears within the type `app::Session`",
+                "required because it appears within the type `app::App`",
+                "required for `app::FetchInBackground` to implement \
+                 `IsProviderFor<app::UserFetcherComponent, app::App>`",
+            ],
+        )
+        .unwrap();
+        assert_eq!(failure.auto_traits, vec!["Sync"]);
+        assert_eq!(failure.offending_type, "Rc<String>");
+        assert_eq!(failure.within_type.as_deref(), Some("App"));
+        let provider = failure.provider.unwrap();
+        assert_eq!(provider.provider_type, "FetchInBackground");
+        assert_eq!(provider.component, "app::UserFetcherComponent");
+
+        // The context itself, named by the help instead of the headline
+        let failure = auto_trait_error(
+            "the trait bound `App: CanUseComponent<UserFetcherComponent>` is not satisfied",
+            &["the trait `std::marker::Send` is not implemented for `App`"],
+        )
+        .unwrap();
+        assert_eq!(failure.auto_traits, vec!["Send"]);
+        assert_eq!(failure.offending_type, "App");
+        assert_eq!(failure.within_type, None);
+
+        // Traits that are not auto traits are other errors
+        assert_eq!(
+            auto_trait_error(
+                "the trait bound `App: CanUseComponent<UserFetcherComponent>` is not satisfied",
+                &["the trait `HasDatabaseUrl` is not implemented for `App`"],
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_generic_context_relationship() {
+        let relationship = extract_provider_relationship(
+            "required for `ScaledArea<RectangleArea>` to implement \
+             `IsProviderFor<AreaCalculatorComponent<'a, 3>, MyContext<Foo,'a>>`",
+            PathStyle::Short,
+        )
+        .unwrap();
+        assert_eq!(relationship.provider_type, "ScaledArea<RectangleArea>");
+        assert_eq!(relationship.component, "AreaCalculatorComponent<'a, 3>");
+        assert_eq!(relationship.context, "MyContext<Foo, 'a>");
+        assert_eq!(relationship.confidence, Confidence::High);
+
+        let dependency = extract_consumer_trait_dependency(
+            "required for `app::MyContext<Foo,  'a>` to implement `CanCalculateArea`",
+            PathStyle::Short,
+        )
+        .unwrap();
+        assert_eq!(dependency.context_type, "MyContext<Foo, 'a>");
+    }
+
+    #[test]
+    fn test_parse_outcome_confidence() {
+        let relationship =
+            |message: &str| extract_provider_relationship(message, PathStyle::Short).unwrap();
+
+        let complete = relationship(
+            "required for `RectangleArea` to implement `IsProviderFor<AreaCalculatorComponent, Rectangle>`",
+        );
+        assert_eq!(complete.context, "Rectangle");
+        assert_eq!(complete.confidence, Confidence::High);
+
+        let truncated = relationship(
+            "required for `RectangleArea` to implement `IsProviderFor<AreaCalculatorComponent, Wrapper<Rectangle",
+        );
+        assert_eq!(truncated.context, "Wrapper<Rectangle");
+        assert_eq!(truncated.confidence, Confidence::Low);
+
+        let elided = relationship(
+            "required for `ScaledArea<...>` to implement `IsProviderFor<AreaCalculatorComponent, Rectangle>`",
+        );
+        assert_eq!(elided.confidence, Confidence::Low);
+
+        let component =
+            extract_component_from_can_use("`CanUseComponent<Wrapper<AreaCalculatorComponent`")
+                .unwrap();
+        assert_eq!(component.component_type, "Wrapper<AreaCalculatorComponent");
+        assert_eq!(component.confidence, Confidence::Low);
+    }
+}
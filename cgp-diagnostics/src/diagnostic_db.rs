@@ -0,0 +1,2115 @@
+/// Module for building an internal database of diagnostics and merging related errors
+/// This implements the approach described in Chapters 7-8 of the report
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel, DiagnosticSpan};
+use cargo_metadata::{CompilerMessage, PackageId, Target};
+use std::collections::HashMap;
+
+use crate::cgp_diagnostic::{CgpDiagnostic, CompilerOrigin};
+use crate::cgp_patterns::{
+    AutoTraitFailure, ComponentInfo, Confidence, ConsumerTraitDependency, FieldInfo,
+    FieldTypeMismatch, MissingDelegation, NotAProvider, PathStyle, ProviderRelationship,
+    RequirementOverflow, SizedInfo, UnimplementedHint, UsageSite, extract_auto_trait_failure,
+    extract_check_trait, extract_component_info, extract_field_info, extract_field_type_mismatch,
+    extract_missing_delegation, extract_not_a_provider, extract_provider_relationship,
+    extract_requirement_overflow, extract_sized_info, extract_unimplemented_hint,
+    extract_unsatisfied_bound, extract_usage_site, has_other_hasfield_implementations,
+    is_function_name, mentions_bound_trait,
+};
+use crate::doc_tests::map_doc_test_message;
+use crate::error_formatting::{ChainStyle, DependencyNode};
+use crate::field_name::{FieldName, recover_field_name};
+use crate::grouping_trace::{GroupingDecision, GroupingEvent, GroupingRule, GroupingTrace};
+use crate::long_types::LongTypeCache;
+use crate::macro_warnings::MacroWarnings;
+use crate::name_resolution::NameResolver;
+use crate::panic_guard::catch_analysis_panic;
+use crate::raw_filter::component_matches;
+use crate::root_cause::{
+    deduplicate_delegation_notes, deduplicate_provider_relationships, is_transitive_failure,
+    rank_by_causal_priority,
+};
+use crate::symbol_cache::SymbolCache;
+use crate::workspace_scan::{base_type_name, checked_component_names};
+
+/// Returns a component name without its path or generic parameters
+fn component_name(component_type: &str) -> &str {
+    let name = base_type_name(component_type);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// How many lines apart two fragments of the same error may be reported
+/// (e.g., when a macro expansion shifts the span of a late note)
+const FRAGMENT_LINE_WINDOW: usize = 2;
+
+/// A database that collects and merges related diagnostic information
+#[derive(Debug, Default)]
+pub struct DiagnosticDatabase {
+    /// Map from diagnostic key to merged diagnostic entry
+    entries: HashMap<DiagnosticKey, DiagnosticEntry>,
+    /// Resolves names that are not spelled out in the compiler diagnostics
+    name_resolver: NameResolver,
+    /// Field names decoded from symbol types, shared by all diagnostics of the run
+    symbol_cache: SymbolCache,
+    /// Long-type files referenced by the diagnostics, each read once per run
+    long_types: LongTypeCache,
+    /// CGP diagnostics whose analysis panicked, which are shown as reported by rustc
+    unanalyzed: Vec<CompilerMessage>,
+    /// Grouping decisions, recorded only when enabled with `--dump-grouping`
+    grouping_trace: Option<GroupingTrace>,
+    /// Number of diagnostics added so far
+    diagnostics_seen: usize,
+    /// Number of diagnostics merged into an existing entry
+    diagnostics_merged: usize,
+    /// How the dependency chains are written (depth and narration)
+    chain_style: ChainStyle,
+    /// Whether redundant hops are kept in the dependency chains (`--no-dedup`)
+    keep_redundant_hops: bool,
+    /// Globs selecting the components whose errors are shown (`--component`), all if empty
+    component_filters: Vec<String>,
+    /// Warnings in code generated by the cgp macros, collected for one summary
+    macro_warnings: MacroWarnings,
+}
+
+/// Key used to identify and group related diagnostics
+/// We key only by location to allow merging errors for different components
+/// that share the same root cause (e.g., transitive dependencies)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct DiagnosticKey {
+    /// Primary source location (file:line:column)
+    /// This is typically the line in check_components! where the error occurs
+    location: SourceLocation,
+}
+
+/// Source code location
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct SourceLocation {
+    file: String,
+    line: usize,
+    column: usize,
+}
+
+impl SourceLocation {
+    fn from_span(span: &DiagnosticSpan) -> Self {
+        SourceLocation {
+            file: span.file_name.clone(),
+            line: span.line_start,
+            column: span.column_start,
+        }
+    }
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// How a diagnostic was grouped: the decision, the matching rule, and the entry key,
+/// with the facts the diagnostic added to the entry
+type GroupingOutcome = (
+    GroupingDecision,
+    Option<GroupingRule>,
+    Option<DiagnosticKey>,
+    Vec<String>,
+);
+
+/// rustc's rendering of a diagnostic, or its message and notes if it was not rendered
+fn raw_rendering(diagnostic: &Diagnostic) -> String {
+    if let Some(rendered) = &diagnostic.rendered {
+        return rendered.clone();
+    }
+
+    let mut lines = vec![format!("error: {}", diagnostic.message)];
+    for child in &diagnostic.children {
+        let level = match child.level {
+            DiagnosticLevel::Help => "help",
+            DiagnosticLevel::Warning => "warning",
+            _ => "note",
+        };
+        lines.push(format!("  = {}: {}", level, child.message));
+    }
+    lines.join("\n")
+}
+
+/// The facts of an entry that grouping decisions are traced with
+fn entry_facts(entry: &DiagnosticEntry) -> Vec<String> {
+    let mut facts = Vec::new();
+    if let Some(check_trait) = &entry.check_trait {
+        facts.push(format!("check trait `{}`", check_trait));
+    }
+    if let Some(usage_site) = &entry.usage_site {
+        facts.push(format!("usage site `{}`", usage_site.function));
+    }
+    for component_info in entry.component_infos() {
+        facts.push(format!("component `{}`", component_info.component_type));
+    }
+    if let Some(field_info) = &entry.field_info {
+        facts.push(format!("field `{}`", FieldName::of(field_info)));
+    }
+    if let Some(mismatch) = &entry.field_type_mismatch {
+        facts.push(format!("field type `{}` required", mismatch.expected_type));
+    }
+    if let Some(sized_info) = &entry.sized_info {
+        facts.push(format!("unsized type `{}`", sized_info.unsized_type));
+    }
+    if let Some(missing_delegation) = &entry.missing_delegation {
+        facts.push(format!("undelegated `{}`", missing_delegation.component));
+    }
+    if let Some(not_a_provider) = &entry.not_a_provider {
+        facts.push(format!(
+            "not a provider `{}` of `{}`",
+            not_a_provider.provider_type, not_a_provider.component
+        ));
+    }
+    if let Some(overflow) = &entry.overflow {
+        facts.push(format!("overflow `{}`", overflow.requirement));
+    }
+    if let Some(auto_trait) = &entry.auto_trait {
+        facts.push(format!(
+            "not {} `{}`",
+            auto_trait.auto_traits.join(" + "),
+            auto_trait.offending_type
+        ));
+    }
+    for relationship in entry.provider_relationships() {
+        facts.push(format!(
+            "provider `{}` for `{}`",
+            relationship.provider_type, relationship.component
+        ));
+    }
+    for span in entry.primary_spans() {
+        facts.push(format!("span {}", SourceLocation::from_span(span)));
+    }
+    if entry.is_root_cause {
+        facts.push("root cause".to_string());
+    }
+    facts
+}
+
+/// The kind of error that a diagnostic entry reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorKind {
+    /// A field that the context has other `HasField` implementations for is missing
+    MissingField,
+    /// The context has the field, but with another type than its getter reads
+    FieldTypeMismatch,
+    /// The context has no `HasField` implementations at all (e.g., no `#[derive(HasField)]`)
+    MissingHasField,
+    /// A provider requires a type to be `Sized`
+    UnsizedType,
+    /// The context does not delegate a component in `delegate_components!`
+    MissingDelegation,
+    /// The context wires a component to a type that is not a provider of it
+    NotAProvider,
+    /// Evaluating the wiring overflows, because it depends on itself or grows without bound
+    RequirementOverflow,
+    /// A provider requires the context to implement an auto trait (e.g., `Send`) it does not
+    AutoTrait,
+    /// A provider does not implement the provider trait for the context
+    UnsatisfiedProvider,
+    /// Any other unsatisfied trait bound
+    UnsatisfiedBound,
+}
+
+impl ErrorKind {
+    /// The name used in machine output (e.g., "missing-field")
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::MissingField => "missing-field",
+            ErrorKind::FieldTypeMismatch => "field-type-mismatch",
+            ErrorKind::MissingHasField => "missing-has-field",
+            ErrorKind::UnsizedType => "unsized-type",
+            ErrorKind::MissingDelegation => "missing-delegation",
+            ErrorKind::NotAProvider => "not-a-provider",
+            ErrorKind::RequirementOverflow => "requirement-overflow",
+            ErrorKind::AutoTrait => "auto-trait",
+            ErrorKind::UnsatisfiedProvider => "unsatisfied-provider",
+            ErrorKind::UnsatisfiedBound => "unsatisfied-bound",
+        }
+    }
+
+    /// The name used in human-readable output (e.g., "missing field")
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorKind::MissingField => "missing field",
+            ErrorKind::FieldTypeMismatch => "wrong field type",
+            ErrorKind::MissingHasField => "missing `HasField`",
+            ErrorKind::UnsizedType => "unsized type",
+            ErrorKind::MissingDelegation => "missing delegation",
+            ErrorKind::NotAProvider => "not a provider",
+            ErrorKind::RequirementOverflow => "requirement overflow",
+            ErrorKind::AutoTrait => "auto trait not implemented",
+            ErrorKind::UnsatisfiedProvider => "unsatisfied provider",
+            ErrorKind::UnsatisfiedBound => "unsatisfied bound",
+        }
+    }
+}
+
+/// A failing provider of an entry's chain whose own requirements are traced by another entry
+/// of the same run (e.g., a check of `DensityCalculatorComponent` that fails in the provider
+/// of `AreaCalculatorComponent`, which a second check traces down to a missing field)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkedChain {
+    /// The provider node of the other entry's chain, with the requirements below it
+    pub provider: DependencyNode,
+    /// Where the other entry is reported
+    pub location: String,
+}
+
+/// A merged diagnostic entry combining information from multiple related errors
+#[derive(Debug, Clone)]
+pub struct DiagnosticEntry {
+    /// The original diagnostic (we keep the first one as the primary)
+    pub original: Diagnostic,
+
+    /// Package ID from the CompilerMessage
+    pub package_id: PackageId,
+
+    /// Target from the CompilerMessage
+    pub target: Target,
+
+    /// Extracted field information (missing field errors)
+    pub field_info: Option<FieldInfo>,
+
+    /// The required and actual types of a field that has the wrong type
+    /// (`field_info` names the field)
+    pub field_type_mismatch: Option<FieldTypeMismatch>,
+
+    /// Extracted unsized type information (errors about a type that must be `Sized`)
+    pub sized_info: Option<SizedInfo>,
+
+    /// The component that the context does not delegate (unsatisfied `DelegateComponent`)
+    pub missing_delegation: Option<MissingDelegation>,
+
+    /// The type wired for a component without providing it (unsatisfied `IsProviderFor`)
+    pub not_a_provider: Option<NotAProvider>,
+
+    /// The chain of requirements of an overflow (E0275)
+    pub overflow: Option<RequirementOverflow>,
+
+    /// The auto traits (e.g., `Send`) that a provider requires and the context does not implement
+    pub auto_trait: Option<AutoTraitFailure>,
+
+    /// Hints from the cgp library's `#[diagnostic::on_unimplemented]` attributes
+    pub unimplemented_hint: Option<UnimplementedHint>,
+
+    /// Component information - supports multiple components at the same location
+    /// This allows merging errors for different components that share the same root cause
+    component_infos: Vec<ComponentInfo>,
+
+    /// Check trait name (from "required by a bound in")
+    /// This is the trait generated by check_components!, not the actual consumer trait
+    pub check_trait: Option<String>,
+
+    /// The generic function whose bound failed, for errors from plain code rather than
+    /// `check_components!`
+    pub usage_site: Option<UsageSite>,
+
+    /// Provider relationships extracted from error chain, without duplicates
+    provider_relationships: Vec<ProviderRelationship>,
+
+    /// Delegation chain notes (raw, for later processing), without duplicates
+    delegation_notes: Vec<String>,
+
+    /// Consumer trait dependencies extracted from delegation notes
+    /// These are consumer traits that providers depend on
+    consumer_trait_dependencies: Vec<ConsumerTraitDependency>,
+
+    /// Components that this component depends on (derived from consumer trait dependencies)
+    /// This is populated during the second pass after all diagnostics are collected
+    pub depends_on_components: Vec<String>,
+
+    /// The chains of other entries that continue where this entry's chain stops
+    /// This is populated after all diagnostics are collected
+    pub linked_chains: Vec<LinkedChain>,
+
+    /// Whether this type has other HasField implementations
+    pub has_other_hasfield_impls: bool,
+
+    /// Primary spans for error reporting - one span per component, sorted by location
+    /// This allows showing multiple components in a merged error message
+    primary_spans: Vec<DiagnosticSpan>,
+
+    /// Error code (e.g., "E0277")
+    pub error_code: Option<String>,
+
+    /// Main error message
+    pub message: String,
+
+    /// Whether this is a root cause or a transitive error
+    pub is_root_cause: bool,
+
+    /// Whether this error should be suppressed (because it's redundant)
+    pub suppressed: bool,
+
+    /// The compiler diagnostics merged into this entry after the first, one line each
+    merged_diagnostics: Vec<String>,
+
+    /// rustc's rendering of each compiler diagnostic grouped into this entry
+    /// (reprinted by `--raw-notes`)
+    raw_notes: Vec<String>,
+
+    /// Whether the dependency chain keeps the hops that are redundant (`--no-dedup`)
+    pub keep_redundant_hops: bool,
+}
+
+impl DiagnosticEntry {
+    /// Classifies the entry by the kind of error it reports
+    pub fn kind(&self) -> ErrorKind {
+        match (&self.field_info, &self.sized_info) {
+            (Some(_), _) if self.field_type_mismatch.is_some() => ErrorKind::FieldTypeMismatch,
+            (Some(_), _) if self.has_other_hasfield_impls => ErrorKind::MissingField,
+            (Some(_), _) => ErrorKind::MissingHasField,
+            (None, Some(_)) => ErrorKind::UnsizedType,
+            (None, None) if self.missing_delegation.is_some() => ErrorKind::MissingDelegation,
+            (None, None) if self.not_a_provider.is_some() => ErrorKind::NotAProvider,
+            (None, None) if self.overflow.is_some() => ErrorKind::RequirementOverflow,
+            (None, None) if self.auto_trait.is_some() => ErrorKind::AutoTrait,
+            (None, None) if !self.provider_relationships.is_empty() => {
+                ErrorKind::UnsatisfiedProvider
+            }
+            (None, None) => ErrorKind::UnsatisfiedBound,
+        }
+    }
+
+    /// The primary spans, sorted by location
+    pub fn primary_spans(&self) -> &[DiagnosticSpan] {
+        &self.primary_spans
+    }
+
+    /// The span that comes first in the source, which locates the entry
+    pub fn primary_span(&self) -> Option<&DiagnosticSpan> {
+        self.primary_spans.first()
+    }
+
+    /// Adds a primary span, keeping the spans sorted and ignoring a span at the same location
+    pub fn add_primary_span(&mut self, span: DiagnosticSpan) {
+        let location = SourceLocation::from_span(&span);
+        match self
+            .primary_spans
+            .binary_search_by(|existing| SourceLocation::from_span(existing).cmp(&location))
+        {
+            Ok(_) => {}
+            Err(index) => self.primary_spans.insert(index, span),
+        }
+    }
+
+    /// The components of the entry, one per component type
+    pub fn component_infos(&self) -> &[ComponentInfo] {
+        &self.component_infos
+    }
+
+    /// Adds a component, returning false if the entry already has the component type
+    pub fn add_component_info(&mut self, component_info: ComponentInfo) -> bool {
+        if self
+            .component_infos
+            .iter()
+            .any(|info| info.component_type == component_info.component_type)
+        {
+            return false;
+        }
+        self.component_infos.push(component_info);
+        true
+    }
+
+    /// The provider relationships, in the order they were reported
+    pub fn provider_relationships(&self) -> &[ProviderRelationship] {
+        &self.provider_relationships
+    }
+
+    pub fn add_provider_relationship(&mut self, relationship: ProviderRelationship) {
+        if !self.provider_relationships.contains(&relationship) {
+            self.provider_relationships.push(relationship);
+        }
+    }
+
+    /// The delegation notes, in the order they were reported
+    pub fn delegation_notes(&self) -> &[String] {
+        &self.delegation_notes
+    }
+
+    /// The provider relationships shown in the dependency chain
+    /// Inner providers of a higher-order provider are left out, unless redundant hops are kept
+    pub fn chain_provider_relationships(&self) -> Vec<ProviderRelationship> {
+        if self.keep_redundant_hops {
+            self.provider_relationships.clone()
+        } else {
+            deduplicate_provider_relationships(&self.provider_relationships)
+        }
+    }
+
+    /// The delegation notes shown in the dependency chain
+    pub fn chain_delegation_notes(&self) -> Vec<String> {
+        if self.keep_redundant_hops {
+            self.delegation_notes.clone()
+        } else {
+            deduplicate_delegation_notes(&self.delegation_notes)
+        }
+    }
+
+    pub fn add_delegation_note(&mut self, note: String) {
+        if !self.delegation_notes.contains(&note) {
+            self.delegation_notes.push(note);
+        }
+    }
+
+    /// The consumer traits that the providers of the entry depend on
+    pub fn consumer_trait_dependencies(&self) -> &[ConsumerTraitDependency] {
+        &self.consumer_trait_dependencies
+    }
+
+    pub fn add_consumer_trait_dependency(&mut self, dependency: ConsumerTraitDependency) {
+        if !self.consumer_trait_dependencies.contains(&dependency) {
+            self.consumer_trait_dependencies.push(dependency);
+        }
+    }
+
+    /// The compiler diagnostics merged into this entry, as `file:line:column: message`
+    pub fn merged_diagnostics(&self) -> &[String] {
+        &self.merged_diagnostics
+    }
+}
+
+impl DiagnosticDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the resolver used to look up component, provider and consumer trait names
+    pub fn set_name_resolver(&mut self, name_resolver: NameResolver) {
+        self.name_resolver = name_resolver;
+    }
+
+    /// Limits how many levels of the dependency chain are shown in the rendered errors
+    pub fn set_tree_depth(&mut self, tree_depth: Option<usize>) {
+        self.chain_style.max_depth = tree_depth;
+    }
+
+    /// Narrates the dependency chains as numbered sentences instead of drawing trees
+    pub fn set_narrate_chains(&mut self, narrate: bool) {
+        self.chain_style.narrate = narrate;
+    }
+
+    /// Keeps the hops of the dependency chains that are normally removed as redundant
+    /// (e.g., an inner provider next to the higher-order provider wrapping it)
+    pub fn set_keep_redundant_hops(&mut self, keep_redundant_hops: bool) {
+        self.keep_redundant_hops = keep_redundant_hops;
+    }
+
+    /// Shows only the errors about components matching one of the globs
+    pub fn set_component_filters(&mut self, component_filters: Vec<String>) {
+        self.component_filters = component_filters;
+    }
+
+    /// Starts recording the grouping decision for each diagnostic added from now on
+    pub fn enable_grouping_trace(&mut self) {
+        self.grouping_trace
+            .get_or_insert_with(GroupingTrace::default);
+    }
+
+    /// Returns the recorded grouping decisions, if tracing is enabled
+    pub fn grouping_trace(&self) -> Option<&GroupingTrace> {
+        self.grouping_trace.as_ref()
+    }
+
+    /// Returns the field names decoded so far, for debug output
+    pub fn symbol_cache(&self) -> &SymbolCache {
+        &self.symbol_cache
+    }
+
+    pub fn long_types(&self) -> &LongTypeCache {
+        &self.long_types
+    }
+
+    /// Collects a warning in code generated by a cgp macro for the summary
+    pub fn add_macro_warning(&mut self, diagnostic: &Diagnostic, macro_name: String) {
+        self.macro_warnings.add(diagnostic, macro_name);
+    }
+
+    pub fn macro_warnings(&self) -> &MacroWarnings {
+        &self.macro_warnings
+    }
+
+    /// First pass: Add a compiler message to the database
+    /// If a related diagnostic already exists, merge information
+    /// Diagnostics at the same location are merged to handle transitive dependencies
+    /// For check_components! blocks, errors from consecutive lines with the same check_trait are merged
+    pub fn add_diagnostic(&mut self, compiler_message: &CompilerMessage) {
+        let compiler_message = &*map_doc_test_message(compiler_message);
+        let index = self.diagnostics_seen;
+        self.diagnostics_seen += 1;
+
+        match catch_analysis_panic(|| self.analyze_diagnostic(compiler_message)) {
+            Ok(event) => {
+                if let Some(key) = &event.2 {
+                    self.complete_from_long_types(key.clone(), &compiler_message.message);
+                }
+                self.record_grouping(index, &compiler_message.message, event)
+            }
+            Err(_) => {
+                self.unanalyzed.push(compiler_message.clone());
+                self.record_grouping(
+                    index,
+                    &compiler_message.message,
+                    (GroupingDecision::Panicked, None, None, Vec::new()),
+                );
+            }
+        }
+    }
+
+    /// Completes a truncated field name of an entry from the long-type files that a
+    /// diagnostic merged into it references
+    fn complete_from_long_types(&mut self, key: DiagnosticKey, diagnostic: &Diagnostic) {
+        if let Some(field_info) = self
+            .entries
+            .get_mut(&key)
+            .and_then(|entry| entry.field_info.as_mut())
+        {
+            self.long_types.complete_field_info(diagnostic, field_info);
+        }
+    }
+
+    /// Suppresses the entries that lose to a more likely root cause at the same location
+    fn suppress_transitive_failures(&mut self) {
+        let entries: Vec<&DiagnosticEntry> = self.entries.values().collect();
+        let transitive: Vec<DiagnosticKey> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| is_transitive_failure(entry, &entries))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for (key, entry) in self.entries.iter_mut() {
+            entry.suppressed = transitive.contains(key);
+        }
+    }
+
+    /// Links each failing provider at the end of a chain to the chain of another entry that
+    /// traces the requirements of the same provider, so that the chain can be continued
+    fn link_chains(&mut self) {
+        use crate::error_formatting::build_dependency_tree;
+
+        for entry in self.entries.values_mut() {
+            entry.linked_chains.clear();
+        }
+
+        let mut trees: Vec<(&DiagnosticKey, DependencyNode)> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| !entry.suppressed)
+            .filter_map(|(key, entry)| {
+                catch_analysis_panic(|| build_dependency_tree(entry, &self.name_resolver))
+                    .ok()
+                    .flatten()
+                    .map(|tree| (key, tree))
+            })
+            .collect();
+        // The first entry in the source wins when several trace the same provider
+        trees.sort_by(|(a, _), (b, _)| a.location.cmp(&b.location));
+
+        let mut links: Vec<(DiagnosticKey, LinkedChain)> = Vec::new();
+        for (key, tree) in &trees {
+            for leaf in tree.failing_provider_leaves() {
+                let traced = trees
+                    .iter()
+                    .filter(|(other_key, _)| other_key != key)
+                    .find_map(|(other_key, other_tree)| {
+                        other_tree
+                            .find_traced_provider(&leaf.description)
+                            .map(|provider| (other_key, provider))
+                    });
+                if let Some((other_key, provider)) = traced {
+                    links.push((
+                        (*key).clone(),
+                        LinkedChain {
+                            provider: provider.clone(),
+                            location: other_key.location.to_string(),
+                        },
+                    ));
+                }
+            }
+        }
+
+        for (key, link) in links {
+            if let Some(entry) = self.entries.get_mut(&key) {
+                entry.linked_chains.push(link);
+            }
+        }
+    }
+
+    /// Recovers the field names that the compiler cut off or partly hid, from the field
+    /// and getter names in the workspace sources
+    fn recover_field_names(&mut self) {
+        for entry in self.entries.values_mut() {
+            let Some(field_info) = &mut entry.field_info else {
+                continue;
+            };
+            let candidates = self
+                .name_resolver
+                .field_name_candidates(&field_info.target_type);
+            if let Some(field_name) = recover_field_name(field_info, &candidates) {
+                field_info.field_name = field_name;
+                field_info.is_complete = true;
+                field_info.has_unknown_chars = false;
+            }
+        }
+    }
+
+    /// Records a grouping decision in the trace, if tracing is enabled
+    fn record_grouping(&mut self, index: usize, diagnostic: &Diagnostic, event: GroupingOutcome) {
+        let Some(trace) = &mut self.grouping_trace else {
+            return;
+        };
+        let (decision, rule, entry, added) = event;
+
+        trace.events.push(GroupingEvent {
+            index,
+            message: diagnostic.message.clone(),
+            location: diagnostic
+                .spans
+                .iter()
+                .find(|span| span.is_primary)
+                .map(|span| SourceLocation::from_span(span).to_string()),
+            check_trait: Self::extract_check_trait_from_diagnostic(diagnostic),
+            unsatisfied_bound: extract_unsatisfied_bound(&diagnostic.message).map(str::to_string),
+            decision,
+            rule,
+            entry: entry.map(|key| key.location.to_string()),
+            added,
+        });
+    }
+
+    /// Extracts the CGP information of a diagnostic and merges it into the entries
+    /// Returns how the diagnostic was grouped, for the grouping trace
+    fn analyze_diagnostic(&mut self, compiler_message: &CompilerMessage) -> GroupingOutcome {
+        let diagnostic = &compiler_message.message;
+
+        // Extract key components for grouping
+        let primary_span = match diagnostic.spans.iter().find(|s| s.is_primary) {
+            Some(span) => span,
+            None => {
+                // Can't process without a location
+                return (GroupingDecision::Skipped, None, None, Vec::new());
+            }
+        };
+
+        let location = SourceLocation::from_span(primary_span);
+
+        // Build the key using only location
+        // But first check if there's an existing entry in the same file with the same check_trait
+        // within a few lines (to handle check_components! blocks)
+        let check_trait = Self::extract_check_trait_from_diagnostic(diagnostic);
+        let mut matched_key = None;
+
+        if let Some(ref trait_name) = check_trait {
+            // Look for existing entries with the same check_trait in the same file
+            for (existing_key, existing_entry) in &self.entries {
+                if let Some(ref existing_trait) = existing_entry.check_trait {
+                    // Check if it's the same check trait and in the same file
+                    if existing_trait == trait_name && existing_key.location.file == location.file {
+                        // Check if they're close together (within 10 lines - typical for check_components! blocks)
+                        let line_diff = existing_key.location.line.abs_diff(location.line);
+
+                        if line_diff <= 10 {
+                            // These are likely in the same check_components! block
+                            matched_key =
+                                Some((existing_key.clone(), GroupingRule::SameCheckBlock));
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Otherwise, rustc may have split one error across messages whose spans differ slightly
+        if matched_key.is_none() {
+            matched_key = self.find_fragment_owner(&location, diagnostic);
+        }
+
+        if let Some((existing_key, rule)) = matched_key {
+            // Merge into a copy of the entry, which replaces the entry only once the merge
+            // is complete, so that a panic during the merge leaves the entry unchanged
+            let Some(existing) = self.entries.get(&existing_key) else {
+                return (GroupingDecision::Skipped, None, None, Vec::new());
+            };
+            let facts_before = entry_facts(existing);
+            let mut entry = existing.clone();
+
+            Self::merge_diagnostic_info(
+                &mut entry,
+                &mut self.symbol_cache,
+                diagnostic,
+                primary_span.clone(),
+                self.name_resolver.path_style(),
+            );
+            entry
+                .merged_diagnostics
+                .push(format!("{}: {}", location, diagnostic.message));
+            entry.raw_notes.push(raw_rendering(diagnostic));
+
+            let added = entry_facts(&entry)
+                .into_iter()
+                .filter(|fact| !facts_before.contains(fact))
+                .collect();
+            self.entries.insert(existing_key.clone(), entry);
+            self.diagnostics_merged += 1;
+            (
+                GroupingDecision::Merged,
+                Some(rule),
+                Some(existing_key),
+                added,
+            )
+        } else {
+            // Create new entry with this location as the key
+            let key = DiagnosticKey { location };
+            let mut entry = Self::create_entry(
+                diagnostic,
+                primary_span.clone(),
+                compiler_message.package_id.clone(),
+                compiler_message.target.clone(),
+                &mut self.symbol_cache,
+                self.name_resolver.path_style(),
+            );
+            entry.keep_redundant_hops = self.keep_redundant_hops;
+            let added = entry_facts(&entry);
+            self.entries.insert(key.clone(), entry);
+            (
+                GroupingDecision::Created,
+                Some(GroupingRule::NewLocation),
+                Some(key),
+                added,
+            )
+        }
+    }
+
+    /// Finds the entry that a fragment of an error belongs to: an entry in the same file
+    /// within a few lines that has the same unsatisfied trait bound
+    /// A fragment without a bound of its own (e.g., a note-only message) belongs to an entry
+    /// if it mentions the trait of the entry's bound
+    fn find_fragment_owner(
+        &self,
+        location: &SourceLocation,
+        diagnostic: &Diagnostic,
+    ) -> Option<(DiagnosticKey, GroupingRule)> {
+        let new_bound = extract_unsatisfied_bound(&diagnostic.message);
+
+        self.entries
+            .iter()
+            .filter(|(key, _)| {
+                key.location.file == location.file
+                    && key.location.line.abs_diff(location.line) <= FRAGMENT_LINE_WINDOW
+            })
+            .filter(|(_, entry)| {
+                let Some(bound) = extract_unsatisfied_bound(&entry.original.message) else {
+                    return false;
+                };
+                match new_bound {
+                    Some(new_bound) => new_bound == bound,
+                    None => mentions_bound_trait(diagnostic, bound),
+                }
+            })
+            // Prefer the closest entry, and break ties by location so the result is deterministic
+            .min_by_key(|(key, _)| {
+                (
+                    key.location.line.abs_diff(location.line),
+                    key.location.column.abs_diff(location.column),
+                    &key.location,
+                )
+            })
+            .map(|(key, _)| {
+                let rule = match new_bound {
+                    Some(_) => GroupingRule::SameUnsatisfiedBound,
+                    None => GroupingRule::MentionsBoundTrait,
+                };
+                (key.clone(), rule)
+            })
+    }
+
+    /// Creates a new diagnostic entry from a diagnostic
+    fn create_entry(
+        diagnostic: &Diagnostic,
+        primary_span: DiagnosticSpan,
+        package_id: PackageId,
+        target: Target,
+        symbol_cache: &mut SymbolCache,
+        paths: PathStyle,
+    ) -> DiagnosticEntry {
+        // Extract all available information
+        // A field with the wrong type is reported as a type mismatch, which names the field too
+        let (field_info, field_type_mismatch) =
+            match extract_field_type_mismatch(diagnostic, symbol_cache, paths) {
+                Some((field_info, mismatch)) => (Some(field_info), Some(mismatch)),
+                None => (extract_field_info(diagnostic, symbol_cache, paths), None),
+            };
+        let sized_info = extract_sized_info(diagnostic);
+        let missing_delegation = extract_missing_delegation(diagnostic, paths);
+        let not_a_provider = extract_not_a_provider(diagnostic, paths);
+        let overflow = extract_requirement_overflow(diagnostic, paths);
+        let auto_trait = extract_auto_trait_failure(diagnostic, paths);
+        let unimplemented_hint = extract_unimplemented_hint(diagnostic);
+        let component_info = Self::extract_component_info_from_diagnostic(diagnostic);
+        let check_trait = Self::extract_check_trait_from_diagnostic(diagnostic);
+        let usage_site = extract_usage_site(diagnostic, paths);
+        let provider_relationships =
+            Self::extract_provider_relationships_from_diagnostic(diagnostic, paths);
+        let delegation_notes = Self::extract_delegation_notes(diagnostic);
+        let consumer_trait_dependencies =
+            Self::extract_consumer_trait_dependencies_from_diagnostic(diagnostic, paths);
+        let has_other_hasfield_impls = has_other_hasfield_implementations(diagnostic);
+        let error_code = diagnostic.code.as_ref().map(|c| c.code.clone());
+
+        // Determine if this is a root cause
+        // A root cause has field_info (missing field), sized_info (unsized type),
+        // missing_delegation (component not wired), not_a_provider (wired to a non-provider),
+        // overflow (wiring that depends on itself), auto_trait (a context that is not `Send`),
+        // or is the most specific error
+        let is_root_cause = field_info.is_some()
+            || sized_info.is_some()
+            || missing_delegation.is_some()
+            || not_a_provider.is_some()
+            || overflow.is_some()
+            || auto_trait.is_some();
+
+        let mut entry = DiagnosticEntry {
+            original: diagnostic.clone(),
+            package_id,
+            target,
+            field_info,
+            field_type_mismatch,
+            sized_info,
+            missing_delegation,
+            not_a_provider,
+            overflow,
+            auto_trait,
+            unimplemented_hint,
+            component_infos: Vec::new(),
+            check_trait,
+            usage_site,
+            provider_relationships: Vec::new(),
+            delegation_notes: Vec::new(),
+            consumer_trait_dependencies: Vec::new(),
+            depends_on_components: Vec::new(), // Populated in second pass
+            linked_chains: Vec::new(),         // Populated in the linking pass
+            has_other_hasfield_impls,
+            primary_spans: Vec::new(),
+            error_code,
+            message: diagnostic.message.clone(),
+            is_root_cause,
+            suppressed: false,
+            merged_diagnostics: Vec::new(),
+            raw_notes: vec![raw_rendering(diagnostic)],
+            keep_redundant_hops: false,
+        };
+
+        entry.add_primary_span(primary_span);
+        if let Some(info) = component_info {
+            entry.add_component_info(info);
+        }
+        for relationship in provider_relationships {
+            entry.add_provider_relationship(relationship);
+        }
+        for note in delegation_notes {
+            entry.add_delegation_note(note);
+        }
+        for dependency in consumer_trait_dependencies {
+            entry.add_consumer_trait_dependency(dependency);
+        }
+
+        entry
+    }
+
+    /// Merges information from a new diagnostic into an existing entry
+    /// This handles the case where multiple components at the same location fail
+    /// due to the same root cause (e.g., transitive dependencies)
+    fn merge_diagnostic_info(
+        existing: &mut DiagnosticEntry,
+        symbol_cache: &mut SymbolCache,
+        new: &Diagnostic,
+        new_span: DiagnosticSpan,
+        paths: PathStyle,
+    ) {
+        // A field with the wrong type replaces a field that seemed to be missing
+        if existing.field_type_mismatch.is_none()
+            && let Some((field_info, mismatch)) =
+                extract_field_type_mismatch(new, symbol_cache, paths)
+        {
+            existing.field_info = Some(field_info);
+            existing.field_type_mismatch = Some(mismatch);
+            existing.is_root_cause = true;
+        }
+
+        // If the new diagnostic has field info and existing doesn't, add it
+        if existing.field_info.is_none()
+            && let Some(field_info) = extract_field_info(new, symbol_cache, paths)
+        {
+            existing.field_info = Some(field_info);
+            existing.is_root_cause = true;
+        }
+
+        if existing.sized_info.is_none()
+            && let Some(sized_info) = extract_sized_info(new)
+        {
+            existing.sized_info = Some(sized_info);
+            existing.is_root_cause = true;
+        }
+
+        if existing.missing_delegation.is_none()
+            && let Some(missing_delegation) = extract_missing_delegation(new, paths)
+        {
+            existing.missing_delegation = Some(missing_delegation);
+            existing.is_root_cause = true;
+        }
+
+        if existing.not_a_provider.is_none()
+            && let Some(not_a_provider) = extract_not_a_provider(new, paths)
+        {
+            existing.not_a_provider = Some(not_a_provider);
+            existing.is_root_cause = true;
+        }
+
+        if existing.overflow.is_none()
+            && let Some(overflow) = extract_requirement_overflow(new, paths)
+        {
+            existing.overflow = Some(overflow);
+            existing.is_root_cause = true;
+        }
+
+        // A context that is neither `Send` nor `Sync` fails both bounds of a provider
+        if let Some(new_failure) = extract_auto_trait_failure(new, paths) {
+            match &mut existing.auto_trait {
+                Some(failure) if failure.offending_type == new_failure.offending_type => {
+                    for auto_trait in new_failure.auto_traits {
+                        if !failure.auto_traits.contains(&auto_trait) {
+                            failure.auto_traits.push(auto_trait);
+                        }
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    existing.auto_trait = Some(new_failure);
+                    existing.is_root_cause = true;
+                }
+            }
+        }
+
+        // Merge hints, keeping the first custom headline
+        if let Some(new_hint) = extract_unimplemented_hint(new) {
+            let hint = existing
+                .unimplemented_hint
+                .get_or_insert_with(Default::default);
+            if hint.message.is_none() {
+                hint.message = new_hint.message;
+            }
+            for note in new_hint.notes {
+                if !hint.notes.contains(&note) {
+                    hint.notes.push(note);
+                }
+            }
+        }
+
+        // Merge component info - add new component and its span if not already present
+        if let Some(new_component_info) = Self::extract_component_info_from_diagnostic(new)
+            && existing.add_component_info(new_component_info)
+        {
+            existing.add_primary_span(new_span);
+        }
+
+        // Merge check trait
+        if existing.check_trait.is_none() {
+            existing.check_trait = Self::extract_check_trait_from_diagnostic(new);
+        }
+        if existing.usage_site.is_none() {
+            existing.usage_site = extract_usage_site(new, paths);
+        }
+
+        // Add new provider relationships
+        for rel in Self::extract_provider_relationships_from_diagnostic(new, paths) {
+            existing.add_provider_relationship(rel);
+        }
+
+        // Merge delegation notes
+        for note in Self::extract_delegation_notes(new) {
+            existing.add_delegation_note(note);
+        }
+
+        // Merge consumer trait dependencies
+        for dep in Self::extract_consumer_trait_dependencies_from_diagnostic(new, paths) {
+            existing.add_consumer_trait_dependency(dep);
+        }
+
+        // Update hasfield implementations flag
+        if !existing.has_other_hasfield_impls {
+            existing.has_other_hasfield_impls = has_other_hasfield_implementations(new);
+        }
+
+        // If the new diagnostic has an error code and existing doesn't, use it
+        if existing.error_code.is_none() {
+            existing.error_code = new.code.as_ref().map(|c| c.code.clone());
+        }
+    }
+
+    /// Extract component info from anywhere in the diagnostic
+    fn extract_component_info_from_diagnostic(diagnostic: &Diagnostic) -> Option<ComponentInfo> {
+        // A name from a truncated or elided type is only used if no complete name is found
+        let mut fallback = None;
+
+        // Try main message, then all children
+        let messages = std::iter::once(&diagnostic.message)
+            .chain(diagnostic.children.iter().map(|child| &child.message));
+        for message in messages {
+            if let Some(info) = extract_component_info(message) {
+                if info.confidence == Confidence::High {
+                    return Some(info);
+                }
+                fallback.get_or_insert(info);
+            }
+        }
+
+        // If message-based extraction failed or returned truncated result,
+        // try to extract component name from the span text
+        if let Some(span) = diagnostic.spans.iter().find(|s| s.is_primary)
+            && let Some(info) = Self::extract_component_info_from_span(span)
+        {
+            return Some(info);
+        }
+
+        fallback
+    }
+
+    /// Extract component info from the span's source text
+    /// This is used as a fallback when the compiler truncates type names in error messages
+    fn extract_component_info_from_span(span: &DiagnosticSpan) -> Option<ComponentInfo> {
+        // Concatenate all text lines from the span
+        let span_text: String = span
+            .text
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Extract component name from the span text
+        // The highlighted portion should contain something like "AreaCalculatorComponent"
+        extract_component_info(&span_text)
+    }
+
+    /// Extract check trait from diagnostic notes
+    fn extract_check_trait_from_diagnostic(diagnostic: &Diagnostic) -> Option<String> {
+        // Functions bounded by a consumer trait are usage sites, not check traits
+        for child in &diagnostic.children {
+            if matches!(child.level, DiagnosticLevel::Note)
+                && let Some(trait_name) = extract_check_trait(&child.message)
+                && !is_function_name(&trait_name)
+            {
+                return Some(trait_name);
+            }
+        }
+        None
+    }
+
+    /// Extract provider relationships from diagnostic notes
+    fn extract_provider_relationships_from_diagnostic(
+        diagnostic: &Diagnostic,
+        paths: PathStyle,
+    ) -> Vec<ProviderRelationship> {
+        let mut relationships = Vec::new();
+
+        for child in &diagnostic.children {
+            if matches!(child.level, DiagnosticLevel::Note)
+                && let Some(rel) = extract_provider_relationship(&child.message, paths)
+            {
+                relationships.push(rel);
+            }
+        }
+
+        relationships
+    }
+
+    /// Extract delegation chain notes
+    fn extract_delegation_notes(diagnostic: &Diagnostic) -> Vec<String> {
+        let mut notes = Vec::new();
+
+        for child in &diagnostic.children {
+            if matches!(child.level, DiagnosticLevel::Note)
+                && child.message.contains("required for")
+                && child.message.contains("to implement")
+            {
+                notes.push(child.message.clone());
+            }
+        }
+
+        notes
+    }
+
+    /// Extract consumer trait dependencies from delegation notes
+    fn extract_consumer_trait_dependencies_from_diagnostic(
+        diagnostic: &Diagnostic,
+        paths: PathStyle,
+    ) -> Vec<crate::cgp_patterns::ConsumerTraitDependency> {
+        use crate::cgp_patterns::extract_consumer_trait_dependency;
+
+        let mut dependencies = Vec::new();
+
+        for child in &diagnostic.children {
+            if matches!(child.level, DiagnosticLevel::Note)
+                && let Some(dep) = extract_consumer_trait_dependency(&child.message, paths)
+            {
+                dependencies.push(dep);
+            }
+        }
+
+        dependencies
+    }
+
+    /// Get all non-suppressed entries about the components selected by `--component`,
+    /// the most likely root causes first
+    pub fn get_active_entries(&self) -> Vec<&DiagnosticEntry> {
+        let mut entries: Vec<&DiagnosticEntry> = self
+            .entries
+            .values()
+            .filter(|e| !e.suppressed && self.matches_component_filters(e))
+            .collect();
+        rank_by_causal_priority(&mut entries);
+        entries
+    }
+
+    /// Returns true if an entry is about a component selected by `--component`
+    fn matches_component_filters(&self, entry: &DiagnosticEntry) -> bool {
+        self.component_filters.is_empty()
+            || entry.component_infos().iter().any(|info| {
+                self.component_filters
+                    .iter()
+                    .any(|pattern| component_matches(pattern, &info.component_type))
+            })
+    }
+
+    /// Returns the number of non-suppressed entries hidden by `--component`
+    pub fn hidden_by_component_filters(&self) -> usize {
+        self.entries
+            .values()
+            .filter(|e| !e.suppressed && !self.matches_component_filters(e))
+            .count()
+    }
+
+    /// Returns the number of compiler diagnostics added to the database
+    pub fn diagnostics_seen(&self) -> usize {
+        self.diagnostics_seen
+    }
+
+    /// Returns the number of diagnostics that were merged into an existing entry
+    pub fn merged_count(&self) -> usize {
+        self.diagnostics_merged
+    }
+
+    /// Returns the number of entries suppressed as redundant
+    pub fn suppressed_count(&self) -> usize {
+        self.entries.values().filter(|e| e.suppressed).count()
+    }
+
+    /// Returns the suppressed entries about the selected components, as
+    /// `file:line:column: message`, in source order
+    pub fn suppressed_summaries(&self) -> Vec<String> {
+        let mut suppressed: Vec<(&DiagnosticKey, &DiagnosticEntry)> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| e.suppressed && self.matches_component_filters(e))
+            .collect();
+        suppressed.sort_by_key(|(key, _)| &key.location);
+
+        suppressed
+            .into_iter()
+            .map(|(key, entry)| format!("{}: {}", key.location, entry.message))
+            .collect()
+    }
+
+    /// Get all entries (including suppressed)
+    pub fn get_all_entries(&self) -> Vec<&DiagnosticEntry> {
+        self.entries.values().collect()
+    }
+
+    /// Second pass: resolve component dependencies
+    /// This should be called after all diagnostics have been added
+    /// It matches consumer trait dependencies to actual components in the list
+    pub fn resolve_component_dependencies(&mut self) {
+        // Build a map of component names that exist in our diagnostic set
+        let mut component_names: std::collections::HashSet<String> =
+            std::collections::HashSet::new();
+        // Also build a map from consumer traits to components (based on the component index)
+        let mut consumer_trait_to_component: std::collections::HashMap<String, Vec<String>> =
+            std::collections::HashMap::new();
+
+        for entry in self.entries.values() {
+            for component_info in entry.component_infos() {
+                let component_name =
+                    crate::cgp_patterns::strip_module_prefixes(&component_info.component_type);
+                component_names.insert(component_name.clone());
+
+                // Map consumer trait to component
+                // Consumer trait "CanCalculateArea" corresponds to "AreaCalculatorComponent"
+                if let Some(consumer_trait) = self
+                    .name_resolver
+                    .consumer_trait_for_component(&component_name)
+                {
+                    consumer_trait_to_component
+                        .entry(consumer_trait)
+                        .or_default()
+                        .push(component_name.clone());
+                }
+            }
+        }
+
+        // Now populate depends_on_components for each entry
+        // We need to collect the updates first to avoid borrowing issues
+        let mut updates: Vec<(DiagnosticKey, Vec<String>)> = Vec::new();
+
+        for (key, entry) in &self.entries {
+            let mut depends_on = Vec::new();
+
+            for consumer_dep in entry.consumer_trait_dependencies() {
+                // Check if this consumer trait maps to any component in our set
+                // First try exact match with the resolved component name
+                if let Some(ref component_name) = self
+                    .name_resolver
+                    .component_for_consumer_trait(&consumer_dep.trait_name)
+                    && component_names.contains(component_name)
+                {
+                    if !depends_on.contains(component_name) {
+                        depends_on.push(component_name.clone());
+                    }
+                    continue;
+                }
+
+                // Try fuzzy match - check if any component could satisfy this consumer trait
+                // by checking if the consumer trait matches what any component provides
+                if let Some(components) = consumer_trait_to_component.get(&consumer_dep.trait_name)
+                {
+                    for comp in components {
+                        // Only add if it's not the same as one of our own components
+                        let is_own_component = entry.component_infos().iter().any(|c| {
+                            crate::cgp_patterns::strip_module_prefixes(&c.component_type) == *comp
+                        });
+
+                        if !is_own_component && !depends_on.contains(comp) {
+                            depends_on.push(comp.clone());
+                        }
+                    }
+                }
+            }
+
+            if !depends_on.is_empty() {
+                updates.push((key.clone(), depends_on));
+            }
+        }
+
+        // Apply the updates
+        for (key, depends_on) in updates {
+            if let Some(entry) = self.entries.get_mut(&key) {
+                entry.depends_on_components = depends_on;
+            }
+        }
+    }
+
+    /// Render all CGP error messages as CgpDiagnostic objects
+    /// This should be called after all diagnostics have been collected
+    /// Returns a vector of CgpDiagnostic objects with improved CGP diagnostics
+    pub fn render_cgp_diagnostics(&mut self) -> Vec<CgpDiagnostic> {
+        use crate::error_formatting::format_unanalyzed_diagnostic;
+
+        // First, resolve component dependencies
+        self.resolve_component_dependencies();
+        self.recover_field_names();
+        self.suppress_transitive_failures();
+        self.link_chains();
+
+        // Get all active (non-suppressed) entries
+        let active_entries = self.get_active_entries();
+
+        // Build CgpDiagnostic for each entry
+        // The first error of a check block also summarizes which of its components pass
+        let mut results = Vec::new();
+        let mut summarized_checks: Vec<&str> = Vec::new();
+        for entry in active_entries {
+            if let Some(mut diagnostic) = self.format_entry(entry) {
+                if let Some(check_trait) = entry.check_trait.as_deref()
+                    && !summarized_checks.contains(&check_trait)
+                {
+                    summarized_checks.push(check_trait);
+                    self.add_check_summary(&mut diagnostic, entry);
+                }
+                results.push(diagnostic);
+            }
+        }
+
+        results.extend(self.unanalyzed.iter().map(|compiler_message| {
+            let mut diagnostic = format_unanalyzed_diagnostic(&compiler_message.message);
+            diagnostic.raw_notes = vec![raw_rendering(&compiler_message.message)];
+            diagnostic.origin = Some(CompilerOrigin {
+                package_id: compiler_message.package_id.clone(),
+                target: compiler_message.target.clone(),
+                diagnostic: compiler_message.message.clone(),
+            });
+            diagnostic.display_symbols();
+            diagnostic
+        }));
+
+        results
+    }
+
+    /// Formats an entry, falling back to the original diagnostic if formatting panics
+    fn format_entry(&self, entry: &DiagnosticEntry) -> Option<CgpDiagnostic> {
+        use crate::error_formatting::{format_error_message, format_unanalyzed_diagnostic};
+
+        let mut diagnostic = catch_analysis_panic(|| {
+            format_error_message(entry, &self.name_resolver, self.chain_style)
+        })
+        .unwrap_or_else(|_| Some(format_unanalyzed_diagnostic(&entry.original)))?;
+        diagnostic.merged = entry.merged_diagnostics.clone();
+        diagnostic.raw_notes = entry.raw_notes.clone();
+        diagnostic.origin = Some(CompilerOrigin {
+            package_id: entry.package_id.clone(),
+            target: entry.target.clone(),
+            diagnostic: entry.original.clone(),
+        });
+        diagnostic.display_symbols();
+        Some(diagnostic)
+    }
+
+    /// Returns true if a confidently classified root cause (e.g., a missing field) was collected
+    pub fn has_root_cause(&self) -> bool {
+        self.entries.values().any(|entry| {
+            entry.is_root_cause && !entry.suppressed && self.matches_component_filters(entry)
+        })
+    }
+
+    /// Render only the root cause that appears first in the source
+    /// Used when the check is stopped as soon as one root cause is known, so the errors of
+    /// the other components are missing and no check summary is added
+    pub fn render_first_root_cause(&mut self) -> Option<CgpDiagnostic> {
+        self.resolve_component_dependencies();
+        self.recover_field_names();
+        self.suppress_transitive_failures();
+        self.link_chains();
+
+        self.entries
+            .iter()
+            .filter(|(_, entry)| {
+                entry.is_root_cause && !entry.suppressed && self.matches_component_filters(entry)
+            })
+            .min_by_key(|(key, _)| &key.location)
+            .and_then(|(_, entry)| self.format_entry(entry))
+    }
+
+    /// Lists which components of an entry's check block fail and which pass at the top of
+    /// its help, when the block lists several components and only some of them fail
+    fn add_check_summary(&self, diagnostic: &mut CgpDiagnostic, entry: &DiagnosticEntry) {
+        use crate::error_formatting::prepend_check_summary;
+
+        let Some(check_trait) = entry.check_trait.as_deref() else {
+            return;
+        };
+
+        // The note about the check trait bound spans the whole `check_components!` block
+        let trait_name = check_trait.rsplit("::").next().unwrap_or(check_trait);
+        let listed = entry
+            .original
+            .children
+            .iter()
+            .filter(|child| extract_check_trait(&child.message).as_deref() == Some(check_trait))
+            .flat_map(|child| &child.spans)
+            .map(|span| {
+                let lines: Vec<&str> = span.text.iter().map(|line| line.text.as_str()).collect();
+                checked_component_names(&lines.join("\n"), trait_name)
+            })
+            .find(|names| !names.is_empty())
+            .unwrap_or_default();
+
+        // Every error of the check trait counts, including the ones suppressed as transitive
+        let failing: Vec<&str> = self
+            .entries
+            .values()
+            .filter(|other| other.check_trait.as_deref() == Some(check_trait))
+            .flat_map(|other| other.component_infos())
+            .map(|info| component_name(&info.component_type))
+            .collect();
+
+        let components: Vec<(String, bool)> = listed
+            .iter()
+            .map(|name| (name.clone(), failing.contains(&component_name(name))))
+            .collect();
+        let failing_count = components
+            .iter()
+            .filter(|(_, is_failing)| *is_failing)
+            .count();
+        if failing_count == 0 || failing_count == components.len() {
+            return;
+        }
+
+        prepend_check_summary(diagnostic, check_trait, &components);
+    }
+
+    /// Build the dependency tree of every active entry
+    /// This should be called after all diagnostics have been collected
+    /// Entries without a check trait have no dependency tree and are skipped
+    pub fn dependency_trees(&mut self) -> Vec<DependencyNode> {
+        use crate::error_formatting::build_dependency_tree;
+
+        self.resolve_component_dependencies();
+        self.recover_field_names();
+        self.suppress_transitive_failures();
+        self.link_chains();
+
+        self.get_active_entries()
+            .into_iter()
+            .filter_map(|entry| build_dependency_tree(entry, &self.name_resolver))
+            .map(|mut tree| {
+                tree.display_symbols();
+                tree
+            })
+            .collect()
+    }
+
+    /// Render all CGP error messages
+    /// This should be called after all diagnostics have been collected
+    /// Returns a vector of formatted error message strings ready to print
+    pub fn render_cgp_errors(&mut self) -> Vec<String> {
+        use crate::error_formatting::render_diagnostic_plain;
+
+        self.render_cgp_diagnostics()
+            .iter()
+            .map(render_diagnostic_plain)
+            .collect()
+    }
+}
+
+/// Returns the package name from a cargo package ID
+/// Handles both the package ID spec format (e.g., "path+file:///work/my-app#0.1.0")
+/// and the legacy format (e.g., "my-app 0.1.0 (path+file:///work/my-app)")
+pub fn package_name(package_id: &PackageId) -> &str {
+    let repr = package_id.repr.as_str();
+
+    if let Some((url, fragment)) = repr.split_once('#') {
+        return match fragment.split_once('@') {
+            Some((name, _)) => name,
+            // The name is omitted when it matches the last segment of the URL
+            None => url.trim_end_matches('/').rsplit('/').next().unwrap_or(url),
+        };
+    }
+
+    repr.split(' ').next().unwrap_or(repr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cargo_metadata::Message;
+
+    #[test]
+    fn test_diagnostic_database_basic() {
+        let db = DiagnosticDatabase::new();
+        assert_eq!(db.get_all_entries().len(), 0);
+    }
+
+    #[test]
+    fn test_package_name() {
+        let id = |repr: &str| PackageId {
+            repr: repr.to_string(),
+        };
+
+        assert_eq!(
+            package_name(&id("path+file:///work/examples#cgp-example@0.1.0")),
+            "cgp-example"
+        );
+        assert_eq!(
+            package_name(&id("path+file:///work/my-app#0.1.0")),
+            "my-app"
+        );
+        assert_eq!(
+            package_name(&id("my-app 0.1.0 (path+file:///work/my-app)")),
+            "my-app"
+        );
+    }
+
+    #[test]
+    fn test_render_first_root_cause() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let fixture =
+            |name: &str| format!("{}/../examples/src/{}", env!("CARGO_MANIFEST_DIR"), name);
+
+        let mut db = load_diagnostic_database(fixture("base_area.json"));
+        assert!(db.has_root_cause());
+        let diagnostic = db.render_first_root_cause().unwrap();
+        assert!(diagnostic.message.starts_with("missing field"));
+
+        // A transitive provider failure is not a confidently classified root cause
+        let mut db = load_diagnostic_database(fixture("density.json"));
+        assert!(!db.has_root_cause());
+        assert!(db.render_first_root_cause().is_none());
+    }
+
+    #[test]
+    fn test_strict_resolution() {
+        use crate::name_resolution::ResolutionMode;
+        use crate::test_utils::{examples_dir, load_diagnostic_database, load_example_with_index};
+
+        // Without a component index, strict mode reports the provider traits as unresolved
+        let mut db = load_diagnostic_database(examples_dir().join("density.json"));
+        db.set_name_resolver(NameResolver::new(Vec::new(), ResolutionMode::Strict));
+        let rendered = db.render_cgp_errors().join("\n");
+        assert!(rendered.contains("provider trait of `DensityCalculatorComponent`"));
+        assert!(!rendered.contains("DensityCalculator<"));
+
+        // With the component index, the names come from the `#[cgp_component]` declarations
+        let (mut db, scan) = load_example_with_index("density");
+        db.set_name_resolver(NameResolver::new(
+            scan.component_decls,
+            ResolutionMode::Strict,
+        ));
+        let rendered = db.render_cgp_errors().join("\n");
+        assert!(!rendered.contains("(unresolved)"));
+        assert!(rendered.contains("`CanCalculateDensity` for `Rectangle`"));
+        assert!(rendered.contains("`DensityCalculator<Rectangle>` for provider"));
+    }
+
+    #[test]
+    fn test_doc_comments_in_dependency_chain() {
+        use crate::name_resolution::ResolutionMode;
+        use crate::test_utils::{examples_dir, load_diagnostic_database, scan_example};
+
+        // Document the provider and the component in a copy of the example source
+        let content = std::fs::read_to_string(examples_dir().join("density.rs"))
+            .unwrap()
+            .replace(
+                "#[cgp_impl(new RectangleArea)]",
+                "/// Multiplies the width by the height\n#[cgp_impl(new RectangleArea)]",
+            )
+            .replace(
+                "#[cgp_component(AreaCalculator)]",
+                "/// Calculates the area of a shape\n#[cgp_component(AreaCalculator)]",
+            );
+        let scan = scan_example("density", &content);
+
+        let mut db = load_diagnostic_database(examples_dir().join("density.json"));
+        db.set_name_resolver(NameResolver::from_scan(&scan, ResolutionMode::Heuristic));
+        let rendered = db.render_cgp_errors().join("\n");
+
+        assert!(rendered.contains(
+            "`CanCalculateArea` for `Rectangle` (consumer trait) — Calculates the area of a shape"
+        ));
+        assert!(rendered.contains(
+            "for provider `RectangleArea` (provider trait) ✗ — Multiplies the width by the height"
+        ));
+    }
+
+    #[test]
+    fn test_merge_split_error_fragments() {
+        use std::fs::File;
+        use std::io::BufReader;
+
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let message = Message::parse_stream(BufReader::new(File::open(json_path).unwrap()))
+            .find_map(|message| match message.unwrap() {
+                Message::CompilerMessage(msg) if msg.message.level == DiagnosticLevel::Error => {
+                    Some(msg)
+                }
+                _ => None,
+            })
+            .unwrap();
+
+        // A late note-only fragment whose span is shifted by a macro expansion
+        let fragment = |line_offset: usize| {
+            let mut fragment = message.clone();
+            fragment.message.level = DiagnosticLevel::Note;
+            fragment.message.message = "required for `Rectangle` to implement `cgp::prelude::CanUseComponent<AreaCalculatorComponent>`".to_string();
+            fragment.message.children.clear();
+            for span in &mut fragment.message.spans {
+                span.line_start += line_offset;
+                span.line_end += line_offset;
+                span.column_start += 4;
+                span.column_end += 4;
+            }
+            fragment
+        };
+
+        let mut db = DiagnosticDatabase::new();
+        db.add_diagnostic(&message);
+        db.add_diagnostic(&fragment(1));
+        assert_eq!(db.get_all_entries().len(), 1);
+
+        // Fragments far away from the error are kept separate
+        db.add_diagnostic(&fragment(20));
+        assert_eq!(db.get_all_entries().len(), 2);
+    }
+
+    #[test]
+    fn test_grouping_trace() {
+        use crate::grouping_trace::{GroupingDecision, GroupingRule};
+        use std::fs::File;
+        use std::io::BufReader;
+
+        let json_path = format!(
+            "{}/../examples/src/density.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let mut db = DiagnosticDatabase::new();
+        db.enable_grouping_trace();
+        for message in Message::parse_stream(BufReader::new(File::open(json_path).unwrap())) {
+            if let Message::CompilerMessage(msg) = message.unwrap()
+                && crate::cgp_patterns::is_cgp_diagnostic(&msg.message)
+            {
+                db.add_diagnostic(&msg);
+            }
+        }
+
+        let events = &db.grouping_trace().unwrap().events;
+        let first = &events[0];
+        assert_eq!(first.decision, GroupingDecision::Created);
+        assert_eq!(first.rule, Some(GroupingRule::NewLocation));
+        assert_eq!(first.entry.as_deref(), Some("examples/src/density.rs:64:9"));
+        assert!(first.added.contains(
+            &"provider `DensityFromMassField` for `DensityCalculatorComponent`".to_string()
+        ));
+        assert_eq!(
+            events.len(),
+            db.get_all_entries().len()
+                + events
+                    .iter()
+                    .filter(|event| event.decision == GroupingDecision::Merged)
+                    .count()
+        );
+    }
+
+    #[test]
+    fn test_component_filters() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let mut db = load_diagnostic_database(json_path);
+
+        db.set_component_filters(vec!["*Calculator*".to_string()]);
+        assert_eq!(db.get_active_entries().len(), 1);
+        assert_eq!(db.hidden_by_component_filters(), 0);
+
+        db.set_component_filters(vec!["Density*".to_string()]);
+        assert!(db.get_active_entries().is_empty());
+        assert_eq!(db.hidden_by_component_filters(), 1);
+        assert!(!db.has_root_cause());
+    }
+
+    #[test]
+    fn test_entry_invariants() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let db = load_diagnostic_database(json_path);
+        let mut entry = db.get_all_entries()[0].clone();
+
+        let first = entry.primary_span().unwrap().clone();
+        let mut earlier = first.clone();
+        earlier.line_start -= 1;
+        entry.add_primary_span(first.clone());
+        entry.add_primary_span(earlier.clone());
+        assert_eq!(entry.primary_spans(), &[earlier.clone(), first]);
+        assert_eq!(entry.primary_span(), Some(&earlier));
+
+        let notes = entry.delegation_notes().len();
+        entry.add_delegation_note("note".to_string());
+        entry.add_delegation_note("note".to_string());
+        assert_eq!(entry.delegation_notes().len(), notes + 1);
+
+        let component = entry.component_infos()[0].clone();
+        assert!(!entry.add_component_info(component));
+    }
+
+    #[test]
+    fn test_recover_field_names() {
+        use crate::test_utils::load_example_with_index;
+
+        let (mut db, _) = load_example_with_index("base_area");
+
+        // rustc hides the `h` of `height`, which the getter `HasRectangleFields` reads
+        let diagnostic = &db.render_cgp_diagnostics()[0];
+        assert_eq!(
+            diagnostic.message,
+            "missing field `height` in the context `Rectangle`."
+        );
+        let help = diagnostic.help.clone().unwrap();
+        assert!(help.contains("└─ field `height` on `Rectangle` ✗"));
+        assert!(!help.contains("hidden by the compiler"));
+    }
+
+    #[test]
+    fn test_keep_redundant_hops() {
+        use crate::cgp_patterns::Confidence;
+        use crate::test_utils::load_diagnostic_database;
+
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let db = load_diagnostic_database(json_path);
+        let mut entry = db.get_all_entries()[0].clone();
+        let relationship = |provider_type: &str| ProviderRelationship {
+            provider_type: provider_type.to_string(),
+            component: "AreaCalculatorComponent".to_string(),
+            context: "Rectangle".to_string(),
+            confidence: Confidence::High,
+        };
+        entry.add_provider_relationship(relationship("ScaledArea<RectangleArea>"));
+        entry.add_provider_relationship(relationship("RectangleArea"));
+
+        let providers = |entry: &DiagnosticEntry| -> Vec<String> {
+            entry
+                .chain_provider_relationships()
+                .into_iter()
+                .map(|rel| rel.provider_type)
+                .filter(|provider| provider.contains("RectangleArea"))
+                .collect()
+        };
+        assert_eq!(providers(&entry), vec!["ScaledArea<RectangleArea>"]);
+
+        entry.keep_redundant_hops = true;
+        assert_eq!(
+            providers(&entry),
+            vec!["RectangleArea", "ScaledArea<RectangleArea>"]
+        );
+    }
+
+    #[test]
+    fn test_usage_site() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let fixture =
+            |name: &str| format!("{}/../examples/src/{}", env!("CARGO_MANIFEST_DIR"), name);
+
+        let db = load_diagnostic_database(fixture("usage_area.json"));
+        let entries = db.get_all_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].check_trait, None);
+
+        let usage_site = entries[0].usage_site.as_ref().unwrap();
+        assert_eq!(usage_site.function, "total_area");
+        assert_eq!(usage_site.consumer_trait, "CanCalculateArea");
+        assert_eq!(usage_site.context_type, "Rectangle");
+        assert_eq!(usage_site.bound_span.as_ref().unwrap().line_start, 39);
+
+        // The check trait of `check_components!` is not a usage site
+        let db = load_diagnostic_database(fixture("base_area.json"));
+        assert!(
+            db.get_all_entries()
+                .iter()
+                .all(|entry| entry.usage_site.is_none())
+        );
+    }
+
+    #[test]
+    fn test_qualified_paths() {
+        use crate::cgp_patterns::is_cgp_diagnostic;
+        use crate::name_resolution::ResolutionMode;
+        use cargo_metadata::Message;
+
+        // The fixture, with the context in a module as if another module had a `Rectangle` too
+        let json = std::fs::read_to_string(format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap()
+        .replace("`Rectangle", "`shapes::Rectangle")
+        .replace(", Rectangle>", ", shapes::Rectangle>");
+
+        let render = |paths: PathStyle| {
+            let mut db = DiagnosticDatabase::new();
+            db.set_name_resolver(
+                NameResolver::new(Vec::new(), ResolutionMode::Heuristic).with_path_style(paths),
+            );
+            for message in Message::parse_stream(json.as_bytes()) {
+                if let Message::CompilerMessage(msg) = message.unwrap()
+                    && is_cgp_diagnostic(&msg.message)
+                {
+                    db.add_diagnostic(&msg);
+                }
+            }
+            db.render_cgp_errors().join("\n")
+        };
+
+        let short = render(PathStyle::Short);
+        assert!(short.contains("in the context `Rectangle`"));
+        assert!(!short.contains("shapes::"));
+
+        let qualified = render(PathStyle::Qualified);
+        assert!(qualified.contains("in the context `shapes::Rectangle`"));
+    }
+
+    #[test]
+    fn test_missing_delegation() {
+        use crate::test_utils::load_example_with_index;
+
+        let (mut db, _) = load_example_with_index("undelegated_area");
+
+        let entries = db.get_all_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind(), ErrorKind::MissingDelegation);
+        assert!(entries[0].is_root_cause);
+        let missing_delegation = entries[0].missing_delegation.as_ref().unwrap();
+        assert_eq!(missing_delegation.context_type, "Rectangle");
+        assert_eq!(missing_delegation.component, "PerimeterCalculatorComponent");
+        assert_eq!(
+            missing_delegation.block_span.as_ref().unwrap().line_start,
+            46
+        );
+        assert_eq!(
+            missing_delegation.wired_components,
+            vec!["AreaCalculatorComponent"]
+        );
+
+        // With the workspace index, the fix points at the entries of the block
+        let rendered = db.render_cgp_errors().join("\n");
+        assert!(rendered.contains(
+            "`delegate_components!` block of `Rectangle` at examples/src/undelegated_area.rs:48"
+        ));
+    }
+
+    #[test]
+    fn test_misnamed_component() {
+        use crate::test_utils::load_example_with_index;
+
+        let (mut db, _) = load_example_with_index("misnamed_component");
+
+        // The near-miss name is found in the delegation table of the workspace index
+        let rendered = db.render_cgp_errors().join("\n");
+        assert!(rendered.contains("did you mean `AreaCalculatorComponent`?"));
+        assert!(rendered.contains(
+            "`Rectangle` wires `AreaCalculatorComponent` at examples/src/misnamed_component.rs:38"
+        ));
+    }
+
+    #[test]
+    fn test_linked_chains() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let json_path = format!(
+            "{}/../examples/src/linked_density.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let mut db = load_diagnostic_database(json_path);
+        db.render_cgp_diagnostics();
+
+        // Only the density check stops at a provider that the area check traces further
+        let entries = db.get_active_entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].linked_chains.is_empty());
+        assert_eq!(entries[1].linked_chains.len(), 1);
+        let linked = &entries[1].linked_chains[0];
+        assert_eq!(
+            linked.provider.description,
+            "`AreaCalculator<Rectangle>` for provider `RectangleArea`"
+        );
+        assert_eq!(linked.location, "examples/src/linked_density.rs:73:9");
+
+        // The chain stays complete when `--component` hides the other entry
+        db.set_component_filters(vec!["Density*".to_string()]);
+        db.render_cgp_diagnostics();
+        assert_eq!(db.get_active_entries()[0].linked_chains.len(), 1);
+    }
+
+    #[test]
+    fn test_check_summary() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let examples = format!("{}/../examples/src", env!("CARGO_MANIFEST_DIR"));
+        let mut db = load_diagnostic_database(format!("{}/partial_check.json", examples));
+        let diagnostics = db.render_cgp_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        let help = diagnostics[0].help.as_deref().unwrap();
+        assert!(help.starts_with(
+            "Components checked by `CanUseRectangle`:\n    \
+             `AreaCalculatorComponent` ✗\n    \
+             `DensityCalculatorComponent` ✗\n    \
+             `MassGetterComponent` ✓\n\n"
+        ));
+        // The check stops at the first root cause, so the other components are not known
+        let first = db.render_first_root_cause().unwrap();
+        assert!(!first.help.unwrap().contains("Components checked"));
+
+        // No table when every component of the block fails
+        let mut db = load_diagnostic_database(format!("{}/density_3.json", examples));
+        let diagnostics = db.render_cgp_diagnostics();
+        assert!(diagnostics.iter().all(|diagnostic| {
+            !diagnostic
+                .help
+                .as_deref()
+                .unwrap()
+                .contains("Components checked")
+        }));
+    }
+
+    #[test]
+    fn test_not_a_provider() {
+        use crate::test_utils::load_example_with_index;
+
+        let (mut db, _) = load_example_with_index("not_a_provider_area");
+
+        let entries = db.get_all_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind(), ErrorKind::NotAProvider);
+        assert!(entries[0].is_root_cause);
+        let not_a_provider = entries[0].not_a_provider.as_ref().unwrap();
+        assert_eq!(not_a_provider.provider_type, "RectangleArea");
+        assert_eq!(not_a_provider.component, "PerimeterCalculatorComponent");
+        assert_eq!(not_a_provider.context_type, "Rectangle");
+        assert_eq!(
+            not_a_provider.provided_components,
+            vec!["AreaCalculatorComponent"]
+        );
+        assert_eq!(
+            not_a_provider.provider_span.as_ref().unwrap().line_start,
+            20
+        );
+
+        // With the workspace index, the fix names a provider of the trait
+        let rendered = db.render_cgp_errors().join("\n");
+        assert!(
+            rendered.contains("provider of `PerimeterCalculator` (e.g., `RectanglePerimeter`)")
+        );
+        assert!(rendered.contains("not_a_provider_area.rs:51"));
+    }
+
+    #[test]
+    fn test_field_type_mismatch() {
+        use crate::test_utils::load_example_with_index;
+
+        let (mut db, _) = load_example_with_index("wrong_type_area");
+
+        let entries = db.get_all_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind(), ErrorKind::FieldTypeMismatch);
+        assert!(entries[0].is_root_cause);
+        assert_eq!(entries[0].field_info.as_ref().unwrap().field_name, "height");
+        let mismatch = entries[0].field_type_mismatch.as_ref().unwrap();
+        assert_eq!(mismatch.expected_type, "f64");
+        assert_eq!(mismatch.actual_type.as_deref(), Some("u32"));
+
+        // With the workspace index, the fixes point at the struct and the getter trait
+        let rendered = db.render_cgp_errors().join("\n");
+        assert!(rendered.contains("field `height` has type `u32` but `f64` is required"));
+        assert!(rendered.contains("to `f64` at examples/src/wrong_type_area.rs:26"));
+        assert!(rendered.contains("to return `u32` at examples/src/wrong_type_area.rs:8"));
+    }
+
+    #[test]
+    fn test_symbols_in_copied_text() {
+        use serde_json::json;
+
+        // A component tagged with a field name, whose unsatisfied bound is copied into
+        // the message of the generic error
+        let symbol = "Symbol<3, cgp::prelude::Chars<'u', cgp::prelude::Chars<'r', \
+                      cgp::prelude::Chars<'l', Nil>>>>";
+        let note = |message: String| {
+            json!({
+                "message": message, "code": null, "level": "note",
+                "spans": [], "children": [], "rendered": null,
+            })
+        };
+        let message: CompilerMessage = serde_json::from_value(json!({
+            "package_id": "path+file:///app#app@0.1.0",
+            "target": {
+                "kind": ["lib"], "crate_types": ["lib"], "name": "app",
+                "src_path": "/app/src/lib.rs", "edition": "2024",
+                "doc": true, "doctest": true, "test": true,
+            },
+            "message": {
+                "message": format!(
+                    "the trait bound `Config: cgp::prelude::CanUseComponent<SettingGetterComponent<{}>>` is not satisfied",
+                    symbol
+                ),
+                "code": { "code": "E0277", "explanation": null },
+                "level": "error",
+                "spans": [{
+                    "file_name": "src/lib.rs", "byte_start": 0, "byte_end": 0,
+                    "line_start": 40, "line_end": 40, "column_start": 9, "column_end": 31,
+                    "is_primary": true,
+                    "text": [{ "text": "        SettingGetterComponent<Symbol!(\"url\")>,", "highlight_start": 9, "highlight_end": 31 }],
+                    "label": null, "suggested_replacement": null,
+                    "suggestion_applicability": null, "expansion": null,
+                }],
+                "children": [note("required by a bound in `CanUseConfig`".to_string())],
+                "rendered": null,
+            },
+        }))
+        .unwrap();
+
+        let mut db = DiagnosticDatabase::new();
+        db.add_diagnostic(&message);
+        let diagnostics = db.render_cgp_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        let text = format!(
+            "{}\n{}",
+            diagnostics[0].message,
+            diagnostics[0].help.as_deref().unwrap_or_default()
+        );
+        assert!(
+            text.contains("SettingGetterComponent<Symbol!(\"url\")>"),
+            "{}",
+            text
+        );
+        assert!(!text.contains("Symbol<"), "{}", text);
+        // rustc's notes are reprinted as reported
+        assert!(diagnostics[0].raw_notes[0].contains(symbol));
+    }
+
+    #[test]
+    fn test_requirement_overflow() {
+        use crate::test_utils::load_example_with_index;
+
+        let (mut db, _) = load_example_with_index("cyclic_area");
+
+        let entries = db.get_all_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind(), ErrorKind::RequirementOverflow);
+        assert!(entries[0].is_root_cause);
+        let overflow = entries[0].overflow.as_ref().unwrap();
+        assert_eq!(
+            overflow.requirement.to_string(),
+            "Rectangle: CanCalculateArea"
+        );
+        assert_eq!(overflow.cycle().unwrap().len(), 3);
+
+        // With the workspace index, the fix points at the delegation of the component
+        let rendered = db.render_cgp_errors().join("\n");
+        assert!(rendered.contains("`Rectangle: CanCalculateArea` depends on itself"));
+        assert!(rendered.contains(
+            "Remove the bound `CanCalculateArea` from `ScaledArea` at examples/src/cyclic_area.rs:17"
+        ));
+        assert!(rendered.contains("cyclic_area.rs:33"));
+    }
+
+    #[test]
+    fn test_auto_trait_failure() {
+        use crate::test_utils::load_example_with_index;
+
+        let (mut db, _) = load_example_with_index("send_context");
+
+        let entries = db.get_all_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind(), ErrorKind::AutoTrait);
+        assert!(entries[0].is_root_cause);
+        let failure = entries[0].auto_trait.as_ref().unwrap();
+        assert_eq!(failure.auto_traits, vec!["Sync"]);
+        assert_eq!(failure.offending_type, "Rc<String>");
+        assert_eq!(failure.within_type.as_deref(), Some("App"));
+
+        // With the workspace index, the help names the field that holds the type
+        let rendered = db.render_cgp_errors().join("\n");
+        assert!(rendered.contains("the field `cache` of `App` has type `Rc<String>`"));
+        assert!(rendered.contains("Replace the type of the field `cache`"));
+        assert!(rendered.contains("send_context.rs:39"));
+    }
+}
@@ -0,0 +1,204 @@
+/// Module for mapping the spans of doc-tests back to the documented source file
+/// rustdoc compiles each doc-test under a pseudo file name such as
+/// `src/lib.rs - shapes::Rectangle (line 42)`, with line numbers counted from the opening
+/// code fence. The spans are mapped to the real file and line, and the columns are shifted
+/// past the doc comment marker, so that snippets are rendered from the real source.
+use std::borrow::Cow;
+
+use cargo_metadata::CompilerMessage;
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticSpan};
+
+/// The documented file and the line of the code fence of a doc-test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocTestPath<'a> {
+    /// The file containing the doc comment (e.g., "src/lib.rs")
+    pub file: &'a str,
+    /// The item documented by the doc comment (e.g., "shapes::Rectangle")
+    pub item: &'a str,
+    /// The line of the opening code fence (1-indexed)
+    pub fence_line: usize,
+}
+
+/// Parses a doc-test pseudo file name (e.g., `src/lib.rs - shapes::Rectangle (line 42)`)
+pub fn parse_doc_test_path(file_name: &str) -> Option<DocTestPath<'_>> {
+    let (rest, line) = file_name.strip_suffix(')')?.rsplit_once(" (line ")?;
+    let (file, item) = rest.split_once(" - ")?;
+
+    Some(DocTestPath {
+        file,
+        item,
+        fence_line: line.parse().ok()?,
+    })
+}
+
+/// Returns the compiler message with the spans of doc-tests mapped to the documented file,
+/// or the message itself if it has none
+pub fn map_doc_test_message(compiler_message: &CompilerMessage) -> Cow<'_, CompilerMessage> {
+    if !has_doc_test_spans(&compiler_message.message) {
+        return Cow::Borrowed(compiler_message);
+    }
+
+    let mut mapped = compiler_message.clone();
+    let mut sources = Vec::new();
+    map_diagnostic(&mut mapped.message, &mut sources);
+    Cow::Owned(mapped)
+}
+
+fn has_doc_test_spans(diagnostic: &Diagnostic) -> bool {
+    diagnostic
+        .spans
+        .iter()
+        .any(|span| parse_doc_test_path(&span.file_name).is_some())
+        || diagnostic.children.iter().any(has_doc_test_spans)
+}
+
+/// The documented files read so far, by file name (None if unreadable)
+type SourceCache = Vec<(String, Option<String>)>;
+
+fn map_diagnostic(diagnostic: &mut Diagnostic, sources: &mut SourceCache) {
+    for span in &mut diagnostic.spans {
+        map_span(span, sources);
+    }
+    for child in &mut diagnostic.children {
+        map_diagnostic(child, sources);
+    }
+}
+
+/// Maps a span (and the macro expansions it comes from) to the documented file
+fn map_span(span: &mut DiagnosticSpan, sources: &mut SourceCache) {
+    if let Some(expansion) = &mut span.expansion {
+        map_span(&mut expansion.span, sources);
+        if let Some(def_site_span) = &mut expansion.def_site_span {
+            map_span(def_site_span, sources);
+        }
+    }
+
+    let Some(doc_test) = parse_doc_test_path(&span.file_name) else {
+        return;
+    };
+    let file = doc_test.file.to_string();
+    let line_offset = doc_test.fence_line;
+
+    span.file_name = file.clone();
+    span.line_start += line_offset;
+    span.line_end += line_offset;
+
+    // Without the source, the columns stay relative to the code of the doc-test
+    let Some(content) = source_content(sources, &file) else {
+        return;
+    };
+    let real_line = |line: usize| content.lines().nth(line.wrapping_sub(1));
+
+    if let Some(line) = real_line(span.line_start) {
+        span.column_start += doc_comment_prefix_len(line);
+    }
+    if let Some(line) = real_line(span.line_end) {
+        span.column_end += doc_comment_prefix_len(line);
+    }
+    for (i, text) in span.text.iter_mut().enumerate() {
+        if let Some(line) = real_line(span.line_start + i) {
+            let prefix_len = doc_comment_prefix_len(line);
+            text.text = line.to_string();
+            text.highlight_start += prefix_len;
+            text.highlight_end += prefix_len;
+        }
+    }
+}
+
+/// Reads a documented file, once per file
+fn source_content<'a>(sources: &'a mut SourceCache, file: &str) -> Option<&'a str> {
+    let index = match sources.iter().position(|(name, _)| name == file) {
+        Some(index) => index,
+        None => {
+            sources.push((file.to_string(), std::fs::read_to_string(file).ok()));
+            sources.len() - 1
+        }
+    };
+    sources[index].1.as_deref()
+}
+
+/// Returns the length of the doc comment marker that rustdoc strips from a line of a doc-test
+/// (e.g., 8 for `    /// let x = 1;`): the indentation, `///` or `//!`, and one space
+fn doc_comment_prefix_len(line: &str) -> usize {
+    let code = line.trim_start();
+    let indent = line.len() - code.len();
+    match code
+        .strip_prefix("///")
+        .or_else(|| code.strip_prefix("//!"))
+    {
+        Some(rest) => indent + 3 + usize::from(rest.starts_with(' ')),
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_doc_test_path() {
+        assert_eq!(
+            parse_doc_test_path("src/lib.rs - shapes::Rectangle (line 42)"),
+            Some(DocTestPath {
+                file: "src/lib.rs",
+                item: "shapes::Rectangle",
+                fence_line: 42,
+            })
+        );
+        assert_eq!(parse_doc_test_path("src/lib.rs"), None);
+        assert_eq!(parse_doc_test_path("src/lib.rs - mymod (line x)"), None);
+
+        assert_eq!(doc_comment_prefix_len("    /// let x = 1;"), 8);
+        assert_eq!(doc_comment_prefix_len("//!let x = 1;"), 3);
+        assert_eq!(doc_comment_prefix_len("let x = 1;"), 0);
+    }
+
+    #[test]
+    fn test_map_doc_test_message() {
+        let file =
+            std::env::temp_dir().join(format!("cargo-cgp-doctest-{}.rs", std::process::id()));
+        std::fs::write(
+            &file,
+            "/// Wires the rectangle\n///\n/// ```\n/// check_components! {\n///     CanUseRectangle for Rectangle {\n///         AreaCalculatorComponent,\n///     }\n/// }\n/// ```\npub struct Rectangle;\n",
+        )
+        .unwrap();
+
+        let message: CompilerMessage = serde_json::from_value(serde_json::json!({
+            "package_id": "path+file:///app#0.1.0",
+            "manifest_path": "/app/Cargo.toml",
+            "target": {
+                "name": "app", "kind": ["lib"], "crate_types": ["lib"],
+                "src_path": "/app/src/lib.rs", "edition": "2024",
+                "doctest": true, "test": true, "doc": true
+            },
+            "message": {
+                "message": "the trait bound `Rectangle: CanUseComponent<AreaCalculatorComponent>` is not satisfied",
+                "code": null, "level": "error", "children": [], "rendered": null,
+                "spans": [{
+                    "file_name": format!("{} - Rectangle (line 3)", file.display()),
+                    "byte_start": 0, "byte_end": 0,
+                    "line_start": 3, "line_end": 3,
+                    "column_start": 9, "column_end": 32,
+                    "is_primary": true, "label": null,
+                    "suggested_replacement": null, "suggestion_applicability": null,
+                    "expansion": null,
+                    "text": [{ "text": "        AreaCalculatorComponent,", "highlight_start": 9, "highlight_end": 32 }]
+                }]
+            }
+        }))
+        .unwrap();
+
+        let mapped = map_doc_test_message(&message);
+        let span = &mapped.message.spans[0];
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(span.file_name, file.display().to_string());
+        assert_eq!((span.line_start, span.line_end), (6, 6));
+        assert_eq!((span.column_start, span.column_end), (13, 36));
+        assert_eq!(span.text[0].text, "///         AreaCalculatorComponent,");
+        assert_eq!(
+            (span.text[0].highlight_start, span.text[0].highlight_end),
+            (13, 36)
+        );
+    }
+}
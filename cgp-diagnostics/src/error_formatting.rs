@@ -0,0 +1,4307 @@
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel};
+use serde::{Deserialize, Serialize};
+
+use crate::cgp_diagnostic::{
+    CgpDiagnostic, Severity, SpanLocation, WiringKind, WiringLocation, label_id_of, sort_spans,
+};
+use crate::cgp_patterns::{
+    AutoTraitFailure, ComponentInfo, Confidence, FieldInfo, FieldTypeMismatch, MissingDelegation,
+    NotAProvider, PathStyle, ProviderRelationship, Requirement, RequirementOverflow,
+    UnimplementedHint, UsageSite, strip_module_prefixes, strip_module_prefixes_in,
+    strip_path_prefixes,
+};
+use crate::diagnostic_db::{DiagnosticEntry, LinkedChain, package_name};
+use crate::explain::CgpErrorCode;
+use crate::field_name::FieldName;
+use crate::name_resolution::{NameResolver, closest_name, shares_significant_word};
+use crate::panic_guard::LOG_ENV;
+#[cfg(not(feature = "graphical"))]
+use crate::plain_render::render_plain;
+use crate::root_cause::{ProviderNesting, provider_nesting};
+use crate::symbol_display::display_symbols;
+use crate::type_expr::{
+    generic_arguments_at, normalize_type, parse_trait_bound, parse_type, same_type,
+};
+use crate::workspace_scan::{
+    CheckBlock, DuplicateCheck, SourceLocation, base_type_name, contains_type_argument,
+};
+
+/// Number of components named in the headline of an error about many components
+const MAX_LISTED_COMPONENTS: usize = 5;
+
+/// The kind of requirement represented by a dependency tree node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    /// The check trait generated by `check_components!`
+    Check,
+    /// The components used outside of `check_components!` (e.g., in a function body)
+    Usage,
+    /// A consumer trait, e.g. `CanCalculateArea`
+    Consumer,
+    /// A provider trait implemented by a provider type
+    Provider,
+    /// A getter trait such as `HasRectangleFields`
+    Getter,
+    /// A field required on the context
+    Field,
+}
+
+impl NodeKind {
+    /// All node kinds, in the order they typically appear in a dependency chain
+    pub const ALL: [NodeKind; 6] = [
+        NodeKind::Check,
+        NodeKind::Usage,
+        NodeKind::Consumer,
+        NodeKind::Provider,
+        NodeKind::Getter,
+        NodeKind::Field,
+    ];
+
+    /// Short name of the node kind, matching its serialized form
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeKind::Check => "check",
+            NodeKind::Usage => "usage",
+            NodeKind::Consumer => "consumer",
+            NodeKind::Provider => "provider",
+            NodeKind::Getter => "getter",
+            NodeKind::Field => "field",
+        }
+    }
+
+    /// Parses a node kind from its short name
+    pub fn from_name(name: &str) -> Option<NodeKind> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Annotation shown after the node in the rendered tree (e.g., "check trait")
+    /// Usages and fields are described by their node text and have no annotation
+    pub fn trait_label(self) -> Option<&'static str> {
+        match self {
+            NodeKind::Check => Some("check trait"),
+            NodeKind::Consumer => Some("consumer trait"),
+            NodeKind::Provider => Some("provider trait"),
+            NodeKind::Getter => Some("getter trait"),
+            NodeKind::Usage | NodeKind::Field => None,
+        }
+    }
+}
+
+/// How the dependency chain is written in the help text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ChainStyle {
+    /// How many levels below the root to show, all if unset (`--tree-depth <n>`)
+    pub max_depth: Option<usize>,
+    /// Narrate the chain as numbered sentences instead of drawing a tree (`--narratable`)
+    pub narrate: bool,
+}
+
+/// Node in a dependency tree showing trait requirement relationships
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyNode {
+    /// Description of this requirement
+    pub description: String,
+    /// Kind of requirement (check, usage, consumer, provider, getter or field)
+    pub kind: NodeKind,
+    /// Whether this requirement is satisfied
+    pub is_satisfied: Option<bool>,
+    /// Whether this node is a reference to an earlier node (shown with (*) marker)
+    /// Used in flattened dependency trees to avoid duplicating subtrees
+    pub is_reference: bool,
+    /// Child dependencies
+    pub children: Vec<DependencyNode>,
+    /// First line of the doc comment of the provider or component behind this node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Where the provider struct behind this node is declared, when a CGP macro generates it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_at: Option<String>,
+}
+
+impl DependencyNode {
+    /// Rewrites the `Symbol` types in the descriptions of the tree as `Symbol!("name")`
+    pub fn display_symbols(&mut self) {
+        self.description = display_symbols(&self.description);
+        self.children
+            .iter_mut()
+            .for_each(DependencyNode::display_symbols);
+    }
+
+    /// Checks if any path from this node down to a leaf passes through the given hops in order
+    /// Each hop is a (kind, name) pair, where the name must appear in the node description
+    /// Hops do not need to be adjacent, so intermediate nodes can be skipped
+    /// Every child is searched, with the hop both matched at this node and left for a
+    /// descendant, so the chain is found even when sibling nodes share a kind and label
+    pub fn contains_chain(&self, hops: &[(NodeKind, &str)]) -> bool {
+        let Some(((kind, name), rest)) = hops.split_first() else {
+            return true;
+        };
+
+        let is_match = self.kind == *kind && self.description.contains(name);
+        if is_match && rest.is_empty() {
+            return true;
+        }
+
+        self.children
+            .iter()
+            .any(|child| (is_match && child.contains_chain(rest)) || child.contains_chain(hops))
+    }
+
+    /// Returns the providers that fail without any requirement traced below them
+    pub fn failing_provider_leaves(&self) -> Vec<&DependencyNode> {
+        if self.kind == NodeKind::Provider
+            && self.is_satisfied == Some(false)
+            && self.children.is_empty()
+        {
+            return vec![self];
+        }
+        self.children
+            .iter()
+            .flat_map(DependencyNode::failing_provider_leaves)
+            .collect()
+    }
+
+    /// Finds a provider node with the given description that has requirements traced below it
+    pub fn find_traced_provider(&self, description: &str) -> Option<&DependencyNode> {
+        if self.kind == NodeKind::Provider
+            && self.description == description
+            && !self.children.is_empty()
+            && !self.is_reference
+        {
+            return Some(self);
+        }
+        self.children
+            .iter()
+            .find_map(|child| child.find_traced_provider(description))
+    }
+
+    /// Continues the failing providers at the end of this tree with the requirements that
+    /// other entries trace below the same providers
+    fn link_chains(&mut self, linked_chains: &[LinkedChain]) {
+        if self.kind == NodeKind::Provider
+            && self.is_satisfied == Some(false)
+            && self.children.is_empty()
+        {
+            if let Some(linked) = linked_chains
+                .iter()
+                .find(|linked| linked.provider.description == self.description)
+            {
+                self.children = linked.provider.children.clone();
+                self.is_satisfied = linked.provider.is_satisfied;
+            }
+            return;
+        }
+        for child in &mut self.children {
+            child.link_chains(linked_chains);
+        }
+    }
+
+    /// Renders this tree with box-drawing characters, as shown in the dependency chain
+    pub fn render(&self) -> Vec<String> {
+        self.render_to_depth(None)
+    }
+
+    /// Renders the levels of this tree up to `max_depth` below the root, all if unset
+    /// Deeper requirements are summarized in one line
+    pub fn render_to_depth(&self, max_depth: Option<usize>) -> Vec<String> {
+        render_dependency_tree(self, "", true, true, max_depth)
+    }
+
+    /// Narrates the levels of this tree up to `max_depth` below the root as numbered
+    /// sentences, for screen readers (e.g., "2. Step 1 is provided by ...")
+    /// Each sentence names the step it belongs to, so that branches can be followed
+    pub fn narrate(&self, max_depth: Option<usize>) -> Vec<String> {
+        let mut sentences = Vec::new();
+        narrate_dependency_tree(self, None, max_depth, &mut sentences);
+        sentences
+    }
+
+    /// Renders this tree as a Graphviz DOT digraph, e.g. for `dot -Tsvg`
+    /// Unsatisfied requirements are drawn in red, references to earlier nodes dashed
+    pub fn render_dot(&self) -> String {
+        let mut lines = vec![
+            "digraph cgp_dependencies {".to_string(),
+            "    node [shape=box];".to_string(),
+        ];
+        let mut edges = Vec::new();
+        self.collect_graph(&mut 0, None, &mut |id, parent, node| {
+            let mut attributes = vec![format!("label={:?}", node.graph_label())];
+            if node.is_satisfied == Some(false) {
+                attributes.push("color=red".to_string());
+            }
+            if node.is_reference {
+                attributes.push("style=dashed".to_string());
+            }
+            lines.push(format!("    n{} [{}];", id, attributes.join(", ")));
+            if let Some(parent) = parent {
+                edges.push(format!("    n{} -> n{};", parent, id));
+            }
+        });
+
+        lines.extend(edges);
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    /// Renders this tree as a Mermaid flowchart, e.g. for Markdown in issue reports
+    pub fn render_mermaid(&self) -> String {
+        let mut lines = vec!["graph TD".to_string()];
+        let mut edges = Vec::new();
+        let mut unsatisfied = Vec::new();
+        self.collect_graph(&mut 0, None, &mut |id, parent, node| {
+            lines.push(format!(
+                "    n{}[\"{}\"]",
+                id,
+                escape_mermaid_label(&node.graph_label())
+            ));
+            if let Some(parent) = parent {
+                let arrow = if node.is_reference { "-.->" } else { "-->" };
+                edges.push(format!("    n{} {} n{}", parent, arrow, id));
+            }
+            if node.is_satisfied == Some(false) {
+                unsatisfied.push(format!("n{}", id));
+            }
+        });
+
+        lines.extend(edges);
+        if !unsatisfied.is_empty() {
+            lines.push("    classDef unsatisfied stroke:#d00,color:#d00".to_string());
+            lines.push(format!("    class {} unsatisfied", unsatisfied.join(",")));
+        }
+        lines.join("\n")
+    }
+
+    /// The text of this node in an exported graph, with its trait annotation
+    fn graph_label(&self) -> String {
+        match self.kind.trait_label() {
+            Some(trait_label) => format!("{} ({})", self.description, trait_label),
+            None => self.description.clone(),
+        }
+    }
+
+    /// Visits the nodes of this tree depth-first, numbering them from `next_id`
+    /// The visitor gets the number of each node and of its parent
+    fn collect_graph(
+        &self,
+        next_id: &mut usize,
+        parent: Option<usize>,
+        visit: &mut impl FnMut(usize, Option<usize>, &DependencyNode),
+    ) {
+        let id = *next_id;
+        *next_id += 1;
+        visit(id, parent, self);
+
+        // The subtree of a reference is shown where it first appears
+        if self.is_reference {
+            return;
+        }
+        for child in &self.children {
+            child.collect_graph(next_id, Some(id), visit);
+        }
+    }
+
+    /// Returns the number of requirements below this node
+    fn descendant_count(&self) -> usize {
+        self.children
+            .iter()
+            .map(|child| 1 + child.descendant_count())
+            .sum()
+    }
+}
+
+/// Escapes characters that Mermaid would interpret inside a quoted label
+/// Backticks are escaped too, since a label quoted with them is read as Markdown
+pub fn escape_mermaid_label(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace('`', "#96;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+/// Returns the context type that an entry is about, as spelled in the workspace
+pub fn entry_context_type(entry: &DiagnosticEntry, resolver: &NameResolver) -> Option<String> {
+    let context_type = entry
+        .field_info
+        .as_ref()
+        .map(|f| f.target_type.clone())
+        .or_else(|| {
+            // The context of a provider relationship is spelled out by the compiler
+            deepest_failing_provider(entry.provider_relationships()).map(|rel| rel.context.clone())
+        })
+        .or_else(|| {
+            // Try to extract from delegation notes
+            extract_context_from_notes(entry.delegation_notes(), resolver.path_style())
+        })
+        .or_else(|| {
+            // Usage sites name the context that does not implement the bound
+            entry
+                .usage_site
+                .as_ref()
+                .map(|site| site.context_type.clone())
+        })?;
+
+    // Checks in another crate (e.g., integration tests) see the context by its crate path
+    Some(resolver.strip_crate_paths(&context_type))
+}
+
+/// Formats a diagnostic entry as an improved CGP error message
+/// Names that are not spelled out by the compiler are looked up with the resolver
+pub fn format_error_message(
+    entry: &DiagnosticEntry,
+    resolver: &NameResolver,
+    chain_style: ChainStyle,
+) -> Option<CgpDiagnostic> {
+    // Format based on what kind of error this is
+    let diagnostic = if let (Some(field_info), Some(mismatch)) =
+        (&entry.field_info, &entry.field_type_mismatch)
+    {
+        format_field_type_mismatch_error(entry, field_info, mismatch, resolver, chain_style)
+    } else if let Some(field_info) = &entry.field_info {
+        // This is a missing field error - the most common CGP error
+        format_missing_field_error(entry, field_info, resolver, chain_style)
+    } else if let Some(sized_info) = &entry.sized_info {
+        format_unsized_type_error(entry, sized_info, resolver, chain_style)
+    } else if let Some(missing_delegation) = &entry.missing_delegation {
+        format_missing_delegation_error(entry, missing_delegation, resolver)
+    } else if let Some(not_a_provider) = &entry.not_a_provider {
+        format_not_a_provider_error(entry, not_a_provider, resolver)
+    } else if let Some(overflow) = &entry.overflow {
+        format_requirement_overflow_error(entry, overflow, resolver)
+    } else if let Some(auto_trait) = &entry.auto_trait {
+        format_auto_trait_error(entry, auto_trait, resolver)
+    } else if let Some(usage_site) = &entry.usage_site {
+        format_usage_site_error(entry, usage_site, resolver, chain_style)
+    } else {
+        // Fallback to a generic CGP error format
+        format_generic_cgp_error(entry, resolver, chain_style)
+    }?;
+
+    let mut diagnostic = match &entry.unimplemented_hint {
+        Some(hint) => apply_unimplemented_hint(diagnostic, hint),
+        None => diagnostic,
+    };
+    diagnostic.context_type = entry_context_type(entry, resolver);
+    diagnostic.wiring = wiring_locations(entry, diagnostic.context_type.as_deref(), resolver);
+
+    Some(diagnostic)
+}
+
+/// Locates the wiring constructs involved in an entry in the workspace sources: the check
+/// block, the delegation entries of its components, the implementations of their
+/// providers, the getter traits those providers require and the context struct
+pub fn wiring_locations(
+    entry: &DiagnosticEntry,
+    context_type: Option<&str>,
+    resolver: &NameResolver,
+) -> Vec<WiringLocation> {
+    let mut locations: Vec<WiringLocation> = Vec::new();
+    let mut push = |kind: WiringKind, name: &str, location: &SourceLocation| {
+        let location = WiringLocation {
+            kind,
+            name: name.to_string(),
+            file: location.display_path.display().to_string(),
+            line: location.line,
+            column: location.column,
+        };
+        if !locations.contains(&location) {
+            locations.push(location);
+        }
+    };
+
+    if let Some(check_trait) = &entry.check_trait
+        && let Some(location) = resolver.check_block_location(check_trait)
+    {
+        push(
+            WiringKind::CheckBlock,
+            base_type_name(check_trait),
+            location,
+        );
+    }
+
+    // The providers involved, with the provider trait they are used for
+    let mut providers: Vec<(String, String)> = Vec::new();
+    if let Some(context_type) = context_type {
+        for component_info in entry.component_infos() {
+            let component = strip_module_prefixes(&component_info.component_type);
+            let Some(delegation) = resolver.delegation_entry(context_type, &component) else {
+                continue;
+            };
+            push(
+                WiringKind::DelegationEntry,
+                &delegation.component,
+                &delegation.location,
+            );
+
+            // Every provider named by the entry (e.g., `ScaledArea` and `RectangleArea`)
+            if let Some(provider_trait) = resolver.provider_trait_for_component(&component) {
+                let names = delegation
+                    .provider
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .filter(|name| !name.is_empty());
+                for name in names {
+                    providers.push((name.to_string(), provider_trait.clone()));
+                }
+            }
+        }
+    }
+    for relationship in entry.provider_relationships() {
+        if let Some(provider_trait) = resolver.provider_trait_for_component(&relationship.component)
+        {
+            providers.push((relationship.provider_type.clone(), provider_trait));
+        }
+    }
+    if let Some(not_a_provider) = &entry.not_a_provider {
+        // The type is not a provider of the component, so its other implementations are shown
+        for provider_trait in resolver.implemented_provider_traits(&not_a_provider.provider_type) {
+            providers.push((
+                not_a_provider.provider_type.clone(),
+                provider_trait.to_string(),
+            ));
+        }
+    }
+
+    // For a missing field, only the getters reading the field are involved
+    let missing_field = entry
+        .field_info
+        .as_ref()
+        .map(|field_info| FieldName::of(field_info).to_string());
+    for (provider_type, provider_trait) in &providers {
+        let Some(provider) = resolver.provider_impl(provider_type, provider_trait) else {
+            continue;
+        };
+        push(
+            WiringKind::ProviderImpl,
+            base_type_name(&provider.provider_type),
+            &provider.location,
+        );
+        for getter in provider
+            .required_getters
+            .iter()
+            .filter_map(|getter_trait| resolver.getter_decl(getter_trait))
+        {
+            if missing_field.as_ref().is_none_or(|field_name| {
+                getter.fields.iter().any(|field| field.name == *field_name)
+            }) {
+                push(
+                    WiringKind::GetterTrait,
+                    &getter.trait_name,
+                    &getter.location,
+                );
+            }
+        }
+    }
+
+    if let Some(context_type) = context_type {
+        // Fields are added to the struct the context derefs to
+        match resolver.deref_target(context_type) {
+            Some(target) if entry.field_info.is_some() => {
+                push(
+                    WiringKind::ContextStruct,
+                    &target.type_name,
+                    &target.location,
+                );
+            }
+            _ => {
+                if let Some(location) = resolver.context_location(context_type) {
+                    push(
+                        WiringKind::ContextStruct,
+                        base_type_name(context_type),
+                        location,
+                    );
+                }
+            }
+        }
+    }
+
+    locations
+}
+
+/// Merges the hints of cgp's `#[diagnostic::on_unimplemented]` attributes into an error
+/// The library's headline is authoritative, so it replaces ours, which moves to the help
+/// Notes already covered by the help are not repeated
+fn apply_unimplemented_hint(
+    mut diagnostic: CgpDiagnostic,
+    hint: &UnimplementedHint,
+) -> CgpDiagnostic {
+    let help = diagnostic.help.take().unwrap_or_default();
+    let mut help_sections = Vec::new();
+
+    for note in &hint.notes {
+        if !help.contains(note.as_str()) {
+            help_sections.push(format!("note: {}", note));
+        }
+    }
+
+    if let Some(message) = &hint.message
+        && *message != diagnostic.message
+    {
+        let analysis = std::mem::replace(&mut diagnostic.message, message.clone());
+        help_sections.push(analysis);
+    }
+
+    if !help_sections.is_empty() && !help.is_empty() {
+        help_sections.push(String::new());
+    }
+    help_sections.push(help);
+
+    let help = help_sections.join("\n");
+    diagnostic.help = (!help.is_empty()).then_some(help);
+    diagnostic
+}
+
+/// Adds a table of the failing and passing components of a check block at the top of the help
+pub fn prepend_check_summary(
+    diagnostic: &mut CgpDiagnostic,
+    check_trait: &str,
+    components: &[(String, bool)],
+) {
+    let mut lines = vec![format!("Components checked by `{}`:", check_trait)];
+    for (component, is_failing) in components {
+        lines.push(format!(
+            "    `{}` {}",
+            component,
+            if *is_failing { "✗" } else { "✓" }
+        ));
+    }
+
+    if let Some(help) = diagnostic.help.take() {
+        lines.push(String::new());
+        lines.push(help);
+    }
+    diagnostic.help = Some(lines.join("\n"));
+}
+
+/// Classifies an entry into the CGP error catalog, see `cargo cgp explain`
+/// Entries that fit no class keep the compiler's error code
+pub fn cgp_error_code(entry: &DiagnosticEntry) -> Option<CgpErrorCode> {
+    if entry.sized_info.is_some() {
+        return Some(CgpErrorCode::UnsizedType);
+    }
+    if entry.missing_delegation.is_some() {
+        return Some(CgpErrorCode::MissingDelegation);
+    }
+    if entry.not_a_provider.is_some() {
+        return Some(CgpErrorCode::NotAProvider);
+    }
+    if entry.field_type_mismatch.is_some() {
+        return Some(CgpErrorCode::FieldTypeMismatch);
+    }
+    if entry.overflow.is_some() {
+        return Some(CgpErrorCode::RequirementOverflow);
+    }
+    if entry.auto_trait.is_some() {
+        return Some(CgpErrorCode::AutoTraitBound);
+    }
+
+    match &entry.field_info {
+        Some(_) if entry.has_other_hasfield_impls => Some(CgpErrorCode::MissingField),
+        Some(_) => Some(CgpErrorCode::MissingHasFieldDerive),
+        None if entry.usage_site.is_some() => Some(CgpErrorCode::UnsatisfiedUsageBound),
+        None if !entry.provider_relationships().is_empty() => {
+            Some(CgpErrorCode::UnsatisfiedProviderDependency)
+        }
+        // Without a provider, the component is not wired for the context at all
+        None if entry.message.contains("CanUseComponent<")
+            || entry.message.contains("DelegateComponent<") =>
+        {
+            Some(CgpErrorCode::MissingDelegation)
+        }
+        None => None,
+    }
+}
+
+/// Formats a missing field error with CGP-aware messaging
+fn format_missing_field_error(
+    entry: &DiagnosticEntry,
+    field_info: &crate::cgp_patterns::FieldInfo,
+    resolver: &NameResolver,
+    chain_style: ChainStyle,
+) -> Option<CgpDiagnostic> {
+    let formatted_field_name = FieldName::of(field_info).to_string();
+    let target_type = resolver.strip_crate_paths(&field_info.target_type);
+
+    // The fixes declare the field with the type read by its getter (e.g., `pub height: f64`),
+    // when the name is known in full
+    let field_declaration = match resolver.field_type(&field_info.field_name) {
+        Some(field_type) if field_info.is_complete && !field_info.has_unknown_chars => {
+            format!("pub {}: {}", formatted_field_name, field_type)
+        }
+        _ => formatted_field_name.clone(),
+    };
+
+    // Contexts that implement `Deref` get `HasField` from the fields of the target struct
+    let deref_target = resolver.deref_target(&target_type);
+
+    // Build the main error message
+    let message = if let Some(deref_target) = deref_target {
+        format!(
+            "missing field `{}` in `{}`, which the context `{}` derefs to.",
+            formatted_field_name, deref_target.type_name, target_type
+        )
+    } else if entry.has_other_hasfield_impls {
+        format!(
+            "missing field `{}` in the context `{}`.",
+            formatted_field_name, target_type
+        )
+    } else {
+        format!(
+            "missing field `{}` or `#[derive(HasField)]` in the context `{}`.",
+            formatted_field_name, target_type
+        )
+    };
+
+    // Build help message with clear sections
+    let mut help_sections = Vec::new();
+    let spans = entry_spans(entry);
+
+    // Get component names for context
+    // Entries merged from many diagnostics can name the same component with different paths
+    let mut component_names: Vec<String> = entry
+        .component_infos()
+        .iter()
+        .map(|c| strip_module_prefixes(&c.component_type))
+        .filter(|name| !name.contains("IsProviderFor<") && !name.contains("CanUseComponent<"))
+        .collect();
+    component_names.sort();
+    component_names.dedup();
+
+    // Section 1: High-level context
+    if entry.field_info.is_some() {
+        if !component_names.is_empty() {
+            if component_names.len() == 1 {
+                help_sections.push(format!(
+                    "Context `{}` is missing a required field to use `{}`.",
+                    target_type, component_names[0]
+                ));
+            } else {
+                // Multiple components affected
+                help_sections.push(format!(
+                    "Context `{}` is missing a required field to use multiple components: {}.",
+                    target_type,
+                    list_components_with_labels(&component_names, &spans)
+                ));
+            }
+        } else {
+            help_sections.push(format!(
+                "Context `{}` is missing a required field.",
+                target_type
+            ));
+        }
+    } else if !component_names.is_empty() {
+        if component_names.len() == 1 {
+            help_sections.push(format!(
+                "Context `{}` is missing a required field to use `{}`.",
+                target_type, component_names[0]
+            ));
+        } else {
+            help_sections.push(format!(
+                "Context `{}` is missing a required field to use multiple components: {}.",
+                target_type,
+                list_components_with_labels(&component_names, &spans)
+            ));
+        }
+    }
+
+    // Add note about missing field or derive
+    if let Some(deref_target) = deref_target {
+        help_sections.push(format!(
+            "    note: the field must be added to `{}`, which `{}` derefs to",
+            deref_target.type_name, target_type
+        ));
+    } else if entry.has_other_hasfield_impls {
+        help_sections.push(format!(
+            "    note: Missing field: `{}`",
+            formatted_field_name
+        ));
+    } else {
+        help_sections.push(format!(
+            "    note: Missing field: `{}` or struct needs `#[derive(HasField)]`",
+            formatted_field_name
+        ));
+    }
+
+    if let Some(note) = cross_crate_note(entry, &target_type, resolver) {
+        help_sections.push(format!("    note: {}", note));
+    }
+
+    let unwired_getter = find_unwired_getter(entry, &target_type, resolver);
+    if let Some((getter_trait, component)) = &unwired_getter {
+        help_sections.push(format!(
+            "    note: `{}` is declared with `#[cgp_getter]`, so `{}` must wire `{}` in `delegate_components!`",
+            getter_trait, target_type, component
+        ));
+    }
+
+    help_sections.push(String::new()); // Blank line
+
+    // Section 2: Field name warnings (if applicable)
+    if field_info.has_unknown_chars {
+        help_sections.push("note: some characters in the field name are hidden by the compiler and shown as '\u{FFFD}'".to_string());
+        help_sections.push(String::new());
+    }
+
+    // Section 3: Struct location (if we have source span)
+    // Use the first span if available
+    if let Some(deref_target) = deref_target {
+        help_sections.push(format!(
+            "The struct `{}` is defined at `{}:{}` but does not have the required field `{}`.",
+            deref_target.type_name,
+            deref_target.location.display_path.display(),
+            deref_target.location.line,
+            formatted_field_name
+        ));
+        help_sections.push(String::new());
+    } else if let Some(span) = entry.primary_span() {
+        help_sections.push(format!(
+            "The struct `{}` is defined at `{}:{}` but does not have the required field `{}`.",
+            target_type, span.file_name, span.line_start, formatted_field_name
+        ));
+        help_sections.push(String::new());
+    }
+
+    // Section 4: Dependency chain as tree
+    if !entry.delegation_notes().is_empty() {
+        help_sections.push("Dependency chain:".to_string());
+        let tree_lines = format_delegation_chain(entry, resolver, chain_style);
+        for line in tree_lines {
+            help_sections.push(format!("    {}", line));
+        }
+        help_sections.push(String::new());
+    }
+
+    // Section 5: Inner provider note (for higher-order providers)
+    let all_inner_providers = detect_inner_providers(entry.provider_relationships());
+    let deduped_relationships = entry.chain_provider_relationships();
+
+    if !all_inner_providers.is_empty() {
+        let outer_providers: Vec<_> = deduped_relationships
+            .iter()
+            .filter(|r| {
+                !all_inner_providers
+                    .iter()
+                    .any(|inner| inner == &r.provider_type)
+            })
+            .collect();
+
+        if !outer_providers.is_empty() {
+            let inner_providers: Vec<String> = all_inner_providers
+                .iter()
+                .filter(|inner| contains_type_argument(&outer_providers[0].provider_type, inner))
+                .map(|inner| format!("`{}`", inner))
+                .collect();
+            help_sections.push(format!(
+                "The error in the higher-order provider `{}` might be caused by its inner provider{} {}.",
+                outer_providers[0].provider_type,
+                if inner_providers.len() == 1 { "" } else { "s" },
+                inner_providers.join(" or ")
+            ));
+            help_sections.push(String::new());
+        }
+    }
+
+    // Section 6: Available fields, so that a typo stands out from a missing field
+    let fields_struct = deref_target.map_or(target_type.as_str(), |target| &target.type_name);
+    if let Some(fields) = resolver.struct_fields(fields_struct)
+        && !fields.is_empty()
+    {
+        help_sections.push(format!(
+            "available fields of `{}`: {}",
+            fields_struct,
+            fields.join(", ")
+        ));
+        help_sections.push(String::new());
+    }
+
+    // Section 7: How to fix
+    // Fixes are ordered from the shallowest in the dependency chain to the deepest,
+    // so a change to the wiring comes before a change to the context struct
+    help_sections.push("To fix this error:".to_string());
+    if let Some(replacement) = find_inner_replacement(entry, &target_type, resolver) {
+        for (index, line) in replacement.describe(&target_type).into_iter().enumerate() {
+            let bullet = if index == 0 { "    • " } else { "      " };
+            help_sections.push(format!("{}{}", bullet, line));
+        }
+    }
+    if let Some(swap) = find_provider_swap(entry) {
+        let wrapper_name = base_type_name(&swap.wrapper_provider);
+        help_sections.push(format!(
+            "    • Wire `{}` to the inner provider `{}` instead of `{}` for `{}`",
+            strip_module_prefixes(&swap.component),
+            swap.inner_provider,
+            swap.wrapper_provider,
+            target_type
+        ));
+        help_sections.push(format!(
+            "      (only `{}` requires the field `{}`, so this also drops the behavior of `{}`)",
+            wrapper_name, formatted_field_name, wrapper_name
+        ));
+    }
+    if let Some((_, component)) = &unwired_getter {
+        // The getter is not implemented for the context at all, whatever its fields
+        help_sections.push(format!(
+            "    • Wire `{}` to `UseFields` (or a custom getter) in `delegate_components!` for `{}`",
+            component, target_type
+        ));
+    } else if let Some(deref_target) = deref_target {
+        // Deriving `HasField` on the context would conflict with the impl through `Deref`
+        help_sections.push(format!(
+            "    • Add a field `{}` to the `{}` struct at {}:{}",
+            field_declaration,
+            deref_target.type_name,
+            deref_target.location.display_path.display(),
+            deref_target.location.line
+        ));
+        if !deref_target.derives_has_field {
+            help_sections.push(format!(
+                "    • Add `#[derive(HasField)]` to `{}` (not to `{}`, which gets its fields through `Deref`)",
+                deref_target.type_name, target_type
+            ));
+        }
+    } else if entry.has_other_hasfield_impls {
+        if let Some(span) = entry.primary_span() {
+            help_sections.push(format!(
+                "    • Add a field `{}` to the `{}` struct at {}:{}",
+                field_declaration, target_type, span.file_name, span.line_start
+            ));
+        } else {
+            help_sections.push(format!(
+                "    • Add a field `{}` to the `{}` struct",
+                field_declaration, target_type
+            ));
+        }
+    } else {
+        if let Some(span) = entry.primary_span() {
+            help_sections.push(format!(
+                "    • If the struct has the field `{}`, add `#[derive(HasField)]` to the struct definition at `{}:{}`",
+                formatted_field_name, span.file_name, span.line_start
+            ));
+        } else {
+            help_sections.push(format!(
+                "    • If the struct has the field `{}`, add `#[derive(HasField)]` to the struct definition",
+                formatted_field_name
+            ));
+        }
+        help_sections.push(format!(
+            "    • If the field is missing, add a `{}` field to the struct",
+            field_declaration
+        ));
+    }
+
+    let help = Some(help_sections.join("\n"));
+
+    let mut diagnostic = CgpDiagnostic::new(message, spans);
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = help;
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// A wiring change that removes a requirement introduced by a higher-order provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProviderSwap {
+    component: String,
+    /// The higher-order provider that introduces the failing requirement (e.g., "ScaledArea<RectangleArea>")
+    wrapper_provider: String,
+    /// The provider wrapped by it (e.g., "RectangleArea")
+    inner_provider: String,
+}
+
+/// Finds a higher-order provider that itself introduces the failing requirement
+/// If the deepest failing provider wraps another provider, wiring the inner provider
+/// directly avoids the requirement, at the cost of the wrapper's behavior
+fn find_provider_swap(entry: &DiagnosticEntry) -> Option<ProviderSwap> {
+    let deepest = deepest_failing_provider(entry.provider_relationships())?;
+    if deepest.confidence < Confidence::High {
+        return None;
+    }
+
+    let provider_type = &deepest.provider_type;
+    // A wiring change is only suggested for a provider type that was parsed completely
+    let provider = parse_type(provider_type).complete()?;
+    let inner_provider = provider
+        .generic_arguments()
+        .iter()
+        .find(|argument| argument.is_type())?;
+
+    Some(ProviderSwap {
+        component: deepest.component.clone(),
+        wrapper_provider: provider_type.clone(),
+        inner_provider: inner_provider.to_string(),
+    })
+}
+
+/// Another provider to wrap, when the provider inside a higher-order provider is the one
+/// that fails (e.g., `ScaledArea<SquareArea>` for a failing `ScaledArea<RectangleArea>`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InnerReplacement {
+    component: String,
+    /// The failing inner provider (e.g., "RectangleArea")
+    inner_provider: String,
+    /// A provider of the same trait whose requirements the context meets
+    replacement: String,
+    /// The wired provider with the inner provider replaced (e.g., "ScaledArea<SquareArea>")
+    rewired_provider: String,
+    /// The other providers that would fit
+    other_candidates: Vec<String>,
+}
+
+impl InnerReplacement {
+    /// The suggestion, followed by the delegation entry to write
+    fn describe(&self, context_type: &str) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "Replace the inner provider `{}` with `{}`, whose requirements `{}` meets:",
+                self.inner_provider, self.replacement, context_type
+            ),
+            format!("    {}: {},", self.component, self.rewired_provider),
+        ];
+        if !self.other_candidates.is_empty() {
+            let candidates: Vec<String> = self
+                .other_candidates
+                .iter()
+                .map(|candidate| format!("`{}`", candidate))
+                .collect();
+            lines.push(format!(
+                "(other providers that fit: {})",
+                candidates.join(", ")
+            ));
+        }
+        lines
+    }
+}
+
+/// Finds another inner provider for the higher-order provider wired for a component, when
+/// the inner provider fails and the workspace index has a provider of the same trait whose
+/// requirements the context meets
+fn find_inner_replacement(
+    entry: &DiagnosticEntry,
+    context_type: &str,
+    resolver: &NameResolver,
+) -> Option<InnerReplacement> {
+    let relationships = entry.provider_relationships();
+    let deepest = deepest_failing_provider(relationships)?;
+    if deepest.confidence < Confidence::High {
+        return None;
+    }
+
+    // The provider wired for the component wraps the failing one and is wrapped by none
+    let wired = relationships.iter().find(|rel| {
+        contains_type_argument(&rel.provider_type, &deepest.provider_type)
+            && !relationships
+                .iter()
+                .any(|other| contains_type_argument(&other.provider_type, &rel.provider_type))
+    })?;
+    let wired_type = parse_type(&wired.provider_type).complete()?;
+    let inner_type = parse_type(&deepest.provider_type).complete()?;
+
+    let provider_trait = resolver.provider_trait_for_component(&deepest.component)?;
+    let inner_name = base_type_name(&deepest.provider_type);
+    let mut candidates = resolver
+        .providers_satisfied_by(&provider_trait, context_type)
+        .into_iter()
+        .filter(|candidate| *candidate != inner_name);
+    let replacement = candidates.next()?;
+    let rewired_provider =
+        wired_type.replace_type_argument(&inner_type, &parse_type(replacement).complete()?);
+
+    Some(InnerReplacement {
+        component: strip_module_prefixes(&wired.component),
+        inner_provider: deepest.provider_type.clone(),
+        replacement: replacement.to_string(),
+        rewired_provider: rewired_provider.to_string(),
+        other_candidates: candidates.map(str::to_string).collect(),
+    })
+}
+
+/// The deepest failing provider, which does not wrap any other failing provider
+fn deepest_failing_provider(
+    relationships: &[ProviderRelationship],
+) -> Option<&ProviderRelationship> {
+    relationships.iter().find(|rel| {
+        !relationships.iter().any(|other| {
+            other.provider_type != rel.provider_type
+                && contains_type_argument(&rel.provider_type, &other.provider_type)
+        })
+    })
+}
+
+/// Formats an error about an unsized type used for a provider parameter that must be `Sized`
+fn format_unsized_type_error(
+    entry: &DiagnosticEntry,
+    sized_info: &crate::cgp_patterns::SizedInfo,
+    resolver: &NameResolver,
+    chain_style: ChainStyle,
+) -> Option<CgpDiagnostic> {
+    let unsized_type = resolver.strip_module_prefixes(&sized_info.unsized_type);
+    let provider = deepest_failing_provider(entry.provider_relationships());
+    let context_type = provider
+        .map(|rel| rel.context.clone())
+        .or_else(|| extract_context_from_notes(entry.delegation_notes(), resolver.path_style()))
+        .map(|context| resolver.strip_crate_paths(&context));
+
+    let message = match (provider, &context_type) {
+        (Some(provider), Some(context_type)) => format!(
+            "unsized type `{}` used by the provider `{}` in the context `{}`.",
+            unsized_type, provider.provider_type, context_type
+        ),
+        (None, Some(context_type)) => format!(
+            "unsized type `{}` used in the context `{}`.",
+            unsized_type, context_type
+        ),
+        _ => format!("unsized type `{}` used by a CGP provider.", unsized_type),
+    };
+
+    let mut help_sections = Vec::new();
+
+    let parameter = sized_info.type_parameter.as_ref().map_or_else(
+        || "a type parameter".to_string(),
+        |p| format!("its type parameter `{}`", p),
+    );
+    match provider {
+        Some(provider) => help_sections.push(format!(
+            "Provider `{}` requires {} to be `Sized`, but it is wired with `{}` for `{}`.",
+            provider.provider_type,
+            parameter,
+            unsized_type,
+            strip_module_prefixes(&provider.component)
+        )),
+        None => help_sections.push(format!(
+            "A provider requires {} to be `Sized`, but it is wired with `{}`.",
+            parameter, unsized_type
+        )),
+    }
+    if let Some(required_by) = &sized_info.required_by {
+        help_sections.push(format!(
+            "    note: the `Sized` requirement is implicit in the type parameters of `{}`",
+            required_by
+        ));
+    }
+    if let Some(note) = context_type
+        .as_deref()
+        .and_then(|context_type| cross_crate_note(entry, context_type, resolver))
+    {
+        help_sections.push(format!("    note: {}", note));
+    }
+    help_sections.push(String::new());
+
+    if !entry.delegation_notes().is_empty() {
+        help_sections.push("Dependency chain:".to_string());
+        for line in format_delegation_chain(entry, resolver, chain_style) {
+            help_sections.push(format!("    {}", line));
+        }
+        help_sections.push(String::new());
+    }
+
+    let provider_name = provider.map(|rel| base_type_name(&rel.provider_type));
+    help_sections.push("To fix this error:".to_string());
+    let implementation = provider_name.map_or_else(
+        || "the provider implementation".to_string(),
+        |name| format!("the implementation of `{}`", name),
+    );
+    let location = sized_info
+        .parameter_location
+        .as_ref()
+        .map(|location| format!(" at {}", location))
+        .unwrap_or_default();
+    match &sized_info.type_parameter {
+        Some(type_parameter) => help_sections.push(format!(
+            "    • Relax the bound with `{}: ?Sized` in {}{}, if it only uses `{}` behind a reference",
+            type_parameter, implementation, location, type_parameter
+        )),
+        None => help_sections.push(format!(
+            "    • Relax the bound with `?Sized` on the type parameter in {}{}, if it only uses the type behind a reference",
+            implementation, location
+        )),
+    }
+    match sized_alternative(&unsized_type) {
+        Some(alternative) => help_sections.push(format!(
+            "    • Or wire the provider with a sized type, such as `{}` instead of `{}`",
+            alternative, unsized_type
+        )),
+        None => help_sections.push(format!(
+            "    • Or wire the provider with a sized type instead of `{}`",
+            unsized_type
+        )),
+    }
+
+    let mut diagnostic = CgpDiagnostic::new(message, entry_spans(entry));
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// A common sized replacement for an unsized type
+fn sized_alternative(unsized_type: &str) -> Option<String> {
+    if unsized_type == "str" {
+        return Some("String".to_string());
+    }
+    if unsized_type.starts_with("dyn ") {
+        return Some(format!("Box<{}>", unsized_type));
+    }
+    unsized_type
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .map(|element| format!("Vec<{}>", element))
+}
+
+/// Formats a generic CGP error (when we don't have specific field info)
+fn format_generic_cgp_error(
+    entry: &DiagnosticEntry,
+    resolver: &NameResolver,
+    chain_style: ChainStyle,
+) -> Option<CgpDiagnostic> {
+    let message = entry.message.clone();
+
+    // Build help with simplified notes
+    let mut help_sections = Vec::new();
+
+    let context_type =
+        extract_unsatisfied_provider_from_message(&entry.message, resolver.path_style())
+            .map(|u| u.context_type)
+            .or_else(|| {
+                extract_context_from_notes(entry.delegation_notes(), resolver.path_style())
+            });
+    if let Some(note) = context_type
+        .as_deref()
+        .and_then(|context_type| cross_crate_note(entry, context_type, resolver))
+    {
+        help_sections.push(format!("note: {}", note));
+        help_sections.push(String::new());
+    }
+
+    if !entry.delegation_notes().is_empty() {
+        help_sections.push("Dependency chain:".to_string());
+        let delegation_lines = format_delegation_chain(entry, resolver, chain_style);
+        for line in delegation_lines {
+            help_sections.push(format!("  {}", line));
+        }
+        help_sections.push(String::new()); // Blank line
+    }
+
+    // A chain continued from another error already shows the missing dependencies
+    for linked in &entry.linked_chains {
+        help_sections.push(format!(
+            "The requirements below {} are traced by the error at {}.",
+            linked.provider.description, linked.location
+        ));
+    }
+
+    // Check for nested consumer traits and add help message for indirect components
+    let nested_consumers =
+        extract_nested_consumer_traits(entry.delegation_notes(), resolver.path_style());
+    if !nested_consumers.is_empty() && entry.linked_chains.is_empty() {
+        let context_type = context_type.as_deref().map_or_else(
+            || "the context".to_string(),
+            |c| resolver.strip_crate_paths(c),
+        );
+
+        // For each nested consumer trait, suggest checking its component
+        for nested_consumer in &nested_consumers {
+            if let Some(component_name) =
+                resolver.component_for_consumer_trait(&nested_consumer.trait_name)
+            {
+                help_sections.push(format!(
+                    "Add a check that `{}` can use `{}` using `check_components!` to get further details on the missing dependencies.",
+                    context_type,
+                    component_name
+                ));
+            } else if resolver.is_strict() {
+                help_sections.push(format!(
+                    "Add a check that `{}` can use the component of `{}` (unresolved) using `check_components!` to get further details on the missing dependencies.",
+                    context_type,
+                    nested_consumer.trait_name
+                ));
+            }
+        }
+    }
+
+    if let Some(context_type) = &context_type {
+        let context_type = resolver.strip_crate_paths(context_type);
+        if let Some(replacement) = find_inner_replacement(entry, &context_type, resolver) {
+            if !help_sections.is_empty() && help_sections.last() != Some(&String::new()) {
+                help_sections.push(String::new());
+            }
+            help_sections.extend(replacement.describe(&context_type));
+        }
+    }
+
+    let help = if help_sections.is_empty() {
+        None
+    } else {
+        Some(help_sections.join("\n"))
+    };
+
+    let mut diagnostic = CgpDiagnostic::new(message, entry_spans(entry));
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = help;
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// Formats an error at a usage site: a generic function bounded by a consumer trait is
+/// called with a context that does not implement it
+/// Without a check, the compiler stops at the provider that fails, so the fixes point to
+/// the wiring of the component and to a check that reports what the provider is missing
+fn format_usage_site_error(
+    entry: &DiagnosticEntry,
+    usage_site: &UsageSite,
+    resolver: &NameResolver,
+    chain_style: ChainStyle,
+) -> Option<CgpDiagnostic> {
+    let context_type = resolver.strip_crate_paths(&usage_site.context_type);
+    let message = format!(
+        "`{}` does not implement `{}`, which `{}` requires",
+        context_type, usage_site.consumer_trait, usage_site.function
+    );
+
+    let mut spans = entry_spans(entry);
+    if let Some(bound_span) = &usage_site.bound_span {
+        spans.push(SpanLocation::from_span(
+            bound_span,
+            "required by this bound",
+        ));
+    }
+
+    let unsatisfied_provider =
+        extract_unsatisfied_provider_from_message(&entry.message, resolver.path_style());
+    // The provider trait is spelled out by the compiler, unlike the component of the bound
+    let component = unsatisfied_provider
+        .as_ref()
+        .and_then(|provider| resolver.component_for_provider_trait(&provider.trait_name))
+        .or_else(|| resolver.component_for_consumer_trait(&usage_site.consumer_trait));
+    let is_wired = component
+        .as_deref()
+        .is_some_and(|component| resolver.wires_component(&context_type, component));
+
+    let mut help_sections = Vec::new();
+    if let Some(span) = &usage_site.bound_span {
+        help_sections.push(format!(
+            "The function `{}` requires `{}: {}` at `{}:{}`.",
+            usage_site.function,
+            base_type_name(&context_type),
+            usage_site.consumer_trait,
+            span.file_name,
+            span.line_start
+        ));
+    }
+    if let (true, Some(component), Some(provider)) = (is_wired, &component, &unsatisfied_provider) {
+        help_sections.push(format!(
+            "`{}` wires `{}` to `{}`, which does not implement `{}` for it.",
+            context_type, component, provider.provider_type, provider.trait_name
+        ));
+    }
+    if !help_sections.is_empty() {
+        help_sections.push(String::new());
+    }
+
+    help_sections.push("Dependency chain:".to_string());
+    for line in format_delegation_chain(entry, resolver, chain_style) {
+        help_sections.push(format!("    {}", line));
+    }
+    help_sections.push(String::new());
+
+    let component_name = component.as_deref().unwrap_or("the component");
+    help_sections.push("To fix this error:".to_string());
+    if !is_wired {
+        help_sections.push(format!(
+            "    • If `{}` does not wire `{}`, wire it to a provider in `delegate_components!`",
+            context_type, component_name
+        ));
+    }
+    help_sections.push(format!(
+        "    • Check `{}` next to the definition of `{}`, so that the compiler reports the dependencies that its provider is missing:",
+        component_name, context_type
+    ));
+    help_sections.push("      check_components! {".to_string());
+    help_sections.push(format!(
+        "          CanUse{} for {} {{",
+        base_type_name(&context_type),
+        context_type
+    ));
+    help_sections.push(format!("              {},", component_name));
+    help_sections.push("          }".to_string());
+    help_sections.push("      }".to_string());
+
+    let mut diagnostic = CgpDiagnostic::new(message, spans);
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// Formats an error about a component that the context does not delegate, which
+/// `check_components!` reports as an unsatisfied `DelegateComponent` bound
+fn format_missing_delegation_error(
+    entry: &DiagnosticEntry,
+    missing_delegation: &MissingDelegation,
+    resolver: &NameResolver,
+) -> Option<CgpDiagnostic> {
+    let context_type = resolver.strip_crate_paths(&missing_delegation.context_type);
+    let component = strip_module_prefixes(&missing_delegation.component);
+
+    // A wired component with a near-miss name is likely what the check meant, or the other
+    // way around; the wiring comes from the workspace index, or else from the compiler's note
+    let mut wired: Vec<String> = resolver
+        .wired_components(&context_type)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    if wired.is_empty() {
+        wired = missing_delegation
+            .wired_components
+            .iter()
+            .map(|component| strip_module_prefixes(component))
+            .collect();
+    }
+    let similar = closest_name(&component, wired.iter().map(String::as_str));
+
+    let message = match similar {
+        Some(similar) => format!(
+            "context `{}` does not delegate `{}`; did you mean `{}`?",
+            context_type, component, similar
+        ),
+        None => format!(
+            "context `{}` does not delegate `{}`; add it to `delegate_components!`",
+            context_type, component
+        ),
+    };
+
+    let mut spans = entry_spans(entry);
+    if let Some(block_span) = &missing_delegation.block_span {
+        spans.push(SpanLocation::from_span(
+            block_span,
+            &format!("`{}` is not wired here", component),
+        ));
+    }
+
+    // The block is located from the workspace index, or else from the compiler's note
+    let block_location = resolver
+        .delegation_block(&context_type)
+        .map(|location| format!(" at {}:{}", location.display_path.display(), location.line))
+        .or_else(|| {
+            missing_delegation
+                .block_span
+                .as_ref()
+                .map(|span| format!(" at {}:{}", span.file_name, span.line_start))
+        })
+        .unwrap_or_default();
+
+    let mut help_sections = vec![format!(
+        "The component `{}` is used with `{}`, but no provider is wired for it.",
+        component, context_type
+    )];
+    if let Some(similar) = similar {
+        let wired_at = resolver
+            .delegation_entry(&context_type, similar)
+            .map(|delegation| {
+                format!(
+                    " at {}:{}",
+                    delegation.location.display_path.display(),
+                    delegation.location.line
+                )
+            })
+            .unwrap_or_default();
+        help_sections.push(format!(
+            "    note: `{}` wires `{}`{}, whose name is close to `{}`",
+            context_type, similar, wired_at, component
+        ));
+    }
+    if let Some(note) = cross_crate_note(entry, &context_type, resolver) {
+        help_sections.push(format!("    note: {}", note));
+    }
+    help_sections.push(String::new());
+
+    // A misspelled component has no provider trait, so the one derived from its name
+    // would be invented
+    let provider = match similar {
+        Some(_) => None,
+        None => resolver.provider_trait_for_component(&component),
+    }
+    .map_or_else(
+        || "a provider".to_string(),
+        |provider_trait| format!("a provider implementing `{}`", provider_trait),
+    );
+    help_sections.push("To fix this error:".to_string());
+    if let Some(similar) = similar {
+        let used_in = entry
+            .check_trait
+            .as_ref()
+            .map(|check_trait| format!(" in `{}`", resolver.strip_crate_paths(check_trait)))
+            .unwrap_or_default();
+        help_sections.push(format!(
+            "    • If `{}` is misspelled, use `{}`{} instead",
+            component, similar, used_in
+        ));
+        help_sections.push(format!(
+            "    • Or, if `{}` is misspelled, rename it to `{}` in `delegate_components!`",
+            similar, component
+        ));
+    }
+    let wire = if similar.is_some() { "Or wire" } else { "Wire" };
+    help_sections.push(format!(
+        "    • {} `{}` to {} in the `delegate_components!` block of `{}`{}",
+        wire, component, provider, context_type, block_location
+    ));
+    if let Some(check_trait) = &entry.check_trait {
+        help_sections.push(format!(
+            "    • Or remove `{}` from `{}`, if `{}` should not use it",
+            component,
+            resolver.strip_crate_paths(check_trait),
+            context_type
+        ));
+    }
+
+    let mut diagnostic = CgpDiagnostic::new(message, spans);
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// Formats an error about a component delegated to a type that does not implement its
+/// provider trait, which the compiler reports as an unsatisfied `IsProviderFor` bound
+fn format_not_a_provider_error(
+    entry: &DiagnosticEntry,
+    not_a_provider: &NotAProvider,
+    resolver: &NameResolver,
+) -> Option<CgpDiagnostic> {
+    let provider_type = resolver.strip_crate_paths(&not_a_provider.provider_type);
+    let context_type = resolver.strip_crate_paths(&not_a_provider.context_type);
+    let component = strip_module_prefixes(&not_a_provider.component);
+    let provider_trait = resolver.provider_trait_for_component(&component);
+    let provided = provider_trait.as_deref().unwrap_or(&component);
+    let message = format!("`{}` is not a provider of `{}`", provider_type, provided);
+
+    let mut spans = entry_spans(entry);
+    if let Some(provider_span) = &not_a_provider.provider_span {
+        spans.push(SpanLocation::from_span(
+            provider_span,
+            &format!("`{}` is defined here", provider_type),
+        ));
+    }
+
+    let mut help_sections = vec![format!(
+        "`{}` delegates `{}` to `{}`, but `{}` does not implement `{}`.",
+        context_type, component, provider_type, provider_type, provided
+    )];
+
+    // What the type provides instead, from the workspace index or the compiler's note
+    let mut implemented: Vec<String> = resolver
+        .implemented_provider_traits(&provider_type)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    if implemented.is_empty() {
+        implemented = not_a_provider
+            .provided_components
+            .iter()
+            .map(|component| {
+                let component = strip_module_prefixes(component);
+                resolver
+                    .provider_trait_for_component(&component)
+                    .unwrap_or(component)
+            })
+            .collect();
+    }
+    if !implemented.is_empty() {
+        let implemented: Vec<String> = implemented
+            .iter()
+            .map(|provider_trait| format!("`{}`", provider_trait))
+            .collect();
+        help_sections.push(format!(
+            "    note: `{}` implements {}",
+            provider_type,
+            implemented.join(", ")
+        ));
+    }
+    help_sections.push(String::new());
+
+    let block_location = resolver
+        .delegation_block(&context_type)
+        .map(|location| format!(" at {}:{}", location.display_path.display(), location.line))
+        .unwrap_or_default();
+    let alternatives = provider_trait
+        .as_deref()
+        .map(|provider_trait| resolver.providers_of(provider_trait))
+        .unwrap_or_default();
+    let example = match alternatives.first() {
+        Some(alternative) => format!(" (e.g., `{}`)", alternative),
+        None => String::new(),
+    };
+
+    help_sections.push("To fix this error:".to_string());
+    help_sections.push(format!(
+        "    • Check that the `#[cgp_impl]` attribute of `{}` names the provider trait `{}`",
+        provider_type, provided
+    ));
+    help_sections.push(format!(
+        "    • Or delegate `{}` to a provider of `{}`{} in the `delegate_components!` block of `{}`{}",
+        component, provided, example, context_type, block_location
+    ));
+
+    let mut diagnostic = CgpDiagnostic::new(message, spans);
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// Formats an error about a field that the context has, but with another type than its
+/// getter reads
+fn format_field_type_mismatch_error(
+    entry: &DiagnosticEntry,
+    field_info: &FieldInfo,
+    mismatch: &FieldTypeMismatch,
+    resolver: &NameResolver,
+    chain_style: ChainStyle,
+) -> Option<CgpDiagnostic> {
+    let field_name = FieldName::of(field_info).to_string();
+    let target_type = resolver.strip_crate_paths(&field_info.target_type);
+    let expected_type = &mismatch.expected_type;
+
+    // Contexts that implement `Deref` get `HasField` from the fields of the target struct
+    let fields_struct = resolver
+        .deref_target(&target_type)
+        .map_or(target_type.clone(), |target| target.type_name.clone());
+    let actual_type = mismatch.actual_type.clone().or_else(|| {
+        resolver
+            .struct_field_type(&fields_struct, &field_info.field_name)
+            .map(str::to_string)
+    });
+
+    let message = match &actual_type {
+        Some(actual_type) => format!(
+            "field `{}` has type `{}` but `{}` is required",
+            field_name, actual_type, expected_type
+        ),
+        None => format!(
+            "field `{}` of `{}` does not have the required type `{}`",
+            field_name, target_type, expected_type
+        ),
+    };
+
+    let mut component_names: Vec<String> = entry
+        .component_infos()
+        .iter()
+        .map(|c| strip_module_prefixes(&c.component_type))
+        .filter(|name| !name.contains("IsProviderFor<") && !name.contains("CanUseComponent<"))
+        .collect();
+    component_names.sort();
+    component_names.dedup();
+
+    let mut help_sections = vec![match component_names.as_slice() {
+        [component] => format!(
+            "Context `{}` has the field `{}`, but not with the type required to use `{}`.",
+            target_type, field_name, component
+        ),
+        _ => format!(
+            "Context `{}` has the field `{}`, but not with the type it is required to have.",
+            target_type, field_name
+        ),
+    }];
+
+    // The getters that read the field, from the workspace index or the dependency chain
+    let mut getters: Vec<(String, Option<&SourceLocation>)> = resolver
+        .getters_reading(&field_info.field_name)
+        .into_iter()
+        .map(|getter| (getter.trait_name.clone(), Some(&getter.location)))
+        .collect();
+    if getters.is_empty() {
+        getters = entry
+            .delegation_notes()
+            .iter()
+            .filter_map(|note| extract_getter_trait_from_note(note))
+            .take(1)
+            .map(|getter_trait| (getter_trait, None))
+            .collect();
+    }
+    for (getter_trait, _) in &getters {
+        help_sections.push(format!(
+            "    note: `{}` reads `{}` as `{}`",
+            getter_trait, field_name, expected_type
+        ));
+    }
+    help_sections.push(String::new());
+
+    if !entry.delegation_notes().is_empty() {
+        help_sections.push("Dependency chain:".to_string());
+        for line in format_delegation_chain(entry, resolver, chain_style) {
+            help_sections.push(format!("    {}", line));
+        }
+        help_sections.push(String::new());
+    }
+
+    let at = |location: Option<&SourceLocation>| {
+        location
+            .map(|location| format!(" at {}:{}", location.display_path.display(), location.line))
+            .unwrap_or_default()
+    };
+    help_sections.push("To fix this error:".to_string());
+    help_sections.push(format!(
+        "    • Change the type of the field `{}` of `{}` to `{}`{}",
+        field_name,
+        fields_struct,
+        expected_type,
+        at(resolver.context_location(&fields_struct))
+    ));
+    if let (Some(actual_type), Some((getter_trait, location))) = (&actual_type, getters.first()) {
+        help_sections.push(format!(
+            "    • Or, if the field has the intended type, change `{}` in `{}` to return `{}`{}",
+            field_name,
+            getter_trait,
+            actual_type,
+            at(*location)
+        ));
+    }
+
+    let mut diagnostic = CgpDiagnostic::new(message, entry_spans(entry));
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// The role of a requirement in an overflowing chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequirementRole {
+    /// A consumer trait of the context (e.g., `Rectangle: CanCalculateArea`)
+    Consumer,
+    /// A provider trait implemented by the context, which delegates it
+    /// (e.g., `Rectangle: AreaCalculator<Rectangle>`)
+    Delegation,
+    /// A provider trait implemented by a provider (e.g., `ScaledArea: AreaCalculator<Rectangle>`)
+    Provider,
+}
+
+impl RequirementRole {
+    /// Classifies a requirement by whether its trait takes the context as its last argument
+    fn of(requirement: &Requirement) -> Self {
+        let context = parse_type(&requirement.trait_name)
+            .value()
+            .and_then(|trait_type| trait_type.generic_arguments().last().map(|c| c.to_string()));
+        match context {
+            Some(context) if context == requirement.type_name => RequirementRole::Delegation,
+            Some(_) => RequirementRole::Provider,
+            None => RequirementRole::Consumer,
+        }
+    }
+}
+
+/// Formats an overflow (E0275) in the wiring of a component: the cycle of requirements
+/// that the wiring resolves back into, or the chain that grows until the recursion limit
+fn format_requirement_overflow_error(
+    entry: &DiagnosticEntry,
+    overflow: &RequirementOverflow,
+    resolver: &NameResolver,
+) -> Option<CgpDiagnostic> {
+    let show = |requirement: &Requirement| {
+        format!(
+            "`{}: {}`",
+            resolver.strip_crate_paths(&requirement.type_name),
+            resolver.strip_crate_paths(&requirement.trait_name)
+        )
+    };
+    let at = |location: &SourceLocation| {
+        format!(" at {}:{}", location.display_path.display(), location.line)
+    };
+
+    let Some(cycle) = overflow.cycle() else {
+        return format_unbounded_overflow_error(entry, overflow, resolver);
+    };
+    let (first, closing) = (cycle[0], cycle[cycle.len() - 1]);
+    let message = format!("{} depends on itself through its wiring", show(first));
+
+    let mut spans = entry_spans(entry);
+    if let Some(bound_span) = &closing.bound_span {
+        let mut span = SpanLocation::from_span(bound_span, "");
+        span.label = format!("this bound requires {} again", show(first));
+        spans.push(span);
+    }
+
+    let mut help_sections = vec![
+        format!(
+            "Resolving {} leads back to itself, so the compiler gives up with an overflow \
+             that raising the recursion limit does not fix.",
+            show(first)
+        ),
+        String::new(),
+        "Cycle:".to_string(),
+    ];
+    for (index, requirement) in cycle.iter().chain([&first]).enumerate() {
+        let role = match RequirementRole::of(requirement) {
+            RequirementRole::Consumer => "consumer trait".to_string(),
+            RequirementRole::Delegation => format!(
+                "delegated by `{}`",
+                resolver.strip_crate_paths(&requirement.type_name)
+            ),
+            RequirementRole::Provider => "provider trait".to_string(),
+        };
+        let arrow = if index == 0 { "" } else { "→ " };
+        help_sections.push(format!("    {}{} ({})", arrow, show(requirement), role));
+    }
+    help_sections.push(String::new());
+
+    help_sections.push("To fix this error:".to_string());
+    if RequirementRole::of(closing) == RequirementRole::Provider {
+        let provider = resolver.strip_crate_paths(&closing.type_name);
+        let location = closing
+            .bound_span
+            .as_ref()
+            .map(|span| format!(" at {}:{}", span.file_name, span.line_start))
+            .unwrap_or_default();
+        let provider_trait = base_type_name(&closing.trait_name).to_string();
+        help_sections.push(format!(
+            "    • Remove the bound `{}` from `{}`{}; to call another provider, take it as a \
+             type parameter bounded by `{}<Self>`",
+            resolver.strip_crate_paths(&first.trait_name),
+            provider,
+            location,
+            provider_trait
+        ));
+    }
+    for delegation in cycle
+        .iter()
+        .filter(|requirement| RequirementRole::of(requirement) == RequirementRole::Delegation)
+    {
+        let context_type = resolver.strip_crate_paths(&delegation.type_name);
+        let Some(component) = resolver.component_for_provider_trait(&delegation.trait_name) else {
+            continue;
+        };
+        help_sections.push(format!(
+            "    • Or delegate `{}` to a provider that does not require {} in the \
+             `delegate_components!` block of `{}`{}",
+            component,
+            show(first),
+            context_type,
+            resolver
+                .delegation_entry(&context_type, &component)
+                .map(|delegation| &delegation.location)
+                .or_else(|| resolver.delegation_block(&context_type))
+                .map(at)
+                .unwrap_or_default()
+        ));
+    }
+
+    let mut diagnostic = CgpDiagnostic::new(message, spans);
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// Formats an auto trait (e.g., `Send`) that a provider requires of the context, naming the
+/// field of the context whose type does not implement it
+fn format_auto_trait_error(
+    entry: &DiagnosticEntry,
+    failure: &AutoTraitFailure,
+    resolver: &NameResolver,
+) -> Option<CgpDiagnostic> {
+    let auto_traits = failure
+        .auto_traits
+        .iter()
+        .map(|auto_trait| format!("`{}`", auto_trait))
+        .collect::<Vec<_>>()
+        .join(" and ");
+    let offending_type = resolver.strip_crate_paths(&failure.offending_type);
+    let provider_type = failure
+        .provider
+        .as_ref()
+        .map(|provider| resolver.strip_crate_paths(&provider.provider_type));
+    let context_type = failure
+        .provider
+        .as_ref()
+        .map(|provider| resolver.strip_crate_paths(&provider.context))
+        .or_else(|| failure.within_type.clone())
+        .unwrap_or_else(|| offending_type.clone());
+
+    let message = match &provider_type {
+        Some(provider_type) => format!(
+            "provider `{}` requires the context `{}` to be {}",
+            provider_type, context_type, auto_traits
+        ),
+        None => format!("`{}` is not {}", context_type, auto_traits),
+    };
+
+    let mut spans = entry_spans(entry);
+    if let Some(bound_span) = &failure.bound_span {
+        let mut span = SpanLocation::from_span(bound_span, "");
+        span.label = format!("{} is required here", auto_traits);
+        spans.push(span);
+    }
+    if let (Some(within_type), Some(within_span)) = (&failure.within_type, &failure.within_span) {
+        spans.push(SpanLocation::from_span(
+            within_span,
+            &format!("`{}` contains `{}`", within_type, offending_type),
+        ));
+    }
+
+    let mut help_sections = Vec::new();
+    let field_path = failure
+        .within_type
+        .as_deref()
+        .and_then(|within_type| field_path_to_type(resolver, within_type, &offending_type, 0));
+    match &failure.within_type {
+        Some(within_type) => {
+            help_sections.push(format!(
+                "`{}` contains `{}`, which is not {}, so `{}` is not {} either.",
+                within_type, offending_type, auto_traits, within_type, auto_traits
+            ));
+            if let Some(field_path) = &field_path {
+                help_sections.push(format!(
+                    "    note: the field `{}` of `{}` has type `{}`",
+                    field_path.join("."),
+                    within_type,
+                    offending_type
+                ));
+            }
+        }
+        None => help_sections.push(format!("`{}` is not {}.", offending_type, auto_traits)),
+    }
+    help_sections.push(String::new());
+
+    let example = match thread_safe_alternative(&offending_type) {
+        Some(alternative) => format!(" (e.g., `{}`)", alternative),
+        None => String::new(),
+    };
+    help_sections.push("To fix this error:".to_string());
+    match (&failure.within_type, &field_path) {
+        (Some(within_type), Some(field_path)) => help_sections.push(format!(
+            "    • Replace the type of the field `{}` of `{}` with a type that is {}{}",
+            field_path.join("."),
+            within_type,
+            auto_traits,
+            example
+        )),
+        (Some(within_type), None) => help_sections.push(format!(
+            "    • Replace `{}` in `{}` with a type that is {}{}",
+            offending_type, within_type, auto_traits, example
+        )),
+        (None, _) => help_sections.push(format!(
+            "    • Make `{}` {}{}",
+            offending_type, auto_traits, example
+        )),
+    }
+    if let Some(provider) = &failure.provider {
+        let component = strip_module_prefixes(&provider.component);
+        let delegation = resolver
+            .delegation_entry(&context_type, &component)
+            .map(|delegation| &delegation.location)
+            .or_else(|| resolver.delegation_block(&context_type))
+            .map(|location| format!(" at {}:{}", location.display_path.display(), location.line))
+            .unwrap_or_default();
+        help_sections.push(format!(
+            "    • Or, if `{}` is not shared between threads, delegate `{}` to a provider \
+             that does not require {}{}",
+            context_type, component, auto_traits, delegation
+        ));
+    }
+
+    let mut diagnostic = CgpDiagnostic::new(message, spans);
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// Maximum depth of nested structs searched for the field that holds a type
+const MAX_FIELD_DEPTH: usize = 4;
+
+/// The path of fields from a struct to a field that has or contains a type
+/// (e.g., ["session", "cache"] for `Rc<String>` in `App`), searching nested structs
+fn field_path_to_type(
+    resolver: &NameResolver,
+    struct_type: &str,
+    target_type: &str,
+    depth: usize,
+) -> Option<Vec<String>> {
+    let normalize = |type_name: &str| {
+        strip_path_prefixes(type_name, false)
+            .split_whitespace()
+            .collect()
+    };
+    let target: String = normalize(target_type);
+    let fields = resolver.struct_fields(struct_type)?;
+
+    let direct = fields.iter().find(|field| {
+        resolver
+            .struct_field_type(struct_type, field)
+            .map(normalize)
+            .is_some_and(|field_type: String| {
+                field_type == target || contains_type_argument(&field_type, &target)
+            })
+    });
+    if let Some(field) = direct {
+        return Some(vec![field.to_string()]);
+    }
+    if depth >= MAX_FIELD_DEPTH {
+        return None;
+    }
+    fields.iter().find_map(|field| {
+        let field_type = resolver.struct_field_type(struct_type, field)?;
+        let mut path = field_path_to_type(resolver, field_type, target_type, depth + 1)?;
+        path.insert(0, field.to_string());
+        Some(path)
+    })
+}
+
+/// A thread-safe replacement for a type that is not `Send` or `Sync`
+/// (e.g., "Arc<String>" for "Rc<String>")
+fn thread_safe_alternative(type_name: &str) -> Option<String> {
+    let (base, arguments) = type_name.split_once('<')?;
+    let replacement = match base.rsplit("::").next()? {
+        "Rc" => "Arc",
+        "RefCell" | "Cell" => "Mutex",
+        _ => return None,
+    };
+    Some(format!("{}<{}", replacement, arguments))
+}
+
+/// Formats an overflow whose chain does not repeat a requirement, as with provider types
+/// that wrap themselves without bound or a chain deeper than the recursion limit
+fn format_unbounded_overflow_error(
+    entry: &DiagnosticEntry,
+    overflow: &RequirementOverflow,
+    resolver: &NameResolver,
+) -> Option<CgpDiagnostic> {
+    let show = |requirement: &Requirement| {
+        format!(
+            "`{}: {}`",
+            resolver.strip_crate_paths(&requirement.type_name),
+            resolver.strip_crate_paths(&requirement.trait_name)
+        )
+    };
+    let message = format!(
+        "overflow evaluating the wiring of {}",
+        show(&overflow.requirement)
+    );
+
+    let mut help_sections = vec![
+        "The requirements keep growing along the chain (e.g., a provider that wraps itself), \
+         or the chain is deeper than the recursion limit."
+            .to_string(),
+        String::new(),
+        "Requirements, from the outermost:".to_string(),
+    ];
+    for requirement in overflow.required_for.iter().rev() {
+        help_sections.push(format!("    {}", show(requirement)));
+    }
+    if overflow.hidden > 0 {
+        help_sections.push(format!(
+            "    ... ({} redundant requirements hidden)",
+            overflow.hidden
+        ));
+    }
+    help_sections.push(format!("    {}", show(&overflow.requirement)));
+    help_sections.push(String::new());
+
+    // rustc suggests a limit twice the current one
+    let limit_attribute = entry
+        .original
+        .children
+        .iter()
+        .find_map(|child| {
+            let start = child.message.find("`#![recursion_limit")?;
+            let rest = &child.message[start + 1..];
+            Some(rest[..rest.find('`')?].to_string())
+        })
+        .unwrap_or_else(|| "#![recursion_limit = \"256\"]".to_string());
+    help_sections.push("To fix this error:".to_string());
+    help_sections.push(
+        "    • Check the providers in the chain for a bound that requires a larger version \
+         of the same provider"
+            .to_string(),
+    );
+    help_sections.push(format!(
+        "    • Or, if the chain is finite, raise the limit with `{}` in the crate root",
+        limit_attribute
+    ));
+
+    let mut diagnostic = CgpDiagnostic::new(message, entry_spans(entry));
+    diagnostic.code = cgp_error_code(entry)
+        .map(|code| code.code().to_string())
+        .or_else(|| entry.error_code.clone());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.dependency_tree = build_dependency_tree(entry, resolver);
+
+    Some(diagnostic)
+}
+
+/// Explains that a check and its context live in different crates
+/// (e.g., a check in an integration test crate for a context in the library crate)
+fn cross_crate_note(
+    entry: &DiagnosticEntry,
+    context_type: &str,
+    resolver: &NameResolver,
+) -> Option<String> {
+    let check_trait = entry.check_trait.as_ref()?;
+    let check_crate = package_name(&entry.package_id);
+    let context_crate = resolver.context_crate(context_type)?;
+
+    if check_crate == context_crate {
+        return None;
+    }
+
+    Some(format!(
+        "`{}` is checked in `{}`, and the context `{}` is defined in `{}`",
+        resolver.strip_crate_paths(check_trait),
+        check_crate,
+        resolver.strip_crate_paths(context_type),
+        context_crate
+    ))
+}
+
+/// Finds a getter trait in the dependency chain that is declared with `#[cgp_getter]`
+/// and not wired by the context, with its component
+fn find_unwired_getter(
+    entry: &DiagnosticEntry,
+    context_type: &str,
+    resolver: &NameResolver,
+) -> Option<(String, String)> {
+    entry.delegation_notes().iter().find_map(|note| {
+        let (_, rest) = note.split_once("to implement `")?;
+        let getter_trait = rest.split('`').next()?;
+        let component = resolver.unwired_getter_component(getter_trait, context_type)?;
+        Some((strip_module_prefixes(getter_trait), component.to_string()))
+    })
+}
+
+/// Formats a diagnostic that could not be analyzed, keeping the compiler's message and notes
+pub fn format_unanalyzed_diagnostic(original: &Diagnostic) -> CgpDiagnostic {
+    let spans = original
+        .spans
+        .iter()
+        .filter(|span| span.is_primary)
+        .map(|span| SpanLocation::from_span(span, "unsatisfied trait bound"))
+        .collect();
+
+    let mut help_sections: Vec<String> = original
+        .children
+        .iter()
+        .map(|child| {
+            let level = match child.level {
+                DiagnosticLevel::Help => "help",
+                DiagnosticLevel::Warning => "warning",
+                _ => "note",
+            };
+            format!("{}: {}", level, child.message)
+        })
+        .collect();
+    if !help_sections.is_empty() {
+        help_sections.push(String::new());
+    }
+    help_sections.push(
+        "Sorry, cargo-cgp failed while analyzing this error, so it is shown as reported by rustc."
+            .to_string(),
+    );
+    help_sections.push(format!(
+        "Set `{}=1` to log the internal error, and please report it with the output of `cargo check --message-format=json`.",
+        LOG_ENV
+    ));
+
+    let mut diagnostic = CgpDiagnostic::new(original.message.clone(), spans);
+    diagnostic.code = original.code.as_ref().map(|code| code.code.clone());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic
+}
+
+/// Collects the primary spans of a diagnostic entry, which are kept sorted by location
+/// When there are multiple components, there is one span for each component
+fn entry_spans(entry: &DiagnosticEntry) -> Vec<SpanLocation> {
+    entry
+        .primary_spans()
+        .iter()
+        .map(|span| SpanLocation::from_span(span, "unsatisfied trait bound"))
+        .collect()
+}
+
+/// Lists components, referring to the label that highlights each of them
+/// (e.g., "`AreaCalculatorComponent` (see label [1]), `DensityCalculatorComponent` (see label [2])")
+/// At most `MAX_LISTED_COMPONENTS` are named, followed by the number of the others
+fn list_components_with_labels(component_names: &[String], spans: &[SpanLocation]) -> String {
+    let mut listed: Vec<String> = component_names
+        .iter()
+        .take(MAX_LISTED_COMPONENTS)
+        .map(|name| match label_id_of(spans, name) {
+            Some(id) if spans.len() > 1 => format!("`{}` (see label [{}])", name, id),
+            _ => format!("`{}`", name),
+        })
+        .collect();
+
+    let others = component_names.len().saturating_sub(MAX_LISTED_COMPONENTS);
+    if others > 0 {
+        listed.push(format!("and {} more", others));
+    }
+
+    listed.join(", ")
+}
+
+/// Formats a warning for check traits generated with the same name by multiple
+/// `check_components!` blocks, naming every location that defines the check trait
+pub fn format_shadowed_check_trait_warning(blocks: &[&CheckBlock]) -> Option<CgpDiagnostic> {
+    let first = blocks.first()?;
+
+    let message = format!(
+        "check trait `{}` is generated by {} `check_components!` blocks",
+        first.trait_name,
+        blocks.len()
+    );
+
+    let mut help_sections = Vec::new();
+    help_sections.push(format!(
+        "The check trait `{}` is defined at:",
+        first.trait_name
+    ));
+    for block in blocks {
+        help_sections.push(format!(
+            "    • `{}:{}` in module `{}` for `{}`",
+            block.display_path.display(),
+            block.line,
+            block.module_path,
+            block.context_type
+        ));
+    }
+    help_sections.push(String::new());
+    help_sections.push(
+        "Errors from these blocks may show up as duplicate or conflicting implementations of the same check trait."
+            .to_string(),
+    );
+    help_sections.push(String::new());
+    help_sections.push("To fix this warning:".to_string());
+    help_sections
+        .push("    • Give each `check_components!` block a unique check trait name".to_string());
+
+    // Highlight the first definition, read from the file on disk
+    let span = SpanLocation {
+        file: first.display_path.display().to_string(),
+        line_start: first.line,
+        column_start: first.column,
+        line_end: first.line,
+        column_end: first.column + first.trait_name.len(),
+        label: "check trait defined here".to_string(),
+        text: Vec::new(),
+        source_path: Some(first.file.clone()),
+    };
+
+    let mut diagnostic = CgpDiagnostic::new(message, vec![span]);
+    diagnostic.code = Some(CgpErrorCode::ShadowedCheckTrait.code().to_string());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.severity = Some(Severity::Warning);
+
+    Some(diagnostic)
+}
+
+/// Formats a warning for a (context, component) pair checked in several `check_components!` blocks
+pub fn format_duplicate_check_warning(duplicate: &DuplicateCheck) -> Option<CgpDiagnostic> {
+    let (first_block, first_component) = duplicate.checks.first()?;
+
+    let message = format!(
+        "`{}` is checked for `{}` in {} `check_components!` blocks",
+        duplicate.component,
+        duplicate.context_type,
+        duplicate.checks.len()
+    );
+
+    let mut help_sections = vec![format!(
+        "The component `{}` is checked for `{}` at:",
+        duplicate.component, duplicate.context_type
+    )];
+    help_sections.extend(duplicate_check_locations(duplicate));
+    help_sections.push(String::new());
+    help_sections.push(
+        "Errors for this component are reported once for each check, although they have the same cause."
+            .to_string(),
+    );
+    help_sections.push(String::new());
+    help_sections.push("To fix this warning:".to_string());
+    help_sections.push(format!(
+        "    • Consolidate the checks for `{}` into a single `check_components!` block",
+        duplicate.context_type
+    ));
+
+    // Highlight the first check, read from the file on disk
+    let span = SpanLocation {
+        file: first_block.display_path.display().to_string(),
+        line_start: first_component.line,
+        column_start: first_component.column,
+        line_end: first_component.line,
+        column_end: first_component.column + first_component.name.len(),
+        label: "component checked here".to_string(),
+        text: Vec::new(),
+        source_path: Some(first_block.file.clone()),
+    };
+
+    let mut diagnostic = CgpDiagnostic::new(message, vec![span]);
+    diagnostic.code = Some(CgpErrorCode::DuplicateCheck.code().to_string());
+    diagnostic.help = Some(help_sections.join("\n"));
+    diagnostic.severity = Some(Severity::Warning);
+
+    Some(diagnostic)
+}
+
+/// Lists the check locations of a duplicated check as help bullets
+fn duplicate_check_locations(duplicate: &DuplicateCheck) -> Vec<String> {
+    duplicate
+        .checks
+        .iter()
+        .map(|(block, component)| {
+            format!(
+                "    • `{}:{}` in check trait `{}`",
+                block.display_path.display(),
+                component.line,
+                block.trait_name
+            )
+        })
+        .collect()
+}
+
+/// Merges the errors reported for each check of a duplicated (context, component) pair
+/// into the first of them, which then points at all check locations
+pub fn merge_duplicate_check_errors(
+    diagnostics: Vec<CgpDiagnostic>,
+    duplicates: &[DuplicateCheck],
+) -> Vec<CgpDiagnostic> {
+    let mut diagnostics: Vec<Option<CgpDiagnostic>> = diagnostics.into_iter().map(Some).collect();
+
+    for duplicate in duplicates {
+        let covered = |diagnostic: &CgpDiagnostic| {
+            !diagnostic.spans.is_empty()
+                && diagnostic
+                    .spans
+                    .iter()
+                    .all(|span| duplicate.covers(&span.file, span.line_start))
+        };
+
+        let matching: Vec<usize> = diagnostics
+            .iter()
+            .enumerate()
+            .filter(|(_, diagnostic)| diagnostic.as_ref().is_some_and(covered))
+            .map(|(index, _)| index)
+            .collect();
+
+        let Some((&first, rest)) = matching.split_first() else {
+            continue;
+        };
+        if rest.is_empty() {
+            continue;
+        }
+
+        let mut extra_spans = Vec::new();
+        let mut extra_merged = Vec::new();
+        let mut extra_raw_notes = Vec::new();
+        for &index in rest {
+            if let Some(other) = diagnostics[index].take() {
+                extra_merged.extend(other.merged);
+                extra_raw_notes.extend(other.raw_notes);
+                for mut span in other.spans {
+                    span.label = "also checked here".to_string();
+                    extra_spans.push(span);
+                }
+            }
+        }
+
+        if let Some(diagnostic) = diagnostics[first].as_mut() {
+            diagnostic.spans.extend(extra_spans);
+            sort_spans(&mut diagnostic.spans);
+            diagnostic.merged.extend(extra_merged);
+            diagnostic.raw_notes.extend(extra_raw_notes);
+
+            let mut help = diagnostic.help.take().unwrap_or_default();
+            if !help.is_empty() {
+                help.push_str("\n\n");
+            }
+            help.push_str(&format!(
+                "This error is reported once for all {} checks of `{}` for `{}`:\n",
+                duplicate.checks.len(),
+                duplicate.component,
+                duplicate.context_type
+            ));
+            help.push_str(&duplicate_check_locations(duplicate).join("\n"));
+            diagnostic.help = Some(help);
+        }
+    }
+
+    diagnostics.into_iter().flatten().collect()
+}
+
+/// Narrates a node and its children as numbered sentences, given the number of the
+/// sentence about its parent
+fn narrate_dependency_tree(
+    node: &DependencyNode,
+    parent: Option<(usize, NodeKind)>,
+    remaining_depth: Option<usize>,
+    sentences: &mut Vec<String>,
+) {
+    let number = sentences.len() + 1;
+    let mut sentence = match parent {
+        None => format!("{}. The error is in {}", number, node.description),
+        // Providers implement the consumer trait that requires them
+        Some((step, NodeKind::Check | NodeKind::Usage | NodeKind::Consumer))
+            if node.kind == NodeKind::Provider =>
+        {
+            format!(
+                "{}. Step {} is provided by {}",
+                number, step, node.description
+            )
+        }
+        Some((step, _)) => format!("{}. Step {} requires {}", number, step, node.description),
+    };
+
+    if let Some(trait_label) = node.kind.trait_label() {
+        sentence.push_str(&format!(" ({})", trait_label));
+    }
+    match node.is_satisfied {
+        Some(true) => sentence.push_str(", which is satisfied"),
+        Some(false) => sentence.push_str(", which is not satisfied"),
+        None => {}
+    }
+    if node.is_reference {
+        sentence.push_str(", as described in an earlier step");
+    }
+    sentence.push('.');
+    if let Some(doc) = &node.doc {
+        sentence.push_str(&format!(" Its documentation says: {}", doc));
+    }
+    if let Some(declared_at) = &node.declared_at {
+        sentence.push_str(&format!(" The provider is {}.", declared_at));
+    }
+    sentences.push(sentence);
+
+    if node.is_reference {
+        return;
+    }
+
+    if remaining_depth == Some(0) {
+        match node.descendant_count() {
+            0 => {}
+            1 => sentences.push(format!(
+                "{}. Step {} has 1 more requirement, which is not shown.",
+                number + 1,
+                number
+            )),
+            n => sentences.push(format!(
+                "{}. Step {} has {} more requirements, which are not shown.",
+                number + 1,
+                number,
+                n
+            )),
+        }
+        return;
+    }
+
+    for child in &node.children {
+        narrate_dependency_tree(
+            child,
+            Some((number, node.kind)),
+            remaining_depth.map(|depth| depth - 1),
+            sentences,
+        );
+    }
+}
+
+/// Renders a dependency tree with box-drawing characters
+fn render_dependency_tree(
+    node: &DependencyNode,
+    prefix: &str,
+    is_last: bool,
+    is_root: bool,
+    remaining_depth: Option<usize>,
+) -> Vec<String> {
+    let mut result = Vec::new();
+
+    // Build the line for this node
+    if is_root {
+        // Root node has no branch character
+        let mut line = node.description.clone();
+
+        // Add trait type annotation if present
+        if let Some(trait_label) = node.kind.trait_label() {
+            line.push_str(&format!(" ({})", trait_label));
+        }
+
+        result.push(line);
+    } else {
+        let branch = if is_last { "└─" } else { "├─" };
+        let mut line = format!("{}{} {}", prefix, branch, node.description);
+
+        // Add trait type annotation if present
+        if let Some(trait_label) = node.kind.trait_label() {
+            line.push_str(&format!(" ({})", trait_label));
+        }
+
+        // Add satisfaction marker if present
+        if let Some(is_satisfied) = node.is_satisfied {
+            line.push_str(if is_satisfied { " ✓" } else { " ✗" });
+        }
+
+        // If this is a reference node, add (*) marker
+        // This indicates the full tree is shown elsewhere (cargo tree style)
+        if node.is_reference {
+            line.push_str(" (*)");
+        }
+
+        // Describe what this hop does, from the doc comment in the source
+        if let Some(doc) = &node.doc {
+            line.push_str(&format!(" — {}", doc));
+        }
+
+        result.push(line);
+    }
+
+    // Render children with updated prefix
+    let child_prefix = if is_root {
+        prefix.to_string()
+    } else if is_last {
+        format!("{}   ", prefix)
+    } else {
+        format!("{}│  ", prefix)
+    };
+
+    // Point generated provider structs back to the macro that declares them, beneath the node
+    if let Some(declared_at) = &node.declared_at {
+        let continuation = if node.children.is_empty() || node.is_reference {
+            "   "
+        } else {
+            "│  "
+        };
+        result.push(format!("{}{}{}", child_prefix, continuation, declared_at));
+    }
+
+    // If this is a reference node, don't render children
+    // The full tree is shown at the root level
+    if node.is_reference {
+        return result;
+    }
+
+    // Past the depth limit, only say how much is hidden
+    if remaining_depth == Some(0) {
+        match node.descendant_count() {
+            0 => {}
+            1 => result.push(format!("{}└─ … 1 more requirement", child_prefix)),
+            n => result.push(format!("{}└─ … {} more requirements", child_prefix, n)),
+        }
+        return result;
+    }
+
+    // Render all children normally
+    // All children are treated the same in the flattened structure
+    for (i, child) in node.children.iter().enumerate() {
+        let child_is_last = i == node.children.len() - 1;
+        result.extend(render_dependency_tree(
+            child,
+            &child_prefix,
+            child_is_last,
+            false,
+            remaining_depth.map(|depth| depth - 1),
+        ));
+    }
+
+    result
+}
+
+/// Finds the actual consumer trait name for a given component
+/// by looking it up in the diagnostic entry's consumer trait dependencies
+///
+/// This avoids the need to derive consumer trait names from component names,
+/// which is error-prone due to naming variations. Instead, we use the actual
+/// trait names extracted from the compiler diagnostics.
+///
+/// Returns None if no matching consumer trait is found for this component.
+fn find_consumer_trait_for_component(
+    component_name: &str,
+    entry: &DiagnosticEntry,
+    resolver: &NameResolver,
+) -> Option<String> {
+    // The component index knows the consumer trait of components declared in the workspace
+    if let Some(trait_name) = resolver.consumer_trait_for_component(component_name) {
+        return Some(trait_name);
+    }
+
+    // Check each consumer trait dependency to see if it matches this component
+    for dep in entry.consumer_trait_dependencies() {
+        // Match by the component that the consumer trait belongs to
+        if resolver
+            .component_for_consumer_trait(&dep.trait_name)
+            .as_deref()
+            == Some(component_name)
+        {
+            return Some(dep.trait_name.clone());
+        }
+    }
+
+    if resolver.is_strict() {
+        return None;
+    }
+
+    // Also check provider relationships to find the provider trait,
+    // then search for consumer traits that might correspond to it
+    for provider_rel in entry.provider_relationships() {
+        if strip_module_prefixes(&provider_rel.component) == component_name {
+            // Found the provider for this component
+            // Now look for consumer traits that might match this provider's trait
+            // This is a fallback heuristic when the component_name derivation doesn't match
+
+            // The heuristic here is fuzzy matching based on significant words
+            // For example: AreaCalculator (provider) ~ CanCalculateArea (consumer)
+            // We look for shared words between them
+
+            let provider_trait = resolver.provider_trait_for_component(component_name)?;
+
+            for dep in entry.consumer_trait_dependencies() {
+                let consumer_action = dep
+                    .trait_name
+                    .strip_prefix("Can")
+                    .unwrap_or(&dep.trait_name);
+
+                // Check if they share significant words
+                if shares_significant_word(&provider_trait, consumer_action) {
+                    return Some(dep.trait_name.clone());
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Matches a component to its specific provider relationship from the error
+/// This is based on the IsProviderFor<Component, Context> notes in the diagnostics
+fn match_component_to_provider<'a>(
+    component_info: &ComponentInfo,
+    provider_relationships: &'a [ProviderRelationship],
+    resolver: &NameResolver,
+) -> Option<&'a ProviderRelationship> {
+    let component_name = strip_module_prefixes(&component_info.component_type);
+
+    // Try exact match first
+    for rel in provider_relationships {
+        if strip_module_prefixes(&rel.component) == component_name {
+            return Some(rel);
+        }
+    }
+
+    // If no exact match, try matching by provider trait name
+    // The provider trait should be derivable from the component name
+    if let Some(provider_trait) = resolver.provider_trait(component_info) {
+        for rel in provider_relationships {
+            // Check if this relationship's component has the same provider trait
+            if let Some(rel_provider_trait) = resolver.provider_trait_for_component(&rel.component)
+                && rel_provider_trait == provider_trait
+            {
+                return Some(rel);
+            }
+        }
+    }
+
+    None
+}
+
+/// Builds a dependency tree from delegation notes and provider relationships
+/// When there are multiple components, shows them as siblings at the root level (flattened structure)
+/// This creates a cargo-tree-style view where shared dependencies are marked with (*)
+pub fn build_dependency_tree(
+    entry: &DiagnosticEntry,
+    resolver: &NameResolver,
+) -> Option<DependencyNode> {
+    let context_type = entry_context_type(entry, resolver)?;
+
+    // Build root node from check trait
+    // Errors from plain code (e.g., a function body) have no check trait, so the root
+    // names the components they use instead
+    let (description, kind) = match &entry.check_trait {
+        // Wrap trait and type names in backticks for consistent code construct formatting
+        // Rationale: Backticks visually distinguish code elements from descriptive text
+        Some(check_trait) => (
+            format!(
+                "`{}` for `{}`",
+                resolver.strip_crate_paths(check_trait),
+                context_type
+            ),
+            NodeKind::Check,
+        ),
+        // The bound of a generic function names the consumer trait, like a check trait
+        // names the components, and the function is named in the message
+        None => match &entry.usage_site {
+            Some(usage_site) => (
+                format!("`{}` for `{}`", usage_site.consumer_trait, context_type),
+                NodeKind::Consumer,
+            ),
+            None => (usage_description(entry, &context_type), NodeKind::Usage),
+        },
+    };
+
+    let mut root = DependencyNode {
+        description,
+        kind,
+        is_satisfied: None,
+        is_reference: false,
+        children: Vec::new(),
+        doc: None,
+        declared_at: None,
+    };
+
+    // Track which consumer traits have been rendered to avoid duplicating full trees
+    // This implements the flattened dependency view similar to cargo tree
+    let mut rendered_consumer_traits: Vec<String> = Vec::new();
+
+    // Process all components in order, showing them as siblings at the root level
+    // This is the key change for flattened dependency trees
+    for component_info in entry.component_infos() {
+        let component_name = strip_module_prefixes(&component_info.component_type);
+
+        // Try to find the actual consumer trait name for this component
+        // If found, use it directly; otherwise fall back to generic description
+        let (consumer_desc, consumer_trait_name) = if let Some(trait_name) =
+            find_consumer_trait_for_component(&component_name, entry, resolver)
+        {
+            // Found the actual consumer trait - use it directly
+            // Wrap both trait name and context type in backticks
+            let desc = format!("`{}` for `{}`", trait_name, context_type);
+            (desc, Some(trait_name.clone()))
+        } else {
+            // Fallback to generic description
+            // Note: component_name and context_type are already wrapped in backticks
+            let mut desc = format!(
+                "consumer trait of `{}` for `{}`",
+                component_name, context_type
+            );
+            if resolver.is_strict() {
+                desc.push_str(" (unresolved)");
+            }
+            (desc, None)
+        };
+
+        let doc = consumer_trait_name
+            .as_deref()
+            .and_then(|trait_name| resolver.consumer_trait_doc(trait_name))
+            .map(str::to_string);
+        let mut consumer_node = DependencyNode {
+            description: consumer_desc,
+            kind: NodeKind::Consumer,
+            is_satisfied: None,
+            is_reference: false,
+            children: Vec::new(),
+            doc,
+            declared_at: None,
+        };
+
+        // Match this component to its specific provider relationship
+        if let Some(provider_rel) =
+            match_component_to_provider(component_info, entry.provider_relationships(), resolver)
+        {
+            // Build provider node for this specific relationship
+            // Pass the rendered_consumer_traits and current consumer trait name
+            // to avoid showing the component's own consumer trait as a nested dependency
+            let provider_nodes = build_provider_nodes_for_component(
+                entry,
+                &context_type,
+                Some(component_info),
+                Some(provider_rel),
+                &rendered_consumer_traits,
+                consumer_trait_name.as_deref(),
+                resolver,
+            );
+            consumer_node.children = provider_nodes;
+        } else {
+            // Fallback: build without specific provider relationship
+            let provider_nodes = build_provider_nodes_for_component(
+                entry,
+                &context_type,
+                Some(component_info),
+                None,
+                &rendered_consumer_traits,
+                consumer_trait_name.as_deref(),
+                resolver,
+            );
+            consumer_node.children = provider_nodes;
+        }
+
+        // Track this consumer trait as rendered (if we know its name)
+        if let Some(trait_name) = consumer_trait_name {
+            rendered_consumer_traits.push(trait_name);
+        }
+
+        root.children.push(consumer_node);
+    }
+
+    // If no component info, try building without it (fallback)
+    if entry.component_infos().is_empty() && !entry.provider_relationships().is_empty() {
+        let provider_nodes = build_provider_nodes_for_component(
+            entry,
+            &context_type,
+            None,
+            None,
+            &Vec::new(),
+            None,
+            resolver,
+        );
+        root.children.extend(provider_nodes);
+    }
+
+    // A usage site without notes on the provider still names the provider that fails
+    if root.children.is_empty()
+        && entry.usage_site.is_some()
+        && let Some(provider) =
+            extract_unsatisfied_provider_from_message(&entry.message, resolver.path_style())
+    {
+        root.children.push(DependencyNode {
+            description: format!(
+                "`{}<{}>` for provider `{}`",
+                provider.trait_name, context_type, provider.provider_type
+            ),
+            kind: NodeKind::Provider,
+            is_satisfied: Some(false),
+            is_reference: false,
+            children: Vec::new(),
+            doc: resolver
+                .provider_doc(&provider.provider_type)
+                .map(str::to_string),
+            declared_at: resolver.provider_declaration(&provider.provider_type),
+        });
+    }
+
+    root.link_chains(&entry.linked_chains);
+
+    Some(root)
+}
+
+/// Describes the root of a dependency tree without a check trait, e.g.
+/// "requirements of `AreaCalculatorComponent` for `Rectangle`"
+fn usage_description(entry: &DiagnosticEntry, context_type: &str) -> String {
+    let mut component_names: Vec<String> = entry
+        .component_infos()
+        .iter()
+        .map(|info| format!("`{}`", strip_module_prefixes(&info.component_type)))
+        .collect();
+    component_names.dedup();
+
+    if component_names.is_empty() {
+        format!("requirements of `{}`", context_type)
+    } else {
+        format!(
+            "requirements of {} for `{}`",
+            component_names.join(", "),
+            context_type
+        )
+    }
+}
+
+/// Builds provider nodes for a specific component and its provider relationship
+/// If component_info is None, builds nodes based on provider relationships alone
+/// If provider_rel is provided, uses that specific relationship; otherwise uses first available
+/// The rendered_consumer_traits parameter tracks which consumer traits have already been shown
+/// at the root level, so we can mark them as references (*) instead of duplicating the full tree
+/// The current_consumer_trait parameter is the consumer trait of the current component,
+/// which should be excluded from nested consumer traits to avoid showing it as its own dependency
+fn build_provider_nodes_for_component(
+    entry: &DiagnosticEntry,
+    context_type: &str,
+    component_info: Option<&ComponentInfo>,
+    provider_rel: Option<&ProviderRelationship>,
+    rendered_consumer_traits: &[String],
+    current_consumer_trait: Option<&str>,
+    resolver: &NameResolver,
+) -> Vec<DependencyNode> {
+    let mut provider_nodes = Vec::new();
+
+    // Determine which provider relationship to use
+    let nesting = provider_nesting(entry.provider_relationships());
+    let deduped_relationships = entry.chain_provider_relationships();
+
+    let rel_to_use = if let Some(rel) = provider_rel {
+        Some(rel)
+    } else {
+        deduped_relationships.first()
+    };
+
+    if let Some(rel) = rel_to_use
+        && let Some(component_info) = component_info
+        && let Some(description) = describe_provider_trait(
+            resolver,
+            component_info,
+            context_type,
+            "provider",
+            &rel.provider_type,
+        )
+    {
+        // Check if this is a higher-order provider (has inner providers), however deeply
+        // they are nested (e.g., `RectangleArea` in `LoggedArea<ScaledArea<RectangleArea>>`)
+        let innermost_providers = nesting
+            .iter()
+            .find_map(|tree| tree.find(&rel.provider_type))
+            .map(ProviderNesting::innermost)
+            .unwrap_or_default();
+
+        let mut provider_node = DependencyNode {
+            description,
+            kind: NodeKind::Provider,
+            is_satisfied: None,
+            is_reference: false,
+            children: Vec::new(),
+            doc: resolver
+                .provider_doc(&rel.provider_type)
+                .map(str::to_string),
+            declared_at: resolver.provider_declaration(&rel.provider_type),
+        };
+
+        // Add nested consumer trait dependencies (transitive dependencies)
+        // These are consumer traits that this provider depends on
+        // Filter out the current component's own consumer trait to avoid showing it as its own dependency
+        let all_nested_consumers: Vec<_> =
+            extract_nested_consumer_traits(entry.delegation_notes(), resolver.path_style())
+                .into_iter()
+                .filter(|nested| {
+                    // Exclude the current component's consumer trait
+                    if let Some(current_trait) = current_consumer_trait {
+                        nested.trait_name != current_trait
+                    } else {
+                        true
+                    }
+                })
+                .collect();
+        let has_nested_consumer_deps = !all_nested_consumers.is_empty();
+
+        // Add getter requirements as children (if this provider directly requires fields)
+        // Only add getters if there's no nested consumer trait (to avoid duplication)
+        if !has_nested_consumer_deps {
+            let getter_children = build_getter_nodes(entry, context_type);
+            provider_node.children.extend(getter_children);
+        }
+
+        // Add all nested consumer dependencies
+        for nested_consumer in &all_nested_consumers {
+            // Build nodes for the nested consumer + its provider tree
+            // Pass rendered_consumer_traits to mark references as needed
+            let nested_nodes = build_nested_consumer_provider_nodes(
+                entry,
+                nested_consumer,
+                context_type,
+                rendered_consumer_traits,
+                resolver,
+            );
+            provider_node.children.extend(nested_nodes);
+        }
+
+        // If this is a higher-order provider, add its innermost providers as info nodes
+        // The wrappers in between are named rather than shown as hops of their own
+        for (inner_provider, wrapper) in innermost_providers {
+            let Some(mut inner_desc) = describe_provider_trait(
+                resolver,
+                component_info,
+                context_type,
+                "inner provider",
+                inner_provider,
+            ) else {
+                continue;
+            };
+            if !same_type(wrapper, &rel.provider_type) {
+                inner_desc.push_str(&format!(
+                    " of `{}`",
+                    resolver.strip_module_prefixes(wrapper)
+                ));
+            }
+            let inner_node = DependencyNode {
+                description: inner_desc,
+                kind: NodeKind::Provider,
+                is_satisfied: Some(true), // Inner is OK if outer has the error
+                is_reference: false,
+                children: Vec::new(),
+                doc: resolver.provider_doc(inner_provider).map(str::to_string),
+                declared_at: resolver.provider_declaration(inner_provider),
+            };
+            provider_node.children.push(inner_node);
+        }
+
+        provider_nodes.push(provider_node);
+    }
+
+    provider_nodes
+}
+
+/// Describes a provider implementing the provider trait of a component
+/// E.g., "`AreaCalculator<Rectangle>` for provider `RectangleArea`"
+/// In strict mode, a provider trait that is not in the component index is reported as unresolved
+fn describe_provider_trait(
+    resolver: &NameResolver,
+    component_info: &ComponentInfo,
+    context_type: &str,
+    provider_label: &str,
+    provider_type: &str,
+) -> Option<String> {
+    let description = match resolver.provider_trait(component_info) {
+        // Wrap all code constructs in backticks: provider trait, context type, and provider type
+        Some(provider_trait) => format!(
+            "`{}<{}>` for {} `{}`",
+            provider_trait, context_type, provider_label, provider_type
+        ),
+        None if resolver.is_strict() => format!(
+            "provider trait of `{}` for `{}` with {} `{}` (unresolved)",
+            component_info.component_type, context_type, provider_label, provider_type
+        ),
+        None => return None,
+    };
+
+    Some(resolver.strip_module_prefixes(&description))
+}
+
+/// Builds getter trait nodes from delegation notes
+fn build_getter_nodes(entry: &DiagnosticEntry, context_type: &str) -> Vec<DependencyNode> {
+    let mut getter_nodes = Vec::new();
+
+    // Look for "HasXxx" patterns in delegation notes
+    for note in entry.delegation_notes() {
+        if let Some(getter_trait) = extract_getter_trait_from_note(note) {
+            let mut getter_node = DependencyNode {
+                // Wrap getter trait name and context type in backticks
+                description: format!("`{}` for `{}`", getter_trait, context_type),
+                kind: NodeKind::Getter,
+                is_satisfied: None,
+                is_reference: false,
+                children: Vec::new(),
+                doc: None,
+                declared_at: None,
+            };
+
+            // If we have field info, add the field requirement as a child
+            // We add it to the first getter trait we find, since that's typically the most relevant one
+            if getter_nodes.is_empty()
+                && let Some(field_info) = &entry.field_info
+            {
+                let formatted_field = match &entry.field_type_mismatch {
+                    // The field exists, so the node names the type it must have
+                    Some(mismatch) => {
+                        format!("{}: {}", FieldName::of(field_info), mismatch.expected_type)
+                    }
+                    None => FieldName::of(field_info).to_string(),
+                };
+                let field_node = DependencyNode {
+                    // Wrap both field name and target type in backticks
+                    description: format!(
+                        "field `{}` on `{}`",
+                        formatted_field, field_info.target_type
+                    ),
+                    kind: NodeKind::Field,
+                    is_satisfied: Some(false), // This is the missing field
+                    is_reference: false,
+                    children: Vec::new(),
+                    doc: None,
+                    declared_at: None,
+                };
+                getter_node.children.push(field_node);
+            }
+
+            getter_nodes.push(getter_node);
+        }
+    }
+
+    getter_nodes
+}
+
+/// Builds nodes for nested consumer+provider dependencies
+/// This handles cases where a provider depends on another consumer trait,
+/// which in turn requires another provider that's not satisfied.
+///
+/// For example: DensityFromMassField -> requires CanCalculateArea -> requires RectangleArea
+///
+/// If the nested consumer is in rendered_consumer_traits, marks it as a reference (*)
+/// instead of building the full tree to avoid duplication (cargo tree style).
+fn build_nested_consumer_provider_nodes(
+    entry: &DiagnosticEntry,
+    nested_consumer: &NestedConsumerTrait,
+    _parent_context_type: &str,
+    rendered_consumer_traits: &[String],
+    resolver: &NameResolver,
+) -> Vec<DependencyNode> {
+    let mut nodes = Vec::new();
+
+    // Check if this consumer trait has already been rendered at the root level
+    // If so, mark it as a reference instead of building the full tree
+    let is_reference = rendered_consumer_traits.contains(&nested_consumer.trait_name);
+
+    // Check if this consumer trait maps to a checked component (appears in component_infos)
+    // For example: CanCalculateArea consumer trait → AreaCalculatorComponent
+    let matching_component = entry
+        .component_infos()
+        .iter()
+        .find(|comp| resolver.component_provides_consumer_trait(comp, &nested_consumer.trait_name));
+
+    let is_shared_component = matching_component.is_some();
+
+    // Create a node for the nested consumer trait
+    // Wrap consumer trait name and context type in backticks
+    let consumer_desc = format!(
+        "`{}` for `{}`",
+        nested_consumer.trait_name, nested_consumer.context_type
+    );
+    let mut consumer_node = DependencyNode {
+        description: consumer_desc,
+        kind: NodeKind::Consumer,
+        is_satisfied: None,
+        children: Vec::new(),
+        is_reference, // Mark if it's a reference to an earlier node
+        doc: resolver
+            .consumer_trait_doc(&nested_consumer.trait_name)
+            .map(str::to_string),
+        declared_at: None,
+    };
+
+    // If this is a reference, don't build children - the full tree is shown elsewhere
+    if is_reference {
+        nodes.push(consumer_node);
+        return nodes;
+    }
+
+    if is_shared_component {
+        // This is a shared component dependency that's also checked at root level
+        // Build the full provider tree for it, including getter and field requirements
+
+        if let Some(component_info) = matching_component {
+            // Match this component to its provider relationship
+            if let Some(provider_rel) = match_component_to_provider(
+                component_info,
+                entry.provider_relationships(),
+                resolver,
+            ) && let Some(provider_desc) = describe_provider_trait(
+                resolver,
+                component_info,
+                &nested_consumer.context_type,
+                "provider",
+                &provider_rel.provider_type,
+            ) {
+                let mut provider_node = DependencyNode {
+                    description: provider_desc,
+                    kind: NodeKind::Provider,
+                    is_satisfied: None,
+                    children: Vec::new(),
+                    is_reference: false,
+                    doc: resolver
+                        .provider_doc(&provider_rel.provider_type)
+                        .map(str::to_string),
+                    declared_at: resolver.provider_declaration(&provider_rel.provider_type),
+                };
+
+                // Add getter requirements and field nodes for this provider
+                // This ensures the full dependency tree is shown, including the missing field
+                let getter_children = build_getter_nodes(entry, &nested_consumer.context_type);
+                provider_node.children.extend(getter_children);
+
+                consumer_node.children.push(provider_node);
+            }
+        }
+    } else {
+        // Not a shared component - this consumer trait is not checked at root
+        // Just show that it's not satisfied, don't build a full tree
+        // Try to extract the unsatisfied provider from the main error message
+        if let Some(unsatisfied) =
+            extract_unsatisfied_provider_from_message(&entry.message, resolver.path_style())
+        {
+            // Create a provider node that's marked as unsatisfied
+            // Wrap all code constructs in backticks
+            let provider_desc = format!(
+                "`{}<{}>` for provider `{}`",
+                unsatisfied.trait_name, unsatisfied.context_type, unsatisfied.provider_type
+            );
+
+            let provider_node = DependencyNode {
+                description: resolver.strip_module_prefixes(&provider_desc),
+                kind: NodeKind::Provider,
+                is_satisfied: Some(false), // Mark as unsatisfied
+                children: Vec::new(),
+                is_reference: false,
+                doc: resolver
+                    .provider_doc(&unsatisfied.provider_type)
+                    .map(str::to_string),
+                declared_at: resolver.provider_declaration(&unsatisfied.provider_type),
+            };
+
+            consumer_node.children.push(provider_node);
+        }
+    }
+
+    nodes.push(consumer_node);
+    nodes
+}
+
+/// Extracts getter trait name from a delegation note
+fn extract_getter_trait_from_note(note: &str) -> Option<String> {
+    // Look for "to implement `HasXxx`" pattern
+    if let Some(trait_name) = extract_trait_from_note(note) {
+        // Only return if it looks like a getter trait (Has*)
+        if trait_name.starts_with("Has") {
+            return Some(trait_name);
+        }
+    }
+    None
+}
+
+/// Extracts any trait name from a delegation note
+fn extract_trait_from_note(note: &str) -> Option<String> {
+    if let Some(start) = note.find("to implement `") {
+        let after_start = start + "to implement `".len();
+        if let Some(end) = note[after_start..].find('`') {
+            let trait_name = &note[after_start..after_start + end];
+            let cleaned = strip_module_prefixes(trait_name);
+            // Further clean up IsProviderFor patterns
+            if cleaned.starts_with("IsProviderFor<") {
+                // Extract the component from IsProviderFor<Component, Context>, whose
+                // arguments may have generic arguments of their own
+                // If parsing fails, return None to skip this
+                return generic_arguments_at(&cleaned, "IsProviderFor<".len())
+                    .complete()
+                    .and_then(|list| {
+                        let [component, _, ..] = list.arguments.as_slice() else {
+                            return None;
+                        };
+                        Some(cleaned[component.clone()].to_string())
+                    });
+            }
+            return Some(cleaned);
+        }
+    }
+    None
+}
+
+/// Extracts any trait name from a delegation note
+fn extract_context_from_notes(notes: &[String], paths: PathStyle) -> Option<String> {
+    for note in notes {
+        // Look for "for `Type` to implement" pattern
+        if let Some(start) = note.find("for `") {
+            let after_start = start + 5;
+            if let Some(end) = note[after_start..].find("` to") {
+                let type_name = &note[after_start..after_start + end];
+                return Some(normalize_type(&strip_module_prefixes_in(type_name, paths)));
+            }
+        }
+    }
+    None
+}
+
+/// Information about a nested consumer trait dependency extracted from delegation notes
+/// This represents a consumer trait that a provider depends on
+#[derive(Debug, Clone)]
+struct NestedConsumerTrait {
+    /// The consumer trait name (e.g., "CanCalculateArea")
+    trait_name: String,
+    /// The context type (e.g., "Rectangle")
+    context_type: String,
+}
+
+/// Extracts nested consumer trait dependencies from delegation notes
+/// These are consumer traits that providers depend on, shown in notes like:
+/// "required for `Rectangle` to implement `CanCalculateArea`"
+fn extract_nested_consumer_traits(notes: &[String], paths: PathStyle) -> Vec<NestedConsumerTrait> {
+    let mut results = Vec::new();
+
+    for note in notes {
+        // Look for pattern: "required for `Context` to implement `TraitName`"
+        // This indicates that the provider depends on this consumer trait
+        if let Some(for_pos) = note.find("required for `") {
+            let after_for = for_pos + "required for `".len();
+
+            // Extract context type (between first ` and next `)
+            if let Some(context_end) = note[after_for..].find('`') {
+                let context_type = &note[after_for..after_for + context_end];
+
+                // Look for the trait name after "to implement `"
+                if let Some(implement_pos) = note[after_for + context_end..].find("to implement `")
+                {
+                    let trait_start =
+                        after_for + context_end + implement_pos + "to implement `".len();
+
+                    if let Some(trait_end) = note[trait_start..].find('`') {
+                        let trait_name = &note[trait_start..trait_start + trait_end];
+
+                        // Filter out internal CGP traits - we only want consumer traits
+                        // Consumer traits typically start with "Can" but exclude framework traits
+                        let cleaned_trait = strip_module_prefixes(trait_name);
+
+                        // Skip if it's an IsProviderFor or CanUseComponent trait (these are internal)
+                        if cleaned_trait.starts_with("Can")
+                            && !cleaned_trait.contains("CanUseComponent")
+                            && !cleaned_trait.starts_with("IsProviderFor")
+                        {
+                            results.push(NestedConsumerTrait {
+                                trait_name: cleaned_trait,
+                                context_type: normalize_type(&strip_module_prefixes_in(
+                                    context_type,
+                                    paths,
+                                )),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    results
+}
+
+/// Information about an unsatisfied provider trait extracted from the error message
+#[derive(Debug, Clone)]
+struct UnsatisfiedProvider {
+    /// The provider type (e.g., "RectangleArea")
+    provider_type: String,
+    /// The trait that's not satisfied (e.g., "AreaCalculator")
+    trait_name: String,
+    /// The context type (e.g., "Rectangle")
+    context_type: String,
+}
+
+/// Extracts unsatisfied provider information from the main error message
+/// Error messages follow the pattern:
+/// "the trait bound `ProviderType: TraitName<Context>` is not satisfied"
+fn extract_unsatisfied_provider_from_message(
+    message: &str,
+    paths: PathStyle,
+) -> Option<UnsatisfiedProvider> {
+    let after_bound = message.find("the trait bound `")? + "the trait bound `".len();
+    let bound_end = message[after_bound..].find("` is not satisfied")?;
+    let bound = &message[after_bound..after_bound + bound_end];
+
+    // Parse "Provider: Trait<Context>"
+    let (provider_type, trait_type) = parse_trait_bound(bound).value()?;
+    let context_type = trait_type.generic_arguments().first()?;
+
+    Some(UnsatisfiedProvider {
+        provider_type: strip_module_prefixes_in(&provider_type.to_string(), paths),
+        trait_name: trait_type.name()?.to_string(),
+        context_type: strip_module_prefixes_in(&context_type.to_string(), paths),
+    })
+}
+
+/// Formats the delegation chain with better structure and CGP-aware terminology
+fn format_delegation_chain(
+    entry: &DiagnosticEntry,
+    resolver: &NameResolver,
+    chain_style: ChainStyle,
+) -> Vec<String> {
+    // Try to build a proper dependency tree
+    if let Some(tree) = build_dependency_tree(entry, resolver) {
+        return if chain_style.narrate {
+            tree.narrate(chain_style.max_depth)
+        } else {
+            tree.render_to_depth(chain_style.max_depth)
+        };
+    }
+
+    // Fallback to old format if tree building fails
+    format_delegation_chain_legacy(entry, resolver)
+}
+
+/// Legacy delegation chain formatting (fallback)
+fn format_delegation_chain_legacy(entry: &DiagnosticEntry, resolver: &NameResolver) -> Vec<String> {
+    // Detect inner providers BEFORE deduplication
+    let all_inner_providers: Vec<String> = detect_inner_providers(entry.provider_relationships());
+
+    // First deduplicate the provider relationships to remove nested redundancies
+    let deduped_relationships = entry.chain_provider_relationships();
+
+    // Build a set of provider types we should keep
+    let kept_provider_types: std::collections::HashSet<String> = deduped_relationships
+        .iter()
+        .map(|r| r.provider_type.clone())
+        .collect();
+
+    // Deduplicate notes first
+    let deduped_notes = entry.chain_delegation_notes();
+
+    let mut formatted = Vec::new();
+
+    // If we have inner providers and field errors, add a hint about the root cause
+    // We check this AFTER deduplication to see which outer providers remain
+    if !all_inner_providers.is_empty() && entry.field_info.is_some() {
+        let outer_providers: Vec<_> = deduped_relationships
+            .iter()
+            .filter(|r| {
+                !all_inner_providers
+                    .iter()
+                    .any(|inner| inner == &r.provider_type)
+            })
+            .collect();
+
+        if !outer_providers.is_empty() && !all_inner_providers.is_empty() {
+            formatted.push(format!(
+                "→ The error in `{}` is caused by the inner provider `{}`",
+                outer_providers[0].provider_type, all_inner_providers[0]
+            ));
+        }
+    }
+
+    for note in deduped_notes {
+        // Parse provider info from the note to check if it should be kept
+        let should_keep = if let Some(provider_info) =
+            crate::cgp_patterns::extract_provider_relationship(&note, resolver.path_style())
+        {
+            // Keep this note only if its provider type is in the kept set, OR if we have no provider info
+            kept_provider_types.is_empty()
+                || kept_provider_types.contains(&provider_info.provider_type)
+        } else {
+            // Not a provider relationship note, always keep it
+            true
+        };
+
+        if !should_keep {
+            // Skip this note as it's redundant
+            continue;
+        }
+
+        let formatted_note = format_delegation_note(&note, resolver);
+        formatted.push(format!("→ {}", formatted_note));
+    }
+
+    formatted
+}
+
+/// Detects inner providers in a list of provider relationships
+/// Returns the innermost failing providers nested in higher-order providers, at any depth;
+/// the wrappers between them and the outermost provider are left out
+fn detect_inner_providers(relationships: &[ProviderRelationship]) -> Vec<String> {
+    let mut inner_providers: Vec<String> = Vec::new();
+
+    for tree in provider_nesting(relationships) {
+        for (inner_provider, _) in tree.innermost() {
+            if !inner_providers.iter().any(|known| known == inner_provider) {
+                inner_providers.push(inner_provider.to_string());
+            }
+        }
+    }
+
+    inner_providers
+}
+
+/// Simplifies a single delegation note
+fn format_delegation_note(note: &str, resolver: &NameResolver) -> String {
+    let mut result = note.to_string();
+
+    // Remove module prefixes
+    result = resolver.strip_module_prefixes(&result);
+
+    // Replace IsProviderFor with user-friendly "provider trait" terminology
+    result = replace_is_provider_for(&result, resolver);
+
+    // Replace CanUseComponent with simpler terminology
+    result = replace_can_use_component(&result);
+
+    // Truncate overly long type names
+    if result.len() > 150
+        && let Some(ellipsis_pos) = result.find(", ...>")
+    {
+        result = format!("{}...", &result[..ellipsis_pos]);
+    }
+
+    result
+}
+
+/// Replaces `IsProviderFor<Component, Context>` with "the provider trait `ProviderTrait`"
+fn replace_is_provider_for(message: &str, resolver: &NameResolver) -> String {
+    if !message.contains("IsProviderFor") {
+        return message.to_string();
+    }
+
+    // Find the IsProviderFor pattern
+    if let Some(start) = message.find("IsProviderFor<") {
+        let after_start = start + "IsProviderFor<".len();
+
+        // The component is the first argument
+        if let Some(list) = generic_arguments_at(message, after_start).value()
+            && let [component, _, ..] = list.arguments.as_slice()
+        {
+            let component_name = &message[component.clone()];
+
+            // Resolve provider trait name
+            let replacement = match resolver.provider_trait_for_component(component_name) {
+                Some(provider_trait_name) => {
+                    format!("the provider trait `{}`", provider_trait_name)
+                }
+                None => format!("the provider trait of `{}` (unresolved)", component_name),
+            };
+
+            // The end of IsProviderFor<...>, or where the cut-off type stops
+            let end_pos = list.end;
+
+            // Build replacement
+            let before = &message[..start];
+            let after = &message[end_pos..];
+
+            // Handle backticks
+            let has_opening_backtick = before.ends_with('`');
+            let has_closing_backtick = after.starts_with('`');
+
+            if has_opening_backtick && has_closing_backtick {
+                return format!(
+                    "{}{}{}",
+                    &before[..before.len() - 1],
+                    replacement,
+                    &after[1..]
+                );
+            } else {
+                return format!("{}{}{}", before, replacement, after);
+            }
+        }
+    }
+
+    message.to_string()
+}
+
+/// Replaces `CanUseComponent<Component>` with simpler terminology
+fn replace_can_use_component(message: &str) -> String {
+    if !message.contains("CanUseComponent") {
+        return message.to_string();
+    }
+
+    // Find the CanUseComponent pattern
+    if let Some(start) = message.find("CanUseComponent<") {
+        let after_start = start + "CanUseComponent<".len();
+
+        // Find the end of the generic type, excluding the closing bracket
+        let (component_end, end_pos) = match generic_arguments_at(message, after_start).value() {
+            Some(list) if list.is_closed => (list.end - 1, list.end),
+            _ => (message.len(), message.len()),
+        };
+
+        let component_name = message[after_start..component_end].trim();
+
+        // Build replacement - just explain it's checking component availability
+        let replacement = format!("use component `{}`", component_name);
+
+        // Handle backticks
+        let before = &message[..start];
+        let after = &message[end_pos..];
+
+        let has_opening_backtick = before.ends_with('`');
+        let has_closing_backtick = after.starts_with('`');
+
+        if has_opening_backtick && has_closing_backtick {
+            return format!(
+                "{}{}{}",
+                &before[..before.len() - 1],
+                replacement,
+                &after[1..]
+            );
+        } else {
+            return format!("{}{}{}", before, replacement, after);
+        }
+    }
+
+    message.to_string()
+}
+
+/// Renders a CGP diagnostic to a string using the graphical (colorful) handler
+#[cfg(feature = "graphical")]
+pub fn render_diagnostic_graphical(diagnostic: &CgpDiagnostic) -> String {
+    let handler = miette::GraphicalReportHandler::new();
+    let mut output = String::new();
+
+    match handler.render_report(&mut output, diagnostic) {
+        Ok(_) => output,
+        Err(_) => {
+            // Fallback to simple display if rendering fails
+            format!("error: {}", diagnostic.message)
+        }
+    }
+}
+
+/// Renders a CGP diagnostic to a plain text string (no colors)
+#[cfg(feature = "graphical")]
+pub fn render_diagnostic_plain(diagnostic: &CgpDiagnostic) -> String {
+    // Use the graphical handler without a theme, which produces plain text
+    let handler = miette::GraphicalReportHandler::new_themed(miette::GraphicalTheme::none());
+    let mut output = String::new();
+
+    match handler.render_report(&mut output, diagnostic) {
+        Ok(_) => output,
+        Err(_) => {
+            // Fallback to simple display if rendering fails
+            format!("error: {}", diagnostic.message)
+        }
+    }
+}
+
+/// Renders a CGP diagnostic in sentences, without drawings, for screen readers
+#[cfg(feature = "graphical")]
+pub fn render_diagnostic_narrated(diagnostic: &CgpDiagnostic) -> String {
+    let handler = miette::NarratableReportHandler::new();
+    let mut output = String::new();
+
+    match handler.render_report(&mut output, diagnostic) {
+        Ok(_) => output,
+        Err(_) => {
+            // Fallback to simple display if rendering fails
+            format!("error: {}", diagnostic.message)
+        }
+    }
+}
+
+/// Renders a CGP diagnostic as plain text, since colors need the `graphical` feature
+#[cfg(not(feature = "graphical"))]
+pub fn render_diagnostic_graphical(diagnostic: &CgpDiagnostic) -> String {
+    render_plain(diagnostic)
+}
+
+/// Renders a CGP diagnostic to a plain text string (no colors)
+#[cfg(not(feature = "graphical"))]
+pub fn render_diagnostic_plain(diagnostic: &CgpDiagnostic) -> String {
+    render_plain(diagnostic)
+}
+
+/// Renders a CGP diagnostic as plain text, whose source lines screen readers can read
+#[cfg(not(feature = "graphical"))]
+pub fn render_diagnostic_narrated(diagnostic: &CgpDiagnostic) -> String {
+    render_plain(diagnostic)
+}
+
+/// Detects if we're running in a terminal that supports colors
+pub fn is_terminal() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdout().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_duplicate_check_errors() {
+        use crate::workspace_scan::WorkspaceScan;
+        use std::path::Path;
+
+        let mut scan = WorkspaceScan::new();
+        for file in ["src/a.rs", "src/b.rs"] {
+            let path = Path::new(file);
+            scan.scan_file(
+                path,
+                path,
+                "shapes",
+                "a",
+                "check_components! {\n    CanUseRectangle for Rectangle {\n        AreaComponent,\n    }\n}",
+            );
+        }
+        let duplicates = scan.duplicate_checks();
+        assert_eq!(duplicates.len(), 1);
+
+        let span = |file: &str, line: usize| SpanLocation {
+            file: file.to_string(),
+            line_start: line,
+            column_start: 9,
+            line_end: line,
+            column_end: 22,
+            label: "unsatisfied".to_string(),
+            text: Vec::new(),
+            source_path: None,
+        };
+        let diagnostics = vec![
+            CgpDiagnostic::new("first".to_string(), vec![span("src/a.rs", 3)]),
+            CgpDiagnostic::new("unrelated".to_string(), vec![span("src/c.rs", 3)]),
+            CgpDiagnostic::new("second".to_string(), vec![span("src/b.rs", 3)]),
+        ];
+
+        let merged = merge_duplicate_check_errors(diagnostics, &duplicates);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].message, "first");
+        assert_eq!(merged[0].spans.len(), 2);
+        assert_eq!(merged[0].spans[1].label, "also checked here");
+        assert!(
+            merged[0]
+                .help
+                .as_ref()
+                .unwrap()
+                .contains("• `src/b.rs:3` in check trait `CanUseRectangle`")
+        );
+        assert_eq!(merged[1].message, "unrelated");
+
+        let warning = format_duplicate_check_warning(&duplicates[0]).unwrap();
+        assert_eq!(
+            warning.message,
+            "`AreaComponent` is checked for `Rectangle` in 2 `check_components!` blocks"
+        );
+        assert_eq!(warning.spans[0].file, "src/a.rs");
+        assert_eq!(
+            warning.spans[0].source_path.as_deref(),
+            Some(Path::new("src/a.rs"))
+        );
+    }
+
+    #[test]
+    fn test_shadowed_check_trait_warning_paths() {
+        use crate::workspace_scan::CheckBlock;
+        use std::path::PathBuf;
+
+        let block = |file: &str, display_path: &str, module_path: &str| CheckBlock {
+            trait_name: "CanUseRectangle".to_string(),
+            context_type: "Rectangle".to_string(),
+            crate_name: "app".to_string(),
+            module_path: module_path.to_string(),
+            file: PathBuf::from(file),
+            display_path: PathBuf::from(display_path),
+            line: 3,
+            column: 5,
+            components: Vec::new(),
+        };
+        let first = block("/work/app/src/a.rs", "src/a.rs", "crate::a");
+        let second = block("/work/app/src/b.rs", "src/b.rs", "crate::b");
+
+        let warning = format_shadowed_check_trait_warning(&[&first, &second]).unwrap();
+        // The span shows the workspace-relative path and reads the source from the disk path
+        assert_eq!(warning.spans[0].file, "src/a.rs");
+        assert_eq!(
+            warning.spans[0].source_path,
+            Some(PathBuf::from("/work/app/src/a.rs"))
+        );
+        let help = warning.help.unwrap();
+        assert!(help.contains("• `src/a.rs:3` in module `crate::a` for `Rectangle`"));
+        assert!(help.contains("• `src/b.rs:3` in module `crate::b` for `Rectangle`"));
+    }
+
+    #[test]
+    fn test_cross_crate_check_note() {
+        use crate::name_resolution::ResolutionMode;
+        use crate::test_utils::load_diagnostic_database;
+        use crate::workspace_scan::WorkspaceScan;
+        use std::path::Path;
+
+        // The fixture is compiled in `cgp-error-messages-example`; pretend the context lives elsewhere
+        let mut scan = WorkspaceScan::new();
+        scan.scan_file(
+            Path::new("src/lib.rs"),
+            Path::new("src/lib.rs"),
+            "my-app",
+            "crate",
+            "#[derive(HasField)]\npub struct Rectangle {\n    pub width: f64,\n}\n",
+        );
+
+        let mut db = load_diagnostic_database(format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        ));
+        db.set_name_resolver(NameResolver::from_scan(&scan, ResolutionMode::Heuristic));
+
+        let help = db.render_cgp_diagnostics()[0].help.clone().unwrap();
+        assert!(help.contains(
+            "note: `CanUseRectangle` is checked in `cgp-error-messages-example`, \
+             and the context `Rectangle` is defined in `my-app`"
+        ));
+    }
+
+    #[test]
+    fn test_generated_provider_declaration() {
+        use crate::test_utils::load_example_with_index;
+
+        let (mut db, _) = load_example_with_index("base_area");
+
+        // The generated provider struct points back to the attribute that declares it
+        let help = db.render_cgp_diagnostics()[0].help.clone().unwrap();
+        assert!(help.contains(
+            "for provider `RectangleArea` (provider trait)\n          \
+             │  declared as `#[cgp_impl(new RectangleArea)]` at examples/src/base_area.rs:15\n"
+        ));
+    }
+
+    #[test]
+    fn test_unwired_getter_fix() {
+        use crate::name_resolution::ResolutionMode;
+        use crate::test_utils::load_diagnostic_database;
+        use crate::workspace_scan::WorkspaceScan;
+        use std::path::Path;
+
+        let source = "\
+#[cgp_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+    fn height(&self) -> f64;
+}
+";
+        let load = |wiring: &str| {
+            let mut scan = WorkspaceScan::new();
+            let path = Path::new("src/lib.rs");
+            scan.scan_file(
+                path,
+                path,
+                "shapes",
+                "crate",
+                &format!("{}{}", source, wiring),
+            );
+
+            let mut db = load_diagnostic_database(format!(
+                "{}/../examples/src/base_area.json",
+                env!("CARGO_MANIFEST_DIR")
+            ));
+            db.set_name_resolver(NameResolver::from_scan(&scan, ResolutionMode::Heuristic));
+            db.render_cgp_diagnostics()[0].help.clone().unwrap()
+        };
+
+        let help = load("");
+        assert!(help.contains(
+            "    • Wire `RectangleFieldsGetterComponent` to `UseFields` (or a custom getter) \
+             in `delegate_components!` for `Rectangle`"
+        ));
+        assert!(!help.contains("add `#[derive(HasField)]`"));
+
+        // Once the getter is wired to `UseFields`, the field itself is missing
+        let help = load(
+            "delegate_components! {\n    Rectangle {\n        RectangleFieldsGetterComponent: UseFields,\n    }\n}\n",
+        );
+        assert!(!help.contains("UseFields"));
+        assert!(help.contains("• Add a field `"));
+    }
+
+    #[test]
+    fn test_inner_provider_replacement() {
+        use crate::name_resolution::ResolutionMode;
+        use crate::test_utils::load_example_with_index;
+        use std::path::Path;
+
+        let load = |extra_providers: &str| {
+            let (mut db, mut scan) = load_example_with_index("scaled_area");
+            let extra = Path::new("src/square_area.rs");
+            scan.scan_file(
+                extra,
+                extra,
+                "cgp-error-messages-example",
+                "crate::square_area",
+                extra_providers,
+            );
+            db.set_name_resolver(NameResolver::from_scan(&scan, ResolutionMode::Heuristic));
+            db.render_cgp_diagnostics()[0].help.clone().unwrap()
+        };
+
+        // Without another provider of `AreaCalculator`, there is nothing to suggest
+        assert!(!load("").contains("Replace the inner provider"));
+
+        // `SquareArea` only reads `width`, which `Rectangle` has; `CircleArea` reads `radius`
+        let help = load(
+            "\
+#[cgp_auto_getter]
+pub trait HasWidth {
+    fn width(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasRadius {
+    fn radius(&self) -> f64;
+}
+
+#[cgp_impl(new SquareArea)]
+impl AreaCalculator
+where
+    Self: HasWidth,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.width()
+    }
+}
+
+#[cgp_impl(new CircleArea)]
+impl AreaCalculator
+where
+    Self: HasRadius,
+{
+    fn area(&self) -> f64 {
+        3.14 * self.radius() * self.radius()
+    }
+}
+",
+        );
+        assert!(help.contains(
+            "To fix this error:\n    \
+             • Replace the inner provider `RectangleArea` with `SquareArea`, \
+             whose requirements `Rectangle` meets:\n"
+        ));
+        assert!(help.contains("\n          AreaCalculatorComponent: ScaledArea<SquareArea>,\n"));
+        assert!(!help.contains("CircleArea"));
+    }
+
+    #[test]
+    fn test_deref_context_missing_field() {
+        use crate::test_utils::load_example_with_index;
+
+        let (mut db, _) = load_example_with_index("deref_area");
+        let diagnostic = &db.render_cgp_diagnostics()[0];
+
+        assert_eq!(
+            diagnostic.message,
+            "missing field `height` in `RectangleConfig`, which the context `Rectangle` derefs to."
+        );
+        let help = diagnostic.help.clone().unwrap();
+        assert!(help.contains(
+            "note: the field must be added to `RectangleConfig`, which `Rectangle` derefs to"
+        ));
+        assert!(help.contains(
+            "    • Add a field `pub height: f64` to the `RectangleConfig` struct at examples/src/deref_area.rs:28"
+        ));
+        assert!(!help.contains("#[derive(HasField)]"));
+        assert!(help.contains("available fields of `RectangleConfig`: width\n"));
+    }
+
+    #[test]
+    fn test_many_merged_components() {
+        use crate::diagnostic_db::DiagnosticDatabase;
+        use cargo_metadata::Message;
+        use std::fs::File;
+        use std::io::BufReader;
+
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let message = Message::parse_stream(BufReader::new(File::open(json_path).unwrap()))
+            .find_map(|message| match message.unwrap() {
+                Message::CompilerMessage(msg) if msg.message.level == DiagnosticLevel::Error => {
+                    Some(msg)
+                }
+                _ => None,
+            })
+            .unwrap();
+
+        // The same check block fails for seven components, each reported twice
+        let components = ["G", "F", "E", "D", "C", "B", "A"];
+        let mut db = DiagnosticDatabase::new();
+        for _ in 0..2 {
+            for (offset, name) in components.iter().enumerate() {
+                let json = serde_json::to_string(&message)
+                    .unwrap()
+                    .replace("AreaCalculatorComponent", &format!("{}Component", name));
+                let mut fragment: cargo_metadata::CompilerMessage =
+                    serde_json::from_str(&json).unwrap();
+                for span in &mut fragment.message.spans {
+                    span.line_start += offset;
+                    span.line_end += offset;
+                }
+                db.add_diagnostic(&fragment);
+            }
+        }
+        assert_eq!(db.get_all_entries().len(), 1);
+
+        let help = db.render_cgp_diagnostics()[0].help.clone().unwrap();
+        assert!(help.contains(
+            "to use multiple components: `AComponent`, `BComponent`, `CComponent`, `DComponent`, \
+             `EComponent`, and 2 more."
+        ));
+    }
+
+    #[test]
+    fn test_replace_is_provider_for() {
+        let input =
+            "required for `Foo` to implement `IsProviderFor<AreaCalculatorComponent, Context>`";
+        let output = replace_is_provider_for(input, &NameResolver::default());
+        assert!(output.contains("provider trait `AreaCalculator`"));
+        assert!(!output.contains("IsProviderFor"));
+
+        // Strict mode never derives the provider trait from the component name
+        let strict = NameResolver::new(Vec::new(), crate::name_resolution::ResolutionMode::Strict);
+        let output = replace_is_provider_for(input, &strict);
+        assert!(output.contains("the provider trait of `AreaCalculatorComponent` (unresolved)"));
+    }
+
+    #[test]
+    fn test_unsized_type_error() {
+        use crate::diagnostic_db::DiagnosticDatabase;
+        use cargo_metadata::CompilerMessage;
+        use serde_json::json;
+
+        let span = |line: usize, start: usize, end: usize, text: &str, label: Option<&str>| {
+            json!({
+                "file_name": "src/lib.rs", "byte_start": 0, "byte_end": 0,
+                "line_start": line, "line_end": line, "column_start": start, "column_end": end,
+                "is_primary": label != Some("unsatisfied trait bound introduced here"),
+                "text": [{ "text": text, "highlight_start": start, "highlight_end": end }],
+                "label": label, "suggested_replacement": null,
+                "suggestion_applicability": null, "expansion": null,
+            })
+        };
+        let child = |level: &str, message: &str, spans: Vec<serde_json::Value>| {
+            json!({
+                "message": message, "code": null, "level": level,
+                "spans": spans, "children": [], "rendered": null,
+            })
+        };
+
+        let message: CompilerMessage = serde_json::from_value(json!({
+            "package_id": "path+file:///app#app@0.1.0",
+            "target": {
+                "kind": ["lib"], "crate_types": ["lib"], "name": "app",
+                "src_path": "/app/src/lib.rs", "edition": "2024",
+                "doc": true, "doctest": true, "test": true,
+            },
+            "message": {
+                "message": "the size for values of type `str` cannot be known at compilation time",
+                "code": { "code": "E0277", "explanation": null },
+                "level": "error",
+                "spans": [span(58, 9, 28, "        NameGetterComponent,", Some("doesn't have a size known at compile-time"))],
+                "children": [
+                    child("help", "the trait `Sized` is not implemented for `str`", vec![]),
+                    child(
+                        "note",
+                        "required for `GetName<str>` to implement `cgp::prelude::IsProviderFor<NameGetterComponent, Person>`",
+                        vec![span(31, 6, 10, "impl<Name> NameGetter", Some("unsatisfied trait bound introduced here"))],
+                    ),
+                    child("note", "required for `Person` to implement `cgp::prelude::CanUseComponent<NameGetterComponent>`", vec![]),
+                    child("note", "required by a bound in `CanUsePerson`", vec![]),
+                ],
+                "rendered": null,
+            },
+        }))
+        .unwrap();
+
+        let mut db = DiagnosticDatabase::new();
+        db.add_diagnostic(&message);
+        let diagnostics = db.render_cgp_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.code.as_deref(), Some("CGP0009"));
+        assert_eq!(
+            diagnostic.message,
+            "unsized type `str` used by the provider `GetName<str>` in the context `Person`."
+        );
+
+        let help = diagnostic.help.as_deref().unwrap();
+        assert!(help.contains(
+            "Provider `GetName<str>` requires its type parameter `Name` to be `Sized`, but it is wired with `str` for `NameGetterComponent`."
+        ));
+        assert!(help.contains(
+            "• Relax the bound with `Name: ?Sized` in the implementation of `GetName` at src/lib.rs:31"
+        ));
+        assert!(help.contains("such as `String` instead of `str`"));
+    }
+
+    #[test]
+    fn test_unimplemented_hint() {
+        use crate::cgp_patterns::extract_unimplemented_hint;
+        use serde_json::json;
+
+        let child = |message: &str| {
+            json!({
+                "message": message, "code": null, "level": "note",
+                "spans": [], "children": [], "rendered": null,
+            })
+        };
+        let diagnostic: Diagnostic = serde_json::from_value(json!({
+            "message": "the context `Person` does not implement `NameGetterComponent`",
+            "code": { "code": "E0277", "explanation": null },
+            "level": "error",
+            "spans": [],
+            "children": [
+                child("delegate `NameGetterComponent` to a provider in `delegate_components!`"),
+                child("required for `Person` to implement `CanUseComponent<NameGetterComponent>`"),
+                child("required by a bound in `CanUsePerson`"),
+            ],
+            "rendered": null,
+        }))
+        .unwrap();
+
+        let hint = extract_unimplemented_hint(&diagnostic).unwrap();
+        assert_eq!(
+            hint,
+            UnimplementedHint {
+                message: Some(
+                    "the context `Person` does not implement `NameGetterComponent`".to_string()
+                ),
+                notes: vec![
+                    "delegate `NameGetterComponent` to a provider in `delegate_components!`"
+                        .to_string()
+                ],
+            }
+        );
+
+        let mut ours = CgpDiagnostic::new("missing delegation".to_string(), Vec::new());
+        ours.help = Some(
+            "To fix this error:\n    • delegate `NameGetterComponent` to a provider in `delegate_components!`"
+                .to_string(),
+        );
+        let merged = apply_unimplemented_hint(ours, &hint);
+        assert_eq!(
+            merged.message,
+            "the context `Person` does not implement `NameGetterComponent`"
+        );
+        assert_eq!(
+            merged.help.as_deref(),
+            Some(
+                "missing delegation\n\nTo fix this error:\n    • delegate `NameGetterComponent` to a provider in `delegate_components!`"
+            )
+        );
+
+        // rustc's own headline is not a hint
+        let mut diagnostic = diagnostic;
+        diagnostic.message =
+            "the trait bound `Person: CanUseComponent<NameGetterComponent>` is not satisfied"
+                .to_string();
+        diagnostic.children.truncate(0);
+        assert_eq!(extract_unimplemented_hint(&diagnostic), None);
+    }
+
+    #[test]
+    fn test_render_to_depth() {
+        let node =
+            |description: &str, kind: NodeKind, children: Vec<DependencyNode>| DependencyNode {
+                description: description.to_string(),
+                kind,
+                is_satisfied: None,
+                is_reference: false,
+                children,
+                doc: None,
+                declared_at: None,
+            };
+        let tree = node(
+            "CanUseRectangle",
+            NodeKind::Check,
+            vec![node(
+                "CanCalculateArea",
+                NodeKind::Consumer,
+                vec![node(
+                    "RectangleArea",
+                    NodeKind::Provider,
+                    vec![node("height", NodeKind::Field, Vec::new())],
+                )],
+            )],
+        );
+
+        assert_eq!(tree.render_to_depth(None), tree.render());
+        assert_eq!(tree.render().len(), 4);
+        assert_eq!(
+            tree.render_to_depth(Some(1)),
+            vec![
+                "CanUseRectangle (check trait)".to_string(),
+                "└─ CanCalculateArea (consumer trait)".to_string(),
+                "   └─ … 2 more requirements".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_narrate() {
+        let node =
+            |description: &str, kind: NodeKind, children: Vec<DependencyNode>| DependencyNode {
+                description: description.to_string(),
+                kind,
+                is_satisfied: None,
+                is_reference: false,
+                children,
+                doc: None,
+                declared_at: None,
+            };
+        let mut field = node("field `height` on `Rectangle`", NodeKind::Field, Vec::new());
+        field.is_satisfied = Some(false);
+        let mut provider = node(
+            "`AreaCalculator<Rectangle>` for provider `RectangleArea`",
+            NodeKind::Provider,
+            vec![field],
+        );
+        provider.doc = Some("Computes the area from the width and height".to_string());
+        let tree = node(
+            "`CanUseRectangle` for `Rectangle`",
+            NodeKind::Check,
+            vec![node(
+                "consumer trait of `AreaCalculatorComponent` for `Rectangle`",
+                NodeKind::Consumer,
+                vec![provider],
+            )],
+        );
+
+        assert_eq!(
+            tree.narrate(None),
+            vec![
+                "1. The error is in `CanUseRectangle` for `Rectangle` (check trait).",
+                "2. Step 1 requires consumer trait of `AreaCalculatorComponent` for `Rectangle` (consumer trait).",
+                "3. Step 2 is provided by `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait). Its documentation says: Computes the area from the width and height",
+                "4. Step 3 requires field `height` on `Rectangle`, which is not satisfied.",
+            ]
+        );
+        assert_eq!(
+            tree.narrate(Some(1))[2],
+            "3. Step 2 has 2 more requirements, which are not shown."
+        );
+    }
+
+    #[test]
+    fn test_dependency_tree_without_check_trait() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let mut db = load_diagnostic_database(format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        ));
+        db.resolve_component_dependencies();
+
+        // As if the component were used in a function body rather than a check block
+        let mut entry = db.get_active_entries()[0].clone();
+        entry.check_trait = None;
+
+        let tree = build_dependency_tree(&entry, &NameResolver::default()).unwrap();
+        assert_eq!(tree.kind, NodeKind::Usage);
+        assert_eq!(
+            tree.description,
+            "requirements of `AreaCalculatorComponent` for `Rectangle`"
+        );
+        assert!(tree.contains_chain(&[(NodeKind::Field, "heig")]));
+        assert_eq!(
+            format_delegation_chain(&entry, &NameResolver::default(), ChainStyle::default())[0],
+            "requirements of `AreaCalculatorComponent` for `Rectangle`"
+        );
+    }
+
+    #[test]
+    fn test_contains_chain_through_second_sibling() {
+        let node = |description: &str, kind, children| DependencyNode {
+            description: description.to_string(),
+            kind,
+            is_satisfied: Some(false),
+            is_reference: false,
+            children,
+            doc: None,
+            declared_at: None,
+        };
+        let tree = node(
+            "`CanUseRectangle` for `Rectangle`",
+            NodeKind::Check,
+            vec![
+                node(
+                    "`CanCalculateArea` for `Rectangle`",
+                    NodeKind::Consumer,
+                    vec![node(
+                        "`AreaCalculator<Rectangle>` for provider `RectangleArea`",
+                        NodeKind::Provider,
+                        Vec::new(),
+                    )],
+                ),
+                node(
+                    "`CanCalculateAreaTwice` for `Rectangle`",
+                    NodeKind::Consumer,
+                    vec![node(
+                        "field `height` on `Rectangle`",
+                        NodeKind::Field,
+                        Vec::new(),
+                    )],
+                ),
+            ],
+        );
+
+        // Both consumers match the first hop, but only the second one leads to the field
+        assert!(tree.contains_chain(&[
+            (NodeKind::Consumer, "CanCalculateArea"),
+            (NodeKind::Field, "height"),
+        ]));
+        assert!(!tree.contains_chain(&[
+            (NodeKind::Consumer, "CanCalculateArea"),
+            (NodeKind::Field, "width"),
+        ]));
+    }
+
+    #[test]
+    fn test_render_graph_exports() {
+        let tree = DependencyNode {
+            description: "`CanUseRectangle` for `Rectangle`".to_string(),
+            kind: NodeKind::Check,
+            is_satisfied: None,
+            is_reference: false,
+            children: vec![DependencyNode {
+                description: "field `height` on `Rectangle<T>`".to_string(),
+                kind: NodeKind::Field,
+                is_satisfied: Some(false),
+                is_reference: false,
+                children: Vec::new(),
+                doc: None,
+                declared_at: None,
+            }],
+            doc: None,
+            declared_at: None,
+        };
+
+        assert_eq!(
+            tree.render_dot(),
+            "digraph cgp_dependencies {
+    node [shape=box];
+    n0 [label=\"`CanUseRectangle` for `Rectangle` (check trait)\"];
+    n1 [label=\"field `height` on `Rectangle<T>`\", color=red];
+    n0 -> n1;
+}"
+        );
+        assert_eq!(
+            tree.render_mermaid(),
+            "graph TD
+    n0[\"#96;CanUseRectangle#96; for #96;Rectangle#96; (check trait)\"]
+    n1[\"field #96;height#96; on #96;Rectangle#lt;T#gt;#96;\"]
+    n0 --> n1
+    classDef unsatisfied stroke:#d00,color:#d00
+    class n1 unsatisfied"
+        );
+    }
+
+    #[test]
+    fn test_sized_alternative() {
+        assert_eq!(sized_alternative("str").as_deref(), Some("String"));
+        assert_eq!(
+            sized_alternative("dyn Display").as_deref(),
+            Some("Box<dyn Display>")
+        );
+        assert_eq!(sized_alternative("[u8]").as_deref(), Some("Vec<u8>"));
+        assert_eq!(sized_alternative("Path"), None);
+    }
+
+    #[test]
+    fn test_extract_unsatisfied_provider_from_message() {
+        let provider = extract_unsatisfied_provider_from_message(
+            "the trait bound `ScaledArea<RectangleArea>: cgp::AreaCalculator<Wrapper<Rectangle, (u8, u16)>>` is not satisfied",
+            PathStyle::Short,
+        )
+        .unwrap();
+        assert_eq!(provider.provider_type, "ScaledArea<RectangleArea>");
+        assert_eq!(provider.trait_name, "AreaCalculator");
+        assert_eq!(provider.context_type, "Wrapper<Rectangle, (u8, u16)>");
+
+        assert!(
+            extract_unsatisfied_provider_from_message(
+                "the trait bound `Rectangle: CanCalculateArea` is not satisfied",
+                PathStyle::Short,
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_replace_can_use_component() {
+        assert_eq!(
+            replace_can_use_component(
+                "required for `Rectangle` to implement `CanUseComponent<AreaCalculatorComponent>`"
+            ),
+            "required for `Rectangle` to implement use component `AreaCalculatorComponent`"
+        );
+
+        // A message cut off inside the component type keeps the rest of the type
+        assert_eq!(
+            replace_can_use_component(
+                "required for `Rectangle` to implement `CanUseComponent<Foo<Bar"
+            ),
+            "required for `Rectangle` to implement `use component `Foo<Bar`"
+        );
+    }
+}
@@ -0,0 +1,774 @@
+/// Module for the CGP error codes and the `cargo cgp explain` catalog
+/// Every class of improved error has a stable code (e.g., `CGP0001`), so that users can
+/// look up a longer explanation with examples, like `rustc --explain` does for `E0277`.
+use anyhow::{Result, bail};
+
+/// A class of CGP error or warning reported by cargo-cgp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgpErrorCode {
+    /// A getter requires a field that the context struct does not have
+    MissingField,
+    /// A component is used by a context that does not delegate it to a provider
+    MissingDelegation,
+    /// A getter requires a field from a struct without `#[derive(HasField)]`
+    MissingHasFieldDerive,
+    /// The provider of a component requires something that the context does not implement
+    UnsatisfiedProviderDependency,
+    /// Two `check_components!` blocks in a crate generate check traits with the same name
+    ShadowedCheckTrait,
+    /// The same component is checked for the same context in several files
+    DuplicateCheck,
+    /// A component is delegated by a context but never checked with `check_components!`
+    UncheckedComponent,
+    /// A provider is implemented but never used in `delegate_components!`
+    UnusedProvider,
+    /// A provider is wired with an unsized type for a parameter that must be `Sized`
+    UnsizedType,
+    /// A component is wired to a provider stack deeper than the configured budget
+    DeepProviderStack,
+    /// A generic function is called with a context that does not implement its consumer trait bound
+    UnsatisfiedUsageBound,
+    /// A component is delegated to a type that does not implement its provider trait
+    NotAProvider,
+    /// A getter reads a field with another type than the context struct declares
+    FieldTypeMismatch,
+    /// Evaluating the wiring of a component overflows, usually because it depends on itself
+    RequirementOverflow,
+    /// A provider requires the context to be `Send` or `Sync`, and a field of it is not
+    AutoTraitBound,
+}
+
+impl CgpErrorCode {
+    /// All error codes, in catalog order
+    pub const ALL: [CgpErrorCode; 15] = [
+        CgpErrorCode::MissingField,
+        CgpErrorCode::MissingDelegation,
+        CgpErrorCode::MissingHasFieldDerive,
+        CgpErrorCode::UnsatisfiedProviderDependency,
+        CgpErrorCode::ShadowedCheckTrait,
+        CgpErrorCode::DuplicateCheck,
+        CgpErrorCode::UncheckedComponent,
+        CgpErrorCode::UnusedProvider,
+        CgpErrorCode::UnsizedType,
+        CgpErrorCode::DeepProviderStack,
+        CgpErrorCode::UnsatisfiedUsageBound,
+        CgpErrorCode::NotAProvider,
+        CgpErrorCode::FieldTypeMismatch,
+        CgpErrorCode::RequirementOverflow,
+        CgpErrorCode::AutoTraitBound,
+    ];
+
+    /// The code as shown in diagnostics (e.g., "CGP0001")
+    pub fn code(self) -> &'static str {
+        match self {
+            CgpErrorCode::MissingField => "CGP0001",
+            CgpErrorCode::MissingDelegation => "CGP0002",
+            CgpErrorCode::MissingHasFieldDerive => "CGP0003",
+            CgpErrorCode::UnsatisfiedProviderDependency => "CGP0004",
+            CgpErrorCode::ShadowedCheckTrait => "CGP0005",
+            CgpErrorCode::DuplicateCheck => "CGP0006",
+            CgpErrorCode::UncheckedComponent => "CGP0007",
+            CgpErrorCode::UnusedProvider => "CGP0008",
+            CgpErrorCode::UnsizedType => "CGP0009",
+            CgpErrorCode::DeepProviderStack => "CGP0010",
+            CgpErrorCode::UnsatisfiedUsageBound => "CGP0011",
+            CgpErrorCode::NotAProvider => "CGP0012",
+            CgpErrorCode::FieldTypeMismatch => "CGP0013",
+            CgpErrorCode::RequirementOverflow => "CGP0014",
+            CgpErrorCode::AutoTraitBound => "CGP0015",
+        }
+    }
+
+    /// Looks up an error code, ignoring case (e.g., "cgp0001" or "CGP0001")
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|error_code| error_code.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// A one-line summary of the error class
+    pub fn title(self) -> &'static str {
+        match self {
+            CgpErrorCode::MissingField => "missing field in the context",
+            CgpErrorCode::MissingDelegation => "component is not delegated by the context",
+            CgpErrorCode::MissingHasFieldDerive => "context does not derive `HasField`",
+            CgpErrorCode::UnsatisfiedProviderDependency => "provider dependency is not satisfied",
+            CgpErrorCode::ShadowedCheckTrait => "check trait is shadowed",
+            CgpErrorCode::DuplicateCheck => "component is checked more than once",
+            CgpErrorCode::UncheckedComponent => "delegated component is never checked",
+            CgpErrorCode::UnusedProvider => "provider is never delegated",
+            CgpErrorCode::UnsizedType => "provider requires a `Sized` type",
+            CgpErrorCode::DeepProviderStack => "provider stack is nested too deeply",
+            CgpErrorCode::UnsatisfiedUsageBound => {
+                "context does not satisfy the bound of a generic function"
+            }
+            CgpErrorCode::NotAProvider => "delegated type is not a provider of the component",
+            CgpErrorCode::FieldTypeMismatch => "field has another type than its getter reads",
+            CgpErrorCode::RequirementOverflow => "evaluating the wiring overflows",
+            CgpErrorCode::AutoTraitBound => "provider requires the context to be `Send` or `Sync`",
+        }
+    }
+
+    /// The tutorial-style explanation, with example code
+    pub fn explanation(self) -> &'static str {
+        match self {
+            CgpErrorCode::MissingField => MISSING_FIELD,
+            CgpErrorCode::MissingDelegation => MISSING_DELEGATION,
+            CgpErrorCode::MissingHasFieldDerive => MISSING_HAS_FIELD_DERIVE,
+            CgpErrorCode::UnsatisfiedProviderDependency => UNSATISFIED_PROVIDER_DEPENDENCY,
+            CgpErrorCode::ShadowedCheckTrait => SHADOWED_CHECK_TRAIT,
+            CgpErrorCode::DuplicateCheck => DUPLICATE_CHECK,
+            CgpErrorCode::UncheckedComponent => UNCHECKED_COMPONENT,
+            CgpErrorCode::UnusedProvider => UNUSED_PROVIDER,
+            CgpErrorCode::UnsizedType => UNSIZED_TYPE,
+            CgpErrorCode::DeepProviderStack => DEEP_PROVIDER_STACK,
+            CgpErrorCode::UnsatisfiedUsageBound => UNSATISFIED_USAGE_BOUND,
+            CgpErrorCode::NotAProvider => NOT_A_PROVIDER,
+            CgpErrorCode::FieldTypeMismatch => FIELD_TYPE_MISMATCH,
+            CgpErrorCode::RequirementOverflow => REQUIREMENT_OVERFLOW,
+            CgpErrorCode::AutoTraitBound => AUTO_TRAIT_BOUND,
+        }
+    }
+}
+
+const MISSING_FIELD: &str = r#"A getter trait requires a field that the context struct does not have.
+
+Getter traits declared with `#[cgp_auto_getter]` read fields from the context
+through `HasField`. A provider that requires such a getter can only be used by
+contexts that have a field with the same name and type.
+
+Erroneous code example:
+
+```
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> &f64;
+    fn height(&self) -> &f64;
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    // `height` is missing
+}
+```
+
+Add the missing field to the context, with the type returned by the getter:
+
+```
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    pub height: f64,
+}
+```
+
+`cargo cgp fix` adds missing fields automatically.
+"#;
+
+const MISSING_DELEGATION: &str = r#"A component is used by a context that does not delegate it to a provider.
+
+A context can only use a component if `delegate_components!` wires the
+component to a provider for that context.
+
+Erroneous code example:
+
+```
+delegate_components! {
+    Rectangle {
+        // `AreaCalculatorComponent` is not wired
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
+```
+
+Wire the component to a provider:
+
+```
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: RectangleArea,
+    }
+}
+```
+"#;
+
+const MISSING_HAS_FIELD_DERIVE: &str = r#"A getter trait reads a field from a context that does not derive `HasField`.
+
+Getter traits declared with `#[cgp_auto_getter]` can only read the fields of
+structs that implement `HasField`, which is usually derived.
+
+Erroneous code example:
+
+```
+pub struct Rectangle {
+    pub width: f64,
+    pub height: f64,
+}
+```
+
+Derive `HasField` on the context:
+
+```
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    pub height: f64,
+}
+```
+
+If the struct already derives `HasField`, the field itself is missing (see CGP0001).
+"#;
+
+const UNSATISFIED_PROVIDER_DEPENDENCY: &str = r#"The provider of a component requires something that the context does not implement.
+
+A provider implementation may require the context to implement other consumer
+traits or getter traits. If the context does not implement one of them, the
+provider cannot be used, even though the component is delegated to it.
+
+Erroneous code example:
+
+```
+#[cgp_impl(new DensityFromMassField)]
+impl DensityCalculator
+where
+    Self: HasMass + CanCalculateArea,
+{
+    fn density(&self) -> f64 {
+        self.mass() / self.area()
+    }
+}
+
+delegate_components! {
+    Rectangle {
+        DensityCalculatorComponent: DensityFromMassField,
+        // `AreaCalculatorComponent` is not wired, so `CanCalculateArea` is missing
+    }
+}
+```
+
+Follow the dependency chain in the error to the first unsatisfied requirement and
+implement it for the context, e.g., by wiring the missing component:
+
+```
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: RectangleArea,
+        DensityCalculatorComponent: DensityFromMassField,
+    }
+}
+```
+
+Adding a `check_components!` entry for the missing component shows its own error.
+"#;
+
+const SHADOWED_CHECK_TRAIT: &str = r#"Two `check_components!` blocks in the same crate generate check traits with the same name.
+
+Each `check_components!` block generates a trait. If two blocks use the same
+trait name, one may shadow the other, and its checks are silently skipped.
+
+Erroneous code example:
+
+```
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        DensityCalculatorComponent,
+    }
+}
+```
+
+Give each check trait a unique name, or merge the blocks:
+
+```
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+        DensityCalculatorComponent,
+    }
+}
+```
+"#;
+
+const DUPLICATE_CHECK: &str = r#"The same component is checked for the same context in several files.
+
+Each check reports its own error, so a single wiring problem is reported once
+for every file that checks it.
+
+Erroneous code example:
+
+```
+// src/shapes.rs
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
+
+// tests/shapes.rs
+check_components! {
+    CanUseRectangleInTests for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
+```
+
+Keep one of the checks.
+"#;
+
+const UNCHECKED_COMPONENT: &str = r#"A component is delegated by a context but never checked with `check_components!`.
+
+The wiring in `delegate_components!` is evaluated lazily: a missing dependency
+of the provider is only reported where the component is used, which may be far
+from the wiring, or not at all until a downstream crate uses it. Checking the
+component reports wiring errors right next to the context.
+
+Example code with an unchecked component:
+
+```
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: RectangleArea,
+    }
+}
+```
+
+Check every delegated component:
+
+```
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
+```
+
+This is reported by `cargo cgp doctor`.
+"#;
+
+const UNUSED_PROVIDER: &str = r#"A provider is implemented but never used in `delegate_components!`.
+
+A provider that is not wired for any context is never type-checked against a
+real context, so a missing requirement in it goes unnoticed. It may also be
+left over from a refactoring.
+
+Example code with an unused provider:
+
+```
+#[cgp_impl(new SquareArea)]
+impl AreaCalculator
+where
+    Self: HasSideLength,
+{
+    fn area(&self) -> f64 {
+        self.side_length() * self.side_length()
+    }
+}
+```
+
+Wire the provider for a context, or remove it. Providers that are meant to be
+wired by downstream crates can be ignored.
+
+This is reported by `cargo cgp doctor`.
+"#;
+
+const UNSIZED_TYPE: &str = r#"A provider is wired with an unsized type for a parameter that must be `Sized`.
+
+Type parameters of a provider are implicitly `Sized`, so a provider such as
+`GetName<Name>` cannot be instantiated with `str`, `[T]` or `dyn Trait`.
+The compiler reports this deep inside the provider wiring, as "the size for
+values of type ... cannot be known at compilation time".
+
+Erroneous code example:
+
+```
+#[cgp_impl(GetName<Name>)]
+impl<Name> NameGetter
+where
+    Self: HasField<Symbol!("name"), Value = Name>,
+{
+    fn name(&self) -> &Name {
+        self.get_field(PhantomData)
+    }
+}
+
+delegate_components! {
+    Person {
+        NameGetterComponent: GetName<str>,
+    }
+}
+```
+
+If the provider only uses the type behind a reference, relax the implicit
+bound in its implementation:
+
+```
+#[cgp_impl(GetName<Name>)]
+impl<Name: ?Sized> NameGetter
+```
+
+Otherwise, wire the provider with a sized type, such as `String` instead of
+`str`, or `Box<dyn Trait>` instead of `dyn Trait`.
+"#;
+
+const DEEP_PROVIDER_STACK: &str = r#"A component is wired to a provider stack deeper than the configured budget.
+
+Higher-order providers wrap other providers, and can be nested freely. When a
+provider deep inside a stack has an unsatisfied requirement, the compiler
+reports it through every wrapping provider, so errors in deep stacks are
+among the hardest to read.
+
+Example code with a deep provider stack (with `max-provider-depth = 3`):
+
+```
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent:
+            Rounded<Cached<ScaledArea<DoubledArea<RectangleArea>>>>,
+    }
+}
+```
+
+Give a name to part of the stack with a provider of its own, so that each
+wiring stays shallow:
+
+```
+#[cgp_impl(new ScaledRectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields + HasScaleFactor,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.height() * self.scale_factor() * 2.0
+    }
+}
+```
+
+This is reported by `cargo cgp doctor` when a depth budget is set, with
+`--max-provider-depth <depth>` or `max-provider-depth` in `cgp.toml`.
+"#;
+
+const UNSATISFIED_USAGE_BOUND: &str = r#"A generic function is called with a context that does not implement its consumer trait bound.
+
+Generic code can require a consumer trait from its context directly. When the
+context does not implement it, the compiler reports the error at the call,
+without the `check_components!` block that usually explains what is missing.
+
+Erroneous code example:
+
+```
+pub fn total_area<Context: CanCalculateArea>(shapes: &[Context]) -> f64 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+pub fn rectangles_area(rectangles: &[Rectangle]) -> f64 {
+    total_area(rectangles) // `Rectangle` does not implement `CanCalculateArea`
+}
+```
+
+If the context does not wire the component yet, wire it:
+
+```
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: RectangleArea,
+    }
+}
+```
+
+If it does, the provider has a missing dependency. Check the component next to
+the context, so that the compiler reports what the provider is missing:
+
+```
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
+```
+"#;
+
+const NOT_A_PROVIDER: &str = r#"A component is delegated to a type that does not implement its provider trait.
+
+A type is a provider of a component only if `#[cgp_impl]` implements the
+provider trait of that component for it. Delegating a component to any other
+type, such as the provider of another component, fails when the component is
+used.
+
+Erroneous code example:
+
+```
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator {
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+delegate_components! {
+    Rectangle {
+        // `RectangleArea` implements `AreaCalculator`, not `PerimeterCalculator`
+        PerimeterCalculatorComponent: RectangleArea,
+    }
+}
+```
+
+Delegate the component to a provider of its provider trait, or check that the
+`#[cgp_impl]` attribute of the provider names the right trait:
+
+```
+delegate_components! {
+    Rectangle {
+        PerimeterCalculatorComponent: RectanglePerimeter,
+    }
+}
+```
+"#;
+
+const FIELD_TYPE_MISMATCH: &str = r#"A getter trait reads a field with another type than the context struct declares.
+
+`#[derive(HasField)]` implements `HasField` for each field with the type of the
+field as its `Value`. A getter declared with `#[cgp_auto_getter]` requires the
+`Value` to be the return type of its method, so a field with the right name
+but another type does not satisfy it.
+
+Erroneous code example:
+
+```
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+    fn height(&self) -> f64;
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    pub height: u32, // the getter reads `height` as `f64`
+}
+```
+
+Change the type of the field to the type that the getter returns, or change the
+getter if the field has the intended type:
+
+```
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    pub height: f64,
+}
+```
+"#;
+
+const REQUIREMENT_OVERFLOW: &str = r#"Evaluating the wiring of a component overflows the trait solver.
+
+A provider that requires the consumer trait of its own component resolves back
+to itself when a context delegates the component to it: the context implements
+the consumer trait through the provider, and the provider requires the consumer
+trait of the context. The compiler reports the cycle as an overflow (E0275),
+which raising the recursion limit does not fix. Provider types that wrap
+themselves without bound (e.g., `ScaledArea<ScaledArea<...>>`) overflow too.
+
+Erroneous code example:
+
+```
+#[cgp_impl(new ScaledArea)]
+impl AreaCalculator
+where
+    Self: HasScaleFactor + CanCalculateArea, // resolves back to `ScaledArea`
+{
+    fn area(&self) -> f64 {
+        self.scale_factor() * self.area()
+    }
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: ScaledArea,
+    }
+}
+```
+
+Take the inner provider as a type parameter and call it through its provider
+trait, so that the wiring names the provider that does the work:
+
+```
+#[cgp_impl(new ScaledArea<InnerCalculator>)]
+impl<InnerCalculator> AreaCalculator
+where
+    Self: HasScaleFactor,
+    InnerCalculator: AreaCalculator<Self>,
+{
+    fn area(&self) -> f64 {
+        self.scale_factor() * InnerCalculator::area(self)
+    }
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: ScaledArea<RectangleArea>,
+    }
+}
+```
+"#;
+
+const AUTO_TRAIT_BOUND: &str = r#"A provider requires the context to be `Send` or `Sync`, and it is not.
+
+Providers that run work on other threads, or async providers whose futures must
+be `Send`, require the context to implement the auto traits `Send` and `Sync`.
+A context is only `Send` or `Sync` if all of its fields are, so a single field
+such as `Rc<T>`, `RefCell<T>` or `Cell<T>` makes the wiring fail. The compiler
+reports the field's type deep in the `IsProviderFor` notes of the component.
+
+Erroneous code example:
+
+```
+#[cgp_impl(new FetchInBackground)]
+impl UserFetcher
+where
+    Self: HasDatabaseUrl + Sync, // the context is shared with a worker thread
+{
+    fn fetch_user(&self, id: u64) -> String {
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| format!("{}/users/{}", self.database_url(), id))
+                .join()
+                .unwrap()
+        })
+    }
+}
+
+#[derive(HasField)]
+pub struct App {
+    pub database_url: String,
+    pub cache: Rc<String>, // `Rc` is neither `Send` nor `Sync`
+}
+```
+
+Replace the field with a thread-safe type (e.g., `Arc` for `Rc`, `Mutex` or
+`RwLock` for `RefCell`):
+
+```
+#[derive(HasField)]
+pub struct App {
+    pub database_url: String,
+    pub cache: Arc<String>,
+}
+```
+
+If the context is never shared between threads, delegate the component to a
+provider that does not require `Send` or `Sync` instead.
+"#;
+
+/// Formats the catalog entry of an error code
+pub fn format_explanation(error_code: CgpErrorCode) -> String {
+    format!(
+        "{}: {}\n\n{}",
+        error_code.code(),
+        error_code.title(),
+        error_code.explanation()
+    )
+}
+
+/// Formats the list of all error codes with their titles
+pub fn format_catalog() -> String {
+    let mut output = String::from("CGP error codes:\n");
+    for error_code in CgpErrorCode::ALL {
+        output.push_str(&format!(
+            "    {}  {}\n",
+            error_code.code(),
+            error_code.title()
+        ));
+    }
+    output.push_str("\nRun `cargo cgp explain <code>` for a detailed explanation.\n");
+    output
+}
+
+/// Points to `cargo cgp explain` for the CGP error codes that were reported, like rustc does
+/// Returns `None` if no diagnostic has a CGP error code
+pub fn format_explain_trailer<'a>(codes: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let mut codes: Vec<&str> = codes
+        .into_iter()
+        .filter(|code| CgpErrorCode::from_code(code).is_some())
+        .collect();
+    codes.sort();
+    codes.dedup();
+
+    match codes.as_slice() {
+        [] => None,
+        [code] => Some(format!(
+            "For more information about this error, try `cargo cgp explain {}`.",
+            code
+        )),
+        [first, ..] => Some(format!(
+            "Some errors have detailed explanations: {}.\nFor more information about an error, try `cargo cgp explain {}`.",
+            codes.join(", "),
+            first
+        )),
+    }
+}
+
+/// Prints the explanation of an error code, or the catalog if no code is given
+pub fn run_explain(mut args: impl Iterator<Item = String>) -> Result<()> {
+    let Some(code) = args.next() else {
+        print!("{}", format_catalog());
+        return Ok(());
+    };
+
+    match CgpErrorCode::from_code(&code) {
+        Some(error_code) => print!("{}", format_explanation(error_code)),
+        None => bail!(
+            "Unknown error code `{}` (run `cargo cgp explain` for the list of codes)",
+            code
+        ),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(
+            CgpErrorCode::from_code("cgp0002"),
+            Some(CgpErrorCode::MissingDelegation)
+        );
+        assert_eq!(CgpErrorCode::from_code("E0277"), None);
+
+        // Codes are unique and every entry has an example
+        for (index, error_code) in CgpErrorCode::ALL.iter().enumerate() {
+            assert_eq!(error_code.code(), format!("CGP{:04}", index + 1));
+            assert!(error_code.explanation().contains("```"));
+        }
+
+        assert!(format_catalog().contains("    CGP0001  missing field in the context\n"));
+        assert!(format_explanation(CgpErrorCode::MissingField).starts_with("CGP0001: "));
+    }
+
+    #[test]
+    fn test_format_explain_trailer() {
+        assert_eq!(format_explain_trailer(["E0277"]), None);
+        assert_eq!(
+            format_explain_trailer(["CGP0001", "E0277", "CGP0001"]).unwrap(),
+            "For more information about this error, try `cargo cgp explain CGP0001`."
+        );
+        assert_eq!(
+            format_explain_trailer(["CGP0004", "CGP0001"]).unwrap(),
+            "Some errors have detailed explanations: CGP0001, CGP0004.\n\
+             For more information about an error, try `cargo cgp explain CGP0001`."
+        );
+    }
+}
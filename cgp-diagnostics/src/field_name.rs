@@ -0,0 +1,178 @@
+/// Module for displaying the names of the fields required through `HasField`
+/// Field names are read from the `Symbol` types in compiler messages. The same name is
+/// shown in the message, in the leaf of the dependency chain and in the fix suggestions,
+/// so it is formatted here once, the same way everywhere.
+use std::fmt;
+
+use crate::cgp_patterns::FieldInfo;
+
+/// Stands for a character that the compiler hides as `_` in a `Symbol` type
+pub const HIDDEN_CHAR: char = '\u{FFFD}';
+
+/// Marks the end of a name that the compiler cut off
+pub const TRUNCATION_MARKER: char = '…';
+
+/// The name of a field as spelled by a `Symbol`, possibly incomplete
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldName<'a> {
+    name: &'a str,
+    is_complete: bool,
+}
+
+impl<'a> FieldName<'a> {
+    pub fn new(name: &'a str, is_complete: bool) -> Self {
+        FieldName { name, is_complete }
+    }
+
+    /// The name of the missing field of an error
+    pub fn of(field_info: &'a FieldInfo) -> Self {
+        Self::new(&field_info.field_name, field_info.is_complete)
+    }
+
+    /// Whether the name is a raw identifier, such as `r#type` for a field named `type`
+    pub fn is_raw_identifier(&self) -> bool {
+        self.name
+            .strip_prefix("r#")
+            .is_some_and(|rest| !rest.is_empty() && rest.chars().all(is_identifier_char))
+    }
+
+    /// Whether the name is shown as written, rather than quoted like a Rust string
+    /// Hidden characters and hyphens (e.g., from `symbol!("max-width")`) do not need quotes
+    fn is_plain(&self) -> bool {
+        self.is_raw_identifier()
+            || self
+                .name
+                .chars()
+                .all(|c| is_identifier_char(c) || c == '-' || c == HIDDEN_CHAR)
+    }
+}
+
+impl fmt::Display for FieldName<'_> {
+    /// Shows identifiers (including raw identifiers) as written, and other names quoted with
+    /// the escapes of a Rust string, with hidden characters as `�` and cut-off names ending in `…`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_plain() {
+            f.write_str(self.name)?;
+        } else {
+            f.write_str("\"")?;
+            for c in self.name.chars() {
+                match c {
+                    HIDDEN_CHAR => write!(f, "{}", c)?,
+                    c => write!(f, "{}", c.escape_debug())?,
+                }
+            }
+            f.write_str("\"")?;
+        }
+
+        if !self.is_complete {
+            write!(f, "{}", TRUNCATION_MARKER)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Recovers the full name of a field that the compiler cut off or partly hid, from the
+/// names that the field could have (e.g., the fields of the context and the getter methods)
+/// Hidden characters match any one character, and a cut-off name matches as a prefix.
+/// Returns None unless exactly one candidate matches.
+pub fn recover_field_name(field_info: &FieldInfo, candidates: &[&str]) -> Option<String> {
+    if field_info.is_complete && !field_info.has_unknown_chars {
+        return None;
+    }
+
+    let pattern: Vec<char> = field_info.field_name.chars().collect();
+    let matches = |candidate: &str| {
+        let chars: Vec<char> = candidate.chars().collect();
+        let length_matches = if field_info.is_complete {
+            chars.len() == pattern.len()
+        } else {
+            chars.len() >= pattern.len()
+        };
+        length_matches
+            && pattern
+                .iter()
+                .zip(&chars)
+                .all(|(expected, actual)| *expected == HIDDEN_CHAR || expected == actual)
+    };
+
+    let mut found: Option<&str> = None;
+    for candidate in candidates.iter().copied().filter(|c| matches(c)) {
+        match found {
+            Some(previous) if previous != candidate => return None,
+            _ => found = Some(candidate),
+        }
+    }
+
+    found.map(str::to_string)
+}
+
+/// Returns true if a character can appear in an identifier after its first character
+pub(crate) fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_field_name() {
+        let display = |name: &str| FieldName::new(name, true).to_string();
+
+        assert_eq!(display("height"), "height");
+        assert_eq!(display("größe"), "größe");
+        assert_eq!(display("max-width"), "max-width");
+        assert_eq!(display("heig\u{FFFD}t"), "heig\u{FFFD}t");
+
+        assert!(FieldName::new("r#type", true).is_raw_identifier());
+        assert!(!FieldName::new("r#", true).is_raw_identifier());
+        assert_eq!(display("r#type"), "r#type");
+
+        assert_eq!(display("a b"), "\"a b\"");
+        assert_eq!(display("it's\n"), "\"it\\'s\\n\"");
+        // Hidden characters read the same in quoted names
+        assert_eq!(display("a.\u{FFFD}"), "\"a.\u{FFFD}\"");
+
+        assert_eq!(FieldName::new("heig", false).to_string(), "heig…");
+        assert_eq!(FieldName::new("a b", false).to_string(), "\"a b\"…");
+    }
+
+    #[test]
+    fn test_recover_field_name() {
+        let field_info = |field_name: &str, is_complete: bool| FieldInfo {
+            field_name: field_name.to_string(),
+            is_complete,
+            has_unknown_chars: field_name.contains(HIDDEN_CHAR),
+            target_type: "Rectangle".to_string(),
+        };
+        let candidates = ["width", "height", "weight", "height", "heights"];
+
+        assert_eq!(
+            recover_field_name(&field_info("heig\u{FFFD}t", true), &candidates).as_deref(),
+            Some("height")
+        );
+        // Both `height` and `weight` match
+        assert_eq!(
+            recover_field_name(&field_info("\u{FFFD}eight", true), &candidates),
+            None
+        );
+        assert_eq!(
+            recover_field_name(&field_info("wid", false), &candidates).as_deref(),
+            Some("width")
+        );
+        assert_eq!(
+            recover_field_name(&field_info("heights", false), &candidates).as_deref(),
+            Some("heights")
+        );
+        assert_eq!(
+            recover_field_name(&field_info("depth", false), &candidates),
+            None
+        );
+        // Names that are known in full are never replaced
+        assert_eq!(
+            recover_field_name(&field_info("wid", true), &candidates),
+            None
+        );
+    }
+}
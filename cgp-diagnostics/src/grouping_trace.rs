@@ -0,0 +1,110 @@
+/// Module for tracing how the diagnostic database groups compiler messages (`--dump-grouping`)
+/// Each incoming CGP diagnostic produces one event recording how it was keyed, whether it
+/// created or merged into an entry, which rule matched it, and what it added to the entry.
+/// The trace is written as newline-delimited JSON, one event per line.
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// What the database did with an incoming diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupingDecision {
+    /// The diagnostic started a new entry
+    Created,
+    /// The diagnostic was merged into an existing entry
+    Merged,
+    /// The diagnostic has no primary span, so it cannot be keyed
+    Skipped,
+    /// The analysis panicked, so the diagnostic is shown as reported by rustc
+    Panicked,
+}
+
+/// The rule that decided where a diagnostic was grouped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupingRule {
+    /// No existing entry matched, so the diagnostic is keyed by its own location
+    NewLocation,
+    /// An entry with the same check trait is within a few lines in the same file
+    /// (another component of the same `check_components!` block)
+    SameCheckBlock,
+    /// An entry within a few lines has the same unsatisfied trait bound
+    SameUnsatisfiedBound,
+    /// A note-only fragment mentions the trait of a nearby entry's bound
+    MentionsBoundTrait,
+}
+
+/// The grouping decision for one incoming diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GroupingEvent {
+    /// Position of the diagnostic among the CGP diagnostics of the run, starting at 0
+    pub index: usize,
+    /// The compiler's message
+    pub message: String,
+    /// The primary location of the diagnostic (file:line:column)
+    pub location: Option<String>,
+    /// The check trait the diagnostic is required by, if any
+    pub check_trait: Option<String>,
+    /// The unsatisfied trait bound of the message, if any
+    pub unsatisfied_bound: Option<String>,
+    pub decision: GroupingDecision,
+    pub rule: Option<GroupingRule>,
+    /// The key of the entry that was created or merged into (file:line:column)
+    pub entry: Option<String>,
+    /// Facts that the diagnostic added to the entry (e.g., "component `AreaCalculatorComponent`")
+    pub added: Vec<String>,
+}
+
+/// The grouping events of a run, in the order the diagnostics arrived
+#[derive(Debug, Clone, Default)]
+pub struct GroupingTrace {
+    pub events: Vec<GroupingEvent>,
+}
+
+impl GroupingTrace {
+    /// Renders the events as newline-delimited JSON
+    pub fn render_json_lines(&self) -> String {
+        self.events
+            .iter()
+            .filter_map(|event| serde_json::to_string(event).ok())
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    /// Writes the events to a file as newline-delimited JSON
+    pub fn write(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.render_json_lines())
+            .with_context(|| format!("Failed to write the grouping trace to {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_json_lines() {
+        let trace = GroupingTrace {
+            events: vec![GroupingEvent {
+                index: 0,
+                message: "the trait bound `A: B` is not satisfied".to_string(),
+                location: Some("src/lib.rs:41:9".to_string()),
+                check_trait: None,
+                unsatisfied_bound: Some("A: B".to_string()),
+                decision: GroupingDecision::Created,
+                rule: Some(GroupingRule::NewLocation),
+                entry: Some("src/lib.rs:41:9".to_string()),
+                added: vec!["component `AComponent`".to_string()],
+            }],
+        };
+
+        assert_eq!(
+            trace.render_json_lines(),
+            r#"{"index":0,"message":"the trait bound `A: B` is not satisfied","location":"src/lib.rs:41:9","check_trait":null,"unsatisfied_bound":"A: B","decision":"created","rule":"new-location","entry":"src/lib.rs:41:9","added":["component `AComponent`"]}"#
+                .to_string()
+                + "\n"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+/// Module for rendering improved CGP diagnostics as machine-readable JSON
+/// The output follows cargo's `--message-format=json` convention of one JSON object per
+/// line with a `reason` field, so it can be mixed with cargo's own messages
+use serde::Serialize;
+
+use crate::cgp_diagnostic::{CgpDiagnostic, Severity, SpanLocation, WiringLocation};
+use crate::error_formatting::DependencyNode;
+
+/// An improved CGP diagnostic in the `cgp-json` output format
+#[derive(Debug, Serialize)]
+pub struct CgpJsonMessage<'a> {
+    /// Always "cgp-diagnostic", to distinguish from cargo's own messages
+    pub reason: &'static str,
+    /// "error", "warning" or "advice"
+    pub severity: &'static str,
+    /// The error code (e.g., "CGP0001"), see `cargo cgp explain`
+    pub code: Option<&'a str>,
+    /// The improved error message
+    pub message: &'a str,
+    /// Help text with suggestions, as shown in the human output
+    pub help: Option<&'a str>,
+    /// Highlighted source locations as file, line and column, in canonical order
+    /// The source files are never read for machine-readable output
+    pub spans: Vec<JsonSpan<'a>>,
+    /// The dependency chain behind the error
+    pub dependency_tree: Option<&'a DependencyNode>,
+    /// The wiring constructs involved in the error (check block, delegation entry, provider
+    /// implementation, getter trait and context struct), when found in the workspace sources
+    pub wiring: &'a [WiringLocation],
+}
+
+/// A highlighted source location with the ID that the help text uses to refer to it
+#[derive(Debug, Serialize)]
+pub struct JsonSpan<'a> {
+    /// The 1-based label ID, as in "see label [2]"
+    pub id: usize,
+    #[serde(flatten)]
+    pub span: &'a SpanLocation,
+}
+
+impl<'a> CgpJsonMessage<'a> {
+    pub fn from_diagnostic(diagnostic: &'a CgpDiagnostic) -> Self {
+        let severity = match diagnostic.severity {
+            None | Some(Severity::Error) => "error",
+            Some(Severity::Warning) => "warning",
+            Some(Severity::Advice) => "advice",
+        };
+
+        CgpJsonMessage {
+            reason: "cgp-diagnostic",
+            severity,
+            code: diagnostic.code.as_deref(),
+            message: &diagnostic.message,
+            help: diagnostic.help.as_deref(),
+            spans: diagnostic
+                .spans
+                .iter()
+                .enumerate()
+                .map(|(index, span)| JsonSpan {
+                    id: index + 1,
+                    span,
+                })
+                .collect(),
+            dependency_tree: diagnostic.dependency_tree.as_ref(),
+            wiring: &diagnostic.wiring,
+        }
+    }
+}
+
+/// Renders a CGP diagnostic as a single line of JSON
+pub fn render_diagnostic_json(diagnostic: &CgpDiagnostic) -> String {
+    serde_json::to_string(&CgpJsonMessage::from_diagnostic(diagnostic)).unwrap_or_else(|_| {
+        serde_json::json!({
+            "reason": "cgp-diagnostic",
+            "message": diagnostic.message,
+        })
+        .to_string()
+    })
+}
+
+/// Renders a CGP diagnostic as a cargo `compiler-message` with the improved text as its
+/// `rendered` field (the `json` output format)
+/// Diagnostics that were not built from a compiler message are rendered as
+/// `cgp-diagnostic` messages instead
+pub fn render_compiler_message_json(diagnostic: &CgpDiagnostic, rendered: &str) -> String {
+    let Some(origin) = &diagnostic.origin else {
+        return render_diagnostic_json(diagnostic);
+    };
+
+    let mut message = match serde_json::to_value(&origin.diagnostic) {
+        Ok(message) => message,
+        Err(_) => return render_diagnostic_json(diagnostic),
+    };
+    message["rendered"] = rendered.into();
+
+    serde_json::json!({
+        "reason": "compiler-message",
+        "package_id": origin.package_id,
+        "target": origin.target,
+        "message": message,
+    })
+    .to_string()
+}
+
+/// Summary emitted when cargo failed without any CGP errors
+/// This tells consumers that cargo-cgp ran and classified all errors as unrelated to CGP
+#[derive(Debug, Serialize)]
+pub struct CgpExitSummary {
+    /// Always "cgp-exit-summary"
+    pub reason: &'static str,
+    /// The number of improved CGP errors, always zero when the summary is emitted
+    pub cgp_errors: usize,
+    /// The number of other compiler errors reported by cargo
+    pub other_errors: usize,
+}
+
+/// Renders the exit summary for a failed build without CGP errors as a single line of JSON
+pub fn render_exit_summary_json(other_errors: usize) -> String {
+    let summary = CgpExitSummary {
+        reason: "cgp-exit-summary",
+        cgp_errors: 0,
+        other_errors,
+    };
+    serde_json::to_string(&summary).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::load_diagnostic_database;
+
+    #[test]
+    fn test_render_diagnostic_json() {
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let diagnostics = load_diagnostic_database(json_path).render_cgp_diagnostics();
+        let rendered = render_diagnostic_json(&diagnostics[0]);
+
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(value["reason"], "cgp-diagnostic");
+        assert_eq!(value["severity"], "error");
+        assert_eq!(value["spans"][0]["file"], "examples/src/base_area.rs");
+        assert_eq!(value["spans"][0]["line_start"], 41);
+        assert_eq!(value["spans"][0]["column_start"], 9);
+        assert_eq!(value["spans"][0]["id"], 1);
+
+        let tree: DependencyNode =
+            serde_json::from_value(value["dependency_tree"].clone()).unwrap();
+        assert_eq!(Some(&tree), diagnostics[0].dependency_tree.as_ref());
+        assert_eq!(value["dependency_tree"]["kind"], "check");
+    }
+
+    #[test]
+    fn test_wiring_locations_json() {
+        use crate::test_utils::{examples_dir, load_example_with_index};
+
+        let mut db = load_diagnostic_database(examples_dir().join("base_area.json"));
+
+        // Without the workspace sources, nothing is located
+        let value: serde_json::Value =
+            serde_json::from_str(&render_diagnostic_json(&db.render_cgp_diagnostics()[0])).unwrap();
+        assert_eq!(value["wiring"], serde_json::json!([]));
+
+        let (mut db, _) = load_example_with_index("base_area");
+        let value: serde_json::Value =
+            serde_json::from_str(&render_diagnostic_json(&db.render_cgp_diagnostics()[0])).unwrap();
+
+        let wiring: Vec<String> = value["wiring"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|location| {
+                format!(
+                    "{} {} {}:{}:{}",
+                    location["kind"].as_str().unwrap(),
+                    location["name"].as_str().unwrap(),
+                    location["file"].as_str().unwrap(),
+                    location["line"],
+                    location["column"]
+                )
+            })
+            .collect();
+        assert_eq!(
+            wiring,
+            vec![
+                "check-block CanUseRectangle examples/src/base_area.rs:40:5",
+                "delegation-entry AreaCalculatorComponent examples/src/base_area.rs:34:9",
+                "provider-impl RectangleArea examples/src/base_area.rs:15:1",
+                "getter-trait HasRectangleFields examples/src/base_area.rs:8:1",
+                "context-struct Rectangle examples/src/base_area.rs:26:12",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_compiler_message_json() {
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let diagnostics = load_diagnostic_database(json_path).render_cgp_diagnostics();
+        let rendered = render_compiler_message_json(&diagnostics[0], "improved error\n");
+
+        // The message parses as cargo's own, with only the rendering replaced
+        let message: cargo_metadata::Message = serde_json::from_str(&rendered).unwrap();
+        let cargo_metadata::Message::CompilerMessage(message) = message else {
+            panic!("expected a compiler message: {}", rendered);
+        };
+        assert_eq!(
+            message.message.rendered.as_deref(),
+            Some("improved error\n")
+        );
+        assert_eq!(message.message.spans[0].line_start, 41);
+        assert_eq!(message.target.name, "cgp_error_messages_example");
+
+        // Diagnostics not built from a compiler message keep the cgp-json format
+        let warning = CgpDiagnostic::new("shadowed check trait".to_string(), Vec::new());
+        assert!(render_compiler_message_json(&warning, "").contains("\"cgp-diagnostic\""));
+    }
+}
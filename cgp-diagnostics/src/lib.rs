@@ -0,0 +1,23 @@
+pub mod cgp_diagnostic;
+pub mod cgp_patterns;
+pub mod diagnostic_db;
+pub mod doc_tests;
+pub mod error_formatting;
+pub mod explain;
+pub mod field_name;
+pub mod grouping_trace;
+pub mod json_output;
+pub mod long_types;
+pub mod macro_warnings;
+pub mod name_resolution;
+pub mod package_selection;
+pub mod panic_guard;
+pub mod plain_render;
+pub mod raw_filter;
+pub mod root_cause;
+pub mod source_snippet;
+pub mod symbol_cache;
+pub mod symbol_display;
+pub mod test_utils;
+pub mod type_expr;
+pub mod workspace_scan;
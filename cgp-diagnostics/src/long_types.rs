@@ -0,0 +1,241 @@
+/// Module for the files in which rustc writes type names too long for a diagnostic
+/// rustc shortens long types with `...` and notes that "the full name for the type has
+/// been written to '<file>'". Several diagnostics often point to the same file, so each
+/// file is read once per run. The files live in the target directory and may have been
+/// pruned since the messages were captured (e.g., when replaying with `--from-json`),
+/// in which case the shortened types are used as before.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use cargo_metadata::diagnostic::Diagnostic;
+
+use crate::cgp_patterns::{FieldInfo, extract_field_name_from_symbol, extract_symbol_type};
+
+/// The notes in which rustc names the file with the full type name
+const LONG_TYPE_NOTES: [&str; 2] = [
+    "the full name for the type has been written to '",
+    "the full type name has been written to '",
+];
+
+/// A long-type file referenced by diagnostics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LongTypeFile {
+    /// The full type names in the file, or None if it could not be read
+    pub type_names: Option<Vec<String>>,
+    /// Number of times the file was looked up
+    pub uses: usize,
+}
+
+/// Per-run cache of the long-type files referenced by diagnostics
+#[derive(Debug, Clone, Default)]
+pub struct LongTypeCache {
+    /// Ordered by path, so that debug output is stable
+    files: BTreeMap<PathBuf, LongTypeFile>,
+}
+
+impl LongTypeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the full type names written to a file, reading it on first use
+    /// Returns None if the file is missing or unreadable
+    pub fn load(&mut self, path: &Path) -> Option<&[String]> {
+        let file = self
+            .files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| LongTypeFile {
+                type_names: read_type_names(path),
+                uses: 0,
+            });
+        file.uses += 1;
+        file.type_names.as_deref()
+    }
+
+    /// Completes a field name that rustc truncated, using the full symbol types in the
+    /// long-type files that the diagnostic references
+    pub fn complete_field_info(&mut self, diagnostic: &Diagnostic, field_info: &mut FieldInfo) {
+        if field_info.is_complete {
+            return;
+        }
+
+        for path in extract_long_type_paths(diagnostic) {
+            let Some(type_names) = self.load(&path) else {
+                continue;
+            };
+
+            let full_name = type_names
+                .iter()
+                .flat_map(|type_name| symbol_types(type_name))
+                .filter_map(extract_field_name_from_symbol)
+                .find(|(field_name, is_complete, has_unknown_chars)| {
+                    *is_complete
+                        && !has_unknown_chars
+                        && field_name.starts_with(&field_info.field_name)
+                });
+
+            if let Some((field_name, _, _)) = full_name {
+                field_info.field_name = field_name;
+                field_info.is_complete = true;
+                return;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Renders one line per referenced file, for debug output
+    pub fn render_debug(&self) -> String {
+        let mut output = String::new();
+
+        for (path, file) in &self.files {
+            match &file.type_names {
+                Some(type_names) => output.push_str(&format!(
+                    "debug: long-type file {} has {} type names (used {} times)\n",
+                    path.display(),
+                    type_names.len(),
+                    file.uses
+                )),
+                None => output.push_str(&format!(
+                    "debug: long-type file {} is missing (used {} times)\n",
+                    path.display(),
+                    file.uses
+                )),
+            }
+        }
+
+        output
+    }
+}
+
+/// Extracts the paths of the long-type files named in the notes of a diagnostic
+pub fn extract_long_type_paths(diagnostic: &Diagnostic) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    for child in &diagnostic.children {
+        for note in LONG_TYPE_NOTES {
+            let Some(start) = child.message.find(note) else {
+                continue;
+            };
+            let rest = &child.message[start + note.len()..];
+            if let Some(end) = rest.find('\'') {
+                let path = PathBuf::from(&rest[..end]);
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+    }
+
+    paths
+}
+
+/// Reads the type names of a long-type file, one per non-empty line
+fn read_type_names(path: &Path) -> Option<Vec<String>> {
+    let content = std::fs::read_to_string(path).ok()?;
+    Some(
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Returns every `Symbol<...>` type in a type name
+fn symbol_types(type_name: &str) -> Vec<&str> {
+    let mut symbols = Vec::new();
+    let mut rest = type_name;
+
+    while let Some(symbol) = extract_symbol_type(rest) {
+        symbols.push(symbol);
+        let start = rest.find(symbol).unwrap_or(0);
+        rest = &rest[start + symbol.len()..];
+    }
+
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a diagnostic with a note for each message
+    fn note_diagnostic(notes: &[&str]) -> Diagnostic {
+        let children: Vec<_> = notes
+            .iter()
+            .map(|note| {
+                serde_json::json!({
+                    "message": note,
+                    "code": null,
+                    "level": "note",
+                    "spans": [],
+                    "children": [],
+                    "rendered": null
+                })
+            })
+            .collect();
+
+        serde_json::from_value(serde_json::json!({
+            "message": "the trait bound is not satisfied",
+            "code": null,
+            "level": "error",
+            "spans": [],
+            "children": children,
+            "rendered": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_complete_field_info() {
+        let dir = std::env::temp_dir().join(format!("cargo-cgp-long-types-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("example.long-type-1.txt");
+        std::fs::write(
+            &path,
+            "HasField<Symbol<5, Chars<'w', Chars<'i', Chars<'d', Chars<'t', Chars<'h', Nil>>>>>>>\n",
+        )
+        .unwrap();
+        let missing = dir.join("example.long-type-2.txt");
+
+        let diagnostic = note_diagnostic(&[
+            &format!(
+                "the full name for the type has been written to '{}'",
+                missing.display()
+            ),
+            &format!(
+                "the full name for the type has been written to '{}'",
+                path.display()
+            ),
+        ]);
+        assert_eq!(extract_long_type_paths(&diagnostic), vec![missing, path]);
+
+        let mut field_info = FieldInfo {
+            field_name: "wid".to_string(),
+            is_complete: false,
+            has_unknown_chars: false,
+            target_type: "Rectangle".to_string(),
+        };
+        let mut cache = LongTypeCache::new();
+        cache.complete_field_info(&diagnostic, &mut field_info);
+        cache.complete_field_info(&diagnostic, &mut field_info);
+
+        assert_eq!(field_info.field_name, "width");
+        assert!(field_info.is_complete);
+        assert_eq!(cache.len(), 2);
+
+        let debug = cache.render_debug();
+        assert!(debug.contains("example.long-type-1.txt has 1 type names (used 1 times)"));
+        assert!(debug.contains("example.long-type-2.txt is missing (used 1 times)"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+/// Module for warnings in code generated by the cgp macros
+/// The generated code sometimes triggers warnings (e.g., unused imports or dead code in
+/// generated impls) that rustc attributes to the user's files. The expansion data of
+/// their spans tells them apart from warnings about code the user wrote, so that they
+/// can be tagged, grouped into one summary, or hidden (`--macro-warnings <policy>`).
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::bail;
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel, DiagnosticSpan};
+
+/// Names of the cgp macros, without the attribute or macro call syntax
+const CGP_MACROS: [&str; 13] = [
+    "cgp_component",
+    "cgp_impl",
+    "cgp_provider",
+    "cgp_new_provider",
+    "cgp_auto_getter",
+    "cgp_getter",
+    "cgp_context",
+    "cgp_type",
+    "cgp_preset",
+    "delegate_components",
+    "check_components",
+    "HasField",
+    "HasFields",
+];
+
+/// How warnings in code generated by the cgp macros are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MacroWarningPolicy {
+    /// Show each warning as reported by rustc, followed by a note naming the macro
+    #[default]
+    Tag,
+    /// Show one summary of all such warnings, grouped by macro, after the errors
+    Group,
+    /// Do not show them
+    Hide,
+}
+
+impl FromStr for MacroWarningPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "tag" => Ok(MacroWarningPolicy::Tag),
+            "group" => Ok(MacroWarningPolicy::Group),
+            "hide" => Ok(MacroWarningPolicy::Hide),
+            other => bail!(
+                "Unknown macro warning policy `{}` (expected `tag`, `group` or `hide`)",
+                other
+            ),
+        }
+    }
+}
+
+/// Returns the cgp macro that generated the code a warning is about, as rustc names it
+/// (e.g., "delegate_components!" or "#[derive(HasField)]")
+pub fn macro_origin(diagnostic: &Diagnostic) -> Option<String> {
+    if !matches!(diagnostic.level, DiagnosticLevel::Warning) {
+        return None;
+    }
+
+    let span = diagnostic.spans.iter().find(|span| span.is_primary)?;
+    expansion_chain(span)
+        .find(|macro_name| CGP_MACROS.contains(&bare_macro_name(macro_name)))
+        .map(str::to_string)
+}
+
+/// Returns the macros that a span was expanded from, innermost first
+fn expansion_chain(span: &DiagnosticSpan) -> impl Iterator<Item = &str> {
+    std::iter::successors(span.expansion.as_deref(), |expansion| {
+        expansion.span.expansion.as_deref()
+    })
+    .map(|expansion| expansion.macro_decl_name.as_str())
+}
+
+/// Strips the attribute, derive and macro call syntax from a macro name
+fn bare_macro_name(macro_name: &str) -> &str {
+    let name = macro_name
+        .trim_start_matches("#[")
+        .trim_end_matches(']')
+        .trim_end_matches('!');
+    let name = name
+        .strip_prefix("derive(")
+        .and_then(|name| name.strip_suffix(')'))
+        .unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Appends a note naming the macro to rustc's rendering of a warning
+pub fn tag_rendered(rendered: &str, macro_name: &str) -> String {
+    format!(
+        "{}\nnote: this warning is about code generated by `{}` (see `--macro-warnings`)\n",
+        rendered.trim_end(),
+        macro_name
+    )
+}
+
+/// The warnings in generated code collected for one summary (`--macro-warnings group`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MacroWarnings {
+    /// One line per warning, by the macro that generated the code
+    by_macro: BTreeMap<String, Vec<String>>,
+}
+
+impl MacroWarnings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, diagnostic: &Diagnostic, macro_name: String) {
+        let line = match diagnostic.spans.iter().find(|span| span.is_primary) {
+            Some(span) => format!(
+                "{}:{}:{}: {}",
+                span.file_name, span.line_start, span.column_start, diagnostic.message
+            ),
+            None => diagnostic.message.clone(),
+        };
+
+        let lines = self.by_macro.entry(macro_name).or_default();
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_macro.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_macro.is_empty()
+    }
+
+    /// Renders the summary, or None if no warnings were collected
+    pub fn render(&self) -> Option<String> {
+        let mut lines = match self.len() {
+            0 => return None,
+            1 => vec!["warning: 1 warning in code generated by cgp macros:".to_string()],
+            n => vec![format!(
+                "warning: {} warnings in code generated by cgp macros:",
+                n
+            )],
+        };
+
+        for (macro_name, warnings) in &self.by_macro {
+            lines.push(format!("  {}", macro_name));
+            lines.extend(warnings.iter().map(|warning| format!("    {}", warning)));
+        }
+
+        Some(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(message: &str, macros: &[&str]) -> Diagnostic {
+        let mut expansion = serde_json::Value::Null;
+        for macro_name in macros.iter().rev() {
+            expansion = serde_json::json!({
+                "span": span(expansion),
+                "macro_decl_name": macro_name,
+                "def_site_span": null
+            });
+        }
+
+        serde_json::from_value(serde_json::json!({
+            "message": message,
+            "code": null,
+            "level": "warning",
+            "spans": [span(expansion)],
+            "children": [],
+            "rendered": null
+        }))
+        .unwrap()
+    }
+
+    fn span(expansion: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "file_name": "src/lib.rs",
+            "byte_start": 0,
+            "byte_end": 1,
+            "line_start": 12,
+            "line_end": 12,
+            "column_start": 5,
+            "column_end": 9,
+            "is_primary": true,
+            "text": [],
+            "label": null,
+            "suggested_replacement": null,
+            "suggestion_applicability": null,
+            "expansion": expansion
+        })
+    }
+
+    #[test]
+    fn test_macro_origin() {
+        assert_eq!(bare_macro_name("#[derive(HasField)]"), "HasField");
+        assert_eq!(
+            bare_macro_name("cgp::prelude::delegate_components!"),
+            "delegate_components"
+        );
+
+        let generated = warning("unused import: `Foo`", &["delegate_components!"]);
+        assert_eq!(
+            macro_origin(&generated).as_deref(),
+            Some("delegate_components!")
+        );
+
+        // Found through the macros that a user macro expands to
+        let nested = warning("dead code", &["my_macro!", "#[cgp_impl]"]);
+        assert_eq!(macro_origin(&nested).as_deref(), Some("#[cgp_impl]"));
+
+        assert_eq!(macro_origin(&warning("unused variable: `x`", &[])), None);
+        assert_eq!(macro_origin(&warning("dead code", &["vec!"])), None);
+
+        let mut warnings = MacroWarnings::new();
+        warnings.add(&generated, "delegate_components!".to_string());
+        warnings.add(&generated, "delegate_components!".to_string());
+        assert_eq!(
+            warnings.render().unwrap(),
+            "warning: 1 warning in code generated by cgp macros:\n  delegate_components!\n    src/lib.rs:12:5: unused import: `Foo`"
+        );
+        assert_eq!(MacroWarnings::new().render(), None);
+    }
+}
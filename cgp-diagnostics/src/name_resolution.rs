@@ -0,0 +1,907 @@
+/// Module for resolving CGP names (components, provider traits, consumer traits)
+/// Names are looked up in the workspace component index first. In heuristic mode,
+/// names missing from the index are derived from CGP naming conventions instead.
+/// In strict mode, nothing is derived and missing names are reported as unresolved.
+use serde::Deserialize;
+
+use crate::cgp_patterns::{
+    ComponentInfo, PathStyle, strip_module_prefixes, strip_module_prefixes_in,
+};
+use crate::workspace_scan::{
+    ComponentDecl, Delegation, FieldDecl, GetterDecl, SourceLocation, WorkspaceScan, base_type_name,
+};
+
+/// How names that are not found in the component index are resolved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResolutionMode {
+    /// Fall back to naming heuristics (e.g., `FooComponent` -> `Foo`)
+    #[default]
+    Heuristic,
+    /// Only use facts from the component index and report everything else as unresolved
+    Strict,
+}
+
+/// The naming conventions used to derive names in heuristic mode
+/// (the `[naming]` table of `cgp.toml`)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct NamingConventions {
+    /// Prefix of consumer traits, e.g. `Can` in `CanCalculateArea`
+    pub consumer_prefix: String,
+    /// Suffix of component names, e.g. `Component` in `AreaCalculatorComponent`
+    pub component_suffix: String,
+}
+
+impl Default for NamingConventions {
+    fn default() -> Self {
+        NamingConventions {
+            consumer_prefix: "Can".to_string(),
+            component_suffix: "Component".to_string(),
+        }
+    }
+}
+
+impl NamingConventions {
+    /// Derives the provider trait of a component by removing the component suffix
+    /// Example: "AreaCalculatorComponent" -> Some("AreaCalculator")
+    pub fn provider_trait(&self, component_name: &str) -> Option<String> {
+        let pos = component_name.rfind(&self.component_suffix)?;
+        let before = &component_name[..pos];
+        (!before.is_empty()).then(|| before.to_string())
+    }
+
+    /// Derives the component of a consumer trait by replacing the consumer prefix
+    /// with the component suffix
+    /// Example: "CanCalculateArea" -> Some("CalculateAreaComponent")
+    pub fn component_for_consumer_trait(&self, consumer_trait: &str) -> Option<String> {
+        self.consumer_action(consumer_trait)
+            .map(|action| format!("{}{}", action, self.component_suffix))
+    }
+
+    /// Returns the action part of a consumer trait (e.g., "CalculateArea" for "CanCalculateArea")
+    fn consumer_action<'a>(&self, consumer_trait: &'a str) -> Option<&'a str> {
+        consumer_trait.strip_prefix(self.consumer_prefix.as_str())
+    }
+}
+
+/// The struct that supplies the fields of a context through `Deref`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerefTarget {
+    pub type_name: String,
+    /// Whether the struct has `#[derive(HasField)]`
+    pub derives_has_field: bool,
+    /// Location of the struct name
+    pub location: SourceLocation,
+}
+
+/// Where a provider struct generated by a CGP macro is declared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderDeclaration {
+    /// The generated provider struct (e.g., "RectangleArea")
+    pub provider_name: String,
+    /// The declaring attribute (e.g., "`#[cgp_impl(new RectangleArea)]`")
+    pub declaration: String,
+    /// Location of the declaring attribute or struct
+    pub location: SourceLocation,
+}
+
+/// A provider implementation from the workspace index, with what it requires from a context
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedProvider {
+    /// The provider type as written (e.g., "ScaledArea<InnerCalculator>")
+    pub provider_type: String,
+    /// The provider trait it implements (e.g., "AreaCalculator")
+    pub provider_trait: String,
+    /// Getter traits it requires
+    pub required_getters: Vec<String>,
+    /// Fields read by the getter traits it requires
+    pub required_fields: Vec<String>,
+    /// Components of the consumer traits it requires
+    pub required_components: Vec<String>,
+    /// Location of the provider attribute
+    pub location: SourceLocation,
+}
+
+/// Resolves CGP names from the workspace component index
+#[derive(Debug, Clone, Default)]
+pub struct NameResolver {
+    /// Components declared in the workspace sources
+    components: Vec<ComponentDecl>,
+    /// Doc comment summaries of provider implementations, by provider name
+    provider_docs: Vec<(String, String)>,
+    /// Provider implementations declared with `#[cgp_impl]`
+    providers: Vec<IndexedProvider>,
+    /// Provider structs generated by `#[cgp_impl(new ...)]`, `#[cgp_new_provider]` or
+    /// `#[cgp_context]`, which users cannot find by searching for the struct
+    provider_declarations: Vec<ProviderDeclaration>,
+    /// Crates defining the context structs, by context type
+    context_crates: Vec<(String, String)>,
+    /// Names of the workspace crates whose sources were scanned
+    workspace_crates: Vec<String>,
+    /// Components of the getter traits declared with `#[cgp_getter]`, by getter trait
+    getter_components: Vec<(String, String)>,
+    /// Entries of the `delegate_components!` blocks, with the context and component names
+    /// without paths or generic parameters
+    delegations: Vec<Delegation>,
+    /// Location of the last entry of the `delegate_components!` block, by context type
+    delegation_blocks: Vec<(String, SourceLocation)>,
+    /// Structs supplying the fields of contexts that implement `Deref`, by context type
+    deref_targets: Vec<(String, DerefTarget)>,
+    /// Fields of the structs with named fields, by struct name
+    struct_fields: Vec<(String, Vec<FieldDecl>)>,
+    /// Location of the context structs, by struct name
+    struct_locations: Vec<(String, SourceLocation)>,
+    /// Location of the `check_components!` blocks, by check trait
+    check_blocks: Vec<(String, SourceLocation)>,
+    /// Getter traits declared with `#[cgp_auto_getter]` or `#[cgp_getter]`
+    getters: Vec<GetterDecl>,
+    /// Fields read by the getter traits, one per getter method
+    getter_fields: Vec<FieldDecl>,
+    mode: ResolutionMode,
+    naming: NamingConventions,
+    paths: PathStyle,
+}
+
+impl NameResolver {
+    pub fn new(components: Vec<ComponentDecl>, mode: ResolutionMode) -> Self {
+        NameResolver {
+            components,
+            provider_docs: Vec::new(),
+            providers: Vec::new(),
+            provider_declarations: Vec::new(),
+            context_crates: Vec::new(),
+            workspace_crates: Vec::new(),
+            getter_components: Vec::new(),
+            delegations: Vec::new(),
+            delegation_blocks: Vec::new(),
+            deref_targets: Vec::new(),
+            struct_fields: Vec::new(),
+            struct_locations: Vec::new(),
+            check_blocks: Vec::new(),
+            getters: Vec::new(),
+            getter_fields: Vec::new(),
+            mode,
+            naming: NamingConventions::default(),
+            paths: PathStyle::Short,
+        }
+    }
+
+    /// Uses custom naming conventions to derive names in heuristic mode
+    pub fn with_naming(mut self, naming: NamingConventions) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Writes the paths of the types in the rendered errors in the given style
+    pub fn with_path_style(mut self, paths: PathStyle) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// The style of the paths of the types in the rendered errors
+    pub fn path_style(&self) -> PathStyle {
+        self.paths
+    }
+
+    /// Removes the module prefixes from a text, unless the paths are qualified
+    pub fn strip_module_prefixes(&self, text: &str) -> String {
+        strip_module_prefixes_in(text, self.paths)
+    }
+
+    /// Builds a resolver from the components and providers found in the workspace sources
+    pub fn from_scan(scan: &WorkspaceScan, mode: ResolutionMode) -> Self {
+        let mut resolver = Self::new(scan.component_decls.clone(), mode);
+
+        for provider_impl in &scan.provider_impls {
+            let mut required_getters = Vec::new();
+            let mut required_fields = Vec::new();
+            let mut required_components = Vec::new();
+            for bound in &provider_impl.requirements {
+                if let Some(getter) = scan.getter_decl(bound) {
+                    required_getters.push(getter.trait_name.clone());
+                    required_fields.extend(getter.fields.iter().map(|field| field.name.clone()));
+                } else if let Some(decl) = scan.component_of_consumer_trait(bound) {
+                    required_components.push(decl.component_name.clone());
+                }
+            }
+            resolver.providers.push(IndexedProvider {
+                provider_type: provider_impl.provider_type.clone(),
+                provider_trait: base_type_name(&provider_impl.provider_trait).to_string(),
+                required_getters,
+                required_fields,
+                required_components,
+                location: provider_impl.location.clone(),
+            });
+            if let Some(doc) = &provider_impl.doc {
+                resolver
+                    .provider_docs
+                    .push((provider_impl.provider_name().to_string(), doc.clone()));
+            }
+            if provider_impl.generates_struct {
+                resolver.provider_declarations.push(ProviderDeclaration {
+                    provider_name: provider_impl.provider_name().to_string(),
+                    declaration: format!("`{}`", provider_impl.attribute),
+                    location: provider_impl.location.clone(),
+                });
+            }
+        }
+
+        for context in &scan.context_structs {
+            if let (Some(provider_name), Some(attribute)) =
+                (context.context_provider(), &context.context_attribute)
+            {
+                resolver.provider_declarations.push(ProviderDeclaration {
+                    provider_name,
+                    declaration: format!("`{}` on `{}`", attribute, context.type_name),
+                    location: context.location.clone(),
+                });
+            }
+            resolver
+                .context_crates
+                .push((context.type_name.clone(), context.crate_name.clone()));
+            resolver
+                .struct_fields
+                .push((context.type_name.clone(), context.fields.clone()));
+            resolver
+                .struct_locations
+                .push((context.type_name.clone(), context.location.clone()));
+        }
+
+        for block in &scan.check_blocks {
+            resolver.check_blocks.push((
+                block.trait_name.clone(),
+                SourceLocation {
+                    file: block.file.clone(),
+                    display_path: block.display_path.clone(),
+                    line: block.line,
+                    column: block.column,
+                },
+            ));
+        }
+        resolver.getters = scan.getter_decls.clone();
+
+        for field in scan.getter_decls.iter().flat_map(|getter| &getter.fields) {
+            if !resolver.getter_fields.contains(field) {
+                resolver.getter_fields.push(field.clone());
+            }
+        }
+
+        for getter in scan.getter_decls.iter().filter(|getter| !getter.is_auto) {
+            if let Some(decl) = scan.component_of_consumer_trait(&getter.trait_name) {
+                resolver
+                    .getter_components
+                    .push((getter.trait_name.clone(), decl.component_name.clone()));
+            }
+        }
+
+        for delegation in &scan.delegations {
+            let context_type = base_type_name(&delegation.context_type).to_string();
+            resolver.delegations.push(Delegation {
+                context_type: context_type.clone(),
+                component: base_type_name(&delegation.component).to_string(),
+                ..delegation.clone()
+            });
+
+            // Delegations are in source order, so the last one of a context is kept
+            resolver
+                .delegation_blocks
+                .retain(|(context, _)| *context != context_type);
+            resolver
+                .delegation_blocks
+                .push((context_type, delegation.location.clone()));
+        }
+
+        for deref_impl in &scan.deref_impls {
+            if let Some(target) = scan.deref_field_struct(&deref_impl.context_type) {
+                resolver.deref_targets.push((
+                    base_type_name(&deref_impl.context_type).to_string(),
+                    DerefTarget {
+                        type_name: target.type_name.clone(),
+                        derives_has_field: target.derives_has_field,
+                        location: target.location.clone(),
+                    },
+                ));
+            }
+        }
+
+        let crate_names = scan
+            .check_blocks
+            .iter()
+            .map(|block| &block.crate_name)
+            .chain(
+                scan.context_structs
+                    .iter()
+                    .map(|context| &context.crate_name),
+            );
+        for crate_name in crate_names {
+            if !resolver.workspace_crates.contains(crate_name) {
+                resolver.workspace_crates.push(crate_name.clone());
+            }
+        }
+
+        resolver
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.mode == ResolutionMode::Strict
+    }
+
+    /// Looks up the index entry of a component, ignoring module prefixes
+    fn component_decl(&self, component_name: &str) -> Option<&ComponentDecl> {
+        let component_name = last_path_segment(component_name);
+        self.components
+            .iter()
+            .find(|decl| decl.component_name == component_name)
+    }
+
+    /// Returns the provider trait of a component
+    pub fn provider_trait(&self, component_info: &ComponentInfo) -> Option<String> {
+        if let Some(decl) = self.component_decl(&component_info.component_type) {
+            return Some(decl.provider_trait.clone());
+        }
+
+        if self.is_strict() {
+            return None;
+        }
+
+        self.naming.provider_trait(&component_info.component_type)
+    }
+
+    /// Returns the provider trait of a component given only its name
+    pub fn provider_trait_for_component(&self, component_name: &str) -> Option<String> {
+        if let Some(decl) = self.component_decl(component_name) {
+            return Some(decl.provider_trait.clone());
+        }
+
+        if self.is_strict() {
+            return None;
+        }
+
+        self.naming.provider_trait(component_name)
+    }
+
+    /// Returns the consumer trait of a component, if it is declared in the workspace
+    pub fn consumer_trait_for_component(&self, component_name: &str) -> Option<String> {
+        self.component_decl(component_name)
+            .map(|decl| decl.consumer_trait.clone())
+    }
+
+    /// Returns the provider traits implemented by a provider in the workspace sources
+    pub fn implemented_provider_traits(&self, provider_type: &str) -> Vec<&str> {
+        let provider_name = base_type_name(provider_type);
+        self.providers
+            .iter()
+            .filter(|provider| base_type_name(&provider.provider_type) == provider_name)
+            .map(|provider| provider.provider_trait.as_str())
+            .collect()
+    }
+
+    /// Returns the providers implementing a provider trait in the workspace sources
+    pub fn providers_of(&self, provider_trait: &str) -> Vec<&str> {
+        let provider_trait = last_path_segment(provider_trait);
+        self.providers
+            .iter()
+            .filter(|provider| provider.provider_trait == provider_trait)
+            .map(|provider| base_type_name(&provider.provider_type))
+            .collect()
+    }
+
+    /// Returns the providers of a provider trait whose requirements a context meets, as far
+    /// as the index knows them: the fields of their getters exist on the context (or the
+    /// struct it derefs to) and the components of their consumer traits are wired
+    /// Higher-order providers are left out, since they need a provider of their own
+    pub fn providers_satisfied_by(&self, provider_trait: &str, context_type: &str) -> Vec<&str> {
+        let provider_trait = last_path_segment(provider_trait);
+        let fields_struct = self
+            .deref_target(context_type)
+            .map_or(context_type, |target| target.type_name.as_str());
+        let Some(fields) = self.struct_fields(fields_struct) else {
+            return Vec::new();
+        };
+
+        self.providers
+            .iter()
+            .filter(|provider| {
+                provider.provider_trait == provider_trait
+                    && !provider.provider_type.contains('<')
+                    && provider
+                        .required_fields
+                        .iter()
+                        .all(|field| fields.contains(&field.as_str()))
+                    && provider
+                        .required_components
+                        .iter()
+                        .all(|component| self.wires_component(context_type, component))
+            })
+            .map(|provider| provider.provider_type.as_str())
+            .collect()
+    }
+
+    /// Returns the doc comment summary of a provider (e.g., "ScaledArea<RectangleArea>")
+    pub fn provider_doc(&self, provider_type: &str) -> Option<&str> {
+        let provider_name = base_type_name(provider_type);
+        self.provider_docs
+            .iter()
+            .find(|(name, _)| name == provider_name)
+            .map(|(_, doc)| doc.as_str())
+    }
+
+    /// Returns where a provider struct generated by a CGP macro is declared, e.g.
+    /// "declared as `#[cgp_impl(new RectangleArea)]` at src/area.rs:12"
+    pub fn provider_declaration(&self, provider_type: &str) -> Option<String> {
+        let provider_name = base_type_name(provider_type);
+        self.provider_declarations
+            .iter()
+            .find(|declaration| declaration.provider_name == provider_name)
+            .map(|declaration| {
+                format!(
+                    "declared as {} at {}:{}",
+                    declaration.declaration,
+                    declaration.location.display_path.display(),
+                    declaration.location.line
+                )
+            })
+    }
+
+    /// Returns the doc comment summary of the component that a consumer trait belongs to
+    pub fn consumer_trait_doc(&self, consumer_trait: &str) -> Option<&str> {
+        self.components
+            .iter()
+            .find(|decl| decl.consumer_trait == last_path_segment(consumer_trait))
+            .and_then(|decl| decl.doc.as_deref())
+    }
+
+    /// Returns the component that a consumer trait belongs to
+    pub fn component_for_consumer_trait(&self, consumer_trait: &str) -> Option<String> {
+        if let Some(decl) = self
+            .components
+            .iter()
+            .find(|decl| decl.consumer_trait == last_path_segment(consumer_trait))
+        {
+            return Some(decl.component_name.clone());
+        }
+
+        if self.is_strict() {
+            return None;
+        }
+
+        self.naming
+            .component_for_consumer_trait(&strip_module_prefixes(consumer_trait))
+    }
+
+    /// Returns the component of a provider trait (e.g., "AreaCalculatorComponent" for "AreaCalculator")
+    pub fn component_for_provider_trait(&self, provider_trait: &str) -> Option<String> {
+        let provider_trait = last_path_segment(base_type_name(provider_trait));
+        if let Some(decl) = self
+            .components
+            .iter()
+            .find(|decl| decl.provider_trait == provider_trait)
+        {
+            return Some(decl.component_name.clone());
+        }
+
+        if self.is_strict() {
+            return None;
+        }
+
+        Some(format!(
+            "{}{}",
+            provider_trait, self.naming.component_suffix
+        ))
+    }
+
+    /// Returns true if the consumer trait belongs to the given component
+    /// In heuristic mode, this falls back to matching significant words between
+    /// the provider trait and the consumer trait (e.g., AreaCalculator ~ CanCalculateArea)
+    pub fn component_provides_consumer_trait(
+        &self,
+        component_info: &ComponentInfo,
+        consumer_trait: &str,
+    ) -> bool {
+        if let Some(decl) = self.component_decl(&component_info.component_type) {
+            return decl.consumer_trait == last_path_segment(consumer_trait);
+        }
+
+        if self.is_strict() {
+            return false;
+        }
+
+        match (
+            self.naming.provider_trait(&component_info.component_type),
+            self.naming.consumer_action(consumer_trait),
+        ) {
+            (Some(provider_trait), Some(action_part)) => {
+                shares_significant_word(&provider_trait, action_part)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the component of a `#[cgp_getter]` trait if a context does not wire it
+    /// Unlike `#[cgp_auto_getter]`, such getters are only implemented for the contexts
+    /// that delegate their component (e.g., to `UseFields`)
+    pub fn unwired_getter_component(&self, getter_trait: &str, context_type: &str) -> Option<&str> {
+        let getter_trait = last_path_segment(base_type_name(getter_trait));
+        let context_type = last_path_segment(base_type_name(context_type));
+        let (_, component) = self
+            .getter_components
+            .iter()
+            .find(|(name, _)| name == getter_trait)?;
+
+        (!self.wires_component(context_type, component)).then_some(component.as_str())
+    }
+
+    /// Returns true if a context wires a component in `delegate_components!`
+    pub fn wires_component(&self, context_type: &str, component_name: &str) -> bool {
+        self.delegation_entry(context_type, component_name)
+            .is_some()
+    }
+
+    /// Returns the entry wiring a component for a context in `delegate_components!`
+    pub fn delegation_entry(
+        &self,
+        context_type: &str,
+        component_name: &str,
+    ) -> Option<&Delegation> {
+        let context_type = last_path_segment(base_type_name(context_type));
+        let component_name = last_path_segment(component_name);
+        self.delegations.iter().find(|delegation| {
+            delegation.context_type == context_type && delegation.component == component_name
+        })
+    }
+
+    /// Returns the components that a context wires in `delegate_components!`
+    pub fn wired_components(&self, context_type: &str) -> Vec<&str> {
+        let context_type = last_path_segment(base_type_name(context_type));
+        self.delegations
+            .iter()
+            .filter(|delegation| delegation.context_type == context_type)
+            .map(|delegation| delegation.component.as_str())
+            .collect()
+    }
+
+    /// Returns where a check trait is generated by `check_components!`
+    pub fn check_block_location(&self, check_trait: &str) -> Option<&SourceLocation> {
+        let check_trait = last_path_segment(base_type_name(check_trait));
+        self.check_blocks
+            .iter()
+            .find(|(name, _)| name == check_trait)
+            .map(|(_, location)| location)
+    }
+
+    /// Returns where a context struct is defined
+    pub fn context_location(&self, context_type: &str) -> Option<&SourceLocation> {
+        let type_name = last_path_segment(base_type_name(context_type));
+        self.struct_locations
+            .iter()
+            .find(|(name, _)| name == type_name)
+            .map(|(_, location)| location)
+    }
+
+    /// Returns the implementation of a provider trait by a provider
+    pub fn provider_impl(
+        &self,
+        provider_type: &str,
+        provider_trait: &str,
+    ) -> Option<&IndexedProvider> {
+        let provider_name = base_type_name(provider_type);
+        let provider_trait = last_path_segment(provider_trait);
+        self.providers.iter().find(|provider| {
+            base_type_name(&provider.provider_type) == provider_name
+                && provider.provider_trait == provider_trait
+        })
+    }
+
+    /// Returns the declaration of a getter trait
+    pub fn getter_decl(&self, getter_trait: &str) -> Option<&GetterDecl> {
+        let getter_trait = last_path_segment(base_type_name(getter_trait));
+        self.getters
+            .iter()
+            .find(|getter| getter.trait_name == getter_trait)
+    }
+
+    /// Returns where the last component of a context is wired in `delegate_components!`,
+    /// which is where another component would be added
+    pub fn delegation_block(&self, context_type: &str) -> Option<&SourceLocation> {
+        let context_type = last_path_segment(base_type_name(context_type));
+        self.delegation_blocks
+            .iter()
+            .find(|(context, _)| context == context_type)
+            .map(|(_, location)| location)
+    }
+
+    /// Returns the crate defining a context struct (e.g., "my-app" for "my_app::Rectangle")
+    pub fn context_crate(&self, context_type: &str) -> Option<&str> {
+        let type_name = last_path_segment(base_type_name(context_type));
+        self.context_crates
+            .iter()
+            .find(|(name, _)| name == type_name)
+            .map(|(_, crate_name)| crate_name.as_str())
+    }
+
+    /// Returns the struct whose fields a context gets through `Deref`
+    /// (e.g., `AppConfig` for `App` with `impl Deref for App { type Target = AppConfig; }`)
+    pub fn deref_target(&self, context_type: &str) -> Option<&DerefTarget> {
+        let type_name = last_path_segment(base_type_name(context_type));
+        self.deref_targets
+            .iter()
+            .find(|(name, _)| name == type_name)
+            .map(|(_, target)| target)
+    }
+
+    /// Returns the field names of a struct with named fields, if the struct was scanned
+    pub fn struct_fields(&self, type_name: &str) -> Option<Vec<&str>> {
+        let type_name = last_path_segment(base_type_name(type_name));
+        self.struct_fields
+            .iter()
+            .find(|(name, _)| name == type_name)
+            .map(|(_, fields)| fields.iter().map(|field| field.name.as_str()).collect())
+    }
+
+    /// Returns the declared type of a field of a struct (e.g., `u32` for `pub height: u32`)
+    pub fn struct_field_type(&self, type_name: &str, field_name: &str) -> Option<&str> {
+        let type_name = last_path_segment(base_type_name(type_name));
+        self.struct_fields
+            .iter()
+            .find(|(name, _)| name == type_name)?
+            .1
+            .iter()
+            .find(|field| field.name == field_name)
+            .map(|field| field.field_type.as_str())
+    }
+
+    /// Returns the getter traits that read a field
+    pub fn getters_reading(&self, field_name: &str) -> Vec<&GetterDecl> {
+        self.getters
+            .iter()
+            .filter(|getter| getter.fields.iter().any(|field| field.name == field_name))
+            .collect()
+    }
+
+    /// Returns the names that a field required from a context may have: the fields of the
+    /// context struct and of the struct it derefs to, and the fields read by getter traits
+    pub fn field_name_candidates(&self, context_type: &str) -> Vec<&str> {
+        let type_name = last_path_segment(base_type_name(context_type));
+        let deref_target = self
+            .deref_target(type_name)
+            .map(|target| target.type_name.as_str());
+
+        let mut candidates: Vec<&str> = Vec::new();
+        let struct_fields = self
+            .struct_fields
+            .iter()
+            .filter(|(name, _)| name == type_name || Some(name.as_str()) == deref_target)
+            .flat_map(|(_, fields)| fields)
+            .map(|field| &field.name);
+        let getter_fields = self.getter_fields.iter().map(|field| &field.name);
+        for field in struct_fields.chain(getter_fields) {
+            if !candidates.contains(&field.as_str()) {
+                candidates.push(field);
+            }
+        }
+        candidates
+    }
+
+    /// Returns the type of a field as read by the getter traits (e.g., `f64` for
+    /// `fn height(&self) -> f64`), or None when no getter reads it or getters disagree
+    pub fn field_type(&self, field_name: &str) -> Option<&str> {
+        let mut field_types = self
+            .getter_fields
+            .iter()
+            .filter(|field| field.name == field_name)
+            .map(|field| field.field_type.as_str());
+        let field_type = field_types.next()?;
+        field_types
+            .all(|other| other == field_type)
+            .then_some(field_type)
+    }
+
+    /// Removes the paths of workspace crates from type names
+    /// (e.g., "my_app::Rectangle" -> "Rectangle"), so that names read the same
+    /// no matter which crate the compiler reported them from
+    pub fn strip_crate_paths(&self, text: &str) -> String {
+        let mut result = text.to_string();
+
+        for crate_name in &self.workspace_crates {
+            let prefix = format!("{}::", crate_name.replace('-', "_"));
+            let mut stripped = String::with_capacity(result.len());
+            let mut rest = result.as_str();
+
+            while let Some(pos) = rest.find(&prefix) {
+                // Only strip whole path segments, not suffixes of other names
+                let preceded_by_ident = rest[..pos]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == ':');
+                stripped.push_str(&rest[..pos]);
+                if preceded_by_ident {
+                    stripped.push_str(&prefix);
+                }
+                rest = &rest[pos + prefix.len()..];
+            }
+
+            stripped.push_str(rest);
+            result = stripped;
+        }
+
+        result
+    }
+}
+
+/// Returns the last segment of a path (e.g., "crate::shapes::AreaCalculatorComponent" -> "AreaCalculatorComponent")
+fn last_path_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path).trim()
+}
+
+/// Checks if two CamelCase names share a significant word (longer than two characters)
+pub fn shares_significant_word(a: &str, b: &str) -> bool {
+    let words = |name: &str| -> Vec<String> {
+        name.split(|c: char| c.is_uppercase())
+            .filter(|s| s.len() > 2)
+            .map(|s| s.to_ascii_lowercase())
+            .collect()
+    };
+
+    let b_words = words(b);
+    words(a).iter().any(|word| b_words.contains(word))
+}
+
+/// Returns the Levenshtein distance between two names, counted in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Returns the candidate closest to a name, if it is close enough to be a misspelling
+/// Names are compared without the `Component` suffix, so that it does not make two
+/// different components look alike; the first of equally close candidates wins
+pub fn closest_name<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let stem = name.strip_suffix("Component").unwrap_or(name);
+    // Like rustc's suggestions, allow one edit for every three characters
+    let max_distance = stem.chars().count().max(3) / 3;
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| {
+            let candidate_stem = candidate.strip_suffix("Component").unwrap_or(candidate);
+            (edit_distance(stem, candidate_stem), candidate)
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area_decl() -> ComponentDecl {
+        ComponentDecl {
+            component_name: "AreaCalculatorComponent".to_string(),
+            provider_trait: "AreaCalculator".to_string(),
+            consumer_trait: "CanCalculateArea".to_string(),
+            doc: None,
+        }
+    }
+
+    #[test]
+    fn test_strict_resolution() {
+        let strict = NameResolver::new(vec![area_decl()], ResolutionMode::Strict);
+
+        assert_eq!(
+            strict.component_for_consumer_trait("CanCalculateArea"),
+            Some("AreaCalculatorComponent".to_string())
+        );
+        assert_eq!(
+            strict.provider_trait_for_component("crate::AreaCalculatorComponent"),
+            Some("AreaCalculator".to_string())
+        );
+
+        // Names that are not in the index are never derived
+        assert_eq!(
+            strict.component_for_consumer_trait("CanCalculateMass"),
+            None
+        );
+        assert_eq!(
+            strict.provider_trait_for_component("MassCalculatorComponent"),
+            None
+        );
+
+        let heuristic = NameResolver::new(Vec::new(), ResolutionMode::Heuristic);
+        assert_eq!(
+            heuristic.component_for_consumer_trait("CanCalculateMass"),
+            Some("CalculateMassComponent".to_string())
+        );
+    }
+
+    #[test]
+    fn test_custom_naming() {
+        let naming = NamingConventions {
+            consumer_prefix: "Has".to_string(),
+            component_suffix: "Slot".to_string(),
+        };
+        let resolver = NameResolver::new(Vec::new(), ResolutionMode::Heuristic).with_naming(naming);
+
+        assert_eq!(
+            resolver.component_for_consumer_trait("HasArea"),
+            Some("AreaSlot".to_string())
+        );
+        assert_eq!(
+            resolver.provider_trait_for_component("AreaCalculatorSlot"),
+            Some("AreaCalculator".to_string())
+        );
+        assert_eq!(
+            resolver.component_for_consumer_trait("CanCalculateArea"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cross_crate_names() {
+        let content = "#[derive(HasField)]\npub struct Rectangle {\n    pub width: f64,\n}\n";
+        let mut scan = WorkspaceScan::new();
+        scan.scan_file(
+            std::path::Path::new("src/lib.rs"),
+            std::path::Path::new("src/lib.rs"),
+            "my-app",
+            "crate",
+            content,
+        );
+
+        let resolver = NameResolver::from_scan(&scan, ResolutionMode::Heuristic);
+
+        assert_eq!(resolver.context_crate("my_app::Rectangle"), Some("my-app"));
+        assert_eq!(resolver.context_crate("Circle"), None);
+        assert_eq!(
+            resolver.strip_crate_paths("`CanUseRectangle` for `my_app::Rectangle`"),
+            "`CanUseRectangle` for `Rectangle`"
+        );
+        assert_eq!(
+            resolver.strip_crate_paths("not_my_app::Rectangle"),
+            "not_my_app::Rectangle"
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("area", ""), 4);
+        assert_eq!(edit_distance("AreaCalculator", "AreaCalculator"), 0);
+        assert_eq!(edit_distance("AreaCalculator", "AreaCalculation"), 2);
+        assert_eq!(edit_distance("Rectangle", "Rectnagle"), 2);
+    }
+
+    #[test]
+    fn test_closest_name() {
+        let wired = ["PerimeterCalculatorComponent", "AreaCalculatorComponent"];
+
+        assert_eq!(
+            closest_name("AreaCalculationComponent", wired),
+            Some("AreaCalculatorComponent")
+        );
+        assert_eq!(
+            closest_name("AreaCalculatrComponent", wired),
+            Some("AreaCalculatorComponent")
+        );
+
+        // Components that only share a suffix are not misspellings of each other
+        assert_eq!(closest_name("VolumeCalculatorComponent", wired), None);
+        // A name is not a misspelling of itself
+        assert_eq!(closest_name("AreaCalculatorComponent", wired), None);
+    }
+}
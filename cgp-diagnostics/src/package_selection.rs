@@ -0,0 +1,253 @@
+/// Module for the set of workspace members that a cargo command checks
+/// Cargo only builds the packages selected by `-p`, `--workspace` and `--exclude`, or by
+/// default the member in the current directory, or the `default-members` of the workspace.
+/// The component index is limited to those packages and the workspace members they depend
+/// on, so that suggestions never refer to code that was not compiled.
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use cargo_metadata::{Metadata, MetadataCommand, Package};
+
+use crate::workspace_scan::SourceRoot;
+
+/// The package selection flags of a cargo command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageSelection {
+    /// `--workspace` (or its alias `--all`)
+    pub workspace: bool,
+    /// `-p` / `--package`
+    pub packages: Vec<String>,
+    /// `--exclude`
+    pub excludes: Vec<String>,
+    /// `--manifest-path`
+    pub manifest_path: Option<PathBuf>,
+}
+
+impl PackageSelection {
+    /// Reads the package selection flags from the arguments passed through to cargo
+    pub fn from_cargo_args(cargo_args: &[String]) -> Self {
+        let mut selection = PackageSelection::default();
+        let mut args = cargo_args.iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || inline_value.clone().or_else(|| args.next().cloned());
+
+            match flag {
+                "--" => break,
+                "--workspace" | "--all" => selection.workspace = true,
+                "-p" | "--package" => selection.packages.extend(value()),
+                "--exclude" => selection.excludes.extend(value()),
+                "--manifest-path" => selection.manifest_path = value().map(PathBuf::from),
+                _ => {
+                    if let Some(package) = flag.strip_prefix("-p").filter(|p| !p.is_empty()) {
+                        selection.packages.push(package.to_string());
+                    }
+                }
+            }
+        }
+
+        selection
+    }
+
+    /// Returns the workspace members that cargo checks for this selection, with the
+    /// workspace members they depend on
+    /// `current_manifest` is the manifest cargo would use without `--manifest-path`
+    pub fn select<'a>(&self, metadata: &'a Metadata, current_manifest: &Path) -> Vec<&'a Package> {
+        let members = metadata.workspace_packages();
+        let manifest = self.manifest_path.as_deref().unwrap_or(current_manifest);
+        let root_manifest = metadata.workspace_root.join("Cargo.toml");
+
+        let selected: Vec<&Package> = if !self.packages.is_empty() {
+            members
+                .iter()
+                .copied()
+                .filter(|package| {
+                    self.packages.iter().any(|spec| {
+                        // A spec may carry a version (e.g., `shapes@0.1.0`)
+                        let name = spec.split('@').next().unwrap_or(spec);
+                        package.name.as_str() == name
+                    })
+                })
+                .collect()
+        } else if self.workspace {
+            members
+                .iter()
+                .copied()
+                .filter(|package| {
+                    !self
+                        .excludes
+                        .iter()
+                        .any(|name| package.name.as_str() == name)
+                })
+                .collect()
+        } else if let Some(package) = members
+            .iter()
+            .find(|package| package.manifest_path == manifest && manifest != root_manifest)
+        {
+            // In the directory of a member, cargo only checks that member
+            vec![*package]
+        } else if metadata.workspace_default_members.is_available() {
+            metadata.workspace_default_packages()
+        } else {
+            members.clone()
+        };
+
+        // Cargo reports an unknown package itself, so the index is not limited then
+        if selected.is_empty() {
+            return members;
+        }
+
+        with_member_dependencies(&members, selected)
+    }
+}
+
+/// Adds the workspace members that the selected packages depend on, transitively
+fn with_member_dependencies<'a>(
+    members: &[&'a Package],
+    mut selected: Vec<&'a Package>,
+) -> Vec<&'a Package> {
+    let mut index = 0;
+
+    while index < selected.len() {
+        let package = selected[index];
+        for dependency in package.dependencies.iter().filter(|d| d.path.is_some()) {
+            if let Some(member) = members
+                .iter()
+                .find(|member| member.name.as_str() == dependency.name)
+                && !selected.iter().any(|package| package.id == member.id)
+            {
+                selected.push(member);
+            }
+        }
+        index += 1;
+    }
+
+    selected
+}
+
+/// Returns the source roots of the targets of some packages
+pub fn source_roots_of(packages: &[&Package]) -> Vec<SourceRoot> {
+    packages
+        .iter()
+        .flat_map(|package| {
+            package.targets.iter().map(|target| SourceRoot {
+                crate_name: package.name.to_string(),
+                root_file: target.src_path.clone().into_std_path_buf(),
+            })
+        })
+        .collect()
+}
+
+/// Finds the crate roots of the workspace members that a cargo command with these
+/// arguments checks, using `cargo metadata`
+pub fn checked_source_roots(cargo_args: &[String]) -> Result<(PathBuf, Vec<SourceRoot>)> {
+    let current_dir = std::env::current_dir()?;
+    let mut selection = PackageSelection::from_cargo_args(cargo_args);
+    // Cargo's metadata has absolute manifest paths
+    selection.manifest_path = selection
+        .manifest_path
+        .map(|manifest_path| current_dir.join(manifest_path));
+
+    let mut command = MetadataCommand::new();
+    if let Some(manifest_path) = &selection.manifest_path {
+        command.manifest_path(manifest_path);
+    }
+    let metadata = command.no_deps().exec()?;
+
+    let current_manifest = current_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|manifest| manifest.is_file())
+        .unwrap_or_else(|| {
+            metadata
+                .workspace_root
+                .join("Cargo.toml")
+                .into_std_path_buf()
+        });
+
+    let packages = selection.select(&metadata, &current_manifest);
+    Ok((
+        metadata.workspace_root.clone().into_std_path_buf(),
+        source_roots_of(&packages),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_from_cargo_args() {
+        let selection = PackageSelection::from_cargo_args(&args(&[
+            "-p",
+            "shapes",
+            "--package=geometry",
+            "-pcore",
+            "--exclude",
+            "examples",
+            "--manifest-path=app/Cargo.toml",
+            "--",
+            "--workspace",
+        ]));
+
+        assert_eq!(selection.packages, vec!["shapes", "geometry", "core"]);
+        assert_eq!(selection.excludes, vec!["examples"]);
+        assert_eq!(
+            selection.manifest_path,
+            Some(PathBuf::from("app/Cargo.toml"))
+        );
+        assert!(!selection.workspace);
+        assert!(PackageSelection::from_cargo_args(&args(&["--all"])).workspace);
+    }
+
+    #[test]
+    fn test_select() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let metadata = MetadataCommand::new()
+            .manifest_path(root.join("Cargo.toml"))
+            .no_deps()
+            .exec()
+            .unwrap();
+        let names = |selection: &PackageSelection, manifest: &Path| {
+            let mut names: Vec<String> = selection
+                .select(&metadata, manifest)
+                .iter()
+                .map(|package| package.name.to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        let root_manifest = metadata
+            .workspace_root
+            .join("Cargo.toml")
+            .into_std_path_buf();
+        let member_manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+
+        // The members that a selected package depends on are indexed too
+        let selection = PackageSelection::from_cargo_args(&args(&["-p", "cargo-cgp"]));
+        assert_eq!(
+            names(&selection, &root_manifest),
+            vec!["cargo-cgp", "cgp-diagnostics"]
+        );
+
+        // In the directory of a member, only that member is checked
+        let selection = PackageSelection::default();
+        assert_eq!(names(&selection, &member_manifest), vec!["cgp-diagnostics"]);
+
+        // At the root of a virtual workspace without `default-members`, all members are
+        let all = names(&selection, &root_manifest);
+        assert_eq!(all.len(), metadata.workspace_members.len());
+
+        let selection =
+            PackageSelection::from_cargo_args(&args(&["--workspace", "--exclude", "cargo-cgp"]));
+        assert!(!names(&selection, &root_manifest).contains(&"cargo-cgp".to_string()));
+    }
+}
@@ -0,0 +1,81 @@
+/// Module for isolating panics raised while analyzing a single diagnostic
+/// Several extractors index into compiler messages, so an unexpected message shape can panic.
+/// A panic only affects the diagnostic being analyzed, which is then shown as reported by rustc.
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+/// Environment variable that enables logging of internal panics on stderr
+/// The backtrace of a logged panic follows `RUST_BACKTRACE`, as for any Rust program
+pub const LOG_ENV: &str = "CGP_LOG";
+
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    /// Whether the current thread is inside `catch_analysis_panic`
+    static IS_ANALYZING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs the analysis of one diagnostic, returning the panic message if it panicked
+pub fn catch_analysis_panic<T>(analysis: impl FnOnce() -> T) -> Result<T, String> {
+    install_panic_hook();
+
+    let was_analyzing = IS_ANALYZING.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(analysis));
+    IS_ANALYZING.set(was_analyzing);
+
+    result.map_err(|payload| {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+
+        if is_logging_enabled() {
+            eprintln!(
+                "cargo-cgp: internal panic while analyzing a diagnostic: {}",
+                message
+            );
+        }
+
+        message
+    })
+}
+
+/// Silences the default panic output for caught panics unless logging is enabled,
+/// since a caught panic is reported through the diagnostic that caused it
+fn install_panic_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !IS_ANALYZING.get() || is_logging_enabled() {
+                default_hook(info);
+            }
+        }));
+    });
+}
+
+fn is_logging_enabled() -> bool {
+    std::env::var_os(LOG_ENV).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_analysis_panic() {
+        assert_eq!(catch_analysis_panic(|| 42), Ok(42));
+
+        let text = "short";
+        let result = catch_analysis_panic(|| text[..text.len() + 1].to_string());
+        assert!(result.unwrap_err().contains("out of bounds"));
+
+        assert_eq!(
+            catch_analysis_panic(|| -> () { panic!("bad {}", "message") }),
+            Err("bad message".to_string())
+        );
+    }
+}
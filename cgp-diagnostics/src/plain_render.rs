@@ -0,0 +1,166 @@
+/// Module for rendering diagnostics as plain text without miette
+/// Builds without the `graphical` feature (e.g., for wasm tooling or CI bots) render
+/// diagnostics in the layout of rustc's snippets: the headline, each highlighted line
+/// with its label, and the help text.
+use crate::cgp_diagnostic::{CgpDiagnostic, Severity, SpanLocation};
+use crate::source_snippet::{SourceAccess, probe_source_access};
+
+/// Renders a diagnostic as plain text
+pub fn render_plain(diagnostic: &CgpDiagnostic) -> String {
+    let severity = match diagnostic.severity {
+        None | Some(Severity::Error) => "error",
+        Some(Severity::Warning) => "warning",
+        Some(Severity::Advice) => "advice",
+    };
+
+    // Only CGP error codes are shown, as in the graphical rendering
+    let mut output = match diagnostic
+        .code
+        .as_deref()
+        .filter(|code| code.starts_with("CGP"))
+    {
+        Some(code) => format!("{}[{}]: {}\n", severity, code, diagnostic.message),
+        None => format!("{}: {}\n", severity, diagnostic.message),
+    };
+
+    let gutter_width = diagnostic
+        .spans
+        .iter()
+        .map(|span| span.line_start.to_string().len())
+        .max()
+        .unwrap_or(0);
+    // Label IDs are only shown when they are needed to tell labels apart
+    let show_ids = diagnostic.spans.len() > 1;
+
+    for (index, span) in diagnostic.spans.iter().enumerate() {
+        let label = if show_ids {
+            format!("[{}] {}", index + 1, span.label)
+        } else {
+            span.label.clone()
+        };
+        output.push_str(&render_span(span, &label, gutter_width));
+    }
+
+    if let Some(help) = &diagnostic.help {
+        let mut lines = help.lines();
+        if let Some(first) = lines.next() {
+            output.push_str(&format!("help: {}\n", first));
+        }
+        for line in lines {
+            output.push_str(format!("      {}", line).trim_end());
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+/// Renders the location of a span with its first line, underlined and labeled
+fn render_span(span: &SpanLocation, label: &str, gutter_width: usize) -> String {
+    let padding = " ".repeat(gutter_width);
+    let mut output = format!(
+        "{}--> {}:{}:{}\n",
+        padding, span.file, span.line_start, span.column_start
+    );
+
+    let Some(line) = first_source_line(span) else {
+        output.push_str(&format!("{} = {}\n", padding, label));
+        return output;
+    };
+
+    // Multi-line spans are underlined to the end of their first line
+    let line_length = line.chars().count() + 1;
+    let column_end = if span.line_end == span.line_start {
+        span.column_end.min(line_length)
+    } else {
+        line_length
+    };
+    let underline = "^".repeat(column_end.saturating_sub(span.column_start).max(1));
+
+    output.push_str(&format!("{} |\n", padding));
+    output.push_str(&format!(
+        "{:>width$} | {}\n",
+        span.line_start,
+        line,
+        width = gutter_width
+    ));
+    output.push_str(&format!(
+        "{} | {}{} {}\n",
+        padding,
+        " ".repeat(span.column_start.saturating_sub(1)),
+        underline,
+        label
+    ));
+    output.push_str(&format!("{} |\n", padding));
+    output
+}
+
+/// The first line of a span, from the source file or else from the text embedded by rustc
+fn first_source_line(span: &SpanLocation) -> Option<String> {
+    if let SourceAccess::Readable(content) = probe_source_access(span)
+        && let Some(line) = content.lines().nth(span.line_start.saturating_sub(1))
+    {
+        return Some(line.to_string());
+    }
+
+    span.text.first().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_plain() {
+        let span = |line: usize, column_start: usize, column_end: usize, text: &str| SpanLocation {
+            file: "missing/shapes.rs".to_string(),
+            line_start: line,
+            column_start,
+            line_end: line,
+            column_end,
+            label: "unsatisfied trait bound".to_string(),
+            text: vec![text.to_string()],
+            source_path: None,
+        };
+
+        let mut diagnostic = CgpDiagnostic::new(
+            "missing field `height` in the context `Rectangle`.".to_string(),
+            vec![
+                span(9, 9, 32, "        AreaCalculatorComponent,"),
+                span(10, 9, 35, "        DensityCalculatorComponent,"),
+            ],
+        );
+        diagnostic.code = Some("CGP0001".to_string());
+        diagnostic.help = Some("To fix this error:\n\n    • Add a field `height`".to_string());
+
+        assert_eq!(
+            render_plain(&diagnostic),
+            "\
+error[CGP0001]: missing field `height` in the context `Rectangle`.
+  --> missing/shapes.rs:9:9
+   |
+ 9 |         AreaCalculatorComponent,
+   |         ^^^^^^^^^^^^^^^^^^^^^^^ [1] unsatisfied trait bound
+   |
+  --> missing/shapes.rs:10:9
+   |
+10 |         DensityCalculatorComponent,
+   |         ^^^^^^^^^^^^^^^^^^^^^^^^^^ [2] unsatisfied trait bound
+   |
+help: To fix this error:
+
+          • Add a field `height`
+"
+        );
+
+        // Compiler codes and diagnostics without spans
+        diagnostic.code = Some("E0277".to_string());
+        diagnostic.spans.clear();
+        diagnostic.help = None;
+        diagnostic.severity = Some(Severity::Warning);
+        assert_eq!(
+            render_plain(&diagnostic),
+            "warning: missing field `height` in the context `Rectangle`.\n"
+        );
+    }
+}
@@ -0,0 +1,108 @@
+/// Module for filters that keep selected CGP diagnostics unimproved
+/// Matching diagnostics are shown with rustc's own rendering, while all other
+/// CGP diagnostics are improved as usual
+use cargo_metadata::diagnostic::Diagnostic;
+
+use crate::cgp_patterns::extract_component_info;
+
+/// A filter selecting CGP diagnostics to show as reported by rustc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawFilter {
+    /// Diagnostics mentioning a component whose name matches a glob (e.g., `Area*`)
+    Component(String),
+    /// Diagnostics with an error code (e.g., `E0277`)
+    ErrorCode(String),
+}
+
+impl RawFilter {
+    pub fn matches(&self, diagnostic: &Diagnostic) -> bool {
+        match self {
+            RawFilter::ErrorCode(code) => diagnostic
+                .code
+                .as_ref()
+                .is_some_and(|diagnostic_code| &diagnostic_code.code == code),
+            RawFilter::Component(pattern) => std::iter::once(&diagnostic.message)
+                .chain(diagnostic.children.iter().map(|child| &child.message))
+                .filter_map(|message| extract_component_info(message))
+                .any(|info| component_matches(pattern, &info.component_type)),
+        }
+    }
+}
+
+/// Returns true if any filter selects the diagnostic to be shown unimproved
+pub fn is_raw(filters: &[RawFilter], diagnostic: &Diagnostic) -> bool {
+    filters.iter().any(|filter| filter.matches(diagnostic))
+}
+
+/// Returns true if the name of a component, without its module path, matches a glob
+pub fn component_matches(pattern: &str, component_type: &str) -> bool {
+    let name = component_type.rsplit("::").next().unwrap_or_default();
+    glob_matches(pattern, name.trim())
+}
+
+/// Matches a name against a glob where `*` matches any sequence and `?` any character
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Position of the last `*` in the pattern, and the name position it was tried at
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // Let the last `*` consume one more character
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches(
+            "AreaCalculatorComponent",
+            "AreaCalculatorComponent"
+        ));
+        assert!(glob_matches("Area*", "AreaCalculatorComponent"));
+        assert!(glob_matches("*Calculator*", "DensityCalculatorComponent"));
+        assert!(glob_matches("?rea*Component", "AreaCalculatorComponent"));
+        assert!(!glob_matches("Area*", "DensityCalculatorComponent"));
+        assert!(!glob_matches("Area", "AreaCalculatorComponent"));
+    }
+
+    #[test]
+    fn test_raw_filter_matches() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let json_path = format!(
+            "{}/../examples/src/base_area.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let db = load_diagnostic_database(json_path);
+        let diagnostic = &db.get_all_entries()[0].original;
+
+        assert!(RawFilter::Component("Area*".to_string()).matches(diagnostic));
+        assert!(!RawFilter::Component("Density*".to_string()).matches(diagnostic));
+        assert!(RawFilter::ErrorCode("E0277".to_string()).matches(diagnostic));
+        assert!(!is_raw(
+            &[RawFilter::ErrorCode("E0599".to_string())],
+            diagnostic
+        ));
+    }
+}
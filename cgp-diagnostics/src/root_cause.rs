@@ -0,0 +1,420 @@
+/// Module for identifying root causes from transitive failures
+/// This implements the approach described in Chapter 9 of the report
+use crate::cgp_patterns::ProviderRelationship;
+use crate::diagnostic_db::{DiagnosticEntry, ErrorKind};
+use crate::type_expr::same_type;
+use crate::workspace_scan::contains_type_argument;
+use std::cmp::Reverse;
+
+/// Scores how likely an entry is to be the cause of the other errors, higher is more likely
+/// Missing fields are fixed first, then missing `HasField` implementations, then the other
+/// classified root causes, then unsatisfied providers, and generic bounds last
+pub fn causal_priority(entry: &DiagnosticEntry) -> u8 {
+    match entry.kind() {
+        ErrorKind::MissingField | ErrorKind::FieldTypeMismatch => 5,
+        ErrorKind::MissingHasField => 4,
+        ErrorKind::UnsizedType
+        | ErrorKind::MissingDelegation
+        | ErrorKind::NotAProvider
+        | ErrorKind::RequirementOverflow
+        | ErrorKind::AutoTrait => 3,
+        ErrorKind::UnsatisfiedProvider => 2,
+        ErrorKind::UnsatisfiedBound => 1,
+    }
+}
+
+/// Sorts entries by causal priority (higher priority first), breaking ties by source location
+pub fn rank_by_causal_priority(entries: &mut [&DiagnosticEntry]) {
+    entries.sort_by_key(|entry| {
+        (
+            Reverse(causal_priority(entry)),
+            entry
+                .primary_span()
+                .map(|span| (span.file_name.clone(), span.line_start, span.column_start)),
+        )
+    });
+}
+
+/// Returns true if this entry represents a root cause error
+pub fn is_root_cause(entry: &DiagnosticEntry) -> bool {
+    // A root cause is typically:
+    // 1. Has field_info (missing struct field)
+    // 2. Has provider_relationships but no further dependencies
+    // 3. Is the most specific error in a chain
+
+    if entry.field_info.is_some() {
+        return true;
+    }
+
+    // If it has provider relationships, it's explaining a dependency chain
+    // This is useful context but may or may not be a root cause
+    if !entry.provider_relationships().is_empty() {
+        return true;
+    }
+
+    false
+}
+
+/// Determines if an entry should be suppressed because it's a transitive failure
+/// When several entries compete at one location, the one with the highest causal priority wins
+pub fn is_transitive_failure(entry: &DiagnosticEntry, all_entries: &[&DiagnosticEntry]) -> bool {
+    let priority = causal_priority(entry);
+
+    all_entries.iter().any(|other| {
+        other.is_root_cause
+            && causal_priority(other) > priority
+            && entry_locations_match(entry, other)
+    })
+}
+
+/// Checks if two entries are at the same source location
+/// With multiple spans, we check if any spans match
+fn entry_locations_match(a: &DiagnosticEntry, b: &DiagnosticEntry) -> bool {
+    // If either entry has no spans, they can't match
+    if a.primary_spans().is_empty() || b.primary_spans().is_empty() {
+        return false;
+    }
+
+    // Check if any span from a matches any span from b
+    for span_a in a.primary_spans() {
+        for span_b in b.primary_spans() {
+            if span_a.file_name == span_b.file_name
+                && span_a.line_start == span_b.line_start
+                && span_a.column_start == span_b.column_start
+            {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Deduplicates provider relationships by removing nested redundancies
+/// For example, if we have both "ScaledArea<RectangleArea>" and "RectangleArea"
+/// implementing the same trait for the same component, we only keep the outer one
+pub fn deduplicate_provider_relationships(
+    relationships: &[ProviderRelationship],
+) -> Vec<ProviderRelationship> {
+    let mut deduped = Vec::new();
+
+    for rel in relationships {
+        // Check if this relationship is contained within another
+        let is_redundant = relationships.iter().any(|other| {
+            if rel == other {
+                return false; // Don't compare with itself
+            }
+
+            // Check if they have the same component and context, however they are spelled
+            if !same_type(&other.component, &rel.component)
+                || !same_type(&other.context, &rel.context)
+            {
+                return false;
+            }
+
+            // Check if the other provider type contains this one as a type parameter
+            contains_type_argument(&other.provider_type, &rel.provider_type)
+        });
+
+        if !is_redundant {
+            deduped.push(rel.clone());
+        }
+    }
+
+    deduped
+}
+
+/// A failing provider with the failing providers nested in its generic arguments
+/// (e.g., `LoggedArea<ScaledArea<RectangleArea>>` with `ScaledArea<RectangleArea>`, which
+/// itself has `RectangleArea`), so that the innermost ones can be told apart from the wrappers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderNesting {
+    pub provider_type: String,
+    /// The failing providers directly inside this one, with their own nesting
+    pub inner: Vec<ProviderNesting>,
+}
+
+impl ProviderNesting {
+    /// The nesting of a provider inside this tree, including this provider itself
+    pub fn find(&self, provider_type: &str) -> Option<&ProviderNesting> {
+        if same_type(&self.provider_type, provider_type) {
+            return Some(self);
+        }
+        self.inner
+            .iter()
+            .find_map(|inner| inner.find(provider_type))
+    }
+
+    /// The innermost failing providers, each with the provider that directly wraps it
+    pub fn innermost(&self) -> Vec<(&str, &str)> {
+        let mut innermost = Vec::new();
+        for inner in &self.inner {
+            if inner.inner.is_empty() {
+                innermost.push((inner.provider_type.as_str(), self.provider_type.as_str()));
+            } else {
+                innermost.extend(inner.innermost());
+            }
+        }
+        innermost
+    }
+}
+
+/// Builds the nesting of the failing providers, with one tree per outermost provider
+/// A provider may wrap several others (e.g., `SumArea<RectangleArea, CircleArea>`), and the
+/// compiler may hide some of the intermediate ones, which are then left out of the tree
+pub fn provider_nesting(relationships: &[ProviderRelationship]) -> Vec<ProviderNesting> {
+    let mut provider_types: Vec<&str> = Vec::new();
+    for rel in relationships {
+        if !provider_types
+            .iter()
+            .any(|provider_type| same_type(provider_type, &rel.provider_type))
+        {
+            provider_types.push(&rel.provider_type);
+        }
+    }
+
+    // The providers inside `outer` that no other provider inside `outer` wraps
+    let directly_inside = |outer: Option<&str>| -> Vec<&str> {
+        let inside: Vec<&str> = provider_types
+            .iter()
+            .copied()
+            .filter(|provider_type| {
+                outer.is_none_or(|outer| contains_type_argument(outer, provider_type))
+            })
+            .collect();
+        let mut directly: Vec<&str> = inside
+            .iter()
+            .copied()
+            .filter(|provider_type| {
+                !inside
+                    .iter()
+                    .any(|other| contains_type_argument(other, provider_type))
+            })
+            .collect();
+        // Inner providers are listed in the order of the generic arguments
+        if let Some(outer) = outer {
+            directly.sort_by_key(|provider_type| outer.find(provider_type));
+        }
+        directly
+    };
+
+    fn build<'a>(
+        provider_type: &'a str,
+        directly_inside: &dyn Fn(Option<&str>) -> Vec<&'a str>,
+    ) -> ProviderNesting {
+        ProviderNesting {
+            provider_type: provider_type.to_string(),
+            inner: directly_inside(Some(provider_type))
+                .into_iter()
+                .map(|inner| build(inner, directly_inside))
+                .collect(),
+        }
+    }
+
+    directly_inside(None)
+        .into_iter()
+        .map(|outermost| build(outermost, &directly_inside))
+        .collect()
+}
+
+/// Deduplicates delegation notes by removing redundant entries
+pub fn deduplicate_delegation_notes(notes: &[String]) -> Vec<String> {
+    // For now, just remove exact duplicates
+    // A more sophisticated approach would detect semantic duplicates
+    let mut deduped = Vec::new();
+
+    for note in notes {
+        if !deduped.contains(note) {
+            deduped.push(note.clone());
+        }
+    }
+
+    deduped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cgp_patterns::Confidence;
+
+    #[test]
+    fn test_deduplicate_provider_relationships() {
+        let relationships = vec![
+            ProviderRelationship {
+                provider_type: "RectangleArea".to_string(),
+                component: "AreaCalculatorComponent".to_string(),
+                context: "Rectangle".to_string(),
+                confidence: Confidence::High,
+            },
+            ProviderRelationship {
+                provider_type: "ScaledArea<RectangleArea>".to_string(),
+                component: "AreaCalculatorComponent".to_string(),
+                context: "Rectangle".to_string(),
+                confidence: Confidence::High,
+            },
+        ];
+
+        let deduped = deduplicate_provider_relationships(&relationships);
+
+        // Should only keep the outer one (ScaledArea<RectangleArea>)
+        assert_eq!(deduped.len(), 1);
+        assert_eq!(deduped[0].provider_type, "ScaledArea<RectangleArea>");
+    }
+
+    #[test]
+    fn test_provider_nesting() {
+        let relationship = |provider_type: &str| ProviderRelationship {
+            provider_type: provider_type.to_string(),
+            component: "AreaCalculatorComponent".to_string(),
+            context: "Rectangle".to_string(),
+            confidence: Confidence::High,
+        };
+        let relationships = vec![
+            relationship("RectangleArea"),
+            relationship("CircleArea"),
+            relationship("ScaledArea<RectangleArea>"),
+            relationship("LoggedArea<SumArea<ScaledArea<RectangleArea>, CircleArea>>"),
+            relationship("SumArea<ScaledArea<RectangleArea>, CircleArea>"),
+        ];
+
+        // Every intermediate wrapper is deduplicated from the chain
+        let deduped = deduplicate_provider_relationships(&relationships);
+        assert_eq!(deduped.len(), 1);
+
+        let nesting = provider_nesting(&relationships);
+        assert_eq!(nesting.len(), 1);
+        assert_eq!(
+            nesting[0].provider_type,
+            "LoggedArea<SumArea<ScaledArea<RectangleArea>, CircleArea>>"
+        );
+        assert_eq!(
+            nesting[0].inner[0].provider_type,
+            "SumArea<ScaledArea<RectangleArea>, CircleArea>"
+        );
+        assert_eq!(
+            nesting[0].innermost(),
+            vec![
+                ("RectangleArea", "ScaledArea<RectangleArea>"),
+                (
+                    "CircleArea",
+                    "SumArea<ScaledArea<RectangleArea>, CircleArea>"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deduplicate_generic_context_relationships() {
+        let relationship = |provider_type: &str, context: &str| ProviderRelationship {
+            provider_type: provider_type.to_string(),
+            component: "AreaCalculatorComponent".to_string(),
+            context: context.to_string(),
+            confidence: Confidence::High,
+        };
+        let relationships = vec![
+            relationship("RectangleArea", "MyContext<Foo,'a>"),
+            relationship("ScaledArea<RectangleArea>", "MyContext<Foo, 'a>"),
+            relationship("SquareArea", "MyContext<Bar, 'a>"),
+            relationship("ScaledArea<SquareArea>", "MyContext<Foo, 'a>"),
+        ];
+
+        let deduped: Vec<String> = deduplicate_provider_relationships(&relationships)
+            .into_iter()
+            .map(|rel| rel.provider_type)
+            .collect();
+
+        // The contexts are compared structurally, so only the same context makes a hop redundant
+        assert_eq!(
+            deduped,
+            vec![
+                "ScaledArea<RectangleArea>",
+                "SquareArea",
+                "ScaledArea<SquareArea>"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rank_mixed_errors() {
+        use crate::test_utils::add_diagnostics_from_json;
+
+        let mut db = crate::diagnostic_db::DiagnosticDatabase::new();
+        for fixture in [
+            "usage_area",
+            "density",
+            "base_area_2",
+            "undelegated_area",
+            "base_area",
+        ] {
+            add_diagnostics_from_json(
+                &mut db,
+                format!(
+                    "{}/../examples/src/{}.json",
+                    env!("CARGO_MANIFEST_DIR"),
+                    fixture
+                ),
+            );
+        }
+
+        let kinds: Vec<ErrorKind> = db
+            .get_active_entries()
+            .iter()
+            .map(|entry| entry.kind())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ErrorKind::MissingField,
+                ErrorKind::MissingHasField,
+                ErrorKind::MissingDelegation,
+                ErrorKind::UnsatisfiedProvider,
+                ErrorKind::UnsatisfiedBound,
+            ]
+        );
+
+        // The rendered errors follow the same order
+        let rendered = db.render_cgp_errors();
+        assert!(rendered[0].contains("base_area.rs"));
+        assert!(rendered[4].contains("usage_area.rs"));
+    }
+
+    #[test]
+    fn test_competing_entries_at_one_location() {
+        use crate::test_utils::load_diagnostic_database;
+
+        let load_entry = |fixture: &str| {
+            let json_path = format!(
+                "{}/../examples/src/{}.json",
+                env!("CARGO_MANIFEST_DIR"),
+                fixture
+            );
+            load_diagnostic_database(json_path).get_active_entries()[0].clone()
+        };
+        let missing_field = load_entry("base_area");
+
+        // The same failure, seen only as an unsatisfied provider
+        let mut unsatisfied_provider = missing_field.clone();
+        unsatisfied_provider.field_info = None;
+        unsatisfied_provider.is_root_cause = false;
+        assert_eq!(unsatisfied_provider.kind(), ErrorKind::UnsatisfiedProvider);
+
+        // And a generic bound reported at the same location
+        let mut unsatisfied_bound = load_entry("usage_area");
+        unsatisfied_bound.add_primary_span(missing_field.primary_span().unwrap().clone());
+        assert_eq!(unsatisfied_bound.kind(), ErrorKind::UnsatisfiedBound);
+
+        let entries = [&unsatisfied_bound, &missing_field, &unsatisfied_provider];
+        assert!(!is_transitive_failure(&missing_field, &entries));
+        assert!(is_transitive_failure(&unsatisfied_provider, &entries));
+        assert!(is_transitive_failure(&unsatisfied_bound, &entries));
+
+        // Without a root cause, neither of the remaining entries wins over the other
+        let entries = [&unsatisfied_bound, &unsatisfied_provider];
+        assert!(!is_transitive_failure(&unsatisfied_provider, &entries));
+        assert!(!is_transitive_failure(&unsatisfied_bound, &entries));
+
+        let mut ranked = entries.to_vec();
+        rank_by_causal_priority(&mut ranked);
+        assert_eq!(ranked[0].kind(), ErrorKind::UnsatisfiedProvider);
+    }
+}
@@ -0,0 +1,364 @@
+/// Module for loading the source code shown in diagnostic snippets
+/// Source files may be unreadable (sandboxed CI, remote builds, permission problems).
+/// In that case the snippet is rebuilt from the span text that rustc embeds in its
+/// JSON output, keeping the real line numbers and marking the source as unavailable.
+#[cfg(feature = "graphical")]
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::bail;
+
+#[cfg(feature = "graphical")]
+use miette::{
+    LabeledSpan, MietteError, MietteSpanContents, NamedSource, SourceCode, SourceOffset,
+    SourceSpan, SpanContents,
+};
+
+use crate::cgp_diagnostic::SpanLocation;
+
+/// Source code shown in a diagnostic snippet
+#[cfg(feature = "graphical")]
+#[derive(Debug, Clone)]
+pub enum SnippetSource {
+    /// The full content of the source file
+    File(String),
+    /// Only the lines covered by the spans, as embedded in rustc's JSON output
+    SpanText {
+        /// The covered lines, joined with newlines
+        text: String,
+        /// The line number of the first line in `text` (1-indexed)
+        first_line: usize,
+    },
+}
+
+#[cfg(feature = "graphical")]
+impl SourceCode for SnippetSource {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        match self {
+            SnippetSource::File(content) => {
+                content.read_span(span, context_lines_before, context_lines_after)
+            }
+            SnippetSource::SpanText { text, first_line } => {
+                let contents = text.read_span(span, context_lines_before, context_lines_after)?;
+
+                // Shift the line numbers so that they match the real source file
+                Ok(Box::new(MietteSpanContents::new(
+                    contents.data(),
+                    *contents.span(),
+                    contents.line() + first_line.saturating_sub(1),
+                    contents.column(),
+                    contents.line_count(),
+                )))
+            }
+        }
+    }
+}
+
+/// Result of probing whether a source file can be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceAccess {
+    /// The file was found and read
+    Readable(String),
+    /// The file could not be read; the reason is kept for diagnostics
+    Unavailable(String),
+}
+
+/// A rule mapping a path prefix in diagnostics to a local directory
+/// Mirrors rustc's `--remap-path-prefix FROM=TO`, applied in reverse: paths reported
+/// as `FROM/...` (e.g., from a container build) are read from `TO/...` locally
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathRemap {
+    /// The path prefix as it appears in the diagnostics
+    pub from: String,
+    /// The local directory replacing the prefix
+    pub to: PathBuf,
+}
+
+impl FromStr for PathRemap {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() => Ok(PathRemap {
+                from: from.to_string(),
+                to: PathBuf::from(to),
+            }),
+            _ => bail!("Invalid path remapping `{}` (expected `FROM=TO`)", s),
+        }
+    }
+}
+
+/// Environment variable with path remapping rules, separated by `;`
+pub const REMAP_PATH_PREFIX_ENV: &str = "CARGO_CGP_REMAP_PATH_PREFIX";
+
+/// Reads path remapping rules from the `CARGO_CGP_REMAP_PATH_PREFIX` environment variable
+pub fn path_remaps_from_env() -> anyhow::Result<Vec<PathRemap>> {
+    let Ok(value) = std::env::var(REMAP_PATH_PREFIX_ENV) else {
+        return Ok(Vec::new());
+    };
+
+    value
+        .split(';')
+        .filter(|rule| !rule.trim().is_empty())
+        .map(|rule| rule.trim().parse())
+        .collect()
+}
+
+/// Applies the remapping rules to a file name from a diagnostic
+/// Like rustc, the last matching rule wins
+pub fn remap_path(remaps: &[PathRemap], file_name: &str) -> Option<PathBuf> {
+    remaps.iter().rev().find_map(|remap| {
+        let rest = Path::new(file_name).strip_prefix(&remap.from).ok()?;
+        Some(remap.to.join(rest))
+    })
+}
+
+/// Returns the candidate paths for a span's source file
+/// An explicit source path is used as-is; otherwise the file name reported by rustc
+/// might be absolute or relative to the workspace root
+fn candidate_paths(span: &SpanLocation) -> Vec<PathBuf> {
+    if let Some(source_path) = &span.source_path {
+        return vec![source_path.clone()];
+    }
+
+    let file_name = &span.file;
+    let mut candidates = vec![PathBuf::from(file_name)];
+
+    if let Ok(current_dir) = std::env::current_dir() {
+        // Try current directory first
+        candidates.push(current_dir.join(file_name));
+
+        // Try parent directory (in case we're in a subdirectory)
+        if let Some(parent) = current_dir.parent() {
+            candidates.push(parent.join(file_name));
+        }
+    }
+
+    candidates
+}
+
+/// Probes whether the span's source file can be read, returning its content if so
+pub fn probe_source_access(span: &SpanLocation) -> SourceAccess {
+    let mut last_error = None;
+
+    for candidate in candidate_paths(span) {
+        match std::fs::read_to_string(&candidate) {
+            Ok(content) => return SourceAccess::Readable(content),
+            // Prefer reporting errors like permission denied over a plain "not found"
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                last_error = Some(error.to_string());
+            }
+            Err(error) => {
+                last_error.get_or_insert(error.to_string());
+            }
+        }
+    }
+
+    SourceAccess::Unavailable(last_error.unwrap_or_else(|| "file not found".to_string()))
+}
+
+/// Builds source code and labeled spans for the given span locations
+/// This reads the source file, so it is only called when a snippet is rendered
+#[cfg(feature = "graphical")]
+pub fn build_source_and_labels(
+    spans: &[SpanLocation],
+) -> (Option<NamedSource<SnippetSource>>, Vec<LabeledSpan>) {
+    // Use the first span to determine the file
+    let Some(first_span) = spans.first() else {
+        return (None, vec![]);
+    };
+
+    // Label IDs are only shown when they are needed to tell labels apart
+    let show_ids = spans.len() > 1;
+
+    match probe_source_access(first_span) {
+        SourceAccess::Readable(file_content) => {
+            // Create a labeled span for each span, using offsets in the actual file
+            let labels = spans
+                .iter()
+                .enumerate()
+                .filter(|(_, span)| span.file == first_span.file)
+                .map(|(index, span)| {
+                    let start =
+                        line_column_to_offset(&file_content, span.line_start, span.column_start);
+                    let end = line_column_to_offset(&file_content, span.line_end, span.column_end);
+                    labeled_span(span, show_ids.then_some(index + 1), start, end)
+                })
+                .collect();
+
+            let source_code = NamedSource::new(&first_span.file, SnippetSource::File(file_content));
+
+            (Some(source_code), labels)
+        }
+        SourceAccess::Unavailable(_) => build_span_text_source(spans, &first_span.file, show_ids),
+    }
+}
+
+/// Degraded mode: rebuilds the snippet from the span text embedded in rustc's JSON output
+/// Only spans in the same file with embedded text are shown, so every offset is exact
+#[cfg(feature = "graphical")]
+fn build_span_text_source(
+    spans: &[SpanLocation],
+    file: &str,
+    show_ids: bool,
+) -> (Option<NamedSource<SnippetSource>>, Vec<LabeledSpan>) {
+    let spans: Vec<(usize, &SpanLocation)> = spans
+        .iter()
+        .enumerate()
+        .filter(|(_, span)| span.file == file && !span.text.is_empty())
+        .collect();
+
+    // Collect the known lines of the file by line number
+    let mut known_lines: BTreeMap<usize, &str> = BTreeMap::new();
+    for (_, span) in &spans {
+        for (i, line) in span.text.iter().enumerate() {
+            known_lines.insert(span.line_start + i, line);
+        }
+    }
+
+    let (Some(&first_line), Some(&last_line)) =
+        (known_lines.keys().next(), known_lines.keys().next_back())
+    else {
+        // If we have no source text at all, just return nothing
+        return (None, vec![]);
+    };
+
+    // Lines between spans that rustc did not include are left empty
+    let text = (first_line..=last_line)
+        .map(|line| known_lines.get(&line).copied().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let labels = spans
+        .iter()
+        .map(|(index, span)| {
+            let start =
+                line_column_to_offset(&text, span.line_start + 1 - first_line, span.column_start);
+            let end = line_column_to_offset(&text, span.line_end + 1 - first_line, span.column_end);
+            labeled_span(span, show_ids.then_some(index + 1), start, end)
+        })
+        .collect();
+
+    let source_code = NamedSource::new(
+        format!("{} (source unavailable)", file),
+        SnippetSource::SpanText { text, first_line },
+    );
+
+    (Some(source_code), labels)
+}
+
+/// Creates the labeled span for a span location between the given byte offsets
+/// The length is taken from the offsets, so that spans over several lines are covered
+/// The label is prefixed with its ID (e.g., "[2] unsatisfied trait bound") if one is given
+#[cfg(feature = "graphical")]
+fn labeled_span(span: &SpanLocation, id: Option<usize>, start: usize, end: usize) -> LabeledSpan {
+    let span_length = end.saturating_sub(start).max(1);
+    let label = match id {
+        Some(id) => format!("[{}] {}", id, span.label),
+        None => span.label.clone(),
+    };
+
+    LabeledSpan::new_with_span(
+        Some(label),
+        SourceSpan::new(SourceOffset::from(start), span_length),
+    )
+}
+
+/// Converts a 1-indexed line and column into a byte offset within the source content
+#[cfg(feature = "graphical")]
+fn line_column_to_offset(content: &str, line: usize, column: usize) -> usize {
+    let mut byte_offset = 0;
+
+    // Add bytes for all lines before the target line (1-indexed)
+    for (line_idx, line_text) in content.lines().enumerate() {
+        if line_idx + 1 < line {
+            byte_offset += line_text.len() + 1; // +1 for newline
+        } else {
+            break;
+        }
+    }
+
+    // Add column offset (1-indexed, so subtract 1)
+    byte_offset + column.saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "graphical")]
+    fn span(line: usize, column_start: usize, column_end: usize, text: &str) -> SpanLocation {
+        SpanLocation {
+            file: "missing/shapes.rs".to_string(),
+            line_start: line,
+            column_start,
+            line_end: line,
+            column_end,
+            label: "unsatisfied trait bound".to_string(),
+            text: vec![text.to_string()],
+            source_path: None,
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "graphical")]
+    fn test_unavailable_source_uses_span_text() {
+        use crate::cgp_diagnostic::CgpDiagnostic;
+        use crate::error_formatting::render_diagnostic_plain;
+
+        let spans = vec![
+            span(41, 9, 32, "        AreaCalculatorComponent,"),
+            span(42, 9, 35, "        DensityCalculatorComponent,"),
+        ];
+
+        assert!(matches!(
+            probe_source_access(&spans[0]),
+            SourceAccess::Unavailable(_)
+        ));
+
+        let rendered = render_diagnostic_plain(&CgpDiagnostic::new("error".to_string(), spans));
+
+        assert!(rendered.contains("missing/shapes.rs (source unavailable)"));
+        assert!(rendered.contains("41 |         AreaCalculatorComponent,"));
+        assert!(rendered.contains("42 |         DensityCalculatorComponent,"));
+    }
+
+    #[test]
+    #[cfg(feature = "graphical")]
+    fn test_multi_line_span_length() {
+        let mut block = span(36, 1, 14, "delegate_components! {");
+        block.line_end = 37;
+        block.text.push("    Rectangle {".to_string());
+
+        let (_, labels) = build_source_and_labels(&[block]);
+
+        // The span covers the first line and its newline, up to column 14 of the second
+        assert_eq!(labels[0].offset(), 0);
+        assert_eq!(labels[0].len(), "delegate_components! {\n".len() + 13);
+    }
+
+    #[test]
+    fn test_remap_path() {
+        let remaps: Vec<PathRemap> = vec![
+            "/build=/home/user/project".parse().unwrap(),
+            "/build/vendor=/opt/vendor".parse().unwrap(),
+        ];
+
+        assert_eq!(
+            remap_path(&remaps, "/build/src/lib.rs"),
+            Some(PathBuf::from("/home/user/project/src/lib.rs"))
+        );
+        assert_eq!(
+            remap_path(&remaps, "/build/vendor/cgp/src/lib.rs"),
+            Some(PathBuf::from("/opt/vendor/cgp/src/lib.rs"))
+        );
+        assert_eq!(remap_path(&remaps, "/buildx/src/lib.rs"), None);
+        assert!("no-separator".parse::<PathRemap>().is_err());
+    }
+}
@@ -0,0 +1,144 @@
+/// Module for caching field names decoded from `Symbol<N, Chars<...>>` types
+/// Many diagnostics mention the same field, so each symbol type is decoded once per run.
+/// The cache also records how each name was resolved, for verifying decisions in debug output.
+use std::collections::BTreeMap;
+
+use crate::cgp_patterns::{extract_field_name_from_symbol, extract_symbol_type};
+
+/// How a field name was reconstructed from a symbol type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolResolution {
+    /// Every character of the symbol was visible
+    Complete,
+    /// The compiler shortened the type, so only a prefix of the name is known
+    Truncated,
+    /// Some characters were hidden by the compiler (shown as `_`)
+    HiddenChars,
+}
+
+impl SymbolResolution {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SymbolResolution::Complete => "complete",
+            SymbolResolution::Truncated => "truncated",
+            SymbolResolution::HiddenChars => "hidden characters",
+        }
+    }
+}
+
+/// A field name resolved from a symbol type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedSymbol {
+    pub field_name: String,
+    /// Whether the field name was fully extracted (false if truncated)
+    pub is_complete: bool,
+    /// Whether the field name contains unknown characters (shown as �)
+    pub has_unknown_chars: bool,
+    pub resolution: SymbolResolution,
+    /// Number of times the symbol was looked up
+    pub uses: usize,
+}
+
+/// Per-run cache from symbol type strings to resolved field names
+#[derive(Debug, Clone, Default)]
+pub struct SymbolCache {
+    /// Ordered by symbol type, so that debug output is stable
+    entries: BTreeMap<String, ResolvedSymbol>,
+}
+
+impl SymbolCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the field name of the first symbol type in a message
+    pub fn resolve(&mut self, message: &str) -> Option<&ResolvedSymbol> {
+        let symbol_type = extract_symbol_type(message)?;
+
+        if !self.entries.contains_key(symbol_type) {
+            let (field_name, is_complete, has_unknown_chars) =
+                extract_field_name_from_symbol(symbol_type)?;
+
+            let resolution = if has_unknown_chars {
+                SymbolResolution::HiddenChars
+            } else if !is_complete {
+                SymbolResolution::Truncated
+            } else {
+                SymbolResolution::Complete
+            };
+
+            self.entries.insert(
+                symbol_type.to_string(),
+                ResolvedSymbol {
+                    field_name,
+                    is_complete,
+                    has_unknown_chars,
+                    resolution,
+                    uses: 0,
+                },
+            );
+        }
+
+        let entry = self.entries.get_mut(symbol_type)?;
+        entry.uses += 1;
+        Some(entry)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Renders one line per cached symbol, for debug output
+    pub fn render_debug(&self) -> String {
+        let mut output = String::new();
+
+        for (symbol_type, symbol) in &self.entries {
+            output.push_str(&format!(
+                "debug: symbol `{}` resolved to field `{}` ({}, used {} times)\n",
+                symbol_type,
+                symbol.field_name,
+                symbol.resolution.as_str(),
+                symbol.uses
+            ));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_cache() {
+        let mut cache = SymbolCache::new();
+        let message = "the trait `HasField<Symbol<2, Chars<'i', Chars<'d', Nil>>>>` is not implemented for `User`";
+
+        let symbol = cache.resolve(message).unwrap();
+        assert_eq!(symbol.field_name, "id");
+        assert_eq!(symbol.resolution, SymbolResolution::Complete);
+
+        // The same symbol mentioned for another type reuses the entry
+        let other = message.replace("User", "Admin");
+        assert_eq!(cache.resolve(&other).unwrap().uses, 2);
+        assert_eq!(cache.len(), 1);
+
+        let truncated = "`HasField<Symbol<6, Chars<'w', Chars<'i', ...>>>>` is not implemented";
+        assert_eq!(
+            cache.resolve(truncated).unwrap().resolution,
+            SymbolResolution::Truncated
+        );
+
+        assert_eq!(
+            cache.render_debug().lines().next(),
+            Some(
+                "debug: symbol `Symbol<2, Chars<'i', Chars<'d', Nil>>>` resolved to field `id` (complete, used 2 times)"
+            )
+        );
+    }
+}
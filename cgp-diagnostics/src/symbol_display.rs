@@ -0,0 +1,110 @@
+/// Module for the final display pass over the text of improved diagnostics
+/// The formatters decode the `Symbol` types they recognize, but text copied from compiler
+/// messages (e.g., the headline of an unclassified error, the merged diagnostics shown with
+/// `--verbose` or the fallback for a diagnostic that could not be analyzed) still spells
+/// field names as `Symbol<6, Chars<'h', Chars<'e', ...>>>`. The pass rewrites every such
+/// type into the `Symbol!("height")` form that CGP code is written with.
+use crate::cgp_patterns::extract_field_name_from_symbol;
+use crate::field_name::{TRUNCATION_MARKER, is_identifier_char};
+use crate::type_expr::generic_arguments_at;
+
+/// Replaces the `Symbol<N, Chars<...>>` types in a text with `Symbol!("name")`, keeping the
+/// text unchanged where a type cannot be decoded
+/// Names that the compiler cut off end in `…`.
+pub fn display_symbols(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(position) = rest.find("Symbol<") {
+        let (before, symbol) = rest.split_at(position);
+        // The path of the type (e.g., `cgp::prelude::`) is dropped with it
+        let path_start = path_prefix_start(before);
+        let Some(end) = generic_arguments_at(symbol, "Symbol<".len())
+            .value()
+            .map(|list| list.end)
+            .filter(|_| !is_identifier_char_before(&before[..path_start]))
+        else {
+            result.push_str(before);
+            result.push_str("Symbol<");
+            rest = &symbol["Symbol<".len()..];
+            continue;
+        };
+
+        match extract_field_name_from_symbol(&symbol[..end]) {
+            Some((field_name, is_complete, _)) => {
+                result.push_str(&before[..path_start]);
+                let field_name = if is_complete {
+                    field_name
+                } else {
+                    format!("{}{}", field_name, TRUNCATION_MARKER)
+                };
+                result.push_str(&format!("Symbol!({:?})", field_name));
+            }
+            None => {
+                result.push_str(before);
+                result.push_str(&symbol[..end]);
+            }
+        }
+        rest = &symbol[end..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// The start of the path segments (e.g., `cgp::prelude::`) that end a text
+fn path_prefix_start(text: &str) -> usize {
+    let mut start = text.len();
+    while let Some(segment_end) = text[..start].strip_suffix("::").map(str::len) {
+        let segment_start = text[..segment_end]
+            .rfind(|c: char| !is_identifier_char(c))
+            .map_or(0, |position| position + 1);
+        if segment_start == segment_end {
+            break;
+        }
+        start = segment_start;
+    }
+    start
+}
+
+/// Returns true if a text ends with an identifier character, in which case a `Symbol<`
+/// after it is part of a longer name (e.g., `MySymbol<`)
+fn is_identifier_char_before(text: &str) -> bool {
+    text.chars().next_back().is_some_and(is_identifier_char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_symbols() {
+        assert_eq!(
+            display_symbols(
+                "the trait `cgp::prelude::HasField<Symbol<6, cgp::prelude::Chars<'h', \
+                 cgp::prelude::Chars<'e', cgp::prelude::Chars<'i', cgp::prelude::Chars<'g', \
+                 cgp::prelude::Chars<'h', cgp::prelude::Chars<'t', Nil>>>>>>>>` is not \
+                 implemented for `Rectangle`"
+            ),
+            "the trait `cgp::prelude::HasField<Symbol!(\"height\")>` is not implemented for `Rectangle`"
+        );
+
+        // Elided and hidden characters, several symbols, and characters that look like brackets
+        assert_eq!(
+            display_symbols(
+                "`HasField<Symbol<5, Chars<'w', Chars<'i', ...>>>>` and \
+                 `HasField<cgp::Symbol<2, Chars<'>', Chars<_, Nil>>>>`"
+            ),
+            "`HasField<Symbol!(\"wi…\")>` and `HasField<Symbol!(\">\u{FFFD}\")>`"
+        );
+
+        // Text without symbols, and names that only end in `Symbol`, are unchanged
+        for text in [
+            "the trait bound `Rectangle: CanCalculateArea` is not satisfied",
+            "`MySymbol<5, u8>`",
+            "`Symbol<N, Chars>`",
+        ] {
+            assert_eq!(display_symbols(text), text);
+        }
+    }
+}
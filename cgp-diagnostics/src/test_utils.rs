@@ -0,0 +1,380 @@
+use crate::cgp_patterns::is_cgp_diagnostic;
+use crate::diagnostic_db::DiagnosticDatabase;
+use crate::error_formatting::{NodeKind, render_diagnostic_plain};
+use crate::name_resolution::{NameResolver, ResolutionMode};
+use crate::workspace_scan::WorkspaceScan;
+use cargo_metadata::Message;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Helper function to run a CGP error test from a JSON file
+pub fn test_cgp_error_from_json(json_filename: &str, test_name: &str) -> Vec<String> {
+    // Read the JSON fixture (newline-delimited JSON)
+    let json_path = format!(
+        "{}/../examples/src/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        json_filename
+    );
+
+    println!("\n=== Testing {} ===", test_name);
+    println!("Reading JSON from: {}", json_path);
+
+    let mut output_lines = Vec::new();
+
+    let mut db = load_diagnostic_database(&json_path);
+
+    let cgp_diagnostics = db.render_cgp_diagnostics();
+    for diagnostic in cgp_diagnostics {
+        let rendered = render_diagnostic_plain(&diagnostic);
+        println!("{}", rendered);
+        output_lines.push(rendered);
+    }
+
+    // Return the output for snapshot testing
+    output_lines
+}
+
+/// Loads all CGP diagnostics from a file of cargo JSON messages into a database
+pub fn load_diagnostic_database(json_path: impl AsRef<Path>) -> DiagnosticDatabase {
+    let mut db = DiagnosticDatabase::new();
+    add_diagnostics_from_json(&mut db, json_path);
+    db
+}
+
+/// Adds all CGP diagnostics from a file of cargo JSON messages to a database
+pub fn add_diagnostics_from_json(db: &mut DiagnosticDatabase, json_path: impl AsRef<Path>) {
+    let json_path = json_path.as_ref();
+    let file =
+        File::open(json_path).unwrap_or_else(|_| panic!("Failed to open {}", json_path.display()));
+
+    let reader = BufReader::new(file);
+
+    for message in Message::parse_stream(reader) {
+        if let Message::CompilerMessage(msg) = message.expect("Failed to parse message")
+            && is_cgp_diagnostic(&msg.message)
+        {
+            db.add_diagnostic(&msg);
+        }
+    }
+}
+
+/// The directory of the example sources and their JSON fixtures
+pub fn examples_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples/src")
+}
+
+/// Scans the content of an example source file (`examples/src/<name>.rs`) into a
+/// component index, as if it were the whole workspace
+/// The index shows the workspace-relative path, and snippets read the file from the disk
+pub fn scan_example(name: &str, content: &str) -> WorkspaceScan {
+    let display_path = format!("examples/src/{}.rs", name);
+    let mut scan = WorkspaceScan::new();
+    scan.scan_file(
+        &examples_dir().join(format!("{}.rs", name)),
+        Path::new(&display_path),
+        "cgp-error-messages-example",
+        &format!("crate::{}", name),
+        content,
+    );
+    scan
+}
+
+/// Loads the diagnostics of an example (`examples/src/<name>.json`) with the component
+/// index of its source file, which resolves the names of the diagnostics
+pub fn load_example_with_index(name: &str) -> (DiagnosticDatabase, WorkspaceScan) {
+    let content = std::fs::read_to_string(examples_dir().join(format!("{}.rs", name)))
+        .unwrap_or_else(|_| panic!("Failed to read the example `{}`", name));
+    let scan = scan_example(name, &content);
+
+    let mut db = load_diagnostic_database(examples_dir().join(format!("{}.json", name)));
+    db.set_name_resolver(NameResolver::from_scan(&scan, ResolutionMode::Heuristic));
+    (db, scan)
+}
+
+/// Lines of rustc's rendered message that are missing from the improved message, and
+/// lines of the improved message that rustc did not show
+#[derive(Debug, Default, PartialEq)]
+pub struct MessageDiff {
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+impl MessageDiff {
+    /// Renders the diff with `-` and `+` markers, removed lines first
+    pub fn render(&self) -> String {
+        self.removed
+            .iter()
+            .map(|line| format!("- {}", line))
+            .chain(self.added.iter().map(|line| format!("+ {}", line)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Computes a line-oriented diff between rustc's rendered message and the improved one
+///
+/// Lines are compared by the code identifiers they mention, regardless of their order and
+/// wording, since the improved message regroups and rephrases the information. A line is
+/// only reported when some identifier in it is missing from the other message, or when it
+/// mentions no identifiers and its text is missing. Module paths and single characters are
+/// ignored, since the improved message deliberately shortens paths and field name symbols.
+pub fn diff_messages(original: &str, improved: &str) -> MessageDiff {
+    MessageDiff {
+        removed: missing_lines(original, improved),
+        added: missing_lines(improved, original),
+    }
+}
+
+/// Lines of `from` with information that does not appear anywhere in `to`
+fn missing_lines(from: &str, to: &str) -> Vec<String> {
+    let to_identifiers: Vec<String> = to.lines().flat_map(code_identifiers).collect();
+    let to_lines: Vec<String> = to.lines().map(normalize_line).collect();
+
+    let mut lines: Vec<String> = Vec::new();
+    for line in from.lines() {
+        let normalized = normalize_line(line);
+        if !normalized.chars().any(|c| c.is_alphanumeric()) || lines.contains(&normalized) {
+            continue;
+        }
+
+        let identifiers = code_identifiers(line);
+        let missing = if identifiers.is_empty() {
+            !to_lines.contains(&normalized)
+        } else {
+            identifiers
+                .iter()
+                .any(|ident| !to_identifiers.contains(ident))
+        };
+
+        if missing {
+            lines.push(normalized);
+        }
+    }
+    lines
+}
+
+/// Strips the line numbers, gutters and label markers of rustc and miette snippets
+fn normalize_line(line: &str) -> String {
+    let mut rest = line.trim();
+
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 && rest[digits..].starts_with([' ', '|']) {
+        rest = &rest[digits..];
+    }
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(path) = rest.strip_prefix(",-[") {
+            rest = path.strip_suffix(']').unwrap_or(path);
+        } else if let Some(label) = rest.strip_prefix("`-") {
+            rest = label;
+        } else if rest.starts_with(['|', ':', '=', '>', '-', '^', '/', '_']) {
+            rest = &rest[1..];
+        } else {
+            break;
+        }
+    }
+
+    rest.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Keywords, and CGP plumbing that the improved message deliberately describes in words
+const IGNORED_IDENTIFIERS: &[&str] = &[
+    "CanUseComponent",
+    "Chars",
+    "IsProviderFor",
+    "Nil",
+    "Self",
+    "Symbol",
+    "__Context__",
+    "derive",
+    "fn",
+    "impl",
+    "new",
+    "pub",
+    "struct",
+    "trait",
+    "where",
+];
+
+/// The identifiers quoted in backticks on a message line, or all identifiers of a source
+/// snippet line, excluding module path segments
+fn code_identifiers(line: &str) -> Vec<String> {
+    let trimmed = line.trim_start();
+    let is_snippet = trimmed.split_once('|').is_some_and(|(gutter, _)| {
+        let gutter = gutter.trim();
+        !gutter.is_empty() && gutter.chars().all(|c| c.is_ascii_digit())
+    });
+
+    let normalized = normalize_line(line);
+    let code: Vec<&str> = if is_snippet {
+        vec![trimmed.split_once('|').map_or("", |(_, code)| code)]
+    } else {
+        let quoted: Vec<&str> = normalized.split('`').collect();
+        // An unpaired backtick does not start a code span
+        quoted[..quoted.len() - (1 - quoted.len() % 2)]
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .copied()
+            .collect()
+    };
+
+    let mut identifiers = Vec::new();
+    for code in code {
+        let mut rest = code;
+        while let Some(start) = rest.find(|c: char| c.is_alphanumeric() || c == '_') {
+            let after = &rest[start..];
+            let end = after
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            let ident = &after[..end];
+            rest = &after[end..];
+
+            if ident.chars().count() > 1
+                && !ident.chars().all(|c| c.is_ascii_digit())
+                && !rest.starts_with("::")
+                && !IGNORED_IDENTIFIERS.contains(&ident)
+            {
+                identifiers.push(ident.to_string());
+            }
+        }
+    }
+    identifiers
+}
+
+/// Diffs rustc's rendered CGP errors in a JSON fixture against the improved messages
+pub fn diff_cgp_error_from_json(json_filename: &str) -> MessageDiff {
+    let json_path = format!(
+        "{}/../examples/src/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        json_filename
+    );
+
+    let file = File::open(&json_path).unwrap_or_else(|_| panic!("Failed to open {}", json_path));
+    let original = Message::parse_stream(BufReader::new(file))
+        .filter_map(|message| match message.expect("Failed to parse message") {
+            Message::CompilerMessage(msg) if is_cgp_diagnostic(&msg.message) => {
+                msg.message.rendered
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let improved = load_diagnostic_database(&json_path)
+        .render_cgp_diagnostics()
+        .iter()
+        .map(render_diagnostic_plain)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    diff_messages(&original, &improved)
+}
+
+/// Asserts that some dependency tree built from the JSON fixture contains the given hops
+/// Each hop is a (kind, name) pair, where kind is one of "check", "usage", "consumer", "provider",
+/// "getter" or "field", and name is a substring of the node description.
+/// This is the implementation of the `expect_chain!` macro.
+pub fn assert_dependency_chain(json_path: impl AsRef<Path>, hops: &[(&str, &str)]) {
+    let json_path = json_path.as_ref();
+    let trees = load_diagnostic_database(json_path).dependency_trees();
+
+    let kind_hops: Vec<(NodeKind, &str)> = hops
+        .iter()
+        .map(|(kind, name)| {
+            let kind = NodeKind::from_name(kind)
+                .unwrap_or_else(|| panic!("Unknown dependency node kind `{}`", kind));
+            (kind, *name)
+        })
+        .collect();
+
+    if trees.iter().any(|tree| tree.contains_chain(&kind_hops)) {
+        return;
+    }
+
+    let expected = hops
+        .iter()
+        .map(|(kind, name)| format!("{} `{}`", kind, name))
+        .collect::<Vec<_>>()
+        .join(" -> ");
+
+    let rendered = trees
+        .iter()
+        .map(|tree| tree.render().join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    panic!(
+        "No dependency chain in {} contains: {}\n\nDependency trees:\n{}",
+        json_path.display(),
+        expected,
+        rendered
+    );
+}
+
+/// Asserts that a JSON fixture of cargo messages produces a dependency chain with the given hops
+///
+/// The fixture path is resolved relative to the manifest directory of the calling crate.
+/// Hops are written as a node kind (`check`, `consumer`, `provider`, `getter` or `field`)
+/// followed by a name that must appear in the node, in the order they occur in the chain.
+/// Intermediate nodes may be skipped.
+///
+/// ```ignore
+/// cgp_diagnostics::expect_chain!("fixtures/base_area.json" =>
+///     check "CanUseRectangle",
+///     provider "RectangleArea",
+///     field "height",
+/// );
+/// ```
+#[macro_export]
+macro_rules! expect_chain {
+    ($json_path:expr => $($kind:ident $name:expr),+ $(,)?) => {
+        $crate::test_utils::assert_dependency_chain(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($json_path),
+            &[$((stringify!($kind), $name)),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_messages() {
+        let original = "\
+error[E0277]: the trait bound `Rectangle: cgp::prelude::CanUseComponent<AreaCalculatorComponent>` is not satisfied
+  --> src/lib.rs:41:9
+   |
+41 |         AreaCalculatorComponent,
+   |         ^^^^^^^^^^^^^^^^^^^^^^^ unsatisfied trait bound
+note: required for `RectangleArea` to implement `cgp::prelude::IsProviderFor<AreaCalculatorComponent, Rectangle>`
+18 |     Self: HasRectangleFields,
+   |           ------------------ unsatisfied trait bound introduced here";
+
+        let improved = "\
+  x missing field `height` in the context `Rectangle`.
+    ,-[src/lib.rs:41:9]
+ 41 |         AreaCalculatorComponent,
+    :         ^^^^^^^^^^^|^^^^^^^^^^^
+    :                    `-- unsatisfied trait bound
+  help: `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)";
+
+        assert_eq!(
+            diff_messages(original, improved),
+            MessageDiff {
+                removed: vec![
+                    "Self: HasRectangleFields,".to_string(),
+                    "unsatisfied trait bound introduced here".to_string(),
+                ],
+                added: vec![
+                    "x missing field `height` in the context `Rectangle`.".to_string(),
+                    "help: `AreaCalculator<Rectangle>` for provider `RectangleArea` (provider trait)"
+                        .to_string(),
+                ],
+            }
+        );
+    }
+}
@@ -0,0 +1,1253 @@
+/// Module for parsing the types spelled out in compiler messages
+/// rustc prints types with nested generics, lifetimes, tuples, function pointers and
+/// `dyn`/`impl` bounds, and elides parts of long types with `...`. Messages are tokenized
+/// and types are parsed by recursive descent, so that `->`, char literals such as `'>'` in
+/// `Symbol` types and the commas of tuples never end a generic argument list.
+use std::fmt;
+use std::ops::Range;
+
+use crate::cgp_patterns::ParseOutcome;
+use crate::field_name::is_identifier_char;
+
+/// A type (or generic argument) as printed by rustc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeExpr {
+    /// A path such as `cgp::prelude::Symbol<5, Chars<'w', Nil>>` or `Fn(u8) -> u8`
+    Path(Vec<PathSegment>),
+    /// A qualified path such as `<Rectangle as HasField<Tag>>::Value`
+    Qualified {
+        self_type: Box<TypeExpr>,
+        trait_path: Option<Box<TypeExpr>>,
+        segments: Vec<PathSegment>,
+    },
+    /// `&'a mut T`
+    Reference {
+        lifetime: Option<String>,
+        is_mut: bool,
+        inner: Box<TypeExpr>,
+    },
+    /// `*const T` or `*mut T`
+    Pointer { is_mut: bool, inner: Box<TypeExpr> },
+    /// `(A, B)`, `(A,)` or `()`
+    Tuple(Vec<TypeExpr>),
+    /// A type in parentheses, such as `(dyn Any + Send)` in `&(dyn Any + Send)`
+    Paren(Box<TypeExpr>),
+    /// `[T]`
+    Slice(Box<TypeExpr>),
+    /// `[T; N]`
+    Array(Box<TypeExpr>, Box<TypeExpr>),
+    /// `unsafe extern "C" fn(A) -> B`, with the name of a function item (e.g., `{area}`)
+    FnPointer {
+        qualifiers: Vec<String>,
+        inputs: Vec<TypeExpr>,
+        output: Option<Box<TypeExpr>>,
+        item: Option<String>,
+    },
+    /// `dyn A + B` or `impl A + B`
+    TraitObject {
+        is_impl: bool,
+        bounds: Vec<TypeExpr>,
+    },
+    /// `for<'a> fn(&'a u8)`
+    HigherRanked {
+        lifetimes: Vec<String>,
+        inner: Box<TypeExpr>,
+    },
+    /// `?Sized` in a list of bounds
+    Relaxed(Box<TypeExpr>),
+    /// An associated type binding such as `Output = T`
+    Binding { name: String, value: Box<TypeExpr> },
+    /// `'static`
+    Lifetime(String),
+    /// A const argument or a type that rustc prints in braces, such as `3`, `'w'`,
+    /// `{ N + 1 }` or `{closure@src/lib.rs:3:5}`
+    Literal(String),
+    /// `!`
+    Never,
+    /// `_`
+    Infer,
+    /// `...`, where the compiler elided part of the type
+    Elided,
+}
+
+/// A segment of a path, with its generic arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSegment {
+    pub name: String,
+    pub arguments: PathArguments,
+}
+
+/// The generic arguments of a path segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathArguments {
+    None,
+    /// `<A, B>`
+    AngleBracketed(Vec<TypeExpr>),
+    /// `(A, B) -> C`, as in `Fn(A, B) -> C`
+    Parenthesized {
+        inputs: Vec<TypeExpr>,
+        output: Option<Box<TypeExpr>>,
+    },
+}
+
+impl TypeExpr {
+    /// The name of the last segment of a path
+    /// (e.g., "AreaCalculator" for `cgp::AreaCalculator<Rectangle>`)
+    pub fn name(&self) -> Option<&str> {
+        self.last_segment().map(|segment| segment.name.as_str())
+    }
+
+    /// The angle-bracketed generic arguments of the last segment of a path
+    pub fn generic_arguments(&self) -> &[TypeExpr] {
+        match self.last_segment().map(|segment| &segment.arguments) {
+            Some(PathArguments::AngleBracketed(arguments)) => arguments,
+            _ => &[],
+        }
+    }
+
+    /// Whether this is a type, as opposed to a lifetime, a const argument or a binding
+    pub fn is_type(&self) -> bool {
+        !matches!(
+            self,
+            TypeExpr::Lifetime(_) | TypeExpr::Literal(_) | TypeExpr::Binding { .. }
+        )
+    }
+
+    /// Replaces a generic argument of the path, however deeply it is nested
+    /// (e.g., `RectangleArea` with `SquareArea` in `ScaledArea<RectangleArea>`)
+    pub fn replace_type_argument(&self, from: &TypeExpr, to: &TypeExpr) -> TypeExpr {
+        let mut replaced = self.clone();
+        if let TypeExpr::Path(segments) = &mut replaced
+            && let Some(PathSegment {
+                arguments: PathArguments::AngleBracketed(arguments),
+                ..
+            }) = segments.last_mut()
+        {
+            for argument in arguments.iter_mut() {
+                *argument = if argument == from {
+                    to.clone()
+                } else {
+                    argument.replace_type_argument(from, to)
+                };
+            }
+        }
+        replaced
+    }
+
+    fn last_segment(&self) -> Option<&PathSegment> {
+        match self {
+            TypeExpr::Path(segments) | TypeExpr::Qualified { segments, .. } => segments.last(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TypeExpr {
+    /// Prints the type the way rustc does
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeExpr::Path(segments) => write_separated(f, segments, "::"),
+            TypeExpr::Qualified {
+                self_type,
+                trait_path,
+                segments,
+            } => {
+                write!(f, "<{}", self_type)?;
+                if let Some(trait_path) = trait_path {
+                    write!(f, " as {}", trait_path)?;
+                }
+                f.write_str(">")?;
+                for segment in segments {
+                    write!(f, "::{}", segment)?;
+                }
+                Ok(())
+            }
+            TypeExpr::Reference {
+                lifetime,
+                is_mut,
+                inner,
+            } => {
+                f.write_str("&")?;
+                if let Some(lifetime) = lifetime {
+                    write!(f, "{} ", lifetime)?;
+                }
+                if *is_mut {
+                    f.write_str("mut ")?;
+                }
+                write!(f, "{}", inner)
+            }
+            TypeExpr::Pointer { is_mut, inner } => {
+                let mutability = if *is_mut { "mut" } else { "const" };
+                write!(f, "*{} {}", mutability, inner)
+            }
+            TypeExpr::Tuple(elements) => {
+                f.write_str("(")?;
+                write_separated(f, elements, ", ")?;
+                if elements.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+            TypeExpr::Paren(inner) => write!(f, "({})", inner),
+            TypeExpr::Slice(inner) => write!(f, "[{}]", inner),
+            TypeExpr::Array(inner, length) => write!(f, "[{}; {}]", inner, length),
+            TypeExpr::FnPointer {
+                qualifiers,
+                inputs,
+                output,
+                item,
+            } => {
+                for qualifier in qualifiers {
+                    write!(f, "{} ", qualifier)?;
+                }
+                f.write_str("fn(")?;
+                write_separated(f, inputs, ", ")?;
+                f.write_str(")")?;
+                if let Some(output) = output {
+                    write!(f, " -> {}", output)?;
+                }
+                if let Some(item) = item {
+                    write!(f, " {}", item)?;
+                }
+                Ok(())
+            }
+            TypeExpr::TraitObject { is_impl, bounds } => {
+                f.write_str(if *is_impl { "impl " } else { "dyn " })?;
+                write_separated(f, bounds, " + ")
+            }
+            TypeExpr::HigherRanked { lifetimes, inner } => {
+                write!(f, "for<{}> {}", lifetimes.join(", "), inner)
+            }
+            TypeExpr::Relaxed(inner) => write!(f, "?{}", inner),
+            TypeExpr::Binding { name, value } => write!(f, "{} = {}", name, value),
+            TypeExpr::Lifetime(text) | TypeExpr::Literal(text) => f.write_str(text),
+            TypeExpr::Never => f.write_str("!"),
+            TypeExpr::Infer => f.write_str("_"),
+            TypeExpr::Elided => f.write_str("..."),
+        }
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        match &self.arguments {
+            PathArguments::None => Ok(()),
+            PathArguments::AngleBracketed(arguments) => {
+                f.write_str("<")?;
+                write_separated(f, arguments, ", ")?;
+                f.write_str(">")
+            }
+            PathArguments::Parenthesized { inputs, output } => {
+                f.write_str("(")?;
+                write_separated(f, inputs, ", ")?;
+                f.write_str(")")?;
+                match output {
+                    Some(output) => write!(f, " -> {}", output),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+fn write_separated<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    items: &[T],
+    separator: &str,
+) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// Parses a whole type (e.g., the provider type of a relationship)
+/// The outcome is truncated if the compiler elided part of the type with `...`, and fails
+/// if the text is not a single type
+pub fn parse_type(text: &str) -> ParseOutcome<TypeExpr> {
+    let mut parser = Parser::new(text, 0);
+    match parser.parse_type() {
+        Ok(type_expr) if parser.is_at_end() => parser.outcome(type_expr),
+        _ => ParseOutcome::Failed,
+    }
+}
+
+/// Prints a type the way `TypeExpr` displays it, so that types which only differ in
+/// whitespace (e.g., `Rectangle<'a,u32>` and `Rectangle<'a, u32>`) are spelled the same
+/// Text that is not a complete type only has its whitespace collapsed
+pub fn normalize_type(text: &str) -> String {
+    match parse_type(text).complete() {
+        Some(type_expr) => type_expr.to_string(),
+        None => text.split_whitespace().collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// Whether two types printed by rustc are the same type, comparing them structurally
+pub fn same_type(a: &str, b: &str) -> bool {
+    normalize_type(a) == normalize_type(b)
+}
+
+/// Parses a trait bound such as `RectangleArea: AreaCalculator<Rectangle>` into its self
+/// type and its trait
+pub fn parse_trait_bound(text: &str) -> ParseOutcome<(TypeExpr, TypeExpr)> {
+    let mut parser = Parser::new(text, 0);
+    let bound = parser.parse_type().and_then(|self_type| {
+        parser.expect(":")?;
+        Ok((self_type, parser.parse_type()?))
+    });
+    match bound {
+        Ok(bound) if parser.is_at_end() => parser.outcome(bound),
+        _ => ParseOutcome::Failed,
+    }
+}
+
+/// The generic argument list of a type in a compiler message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenericArguments {
+    /// The byte ranges of the arguments in the message, without surrounding whitespace
+    pub arguments: Vec<Range<usize>>,
+    /// Whether the list ends with its closing `>`
+    pub is_closed: bool,
+    /// The position right after the closing `>`, or where the list stops if it is not closed
+    pub end: usize,
+}
+
+/// Parses the generic argument list that starts right after a `<` at `start` in a message
+/// The list is truncated if it stops before its closing `>` (because the message ends, or
+/// the quoted type ends at a backtick), keeping the arguments read so far, or if the
+/// compiler elided part of it. It fails if not even a partial argument could be read.
+pub fn generic_arguments_at(text: &str, start: usize) -> ParseOutcome<GenericArguments> {
+    if text.get(start..).is_none() {
+        return ParseOutcome::Failed;
+    }
+
+    let mut parser = Parser::new(text, start);
+    let mut arguments = Vec::new();
+
+    loop {
+        let argument_start = parser.peek().map(|token| token.start);
+        if parser.parse_generic_argument().is_err() {
+            // Keep what was read of the argument before the list stopped
+            let stop = parser.stop_position();
+            if let Some(argument_start) = argument_start
+                && argument_start < stop
+            {
+                arguments.push(
+                    argument_start..argument_start + text[argument_start..stop].trim_end().len(),
+                );
+            }
+            return parser.unclosed(arguments);
+        }
+        arguments.push(argument_start.unwrap_or(start)..parser.last_end());
+
+        if parser.eat(">") {
+            return parser.closed(arguments);
+        }
+        if !parser.eat(",") {
+            return parser.unclosed(arguments);
+        }
+        // A trailing comma may precede the closing bracket
+        if parser.eat(">") {
+            return parser.closed(arguments);
+        }
+    }
+}
+
+/// The kinds of tokens in a type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    /// An identifier or keyword (e.g., `Rectangle`, `dyn` or `r#type`)
+    Ident,
+    /// A lifetime (e.g., `'static`)
+    Lifetime,
+    /// A number, char or string literal (e.g., `3`, `'w'` or `"C"`)
+    Literal,
+    /// Text in braces (e.g., `{closure@src/lib.rs:3:5}` or `{ N + 1 }`)
+    Braced,
+    /// `...`
+    Ellipsis,
+    /// `::`, `->` or any other single character
+    Punct,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+}
+
+/// Splits text into tokens, from the given position to the end
+/// Any text can be tokenized, since a type is usually followed by the rest of a message
+fn tokenize(text: &str, start: usize) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut pos = start;
+
+    while let Some(c) = text[pos..].chars().next() {
+        let rest = &text[pos..];
+        if c.is_whitespace() {
+            pos += c.len_utf8();
+            continue;
+        }
+
+        let (kind, len) = if is_identifier_start(c) {
+            (TokenKind::Ident, identifier_len(rest))
+        } else if c == '\'' {
+            quote_len(rest)
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !is_identifier_char(c))
+                .unwrap_or(rest.len());
+            (TokenKind::Literal, len)
+        } else if c == '"' {
+            (TokenKind::Literal, string_len(rest))
+        } else if c == '{' {
+            (TokenKind::Braced, braced_len(rest))
+        } else if rest.starts_with("...") {
+            (TokenKind::Ellipsis, 3)
+        } else if rest.starts_with("::") || rest.starts_with("->") {
+            (TokenKind::Punct, 2)
+        } else {
+            (TokenKind::Punct, c.len_utf8())
+        };
+
+        tokens.push(Token {
+            kind,
+            start: pos,
+            end: pos + len,
+        });
+        pos += len;
+    }
+
+    tokens
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+/// The length of an identifier, including the `r#` of a raw identifier
+fn identifier_len(text: &str) -> usize {
+    let prefix_len = match text.strip_prefix("r#") {
+        Some(rest) if rest.starts_with(is_identifier_start) => 2,
+        _ => 0,
+    };
+    let rest = &text[prefix_len..];
+    prefix_len
+        + rest
+            .find(|c: char| !is_identifier_char(c))
+            .unwrap_or(rest.len())
+}
+
+/// The kind and length of a token starting with `'`: a char literal (e.g., `'>'` or `'\''`)
+/// or a lifetime (e.g., `'static`)
+fn quote_len(text: &str) -> (TokenKind, usize) {
+    let after = &text[1..];
+    match after.chars().next() {
+        Some('\\') => {
+            let escaped_len = after[1..].chars().next().map_or(0, char::len_utf8);
+            let rest = &after[1 + escaped_len..];
+            let len = rest
+                .find('\'')
+                .map_or(text.len(), |close| 2 + escaped_len + close + 1);
+            (TokenKind::Literal, len)
+        }
+        Some(c) if after[c.len_utf8()..].starts_with('\'') => {
+            (TokenKind::Literal, 2 + c.len_utf8())
+        }
+        Some(c) if is_identifier_start(c) => (TokenKind::Lifetime, 1 + identifier_len(after)),
+        _ => (TokenKind::Punct, 1),
+    }
+}
+
+/// The length of a string literal, or of the rest of the text if it is not closed
+fn string_len(text: &str) -> usize {
+    let mut is_escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            '\\' if !is_escaped => is_escaped = true,
+            '"' if !is_escaped => return i + 1,
+            _ => is_escaped = false,
+        }
+    }
+    text.len()
+}
+
+/// The length of text in balanced braces, or of the rest of the text if they are not closed
+fn braced_len(text: &str) -> usize {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+/// Why a type could not be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseError {
+    /// The text ends before the type does
+    EndOfInput,
+    /// A token that cannot continue the type
+    Unexpected,
+}
+
+type ParseResult<T> = Result<T, ParseError>;
+
+/// A recursive descent parser over the tokens of a type
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Whether a part of the type was elided with `...`
+    is_elided: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str, start: usize) -> Self {
+        Parser {
+            text,
+            tokens: tokenize(text, start),
+            pos: 0,
+            is_elided: false,
+        }
+    }
+
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn peek_text(&self) -> Option<&'a str> {
+        self.peek().map(|token| self.token_text(token))
+    }
+
+    fn token_text(&self, token: Token) -> &'a str {
+        &self.text[token.start..token.end]
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.pos == self.tokens.len()
+    }
+
+    /// The end of the last token read
+    fn last_end(&self) -> usize {
+        self.pos
+            .checked_sub(1)
+            .map_or(0, |previous| self.tokens[previous].end)
+    }
+
+    /// Where parsing stopped: the start of the next token, or the end of the text
+    fn stop_position(&self) -> usize {
+        self.peek().map_or(self.text.len(), |token| token.start)
+    }
+
+    /// Whether the next token is the given keyword or punctuation
+    fn is_next(&self, text: &str) -> bool {
+        self.peek()
+            .is_some_and(|token| matches!(token.kind, TokenKind::Ident | TokenKind::Punct))
+            && self.peek_text() == Some(text)
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        let is_next = self.is_next(text);
+        if is_next {
+            self.pos += 1;
+        }
+        is_next
+    }
+
+    fn expect(&mut self, text: &str) -> ParseResult<()> {
+        if self.eat(text) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn next_token(&mut self) -> ParseResult<Token> {
+        let token = self.peek().ok_or(ParseError::EndOfInput)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn error(&self) -> ParseError {
+        if self.is_at_end() {
+            ParseError::EndOfInput
+        } else {
+            ParseError::Unexpected
+        }
+    }
+
+    fn outcome<T>(&self, value: T) -> ParseOutcome<T> {
+        if self.is_elided {
+            ParseOutcome::Truncated(value)
+        } else {
+            ParseOutcome::Complete(value)
+        }
+    }
+
+    fn closed(&self, arguments: Vec<Range<usize>>) -> ParseOutcome<GenericArguments> {
+        self.outcome(GenericArguments {
+            arguments,
+            is_closed: true,
+            end: self.last_end(),
+        })
+    }
+
+    fn unclosed(&self, arguments: Vec<Range<usize>>) -> ParseOutcome<GenericArguments> {
+        if arguments.is_empty() {
+            return ParseOutcome::Failed;
+        }
+        ParseOutcome::Truncated(GenericArguments {
+            arguments,
+            is_closed: false,
+            end: self.stop_position(),
+        })
+    }
+
+    fn parse_type(&mut self) -> ParseResult<TypeExpr> {
+        let token = self.peek().ok_or(ParseError::EndOfInput)?;
+        let text = self.token_text(token);
+
+        match token.kind {
+            TokenKind::Ellipsis => {
+                self.pos += 1;
+                self.is_elided = true;
+                Ok(TypeExpr::Elided)
+            }
+            TokenKind::Lifetime => {
+                self.pos += 1;
+                Ok(TypeExpr::Lifetime(text.to_string()))
+            }
+            TokenKind::Literal | TokenKind::Braced => {
+                self.pos += 1;
+                Ok(TypeExpr::Literal(text.to_string()))
+            }
+            TokenKind::Punct => match text {
+                "&" => self.parse_reference(),
+                "*" => self.parse_pointer(),
+                "(" => self.parse_tuple(),
+                "[" => self.parse_slice(),
+                "<" => self.parse_qualified_path(),
+                "::" => {
+                    self.pos += 1;
+                    self.parse_path()
+                }
+                "!" => {
+                    self.pos += 1;
+                    Ok(TypeExpr::Never)
+                }
+                "-" => {
+                    // A negative const argument (e.g., `-1`)
+                    self.pos += 1;
+                    let literal = self.next_token()?;
+                    if literal.kind != TokenKind::Literal {
+                        return Err(ParseError::Unexpected);
+                    }
+                    Ok(TypeExpr::Literal(format!("-{}", self.token_text(literal))))
+                }
+                _ => Err(ParseError::Unexpected),
+            },
+            TokenKind::Ident => match text {
+                "_" => {
+                    self.pos += 1;
+                    Ok(TypeExpr::Infer)
+                }
+                "dyn" | "impl" => self.parse_trait_object(),
+                "for" => self.parse_higher_ranked(),
+                "fn" | "unsafe" | "extern" => self.parse_fn_pointer(),
+                _ => self.parse_path(),
+            },
+        }
+    }
+
+    fn parse_reference(&mut self) -> ParseResult<TypeExpr> {
+        self.expect("&")?;
+        let lifetime = match self.peek() {
+            Some(token) if token.kind == TokenKind::Lifetime => {
+                self.pos += 1;
+                Some(self.token_text(token).to_string())
+            }
+            _ => None,
+        };
+        let is_mut = self.eat("mut");
+        Ok(TypeExpr::Reference {
+            lifetime,
+            is_mut,
+            inner: Box::new(self.parse_type()?),
+        })
+    }
+
+    fn parse_pointer(&mut self) -> ParseResult<TypeExpr> {
+        self.expect("*")?;
+        let is_mut = if self.eat("mut") {
+            true
+        } else {
+            self.expect("const")?;
+            false
+        };
+        Ok(TypeExpr::Pointer {
+            is_mut,
+            inner: Box::new(self.parse_type()?),
+        })
+    }
+
+    fn parse_tuple(&mut self) -> ParseResult<TypeExpr> {
+        self.expect("(")?;
+        let mut elements = Vec::new();
+        let mut has_trailing_comma = false;
+
+        while !self.eat(")") {
+            elements.push(self.parse_type()?);
+            if self.eat(")") {
+                break;
+            }
+            self.expect(",")?;
+            has_trailing_comma = self.is_next(")");
+        }
+
+        if elements.len() == 1 && !has_trailing_comma {
+            Ok(TypeExpr::Paren(Box::new(elements.remove(0))))
+        } else {
+            Ok(TypeExpr::Tuple(elements))
+        }
+    }
+
+    fn parse_slice(&mut self) -> ParseResult<TypeExpr> {
+        self.expect("[")?;
+        let element = Box::new(self.parse_type()?);
+        if self.eat(";") {
+            let length = Box::new(self.parse_type()?);
+            self.expect("]")?;
+            Ok(TypeExpr::Array(element, length))
+        } else {
+            self.expect("]")?;
+            Ok(TypeExpr::Slice(element))
+        }
+    }
+
+    fn parse_qualified_path(&mut self) -> ParseResult<TypeExpr> {
+        self.expect("<")?;
+        let self_type = Box::new(self.parse_type()?);
+        let trait_path = if self.eat("as") {
+            Some(Box::new(self.parse_type()?))
+        } else {
+            None
+        };
+        self.expect(">")?;
+
+        let mut segments = Vec::new();
+        while self.eat("::") {
+            segments.push(self.parse_segment()?);
+        }
+        Ok(TypeExpr::Qualified {
+            self_type,
+            trait_path,
+            segments,
+        })
+    }
+
+    fn parse_path(&mut self) -> ParseResult<TypeExpr> {
+        let mut segments = vec![self.parse_segment()?];
+        while self.is_next("::")
+            && self
+                .tokens
+                .get(self.pos + 1)
+                .is_some_and(|token| token.kind == TokenKind::Ident)
+        {
+            self.pos += 1;
+            segments.push(self.parse_segment()?);
+        }
+        Ok(TypeExpr::Path(segments))
+    }
+
+    fn parse_segment(&mut self) -> ParseResult<PathSegment> {
+        let token = self.next_token()?;
+        if token.kind != TokenKind::Ident {
+            return Err(ParseError::Unexpected);
+        }
+        let name = self.token_text(token).to_string();
+
+        let arguments = if self.eat("<") {
+            PathArguments::AngleBracketed(self.parse_generic_arguments()?)
+        } else if self.is_next("(") {
+            let inputs = self.parse_parenthesized_types()?;
+            let output = self.parse_return_type()?;
+            PathArguments::Parenthesized { inputs, output }
+        } else {
+            PathArguments::None
+        };
+
+        Ok(PathSegment { name, arguments })
+    }
+
+    /// Parses the generic arguments after a `<`, up to and including the closing `>`
+    fn parse_generic_arguments(&mut self) -> ParseResult<Vec<TypeExpr>> {
+        let mut arguments = Vec::new();
+        loop {
+            arguments.push(self.parse_generic_argument()?);
+            if self.eat(">") {
+                return Ok(arguments);
+            }
+            self.expect(",")?;
+            if self.eat(">") {
+                return Ok(arguments);
+            }
+        }
+    }
+
+    fn parse_generic_argument(&mut self) -> ParseResult<TypeExpr> {
+        let is_binding = self
+            .peek()
+            .is_some_and(|token| token.kind == TokenKind::Ident)
+            && self
+                .tokens
+                .get(self.pos + 1)
+                .is_some_and(|token| self.token_text(*token) == "=");
+        if !is_binding {
+            return self.parse_type();
+        }
+
+        let token = self.next_token()?;
+        let name = self.token_text(token).to_string();
+        self.expect("=")?;
+        Ok(TypeExpr::Binding {
+            name,
+            value: Box::new(self.parse_type()?),
+        })
+    }
+
+    /// Parses `(A, B)` in a function pointer or an `Fn` trait
+    fn parse_parenthesized_types(&mut self) -> ParseResult<Vec<TypeExpr>> {
+        self.expect("(")?;
+        let mut types = Vec::new();
+        while !self.eat(")") {
+            types.push(self.parse_type()?);
+            if !self.eat(",") {
+                self.expect(")")?;
+                break;
+            }
+        }
+        Ok(types)
+    }
+
+    fn parse_return_type(&mut self) -> ParseResult<Option<Box<TypeExpr>>> {
+        if self.eat("->") {
+            Ok(Some(Box::new(self.parse_type()?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_trait_object(&mut self) -> ParseResult<TypeExpr> {
+        let is_impl = self.eat("impl");
+        if !is_impl {
+            self.expect("dyn")?;
+        }
+
+        let mut bounds = Vec::new();
+        loop {
+            let bound = if self.eat("?") {
+                TypeExpr::Relaxed(Box::new(self.parse_type()?))
+            } else {
+                self.parse_type()?
+            };
+            bounds.push(bound);
+            if !self.eat("+") {
+                return Ok(TypeExpr::TraitObject { is_impl, bounds });
+            }
+        }
+    }
+
+    fn parse_higher_ranked(&mut self) -> ParseResult<TypeExpr> {
+        self.expect("for")?;
+        self.expect("<")?;
+        let mut lifetimes = Vec::new();
+        while !self.eat(">") {
+            let token = self.next_token()?;
+            if token.kind != TokenKind::Lifetime {
+                return Err(ParseError::Unexpected);
+            }
+            lifetimes.push(self.token_text(token).to_string());
+            if !self.eat(",") {
+                self.expect(">")?;
+                break;
+            }
+        }
+
+        Ok(TypeExpr::HigherRanked {
+            lifetimes,
+            inner: Box::new(self.parse_type()?),
+        })
+    }
+
+    fn parse_fn_pointer(&mut self) -> ParseResult<TypeExpr> {
+        let mut qualifiers = Vec::new();
+        while !self.eat("fn") {
+            let token = self.next_token()?;
+            let text = self.token_text(token);
+            let is_qualifier = matches!(text, "unsafe" | "extern")
+                || (token.kind == TokenKind::Literal && text.starts_with('"'));
+            if !is_qualifier {
+                return Err(ParseError::Unexpected);
+            }
+            qualifiers.push(text.to_string());
+        }
+
+        let inputs = self.parse_parenthesized_types()?;
+        let output = self.parse_return_type()?;
+        // Function items are printed with their name in braces (e.g., `fn(u8) -> u8 {double}`)
+        let item = match self.peek() {
+            Some(token) if token.kind == TokenKind::Braced => {
+                self.pos += 1;
+                Some(self.token_text(token).to_string())
+            }
+            _ => None,
+        };
+
+        Ok(TypeExpr::FnPointer {
+            qualifiers,
+            inputs,
+            output,
+            item,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Types as printed by rustc in CGP errors, each printed back as written
+    const RUSTC_TYPES: [&str; 16] = [
+        "cgp::prelude::IsProviderFor<AreaCalculatorComponent, Rectangle>",
+        "Retry<'static, 3, RectangleArea>",
+        "Symbol<6, Chars<'h', Chars<'e', Chars<'i', Chars<'g', Chars<'h', Chars<'t', Nil>>>>>>>",
+        "Symbol<3, Chars<'>', Chars<',', Chars<'\\'', Nil>>>>",
+        "Box<dyn Fn(&'a str) -> Result<(), anyhow::Error> + Send + Sync>",
+        "fn(&Rectangle) -> f64 {rectangles_area}",
+        "&mut [(u8, String); 3]",
+        "<Rectangle as HasField<Symbol<5, Chars<'w', Nil>>>>::Value",
+        "impl for<'a> Fn(&'a u8) -> Option<&'a u8>",
+        "{closure@src/lib.rs:12:5}",
+        "*const dyn Any",
+        "Pin<Box<dyn Future<Output = ()> + Send + 'static>>",
+        "PhantomData<(Context, fn() -> !)>",
+        "&(dyn Display + ?Sized)",
+        "unsafe extern \"C\" fn(*mut u8, ...)",
+        "Offset<-1, [u8; { N + 1 }], (_,)>",
+    ];
+
+    #[test]
+    fn test_parse_rustc_types() {
+        for text in RUSTC_TYPES {
+            let outcome = parse_type(text);
+            let type_expr = outcome
+                .clone()
+                .value()
+                .unwrap_or_else(|| panic!("{}", text));
+            assert_eq!(type_expr.to_string(), text);
+            // Only the variadic function elides something
+            assert_eq!(
+                outcome.complete().is_some(),
+                !text.contains("..."),
+                "{}",
+                text
+            );
+        }
+
+        let bound = parse_type("ScaledArea<Retry<'static, 3, RectangleArea>>")
+            .complete()
+            .unwrap();
+        assert_eq!(bound.name(), Some("ScaledArea"));
+        assert_eq!(
+            bound.generic_arguments()[0].to_string(),
+            "Retry<'static, 3, RectangleArea>"
+        );
+        let retry = &bound.generic_arguments()[0];
+        assert_eq!(
+            retry
+                .generic_arguments()
+                .iter()
+                .map(TypeExpr::is_type)
+                .collect::<Vec<_>>(),
+            vec![false, false, true]
+        );
+
+        let (self_type, trait_type) = parse_trait_bound("RectangleArea: AreaCalculator<Rectangle>")
+            .complete()
+            .unwrap();
+        assert_eq!(self_type.to_string(), "RectangleArea");
+        assert_eq!(trait_type.name(), Some("AreaCalculator"));
+
+        assert_eq!(parse_type("Foo<Bar"), ParseOutcome::Failed);
+        assert_eq!(parse_type("Foo Bar"), ParseOutcome::Failed);
+        assert_eq!(parse_type("Foo<>"), ParseOutcome::Failed);
+    }
+
+    #[test]
+    fn test_replace_type_argument() {
+        let replace = |text: &str, from: &str, to: &str| {
+            let parse = |text: &str| parse_type(text).complete().unwrap();
+            parse(text)
+                .replace_type_argument(&parse(from), &parse(to))
+                .to_string()
+        };
+
+        assert_eq!(
+            replace("ScaledArea<RectangleArea>", "RectangleArea", "SquareArea"),
+            "ScaledArea<SquareArea>"
+        );
+        assert_eq!(
+            replace(
+                "Retry<ScaledArea<RectangleArea>, 3>",
+                "RectangleArea",
+                "SquareArea"
+            ),
+            "Retry<ScaledArea<SquareArea>, 3>"
+        );
+        assert_eq!(
+            replace("RectangleArea", "RectangleArea", "SquareArea"),
+            "RectangleArea"
+        );
+    }
+
+    #[test]
+    fn test_normalize_type() {
+        assert_eq!(normalize_type("Rectangle<'a,u32>"), "Rectangle<'a, u32>");
+        assert_eq!(
+            normalize_type("MyContext<\n    Foo ,\n    'a,\n>"),
+            "MyContext<Foo, 'a>"
+        );
+        assert_eq!(
+            normalize_type("Wrapper<Rectangle,  ..."),
+            "Wrapper<Rectangle, ..."
+        );
+        assert!(same_type("Rectangle<'a, u32>", "Rectangle<'a,u32>"));
+        assert!(!same_type("Rectangle<'a, u32>", "Rectangle<'a, u64>"));
+    }
+
+    #[test]
+    fn test_generic_arguments_at() {
+        let arguments = |text: &str, start: usize| {
+            generic_arguments_at(text, start).map(|list| {
+                list.arguments
+                    .iter()
+                    .map(|range| text[range.clone()].to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+        let start = "IsProviderFor<".len();
+
+        // Nested generics, tuples and function types end at the matching bracket
+        let text = "IsProviderFor<Foo<A, B>, Bar>` and more";
+        let list = generic_arguments_at(text, start).complete().unwrap();
+        assert_eq!(list.end, text.find('`').unwrap());
+        assert_eq!(
+            arguments(text, start),
+            ParseOutcome::Complete(vec!["Foo<A, B>".to_string(), "Bar".to_string()])
+        );
+        assert_eq!(
+            arguments("IsProviderFor<Wrapper<(A, fn(u8) -> u8)>, Ctx<'>'>>", start),
+            ParseOutcome::Complete(vec![
+                "Wrapper<(A, fn(u8) -> u8)>".to_string(),
+                "Ctx<'>'>".to_string()
+            ])
+        );
+
+        // Lists that stop before the closing bracket keep the arguments read so far
+        assert_eq!(
+            arguments("IsProviderFor<FooComponent, Wrapper<Rectangle", start),
+            ParseOutcome::Truncated(vec![
+                "FooComponent".to_string(),
+                "Wrapper<Rectangle".to_string()
+            ])
+        );
+        let text = "CanUseComponent<Wrapper<FooComponent` is not";
+        let list = generic_arguments_at(text, "CanUseComponent<".len())
+            .value()
+            .unwrap();
+        assert!(!list.is_closed);
+        assert_eq!(list.end, text.find('`').unwrap());
+
+        // Elided types are closed, but not complete
+        let text = "HasField<Symbol<5, Chars<'w', ...>>>`";
+        let list = generic_arguments_at(text, "HasField<".len());
+        assert!(matches!(&list, ParseOutcome::Truncated(list) if list.is_closed));
+
+        assert_eq!(generic_arguments_at("Foo<>", 4), ParseOutcome::Failed);
+        assert_eq!(generic_arguments_at("Foo<", 4), ParseOutcome::Failed);
+        assert_eq!(generic_arguments_at("Foo", 10), ParseOutcome::Failed);
+    }
+
+    /// A small deterministic random number generator (xorshift), so that the property tests
+    /// need no dependency and always check the same cases
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+
+        fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+            items[self.below(items.len())]
+        }
+    }
+
+    const NAMES: [&str; 6] = ["Rectangle", "ScaledArea", "cgp", "Chars", "r#type", "Nil"];
+    const LITERALS: [&str; 7] = ["3", "'w'", "'>'", "','", "'\\''", "-1", "{ N + 1 }"];
+
+    fn random_path(rng: &mut Rng, depth: usize) -> TypeExpr {
+        let count = 1 + rng.below(2);
+        let mut segments = Vec::new();
+        for i in 0..count {
+            // Parenthesized arguments end a path, since the return type would take the rest
+            let is_last = i + 1 == count;
+            let arguments = match rng.below(if depth == 0 { 1 } else { 4 }) {
+                1 | 2 => PathArguments::AngleBracketed(
+                    (0..=rng.below(2))
+                        .map(|_| random_argument(rng, depth - 1))
+                        .collect(),
+                ),
+                3 if is_last => PathArguments::Parenthesized {
+                    inputs: (0..rng.below(3))
+                        .map(|_| random_type(rng, depth - 1))
+                        .collect(),
+                    output: (rng.below(2) == 0).then(|| Box::new(random_path(rng, depth - 1))),
+                },
+                _ => PathArguments::None,
+            };
+            segments.push(PathSegment {
+                name: rng.pick(&NAMES).to_string(),
+                arguments,
+            });
+        }
+        TypeExpr::Path(segments)
+    }
+
+    fn random_argument(rng: &mut Rng, depth: usize) -> TypeExpr {
+        match rng.below(8) {
+            0 => TypeExpr::Lifetime(rng.pick(&["'a", "'static"]).to_string()),
+            1 => TypeExpr::Literal(rng.pick(&LITERALS).to_string()),
+            2 => TypeExpr::Binding {
+                name: "Output".to_string(),
+                value: Box::new(random_type(rng, depth)),
+            },
+            _ => random_type(rng, depth),
+        }
+    }
+
+    fn random_type(rng: &mut Rng, depth: usize) -> TypeExpr {
+        if depth == 0 {
+            return random_path(rng, 0);
+        }
+        let inner = |rng: &mut Rng| Box::new(random_type(rng, depth - 1));
+
+        match rng.below(14) {
+            0 => TypeExpr::Reference {
+                lifetime: (rng.below(2) == 0).then(|| "'a".to_string()),
+                is_mut: rng.below(2) == 0,
+                inner: inner(rng),
+            },
+            1 => TypeExpr::Pointer {
+                is_mut: rng.below(2) == 0,
+                inner: inner(rng),
+            },
+            2 => TypeExpr::Tuple(
+                (0..rng.below(3))
+                    .map(|_| random_type(rng, depth - 1))
+                    .collect(),
+            ),
+            3 => TypeExpr::Slice(inner(rng)),
+            4 => TypeExpr::Array(inner(rng), Box::new(TypeExpr::Literal("3".to_string()))),
+            5 => TypeExpr::FnPointer {
+                qualifiers: Vec::new(),
+                inputs: (0..rng.below(3))
+                    .map(|_| random_type(rng, depth - 1))
+                    .collect(),
+                output: (rng.below(2) == 0).then(|| Box::new(random_path(rng, depth - 1))),
+                item: None,
+            },
+            // Bounds are paths, so that the `+` of a nested trait object is never ambiguous
+            6 => TypeExpr::Paren(Box::new(TypeExpr::TraitObject {
+                is_impl: rng.below(2) == 0,
+                bounds: vec![
+                    random_path(rng, depth - 1),
+                    TypeExpr::Lifetime("'static".to_string()),
+                ],
+            })),
+            7 => TypeExpr::Qualified {
+                self_type: inner(rng),
+                trait_path: Some(Box::new(random_path(rng, depth - 1))),
+                segments: vec![PathSegment {
+                    name: "Value".to_string(),
+                    arguments: PathArguments::None,
+                }],
+            },
+            8 => TypeExpr::Never,
+            9 => TypeExpr::Infer,
+            10 => TypeExpr::Elided,
+            _ => random_path(rng, depth),
+        }
+    }
+
+    #[test]
+    fn test_parse_random_types() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..500 {
+            let type_expr = random_type(&mut rng, 4);
+            let text = type_expr.to_string();
+
+            // Types are read back as they were printed
+            let outcome = parse_type(&text);
+            assert_eq!(
+                outcome.clone().value().as_ref(),
+                Some(&type_expr),
+                "{}",
+                text
+            );
+            assert_eq!(
+                outcome.complete().is_some(),
+                !text.contains("..."),
+                "{}",
+                text
+            );
+
+            // Arguments end where they were printed, even inside a message
+            let arguments = [text.clone(), random_argument(&mut rng, 2).to_string()];
+            let message = format!("IsProviderFor<{}>` is not satisfied", arguments.join(", "));
+            let start = "IsProviderFor<".len();
+            let list = generic_arguments_at(&message, start).value().unwrap();
+            let parsed: Vec<&str> = list
+                .arguments
+                .iter()
+                .map(|range| &message[range.clone()])
+                .collect();
+            assert_eq!(parsed, arguments, "{}", message);
+            assert_eq!(list.end, message.find("` is").unwrap());
+
+            // A message cut off inside the list is never read as a complete list
+            let list_end = list.end;
+            for cut in (start..list_end).filter(|cut| message.is_char_boundary(*cut)) {
+                let outcome = generic_arguments_at(&message[..cut], start);
+                assert!(outcome.complete().is_none(), "{}", &message[..cut]);
+            }
+        }
+    }
+}
@@ -0,0 +1,2162 @@
+/// Module for scanning workspace source files for CGP wiring constructs
+/// Some problems, such as two check traits sharing the same name, cannot be
+/// recognized from compiler diagnostics alone and are detected from the source instead
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use cargo_metadata::MetadataCommand;
+
+use crate::package_selection::source_roots_of;
+
+/// A check trait generated by a `check_components!` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckBlock {
+    /// The generated check trait name (e.g., "CanUseRectangle")
+    pub trait_name: String,
+    /// The context type being checked (e.g., "Rectangle")
+    pub context_type: String,
+    /// Name of the crate containing the block
+    pub crate_name: String,
+    /// Module path derived from the file location (e.g., "shapes::rectangle")
+    pub module_path: String,
+    /// Source file containing the block, as read from disk
+    pub file: PathBuf,
+    /// Source file path relative to the workspace root, as rustc displays it
+    pub display_path: PathBuf,
+    /// Line of the check trait name (1-indexed)
+    pub line: usize,
+    /// Column of the check trait name (1-indexed)
+    pub column: usize,
+    /// Components checked by the block, in source order
+    pub components: Vec<CheckedComponent>,
+}
+
+/// A component entry inside a `check_components!` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedComponent {
+    /// The component name as written (e.g., "AreaCalculatorComponent")
+    pub name: String,
+    /// Line of the component name (1-indexed)
+    pub line: usize,
+    /// Column of the component name (1-indexed)
+    pub column: usize,
+}
+
+/// Where an item was found in the workspace sources
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Source file, as read from disk
+    pub file: PathBuf,
+    /// Source file path relative to the workspace root, as rustc displays it
+    pub display_path: PathBuf,
+    /// Line (1-indexed)
+    pub line: usize,
+    /// Column (1-indexed)
+    pub column: usize,
+}
+
+impl SourceLocation {
+    /// A location in a file that is not known yet, filled in by `WorkspaceScan::scan_file`
+    fn at(content: &str, offset: usize) -> Self {
+        let (line, column) = offset_to_line_column(content, offset);
+        SourceLocation {
+            line,
+            column,
+            ..SourceLocation::default()
+        }
+    }
+
+    fn in_file(self, file: &Path, display_path: &Path) -> Self {
+        SourceLocation {
+            file: file.to_path_buf(),
+            display_path: display_path.to_path_buf(),
+            ..self
+        }
+    }
+}
+
+/// A component declared with `#[cgp_component]` or `#[cgp_getter]` on a consumer trait
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentDecl {
+    /// The component name (e.g., "AreaCalculatorComponent")
+    pub component_name: String,
+    /// The provider trait name (e.g., "AreaCalculator")
+    pub provider_trait: String,
+    /// The consumer trait name (e.g., "CanCalculateArea")
+    pub consumer_trait: String,
+    /// The first line of the doc comment on the consumer trait
+    pub doc: Option<String>,
+}
+
+/// A component wired to a provider for a context by `delegate_components!`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delegation {
+    /// The context type (e.g., "Rectangle")
+    pub context_type: String,
+    /// The component name (e.g., "AreaCalculatorComponent")
+    pub component: String,
+    /// The provider type as written (e.g., "ScaledArea<RectangleArea>")
+    pub provider: String,
+    /// Location of the component key in the `delegate_components!` block
+    pub location: SourceLocation,
+}
+
+/// A provider implementation declared with `#[cgp_impl]`, `#[cgp_provider]` or `#[cgp_new_provider]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderImpl {
+    /// The provider type as written (e.g., "ScaledArea<InnerCalculator>")
+    pub provider_type: String,
+    /// The provider trait being implemented (e.g., "AreaCalculator")
+    pub provider_trait: String,
+    /// Bounds the implementation requires from the context (e.g., "HasRectangleFields")
+    pub requirements: Vec<String>,
+    /// The first line of the doc comment on the implementation
+    pub doc: Option<String>,
+    /// The provider attribute as written (e.g., "#[cgp_impl(new RectangleArea)]")
+    pub attribute: String,
+    /// Whether the attribute generates the provider struct, with `new` or `#[cgp_new_provider]`
+    pub generates_struct: bool,
+    /// Location of the provider attribute
+    pub location: SourceLocation,
+}
+
+impl ProviderImpl {
+    /// The provider type without generic parameters (e.g., "ScaledArea")
+    pub fn provider_name(&self) -> &str {
+        base_type_name(&self.provider_type)
+    }
+}
+
+/// A getter trait declared with `#[cgp_auto_getter]` or `#[cgp_getter]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetterDecl {
+    /// The getter trait name (e.g., "HasRectangleFields")
+    pub trait_name: String,
+    /// The fields read by the getter methods
+    pub fields: Vec<FieldDecl>,
+    /// Whether the getter is implemented for every context with the fields
+    /// (`#[cgp_auto_getter]`), rather than wired as a component (`#[cgp_getter]`)
+    pub is_auto: bool,
+    /// Location of the getter attribute
+    pub location: SourceLocation,
+}
+
+/// A named field of a struct, or the field read by a getter method
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDecl {
+    pub name: String,
+    pub field_type: String,
+}
+
+/// A struct with named fields that may be used as a context
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextStruct {
+    /// The struct name (e.g., "Rectangle")
+    pub type_name: String,
+    /// Whether the struct has `#[derive(HasField)]`
+    pub derives_has_field: bool,
+    pub fields: Vec<FieldDecl>,
+    /// The `#[cgp_context]` attribute on the struct as written, if any
+    pub context_attribute: Option<String>,
+    /// Location of the struct name
+    pub location: SourceLocation,
+    /// Name of the crate defining the struct
+    pub crate_name: String,
+}
+
+impl ContextStruct {
+    /// The provider struct generated by `#[cgp_context]` (e.g., "AppComponents")
+    pub fn context_provider(&self) -> Option<String> {
+        let attribute = self.context_attribute.as_deref()?;
+        let arguments = attribute
+            .strip_prefix("#[cgp_context")?
+            .trim_end_matches(']')
+            .trim()
+            .trim_start_matches('(');
+        match take_identifier(arguments.trim_start()) {
+            name if name.is_empty() => Some(format!("{}Components", self.type_name)),
+            name => Some(name),
+        }
+    }
+}
+
+/// An `impl Deref for Context` that forwards a context to an inner struct
+/// cgp implements `HasField` for such contexts through the fields of the deref target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerefImpl {
+    /// The type implementing `Deref` (e.g., "App")
+    pub context_type: String,
+    /// The `Target` type (e.g., "AppConfig")
+    pub target_type: String,
+    /// Location of the `impl` keyword
+    pub location: SourceLocation,
+}
+
+/// The CGP constructs found by scanning the workspace sources
+#[derive(Debug, Default)]
+pub struct WorkspaceScan {
+    /// All check traits generated by `check_components!` blocks
+    pub check_blocks: Vec<CheckBlock>,
+    /// All components declared on consumer traits
+    pub component_decls: Vec<ComponentDecl>,
+    /// All delegations from `delegate_components!` blocks, in source order
+    pub delegations: Vec<Delegation>,
+    /// All provider implementations
+    pub provider_impls: Vec<ProviderImpl>,
+    /// All getter traits
+    pub getter_decls: Vec<GetterDecl>,
+    /// All structs with named fields
+    pub context_structs: Vec<ContextStruct>,
+    /// All `Deref` implementations
+    pub deref_impls: Vec<DerefImpl>,
+    /// All module files reached from the crate roots, with their module paths
+    pub module_files: Vec<ModuleFile>,
+    /// Module files that exist but could not be read (e.g., in a sandbox)
+    /// Results derived from the scan are incomplete if this is not empty
+    pub unreadable_files: Vec<PathBuf>,
+}
+
+/// A module file reached from a crate root through `mod` declarations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleFile {
+    /// The module file, as read from disk
+    pub file: PathBuf,
+    /// Name of the crate the module belongs to
+    pub crate_name: String,
+    /// The root file of the crate target that declares the module (e.g., "src/lib.rs")
+    pub root_file: PathBuf,
+    /// Module path (e.g., "shapes::rectangle", or "crate" for the root)
+    pub module_path: String,
+}
+
+/// A `check_components!` or `delegate_components!` invocation in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WiringMacro {
+    /// The macro name (e.g., "check_components")
+    pub macro_name: String,
+    /// Line of the macro name (1-indexed)
+    pub start_line: usize,
+    /// Line of the closing brace (1-indexed)
+    pub end_line: usize,
+}
+
+/// The root source file of one crate in the workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRoot {
+    /// Name of the crate the sources belong to
+    pub crate_name: String,
+    /// The crate root file (e.g., "src/lib.rs")
+    pub root_file: PathBuf,
+}
+
+impl WorkspaceScan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scans all modules reachable from the given crate roots through `mod` declarations
+    /// Paths are recorded relative to `base_dir` when possible, matching rustc's output
+    pub fn scan_roots(roots: &[SourceRoot], base_dir: &Path) -> Self {
+        let mut scan = Self::new();
+        let mut visited = Vec::new();
+
+        for root in roots {
+            let Some(module_dir) = root.root_file.parent() else {
+                continue;
+            };
+
+            scan.scan_module(
+                &root.root_file,
+                module_dir,
+                root,
+                "crate",
+                base_dir,
+                &mut visited,
+            );
+        }
+
+        scan
+    }
+
+    /// Scans a module file and recursively all of its child modules
+    fn scan_module(
+        &mut self,
+        file: &Path,
+        module_dir: &Path,
+        root: &SourceRoot,
+        module_path: &str,
+        base_dir: &Path,
+        visited: &mut Vec<PathBuf>,
+    ) {
+        // Several targets (lib, bin) may share the same module files
+        if visited.iter().any(|path| path == file) {
+            return;
+        }
+        visited.push(file.to_path_buf());
+
+        let Ok(content) = std::fs::read_to_string(file) else {
+            self.unreadable_files.push(file.to_path_buf());
+            return;
+        };
+
+        let display_path = file.strip_prefix(base_dir).unwrap_or(file);
+        self.scan_file(file, display_path, &root.crate_name, module_path, &content);
+        self.module_files.push(ModuleFile {
+            file: file.to_path_buf(),
+            crate_name: root.crate_name.clone(),
+            root_file: root.root_file.clone(),
+            module_path: module_path.to_string(),
+        });
+
+        for child in extract_module_declarations(&content) {
+            let child_dir = module_dir.join(&child);
+            let child_path = if module_path == "crate" {
+                child.clone()
+            } else {
+                format!("{}::{}", module_path, child)
+            };
+
+            let candidates = [
+                module_dir.join(format!("{}.rs", child)),
+                child_dir.join("mod.rs"),
+            ];
+
+            if let Some(child_file) = candidates.iter().find(|path| path.is_file()) {
+                self.scan_module(child_file, &child_dir, root, &child_path, base_dir, visited);
+            }
+        }
+    }
+
+    /// Scans the content of a single source file
+    pub fn scan_file(
+        &mut self,
+        file: &Path,
+        display_path: &Path,
+        crate_name: &str,
+        module_path: &str,
+        content: &str,
+    ) {
+        for block in extract_check_blocks(content) {
+            let (line, column) = offset_to_line_column(content, block.offset);
+
+            let components = block
+                .components
+                .into_iter()
+                .map(|(name, offset)| {
+                    let (line, column) = offset_to_line_column(content, offset);
+                    CheckedComponent { name, line, column }
+                })
+                .collect();
+
+            self.check_blocks.push(CheckBlock {
+                trait_name: block.trait_name,
+                context_type: block.context_type,
+                crate_name: crate_name.to_string(),
+                module_path: module_path.to_string(),
+                file: file.to_path_buf(),
+                display_path: display_path.to_path_buf(),
+                line,
+                column,
+                components,
+            });
+        }
+
+        for decl in extract_component_decls(content) {
+            if !self.component_decls.contains(&decl) {
+                self.component_decls.push(decl);
+            }
+        }
+
+        self.delegations
+            .extend(
+                extract_delegations(content)
+                    .into_iter()
+                    .map(|delegation| Delegation {
+                        location: delegation.location.in_file(file, display_path),
+                        ..delegation
+                    }),
+            );
+        self.provider_impls
+            .extend(
+                extract_provider_impls(content)
+                    .into_iter()
+                    .map(|provider_impl| ProviderImpl {
+                        location: provider_impl.location.in_file(file, display_path),
+                        ..provider_impl
+                    }),
+            );
+        self.getter_decls
+            .extend(
+                extract_getter_decls(content)
+                    .into_iter()
+                    .map(|getter| GetterDecl {
+                        location: getter.location.in_file(file, display_path),
+                        ..getter
+                    }),
+            );
+        self.context_structs
+            .extend(
+                extract_context_structs(content)
+                    .into_iter()
+                    .map(|context| ContextStruct {
+                        location: context.location.in_file(file, display_path),
+                        crate_name: crate_name.to_string(),
+                        ..context
+                    }),
+            );
+        self.deref_impls
+            .extend(
+                extract_deref_impls(content)
+                    .into_iter()
+                    .map(|deref_impl| DerefImpl {
+                        location: deref_impl.location.in_file(file, display_path),
+                        ..deref_impl
+                    }),
+            );
+    }
+
+    /// Returns the contexts wired with `delegate_components!`, in source order
+    pub fn wired_contexts(&self) -> Vec<&str> {
+        let mut contexts: Vec<&str> = Vec::new();
+        for delegation in &self.delegations {
+            if !contexts.contains(&delegation.context_type.as_str()) {
+                contexts.push(&delegation.context_type);
+            }
+        }
+        contexts
+    }
+
+    /// Returns the delegations of a context
+    pub fn delegations_of(&self, context_type: &str) -> Vec<&Delegation> {
+        self.delegations
+            .iter()
+            .filter(|delegation| delegation.context_type == context_type)
+            .collect()
+    }
+
+    /// Looks up the component declaration by component name
+    pub fn component_decl(&self, component_name: &str) -> Option<&ComponentDecl> {
+        self.component_decls
+            .iter()
+            .find(|decl| decl.component_name == base_type_name(component_name))
+    }
+
+    /// Looks up the component declaration by consumer trait name
+    pub fn component_of_consumer_trait(&self, consumer_trait: &str) -> Option<&ComponentDecl> {
+        self.component_decls
+            .iter()
+            .find(|decl| decl.consumer_trait == base_type_name(consumer_trait))
+    }
+
+    /// Looks up a getter trait by name
+    pub fn getter_decl(&self, trait_name: &str) -> Option<&GetterDecl> {
+        self.getter_decls
+            .iter()
+            .find(|decl| decl.trait_name == base_type_name(trait_name))
+    }
+
+    /// Looks up a struct by name
+    pub fn context_struct(&self, type_name: &str) -> Option<&ContextStruct> {
+        self.context_structs
+            .iter()
+            .find(|decl| decl.type_name == base_type_name(type_name))
+    }
+
+    /// Looks up the `Deref` implementation of a type by name
+    pub fn deref_impl(&self, type_name: &str) -> Option<&DerefImpl> {
+        self.deref_impls.iter().find(|deref_impl| {
+            base_type_name(&deref_impl.context_type) == base_type_name(type_name)
+        })
+    }
+
+    /// Returns the struct that a context derefs to and whose fields supply `HasField`
+    /// Chains of `Deref` implementations are followed until a struct that derives
+    /// `HasField`, or the last struct of the chain
+    pub fn deref_field_struct(&self, type_name: &str) -> Option<&ContextStruct> {
+        let mut current = self.deref_impl(type_name)?;
+        let mut visited = vec![base_type_name(type_name)];
+
+        loop {
+            let target = base_type_name(&current.target_type);
+            let target_struct = self.context_struct(target);
+            if target_struct.is_some_and(|context| context.derives_has_field)
+                || visited.contains(&target)
+            {
+                return target_struct;
+            }
+            visited.push(target);
+
+            match self.deref_impl(target) {
+                Some(next) => current = next,
+                None => return target_struct,
+            }
+        }
+    }
+
+    /// Returns the provider implementations used by a provider type expression
+    /// For a higher-order provider such as "ScaledArea<RectangleArea>", this includes
+    /// the implementations of both the outer and the inner provider
+    /// If the provider trait is known, only implementations of that trait are returned
+    pub fn provider_impls_in(
+        &self,
+        provider: &str,
+        provider_trait: Option<&str>,
+    ) -> Vec<&ProviderImpl> {
+        let names: Vec<&str> = provider
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|name| !name.is_empty())
+            .collect();
+
+        self.provider_impls
+            .iter()
+            .filter(|provider_impl| names.contains(&provider_impl.provider_name()))
+            .filter(|provider_impl| {
+                provider_trait
+                    .is_none_or(|provider_trait| provider_impl.provider_trait == provider_trait)
+            })
+            .collect()
+    }
+
+    /// Returns groups of check blocks that generate the same check trait name within a crate
+    /// Each group contains at least two blocks, in the order they were scanned
+    pub fn shadowed_check_traits(&self) -> Vec<Vec<&CheckBlock>> {
+        let mut groups: BTreeMap<(&str, &str), Vec<&CheckBlock>> = BTreeMap::new();
+
+        for block in &self.check_blocks {
+            groups
+                .entry((&block.crate_name, &block.trait_name))
+                .or_default()
+                .push(block);
+        }
+
+        groups
+            .into_values()
+            .filter(|blocks| blocks.len() > 1)
+            .collect()
+    }
+
+    /// Finds (context, component) pairs that are checked in blocks in more than one file
+    /// Each returned group lists every check of the pair, in scan order
+    pub fn duplicate_checks(&self) -> Vec<DuplicateCheck<'_>> {
+        let mut groups: BTreeMap<(&str, &str), Vec<(&CheckBlock, &CheckedComponent)>> =
+            BTreeMap::new();
+
+        for block in &self.check_blocks {
+            for component in &block.components {
+                groups
+                    .entry((&block.context_type, base_type_name(&component.name)))
+                    .or_default()
+                    .push((block, component));
+            }
+        }
+
+        groups
+            .into_iter()
+            .filter(|(_, checks)| {
+                checks
+                    .iter()
+                    .any(|(block, _)| block.display_path != checks[0].0.display_path)
+            })
+            .map(|((context_type, component), checks)| DuplicateCheck {
+                context_type,
+                component,
+                checks,
+            })
+            .collect()
+    }
+}
+
+/// A (context, component) pair that is checked in several `check_components!` blocks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateCheck<'a> {
+    pub context_type: &'a str,
+    pub component: &'a str,
+    /// Each check of the pair, with the block containing it
+    pub checks: Vec<(&'a CheckBlock, &'a CheckedComponent)>,
+}
+
+impl DuplicateCheck<'_> {
+    /// Returns true if a span at the given file and line points at one of the checks
+    pub fn covers(&self, file: &str, line: usize) -> bool {
+        self.checks.iter().any(|(block, component)| {
+            Path::new(file) == block.display_path && component.line == line
+        })
+    }
+}
+
+/// Finds the crate roots of all workspace members using `cargo metadata`
+pub fn workspace_source_roots() -> anyhow::Result<(PathBuf, Vec<SourceRoot>)> {
+    let metadata = MetadataCommand::new().no_deps().exec()?;
+    let roots = source_roots_of(&metadata.workspace_packages());
+
+    Ok((metadata.workspace_root.into_std_path_buf(), roots))
+}
+
+/// Extracts the names of out-of-line modules declared with `mod name;`
+/// Commented-out declarations are ignored, so only modules that are compiled are scanned
+fn extract_module_declarations(content: &str) -> Vec<String> {
+    let mut modules = Vec::new();
+
+    for line in mask_comments_and_literals(content).lines() {
+        let mut declaration = line.trim();
+
+        // Strip visibility modifiers such as `pub` or `pub(crate)`
+        if let Some(rest) = declaration.strip_prefix("pub") {
+            let rest = rest.trim_start();
+            declaration = match rest.strip_prefix('(') {
+                Some(restricted) => match restricted.find(')') {
+                    Some(end) => restricted[end + 1..].trim_start(),
+                    None => continue,
+                },
+                None => rest,
+            };
+        }
+
+        if let Some(name) = declaration
+            .strip_prefix("mod ")
+            .and_then(|rest| rest.strip_suffix(';'))
+        {
+            modules.push(name.trim().trim_start_matches("r#").to_string());
+        }
+    }
+
+    modules
+}
+
+/// Extracts the components declared by `#[cgp_component]` and `#[cgp_getter]` attributes
+/// Supports both the short form `#[cgp_component(Provider)]` and the key-value form
+/// `#[cgp_component { provider: Provider, name: NameComponent }]`
+fn extract_component_decls(content: &str) -> Vec<ComponentDecl> {
+    let original = content;
+    let content = &mask_comments_and_literals(content);
+    let mut results = Vec::new();
+
+    for attribute in ["#[cgp_component", "#[cgp_getter"] {
+        let mut search_from = 0;
+
+        while let Some(found) = content[search_from..].find(attribute) {
+            let attribute_pos = search_from + found;
+            let name_end = attribute_pos + attribute.len();
+            search_from = name_end;
+
+            // The attribute arguments must directly follow the attribute name
+            let args_start =
+                name_end + (content[name_end..].len() - content[name_end..].trim_start().len());
+            let Some(open) = content[args_start..].chars().next() else {
+                break;
+            };
+            let (args, after_args) = match open {
+                '(' | '{' => {
+                    let close = if open == '(' { ')' } else { '}' };
+                    let Some(args_len) = content[args_start + 1..].find(close) else {
+                        break;
+                    };
+                    (
+                        &content[args_start + 1..args_start + 1 + args_len],
+                        args_start + 1 + args_len,
+                    )
+                }
+                // A bare `#[cgp_getter]` derives the provider trait from the consumer trait
+                ']' if attribute == "#[cgp_getter" => ("", args_start),
+                _ => continue,
+            };
+
+            // The consumer trait is the next trait declared after the attribute
+            let Some(trait_pos) = content[after_args..].find("trait ") else {
+                break;
+            };
+            let trait_pos = after_args + trait_pos;
+            let consumer_trait = take_identifier(&content[trait_pos + 6..]);
+
+            let (provider_trait, component_name) = if args.contains(':') {
+                (key_value(args, "provider"), key_value(args, "name"))
+            } else if args.trim().is_empty() {
+                (getter_provider_trait(&consumer_trait), None)
+            } else {
+                (Some(take_identifier(args)), None)
+            };
+
+            if let Some(provider_trait) = provider_trait.filter(|p| !p.is_empty())
+                && !consumer_trait.is_empty()
+            {
+                results.push(ComponentDecl {
+                    component_name: component_name
+                        .unwrap_or_else(|| format!("{}Component", provider_trait)),
+                    provider_trait,
+                    consumer_trait,
+                    doc: doc_summary(original, attribute_pos)
+                        .or_else(|| doc_summary(original, trait_pos)),
+                });
+            }
+        }
+    }
+
+    results
+}
+
+/// Derives the provider trait of a bare `#[cgp_getter]` like the macro does
+/// (e.g., "HasRectangleFields" -> "RectangleFieldsGetter")
+fn getter_provider_trait(consumer_trait: &str) -> Option<String> {
+    consumer_trait
+        .strip_prefix("Has")
+        .filter(|name| !name.is_empty())
+        .map(|name| format!("{}Getter", name))
+}
+
+/// Looks up the identifier value of a `key: Value` pair in attribute arguments
+fn key_value(args: &str, key: &str) -> Option<String> {
+    args.split(',').find_map(|pair| {
+        let (k, v) = pair.split_once(':')?;
+        (k.trim() == key).then(|| take_identifier(v))
+    })
+}
+
+/// Takes the leading identifier of a string, skipping leading whitespace
+/// Raw identifiers keep their `r#` prefix, which is part of the field name in `HasField`
+fn take_identifier(text: &str) -> String {
+    let text = text.trim_start();
+    let (prefix, rest) = match text.strip_prefix("r#") {
+        Some(rest) => ("r#", rest),
+        None => ("", text),
+    };
+    let name: String = rest
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+
+    if name.is_empty() {
+        name
+    } else {
+        format!("{}{}", prefix, name)
+    }
+}
+
+/// Extracts the delegations of all `delegate_components!` blocks in a file
+/// Supports array keys such as `[FooComponent, BarComponent]: Provider`
+fn extract_delegations(content: &str) -> Vec<Delegation> {
+    let content = &mask_comments_and_literals(content);
+    let mut results = Vec::new();
+
+    for macro_pos in find_all(content, "delegate_components!") {
+        let Some(open) = find_from(content, macro_pos, "{") else {
+            break;
+        };
+        let Some(close) = matching_delimiter(content, open) else {
+            break;
+        };
+        let body = &content[open + 1..close];
+
+        // The body is a context header followed by a brace group of entries
+        let Some(entries_open) = body.find('{') else {
+            continue;
+        };
+        let Some(entries_close) = matching_delimiter(body, entries_open) else {
+            continue;
+        };
+
+        let header = body[..entries_open].trim();
+        let header = header.strip_prefix("new ").unwrap_or(header).trim();
+        let context_type = normalize_whitespace(skip_generic_parameters(header));
+
+        for entry in split_top_level(&body[entries_open + 1..entries_close], ',') {
+            let Some(separator) = find_key_separator(entry) else {
+                continue;
+            };
+            let key = entry[..separator].trim();
+            let provider = normalize_whitespace(&entry[separator + 1..]);
+
+            let components = match key.strip_prefix('[').and_then(|k| k.strip_suffix(']')) {
+                Some(keys) => split_top_level(keys, ','),
+                None => vec![key],
+            };
+
+            for component in components {
+                // The parts are slices of the content, so their offsets can be recovered
+                let offset = component.trim_start().as_ptr() as usize - content.as_ptr() as usize;
+                let component = normalize_whitespace(component);
+                if !component.is_empty() {
+                    results.push(Delegation {
+                        context_type: context_type.clone(),
+                        component,
+                        provider: provider.clone(),
+                        location: SourceLocation::at(content, offset),
+                    });
+                }
+            }
+        }
+    }
+
+    results
+}
+
+/// Finds the `check_components!` and `delegate_components!` invocations in a file
+pub fn find_wiring_macros(content: &str) -> Vec<WiringMacro> {
+    let masked = &mask_comments_and_literals(content);
+    let mut results = Vec::new();
+
+    for macro_name in ["check_components", "delegate_components"] {
+        for macro_pos in find_all(masked, &format!("{}!", macro_name)) {
+            // Not part of a longer macro name (e.g., `delegate_and_check_components!`)
+            if masked[..macro_pos].ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                continue;
+            }
+            let Some(open) = find_from(masked, macro_pos, "{") else {
+                continue;
+            };
+            let Some(close) = matching_delimiter(masked, open) else {
+                continue;
+            };
+            results.push(WiringMacro {
+                macro_name: macro_name.to_string(),
+                start_line: offset_to_line_column(masked, macro_pos).0,
+                end_line: offset_to_line_column(masked, close).0,
+            });
+        }
+    }
+
+    results.sort_by_key(|wiring_macro| wiring_macro.start_line);
+    results
+}
+
+/// Extracts the provider implementations in a file
+/// `#[cgp_impl(new Provider)] impl ProviderTrait where Self: ...` bounds the context as `Self`,
+/// while `#[cgp_provider] impl<Context> ProviderTrait<Context> for Provider` bounds the
+/// first generic parameter of the provider trait
+fn extract_provider_impls(content: &str) -> Vec<ProviderImpl> {
+    let original = content;
+    let content = &mask_comments_and_literals(content);
+    let mut results = Vec::new();
+
+    for attribute in ["#[cgp_impl", "#[cgp_provider", "#[cgp_new_provider"] {
+        for attribute_pos in find_all(content, attribute) {
+            let Some(attribute_end) = find_from(content, attribute_pos, "]") else {
+                break;
+            };
+            let Some(impl_pos) = find_keyword(content, attribute_end, "impl") else {
+                break;
+            };
+            let Some(body_open) = find_from(content, impl_pos, "{") else {
+                break;
+            };
+
+            let header = skip_generic_parameters(content[impl_pos + 4..body_open].trim());
+            let (signature, where_clause) = match find_keyword(header, 0, "where") {
+                Some(where_pos) => (&header[..where_pos], &header[where_pos + 5..]),
+                None => (header, ""),
+            };
+            let (trait_part, for_part) = match signature.split_once(" for ") {
+                Some((trait_part, for_part)) => (trait_part.trim(), Some(for_part.trim())),
+                None => (signature.trim(), None),
+            };
+
+            let arguments = content[attribute_pos + attribute.len()..attribute_end]
+                .trim()
+                .trim_start_matches('(')
+                .trim_end_matches(')')
+                .trim();
+            let generates_struct = match attribute {
+                "#[cgp_impl" => arguments.starts_with("new "),
+                "#[cgp_new_provider" => true,
+                _ => false,
+            };
+
+            let (provider_type, context_param) = if attribute == "#[cgp_impl" {
+                // The provider is given in the attribute arguments, e.g. `(new RectangleArea)`,
+                // optionally followed by the component, e.g. `(RectangleArea: AreaCalculatorComponent)`
+                let provider = arguments.strip_prefix("new ").unwrap_or(arguments);
+                let provider =
+                    find_key_separator(provider).map_or(provider, |end| &provider[..end]);
+                (normalize_whitespace(provider), "Self".to_string())
+            } else {
+                let Some(provider) = for_part else {
+                    continue;
+                };
+                let context_param = trait_part
+                    .split_once('<')
+                    .map(|(_, args)| take_identifier(args))
+                    .unwrap_or_default();
+                (normalize_whitespace(provider), context_param)
+            };
+
+            let provider_trait = base_type_name(trait_part).to_string();
+            if provider_type.is_empty() || provider_trait.is_empty() {
+                continue;
+            }
+
+            let mut requirements = Vec::new();
+            for predicate in split_top_level(where_clause, ',') {
+                let Some(separator) = find_key_separator(predicate) else {
+                    continue;
+                };
+                if predicate[..separator].trim() != context_param {
+                    continue;
+                }
+                for bound in split_top_level(&predicate[separator + 1..], '+') {
+                    let bound = normalize_whitespace(bound);
+                    if !bound.is_empty() && !requirements.contains(&bound) {
+                        requirements.push(bound);
+                    }
+                }
+            }
+
+            results.push(ProviderImpl {
+                provider_type,
+                provider_trait,
+                requirements,
+                doc: doc_summary(original, attribute_pos)
+                    .or_else(|| doc_summary(original, impl_pos)),
+                attribute: normalize_whitespace(&content[attribute_pos..=attribute_end]),
+                generates_struct,
+                location: SourceLocation::at(content, attribute_pos),
+            });
+        }
+    }
+
+    results
+}
+
+/// Extracts the getter traits declared with `#[cgp_auto_getter]` or `#[cgp_getter]`
+/// Each getter method `fn name(&self) -> Type` reads the field `name`, of the type that
+/// `Type` is derived from (see `getter_field_type`)
+fn extract_getter_decls(content: &str) -> Vec<GetterDecl> {
+    let content = &mask_comments_and_literals(content);
+    let mut results = Vec::new();
+
+    for attribute in ["#[cgp_auto_getter", "#[cgp_getter"] {
+        for attribute_pos in find_all(content, attribute) {
+            let Some(trait_pos) = find_keyword(content, attribute_pos, "trait") else {
+                break;
+            };
+            let Some(body_open) = find_from(content, trait_pos, "{") else {
+                break;
+            };
+            let Some(body_close) = matching_delimiter(content, body_open) else {
+                break;
+            };
+
+            let trait_name = take_identifier(&content[trait_pos + 5..]);
+            let fields = content[body_open + 1..body_close]
+                .split(';')
+                .filter_map(|method| {
+                    let fn_pos = find_keyword(method, 0, "fn")?;
+                    let name = take_identifier(&method[fn_pos + 2..]);
+                    let (_, return_type) = method.split_once("->")?;
+                    Some(FieldDecl {
+                        name,
+                        field_type: getter_field_type(&normalize_whitespace(return_type)),
+                    })
+                })
+                .collect();
+
+            results.push(GetterDecl {
+                trait_name,
+                fields,
+                is_auto: attribute == "#[cgp_auto_getter",
+                location: SourceLocation::at(content, attribute_pos),
+            });
+        }
+    }
+
+    results
+}
+
+/// Returns the type of the field read by a getter method from its return type,
+/// the same way `#[cgp_auto_getter]` does: `&str` reads a `String`, `&[T]` a `Vec<T>`,
+/// `&T` and `MRef<T>` a `T`, and `Option<&T>` an `Option<T>`. Other types are cloned as is.
+pub fn getter_field_type(return_type: &str) -> String {
+    let strip_reference = |text: &str| -> Option<String> {
+        let rest = text.strip_prefix('&')?.trim_start();
+        let rest = match rest.strip_prefix('\'') {
+            Some(lifetime) => lifetime
+                .trim_start_matches(|c: char| c.is_alphanumeric() || c == '_')
+                .trim_start(),
+            None => rest,
+        };
+        Some(rest.to_string())
+    };
+
+    if let Some(referenced) = strip_reference(return_type) {
+        return match referenced.as_str() {
+            "str" => "String".to_string(),
+            _ => match referenced
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                Some(element) => format!("Vec<{}>", element.trim()),
+                None => referenced,
+            },
+        };
+    }
+
+    let generic_argument = |wrapper: &str| {
+        return_type
+            .strip_prefix(wrapper)
+            .and_then(|rest| rest.trim_start().strip_prefix('<'))
+            .and_then(|rest| rest.strip_suffix('>'))
+            .map(|argument| {
+                // Skip the lifetime argument, as in `MRef<'a, T>`
+                let arguments = split_top_level(argument, ',');
+                arguments
+                    .into_iter()
+                    .map(str::trim)
+                    .find(|argument| !argument.starts_with('\''))
+                    .unwrap_or_default()
+                    .to_string()
+            })
+    };
+
+    if let Some(inner) = generic_argument("Option")
+        && let Some(referenced) = strip_reference(&inner)
+    {
+        return format!("Option<{}>", getter_field_type(&format!("&{}", referenced)));
+    }
+    if let Some(inner) = generic_argument("MRef") {
+        return inner;
+    }
+
+    return_type.to_string()
+}
+
+/// Extracts the structs with named fields in a file
+fn extract_context_structs(content: &str) -> Vec<ContextStruct> {
+    let content = &mask_comments_and_literals(content);
+    let mut results = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(struct_pos) = find_keyword(content, search_from, "struct") {
+        search_from = struct_pos + "struct".len();
+
+        let name_start = search_from
+            + (content[search_from..].len() - content[search_from..].trim_start().len());
+        let type_name = take_identifier(&content[name_start..]);
+
+        // Only structs with named fields have a brace group before any `;` or `(`
+        let rest = &content[name_start + type_name.len()..];
+        let Some(body_offset) = rest.find(['{', ';', '(']) else {
+            break;
+        };
+        if type_name.is_empty() || !rest[body_offset..].starts_with('{') {
+            continue;
+        }
+        let body_open = name_start + type_name.len() + body_offset;
+        let Some(body_close) = matching_delimiter(content, body_open) else {
+            break;
+        };
+
+        // The attributes of the struct are between the previous item and the struct keyword
+        let item_start = content[..struct_pos]
+            .rfind([';', '}'])
+            .map_or(0, |pos| pos + 1);
+        let attributes = &content[item_start..struct_pos];
+        let derives_has_field = attributes.contains("derive(") && attributes.contains("HasField");
+        let context_attribute = attributes.find("#[cgp_context").and_then(|start| {
+            let end = find_from(attributes, start, "]")?;
+            Some(normalize_whitespace(&attributes[start..=end]))
+        });
+
+        let fields = split_top_level(&content[body_open + 1..body_close], ',')
+            .into_iter()
+            .filter_map(|field| {
+                let field = skip_attributes(field.trim());
+                let field = field.strip_prefix("pub").map_or(field, |rest| {
+                    let rest = rest.trim_start();
+                    match rest.strip_prefix('(') {
+                        Some(after) => after.split_once(')').map_or(rest, |(_, after)| after),
+                        None => rest,
+                    }
+                });
+                let (name, field_type) = field.split_once(':')?;
+                Some(FieldDecl {
+                    name: name.trim().to_string(),
+                    field_type: normalize_whitespace(field_type),
+                })
+            })
+            .collect();
+
+        results.push(ContextStruct {
+            type_name,
+            derives_has_field,
+            fields,
+            context_attribute,
+            location: SourceLocation::at(content, name_start),
+            crate_name: String::new(),
+        });
+        search_from = body_close;
+    }
+
+    results
+}
+
+/// Extracts the `impl Deref for Type { type Target = Inner; }` implementations in a file
+fn extract_deref_impls(content: &str) -> Vec<DerefImpl> {
+    let content = &mask_comments_and_literals(content);
+    let mut results = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(impl_pos) = find_keyword(content, search_from, "impl") {
+        search_from = impl_pos + "impl".len();
+
+        let Some(body_open) = find_from(content, search_from, "{") else {
+            break;
+        };
+        let header = skip_generic_parameters(&content[search_from..body_open]);
+        let signature = match find_keyword(header, 0, "where") {
+            Some(where_pos) => &header[..where_pos],
+            None => header,
+        };
+        let Some((trait_part, for_part)) = signature.split_once(" for ") else {
+            continue;
+        };
+        if base_type_name(trait_part.trim()) != "Deref" {
+            continue;
+        }
+        let Some(body_close) = matching_delimiter(content, body_open) else {
+            break;
+        };
+
+        let body = &content[body_open + 1..body_close];
+        let target_type = find_keyword(body, 0, "type")
+            .map(|type_pos| body[type_pos + "type".len()..].trim_start())
+            .filter(|rest| rest.starts_with("Target"))
+            .and_then(|rest| rest.split_once('='))
+            .and_then(|(_, rest)| rest.split_once(';'))
+            .map(|(target, _)| normalize_whitespace(target));
+
+        if let Some(target_type) = target_type {
+            results.push(DerefImpl {
+                context_type: normalize_whitespace(for_part),
+                target_type,
+                location: SourceLocation::at(content, impl_pos),
+            });
+        }
+        search_from = body_close;
+    }
+
+    results
+}
+
+/// Returns the first line of the `///` doc comment attached to the item at a byte offset
+/// Attributes between the doc comment and the item are skipped
+fn doc_summary(content: &str, item_pos: usize) -> Option<String> {
+    let item_line_start = content[..item_pos].rfind('\n').map_or(0, |pos| pos + 1);
+    let mut doc_lines = Vec::new();
+
+    for line in content[..item_line_start].lines().rev() {
+        let line = line.trim();
+        if let Some(doc) = line.strip_prefix("///") {
+            doc_lines.push(doc.trim());
+        } else if !line.starts_with("#[") {
+            break;
+        }
+    }
+
+    // Lines were collected bottom-up, so the first line of the comment is last
+    doc_lines
+        .into_iter()
+        .rev()
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// Returns the byte offsets of all occurrences of a pattern
+fn find_all(content: &str, pattern: &str) -> Vec<usize> {
+    content.match_indices(pattern).map(|(pos, _)| pos).collect()
+}
+
+/// Finds a pattern at or after a byte offset, returning its absolute offset
+fn find_from(content: &str, from: usize, pattern: &str) -> Option<usize> {
+    content[from..].find(pattern).map(|pos| from + pos)
+}
+
+/// Finds a keyword that is not part of a longer identifier at or after a byte offset
+fn find_keyword(content: &str, from: usize, keyword: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut search_from = from;
+
+    while let Some(pos) = find_from(content, search_from, keyword) {
+        let before = content[..pos].chars().next_back();
+        let after = content[pos + keyword.len()..].chars().next();
+        if !before.is_some_and(is_ident) && !after.is_some_and(is_ident) {
+            return Some(pos);
+        }
+        search_from = pos + keyword.len();
+    }
+
+    None
+}
+
+/// Returns the offset of the delimiter closing the one at `open`
+fn matching_delimiter(content: &str, open: usize) -> Option<usize> {
+    let open_char = content[open..].chars().next()?;
+    let close_char = match open_char {
+        '{' => '}',
+        '(' => ')',
+        '[' => ']',
+        '<' => '>',
+        _ => return None,
+    };
+
+    let mut depth = 0;
+    for (i, ch) in content[open..].char_indices() {
+        if ch == open_char {
+            depth += 1;
+        } else if ch == close_char {
+            depth -= 1;
+            if depth == 0 {
+                return Some(open + i);
+            }
+        }
+    }
+
+    None
+}
+
+/// Splits text at a separator that is not nested inside brackets
+pub fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut previous = ' ';
+
+    for (i, ch) in text.char_indices() {
+        match ch {
+            '<' | '(' | '[' | '{' => depth += 1,
+            // `->` in function types is not a closing bracket
+            '>' if previous != '-' => depth -= 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ if ch == separator && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + ch.len_utf8();
+            }
+            _ => {}
+        }
+        previous = ch;
+    }
+
+    let last = &text[start..];
+    if !last.trim().is_empty() {
+        parts.push(last);
+    }
+
+    parts
+}
+
+/// Finds the `:` separating a key from its value, skipping `::` path separators
+fn find_key_separator(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0i32;
+
+    for (i, byte) in bytes.iter().enumerate() {
+        match byte {
+            b'<' | b'(' | b'[' | b'{' => depth += 1,
+            b'>' | b')' | b']' | b'}' => depth -= 1,
+            b':' if depth == 0 => {
+                let is_path = bytes.get(i + 1) == Some(&b':') || i > 0 && bytes[i - 1] == b':';
+                if !is_path {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Skips a leading generic parameter list such as `<T: Clone>`
+fn skip_generic_parameters(text: &str) -> &str {
+    let text = text.trim_start();
+    if text.starts_with('<')
+        && let Some(close) = matching_delimiter(text, 0)
+    {
+        return text[close + 1..].trim_start();
+    }
+    text
+}
+
+/// Skips leading attributes such as `#[serde(default)]`
+fn skip_attributes(mut text: &str) -> &str {
+    while text.starts_with("#[") {
+        match matching_delimiter(text, 1) {
+            Some(close) => text = text[close + 1..].trim_start(),
+            None => break,
+        }
+    }
+    text
+}
+
+/// Returns the type name without module path and generic arguments
+/// (e.g., "crate::shapes::ScaledArea<RectangleArea>" -> "ScaledArea")
+pub fn base_type_name(type_name: &str) -> &str {
+    let without_generics = type_name.split('<').next().unwrap_or(type_name);
+    without_generics
+        .rsplit("::")
+        .next()
+        .unwrap_or(without_generics)
+        .trim()
+}
+
+/// Returns the generic arguments of a type, including lifetimes and const arguments
+/// (e.g., "Retry<'static, 3, RectangleArea>" -> ["'static", "3", "RectangleArea"])
+pub fn generic_arguments(type_name: &str) -> Vec<&str> {
+    let type_name = type_name.trim();
+    let Some((open, close)) = type_name
+        .find('<')
+        .and_then(|open| matching_delimiter(type_name, open).map(|close| (open, close)))
+    else {
+        return Vec::new();
+    };
+
+    split_top_level(&type_name[open + 1..close], ',')
+        .into_iter()
+        .map(str::trim)
+        .filter(|argument| !argument.is_empty())
+        .collect()
+}
+
+/// Returns true for a generic argument that is a type, as opposed to a lifetime
+/// (e.g., `'static`) or a const argument (e.g., `3`, `true` or `{ N + 1 }`)
+pub fn is_type_argument(argument: &str) -> bool {
+    let argument = argument.trim();
+    !argument.is_empty()
+        && !argument.starts_with(['\'', '{', '-'])
+        && !argument.starts_with(|c: char| c.is_ascii_digit())
+        && argument != "true"
+        && argument != "false"
+}
+
+/// Returns true if `inner` is a type argument of `outer`, at any depth
+/// (e.g., "RectangleArea" in "Retry<'static, 3, ScaledArea<RectangleArea>>")
+/// Whitespace is ignored, since rustc and the source code may space arguments differently
+pub fn contains_type_argument(outer: &str, inner: &str) -> bool {
+    let inner: String = inner.split_whitespace().collect();
+
+    generic_arguments(outer)
+        .into_iter()
+        .filter(|argument| is_type_argument(argument))
+        .any(|argument| {
+            argument.split_whitespace().collect::<String>() == inner
+                || contains_type_argument(argument, &inner)
+        })
+}
+
+/// Collapses all whitespace to single spaces and removes a trailing comma
+fn normalize_whitespace(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    text.trim_end_matches(',').trim().to_string()
+}
+
+/// A check trait parsed from a `check_components!` block, with byte offsets into the file
+struct ParsedCheckBlock {
+    trait_name: String,
+    context_type: String,
+    /// Byte offset of the trait name
+    offset: usize,
+    /// Checked component names with their byte offsets
+    components: Vec<(String, usize)>,
+}
+
+/// Extracts the check traits declared by all `check_components!` blocks in a file
+fn extract_check_blocks(content: &str) -> Vec<ParsedCheckBlock> {
+    // Search in a copy with comments and literals blanked out, so that the byte offsets
+    // still match the original content but commented-out blocks are ignored
+    let content = &mask_comments_and_literals(content);
+    let mut results = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(found) = content[search_from..].find("check_components!") {
+        let macro_pos = search_from + found;
+        search_from = macro_pos + "check_components!".len();
+
+        // Skip occurrences inside line comments
+        let line_start = content[..macro_pos].rfind('\n').map_or(0, |pos| pos + 1);
+        if content[line_start..macro_pos]
+            .trim_start()
+            .starts_with("//")
+        {
+            continue;
+        }
+
+        let Some(open_pos) = content[search_from..].find('{') else {
+            break;
+        };
+        let body_start = search_from + open_pos + 1;
+
+        // Each entry of the block is a `CheckTrait for Context { ... }` header
+        // followed by a nested brace group, so collect the text before each nested group
+        let mut depth = 1;
+        let mut header_start = body_start;
+        let mut pending: Option<(ParsedCheckBlock, usize)> = None;
+
+        for (i, ch) in content[body_start..].char_indices() {
+            let pos = body_start + i;
+            match ch {
+                '{' => {
+                    if depth == 1 {
+                        pending = parse_check_header(content, header_start, pos)
+                            .map(|block| (block, pos + 1));
+                    }
+                    depth += 1;
+                }
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        search_from = pos + 1;
+                        break;
+                    }
+                    if depth == 1 {
+                        header_start = pos + 1;
+                        if let Some((mut block, entries_start)) = pending.take() {
+                            block.components =
+                                parse_checked_components(content, entries_start, pos);
+                            results.push(block);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    results
+}
+
+/// Returns the components listed for a check trait in source text containing its
+/// `check_components!` block (e.g., the text of the span rustc reports for the check trait)
+pub fn checked_component_names(content: &str, check_trait: &str) -> Vec<String> {
+    extract_check_blocks(content)
+        .into_iter()
+        .find(|block| block.trait_name == check_trait)
+        .map(|block| block.components.into_iter().map(|(name, _)| name).collect())
+        .unwrap_or_default()
+}
+
+/// Parses the component entries of a check trait between two byte offsets
+/// Entries may be plain names, `Component: [Params]`, or `[ComponentA, ComponentB]: Params`
+fn parse_checked_components(content: &str, start: usize, end: usize) -> Vec<(String, usize)> {
+    let body = &content[start..end];
+    let offset_of = |part: &str| start + (part.as_ptr() as usize - body.as_ptr() as usize);
+    let mut components = Vec::new();
+
+    for entry in split_top_level(body, ',') {
+        let key = match find_key_separator(entry) {
+            Some(separator) => &entry[..separator],
+            None => entry,
+        };
+        let key = key.trim();
+
+        let names = match key.strip_prefix('[').and_then(|k| k.strip_suffix(']')) {
+            Some(list) => split_top_level(list, ','),
+            None => vec![key],
+        };
+
+        for name in names {
+            let name = name.trim();
+            if !name.is_empty() {
+                components.push((normalize_whitespace(name), offset_of(name)));
+            }
+        }
+    }
+
+    components
+}
+
+/// Parses a `CheckTrait for Context` header between two byte offsets
+fn parse_check_header(content: &str, start: usize, end: usize) -> Option<ParsedCheckBlock> {
+    let header = &content[start..end];
+    let for_pos = header.find(" for ")?;
+
+    // Skip attributes and leading generic parameters before the trait name
+    let mut name_start = 0;
+    loop {
+        let rest = &header[name_start..for_pos];
+        let trimmed = rest.trim_start();
+        name_start += rest.len() - trimmed.len();
+
+        let closing = if trimmed.starts_with("#[") {
+            ']'
+        } else if trimmed.starts_with('<') {
+            '>'
+        } else {
+            break;
+        };
+
+        name_start += trimmed.find(closing)? + 1;
+    }
+
+    let trait_name: String = header[name_start..for_pos]
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+
+    if trait_name.is_empty() {
+        return None;
+    }
+
+    let context_type = header[for_pos + " for ".len()..].trim().to_string();
+
+    Some(ParsedCheckBlock {
+        trait_name,
+        context_type,
+        offset: start + name_start,
+        components: Vec::new(),
+    })
+}
+
+/// Replaces the contents of comments, string literals and character literals with spaces
+/// The byte length of the content is preserved, so offsets into the result are valid
+/// offsets into the original content
+fn mask_comments_and_literals(content: &str) -> String {
+    let bytes = content.as_bytes();
+    let mut masked = bytes.to_vec();
+    let mut i = 0;
+
+    let blank = |masked: &mut Vec<u8>, from: usize, to: usize| {
+        for byte in &mut masked[from..to] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    };
+
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+
+        let end = if rest.starts_with(b"//") {
+            // Line comment: runs until the end of the line
+            content[i..].find('\n').map_or(bytes.len(), |pos| i + pos)
+        } else if rest.starts_with(b"/*") {
+            // Block comment, which may be nested
+            let mut depth = 0;
+            let mut j = i;
+            while j < bytes.len() {
+                if bytes[j..].starts_with(b"/*") {
+                    depth += 1;
+                    j += 2;
+                } else if bytes[j..].starts_with(b"*/") {
+                    depth -= 1;
+                    j += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    j += 1;
+                }
+            }
+            j
+        } else if rest.starts_with(b"r\"") || rest.starts_with(b"r#") {
+            // Raw string literal: r"..." or r#"..."#
+            let hashes = rest[1..].iter().take_while(|b| **b == b'#').count();
+            if rest.get(1 + hashes) != Some(&b'"') {
+                i += 1;
+                continue;
+            }
+            let terminator = format!("\"{}", "#".repeat(hashes));
+            let body_start = i + 2 + hashes;
+            content[body_start..]
+                .find(&terminator)
+                .map_or(bytes.len(), |pos| body_start + pos + terminator.len())
+        } else if rest[0] == b'"' {
+            // String literal with escapes
+            let mut j = i + 1;
+            while j < bytes.len() && bytes[j] != b'"' {
+                j += if bytes[j] == b'\\' { 2 } else { 1 };
+            }
+            (j + 1).min(bytes.len())
+        } else if rest[0] == b'\'' {
+            // Character literal, as opposed to a lifetime such as `'a`
+            let literal_len = if rest.get(1) == Some(&b'\\') {
+                rest.iter()
+                    .skip(2)
+                    .position(|b| *b == b'\'')
+                    .map(|pos| pos + 3)
+            } else {
+                content[i + 1..]
+                    .chars()
+                    .next()
+                    .map(|ch| ch.len_utf8())
+                    .filter(|len| rest.get(1 + len) == Some(&b'\''))
+                    .map(|len| len + 2)
+            };
+            match literal_len {
+                Some(len) => i + len,
+                None => {
+                    i += 1;
+                    continue;
+                }
+            }
+        } else {
+            i += 1;
+            continue;
+        };
+
+        blank(&mut masked, i, end);
+        i = end;
+    }
+
+    // Only ASCII bytes are replaced by spaces and multi-byte characters are either
+    // kept intact or fully blanked, so the result is still valid UTF-8
+    String::from_utf8(masked).unwrap_or_else(|_| content.to_string())
+}
+
+/// Converts a byte offset into a 1-indexed (line, column) pair
+fn offset_to_line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_deref_impls() {
+        let content = "\
+impl Deref for App {
+    type Target = AppConfig;
+
+    fn deref(&self) -> &AppConfig {
+        &self.config
+    }
+}
+
+impl<T> core::ops::Deref for Wrapper<T> where T: Clone {
+    type Target = T;
+    fn deref(&self) -> &T { &self.0 }
+}
+
+// impl Deref for Ignored { type Target = Comment; }
+impl CanCalculateArea for App {
+    fn area(&self) -> f64 { 0.0 }
+}
+
+#[derive(HasField)]
+pub struct AppConfig {
+    pub width: f64,
+}
+
+pub struct Nested {
+    pub app: App,
+}
+
+impl Deref for Nested {
+    type Target = App;
+    fn deref(&self) -> &App { &self.app }
+}
+";
+        let impls = extract_deref_impls(content);
+        let names: Vec<_> = impls
+            .iter()
+            .map(|deref_impl| {
+                (
+                    deref_impl.context_type.as_str(),
+                    deref_impl.target_type.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![("App", "AppConfig"), ("Wrapper<T>", "T"), ("Nested", "App")]
+        );
+        assert_eq!((impls[0].location.line, impls[0].location.column), (1, 1));
+
+        let mut scan = WorkspaceScan::new();
+        let path = Path::new("src/lib.rs");
+        scan.scan_file(path, path, "app", "crate", content);
+        let struct_name = |type_name| {
+            scan.deref_field_struct(type_name)
+                .map(|context| context.type_name.as_str())
+        };
+        assert_eq!(struct_name("App"), Some("AppConfig"));
+        // Chains are followed to the struct that derives `HasField`
+        assert_eq!(struct_name("Nested"), Some("AppConfig"));
+        assert_eq!(struct_name("AppConfig"), None);
+    }
+
+    #[test]
+    fn test_extract_check_blocks() {
+        let content = "\
+// check_components! { Ignored for Comment {} }
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+    #[check_trait]
+    CanUseCircle for Circle {
+        AreaCalculatorComponent,
+    }
+}
+";
+        let blocks = extract_check_blocks(content);
+        let names: Vec<_> = blocks
+            .iter()
+            .map(|block| (block.trait_name.as_str(), block.context_type.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("CanUseRectangle", "Rectangle"), ("CanUseCircle", "Circle")]
+        );
+
+        assert_eq!(offset_to_line_column(content, blocks[0].offset), (3, 5));
+        let (component, offset) = &blocks[0].components[0];
+        assert_eq!(component, "AreaCalculatorComponent");
+        assert_eq!(offset_to_line_column(content, *offset), (4, 9));
+    }
+
+    #[test]
+    fn test_checked_component_names() {
+        let content = "\
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+        [DensityCalculatorComponent, MassGetterComponent]: (),
+    }
+}";
+        assert_eq!(
+            checked_component_names(content, "CanUseRectangle"),
+            vec![
+                "AreaCalculatorComponent",
+                "DensityCalculatorComponent",
+                "MassGetterComponent"
+            ]
+        );
+        assert!(checked_component_names(content, "CanUseCircle").is_empty());
+    }
+
+    #[test]
+    fn test_find_wiring_macros() {
+        let content = "\
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent: RectangleArea,
+    }
+}
+
+// check_components! { Ignored for Comment {} }
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
+";
+        let macros: Vec<_> = find_wiring_macros(content)
+            .into_iter()
+            .map(|found| (found.macro_name, found.start_line, found.end_line))
+            .collect();
+        assert_eq!(
+            macros,
+            vec![
+                ("delegate_components".to_string(), 1, 5),
+                ("check_components".to_string(), 8, 12)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_component_decls() {
+        let content = "\
+/// Calculates the area of a shape
+///
+/// More details.
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_component {
+    provider: DensityCalculator,
+    name: DensityComponent,
+}]
+pub trait CanCalculateDensity {}
+
+// #[cgp_component(Ignored)]
+#[cgp_auto_getter]
+pub trait HasMass {}
+
+#[cgp_getter]
+pub trait HasRectangleFields {}
+";
+        let decls = extract_component_decls(content);
+        assert_eq!(
+            decls,
+            vec![
+                ComponentDecl {
+                    component_name: "AreaCalculatorComponent".to_string(),
+                    provider_trait: "AreaCalculator".to_string(),
+                    consumer_trait: "CanCalculateArea".to_string(),
+                    doc: Some("Calculates the area of a shape".to_string()),
+                },
+                ComponentDecl {
+                    component_name: "DensityComponent".to_string(),
+                    provider_trait: "DensityCalculator".to_string(),
+                    consumer_trait: "CanCalculateDensity".to_string(),
+                    doc: None,
+                },
+                ComponentDecl {
+                    component_name: "RectangleFieldsGetterComponent".to_string(),
+                    provider_trait: "RectangleFieldsGetter".to_string(),
+                    consumer_trait: "HasRectangleFields".to_string(),
+                    doc: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_generated_providers() {
+        let content = "\
+#[cgp_impl(new RectangleArea: AreaCalculatorComponent)]
+impl AreaCalculator {
+    fn area(&self) -> f64 {
+        0.0
+    }
+}
+
+#[cgp_new_provider(DensityCalculatorComponent)]
+impl<Context> DensityCalculator<Context> for DensityFromMassField {
+    fn density(context: &Context) -> f64 {
+        0.0
+    }
+}
+
+#[cgp_context]
+pub struct App {
+    pub name: String,
+}
+
+#[cgp_context(MyComponents: MyPreset)]
+pub struct Other {
+    pub name: String,
+}
+";
+        let provider_impls = extract_provider_impls(content);
+        let providers: Vec<(&str, &str, bool)> = provider_impls
+            .iter()
+            .map(|provider| {
+                (
+                    provider.provider_type.as_str(),
+                    provider.attribute.as_str(),
+                    provider.generates_struct,
+                )
+            })
+            .collect();
+        assert_eq!(
+            providers,
+            vec![
+                (
+                    "RectangleArea",
+                    "#[cgp_impl(new RectangleArea: AreaCalculatorComponent)]",
+                    true
+                ),
+                (
+                    "DensityFromMassField",
+                    "#[cgp_new_provider(DensityCalculatorComponent)]",
+                    true
+                ),
+            ]
+        );
+
+        let context_providers: Vec<Option<String>> = extract_context_structs(content)
+            .iter()
+            .map(ContextStruct::context_provider)
+            .collect();
+        assert_eq!(
+            context_providers,
+            vec![
+                Some("AppComponents".to_string()),
+                Some("MyComponents".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_wiring() {
+        let content = "\
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+}
+
+/// Multiplies the inner area by the scale factor
+#[cgp_impl(new ScaledArea<InnerCalculator>)]
+impl<InnerCalculator> AreaCalculator
+where
+    Self: HasScaleFactor + HasRectangleFields,
+    InnerCalculator: AreaCalculator<Self>,
+{
+    fn area(&self) -> f64 {
+        0.0
+    }
+}
+
+#[cgp_provider]
+impl<Context> DensityCalculator<Context> for DensityFromMassField
+where
+    Context: CanCalculateArea + HasMass,
+{
+    fn density(context: &Context) -> f64 {
+        0.0
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    #[allow(unused)]
+    pub(crate) width: f64,
+    pub height: Vec<f64>,
+}
+
+// #[derive(HasField)]
+pub struct Circle {
+    pub radius: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        [
+            AreaCalculatorComponent,
+            cgp::prelude::ErrorTypeProviderComponent,
+        ]:
+            ScaledArea<RectangleArea>,
+        DensityCalculatorComponent: DensityFromMassField,
+    }
+}
+";
+        let field = |name: &str, field_type: &str| FieldDecl {
+            name: name.to_string(),
+            field_type: field_type.to_string(),
+        };
+
+        let location = |line: usize, column: usize| SourceLocation {
+            line,
+            column,
+            ..SourceLocation::default()
+        };
+
+        assert_eq!(
+            extract_getter_decls(content),
+            vec![GetterDecl {
+                trait_name: "HasRectangleFields".to_string(),
+                fields: vec![field("width", "f64"), field("height", "f64")],
+                is_auto: true,
+                location: location(1, 1),
+            }]
+        );
+
+        assert_eq!(
+            extract_provider_impls(content),
+            vec![
+                ProviderImpl {
+                    provider_type: "ScaledArea<InnerCalculator>".to_string(),
+                    provider_trait: "AreaCalculator".to_string(),
+                    requirements: vec![
+                        "HasScaleFactor".to_string(),
+                        "HasRectangleFields".to_string()
+                    ],
+                    doc: Some("Multiplies the inner area by the scale factor".to_string()),
+                    attribute: "#[cgp_impl(new ScaledArea<InnerCalculator>)]".to_string(),
+                    generates_struct: true,
+                    location: location(9, 1),
+                },
+                ProviderImpl {
+                    provider_type: "DensityFromMassField".to_string(),
+                    provider_trait: "DensityCalculator".to_string(),
+                    requirements: vec!["CanCalculateArea".to_string(), "HasMass".to_string()],
+                    doc: None,
+                    attribute: "#[cgp_provider]".to_string(),
+                    generates_struct: false,
+                    location: location(20, 1),
+                },
+            ]
+        );
+
+        assert_eq!(
+            extract_context_structs(content),
+            vec![
+                ContextStruct {
+                    type_name: "Rectangle".to_string(),
+                    derives_has_field: true,
+                    fields: vec![field("width", "f64"), field("height", "Vec<f64>")],
+                    context_attribute: None,
+                    location: location(31, 12),
+                    crate_name: String::new(),
+                },
+                ContextStruct {
+                    type_name: "Circle".to_string(),
+                    derives_has_field: false,
+                    fields: vec![field("radius", "f64")],
+                    context_attribute: None,
+                    location: location(38, 12),
+                    crate_name: String::new(),
+                },
+            ]
+        );
+
+        let delegations = extract_delegations(content);
+        assert_eq!(delegations[0].location, location(45, 13));
+        assert_eq!(delegations[2].location, location(49, 9));
+
+        let delegations: Vec<(String, String)> = delegations
+            .into_iter()
+            .map(|d| {
+                assert_eq!(d.context_type, "Rectangle");
+                (d.component, d.provider)
+            })
+            .collect();
+        assert_eq!(
+            delegations,
+            vec![
+                (
+                    "AreaCalculatorComponent".to_string(),
+                    "ScaledArea<RectangleArea>".to_string()
+                ),
+                (
+                    "cgp::prelude::ErrorTypeProviderComponent".to_string(),
+                    "ScaledArea<RectangleArea>".to_string()
+                ),
+                (
+                    "DensityCalculatorComponent".to_string(),
+                    "DensityFromMassField".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mask_comments_and_literals() {
+        let content =
+            "let s = \"check_components!\"; /* mod a; */ let c = '{'; // x\nfn f<'a>() {}";
+        let masked = mask_comments_and_literals(content);
+        assert_eq!(masked.len(), content.len());
+        assert!(!masked.contains("check_components!"));
+        assert!(!masked.contains("mod a;"));
+        assert!(!masked.contains("'{'"));
+        assert!(masked.contains("fn f<'a>() {}"));
+    }
+
+    #[test]
+    fn test_shadowed_check_traits() {
+        let content = "check_components! { CanUseRectangle for Rectangle { FooComponent } }";
+
+        let mut scan = WorkspaceScan::new();
+        let path = Path::new("src/a.rs");
+        scan.scan_file(path, path, "shapes", "a", content);
+        let path = Path::new("src/b.rs");
+        scan.scan_file(path, path, "shapes", "b", content);
+        let path = Path::new("src/lib.rs");
+        scan.scan_file(path, path, "other", "crate", content);
+
+        let shadowed = scan.shadowed_check_traits();
+        assert_eq!(shadowed.len(), 1);
+        assert_eq!(shadowed[0][0].module_path, "a");
+        assert_eq!(shadowed[0][1].module_path, "b");
+    }
+
+    #[test]
+    fn test_duplicate_checks() {
+        let mut scan = WorkspaceScan::new();
+        let path = Path::new("src/a.rs");
+        scan.scan_file(
+            path,
+            path,
+            "shapes",
+            "a",
+            "check_components! { CanUseRectangle for Rectangle { AreaComponent, MassComponent } }",
+        );
+        let path = Path::new("tests/b.rs");
+        scan.scan_file(
+            path,
+            path,
+            "shapes",
+            "b",
+            "check_components! {\n    CanCheckRectangle for Rectangle {\n        [MassComponent, AreaComponent],\n    }\n}",
+        );
+        // Checks of the same pair within one file are not reported
+        let path = Path::new("src/c.rs");
+        scan.scan_file(
+            path,
+            path,
+            "shapes",
+            "c",
+            "check_components! { A for Circle { AreaComponent } B for Circle { AreaComponent } }",
+        );
+
+        let duplicates = scan.duplicate_checks();
+        let pairs: Vec<_> = duplicates
+            .iter()
+            .map(|d| (d.context_type, d.component, d.checks.len()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("Rectangle", "AreaComponent", 2),
+                ("Rectangle", "MassComponent", 2)
+            ]
+        );
+
+        assert!(duplicates[0].covers("tests/b.rs", 3));
+        assert!(duplicates[0].covers("src/a.rs", 1));
+        assert!(!duplicates[0].covers("src/c.rs", 1));
+    }
+
+    #[test]
+    fn test_contains_type_argument() {
+        assert!(contains_type_argument(
+            "ScaledArea<RectangleArea>",
+            "RectangleArea"
+        ));
+        assert!(contains_type_argument("Wrapper<Foo, Bar>", "Foo"));
+        assert!(contains_type_argument("Wrapper<Foo, Bar>", "Bar"));
+        assert!(!contains_type_argument("Wrapper<Foo, Bar>", "Baz"));
+        assert!(!contains_type_argument("ScaledArea<RectangleArea>", "Area"));
+
+        // Lifetimes and const arguments are not providers, and spacing may differ
+        assert_eq!(
+            generic_arguments("Retry<'static, 3, RectangleArea>"),
+            vec!["'static", "3", "RectangleArea"]
+        );
+        assert!(contains_type_argument(
+            "Retry<'static,3,ScaledArea<RectangleArea>>",
+            "ScaledArea< RectangleArea >"
+        ));
+        assert!(contains_type_argument(
+            "Retry<'static, 3, ScaledArea<RectangleArea>>",
+            "RectangleArea"
+        ));
+        assert!(!contains_type_argument("Retry<'static, 3, Inner>", "3"));
+        assert!(!contains_type_argument("Retry<'a, { N + 1 }>", "N"));
+        assert!(generic_arguments("RectangleArea").is_empty());
+    }
+
+    #[test]
+    fn test_getter_field_type() {
+        assert_eq!(getter_field_type("f64"), "f64");
+        assert_eq!(getter_field_type("&f64"), "f64");
+        assert_eq!(getter_field_type("&'a Vec<u8>"), "Vec<u8>");
+        assert_eq!(getter_field_type("&str"), "String");
+        assert_eq!(getter_field_type("&[u8]"), "Vec<u8>");
+        assert_eq!(getter_field_type("Option<&Config>"), "Option<Config>");
+        assert_eq!(getter_field_type("Option<&str>"), "Option<String>");
+        assert_eq!(getter_field_type("MRef<'_, Config>"), "Config");
+        assert_eq!(getter_field_type("Option<u32>"), "Option<u32>");
+    }
+
+    #[test]
+    fn test_extract_module_declarations() {
+        let content = "\
+pub mod shapes;
+pub(crate) mod wiring;
+// pub mod disabled;
+mod inline {
+}
+";
+        assert_eq!(
+            extract_module_declarations(content),
+            vec!["shapes".to_string(), "wiring".to_string()]
+        );
+    }
+}
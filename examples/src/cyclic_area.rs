@@ -0,0 +1,42 @@
+use cgp::prelude::*;
+
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasScaleFactor {
+    fn scale_factor(&self) -> f64;
+}
+
+// meant to scale an inner calculator, but asks the context for its own area
+#[cgp_impl(new ScaledArea)]
+impl AreaCalculator
+where
+    Self: HasScaleFactor + CanCalculateArea,
+{
+    fn area(&self) -> f64 {
+        self.scale_factor() * self.area()
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub scale_factor: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent:
+            ScaledArea,
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
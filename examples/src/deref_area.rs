@@ -0,0 +1,57 @@
+use core::ops::Deref;
+
+use cgp::prelude::*;
+
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+#[derive(HasField)]
+pub struct RectangleConfig {
+    pub width: f64,
+    // missing height field to trigger error
+    // pub height: f64,
+}
+
+pub struct Rectangle {
+    pub config: RectangleConfig,
+}
+
+impl Deref for Rectangle {
+    type Target = RectangleConfig;
+
+    fn deref(&self) -> &RectangleConfig {
+        &self.config
+    }
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent:
+            RectangleArea,
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
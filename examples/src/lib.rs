@@ -5,3 +5,17 @@
 // pub mod density;
 // pub mod density_2;
 // pub mod density_3;
+// pub mod retry_area;
+// pub mod deref_area;
+// pub mod usage_area;
+// pub mod undelegated_area;
+// pub mod not_a_provider_area;
+// pub mod wrong_type_area;
+// pub mod cyclic_area;
+// pub mod send_context;
+// pub mod generic_context;
+// pub mod nested_area;
+// pub mod promoted_lint;
+// pub mod misnamed_component;
+// pub mod linked_density;
+// pub mod partial_check;
@@ -0,0 +1,75 @@
+use cgp::prelude::*;
+
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_component(DensityCalculator)]
+pub trait CanCalculateDensity {
+    fn density(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasMass {
+    fn mass(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+#[cgp_impl(new DensityFromMassField)]
+impl DensityCalculator
+where
+    Self: CanCalculateArea + HasMass,
+{
+    fn density(&self) -> f64 {
+        self.mass() / self.area()
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub mass: f64,
+    pub width: f64,
+    // missing height field to trigger error
+    // pub height: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent:
+            RectangleArea,
+        DensityCalculatorComponent:
+            DensityFromMassField,
+    }
+}
+
+// Missing height field causes RectangleArea -> DensityFromMassField to fail,
+// and RectangleArea is checked on its own as well
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        DensityCalculatorComponent,
+    }
+}
+
+check_components! {
+    CanCalculateRectangleArea for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
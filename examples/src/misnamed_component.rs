@@ -0,0 +1,48 @@
+use cgp::prelude::*;
+
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_component(AreaCalculation)]
+pub trait CanLogAreaCalculation {
+    fn log_area_calculation(&self, area: f64);
+}
+
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    pub height: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent:
+            RectangleArea,
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        // a typo of the wired AreaCalculatorComponent
+        AreaCalculationComponent,
+    }
+}
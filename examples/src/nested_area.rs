@@ -0,0 +1,72 @@
+use cgp::prelude::*;
+
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+#[cgp_auto_getter]
+pub trait HasScaleFactor {
+    fn scale_factor(&self) -> f64;
+}
+
+#[cgp_impl(new ScaledArea<InnerCalculator>)]
+impl<InnerCalculator> AreaCalculator
+where
+    Self: HasScaleFactor,
+    InnerCalculator: AreaCalculator<Self>,
+{
+    fn area(&self) -> f64 {
+        self.scale_factor() * InnerCalculator::area(self)
+    }
+}
+
+#[cgp_impl(new LoggedArea<InnerCalculator>)]
+impl<InnerCalculator> AreaCalculator
+where
+    InnerCalculator: AreaCalculator<Self>,
+{
+    fn area(&self) -> f64 {
+        let area = InnerCalculator::area(self);
+        println!("area: {area}");
+        area
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub scale_factor: f64,
+    pub width: f64,
+    // missing height field to trigger error
+    // pub height: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent:
+            LoggedArea<ScaledArea<RectangleArea>>,
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
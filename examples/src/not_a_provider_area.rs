@@ -0,0 +1,61 @@
+use cgp::prelude::*;
+
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_component(PerimeterCalculator)]
+pub trait CanCalculatePerimeter {
+    fn perimeter(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+#[cgp_impl(new RectanglePerimeter)]
+impl PerimeterCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width() + self.height())
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    pub height: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent:
+            RectangleArea,
+        // wrong provider to trigger error
+        PerimeterCalculatorComponent:
+            RectangleArea,
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+        PerimeterCalculatorComponent,
+    }
+}
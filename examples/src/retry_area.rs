@@ -0,0 +1,57 @@
+use core::marker::PhantomData;
+
+use cgp::prelude::*;
+
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+pub struct Retry<'a, const N: usize, InnerCalculator>(PhantomData<&'a InnerCalculator>);
+
+#[cgp_impl(Retry<'a, N, InnerCalculator>)]
+impl<'a, const N: usize, InnerCalculator> AreaCalculator
+where
+    InnerCalculator: AreaCalculator<Self>,
+{
+    fn area(&self) -> f64 {
+        InnerCalculator::area(self)
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    // missing height field to trigger error
+    // pub height: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent:
+            Retry<'static, 3, RectangleArea>,
+    }
+}
+
+check_components! {
+    CanUseRectangle for Rectangle {
+        AreaCalculatorComponent,
+    }
+}
@@ -0,0 +1,48 @@
+use std::rc::Rc;
+
+use cgp::prelude::*;
+
+#[cgp_component(UserFetcher)]
+pub trait CanFetchUser {
+    fn fetch_user(&self, id: u64) -> String;
+}
+
+#[cgp_auto_getter]
+pub trait HasDatabaseUrl {
+    fn database_url(&self) -> &String;
+}
+
+// fetches on a worker thread, so the context is shared with it
+#[cgp_impl(new FetchInBackground)]
+impl UserFetcher
+where
+    Self: HasDatabaseUrl + Sync,
+{
+    fn fetch_user(&self, id: u64) -> String {
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| format!("{}/users/{}", self.database_url(), id))
+                .join()
+                .unwrap()
+        })
+    }
+}
+
+#[derive(HasField)]
+pub struct App {
+    pub database_url: String,
+    pub cache: Rc<String>,
+}
+
+delegate_components! {
+    App {
+        UserFetcherComponent:
+            FetchInBackground,
+    }
+}
+
+check_components! {
+    CanUseApp for App {
+        UserFetcherComponent,
+    }
+}
@@ -0,0 +1,165 @@
+pub mod send_context {
+    use std::rc::Rc;
+    use cgp::prelude::*;
+    pub struct UserFetcherComponent;
+    pub trait CanFetchUser {
+        fn fetch_user(&self, id: u64)
+        -> String;
+    }
+    pub trait UserFetcher<__Context__>: IsProviderFor<UserFetcherComponent<>,
+        __Context__, ()> {
+        fn fetch_user(__context__: &__Context__, id: u64)
+        -> String;
+    }
+    impl<__Context__> CanFetchUser<> for __Context__ where
+        __Context__: DelegateComponent<UserFetcherComponent<>>,
+        __Context__::Delegate: UserFetcher<__Context__> {
+        fn fetch_user(&self, id: u64) -> String {
+            __Context__::Delegate::fetch_user(self, id)
+        }
+    }
+    impl<__Component__, __Context__> UserFetcher<__Context__> for
+        __Component__ where
+        __Component__: DelegateComponent<UserFetcherComponent<>> +
+        IsProviderFor<UserFetcherComponent<>, __Context__, ()>,
+        __Component__::Delegate: UserFetcher<__Context__> {
+        fn fetch_user(__context__: &__Context__, id: u64) -> String {
+            __Component__::Delegate::fetch_user(__context__, id)
+        }
+    }
+    impl<__Context__> UserFetcher<__Context__> for UseContext where
+        __Context__: CanFetchUser {
+        fn fetch_user(__context__: &__Context__, id: u64) -> String {
+            __Context__::fetch_user(__context__, id)
+        }
+    }
+    impl<__Context__> IsProviderFor<UserFetcherComponent<>, __Context__, ()>
+        for UseContext where __Context__: CanFetchUser {}
+    pub trait HasDatabaseUrl {
+        fn database_url(&self)
+        -> &String;
+    }
+    impl<__Context__> HasDatabaseUrl for __Context__ where
+        __Context__: HasField<ψ<12,
+        ζ<'d',
+        ζ<'a',
+        ζ<'t',
+        ζ<'a',
+        ζ<'b',
+        ζ<'a',
+        ζ<'s', ζ<'e', ζ<'_', ζ<'u', ζ<'r', ζ<'l', ε>>>>>>>>>>>>>, Value
+        = String> {
+        fn database_url(&self) -> &String {
+            self.get_field(::core::marker::PhantomData::<ψ<12,
+                    ζ<'d',
+                    ζ<'a',
+                    ζ<'t',
+                    ζ<'a',
+                    ζ<'b',
+                    ζ<'a',
+                    ζ<'s',
+                    ζ<'e', ζ<'_', ζ<'u', ζ<'r', ζ<'l', ε>>>>>>>>>>>>>>)
+        }
+    }
+    pub struct FetchInBackground;
+    impl<__Context__> UserFetcher<__Context__> for FetchInBackground where
+        __Context__: HasDatabaseUrl + Sync {
+        fn fetch_user(__context__: &__Context__, id: u64) -> String {
+            std::thread::scope(|scope|
+                    {
+                        scope.spawn(||
+                                        ::alloc::__export::must_use({
+                                                ::alloc::fmt::format(format_args!("{0}/users/{1}",
+                                                        __context__.database_url(), id))
+                                            })).join().unwrap()
+                    })
+        }
+    }
+    impl<__Context__> IsProviderFor<UserFetcherComponent, __Context__, ()> for
+        FetchInBackground where __Context__: HasDatabaseUrl + Sync {}
+    pub struct App {
+        pub database_url: String,
+        pub cache: Rc<String>,
+    }
+    impl HasField<ψ<12,
+        ζ<'d',
+        ζ<'a',
+        ζ<'t',
+        ζ<'a',
+        ζ<'b',
+        ζ<'a',
+        ζ<'s', ζ<'e', ζ<'_', ζ<'u', ζ<'r', ζ<'l', ε>>>>>>>>>>>>>> for
+        App {
+        type Value = String;
+        fn get_field(&self,
+            key:
+                ::core::marker::PhantomData<ψ<12,
+                ζ<'d',
+                ζ<'a',
+                ζ<'t',
+                ζ<'a',
+                ζ<'b',
+                ζ<'a',
+                ζ<'s',
+                ζ<'e', ζ<'_', ζ<'u', ζ<'r', ζ<'l', ε>>>>>>>>>>>>>>)
+            -> &Self::Value {
+            &self.database_url
+        }
+    }
+    impl HasFieldMut<ψ<12,
+        ζ<'d',
+        ζ<'a',
+        ζ<'t',
+        ζ<'a',
+        ζ<'b',
+        ζ<'a',
+        ζ<'s', ζ<'e', ζ<'_', ζ<'u', ζ<'r', ζ<'l', ε>>>>>>>>>>>>>> for
+        App {
+        fn get_field_mut(&mut self,
+            key:
+                ::core::marker::PhantomData<ψ<12,
+                ζ<'d',
+                ζ<'a',
+                ζ<'t',
+                ζ<'a',
+                ζ<'b',
+                ζ<'a',
+                ζ<'s',
+                ζ<'e', ζ<'_', ζ<'u', ζ<'r', ζ<'l', ε>>>>>>>>>>>>>>)
+            -> &mut Self::Value {
+            &mut self.database_url
+        }
+    }
+    impl HasField<ψ<5, ζ<'c', ζ<'a', ζ<'c', ζ<'h', ζ<'e', ε>>>>>>> for
+        App {
+        type Value = Rc<String>;
+        fn get_field(&self,
+            key:
+                ::core::marker::PhantomData<ψ<5,
+                ζ<'c', ζ<'a', ζ<'c', ζ<'h', ζ<'e', ε>>>>>>>)
+            -> &Self::Value {
+            &self.cache
+        }
+    }
+    impl HasFieldMut<ψ<5, ζ<'c', ζ<'a', ζ<'c', ζ<'h', ζ<'e', ε>>>>>>>
+        for App {
+        fn get_field_mut(&mut self,
+            key:
+                ::core::marker::PhantomData<ψ<5,
+                ζ<'c', ζ<'a', ζ<'c', ζ<'h', ζ<'e', ε>>>>>>>)
+            -> &mut Self::Value {
+            &mut self.cache
+        }
+    }
+    impl DelegateComponent<UserFetcherComponent> for App {
+        type Delegate = FetchInBackground;
+    }
+    impl<__Context__, __Params__>
+        IsProviderFor<UserFetcherComponent, __Context__, __Params__> for App
+        where
+        FetchInBackground: IsProviderFor<UserFetcherComponent, __Context__,
+        __Params__> {}
+    trait CanUseApp<__Component__,
+        __Params__: ?Sized>: CanUseComponent<__Component__, __Params__> {}
+    impl CanUseApp<UserFetcherComponent, ()> for App {}
+}
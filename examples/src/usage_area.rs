@@ -0,0 +1,45 @@
+use cgp::prelude::*;
+
+#[cgp_component(AreaCalculator)]
+pub trait CanCalculateArea {
+    fn area(&self) -> f64;
+}
+
+#[cgp_auto_getter]
+pub trait HasRectangleFields {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+}
+
+#[cgp_impl(new RectangleArea)]
+impl AreaCalculator
+where
+    Self: HasRectangleFields,
+{
+    fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+}
+
+#[derive(HasField)]
+pub struct Rectangle {
+    pub width: f64,
+    // missing height field to trigger error
+    // pub height: f64,
+}
+
+delegate_components! {
+    Rectangle {
+        AreaCalculatorComponent:
+            RectangleArea,
+    }
+}
+
+pub fn total_area<Context: CanCalculateArea>(shapes: &[Context]) -> f64 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+pub fn rectangles_area(rectangles: &[Rectangle]) -> f64 {
+    total_area(rectangles)
+}